@@ -0,0 +1,222 @@
+use crate::adapters::certificate::{Handshake, RustlsTls};
+use crate::adapters::dns_tools::DnsProcess;
+use crate::adapters::email::LocalFiles;
+use crate::adapters::http::{Exchange, Hop, HyperHttp};
+use crate::adapters::ping::SocketPing;
+use crate::adapters::ports::{PortProbe, TcpPorts};
+use crate::adapters::ssh::{HostKeyExchange, TcpSsh};
+use crate::adapters::traceroute::{TraceProcess, TraceTool};
+use crate::adapters::whois::Port43Whois;
+use crate::models::certificate::StartTls;
+use std::future::Future;
+use std::net::IpAddr;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::time::Duration;
+use url::Url;
+
+pub type BackendFuture<'a, T> = Pin<Box<dyn Future<Output = Result<T, String>> + Send + 'a>>;
+
+// What a DNS tool run printed and how it exited
+#[derive(Debug, Clone, Default)]
+pub struct ProcessOutput {
+    pub stdout: String,
+    pub stderr: String,
+    pub exit_code: i32, // -1 when killed by a signal
+}
+
+impl ProcessOutput {
+    pub fn success(&self) -> bool {
+        self.exit_code == 0
+    }
+}
+
+// Runs dig for DnsAdapter, which builds the arguments and parses the output. A
+// backend running another tool translates the arguments and prints dig's format.
+pub trait DnsBackend: Send + Sync {
+    fn is_available(&self) -> bool;
+    fn dig<'a>(&'a self, args: &'a [String]) -> BackendFuture<'a, ProcessOutput>;
+
+    // The program and arguments actually run for dig's args, for command logs
+    fn program(&self) -> &str {
+        "dig"
+    }
+
+    fn command_line(&self, args: &[String]) -> Vec<String> {
+        args.to_vec()
+    }
+}
+
+// One WHOIS exchange: send the query, read the response until the server closes
+pub trait WhoisBackend: Send + Sync {
+    fn query<'a>(&'a self, server: &'a str, port: u16, query: &'a str)
+        -> BackendFuture<'a, String>;
+}
+
+// A TLS handshake, after a STARTTLS upgrade when asked, returning what the server
+// presented. address connects to one IP rather than whatever host resolves to.
+pub trait TlsBackend: Send + Sync {
+    fn handshake<'a>(
+        &'a self,
+        host: &'a str,
+        address: Option<IpAddr>,
+        port: u16,
+        starttls: Option<StartTls>,
+    ) -> BackendFuture<'a, Handshake>;
+}
+
+// One HTTP request and its response; HttpAdapter follows redirects and retries
+pub trait HttpBackend: Send + Sync {
+    fn send<'a>(&'a self, hop: &'a Hop<'a>, url: &'a Url) -> BackendFuture<'a, Exchange>;
+}
+
+// One latency probe, answering with the round-trip time or None when nothing came
+// back within timeout. echo fails when ICMP sockets aren't permitted here, which
+// PingAdapter takes as its cue to fall back to connect.
+pub trait PingBackend: Send + Sync {
+    fn echo<'a>(
+        &'a self,
+        address: IpAddr,
+        sequence: u16,
+        timeout: Duration,
+    ) -> BackendFuture<'a, Option<Duration>>;
+    fn connect<'a>(
+        &'a self,
+        address: IpAddr,
+        port: u16,
+        timeout: Duration,
+    ) -> BackendFuture<'a, Option<Duration>>;
+}
+
+// A TCP connect to one port, reading the first line the service sends by itself
+// (SSH, SMTP and FTP greet the client) within banner_timeout when one is given
+pub trait PortBackend: Send + Sync {
+    fn probe<'a>(
+        &'a self,
+        address: IpAddr,
+        port: u16,
+        timeout: Duration,
+        banner_timeout: Option<Duration>,
+    ) -> BackendFuture<'a, PortProbe>;
+}
+
+// An SSH key exchange carried only as far as the server's host key, offering
+// just algorithms so the server presents the key of the first it has
+pub trait SshBackend: Send + Sync {
+    fn host_key<'a>(
+        &'a self,
+        address: IpAddr,
+        port: u16,
+        algorithms: &'a [String],
+        timeout: Duration,
+    ) -> BackendFuture<'a, HostKeyExchange>;
+}
+
+// Runs a traceroute tool to address, handing each line to on_line as soon as it's
+// printed so TracerouteAdapter can report hops as they're discovered
+pub trait TraceBackend: Send + Sync {
+    fn is_available(&self) -> bool;
+    fn trace<'a>(
+        &'a self,
+        address: IpAddr,
+        max_hops: u8,
+        on_line: &'a (dyn Fn(&str) + Send + Sync),
+    ) -> BackendFuture<'a, ProcessOutput>;
+
+    // The program and arguments run, for command logs
+    fn program(&self) -> &str {
+        "traceroute"
+    }
+
+    fn command_line(&self, address: IpAddr, max_hops: u8) -> Vec<String> {
+        TraceTool::Traceroute.args(address, max_hops)
+    }
+}
+
+// Reads a file the user picked, such as a DMARC aggregate report for EmailAdapter
+pub trait FileBackend: Send + Sync {
+    fn read<'a>(&'a self, path: &'a str) -> BackendFuture<'a, Vec<u8>>;
+}
+
+// The network, process and file access adapters go through
+//
+// Adapters take these from managed state (see state::backends_for), so they can be
+// swapped while the app runs, or are given them with with_backends. Tests pass
+// backends that return canned output instead of running dig or opening sockets.
+#[derive(Clone)]
+pub struct Backends {
+    pub dns: Arc<dyn DnsBackend>,
+    pub whois: Arc<dyn WhoisBackend>,
+    pub tls: Arc<dyn TlsBackend>,
+    pub http: Arc<dyn HttpBackend>,
+    pub ping: Arc<dyn PingBackend>,
+    pub ports: Arc<dyn PortBackend>,
+    pub trace: Arc<dyn TraceBackend>,
+    pub ssh: Arc<dyn SshBackend>,
+    pub files: Arc<dyn FileBackend>,
+}
+
+impl Backends {
+    pub fn with_dns(mut self, dns: impl DnsBackend + 'static) -> Self {
+        self.dns = Arc::new(dns);
+        self
+    }
+
+    pub fn with_whois(mut self, whois: impl WhoisBackend + 'static) -> Self {
+        self.whois = Arc::new(whois);
+        self
+    }
+
+    pub fn with_tls(mut self, tls: impl TlsBackend + 'static) -> Self {
+        self.tls = Arc::new(tls);
+        self
+    }
+
+    pub fn with_http(mut self, http: impl HttpBackend + 'static) -> Self {
+        self.http = Arc::new(http);
+        self
+    }
+
+    pub fn with_ping(mut self, ping: impl PingBackend + 'static) -> Self {
+        self.ping = Arc::new(ping);
+        self
+    }
+
+    pub fn with_ports(mut self, ports: impl PortBackend + 'static) -> Self {
+        self.ports = Arc::new(ports);
+        self
+    }
+
+    pub fn with_trace(mut self, trace: impl TraceBackend + 'static) -> Self {
+        self.trace = Arc::new(trace);
+        self
+    }
+
+    pub fn with_ssh(mut self, ssh: impl SshBackend + 'static) -> Self {
+        self.ssh = Arc::new(ssh);
+        self
+    }
+
+    pub fn with_files(mut self, files: impl FileBackend + 'static) -> Self {
+        self.files = Arc::new(files);
+        self
+    }
+}
+
+// dig (or kdig, drill or host), port-43 TCP, rustls, hyper, ICMP sockets, plain TCP
+// connects, traceroute (or tracepath or tracert) and SSH key exchanges
+impl Default for Backends {
+    fn default() -> Self {
+        Backends {
+            dns: Arc::new(DnsProcess::detect()),
+            whois: Arc::new(Port43Whois),
+            tls: Arc::new(RustlsTls),
+            http: Arc::new(HyperHttp),
+            ping: Arc::new(SocketPing),
+            ports: Arc::new(TcpPorts),
+            trace: Arc::new(TraceProcess::detect()),
+            ssh: Arc::new(TcpSsh),
+            files: Arc::new(LocalFiles),
+        }
+    }
+}
@@ -1,269 +1,1442 @@
-use crate::models::certificate::{CertificateChain, CertificateInfo, CertificateSubject, TlsInfo};
+use crate::adapters::backend::{BackendFuture, Backends, TlsBackend};
+use crate::adapters::http::HttpAdapter;
+use crate::models::certificate::{
+    CertificateChain, CertificateInfo, CertificateRole, CertificateSubject, CertificateWarning,
+    CertificateWarningKind, CtHistory, CtIssuer, CtLogEntry, IpCertificate, PortTlsResult,
+    StartTls, TlsInfo, TlsPortScan,
+};
 use crate::models::command_log::CommandLog;
-use regex::Regex;
-use std::process::Command;
-use std::time::Instant;
-use tauri::{AppHandle, Emitter};
+use crate::state;
+use crate::state::command_logs;
+use crate::state::rate_limiter::{self, Target};
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use chrono::{DateTime, NaiveDateTime, Utc};
+use rustls::client::danger::{HandshakeSignatureValid, ServerCertVerified, ServerCertVerifier};
+use rustls::crypto::{ring, verify_tls12_signature, verify_tls13_signature, CryptoProvider};
+use rustls::pki_types::{CertificateDer, ServerName, TrustAnchor, UnixTime};
+use rustls::{ClientConfig, DigitallySignedStruct, SignatureScheme};
+use serde::Deserialize;
+use sha2::{Digest, Sha256, Sha512};
+use std::collections::HashMap;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tauri::AppHandle;
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::net::{lookup_host, TcpStream};
+use tokio::sync::Semaphore;
+use tokio::task::JoinSet;
+use tokio_rustls::TlsConnector;
+use tracing::{instrument, Instrument};
+use webpki::{EndEntityCert, KeyUsage};
+use x509_parser::extensions::{GeneralName, ParsedExtension};
+use x509_parser::objects::{oid2sn, oid_registry};
+use x509_parser::prelude::{FromDer, X509Certificate, X509Name};
+use x509_parser::public_key::PublicKey;
+use x509_parser::x509::AttributeTypeAndValue;
 
+// ALPN protocols offered on implicit TLS connections, most preferred first
+const ALPN_PROTOCOLS: [&str; 2] = ["h2", "http/1.1"];
+
+// Result of one TLS handshake
+pub struct Handshake {
+    pub chain: Vec<CertificateDer<'static>>,
+    pub alpn_protocol: Option<String>, // Protocol the server selected, if any
+    pub summary: String,               // Human-readable recap for the command log
+}
+
+// CA/Browser Forum limit for leaf certificates issued since September 2020
+const MAX_LEAF_VALIDITY_DAYS: i64 = 398;
+
+// Row of crt.sh's JSON output
+#[derive(Deserialize)]
+struct CrtShEntry {
+    id: u64,
+    issuer_name: String,
+    #[serde(default)]
+    common_name: Option<String>,
+    #[serde(default)]
+    name_value: String, // SANs, newline-separated
+    serial_number: String,
+    not_before: String,
+    not_after: String,
+    entry_timestamp: Option<String>,
+}
+
+#[derive(Clone)]
 pub struct CertificateAdapter {
     app_handle: Option<AppHandle>,
+    backends: Backends,
+}
+
+// Accepts whatever chain the server presents so expired, self-signed and mismatched
+// certificates can still be inspected. Handshake signatures are still checked.
+#[derive(Debug)]
+struct AcceptAnyCertificate(Arc<CryptoProvider>);
+
+impl ServerCertVerifier for AcceptAnyCertificate {
+    fn verify_server_cert(
+        &self,
+        _end_entity: &CertificateDer<'_>,
+        _intermediates: &[CertificateDer<'_>],
+        _server_name: &ServerName<'_>,
+        _ocsp_response: &[u8],
+        _now: UnixTime,
+    ) -> Result<ServerCertVerified, rustls::Error> {
+        Ok(ServerCertVerified::assertion())
+    }
+
+    fn verify_tls12_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, rustls::Error> {
+        verify_tls12_signature(
+            message,
+            cert,
+            dss,
+            &self.0.signature_verification_algorithms,
+        )
+    }
+
+    fn verify_tls13_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, rustls::Error> {
+        verify_tls13_signature(
+            message,
+            cert,
+            dss,
+            &self.0.signature_verification_algorithms,
+        )
+    }
+
+    fn supported_verify_schemes(&self) -> Vec<SignatureScheme> {
+        self.0.signature_verification_algorithms.supported_schemes()
+    }
+}
+
+// TLS handshakes with rustls over a TCP connection
+pub struct RustlsTls;
+
+impl TlsBackend for RustlsTls {
+    fn handshake<'a>(
+        &'a self,
+        host: &'a str,
+        address: Option<IpAddr>,
+        port: u16,
+        starttls: Option<StartTls>,
+    ) -> BackendFuture<'a, Handshake> {
+        Box::pin(self.fetch_peer_certificates(host, address, port, starttls))
+    }
+}
+
+impl RustlsTls {
+    // Complete a TLS handshake and return the chain the server presented (leaf first)
+    // along with a summary of the negotiated protocol and cipher suite. With STARTTLS,
+    // the plaintext upgrade exchange is included in the summary.
+    pub async fn fetch_peer_certificates(
+        &self,
+        host: &str,
+        address: Option<IpAddr>,
+        port: u16,
+        starttls: Option<StartTls>,
+    ) -> Result<Handshake, String> {
+        let provider = Arc::new(ring::default_provider());
+        let mut config = ClientConfig::builder_with_provider(provider.clone())
+            .with_safe_default_protocol_versions()
+            .map_err(|e| format!("Failed to configure TLS: {}", e))?
+            .dangerous()
+            .with_custom_certificate_verifier(Arc::new(AcceptAnyCertificate(provider)))
+            .with_no_client_auth();
+        // Mail servers don't speak HTTP, so only offer ALPN on implicit TLS
+        if starttls.is_none() {
+            config.alpn_protocols = ALPN_PROTOCOLS
+                .iter()
+                .map(|p| p.as_bytes().to_vec())
+                .collect();
+        }
+
+        let server_name = ServerName::try_from(host.to_string())
+            .map_err(|e| format!("Invalid host {}: {}", host, e))?;
+
+        let connected = match address {
+            Some(ip) => TcpStream::connect((ip, port)).await,
+            None => TcpStream::connect((host, port)).await,
+        };
+        let mut stream = connected.map_err(|e| {
+            let target = address.map(|ip| ip.to_string());
+            format!(
+                "Failed to connect to {}:{}: {}",
+                target.as_deref().unwrap_or(host),
+                port,
+                e
+            )
+        })?;
+
+        let transcript = match starttls {
+            Some(protocol) => self.negotiate_starttls(&mut stream, protocol).await?,
+            None => String::new(),
+        };
+
+        let tls = TlsConnector::from(Arc::new(config))
+            .connect(server_name, stream)
+            .await
+            .map_err(|e| format!("TLS handshake with {}:{} failed: {}", host, port, e))?;
+
+        let (_, connection) = tls.get_ref();
+        let chain: Vec<CertificateDer<'static>> = connection
+            .peer_certificates()
+            .map(|certs| certs.iter().map(|c| c.clone().into_owned()).collect())
+            .unwrap_or_default();
+
+        let alpn_protocol = connection
+            .alpn_protocol()
+            .map(|p| String::from_utf8_lossy(p).to_string());
+
+        let summary = format!(
+            "{}Protocol: {:?}, Cipher: {:?}, ALPN: {}, Certificates: {}",
+            transcript,
+            connection.protocol_version(),
+            connection.negotiated_cipher_suite().map(|s| s.suite()),
+            alpn_protocol.as_deref().unwrap_or("none"),
+            chain.len()
+        );
+
+        Ok(Handshake {
+            chain,
+            alpn_protocol,
+            summary,
+        })
+    }
+
+    // Upgrade a plaintext mail connection to TLS (RFC 3207, RFC 2595)
+    // Returns the exchange so it shows up in the command log
+    pub async fn negotiate_starttls(
+        &self,
+        stream: &mut TcpStream,
+        protocol: StartTls,
+    ) -> Result<String, String> {
+        let mut reader = BufReader::new(stream);
+        let mut transcript = String::new();
+
+        // (command, reply prefix that means success); the first reply is the greeting
+        let exchange: &[(&str, &str)] = match protocol {
+            StartTls::Smtp => &[
+                ("", "220"),
+                ("EHLO d-dns-debugger", "250"),
+                ("STARTTLS", "220"),
+            ],
+            StartTls::Imap => &[("", "* OK"), ("a001 STARTTLS", "a001 OK")],
+            StartTls::Pop3 => &[("", "+OK"), ("STLS", "+OK")],
+        };
+
+        for (command, expected) in exchange {
+            if !command.is_empty() {
+                transcript.push_str(&format!("> {}\n", command));
+                reader
+                    .get_mut()
+                    .write_all(format!("{}\r\n", command).as_bytes())
+                    .await
+                    .map_err(|e| format!("STARTTLS write failed: {}", e))?;
+            }
+
+            let reply = self.read_reply(&mut reader, protocol, command).await?;
+            for line in &reply {
+                transcript.push_str(&format!("< {}\n", line));
+            }
+
+            let last_line = reply.last().map(String::as_str).unwrap_or_default();
+            if !last_line.starts_with(expected) {
+                return Err(format!(
+                    "STARTTLS negotiation failed: expected {} but server replied: {}",
+                    expected, last_line
+                ));
+            }
+
+            // The EHLO reply lists the extensions; without STARTTLS there's no point asking
+            if command.starts_with("EHLO")
+                && !reply.iter().any(|l| l.to_uppercase().contains("STARTTLS"))
+            {
+                return Err("Server does not advertise STARTTLS".to_string());
+            }
+        }
+
+        Ok(transcript)
+    }
+
+    // Read the lines of one server reply: SMTP continues while lines look like "250-...",
+    // IMAP sends untagged "* ..." lines before the tagged result of a command
+    async fn read_reply(
+        &self,
+        reader: &mut BufReader<&mut TcpStream>,
+        protocol: StartTls,
+        command: &str,
+    ) -> Result<Vec<String>, String> {
+        let mut lines = Vec::new();
+
+        loop {
+            let mut line = String::new();
+            let read = reader
+                .read_line(&mut line)
+                .await
+                .map_err(|e| format!("STARTTLS read failed: {}", e))?;
+            if read == 0 {
+                return Err("Server closed the connection during STARTTLS".to_string());
+            }
+
+            let line = line.trim_end().to_string();
+            let done = match protocol {
+                StartTls::Smtp => line.as_bytes().get(3) != Some(&b'-'),
+                StartTls::Imap => command.is_empty() || !line.starts_with("* "),
+                StartTls::Pop3 => true,
+            };
+            lines.push(line);
+
+            if done {
+                return Ok(lines);
+            }
+        }
+    }
 }
 
 impl CertificateAdapter {
     pub fn new() -> Self {
-        CertificateAdapter { app_handle: None }
+        Self::from_app_handle(None)
     }
 
     pub fn with_app_handle(app_handle: AppHandle) -> Self {
+        Self::from_app_handle(Some(app_handle))
+    }
+
+    // For other adapters making TLS connections on behalf of their own handle
+    pub fn from_app_handle(app_handle: Option<AppHandle>) -> Self {
         CertificateAdapter {
-            app_handle: Some(app_handle),
+            backends: state::backends_for(app_handle.as_ref()),
+            app_handle,
         }
     }
 
+    // Connect through these instead of the app's backends
+    pub fn with_backends(mut self, backends: Backends) -> Self {
+        self.backends = backends;
+        self
+    }
+
     fn emit_log(&self, log: CommandLog) {
-        if let Some(handle) = &self.app_handle {
-            let _ = handle.emit("command-log", log);
-        }
+        command_logs::record(self.app_handle.as_ref(), log);
     }
 
-    pub async fn get_certificate_info(&self, host: &str, port: u16) -> Result<TlsInfo, String> {
-        let start = Instant::now();
-        if !self.is_openssl_available() {
-            return Err("openssl command not found. Please install OpenSSL.".to_string());
-        }
+    // A fetch of a certificate already being fetched waits for that one's result
+    #[instrument(skip_all, fields(host = %host, port, starttls = ?starttls))]
+    pub async fn get_certificate_info(
+        &self,
+        host: &str,
+        port: u16,
+        starttls: Option<StartTls>,
+    ) -> Result<TlsInfo, String> {
+        let key = format!("{}:{} {:?}", host.to_lowercase(), port, starttls);
+        // Callers sharing another's connection log nothing of their own
+        state::session_domains::record(self.app_handle.as_ref(), host, false);
+        state::in_flight::coalesce(self.app_handle.as_ref(), "certificate", &key, || {
+            self.fetch_certificate_info(host, port, starttls)
+        })
+        .await
+    }
 
-        // Get certificate chain using openssl s_client
-        let command = format!(
-            "echo Q | openssl s_client -connect {}:{} -showcerts 2>/dev/null",
-            host, port
-        );
+    async fn fetch_certificate_info(
+        &self,
+        host: &str,
+        port: u16,
+        starttls: Option<StartTls>,
+    ) -> Result<TlsInfo, String> {
+        let Handshake {
+            chain,
+            alpn_protocol,
+            summary,
+        } = self.fetch_with_retries(host, None, port, starttls).await?;
 
-        let output = Command::new("sh")
-            .arg("-c")
-            .arg(&command)
-            .output()
-            .map_err(|e| format!("Failed to execute openssl: {}", e))?;
-
-        let stdout = String::from_utf8_lossy(&output.stdout);
-        let exit_code = output.status.code().unwrap_or(1);
-        let duration = start.elapsed().as_millis() as f64;
-
-        // Log the command
-        self.emit_log(CommandLog::new(
-            "openssl".to_string(),
-            vec![
-                "s_client".to_string(),
-                "-connect".to_string(),
-                format!("{}:{}", host, port),
-                "-showcerts".to_string(),
-            ],
-            stdout.to_string(),
-            exit_code,
-            duration,
-            Some(host.to_string()),
+        let settings = state::settings_for(self.app_handle.as_ref());
+        let mut certificates = self.parse_certificate_chain(&chain)?;
+        // A name mismatch is left to validate_chain, which reports it as an error
+        let mut warnings = self.usage_warnings(&certificates[0]);
+        let certificate_warnings = self.classify_chain(&mut certificates);
+        warnings.extend(certificate_warnings.iter().map(|w| w.message.clone()));
+        warnings.extend(self.expiry_warnings(
+            &mut certificates,
+            Utc::now(),
+            &settings.cert_expiry_warning_days,
         ));
 
-        let certificates = self.parse_certificate_chain(&stdout)?;
+        // Load-balanced hosts can serve different certificates from each address
+        let (per_ip, ip_warnings) = self
+            .compare_addresses(host, port, starttls, &certificates[0])
+            .await;
+        warnings.extend(ip_warnings);
+        let (validation_errors, trust_anchor) = self.validate_chain(
+            host,
+            &chain,
+            webpki_roots::TLS_SERVER_ROOTS,
+            UnixTime::now(),
+        );
 
         Ok(TlsInfo {
             host: host.to_string(),
             port,
+            starttls,
+            supports_http2: alpn_protocol.as_deref() == Some("h2"),
+            alpn_protocol,
+            per_ip,
             certificate_chain: CertificateChain {
                 certificates,
-                is_valid: true,
-                validation_errors: vec![],
+                is_valid: validation_errors.is_empty(),
+                validation_errors,
+                trust_anchor,
             },
-            raw_output: Some(stdout.to_string()),
+            warnings,
+            certificate_warnings,
+            raw_output: Some(format!("{}\n{}", summary, self.chain_to_pem(&chain))),
         })
     }
 
-    fn parse_certificate_chain(&self, output: &str) -> Result<Vec<CertificateInfo>, String> {
-        let mut certificates = Vec::new();
+    // Quick TLS inventory: check each port concurrently (bounded by max_concurrency),
+    // using STARTTLS on the well-known plaintext mail ports
+    #[instrument(skip_all, fields(host = %host, ports = ?ports))]
+    pub async fn scan_tls_ports(&self, host: &str, ports: &[u16]) -> TlsPortScan {
+        let start = Instant::now();
+        let settings = state::settings_for(self.app_handle.as_ref());
+        let permits = Arc::new(Semaphore::new(settings.max_concurrency.max(1)));
+        let mut tasks = JoinSet::new();
+
+        for (index, port) in ports.iter().copied().enumerate() {
+            let adapter = self.clone();
+            let host = host.to_string();
+            let permits = permits.clone();
+            tasks.spawn(async move {
+                let _permit = permits.acquire_owned().await;
+                (index, adapter.scan_port(&host, port).await)
+            });
+        }
+
+        let mut results = Vec::new();
+        while let Some(joined) = tasks.join_next().await {
+            if let Ok(entry) = joined {
+                results.push(entry);
+            }
+        }
+        results.sort_by_key(|(index, _)| *index);
+
+        TlsPortScan {
+            host: host.to_string(),
+            results: results.into_iter().map(|(_, result)| result).collect(),
+            total_time: start.elapsed().as_secs_f64(),
+        }
+    }
+
+    async fn scan_port(&self, host: &str, port: u16) -> PortTlsResult {
+        let settings = state::settings_for(self.app_handle.as_ref());
+        let timeout = Duration::from_secs(settings.tls_timeout_secs as u64);
+        let starttls = StartTls::for_port(port);
+        let mut result = PortTlsResult {
+            port,
+            starttls,
+            reachable: false,
+            tls_ok: false,
+            common_name: None,
+            issuer: None,
+            not_after: None,
+            days_until_expiry: None,
+            error: None,
+        };
+
+        // Separate probe so a closed port isn't reported as a TLS failure
+        match tokio::time::timeout(timeout, TcpStream::connect((host, port))).await {
+            Ok(Ok(_)) => result.reachable = true,
+            Ok(Err(e)) => {
+                result.error = Some(format!("Connection failed: {}", e));
+                return result;
+            }
+            Err(_) => {
+                result.error = Some(format!(
+                    "Connection timed out after {} seconds",
+                    settings.tls_timeout_secs
+                ));
+                return result;
+            }
+        }
+
+        let leaf = self
+            .fetch_with_retries(host, None, port, starttls)
+            .await
+            .and_then(|handshake| {
+                let leaf = handshake.chain.first().ok_or("No certificates presented")?;
+                self.parse_der_certificate(leaf)
+            });
+
+        match leaf {
+            Ok(leaf) => {
+                result.tls_ok = true;
+                result.common_name = leaf.subject.common_name;
+                result.issuer = leaf.issuer.common_name;
+                result.days_until_expiry = leaf.not_after_at.map(|t| (t - Utc::now()).num_days());
+                result.not_after = Some(leaf.not_after);
+            }
+            Err(e) => result.error = Some(e),
+        }
+
+        result
+    }
+
+    // Issuance history from Certificate Transparency logs via crt.sh
+    #[instrument(skip_all, fields(domain = %domain, include_subdomains))]
+    pub async fn lookup_ct_history(
+        &self,
+        domain: &str,
+        include_subdomains: bool,
+    ) -> Result<CtHistory, String> {
+        let settings = state::settings_for(self.app_handle.as_ref());
+        // %25 is an escaped "%", crt.sh's wildcard
+        let query = if include_subdomains {
+            format!("%25.{}", domain)
+        } else {
+            domain.to_string()
+        };
+
+        let (status, body) = HttpAdapter::from_app_handle(self.app_handle.clone())
+            .with_backends(self.backends.clone())
+            .get_text(
+                &format!("https://crt.sh/?q={}&output=json", query),
+                "application/json",
+                // crt.sh is slow for busy domains
+                settings.http_timeout_secs * 3,
+                settings.http_retries,
+                domain,
+            )
+            .await
+            .map_err(|e| format!("crt.sh lookup failed: {}", e))?;
+        if !(200..300).contains(&status) {
+            return Err(format!("crt.sh lookup failed: HTTP {}", status));
+        }
+
+        let entries = self.parse_ct_entries(&body, Utc::now())?;
+        let issuers = self.count_ct_issuers(&entries);
+        let warnings = self.ct_warnings(&entries, &issuers);
+
+        Ok(CtHistory {
+            domain: domain.to_string(),
+            include_subdomains,
+            entries,
+            issuers,
+            warnings,
+            raw_output: Some(body),
+        })
+    }
+
+    // crt.sh lists a precertificate and its final certificate separately; they
+    // share issuer and serial, so merge them and keep the earliest log time
+    pub fn parse_ct_entries(
+        &self,
+        json: &str,
+        now: DateTime<Utc>,
+    ) -> Result<Vec<CtLogEntry>, String> {
+        let rows: Vec<CrtShEntry> = serde_json::from_str(json)
+            .map_err(|e| format!("Failed to parse crt.sh response: {}", e))?;
+
+        let mut entries: Vec<CtLogEntry> = Vec::new();
+        let mut seen: HashMap<(String, String), usize> = HashMap::new();
+
+        for row in rows {
+            let logged_at = row.entry_timestamp.unwrap_or_default();
+            let key = (row.issuer_name.clone(), row.serial_number.to_lowercase());
+            if let Some(&index) = seen.get(&key) {
+                let entry = &mut entries[index];
+                if !logged_at.is_empty()
+                    && (entry.logged_at.is_empty() || logged_at < entry.logged_at)
+                {
+                    entry.logged_at = logged_at;
+                }
+                entry.id = entry.id.min(row.id);
+                continue;
+            }
+
+            let mut names: Vec<String> = row
+                .name_value
+                .lines()
+                .map(|n| n.trim().to_lowercase())
+                .filter(|n| !n.is_empty())
+                .collect();
+            names.sort();
+            names.dedup();
+
+            let expired = self
+                .parse_ct_time(&row.not_after)
+                .map(|t| t <= now)
+                .unwrap_or(false);
+
+            seen.insert(key, entries.len());
+            entries.push(CtLogEntry {
+                id: row.id,
+                issuer: row.issuer_name,
+                common_name: row.common_name.unwrap_or_default(),
+                names,
+                serial_number: row.serial_number,
+                not_before: row.not_before,
+                not_after: row.not_after,
+                logged_at,
+                expired,
+            });
+        }
+
+        entries.sort_by(|a, b| b.not_before.cmp(&a.not_before).then(b.id.cmp(&a.id)));
+        Ok(entries)
+    }
+
+    // crt.sh timestamps are UTC without an offset, e.g. "2024-05-01T12:00:00" or
+    // "2024-05-01T12:00:00.123"
+    fn parse_ct_time(&self, value: &str) -> Option<DateTime<Utc>> {
+        NaiveDateTime::parse_from_str(value, "%Y-%m-%dT%H:%M:%S%.f")
+            .ok()
+            .map(|t| t.and_utc())
+    }
+
+    fn count_ct_issuers(&self, entries: &[CtLogEntry]) -> Vec<CtIssuer> {
+        let mut counts: HashMap<&str, usize> = HashMap::new();
+        for entry in entries {
+            *counts.entry(entry.issuer.as_str()).or_default() += 1;
+        }
+
+        let mut issuers: Vec<CtIssuer> = counts
+            .into_iter()
+            .map(|(name, certificates)| CtIssuer {
+                name: name.to_string(),
+                certificates,
+            })
+            .collect();
+        issuers.sort_by(|a, b| {
+            b.certificates
+                .cmp(&a.certificates)
+                .then(a.name.cmp(&b.name))
+        });
+        issuers
+    }
+
+    // Flag still-valid certificates from CAs that issued only once for this domain;
+    // a one-off issuer among regular renewals is the usual sign of a rogue or
+    // forgotten issuance
+    pub fn ct_warnings(&self, entries: &[CtLogEntry], issuers: &[CtIssuer]) -> Vec<String> {
+        if issuers.len() < 2 {
+            return vec![];
+        }
+
+        entries
+            .iter()
+            .filter(|entry| !entry.expired)
+            .filter(|entry| {
+                issuers
+                    .iter()
+                    .any(|i| i.name == entry.issuer && i.certificates == 1)
+            })
+            .map(|entry| {
+                format!(
+                    "Unexpected issuer: certificate {} for {} was issued by {} (only certificate from this CA, valid until {})",
+                    entry.id,
+                    if entry.names.is_empty() {
+                        entry.common_name.clone()
+                    } else {
+                        entry.names.join(", ")
+                    },
+                    entry.issuer,
+                    entry.not_after
+                )
+            })
+            .collect()
+    }
+
+    // Handshake with retries per settings, logging each attempt like the external tools
+    // With an address, connects to that IP while still sending the host as SNI
+    async fn fetch_with_retries(
+        &self,
+        host: &str,
+        address: Option<IpAddr>,
+        port: u16,
+        starttls: Option<StartTls>,
+    ) -> Result<Handshake, String> {
+        let settings = state::settings_for(self.app_handle.as_ref());
+        let timeout = Duration::from_secs(settings.tls_timeout_secs as u64);
+        let target = match address {
+            Some(ip) => format!("{}:{}", ip, port),
+            None => format!("{}:{}", host, port),
+        };
+        let mut log_args = vec!["connect".to_string(), target];
+        if address.is_some() {
+            log_args.push("-servername".to_string());
+            log_args.push(host.to_string());
+        }
+        if let Some(protocol) = starttls {
+            log_args.push("-starttls".to_string());
+            log_args.push(format!("{:?}", protocol).to_lowercase());
+        }
+        let mut attempt = 0;
+
+        loop {
+            attempt += 1;
+            let waited = rate_limiter::throttle(self.app_handle.as_ref(), Target::Host, host).await;
+            let attempt_start = Instant::now();
 
-        // Extract PEM certificates - use (?s) flag for DOTALL mode (. matches newlines)
-        let cert_regex =
-            Regex::new(r"(?s)-----BEGIN CERTIFICATE-----(.*?)-----END CERTIFICATE-----").unwrap();
+            let handshake = self
+                .backends
+                .tls
+                .handshake(host, address, port, starttls)
+                .instrument(tracing::debug_span!("tls_handshake", host, port, attempt));
+            let result = tokio::time::timeout(timeout, handshake)
+                .await
+                .unwrap_or_else(|_| {
+                    Err(format!(
+                        "TLS handshake timed out after {} seconds",
+                        settings.tls_timeout_secs
+                    ))
+                });
 
-        for cap in cert_regex.captures_iter(output) {
-            let pem = format!(
-                "-----BEGIN CERTIFICATE-----{}-----END CERTIFICATE-----",
-                &cap[1]
+            let duration = attempt_start.elapsed().as_millis() as f64;
+            let (output, exit_code) = match &result {
+                Ok(handshake) => (handshake.summary.clone(), 0),
+                Err(e) => (e.clone(), 1),
+            };
+
+            self.emit_log(
+                CommandLog::new(
+                    "tls".to_string(),
+                    log_args.clone(),
+                    output,
+                    exit_code,
+                    duration,
+                    Some(host.to_string()),
+                )
+                .with_throttled(waited),
             );
 
-            if let Ok(cert_info) = self.parse_single_certificate(&pem) {
-                certificates.push(cert_info);
+            match result {
+                Ok(fetched) => return Ok(fetched),
+                Err(e) if attempt > settings.tls_retries => return Err(e),
+                Err(_) => {}
             }
         }
+    }
 
-        if certificates.is_empty() {
-            return Err("No certificates found in chain".to_string());
+    // Connect to every address the host resolves to and compare each leaf with the
+    // one served by hostname. Skipped when the host has a single address.
+    async fn compare_addresses(
+        &self,
+        host: &str,
+        port: u16,
+        starttls: Option<StartTls>,
+        primary: &CertificateInfo,
+    ) -> (Vec<IpCertificate>, Vec<String>) {
+        let mut addresses: Vec<IpAddr> = match lookup_host((host, port)).await {
+            Ok(resolved) => resolved.map(|addr| addr.ip()).collect(),
+            Err(_) => return (vec![], vec![]),
+        };
+        addresses.sort();
+        addresses.dedup();
+
+        if addresses.len() < 2 {
+            return (vec![], vec![]);
         }
 
-        Ok(certificates)
+        let mut tasks = JoinSet::new();
+        for (index, ip) in addresses.into_iter().enumerate() {
+            let adapter = self.clone();
+            let host = host.to_string();
+            tasks.spawn(async move {
+                let result = adapter
+                    .fetch_with_retries(&host, Some(ip), port, starttls)
+                    .await
+                    .and_then(|handshake| {
+                        let leaf = handshake.chain.first().ok_or("No certificates presented")?;
+                        adapter.parse_der_certificate(leaf)
+                    });
+                (index, ip.to_string(), result)
+            });
+        }
+
+        let mut results = Vec::new();
+        while let Some(joined) = tasks.join_next().await {
+            if let Ok(entry) = joined {
+                results.push(entry);
+            }
+        }
+        results.sort_by_key(|(index, _, _)| *index);
+
+        self.compare_ip_certificates(
+            primary,
+            results
+                .into_iter()
+                .map(|(_, ip, result)| (ip, result))
+                .collect(),
+        )
     }
 
-    fn parse_single_certificate(&self, pem: &str) -> Result<CertificateInfo, String> {
-        // Parse certificate using openssl x509 with stdin
-        use std::io::Write;
-        use std::process::Stdio;
+    // Flag addresses whose leaf differs from the primary one (serial, expiry) or that
+    // failed the handshake
+    pub fn compare_ip_certificates(
+        &self,
+        primary: &CertificateInfo,
+        results: Vec<(String, Result<CertificateInfo, String>)>,
+    ) -> (Vec<IpCertificate>, Vec<String>) {
+        let mut warnings = Vec::new();
+
+        let per_ip = results
+            .into_iter()
+            .map(|(ip, result)| match result {
+                Ok(cert) => {
+                    let matches_primary = cert.fingerprint_sha256 == primary.fingerprint_sha256;
+                    if !matches_primary {
+                        let mut differences = Vec::new();
+                        if cert.serial_number != primary.serial_number {
+                            differences.push(format!(
+                                "serial {} vs {}",
+                                cert.serial_number, primary.serial_number
+                            ));
+                        }
+                        if cert.not_after != primary.not_after {
+                            differences.push(format!(
+                                "expires {} vs {}",
+                                cert.not_after, primary.not_after
+                            ));
+                        }
+                        if differences.is_empty() {
+                            differences.push("different fingerprint".to_string());
+                        }
+                        warnings.push(format!(
+                            "{} serves a different certificate ({})",
+                            ip,
+                            differences.join(", ")
+                        ));
+                    }
+
+                    IpCertificate {
+                        ip,
+                        serial_number: Some(cert.serial_number),
+                        fingerprint_sha256: Some(cert.fingerprint_sha256),
+                        not_after: Some(cert.not_after),
+                        matches_primary,
+                        error: None,
+                    }
+                }
+                Err(e) => {
+                    warnings.push(format!("TLS handshake with {} failed: {}", ip, e));
+                    IpCertificate {
+                        ip,
+                        serial_number: None,
+                        fingerprint_sha256: None,
+                        not_after: None,
+                        matches_primary: false,
+                        error: Some(e),
+                    }
+                }
+            })
+            .collect();
+
+        (per_ip, warnings)
+    }
 
-        let mut child = Command::new("openssl")
-            .args(&["x509", "-text", "-noout"])
-            .stdin(Stdio::piped())
-            .stdout(Stdio::piped())
-            .stderr(Stdio::piped())
-            .spawn()
-            .map_err(|e| format!("Failed to spawn openssl: {}", e))?;
+    fn parse_certificate_chain(
+        &self,
+        chain: &[CertificateDer<'_>],
+    ) -> Result<Vec<CertificateInfo>, String> {
+        let certificates: Vec<CertificateInfo> = chain
+            .iter()
+            .filter_map(|der| self.parse_der_certificate(der).ok())
+            .collect();
 
-        // Write PEM to stdin
-        if let Some(mut stdin) = child.stdin.take() {
-            stdin
-                .write_all(pem.as_bytes())
-                .map_err(|e| format!("Failed to write to stdin: {}", e))?;
+        if certificates.is_empty() {
+            return Err("No certificates found in chain".to_string());
         }
 
-        let output = child
-            .wait_with_output()
-            .map_err(|e| format!("Failed to read openssl output: {}", e))?;
+        Ok(certificates)
+    }
+
+    fn parse_der_certificate(&self, der: &[u8]) -> Result<CertificateInfo, String> {
+        let (_, cert) = X509Certificate::from_der(der)
+            .map_err(|e| format!("Failed to parse certificate: {}", e))?;
 
-        let text = String::from_utf8_lossy(&output.stdout);
+        let signature_oid = cert.signature_algorithm.algorithm.to_id_string();
+        let signature_algorithm = oid2sn(&cert.signature_algorithm.algorithm, oid_registry())
+            .map(|name| name.to_string())
+            .unwrap_or_else(|_| match signature_oid.as_str() {
+                // Legacy algorithms missing from the OID registry
+                "1.2.840.10045.4.1" => "ecdsa-with-SHA1".to_string(),
+                "1.2.840.10040.4.3" => "dsa-with-SHA1".to_string(),
+                _ => signature_oid.clone(),
+            });
+
+        let key_oid = cert.public_key().algorithm.algorithm.to_id_string();
+        let (public_key_algorithm, public_key_size) =
+            match (key_oid.as_str(), cert.public_key().parsed()) {
+                (_, Ok(PublicKey::RSA(rsa))) => ("RSA".to_string(), self.rsa_key_size(rsa.modulus)),
+                (_, Ok(PublicKey::EC(point))) => {
+                    ("ECDSA".to_string(), self.ec_key_size(point.data()))
+                }
+                // EdDSA keys have a fixed size per curve
+                ("1.3.101.112", _) => ("Ed25519".to_string(), Some(256)),
+                ("1.3.101.113", _) => ("Ed448".to_string(), Some(456)),
+                _ => (
+                    oid2sn(&cert.public_key().algorithm.algorithm, oid_registry())
+                        .map(|name| name.to_string())
+                        .unwrap_or(key_oid.clone()),
+                    None,
+                ),
+            };
 
-        // Parse certificate fields
-        let subject = self.parse_subject(&text, "Subject:");
-        let issuer = self.parse_subject(&text, "Issuer:");
-        let serial = self.extract_field(&text, "Serial Number:");
+        let key_usage = self.parse_key_usage(&cert);
+        let extended_key_usage = self.parse_extended_key_usage(&cert);
+        let (is_ca, path_length) = match cert.basic_constraints() {
+            Ok(Some(constraints)) => (constraints.value.ca, constraints.value.path_len_constraint),
+            _ => (false, None),
+        };
+        let embedded_scts = cert
+            .extensions()
+            .iter()
+            .map(|ext| match ext.parsed_extension() {
+                ParsedExtension::SCT(scts) => scts.len(),
+                _ => 0,
+            })
+            .sum();
 
-        // Parse NotBefore and NotAfter - handle both old and new openssl formats
-        // Old format: "Not Before: Sep 28 15:13:11 2025 GMT"
-        // New format: "v:NotBefore: Sep 28 15:13:11 2025 GMT; NotAfter: Dec 27 15:13:10 2025 GMT"
-        let (not_before, not_after) = self.extract_validity_dates(&text);
+        // Absent KU/EKU extensions don't restrict usage
+        let valid_for_server_auth = !is_ca
+            && (key_usage.is_empty()
+                || key_usage
+                    .iter()
+                    .any(|u| u == "digitalSignature" || u == "keyEncipherment"))
+            && (extended_key_usage.is_empty()
+                || extended_key_usage
+                    .iter()
+                    .any(|u| u == "serverAuth" || u == "anyExtendedKeyUsage"));
 
         Ok(CertificateInfo {
-            subject,
-            issuer,
-            serial_number: serial.unwrap_or_default(),
-            version: 3,
-            not_before,
-            not_after,
-            subject_alternative_names: vec![],
-            public_key_algorithm: "RSA".to_string(),
-            public_key_size: Some(2048),
-            signature_algorithm: "SHA256withRSA".to_string(),
-            fingerprint_sha256: String::new(),
+            subject: self.parse_name(cert.subject()),
+            issuer: self.parse_name(cert.issuer()),
+            serial_number: cert.raw_serial_as_string().to_uppercase(),
+            version: cert.version().0 as i32 + 1,
+            not_before: self.format_time(cert.validity().not_before.timestamp()),
+            not_after: self.format_time(cert.validity().not_after.timestamp()),
+            not_before_at: DateTime::from_timestamp(cert.validity().not_before.timestamp(), 0),
+            not_after_at: DateTime::from_timestamp(cert.validity().not_after.timestamp(), 0),
+            days_until_expiry: None,
+            subject_alternative_names: self.parse_subject_alternative_names(&cert),
+            public_key_algorithm,
+            public_key_size,
+            signature_algorithm,
+            fingerprint_sha256: self.fingerprint_sha256(der),
+            key_usage,
+            extended_key_usage,
+            is_ca,
+            path_length,
+            embedded_scts,
+            valid_for_server_auth,
+            role: CertificateRole::Leaf,
+            self_signed: cert.subject().as_raw() == cert.issuer().as_raw(),
+            der: der.to_vec(),
         })
     }
 
-    fn parse_subject(&self, text: &str, prefix: &str) -> CertificateSubject {
-        if let Some(line) = text.lines().find(|l| l.contains(prefix)) {
-            let parts: Vec<&str> = line.split(prefix).collect();
-            if parts.len() > 1 {
-                return self.parse_subject_fields(parts[1]);
+    // Assign each certificate its role and flag self-signed leaves, SHA-1/MD5
+    // signatures and overlong leaf validity. Root signatures aren't checked by
+    // clients, so a SHA-1 root isn't flagged.
+    pub fn classify_chain(&self, certificates: &mut [CertificateInfo]) -> Vec<CertificateWarning> {
+        let mut warnings = Vec::new();
+
+        for (index, cert) in certificates.iter_mut().enumerate() {
+            let name = cert
+                .subject
+                .common_name
+                .clone()
+                .unwrap_or_else(|| "unnamed".to_string());
+
+            cert.role = if index == 0 {
+                CertificateRole::Leaf
+            } else if cert.self_signed {
+                CertificateRole::Root
+            } else {
+                CertificateRole::Intermediate
+            };
+
+            if cert.role == CertificateRole::Leaf && cert.self_signed {
+                warnings.push(CertificateWarning {
+                    index,
+                    kind: CertificateWarningKind::SelfSignedLeaf,
+                    message: format!("Leaf certificate ({}) is self-signed", name),
+                });
+            }
+
+            let algorithm = cert.signature_algorithm.to_lowercase();
+            if cert.role != CertificateRole::Root
+                && (algorithm.contains("sha1") || algorithm.contains("md5"))
+            {
+                warnings.push(CertificateWarning {
+                    index,
+                    kind: CertificateWarningKind::WeakSignature,
+                    message: format!(
+                        "Certificate #{} ({}) uses a weak signature algorithm: {}",
+                        index + 1,
+                        name,
+                        cert.signature_algorithm
+                    ),
+                });
+            }
+
+            if cert.role == CertificateRole::Leaf {
+                if let (Some(from), Some(to)) = (cert.not_before_at, cert.not_after_at) {
+                    let days = (to - from).num_days();
+                    if days > MAX_LEAF_VALIDITY_DAYS {
+                        warnings.push(CertificateWarning {
+                            index,
+                            kind: CertificateWarningKind::LongValidity,
+                            message: format!(
+                                "Leaf certificate ({}) is valid for {} days; publicly trusted certificates are limited to {}",
+                                name, days, MAX_LEAF_VALIDITY_DAYS
+                            ),
+                        });
+                    }
+                }
             }
         }
 
-        CertificateSubject {
-            common_name: None,
-            organization: None,
-            organizational_unit: None,
-            locality: None,
-            state: None,
-            country: None,
+        warnings
+    }
+
+    // Key usage bits by their RFC 5280 names
+    fn parse_key_usage(&self, cert: &X509Certificate) -> Vec<String> {
+        let usage = match cert.key_usage() {
+            Ok(Some(usage)) => usage.value,
+            _ => return vec![],
+        };
+
+        [
+            (usage.digital_signature(), "digitalSignature"),
+            (usage.non_repudiation(), "nonRepudiation"),
+            (usage.key_encipherment(), "keyEncipherment"),
+            (usage.data_encipherment(), "dataEncipherment"),
+            (usage.key_agreement(), "keyAgreement"),
+            (usage.key_cert_sign(), "keyCertSign"),
+            (usage.crl_sign(), "cRLSign"),
+            (usage.encipher_only(), "encipherOnly"),
+            (usage.decipher_only(), "decipherOnly"),
+        ]
+        .iter()
+        .filter(|(set, _)| *set)
+        .map(|(_, name)| name.to_string())
+        .collect()
+    }
+
+    // Extended key usage purposes by name, unknown purposes as dotted OIDs
+    fn parse_extended_key_usage(&self, cert: &X509Certificate) -> Vec<String> {
+        let eku = match cert.extended_key_usage() {
+            Ok(Some(eku)) => eku.value,
+            _ => return vec![],
+        };
+
+        let mut purposes: Vec<String> = [
+            (eku.any, "anyExtendedKeyUsage"),
+            (eku.server_auth, "serverAuth"),
+            (eku.client_auth, "clientAuth"),
+            (eku.code_signing, "codeSigning"),
+            (eku.email_protection, "emailProtection"),
+            (eku.time_stamping, "timeStamping"),
+            (eku.ocsp_signing, "OCSPSigning"),
+        ]
+        .iter()
+        .filter(|(set, _)| *set)
+        .map(|(_, name)| name.to_string())
+        .collect();
+        purposes.extend(eku.other.iter().map(|oid| oid.to_id_string()));
+        purposes
+    }
+
+    // Leaf problems that browsers reject or that weaken CT compliance
+    pub fn usage_warnings(&self, leaf: &CertificateInfo) -> Vec<String> {
+        let mut warnings = Vec::new();
+
+        if leaf.is_ca {
+            warnings.push(
+                "Leaf certificate is a CA certificate (basicConstraints CA:TRUE)".to_string(),
+            );
+        }
+
+        if !leaf.valid_for_server_auth {
+            warnings.push(format!(
+                "Leaf certificate is not valid for TLS server authentication (KU: {}; EKU: {})",
+                if leaf.key_usage.is_empty() {
+                    "none".to_string()
+                } else {
+                    leaf.key_usage.join(", ")
+                },
+                if leaf.extended_key_usage.is_empty() {
+                    "none".to_string()
+                } else {
+                    leaf.extended_key_usage.join(", ")
+                },
+            ));
+        }
+
+        if leaf.embedded_scts == 0 {
+            warnings.push(
+                "Leaf certificate has no embedded SCTs (CT proof must come from the TLS handshake or OCSP)"
+                    .to_string(),
+            );
         }
+
+        warnings
     }
 
-    fn parse_subject_fields(&self, subject_str: &str) -> CertificateSubject {
-        let mut subject = CertificateSubject {
-            common_name: None,
-            organization: None,
-            organizational_unit: None,
-            locality: None,
-            state: None,
-            country: None,
+    // DNS names as-is, IP addresses in text form, other name types prefixed
+    fn parse_subject_alternative_names(&self, cert: &X509Certificate) -> Vec<String> {
+        let san = match cert.subject_alternative_name() {
+            Ok(Some(san)) => san,
+            _ => return vec![],
         };
 
-        for part in subject_str.split(',') {
-            let kv: Vec<&str> = part.trim().splitn(2, '=').collect();
-            if kv.len() == 2 {
-                let key = kv[0].trim();
-                let value = kv[1].trim().to_string();
-
-                match key {
-                    "CN" => subject.common_name = Some(value),
-                    "O" => subject.organization = Some(value),
-                    "OU" => subject.organizational_unit = Some(value),
-                    "L" => subject.locality = Some(value),
-                    "ST" => subject.state = Some(value),
-                    "C" => subject.country = Some(value),
-                    _ => {}
-                }
+        san.value
+            .general_names
+            .iter()
+            .filter_map(|name| match name {
+                GeneralName::DNSName(dns) => Some(dns.to_string()),
+                GeneralName::IPAddress(bytes) => match bytes.len() {
+                    4 => <[u8; 4]>::try_from(*bytes)
+                        .ok()
+                        .map(|b| Ipv4Addr::from(b).to_string()),
+                    16 => <[u8; 16]>::try_from(*bytes)
+                        .ok()
+                        .map(|b| Ipv6Addr::from(b).to_string()),
+                    _ => None,
+                },
+                GeneralName::RFC822Name(email) => Some(format!("email:{}", email)),
+                GeneralName::URI(uri) => Some(format!("URI:{}", uri)),
+                _ => None,
+            })
+            .collect()
+    }
+
+    // Validate the presented chain (leaf first) against a root store
+    // Returns concrete failures (expired, out of order, hostname mismatch, untrusted
+    // root) and the name of the trust anchor the chain was built to, if any
+    pub fn validate_chain(
+        &self,
+        host: &str,
+        chain: &[CertificateDer<'_>],
+        roots: &[TrustAnchor<'_>],
+        now: UnixTime,
+    ) -> (Vec<String>, Option<String>) {
+        let mut errors = Vec::new();
+        let parsed: Vec<X509Certificate> = chain
+            .iter()
+            .filter_map(|der| X509Certificate::from_der(der).ok().map(|(_, cert)| cert))
+            .collect();
+
+        if parsed.is_empty() || parsed.len() != chain.len() {
+            return (
+                vec!["Chain contains unparseable certificates".to_string()],
+                None,
+            );
+        }
+
+        let now_secs = now.as_secs() as i64;
+        let label = |index: usize, cert: &X509Certificate| {
+            let name = self.parse_name(cert.subject());
+            format!(
+                "Certificate #{} ({})",
+                index + 1,
+                name.common_name
+                    .or(name.organization)
+                    .unwrap_or_else(|| "unnamed".to_string())
+            )
+        };
+
+        for (index, cert) in parsed.iter().enumerate() {
+            let validity = cert.validity();
+            if validity.not_after.timestamp() < now_secs {
+                errors.push(format!(
+                    "{} expired on {}",
+                    label(index, cert),
+                    self.format_time(validity.not_after.timestamp())
+                ));
+            } else if validity.not_before.timestamp() > now_secs {
+                errors.push(format!(
+                    "{} is not valid until {}",
+                    label(index, cert),
+                    self.format_time(validity.not_before.timestamp())
+                ));
             }
         }
 
-        subject
-    }
+        // Each certificate must be issued by the one after it
+        for (index, pair) in parsed.windows(2).enumerate() {
+            if pair[0].issuer().as_raw() != pair[1].subject().as_raw() {
+                errors.push(format!(
+                    "Chain out of order: {} did not issue {}",
+                    label(index + 1, &pair[1]),
+                    label(index, &pair[0])
+                ));
+            }
+        }
 
-    fn extract_validity_dates(&self, text: &str) -> (String, String) {
-        // Try to find the v: line with NotBefore and NotAfter (new format)
-        if let Some(line) = text
-            .lines()
-            .find(|l| l.contains("NotBefore:") && l.contains("NotAfter:"))
-        {
-            // Format: v:NotBefore: Sep 28 15:13:11 2025 GMT; NotAfter: Dec 27 15:13:10 2025 GMT
-            let not_before = if let Some(start) = line.find("NotBefore:") {
-                let after_label = &line[start + "NotBefore:".len()..];
-                if let Some(end) = after_label.find(';') {
-                    after_label[..end].trim().to_string()
+        let leaf = self.parse_der_certificate(&chain[0]);
+        if let Ok(leaf) = &leaf {
+            let covered = leaf
+                .subject_alternative_names
+                .iter()
+                .any(|name| self.host_matches_name(host, name));
+            if !covered {
+                let names = if leaf.subject_alternative_names.is_empty() {
+                    format!(
+                        "no SANs, and clients ignore the CN ({})",
+                        leaf.subject.common_name.as_deref().unwrap_or("none")
+                    )
                 } else {
-                    after_label.trim().to_string()
-                }
-            } else {
-                String::new()
-            };
+                    format!("SANs: {}", leaf.subject_alternative_names.join(", "))
+                };
+                errors.push(format!(
+                    "Hostname mismatch: {} is not covered by the certificate ({})",
+                    host, names
+                ));
+            }
+        }
 
-            let not_after = if let Some(start) = line.find("NotAfter:") {
-                let after_label = &line[start + "NotAfter:".len()..];
-                after_label.trim().to_string()
-            } else {
-                String::new()
+        let algorithms = ring::default_provider()
+            .signature_verification_algorithms
+            .all;
+        let mut trust_anchor = None;
+
+        match EndEntityCert::try_from(&chain[0]) {
+            Ok(end_entity) => match end_entity.verify_for_usage(
+                algorithms,
+                roots,
+                &chain[1..],
+                now,
+                KeyUsage::server_auth(),
+                None,
+                None,
+            ) {
+                Ok(path) => trust_anchor = Some(self.trust_anchor_name(path.anchor())),
+                // Already reported above with dates
+                Err(webpki::Error::CertExpired { .. })
+                | Err(webpki::Error::CertNotValidYet { .. }) => {}
+                Err(webpki::Error::UnknownIssuer) => errors.push(
+                    "Untrusted root: the chain doesn't lead to a trusted root certificate (self-signed or missing intermediate)"
+                        .to_string(),
+                ),
+                Err(e) => errors.push(format!("Chain validation failed: {:?}", e)),
+            },
+            Err(e) => errors.push(format!("Leaf certificate is malformed: {:?}", e)),
+        }
+
+        (errors, trust_anchor)
+    }
+
+    // Trust anchors only carry the subject's contents, so re-wrap them in a SEQUENCE
+    fn trust_anchor_name(&self, anchor: &TrustAnchor<'_>) -> String {
+        let contents = anchor.subject.as_ref();
+        let mut der = vec![0x30];
+        match contents.len() {
+            len if len < 0x80 => der.push(len as u8),
+            len if len <= 0xFF => der.extend([0x81, len as u8]),
+            len => der.extend([0x82, (len >> 8) as u8, len as u8]),
+        }
+        der.extend_from_slice(contents);
+
+        X509Name::from_der(&der)
+            .ok()
+            .map(|(_, name)| self.parse_name(&name))
+            .and_then(|name| name.common_name.or(name.organization))
+            .unwrap_or_else(|| "unknown".to_string())
+    }
+
+    // Fill in days_until_expiry and warn about certificates inside the tightest
+    // configured threshold (e.g. 30/14/7 days). Expired certificates are reported
+    // as validation errors instead.
+    pub fn expiry_warnings(
+        &self,
+        certificates: &mut [CertificateInfo],
+        now: DateTime<Utc>,
+        thresholds: &[u32],
+    ) -> Vec<String> {
+        let mut warnings = Vec::new();
+
+        for (index, cert) in certificates.iter_mut().enumerate() {
+            let Some(not_after) = cert.not_after_at else {
+                continue;
             };
+            let days = (not_after - now).num_days();
+            cert.days_until_expiry = Some(days);
 
-            return (not_before, not_after);
+            if not_after <= now {
+                continue;
+            }
+
+            let threshold = thresholds.iter().filter(|t| days < **t as i64).min();
+            if let Some(threshold) = threshold {
+                warnings.push(format!(
+                    "Certificate #{} ({}) expires in {} day{} on {} (within {} days)",
+                    index + 1,
+                    cert.subject.common_name.as_deref().unwrap_or("unnamed"),
+                    days,
+                    if days == 1 { "" } else { "s" },
+                    cert.not_after,
+                    threshold
+                ));
+            }
         }
 
-        // Fall back to old format with separate lines
-        let not_before = self.extract_field(text, "Not Before:").unwrap_or_default();
-        let not_after = self.extract_field(text, "Not After:").unwrap_or_default();
+        warnings
+    }
 
-        (not_before, not_after)
+    // RFC 6125 matching: case-insensitive, and a wildcard may only stand in for
+    // the whole leftmost label ("*.example.com" covers "www.example.com" but not
+    // "example.com" or "a.b.example.com"). IP hosts only match IP SANs.
+    pub fn host_matches_name(&self, host: &str, name: &str) -> bool {
+        let host = host.trim_end_matches('.').to_lowercase();
+        let name = name.trim_end_matches('.').to_lowercase();
+
+        if let Ok(ip) = host.parse::<IpAddr>() {
+            return name.parse::<IpAddr>().map(|n| n == ip).unwrap_or(false);
+        }
+
+        match name.strip_prefix("*.") {
+            Some(suffix) => match host.split_once('.') {
+                Some((label, rest)) => !label.is_empty() && rest == suffix,
+                None => false,
+            },
+            None => host == name,
+        }
     }
 
-    fn extract_field(&self, text: &str, field: &str) -> Option<String> {
-        // For date fields, handle both "Not After:" and "Not After :" (with space before colon)
-        let search_term = if field.contains("Not Before") || field.contains("Not After") {
-            field.trim_end_matches(':')
+    // Warn when the queried host isn't one of the leaf certificate's SANs
+    // Browsers ignore the CN, so a certificate without SANs covers nothing
+    pub fn hostname_warnings(&self, host: &str, leaf: &CertificateInfo) -> Vec<String> {
+        if leaf.subject_alternative_names.is_empty() {
+            return vec![format!(
+                "Certificate has no Subject Alternative Names; clients ignore the CN ({})",
+                leaf.subject.common_name.as_deref().unwrap_or("none")
+            )];
+        }
+
+        let covered = leaf
+            .subject_alternative_names
+            .iter()
+            .any(|name| self.host_matches_name(host, name));
+
+        if covered {
+            vec![]
         } else {
-            field
+            vec![format!(
+                "{} is not covered by the certificate (SANs: {})",
+                host,
+                leaf.subject_alternative_names.join(", ")
+            )]
+        }
+    }
+
+    // Certificate association data a TLSA record would carry for this
+    // certificate (RFC 6698 section 2.1), as lowercase hex. None for selectors
+    // and matching types the RFC doesn't define.
+    pub fn tlsa_association(&self, der: &[u8], selector: u8, matching_type: u8) -> Option<String> {
+        let data = match selector {
+            0 => der.to_vec(),
+            1 => X509Certificate::from_der(der)
+                .ok()?
+                .1
+                .public_key()
+                .raw
+                .to_vec(),
+            _ => return None,
         };
+        let digest = match matching_type {
+            0 => data,
+            1 => Sha256::digest(&data).to_vec(),
+            2 => Sha512::digest(&data).to_vec(),
+            _ => return None,
+        };
+        Some(digest.iter().map(|b| format!("{:02x}", b)).collect())
+    }
 
-        text.lines()
-            .find(|l| l.contains(search_term))
-            .and_then(|l| {
-                // For date fields, get everything after the colon (handle spaces before colon)
-                if field.contains("Not Before") || field.contains("Not After") {
-                    // Find the colon and take everything after it
-                    if let Some(colon_pos) = l.find(':') {
-                        return Some(l[colon_pos + 1..].trim().to_string());
-                    }
-                }
-                // For other fields, use normal parsing
-                l.split(':').nth(1).map(|s| s.trim().to_string())
-            })
+    // SHA-256 over the DER encoding, as colon-separated uppercase hex
+    fn fingerprint_sha256(&self, der: &[u8]) -> String {
+        Sha256::digest(der)
+            .iter()
+            .map(|b| format!("{:02X}", b))
+            .collect::<Vec<_>>()
+            .join(":")
     }
 
-    fn is_openssl_available(&self) -> bool {
-        Command::new("openssl").arg("version").output().is_ok()
+    fn parse_name(&self, name: &X509Name) -> CertificateSubject {
+        // First value of each attribute, ignoring ones that aren't valid strings
+        fn first<'a>(
+            mut values: impl Iterator<Item = &'a AttributeTypeAndValue<'a>>,
+        ) -> Option<String> {
+            values
+                .find_map(|attr| attr.as_str().ok())
+                .map(|value| value.to_string())
+        }
+
+        CertificateSubject {
+            common_name: first(name.iter_common_name()),
+            organization: first(name.iter_organization()),
+            organizational_unit: first(name.iter_organizational_unit()),
+            locality: first(name.iter_locality()),
+            state: first(name.iter_state_or_province()),
+            country: first(name.iter_country()),
+        }
+    }
+
+    // Same layout openssl prints, e.g. "Sep 28 15:13:11 2025 GMT"
+    fn format_time(&self, timestamp: i64) -> String {
+        DateTime::from_timestamp(timestamp, 0)
+            .map(|dt| dt.format("%b %e %H:%M:%S %Y GMT").to_string())
+            .unwrap_or_default()
+    }
+
+    // Modulus length in bits, ignoring the DER sign byte and leading zero bits
+    fn rsa_key_size(&self, modulus: &[u8]) -> Option<u32> {
+        let start = modulus.iter().position(|b| *b != 0)?;
+        let bits = (modulus.len() - start) as u32 * 8 - modulus[start].leading_zeros();
+        Some(bits)
+    }
+
+    // Curve size from an uncompressed (0x04 | X | Y) or compressed (0x02/0x03 | X) point
+    fn ec_key_size(&self, point: &[u8]) -> Option<u32> {
+        let coordinate_len = match point.first()? {
+            4 => (point.len() - 1) / 2,
+            2 | 3 => point.len() - 1,
+            _ => return None,
+        };
+
+        // P-521 coordinates are padded to 66 bytes
+        match coordinate_len {
+            66 => Some(521),
+            len => Some(len as u32 * 8),
+        }
+    }
+
+    fn chain_to_pem(&self, chain: &[CertificateDer<'_>]) -> String {
+        let mut pem = String::new();
+        for der in chain {
+            let encoded = BASE64.encode(der.as_ref());
+            pem.push_str("-----BEGIN CERTIFICATE-----\n");
+            for line in encoded.as_bytes().chunks(64) {
+                pem.push_str(&String::from_utf8_lossy(line));
+                pem.push('\n');
+            }
+            pem.push_str("-----END CERTIFICATE-----\n");
+        }
+        pem
     }
 }
 
 #[cfg(test)]
+#[path = "certificate_test.rs"]
 mod tests;
@@ -1,205 +1,837 @@
-#[cfg(test)]
-mod tests {
-    use super::super::certificate::CertificateAdapter;
-    use crate::models::certificate::CertificateSubject;
-
-    #[test]
-    fn test_parse_subject_fields_full() {
-        let adapter = CertificateAdapter::new();
-        let subject_str =
-            "CN=example.com, O=Example Inc, OU=IT Department, L=San Francisco, ST=California, C=US";
-
-        let subject = adapter.parse_subject_fields(subject_str);
-
-        assert_eq!(subject.common_name, Some("example.com".to_string()));
-        assert_eq!(subject.organization, Some("Example Inc".to_string()));
-        assert_eq!(
-            subject.organizational_unit,
-            Some("IT Department".to_string())
-        );
-        assert_eq!(subject.locality, Some("San Francisco".to_string()));
-        assert_eq!(subject.state, Some("California".to_string()));
-        assert_eq!(subject.country, Some("US".to_string()));
-    }
+use super::super::backend::{BackendFuture, Backends, TlsBackend};
+use super::{CertificateAdapter, Handshake, RustlsTls};
+use crate::models::certificate::{CertificateRole, CertificateWarningKind, StartTls};
+use rustls::pki_types::{CertificateDer, UnixTime};
+use std::net::IpAddr;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::net::{TcpListener, TcpStream};
+use x509_parser::pem::parse_x509_pem;
+
+// Self-signed RSA-2048 / SHA-256 certificate with every subject field set
+const RSA_CERT: &str = r"-----BEGIN CERTIFICATE-----
+MIID0TCCArmgAwIBAgIISrLD1OX2eJAwDQYJKoZIhvcNAQELBQAwfjELMAkGA1UE
+BhMCVVMxEzARBgNVBAgMCkNhbGlmb3JuaWExFjAUBgNVBAcMDVNhbiBGcmFuY2lz
+Y28xFDASBgNVBAoMC0V4YW1wbGUgSW5jMRYwFAYDVQQLDA1JVCBEZXBhcnRtZW50
+MRQwEgYDVQQDDAtleGFtcGxlLmNvbTAeFw0yNjEwMTYwODQ4NDJaFw0yNzEwMTYw
+ODQ4NDJaMH4xCzAJBgNVBAYTAlVTMRMwEQYDVQQIDApDYWxpZm9ybmlhMRYwFAYD
+VQQHDA1TYW4gRnJhbmNpc2NvMRQwEgYDVQQKDAtFeGFtcGxlIEluYzEWMBQGA1UE
+CwwNSVQgRGVwYXJ0bWVudDEUMBIGA1UEAwwLZXhhbXBsZS5jb20wggEiMA0GCSqG
+SIb3DQEBAQUAA4IBDwAwggEKAoIBAQC3zEKyWjgjEfkTRFHmbrSuiFeMv2Ls2z0j
+fcLFhnmJEZZmM3UsjqE7kj6GcdOTtCn/W6yIuo00ERIMcqGJb8KbRIOMMZ6gVwjO
++DBVO3hJyDsl9y2YdRhBVJ2DQFFyzQ26i9luiHZwkWfOuyrPVX3MTGmhcDl8j3qB
+U55ek/R7/RgOuu9w+2iPhuYuCAZndnMb+OexQZw/ZA22A9yaL7VF5qXBocg/V6A1
+AiA0V8dT59kEwxc2cPIBgdVtsie0AUPl4zLhlVFxNWhhBk07TclpKBp9HzhYC2AH
+Cv2+wRbR53rtHGVxqsm8XK5n01JDP/Noq/+PTQkkJcMgJKMLOnL9AgMBAAGjUzBR
+MB0GA1UdDgQWBBRfJnuisMpklIOsj/Xdiy8cXejrUzAfBgNVHSMEGDAWgBRfJnui
+sMpklIOsj/Xdiy8cXejrUzAPBgNVHRMBAf8EBTADAQH/MA0GCSqGSIb3DQEBCwUA
+A4IBAQBMyOMfxH2A3DQ09Au4or5OC8aZisJ6cine7U9zLarzpcOJtgxS9Jo7Nlul
+N74RhqZ5qB20+rlrzod6H7q2vjmvuuWQspe0PW36ZzQnKRYlouKqWKz20e+xievv
+6t6a6s8IHFYg6HbPqx3i5DIjKtHOSyqXL1o4tCQPGmaH/KeuuZ4tUxBtN5XbyaG0
+WPUarTxwbemWP6QdqvNIGumFcEUBFiQw89AfHMXQGtUg23WXKgpPDZHPRZvmDGdF
+cTQue1OemtGm+js24cx5LOrppm3xvX5JKveiix+GBaN43KujySteUyzRXwnWphlH
+Lp/eIHX1HktTEM3Aq8MjPNS1N0yG
+-----END CERTIFICATE-----";
+
+// Self-signed P-256 certificate signed with ECDSA/SHA-384
+const EC_CERT: &str = r"-----BEGIN CERTIFICATE-----
+MIIBsjCCAVegAwIBAgIUE0sXxjsrcKC7dqlrr27PaHktVdwwCgYIKoZIzj0EAwMw
+LjEUMBIGA1UECgwLRXhhbXBsZSBJbmMxFjAUBgNVBAMMDSouZXhhbXBsZS5jb20w
+HhcNMjYxMDE2MDg0ODQyWhcNMjcwMTE0MDg0ODQyWjAuMRQwEgYDVQQKDAtFeGFt
+cGxlIEluYzEWMBQGA1UEAwwNKi5leGFtcGxlLmNvbTBZMBMGByqGSM49AgEGCCqG
+SM49AwEHA0IABGISquc3hK9wJbjZkENS+p4BsxBW3PaX/kdlEdZEL0OinS6/+Tdg
+1Xtx4E9o12tn37HXR0SXMcikxIzQscq+y8SjUzBRMB0GA1UdDgQWBBTGHDhkLeUG
+9hrD8+SBvSPHxXsnzDAfBgNVHSMEGDAWgBTGHDhkLeUG9hrD8+SBvSPHxXsnzDAP
+BgNVHRMBAf8EBTADAQH/MAoGCCqGSM49BAMDA0kAMEYCIQD1KCMCl74bxKek9pYr
+L1c7pIJ7Fo30sDe75zXbmvPd2wIhANo0ZMbg2+608pQiJKsyb0d3zIiCnslgAgFe
+V+dO85NM
+-----END CERTIFICATE-----";
+
+// P-256 certificate with DNS, wildcard, IP and email SANs
+const SAN_CERT: &str = r"-----BEGIN CERTIFICATE-----
+MIIBwzCCAWmgAwIBAgIUC32rrCHmifxA4LuKpiymlBrfT/wwCgYIKoZIzj0EAwIw
+FjEUMBIGA1UEAwwLZXhhbXBsZS5jb20wHhcNMjYxMDE2MDg1MjAxWhcNMjcwMTE0
+MDg1MjAxWjAWMRQwEgYDVQQDDAtleGFtcGxlLmNvbTBZMBMGByqGSM49AgEGCCqG
+SM49AwEHA0IABAyZjQ5IX61SSeOW2rIfT0YfxNzt4Fmfpg/zs4jWyaureX6Z6uxc
+g3gcSLHw8L2XcqLvneNUK9H3306Ef7tRgI+jgZQwgZEwHQYDVR0OBBYEFGlpcKt4
+wRdJbeZvDw+mDYZxjImXMB8GA1UdIwQYMBaAFGlpcKt4wRdJbeZvDw+mDYZxjImX
+MA8GA1UdEwEB/wQFMAMBAf8wPgYDVR0RBDcwNYILZXhhbXBsZS5jb22CDSouZXhh
+bXBsZS5jb22HBF242CKBEWFkbWluQGV4YW1wbGUuY29tMAoGCCqGSM49BAMCA0gA
+MEUCIQCHKTeTtdmMTOePl2CA9w99Zi+xsXtlcsu2j3wFyTL9kgIgIkB23eHSHyL/
+nUeQoN6W21qrC34c3u5jBwuXoDOTDsA=
+-----END CERTIFICATE-----";
+
+// Self-signed Ed25519 certificate
+const ED25519_CERT: &str = r"-----BEGIN CERTIFICATE-----
+MIIBUTCCAQOgAwIBAgIUDI+wizKHH+HyO1Pfi1AfDa3wpzMwBQYDK2VwMB4xHDAa
+BgNVBAMME2VkMjU1MTkuZXhhbXBsZS5jb20wHhcNMjYxMDE2MDg1MjQ0WhcNMjYx
+MTE1MDg1MjQ0WjAeMRwwGgYDVQQDDBNlZDI1NTE5LmV4YW1wbGUuY29tMCowBQYD
+K2VwAyEA9e/7oFi9agG+r2+G6rZc1BhopoG2rxxPcs/Gf66l85ijUzBRMB0GA1Ud
+DgQWBBRsua832djuqPv5co60VnBk2Uu7QzAfBgNVHSMEGDAWgBRsua832djuqPv5
+co60VnBk2Uu7QzAPBgNVHRMBAf8EBTADAQH/MAUGAytlcANBAISIek7gCK/gKPHU
+wzqOu6X9wxz637D7x0uwEA2kSIadLBcpegIiHW7fXVfoUlO9ul5pE7ix89+xmPln
+4/cwkAI=
+-----END CERTIFICATE-----";
+
+// Leaf for www.example.com (valid Oct 16 - Nov 15 2026) issued by TEST_ROOT
+const TEST_LEAF: &str = r"-----BEGIN CERTIFICATE-----
+MIIBvjCCAWSgAwIBAgICA+kwCgYIKoZIzj0EAwIwNjEYMBYGA1UECgwPRXhhbXBs
+ZSBUZXN0IENBMRowGAYDVQQDDBFFeGFtcGxlIFRlc3QgUm9vdDAeFw0yNjEwMTYw
+ODUzMzZaFw0yNjExMTUwODUzMzZaMBoxGDAWBgNVBAMMD3d3dy5leGFtcGxlLmNv
+bTBZMBMGByqGSM49AgEGCCqGSM49AwEHA0IABM4+AJPPvL5drfRMZ+qdRwMA7M5F
+uGJQSIp/aBC9wX4UGkN30BNzWNupO7cKL1gssCNJzBNFPDYdXcn/fDr/eUKjfjB8
+MBoGA1UdEQQTMBGCD3d3dy5leGFtcGxlLmNvbTATBgNVHSUEDDAKBggrBgEFBQcD
+ATAJBgNVHRMEAjAAMB0GA1UdDgQWBBQTWWnX6Jdtf7QeiUppymDBa+DH5DAfBgNV
+HSMEGDAWgBTA/s0ipFD8636FBbdciBInpE5ZIDAKBggqhkjOPQQDAgNIADBFAiBj
+Gg4CIFz7RhCDBBxNQu1f7UG0ucGaitbScp2w5NSMsQIhAM2OX186DBMPE2E8FWWb
+pTkWChK2SG8CFNHWd2BiL9k5
+-----END CERTIFICATE-----";
+
+const TEST_ROOT: &str = r"-----BEGIN CERTIFICATE-----
+MIIB0TCCAXegAwIBAgIUIqvDC9BuDtTu+Tqhv0SoounyFCAwCgYIKoZIzj0EAwIw
+NjEYMBYGA1UECgwPRXhhbXBsZSBUZXN0IENBMRowGAYDVQQDDBFFeGFtcGxlIFRl
+c3QgUm9vdDAeFw0yNjEwMTYwODUzMzZaFw0zNjEwMTMwODUzMzZaMDYxGDAWBgNV
+BAoMD0V4YW1wbGUgVGVzdCBDQTEaMBgGA1UEAwwRRXhhbXBsZSBUZXN0IFJvb3Qw
+WTATBgcqhkjOPQIBBggqhkjOPQMBBwNCAARNnigdnaqI0LN8ov60mldaXI2ZsFSV
+eGkZOLVSVM60tIT9qIGMdeIoH9sNCPBgEC2KePNJn075a5UsAJmu2uKwo2MwYTAd
+BgNVHQ4EFgQUwP7NIqRQ/Ot+hQW3XIgSJ6ROWSAwHwYDVR0jBBgwFoAUwP7NIqRQ
+/Ot+hQW3XIgSJ6ROWSAwDwYDVR0TAQH/BAUwAwEB/zAOBgNVHQ8BAf8EBAMCAQYw
+CgYIKoZIzj0EAwIDSAAwRQIhAMPIRXTZemY+MYV2GU94yuCvnFfufDL3dUnxGYqk
+WlYeAiA115UQ1KN6wLkIL1LQHy5fJfmmWkn5KMS/aGYwsXxYqQ==
+-----END CERTIFICATE-----";
+
+fn der(pem: &str) -> Vec<u8> {
+    parse_x509_pem(pem.as_bytes()).unwrap().1.contents
+}
 
-    #[test]
-    fn test_parse_subject_fields_minimal() {
-        let adapter = CertificateAdapter::new();
-        let subject_str = "CN=example.com";
+#[test]
+fn test_parse_der_certificate_rsa() {
+    let adapter = CertificateAdapter::new();
+
+    let cert = adapter.parse_der_certificate(&der(RSA_CERT)).unwrap();
+
+    assert_eq!(cert.subject.common_name, Some("example.com".to_string()));
+    assert_eq!(cert.subject.organization, Some("Example Inc".to_string()));
+    assert_eq!(
+        cert.subject.organizational_unit,
+        Some("IT Department".to_string())
+    );
+    assert_eq!(cert.subject.locality, Some("San Francisco".to_string()));
+    assert_eq!(cert.subject.state, Some("California".to_string()));
+    assert_eq!(cert.subject.country, Some("US".to_string()));
+    assert_eq!(cert.issuer.common_name, Some("example.com".to_string()));
+    assert_eq!(cert.serial_number, "4A:B2:C3:D4:E5:F6:78:90");
+    assert_eq!(cert.version, 3);
+    assert_eq!(cert.not_before, "Oct 16 08:48:42 2026 GMT");
+    assert_eq!(cert.not_after, "Oct 16 08:48:42 2027 GMT");
+    assert_eq!(cert.public_key_algorithm, "RSA");
+    assert_eq!(cert.public_key_size, Some(2048));
+    assert_eq!(cert.signature_algorithm, "sha256WithRSAEncryption");
+}
 
-        let subject = adapter.parse_subject_fields(subject_str);
+#[test]
+fn test_parse_der_certificate_ec() {
+    let adapter = CertificateAdapter::new();
 
-        assert_eq!(subject.common_name, Some("example.com".to_string()));
-        assert_eq!(subject.organization, None);
-        assert_eq!(subject.organizational_unit, None);
-        assert_eq!(subject.locality, None);
-        assert_eq!(subject.state, None);
-        assert_eq!(subject.country, None);
-    }
+    let cert = adapter.parse_der_certificate(&der(EC_CERT)).unwrap();
 
-    #[test]
-    fn test_parse_subject_fields_partial() {
-        let adapter = CertificateAdapter::new();
-        let subject_str = "CN=example.com, O=Example Inc, C=US";
+    assert_eq!(cert.subject.common_name, Some("*.example.com".to_string()));
+    assert_eq!(cert.subject.organization, Some("Example Inc".to_string()));
+    assert_eq!(cert.subject.country, None);
+    assert_eq!(cert.not_after, "Jan 14 08:48:42 2027 GMT");
+    assert_eq!(cert.public_key_algorithm, "ECDSA");
+    assert_eq!(cert.public_key_size, Some(256));
+    assert_eq!(cert.signature_algorithm, "ecdsa-with-SHA384");
+}
 
-        let subject = adapter.parse_subject_fields(subject_str);
+#[test]
+fn test_parse_der_certificate_invalid() {
+    let adapter = CertificateAdapter::new();
 
-        assert_eq!(subject.common_name, Some("example.com".to_string()));
-        assert_eq!(subject.organization, Some("Example Inc".to_string()));
-        assert_eq!(subject.country, Some("US".to_string()));
-        assert_eq!(subject.organizational_unit, None);
-        assert_eq!(subject.locality, None);
-        assert_eq!(subject.state, None);
-    }
+    assert!(adapter.parse_der_certificate(b"not a certificate").is_err());
+}
 
-    #[test]
-    fn test_parse_subject_fields_with_spaces() {
-        let adapter = CertificateAdapter::new();
-        let subject_str = "CN = example.com , O = Example Inc";
+#[test]
+fn test_rsa_key_size_ignores_sign_byte() {
+    let adapter = CertificateAdapter::new();
 
-        let subject = adapter.parse_subject_fields(subject_str);
+    let mut modulus = vec![0x00, 0xB7];
+    modulus.extend(vec![0xFF; 255]);
+    assert_eq!(adapter.rsa_key_size(&modulus), Some(2048));
+    assert_eq!(adapter.rsa_key_size(&[0x01, 0x00]), Some(9));
+    assert_eq!(adapter.rsa_key_size(&[]), None);
+}
 
-        assert_eq!(subject.common_name, Some("example.com".to_string()));
-        assert_eq!(subject.organization, Some("Example Inc".to_string()));
-    }
+#[test]
+fn test_ec_key_size_curves() {
+    let adapter = CertificateAdapter::new();
 
-    #[test]
-    fn test_parse_subject_fields_empty() {
-        let adapter = CertificateAdapter::new();
-        let subject_str = "";
+    let mut p384 = vec![0x04];
+    p384.extend(vec![0xAA; 96]);
+    assert_eq!(adapter.ec_key_size(&p384), Some(384));
 
-        let subject = adapter.parse_subject_fields(subject_str);
+    let mut p521 = vec![0x04];
+    p521.extend(vec![0xAA; 132]);
+    assert_eq!(adapter.ec_key_size(&p521), Some(521));
 
-        assert_eq!(subject.common_name, None);
-        assert_eq!(subject.organization, None);
-    }
+    let mut compressed = vec![0x02];
+    compressed.extend(vec![0xAA; 32]);
+    assert_eq!(adapter.ec_key_size(&compressed), Some(256));
+}
 
-    #[test]
-    fn test_parse_subject() {
-        let adapter = CertificateAdapter::new();
-        let text = "Subject: CN=example.com, O=Example Inc, C=US";
+#[test]
+fn test_chain_to_pem_round_trips() {
+    let adapter = CertificateAdapter::new();
+    let chain = vec![der(RSA_CERT).into(), der(EC_CERT).into()];
 
-        let subject = adapter.parse_subject(text, "Subject:");
+    let pem = adapter.chain_to_pem(&chain);
 
-        assert_eq!(subject.common_name, Some("example.com".to_string()));
-        assert_eq!(subject.organization, Some("Example Inc".to_string()));
-        assert_eq!(subject.country, Some("US".to_string()));
-    }
+    assert_eq!(pem.matches("BEGIN CERTIFICATE").count(), 2);
+    assert!(pem.lines().all(|line| line.len() <= 64));
+    assert_eq!(der(&pem), der(RSA_CERT));
+}
 
-    #[test]
-    fn test_parse_subject_issuer() {
-        let adapter = CertificateAdapter::new();
-        let text = "Issuer: CN=Example CA, O=Example Inc, C=US";
+#[test]
+fn test_parse_subject_alternative_names() {
+    let adapter = CertificateAdapter::new();
 
-        let issuer = adapter.parse_subject(text, "Issuer:");
+    let cert = adapter.parse_der_certificate(&der(SAN_CERT)).unwrap();
 
-        assert_eq!(issuer.common_name, Some("Example CA".to_string()));
-        assert_eq!(issuer.organization, Some("Example Inc".to_string()));
-        assert_eq!(issuer.country, Some("US".to_string()));
-    }
+    assert_eq!(
+        cert.subject_alternative_names,
+        vec![
+            "example.com",
+            "*.example.com",
+            "93.184.216.34",
+            "email:admin@example.com"
+        ]
+    );
 
-    #[test]
-    fn test_parse_subject_not_found() {
-        let adapter = CertificateAdapter::new();
-        let text = "Some other text";
+    let no_san = adapter.parse_der_certificate(&der(RSA_CERT)).unwrap();
+    assert!(no_san.subject_alternative_names.is_empty());
+}
 
-        let subject = adapter.parse_subject(text, "Subject:");
+#[test]
+fn test_host_matches_name() {
+    let adapter = CertificateAdapter::new();
+
+    assert!(adapter.host_matches_name("example.com", "example.com"));
+    assert!(adapter.host_matches_name("WWW.Example.com.", "www.example.com"));
+    assert!(adapter.host_matches_name("www.example.com", "*.example.com"));
+    assert!(!adapter.host_matches_name("example.com", "*.example.com"));
+    assert!(!adapter.host_matches_name("a.b.example.com", "*.example.com"));
+    assert!(!adapter.host_matches_name("example.org", "example.com"));
+    assert!(adapter.host_matches_name("93.184.216.34", "93.184.216.34"));
+    assert!(!adapter.host_matches_name("93.184.216.34", "example.com"));
+}
 
-        assert_eq!(subject.common_name, None);
-        assert_eq!(subject.organization, None);
-    }
+#[test]
+fn test_hostname_warnings() {
+    let adapter = CertificateAdapter::new();
+    let cert = adapter.parse_der_certificate(&der(SAN_CERT)).unwrap();
 
-    #[test]
-    fn test_extract_field_serial() {
-        let adapter = CertificateAdapter::new();
-        let text = "Serial Number: 4A:B2:C3:D4:E5:F6:78:90";
+    assert!(adapter
+        .hostname_warnings("www.example.com", &cert)
+        .is_empty());
+    assert!(adapter.hostname_warnings("93.184.216.34", &cert).is_empty());
 
-        let serial = adapter.extract_field(text, "Serial Number:");
-        assert_eq!(serial, Some("4A:B2:C3:D4:E5:F6:78:90".to_string()));
-    }
+    let warnings = adapter.hostname_warnings("example.org", &cert);
+    assert_eq!(warnings.len(), 1);
+    assert!(warnings[0].starts_with("example.org is not covered"));
 
-    #[test]
-    fn test_extract_field_not_found() {
-        let adapter = CertificateAdapter::new();
-        let text = "Some text without the field";
+    let no_san = adapter.parse_der_certificate(&der(RSA_CERT)).unwrap();
+    let warnings = adapter.hostname_warnings("example.com", &no_san);
+    assert!(warnings[0].contains("no Subject Alternative Names"));
+}
 
-        let result = adapter.extract_field(text, "Serial Number:");
-        assert_eq!(result, None);
-    }
+#[test]
+fn test_parse_der_certificate_ed25519() {
+    let adapter = CertificateAdapter::new();
 
-    #[test]
-    fn test_extract_validity_dates_new_format() {
-        let adapter = CertificateAdapter::new();
-        let text = "v:NotBefore: Sep 28 15:13:11 2025 GMT; NotAfter: Dec 27 15:13:10 2025 GMT";
+    let cert = adapter.parse_der_certificate(&der(ED25519_CERT)).unwrap();
 
-        let (not_before, not_after) = adapter.extract_validity_dates(text);
+    assert_eq!(cert.public_key_algorithm, "Ed25519");
+    assert_eq!(cert.public_key_size, Some(256));
+    assert_eq!(cert.signature_algorithm, "ed25519");
+}
 
-        assert_eq!(not_before, "Sep 28 15:13:11 2025 GMT");
-        assert_eq!(not_after, "Dec 27 15:13:10 2025 GMT");
-    }
+#[test]
+fn test_fingerprint_sha256() {
+    let adapter = CertificateAdapter::new();
 
-    #[test]
-    fn test_extract_validity_dates_old_format() {
-        let adapter = CertificateAdapter::new();
-        let text = r#"Not Before: Sep 28 15:13:11 2025 GMT
-Not After : Dec 27 15:13:10 2025 GMT"#;
+    let cert = adapter.parse_der_certificate(&der(RSA_CERT)).unwrap();
 
-        let (not_before, not_after) = adapter.extract_validity_dates(text);
+    // openssl x509 -noout -fingerprint -sha256
+    assert_eq!(
+        cert.fingerprint_sha256,
+        "CA:C0:55:9D:E3:A8:4E:0F:E4:05:29:FB:6F:1D:2F:C0:FA:BD:C0:D5:68:9E:79:D1:32:9B:B9:D8:8D:06:29:1F"
+    );
+}
 
-        assert_eq!(not_before, "Sep 28 15:13:11 2025 GMT");
-        assert_eq!(not_after, "Dec 27 15:13:10 2025 GMT");
-    }
+#[test]
+fn test_tlsa_association() {
+    let adapter = CertificateAdapter::new();
+    let der = der(RSA_CERT);
+
+    // openssl x509 -pubkey -noout | openssl pkey -pubin -outform der | sha256sum
+    assert_eq!(
+        adapter.tlsa_association(&der, 1, 1).as_deref(),
+        Some("1fe7183dbc1be8f6eb3de1610418c3e70b9e4180c133491f3a24174d1afbde3f")
+    );
+    assert_eq!(
+        adapter.tlsa_association(&der, 0, 1).as_deref(),
+        Some("cac0559de3a84e0fe40529fb6f1d2fc0fabdc0d5689e79d1329bb9d88d06291f")
+    );
+    assert!(adapter
+        .tlsa_association(&der, 0, 2)
+        .unwrap()
+        .starts_with("81b7669bb4bcbc7f4cf5a48bf4eba391bde95f3c"));
+    assert_eq!(
+        adapter.tlsa_association(&der, 0, 0).map(|hex| hex.len()),
+        Some(der.len() * 2)
+    );
+    assert_eq!(adapter.tlsa_association(&der, 2, 1), None);
+    assert_eq!(adapter.tlsa_association(&der, 1, 3), None);
+}
 
-    #[test]
-    fn test_extract_validity_dates_missing() {
-        let adapter = CertificateAdapter::new();
-        let text = "Some text without dates";
+fn at(secs: u64) -> UnixTime {
+    UnixTime::since_unix_epoch(Duration::from_secs(secs))
+}
 
-        let (not_before, not_after) = adapter.extract_validity_dates(text);
+// 2026-10-20, inside the test leaf's validity period
+const DURING_VALIDITY: u64 = 1792454400;
 
-        assert_eq!(not_before, "");
-        assert_eq!(not_after, "");
-    }
+#[test]
+fn test_validate_chain_trusted() {
+    let adapter = CertificateAdapter::new();
+    let root = CertificateDer::from(der(TEST_ROOT));
+    let anchors = vec![webpki::anchor_from_trusted_cert(&root).unwrap()];
+    let chain = vec![CertificateDer::from(der(TEST_LEAF)), root.clone()];
 
-    #[test]
-    fn test_parse_subject_fields_wildcard_cert() {
-        let adapter = CertificateAdapter::new();
-        let subject_str = "CN=*.example.com, O=Example Inc";
+    let (errors, anchor) =
+        adapter.validate_chain("www.example.com", &chain, &anchors, at(DURING_VALIDITY));
 
-        let subject = adapter.parse_subject_fields(subject_str);
+    assert!(errors.is_empty(), "unexpected errors: {:?}", errors);
+    assert_eq!(anchor, Some("Example Test Root".to_string()));
+}
 
-        assert_eq!(subject.common_name, Some("*.example.com".to_string()));
-        assert_eq!(subject.organization, Some("Example Inc".to_string()));
-    }
+#[test]
+fn test_validate_chain_untrusted_and_mismatched() {
+    let adapter = CertificateAdapter::new();
+    let chain = vec![
+        CertificateDer::from(der(TEST_LEAF)),
+        CertificateDer::from(der(TEST_ROOT)),
+    ];
+
+    let (errors, anchor) = adapter.validate_chain(
+        "example.org",
+        &chain,
+        webpki_roots::TLS_SERVER_ROOTS,
+        at(DURING_VALIDITY),
+    );
+
+    assert_eq!(anchor, None);
+    assert_eq!(errors.len(), 2);
+    assert!(errors[0].starts_with("Hostname mismatch: example.org"));
+    assert!(errors[0].contains("(SANs: "));
+    assert!(errors[1].starts_with("Untrusted root"));
+}
 
-    #[test]
-    fn test_parse_subject_with_complex_organization_name() {
-        let adapter = CertificateAdapter::new();
-        let subject_str = "CN=example.com, O=Example, Inc., OU=Web Services";
+#[test]
+fn test_validate_chain_expired() {
+    let adapter = CertificateAdapter::new();
+    let root = CertificateDer::from(der(TEST_ROOT));
+    let anchors = vec![webpki::anchor_from_trusted_cert(&root).unwrap()];
+    let chain = vec![CertificateDer::from(der(TEST_LEAF)), root.clone()];
+
+    // 2027-01-01, after the leaf expired
+    let (errors, anchor) =
+        adapter.validate_chain("www.example.com", &chain, &anchors, at(1798761600));
+
+    assert_eq!(anchor, None);
+    assert_eq!(
+        errors,
+        vec!["Certificate #1 (www.example.com) expired on Nov 15 08:53:36 2026 GMT"]
+    );
+}
 
-        let subject = adapter.parse_subject_fields(subject_str);
+#[test]
+fn test_validate_chain_out_of_order() {
+    let adapter = CertificateAdapter::new();
+    let root = CertificateDer::from(der(TEST_ROOT));
+    let anchors = vec![webpki::anchor_from_trusted_cert(&root).unwrap()];
+    let chain = vec![root.clone(), CertificateDer::from(der(TEST_LEAF))];
 
-        assert_eq!(subject.common_name, Some("example.com".to_string()));
-        // Note: This may have issues with embedded commas, but tests the current implementation
-        assert!(subject.organization.is_some());
-    }
+    let (errors, _) =
+        adapter.validate_chain("www.example.com", &chain, &anchors, at(DURING_VALIDITY));
+
+    assert!(errors.iter().any(|e| e
+        == "Chain out of order: Certificate #2 (www.example.com) did not issue Certificate #1 (Example Test Root)"));
+}
+
+// Scripted plaintext server: sends the greeting, then one reply per line received
+async fn mail_server(greeting: &'static str, replies: Vec<&'static str>) -> TcpStream {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+
+    tokio::spawn(async move {
+        let (socket, _) = listener.accept().await.unwrap();
+        let (read, mut write) = socket.into_split();
+        let mut lines = BufReader::new(read).lines();
+        write.write_all(greeting.as_bytes()).await.unwrap();
+        for reply in replies {
+            if lines.next_line().await.unwrap().is_none() {
+                return;
+            }
+            write.write_all(reply.as_bytes()).await.unwrap();
+        }
+    });
+
+    TcpStream::connect(addr).await.unwrap()
+}
+
+#[tokio::test]
+async fn test_negotiate_starttls_smtp() {
+    let adapter = RustlsTls;
+    let mut stream = mail_server(
+        "220 mx.example.com ESMTP\r\n",
+        vec![
+            "250-mx.example.com\r\n250-PIPELINING\r\n250 STARTTLS\r\n",
+            "220 2.0.0 Ready to start TLS\r\n",
+        ],
+    )
+    .await;
+
+    let transcript = adapter
+        .negotiate_starttls(&mut stream, StartTls::Smtp)
+        .await
+        .unwrap();
+
+    assert!(transcript.contains("> EHLO d-dns-debugger"));
+    assert!(transcript.contains("< 250 STARTTLS"));
+    assert!(transcript.ends_with("< 220 2.0.0 Ready to start TLS\n"));
+}
+
+#[tokio::test]
+async fn test_negotiate_starttls_smtp_not_advertised() {
+    let adapter = RustlsTls;
+    let mut stream = mail_server(
+        "220 mx.example.com ESMTP\r\n",
+        vec!["250-mx.example.com\r\n250 SIZE 10240000\r\n"],
+    )
+    .await;
+
+    let result = adapter
+        .negotiate_starttls(&mut stream, StartTls::Smtp)
+        .await;
+
+    assert_eq!(
+        result,
+        Err("Server does not advertise STARTTLS".to_string())
+    );
+}
 
-    #[test]
-    fn test_certificate_subject_default() {
-        let subject = CertificateSubject {
-            common_name: None,
-            organization: None,
-            organizational_unit: None,
-            locality: None,
-            state: None,
-            country: None,
-        };
-
-        assert_eq!(subject.common_name, None);
-        assert_eq!(subject.organization, None);
-        assert_eq!(subject.organizational_unit, None);
-        assert_eq!(subject.locality, None);
-        assert_eq!(subject.state, None);
-        assert_eq!(subject.country, None);
+#[tokio::test]
+async fn test_negotiate_starttls_imap() {
+    let adapter = RustlsTls;
+    let mut stream = mail_server(
+        "* OK IMAP4rev1 ready\r\n",
+        vec!["* CAPABILITY IMAP4rev1 STARTTLS\r\na001 OK Begin TLS negotiation\r\n"],
+    )
+    .await;
+
+    let transcript = adapter
+        .negotiate_starttls(&mut stream, StartTls::Imap)
+        .await
+        .unwrap();
+
+    assert!(transcript.contains("< a001 OK Begin TLS negotiation"));
+}
+
+#[tokio::test]
+async fn test_negotiate_starttls_pop3_refused() {
+    let adapter = RustlsTls;
+    let mut stream = mail_server("+OK POP3 ready\r\n", vec!["-ERR STLS not supported\r\n"]).await;
+
+    let result = adapter
+        .negotiate_starttls(&mut stream, StartTls::Pop3)
+        .await;
+
+    assert!(result.unwrap_err().contains("-ERR STLS not supported"));
+}
+
+#[test]
+fn test_compare_ip_certificates() {
+    let adapter = CertificateAdapter::new();
+    let primary = adapter.parse_der_certificate(&der(SAN_CERT)).unwrap();
+    let other = adapter.parse_der_certificate(&der(EC_CERT)).unwrap();
+
+    let (per_ip, warnings) = adapter.compare_ip_certificates(
+        &primary,
+        vec![
+            ("192.0.2.1".to_string(), Ok(primary.clone())),
+            ("192.0.2.2".to_string(), Ok(other.clone())),
+            (
+                "2001:db8::1".to_string(),
+                Err("connection refused".to_string()),
+            ),
+        ],
+    );
+
+    assert_eq!(per_ip.len(), 3);
+    assert!(per_ip[0].matches_primary);
+    assert!(!per_ip[1].matches_primary);
+    assert_eq!(per_ip[1].serial_number, Some(other.serial_number.clone()));
+    assert_eq!(per_ip[2].error, Some("connection refused".to_string()));
+
+    assert_eq!(warnings.len(), 2);
+    assert!(warnings[0].starts_with("192.0.2.2 serves a different certificate (serial"));
+    assert_eq!(
+        warnings[1],
+        "TLS handshake with 2001:db8::1 failed: connection refused"
+    );
+}
+
+#[test]
+fn test_expiry_warnings_thresholds() {
+    let adapter = CertificateAdapter::new();
+    // Test leaf expires Nov 15 2026 08:53:36 UTC
+    let leaf = adapter.parse_der_certificate(&der(TEST_LEAF)).unwrap();
+    let at = |date: &str| {
+        chrono::DateTime::parse_from_rfc3339(date)
+            .unwrap()
+            .with_timezone(&chrono::Utc)
+    };
+
+    let mut certificates = vec![leaf.clone()];
+    let warnings =
+        adapter.expiry_warnings(&mut certificates, at("2026-10-20T00:00:00Z"), &[30, 14, 7]);
+    assert_eq!(certificates[0].days_until_expiry, Some(26));
+    assert_eq!(
+        warnings,
+        vec!["Certificate #1 (www.example.com) expires in 26 days on Nov 15 08:53:36 2026 GMT (within 30 days)"]
+    );
+
+    let mut certificates = vec![leaf.clone()];
+    let warnings =
+        adapter.expiry_warnings(&mut certificates, at("2026-11-10T00:00:00Z"), &[30, 14, 7]);
+    assert!(warnings[0].contains("expires in 5 days"));
+    assert!(warnings[0].ends_with("(within 7 days)"));
+
+    // Plenty of time left, and expired certificates are left to validation
+    let mut certificates = vec![leaf.clone()];
+    assert!(adapter
+        .expiry_warnings(&mut certificates, at("2026-10-01T00:00:00Z"), &[30, 14, 7])
+        .is_empty());
+    let mut certificates = vec![leaf];
+    assert!(adapter
+        .expiry_warnings(&mut certificates, at("2027-01-01T00:00:00Z"), &[30, 14, 7])
+        .is_empty());
+    assert!(certificates[0].days_until_expiry.unwrap() < 0);
+}
+
+#[test]
+fn test_parse_usage_extensions() {
+    let adapter = CertificateAdapter::new();
+
+    let leaf = adapter.parse_der_certificate(&der(TEST_LEAF)).unwrap();
+    assert_eq!(leaf.extended_key_usage, vec!["serverAuth"]);
+    assert!(leaf.key_usage.is_empty());
+    assert!(!leaf.is_ca);
+    assert_eq!(leaf.embedded_scts, 0);
+    assert!(leaf.valid_for_server_auth);
+
+    let root = adapter.parse_der_certificate(&der(TEST_ROOT)).unwrap();
+    assert_eq!(root.key_usage, vec!["keyCertSign", "cRLSign"]);
+    assert!(root.is_ca);
+    assert_eq!(root.path_length, None);
+    assert!(!root.valid_for_server_auth);
+}
+
+#[test]
+fn test_usage_warnings() {
+    let adapter = CertificateAdapter::new();
+
+    let leaf = adapter.parse_der_certificate(&der(TEST_LEAF)).unwrap();
+    let warnings = adapter.usage_warnings(&leaf);
+    assert_eq!(warnings.len(), 1);
+    assert!(warnings[0].contains("no embedded SCTs"));
+
+    let root = adapter.parse_der_certificate(&der(TEST_ROOT)).unwrap();
+    let warnings = adapter.usage_warnings(&root);
+    assert!(warnings[0].contains("is a CA certificate"));
+    assert_eq!(
+        warnings[1],
+        "Leaf certificate is not valid for TLS server authentication (KU: keyCertSign, cRLSign; EKU: none)"
+    );
+}
+
+// Precertificate + certificate pair from Let's Encrypt, plus a one-off issuance
+const CRT_SH_JSON: &str = r#"[
+    {"issuer_ca_id":295815,"issuer_name":"C=US, O=Let's Encrypt, CN=R11","common_name":"example.com","name_value":"example.com\nwww.example.com","id":1002,"entry_timestamp":"2026-09-01T10:00:05.5","not_before":"2026-09-01T09:00:00","not_after":"2026-11-30T09:00:00","serial_number":"04aa"},
+    {"issuer_ca_id":295815,"issuer_name":"C=US, O=Let's Encrypt, CN=R11","common_name":"example.com","name_value":"www.example.com\nexample.com","id":1001,"entry_timestamp":"2026-09-01T10:00:00.1","not_before":"2026-09-01T09:00:00","not_after":"2026-11-30T09:00:00","serial_number":"04AA"},
+    {"issuer_ca_id":295815,"issuer_name":"C=US, O=Let's Encrypt, CN=R11","common_name":"example.com","name_value":"example.com","id":900,"entry_timestamp":"2026-06-01T10:00:00","not_before":"2026-06-01T09:00:00","not_after":"2026-08-30T09:00:00","serial_number":"03bb"},
+    {"issuer_ca_id":1,"issuer_name":"C=XX, O=Other CA, CN=Other","common_name":"mail.example.com","name_value":"MAIL.example.com","id":950,"entry_timestamp":"2026-07-01T00:00:00","not_before":"2026-07-01T00:00:00","not_after":"2027-07-01T00:00:00","serial_number":"0c"}
+]"#;
+
+#[test]
+fn test_parse_ct_entries() {
+    let adapter = CertificateAdapter::new();
+    let now = chrono::DateTime::parse_from_rfc3339("2026-10-16T00:00:00Z")
+        .unwrap()
+        .with_timezone(&chrono::Utc);
+
+    let entries = adapter.parse_ct_entries(CRT_SH_JSON, now).unwrap();
+    assert_eq!(entries.len(), 3);
+
+    // Precertificate and certificate merged, newest first
+    assert_eq!(entries[0].id, 1001);
+    assert_eq!(entries[0].names, vec!["example.com", "www.example.com"]);
+    assert_eq!(entries[0].logged_at, "2026-09-01T10:00:00.1");
+    assert!(!entries[0].expired);
+
+    assert_eq!(entries[1].names, vec!["mail.example.com"]);
+    assert!(entries[2].expired);
+
+    assert!(adapter.parse_ct_entries("<html>", now).is_err());
+}
+
+#[test]
+fn test_ct_warnings() {
+    let adapter = CertificateAdapter::new();
+    let now = chrono::DateTime::parse_from_rfc3339("2026-10-16T00:00:00Z")
+        .unwrap()
+        .with_timezone(&chrono::Utc);
+
+    let entries = adapter.parse_ct_entries(CRT_SH_JSON, now).unwrap();
+    let issuers = adapter.count_ct_issuers(&entries);
+    assert_eq!(issuers[0].certificates, 2);
+    assert_eq!(issuers[1].name, "C=XX, O=Other CA, CN=Other");
+
+    let warnings = adapter.ct_warnings(&entries, &issuers);
+    assert_eq!(warnings.len(), 1);
+    assert!(warnings[0].contains("mail.example.com was issued by C=XX, O=Other CA"));
+
+    // A single CA is never "unexpected"
+    assert!(adapter.ct_warnings(&entries[..1], &issuers[..1]).is_empty());
+}
+
+// PKCS#8 key for ED25519_CERT
+const ED25519_KEY: &str = "MC4CAQAwBQYDK2VwBCIEICbdQgrCOUgSeW7y8EOmEJheRjKmgg71JdK35xDaEeDR";
+
+// TLS server on loopback that answers handshakes with ED25519_CERT
+async fn spawn_tls_server(alpn: &[&str]) -> u16 {
+    use base64::Engine;
+    use rustls::pki_types::{PrivateKeyDer, PrivatePkcs8KeyDer};
+    use std::sync::Arc;
+
+    let key = base64::engine::general_purpose::STANDARD
+        .decode(ED25519_KEY)
+        .unwrap();
+    let mut config = rustls::ServerConfig::builder_with_provider(Arc::new(
+        rustls::crypto::ring::default_provider(),
+    ))
+    .with_safe_default_protocol_versions()
+    .unwrap()
+    .with_no_client_auth()
+    .with_single_cert(
+        vec![CertificateDer::from(der(ED25519_CERT))],
+        PrivateKeyDer::Pkcs8(PrivatePkcs8KeyDer::from(key)),
+    )
+    .unwrap();
+    config.alpn_protocols = alpn.iter().map(|p| p.as_bytes().to_vec()).collect();
+
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let port = listener.local_addr().unwrap().port();
+    let acceptor = tokio_rustls::TlsAcceptor::from(Arc::new(config));
+    tokio::spawn(async move {
+        while let Ok((socket, _)) = listener.accept().await {
+            if let Ok(mut tls) = acceptor.accept(socket).await {
+                let _ = tls.shutdown().await;
+            }
+        }
+    });
+    port
+}
+
+// Host, address, port and STARTTLS protocol of a handshake
+type HandshakeRequest = (String, Option<IpAddr>, u16, Option<StartTls>);
+
+// Presents TEST_LEAF and TEST_ROOT for every handshake, recording what was asked for
+struct CannedTls {
+    handshakes: Arc<Mutex<Vec<HandshakeRequest>>>,
+}
+
+impl TlsBackend for CannedTls {
+    fn handshake<'a>(
+        &'a self,
+        host: &'a str,
+        address: Option<IpAddr>,
+        port: u16,
+        starttls: Option<StartTls>,
+    ) -> BackendFuture<'a, Handshake> {
+        self.handshakes
+            .lock()
+            .unwrap()
+            .push((host.to_string(), address, port, starttls));
+        Box::pin(async move {
+            Ok(Handshake {
+                chain: vec![
+                    CertificateDer::from(der(TEST_LEAF)),
+                    CertificateDer::from(der(TEST_ROOT)),
+                ],
+                alpn_protocol: Some("h2".to_string()),
+                summary: "Canned handshake".to_string(),
+            })
+        })
     }
 }
+
+#[tokio::test]
+async fn test_get_certificate_info_through_backend() {
+    let tls = CannedTls {
+        handshakes: Arc::new(Mutex::new(Vec::new())),
+    };
+    let handshakes = tls.handshakes.clone();
+    let adapter = CertificateAdapter::new().with_backends(Backends::default().with_tls(tls));
+
+    let info = adapter
+        .get_certificate_info("localhost", 465, Some(StartTls::Smtp))
+        .await
+        .unwrap();
+    assert!(info.supports_http2);
+    let chain = &info.certificate_chain;
+    assert_eq!(chain.certificates.len(), 2);
+    assert_eq!(
+        chain.certificates[0].subject.common_name.as_deref(),
+        Some("www.example.com")
+    );
+    assert!(!chain.is_valid);
+    assert!(chain.validation_errors[0].starts_with("Hostname mismatch: localhost"));
+    assert!(!info.warnings.iter().any(|w| w.contains("localhost")));
+    assert!(info.raw_output.unwrap().starts_with("Canned handshake\n"));
+
+    // The first handshake is to the host itself; any after it, to each of its addresses
+    let handshakes = handshakes.lock().unwrap();
+    assert_eq!(
+        handshakes[0],
+        ("localhost".to_string(), None, 465, Some(StartTls::Smtp))
+    );
+    assert!(handshakes[1..].iter().all(|h| h.1.is_some()));
+}
+
+#[tokio::test]
+async fn test_alpn_negotiates_h2() {
+    let port = spawn_tls_server(&["h2", "http/1.1"]).await;
+    let adapter = RustlsTls;
+
+    let handshake = adapter
+        .fetch_peer_certificates("localhost", Some("127.0.0.1".parse().unwrap()), port, None)
+        .await
+        .unwrap();
+    assert_eq!(handshake.alpn_protocol.as_deref(), Some("h2"));
+    assert_eq!(handshake.chain.len(), 1);
+    assert!(handshake.summary.contains("ALPN: h2"));
+}
+
+#[tokio::test]
+async fn test_alpn_not_selected() {
+    let port = spawn_tls_server(&[]).await;
+    let adapter = RustlsTls;
+
+    let handshake = adapter
+        .fetch_peer_certificates("localhost", Some("127.0.0.1".parse().unwrap()), port, None)
+        .await
+        .unwrap();
+    assert_eq!(handshake.alpn_protocol, None);
+    assert!(handshake.summary.contains("ALPN: none"));
+}
+
+// Self-signed ecdsa-with-SHA1 certificate valid for 825 days
+const WEAK_CERT: &str = r"-----BEGIN CERTIFICATE-----
+MIIBjjCCATSgAwIBAgIUOlNYrbUXUWxwiohfBMj6d0MGvocwCQYHKoZIzj0EATAd
+MRswGQYDVQQDDBJsZWdhY3kuZXhhbXBsZS5jb20wHhcNMjYxMDE2MDkwNjE0WhcN
+MjkwMTE4MDkwNjE0WjAdMRswGQYDVQQDDBJsZWdhY3kuZXhhbXBsZS5jb20wWTAT
+BgcqhkjOPQIBBggqhkjOPQMBBwNCAASuw3CQT7MfceOMnQ1/RQbW1+jXkQ8JgOfE
+wrVgcXenA+cT08KuWx0gyCIALjTRLAlERuQMgZ9RvhlkpTbvPAjQo1MwUTAdBgNV
+HQ4EFgQU7Xu0w7XPjaxRzkJpwIeQd8UpO8owHwYDVR0jBBgwFoAU7Xu0w7XPjaxR
+zkJpwIeQd8UpO8owDwYDVR0TAQH/BAUwAwEB/zAJBgcqhkjOPQQBA0kAMEYCIQDd
+a+nZ4J+szc3I2tq+CaSxAYTeKibSdGZCVB5tvZgCcAIhAJXvmGTlpAXXpdP+AfGu
+ftv9DyrD/dGdleh0az26e267
+-----END CERTIFICATE-----";
+
+#[test]
+fn test_classify_chain() {
+    let adapter = CertificateAdapter::new();
+
+    let mut chain = vec![
+        adapter.parse_der_certificate(&der(TEST_LEAF)).unwrap(),
+        adapter.parse_der_certificate(&der(TEST_ROOT)).unwrap(),
+    ];
+    let warnings = adapter.classify_chain(&mut chain);
+    assert!(warnings.is_empty());
+    assert_eq!(chain[0].role, CertificateRole::Leaf);
+    assert!(!chain[0].self_signed);
+    assert_eq!(chain[1].role, CertificateRole::Root);
+    assert!(chain[1].self_signed);
+}
+
+#[test]
+fn test_classify_chain_weak_self_signed_leaf() {
+    let adapter = CertificateAdapter::new();
+
+    let mut chain = vec![adapter.parse_der_certificate(&der(WEAK_CERT)).unwrap()];
+    let warnings = adapter.classify_chain(&mut chain);
+    let kinds: Vec<CertificateWarningKind> = warnings.iter().map(|w| w.kind).collect();
+    assert_eq!(
+        kinds,
+        vec![
+            CertificateWarningKind::SelfSignedLeaf,
+            CertificateWarningKind::WeakSignature,
+            CertificateWarningKind::LongValidity,
+        ]
+    );
+    assert_eq!(
+        warnings[1].message,
+        "Certificate #1 (legacy.example.com) uses a weak signature algorithm: ecdsa-with-SHA1"
+    );
+    assert!(warnings[2].message.contains("valid for 825 days"));
+}
+
+#[tokio::test]
+async fn test_scan_tls_ports() {
+    let tls_port = spawn_tls_server(&[]).await;
+    let closed_port = {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        listener.local_addr().unwrap().port()
+    };
+    let adapter = CertificateAdapter::new();
+
+    let scan = adapter
+        .scan_tls_ports("127.0.0.1", &[tls_port, closed_port])
+        .await;
+    assert_eq!(scan.results.len(), 2);
+
+    let open = &scan.results[0];
+    assert_eq!(open.port, tls_port);
+    assert!(open.reachable && open.tls_ok);
+    assert_eq!(open.common_name.as_deref(), Some("ed25519.example.com"));
+    assert!(open.not_after.is_some());
+
+    let closed = &scan.results[1];
+    assert!(!closed.reachable && !closed.tls_ok);
+    assert!(closed
+        .error
+        .as_deref()
+        .unwrap()
+        .starts_with("Connection failed"));
+}
+
+#[test]
+fn test_starttls_for_port() {
+    assert_eq!(StartTls::for_port(587), Some(StartTls::Smtp));
+    assert_eq!(StartTls::for_port(143), Some(StartTls::Imap));
+    assert_eq!(StartTls::for_port(110), Some(StartTls::Pop3));
+    assert_eq!(StartTls::for_port(993), None);
+}
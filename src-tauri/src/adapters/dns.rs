@@ -1,75 +1,357 @@
+use crate::adapters::backend::{Backends, ProcessOutput};
+use crate::adapters::http::HttpAdapter;
 use crate::models::command_log::CommandLog;
-use crate::models::dns::{DnsRecord, DnsResponse, DnskeyRecord, DsRecord, RrsigRecord};
-use std::process::Command;
+use crate::models::dns::{
+    AddressSample, BenchmarkReport, DnsHeader, DnsHosting, DnsProvider, DnsRecord, DnsResponse,
+    DnskeyRecord, DsLink, DsRecord, HygieneIssue, LoadBalancingReport, NameserverAddress,
+    NameserverAnswer, NameserverComparison, NameserverDiversity, NameserverHealth,
+    NameserverNetwork, NameserverStatus, PtrName, RecordEnumeration, RecordTtl, RecordTypeResult,
+    RecursionProbe, ResolverBenchmark, ReverseDnsCheck, RolloverStatus, RrsigRecord, SignatureLink,
+    SoaRecord, ZoneData,
+};
+use crate::models::settings::{self, IpVersion, Settings, TrustAnchor};
+use crate::models::ssh::SshfpRecord;
+use crate::state::command_logs;
+use crate::state::rate_limiter::{self, Target};
+use crate::state::{self, AppState};
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use chrono::{DateTime, Duration, NaiveDateTime, Utc};
+use sha1::Sha1;
+use sha2::{Digest, Sha256, Sha384};
+use std::net::{IpAddr, Ipv6Addr};
+use std::sync::Arc;
 use std::time::Instant;
-use tauri::{AppHandle, Emitter};
+use tauri::{AppHandle, Manager};
+use tokio::sync::Semaphore;
+use tokio::task::JoinSet;
+use tracing::{instrument, Instrument};
+
+// Record types queried by enumerate_records, in display order
+pub const ENUMERATED_RECORD_TYPES: [&str; 20] = [
+    "A", "AAAA", "CNAME", "MX", "TXT", "NS", "SOA", "CAA", "SRV", "TLSA", "HTTPS", "SVCB", "DS",
+    "DNSKEY", "NAPTR", "SSHFP", "LOC", "HINFO", "DNAME", "PTR",
+];
+
+// Returned by every query when none of dig, kdig, drill or host is installed
+pub const NO_DNS_TOOL: &str =
+    "No DNS query tool found: install dig (BIND tools), or else kdig, drill or host";
+
+// Resolvers compared by benchmark_resolvers when the caller doesn't pick any
+pub const BENCHMARK_RESOLVERS: [&str; 4] = ["system", "1.1.1.1", "8.8.8.8", "9.9.9.9"];
+
+// Popular domains likely to be warm in public resolver caches
+pub const BENCHMARK_DOMAINS: [&str; 5] = [
+    "google.com",
+    "cloudflare.com",
+    "wikipedia.org",
+    "amazon.com",
+    "github.com",
+];
+
+// Record types read from the authoritative server by the hygiene check
+pub const HYGIENE_RECORD_TYPES: [&str; 10] = [
+    "CNAME", "A", "AAAA", "MX", "TXT", "NS", "SOA", "CAA", "SRV", "HTTPS",
+];
+
+// Pause between rounds of detect_load_balancing, so answers get a chance to change
+const LOAD_BALANCING_INTERVAL: std::time::Duration = std::time::Duration::from_secs(2);
+
+// TTLs check_ttls warns about, in seconds. Address records are the ones a
+// migration or failover changes; resolvers cap everything at about a week
+// (RFC 8767); RFC 1912 suggests an SOA expire of 2-4 weeks.
+const MIN_RECORD_TTL: u32 = 60;
+const MAX_ADDRESS_TTL: u32 = 86_400;
+const MAX_CACHED_TTL: u32 = 604_800;
+const MIN_SOA_EXPIRE: u32 = 604_800;
+const MIN_NEGATIVE_TTL: u32 = 60;
+const MAX_NEGATIVE_TTL: u32 = 86_400;
+
+// Asked of a zone's nameservers to see whether they recurse; the first one
+// outside the zone is used
+pub const OPEN_RESOLVER_PROBES: [&str; 2] = ["example.com", "example.net"];
+
+// How a DNS host's nameservers are named. A pattern with a dot is a domain the
+// nameserver hostname ends in; one without is the start of one of its labels, for
+// hosts spreading nameservers over several TLDs (ns-1.awsdns-01.org).
+struct DnsProviderPattern {
+    name: &'static str,
+    patterns: &'static [&'static str],
+}
+
+const DNS_PROVIDERS: [DnsProviderPattern; 28] = [
+    DnsProviderPattern {
+        name: "Amazon Route 53",
+        patterns: &["awsdns-"],
+    },
+    DnsProviderPattern {
+        name: "Cloudflare",
+        patterns: &["ns.cloudflare.com"],
+    },
+    DnsProviderPattern {
+        name: "Google Cloud DNS",
+        patterns: &["googledomains.com"],
+    },
+    DnsProviderPattern {
+        name: "Azure DNS",
+        patterns: &["azure-dns"],
+    },
+    DnsProviderPattern {
+        name: "NS1",
+        patterns: &["nsone.net"],
+    },
+    DnsProviderPattern {
+        name: "Akamai Edge DNS",
+        patterns: &["akam.net"],
+    },
+    DnsProviderPattern {
+        name: "UltraDNS",
+        patterns: &["ultradns"],
+    },
+    DnsProviderPattern {
+        name: "Oracle Dyn",
+        patterns: &["dynect.net"],
+    },
+    DnsProviderPattern {
+        name: "Constellix",
+        patterns: &["constellix.com", "constellix.net"],
+    },
+    DnsProviderPattern {
+        name: "GoDaddy",
+        patterns: &["domaincontrol.com"],
+    },
+    DnsProviderPattern {
+        name: "Namecheap",
+        patterns: &["registrar-servers.com"],
+    },
+    DnsProviderPattern {
+        name: "Gandi",
+        patterns: &["gandi.net"],
+    },
+    DnsProviderPattern {
+        name: "OVHcloud",
+        patterns: &["ovh.net", "ovh.ca"],
+    },
+    DnsProviderPattern {
+        name: "IONOS",
+        patterns: &["ui-dns"],
+    },
+    DnsProviderPattern {
+        name: "Hetzner",
+        patterns: &["ns.hetzner.com", "ns.hetzner.de", "your-server.de"],
+    },
+    DnsProviderPattern {
+        name: "DigitalOcean",
+        patterns: &["digitalocean.com"],
+    },
+    DnsProviderPattern {
+        name: "Linode",
+        patterns: &["linode.com"],
+    },
+    DnsProviderPattern {
+        name: "Vultr",
+        patterns: &["vultr.com"],
+    },
+    DnsProviderPattern {
+        name: "Vercel",
+        patterns: &["vercel-dns.com"],
+    },
+    DnsProviderPattern {
+        name: "DNSimple",
+        patterns: &["dnsimple.com", "dnsimple-edge.net", "dnsimple-edge.org"],
+    },
+    DnsProviderPattern {
+        name: "Hurricane Electric",
+        patterns: &["he.net"],
+    },
+    DnsProviderPattern {
+        name: "easyDNS",
+        patterns: &["easydns"],
+    },
+    DnsProviderPattern {
+        name: "deSEC",
+        patterns: &["desec.io", "desec.org"],
+    },
+    DnsProviderPattern {
+        name: "Porkbun",
+        patterns: &["porkbun.com"],
+    },
+    DnsProviderPattern {
+        name: "Network Solutions",
+        patterns: &["worldnic.com"],
+    },
+    DnsProviderPattern {
+        name: "Wix",
+        patterns: &["wixdns.net"],
+    },
+    DnsProviderPattern {
+        name: "Alibaba Cloud DNS",
+        patterns: &["alidns.com", "hichina.com"],
+    },
+    DnsProviderPattern {
+        name: "DNSPod",
+        patterns: &["dnspod.net"],
+    },
+];
+
+// Result of a dig invocation (after any TCP retry)
+#[derive(Clone)]
+struct DigOutput {
+    stdout: String,
+    stderr: String,
+    success: bool,
+    query_time: f64,
+    transport: String,          // "udp" or "tcp"
+    server: Option<String>,     // Address that answered, from the +stats SERVER line
+    ip_version: Option<String>, // "ipv4" or "ipv6", derived from server
+}
 
+#[derive(Clone)]
 pub struct DnsAdapter {
     app_handle: Option<AppHandle>,
+    ip_version: Option<IpVersion>, // Overrides the dns_ip_version setting
+    backends: Backends,
 }
 
 impl DnsAdapter {
     pub fn new() -> Self {
-        DnsAdapter { app_handle: None }
+        Self::from_app_handle(None)
     }
 
     pub fn with_app_handle(app_handle: AppHandle) -> Self {
+        Self::from_app_handle(Some(app_handle))
+    }
+
+    // For other adapters making DNS queries on behalf of their own handle
+    pub fn from_app_handle(app_handle: Option<AppHandle>) -> Self {
         DnsAdapter {
-            app_handle: Some(app_handle),
+            backends: state::backends_for(app_handle.as_ref()),
+            app_handle,
+            ip_version: None,
+        }
+    }
+
+    // Run dig through these instead of the app's backends
+    pub fn with_backends(mut self, backends: Backends) -> Self {
+        self.backends = backends;
+        self
+    }
+
+    // Force queries over IPv4 (dig -4) or IPv6 (dig -6) for this adapter only
+    pub fn with_ip_version(mut self, ip_version: Option<IpVersion>) -> Self {
+        self.ip_version = ip_version;
+        self
+    }
+
+    fn effective_ip_version(&self) -> IpVersion {
+        self.ip_version
+            .unwrap_or_else(|| self.settings().dns_ip_version)
+    }
+
+    // Answers fetched over a forced address family are cached separately
+    fn cache_resolver_key(&self, resolver: &str) -> String {
+        match self.effective_ip_version() {
+            IpVersion::Auto => resolver.to_string(),
+            IpVersion::Ipv4 => format!("{}#ipv4", resolver),
+            IpVersion::Ipv6 => format!("{}#ipv6", resolver),
         }
     }
 
     fn emit_log(&self, log: CommandLog) {
-        if let Some(handle) = &self.app_handle {
-            let _ = handle.emit("command-log", log);
+        command_logs::record(self.app_handle.as_ref(), log);
+    }
+
+    // Look up a previous answer in the managed DNS cache
+    // Adapters created without an AppHandle (tests, CLI) always go to the network
+    fn cached_response(
+        &self,
+        name: &str,
+        record_type: &str,
+        resolver: &str,
+    ) -> Option<DnsResponse> {
+        let state = self.app_handle.as_ref()?.try_state::<AppState>()?;
+        let cached = state
+            .dns_cache
+            .get(name, record_type, &self.cache_resolver_key(resolver))?;
+        // No command runs, so the session's domains wouldn't see it otherwise
+        state.session_domains.record(name, false);
+        Some(cached)
+    }
+
+    fn cache_response(
+        &self,
+        name: &str,
+        record_type: &str,
+        resolver: &str,
+        response: &DnsResponse,
+    ) {
+        if let Some(state) = self
+            .app_handle
+            .as_ref()
+            .and_then(|h| h.try_state::<AppState>())
+        {
+            state.dns_cache.insert(
+                name,
+                record_type,
+                &self.cache_resolver_key(resolver),
+                response,
+            );
         }
     }
 
+    // Query through the resolver chosen in the settings
     pub async fn query(&self, domain: &str, record_type: &str) -> Result<DnsResponse, String> {
-        let start = Instant::now();
+        let resolver = self.settings().dns_resolver;
+        self.query_with_resolver(domain, record_type, &resolver)
+            .await
+    }
 
+    // Query through a specific recursive resolver ("system" uses the OS configuration)
+    #[instrument(skip_all, fields(domain = %domain, record_type = %record_type, resolver = %resolver))]
+    pub async fn query_with_resolver(
+        &self,
+        domain: &str,
+        record_type: &str,
+        resolver: &str,
+    ) -> Result<DnsResponse, String> {
         // Check if dig is available
         if !self.is_dig_available() {
-            return Err("dig command not found. Please install BIND tools.".to_string());
+            return Err(NO_DNS_TOOL.to_string());
         }
 
+        if let Some(cached) = self.cached_response(domain, record_type, resolver) {
+            return Ok(cached);
+        }
+
+        let response = self.resolve(domain, record_type, resolver).await?;
+
+        self.cache_response(domain, record_type, resolver, &response);
+
+        Ok(response)
+    }
+
+    // Uncached recursive lookup
+    // Benchmarks call this directly so cache hits don't skew latency numbers
+    async fn resolve(
+        &self,
+        domain: &str,
+        record_type: &str,
+        resolver: &str,
+    ) -> Result<DnsResponse, String> {
         // Execute dig command
-        let args = vec![
+        let mut args = vec![
             "+noall".to_string(),
+            "+comments".to_string(), // Header with status and flags
             "+answer".to_string(),
-            record_type.to_string(),
-            domain.to_string(),
+            "+stats".to_string(), // Server-reported query time
         ];
 
-        let output = Command::new("dig")
-            .arg("+noall")
-            .arg("+answer")
-            .arg(record_type)
-            .arg(domain)
-            .output()
-            .map_err(|e| format!("Failed to execute dig: {}", e))?;
-
-        let query_time = start.elapsed().as_secs_f64();
-        let exit_code = output.status.code().unwrap_or(-1);
-
-        let stdout = String::from_utf8_lossy(&output.stdout).to_string();
-        let stderr = String::from_utf8_lossy(&output.stderr).to_string();
-
-        // Emit command log
-        let log_output = if !stdout.is_empty() {
-            stdout.clone()
-        } else {
-            stderr.clone()
-        };
+        args.extend(self.resolver_args(resolver));
+        args.push(record_type.to_string());
+        args.push(domain.to_string());
 
-        self.emit_log(CommandLog::new(
-            "dig".to_string(),
-            args,
-            log_output,
-            exit_code,
-            query_time * 1000.0, // Convert to milliseconds
-            Some(domain.to_string()),
-        ));
+        let output = self.run_dig(args, domain).await?;
+        let stdout = output.stdout;
 
         // Don't rely solely on exit code - dig often returns non-zero even on success
         // Check if we got actual DNS data instead
@@ -78,8 +360,13 @@ impl DnsAdapter {
             || stdout.contains("status: NOERROR");
 
         // If we have valid DNS response data, proceed even with non-zero exit code
-        if !output.status.success() && !has_answer {
-            return Err(format!("dig command failed: {}", stderr));
+        if !output.success && !has_answer {
+            let reason = if !output.stderr.is_empty() {
+                output.stderr
+            } else {
+                stdout
+            };
+            return Err(format!("dig command failed: {}", reason.trim()));
         }
 
         let records = self
@@ -88,12 +375,202 @@ impl DnsAdapter {
 
         Ok(DnsResponse {
             records,
-            query_time,
-            resolver: "system".to_string(),
+            query_time: output.query_time,
+            resolver: resolver.to_string(),
+            header: self.parse_dig_header(&stdout),
+            transport: output.transport,
+            server: output.server,
+            ip_version: output.ip_version,
+            from_cache: false,
             raw_output: Some(stdout),
         })
     }
 
+    // Run dig with the given arguments, sharing the output of an identical invocation
+    // that is already running (the dashboard panels often ask for the same records)
+    async fn run_dig(&self, args: Vec<String>, log_domain: &str) -> Result<DigOutput, String> {
+        let key = format!("{:?} {}", self.effective_ip_version(), args.join(" "));
+        // Callers sharing another's run log nothing of their own
+        state::session_domains::record(self.app_handle.as_ref(), log_domain, false);
+        state::in_flight::coalesce(self.app_handle.as_ref(), "dig", &key, || {
+            self.dig_with_retry(args, log_domain)
+        })
+        .await
+    }
+
+    // Run dig, emitting a command log for every invocation
+    // The first attempt goes out over UDP with +ignore so truncation is visible in the
+    // header instead of being retried silently; a truncated (TC) answer is then re-run
+    // over TCP so large TXT/DNSKEY sets don't lose records
+    async fn dig_with_retry(
+        &self,
+        args: Vec<String>,
+        log_domain: &str,
+    ) -> Result<DigOutput, String> {
+        let start = Instant::now();
+        let mut args = self.with_timeout_args(args);
+
+        match self.effective_ip_version() {
+            IpVersion::Auto => {}
+            IpVersion::Ipv4 => args.insert(0, "-4".to_string()),
+            IpVersion::Ipv6 => args.insert(0, "-6".to_string()),
+        }
+
+        // The SERVER stats line shows which address (and family) actually answered
+        if !args.iter().any(|a| a == "+stats") {
+            args.push("+stats".to_string());
+        }
+
+        // DoH has no UDP attempt to make
+        if args.iter().any(|a| a.starts_with("+https")) {
+            let (stdout, stderr, success) = self.execute_dig(args, log_domain).await?;
+            return Ok(self.dig_output(stdout, stderr, success, start, "https"));
+        }
+
+        // Callers that already asked for TCP don't need the UDP attempt
+        if args.iter().any(|a| a == "+tcp" || a == "+vc") {
+            let (stdout, stderr, success) = self.execute_dig(args, log_domain).await?;
+            return Ok(self.dig_output(stdout, stderr, success, start, "tcp"));
+        }
+
+        let mut udp_args = vec!["+ignore".to_string()];
+        udp_args.extend(args.iter().cloned());
+        let (stdout, stderr, success) = self.execute_dig(udp_args, log_domain).await?;
+
+        let truncated = self
+            .parse_dig_header(&stdout)
+            .map(|h| h.truncated)
+            .unwrap_or(false);
+
+        if !truncated {
+            return Ok(self.dig_output(stdout, stderr, success, start, "udp"));
+        }
+
+        let mut tcp_args = vec!["+tcp".to_string()];
+        tcp_args.extend(args);
+        let (stdout, stderr, success) = self.execute_dig(tcp_args, log_domain).await?;
+
+        Ok(self.dig_output(stdout, stderr, success, start, "tcp"))
+    }
+
+    fn dig_output(
+        &self,
+        stdout: String,
+        stderr: String,
+        success: bool,
+        start: Instant,
+        transport: &str,
+    ) -> DigOutput {
+        let server = self.parse_dig_server(&stdout);
+        let ip_version = server
+            .as_deref()
+            .and_then(|address| match address.parse::<IpAddr>() {
+                Ok(IpAddr::V4(_)) => Some("ipv4".to_string()),
+                Ok(IpAddr::V6(_)) => Some("ipv6".to_string()),
+                Err(_) => None,
+            });
+
+        DigOutput {
+            stdout,
+            stderr,
+            success,
+            query_time: start.elapsed().as_secs_f64(),
+            transport: transport.to_string(),
+            server,
+            ip_version,
+        }
+    }
+
+    // Extract the answering address from ";; SERVER: 2606:4700:4700::1111#53(...) (UDP)"
+    pub fn parse_dig_server(&self, output: &str) -> Option<String> {
+        output
+            .lines()
+            .filter_map(|line| line.trim().strip_prefix(";; SERVER:"))
+            .filter_map(|rest| rest.trim().split('#').next())
+            .map(|address| address.trim().to_string())
+            .rfind(|address| !address.is_empty())
+    }
+
+    fn settings(&self) -> Settings {
+        state::settings_for(self.app_handle.as_ref())
+    }
+
+    // Apply the configured per-try timeout and retry count unless the caller set its own
+    fn with_timeout_args(&self, args: Vec<String>) -> Vec<String> {
+        let settings = self.settings();
+        let mut prefixed = Vec::new();
+
+        if !args.iter().any(|a| a.starts_with("+time=")) {
+            prefixed.push(format!("+time={}", settings.dns_timeout_secs));
+        }
+        if !args
+            .iter()
+            .any(|a| a.starts_with("+tries=") || a.starts_with("+retry="))
+        {
+            prefixed.push(format!("+tries={}", settings.dns_retries + 1));
+        }
+
+        prefixed.extend(args);
+        prefixed
+    }
+
+    // Execute a single dig process (or kdig, drill or host in its place) and emit its
+    // command log
+    // Returns (stdout, stderr, exit status success)
+    async fn execute_dig(
+        &self,
+        args: Vec<String>,
+        log_domain: &str,
+    ) -> Result<(String, String, bool), String> {
+        // Nameservers queried directly, TLD servers among them, are rate limited;
+        // the system resolver answers most queries from its own cache
+        let waited = match args.iter().find_map(|a| a.strip_prefix('@')) {
+            Some(server) => {
+                rate_limiter::throttle(self.app_handle.as_ref(), Target::Nameserver, server).await
+            }
+            None => std::time::Duration::ZERO,
+        };
+        let dns = &self.backends.dns;
+        let command_line = dns.command_line(&args);
+        let start = Instant::now();
+        let output = dns
+            .dig(&args)
+            .instrument(tracing::debug_span!(
+                "dns_query",
+                program = dns.program(),
+                args = %command_line.join(" ")
+            ))
+            .await?;
+        let query_time = start.elapsed().as_secs_f64();
+        let success = output.success();
+        let ProcessOutput {
+            stdout,
+            stderr,
+            exit_code,
+        } = output;
+
+        // Emit command log
+        let log_output = if !stdout.is_empty() {
+            stdout.clone()
+        } else {
+            stderr.clone()
+        };
+
+        self.emit_log(
+            CommandLog::new(
+                dns.program().to_string(),
+                command_line,
+                log_output,
+                exit_code,
+                query_time * 1000.0, // Convert to milliseconds
+                Some(log_domain.to_string()),
+            )
+            .with_throttled(waited),
+        );
+
+        Ok((stdout, stderr, success))
+    }
+
     pub async fn query_multiple(
         &self,
         domain: &str,
@@ -106,7 +583,7 @@ impl DnsAdapter {
                 Ok(response) => responses.push(response),
                 Err(e) => {
                     // Log error but continue with other queries
-                    eprintln!("Error querying {} record: {}", record_type, e);
+                    tracing::warn!("Error querying {} record: {}", record_type, e);
                 }
             }
         }
@@ -114,6 +591,625 @@ impl DnsAdapter {
         Ok(responses)
     }
 
+    // An IP address as is, or every A and AAAA address of a hostname
+    pub async fn addresses(&self, host: &str) -> Result<Vec<IpAddr>, String> {
+        if let Ok(address) = host.parse::<IpAddr>() {
+            return Ok(vec![address]);
+        }
+
+        let (a, aaaa) = tokio::join!(self.query(host, "A"), self.query(host, "AAAA"));
+        if let (Err(e), Err(_)) = (&a, &aaaa) {
+            return Err(format!("Failed to resolve {}: {}", host, e));
+        }
+
+        let mut addresses: Vec<IpAddr> = [a, aaaa]
+            .into_iter()
+            .filter_map(Result::ok)
+            .flat_map(|response| response.records)
+            .filter(|r| r.record_type == "A" || r.record_type == "AAAA")
+            .filter_map(|r| r.value.parse().ok())
+            .collect();
+        addresses.dedup();
+        if addresses.is_empty() {
+            return Err(format!("{} has no A or AAAA records", host));
+        }
+        Ok(addresses)
+    }
+
+    // An IP address as is, or the hostname's first A record (AAAA without one),
+    // for probes that go to a single address
+    pub async fn first_address(&self, host: &str) -> Result<IpAddr, String> {
+        if let Ok(address) = host.parse::<IpAddr>() {
+            return Ok(address);
+        }
+        for record_type in ["A", "AAAA"] {
+            let response = self
+                .query(host, record_type)
+                .await
+                .map_err(|e| format!("Failed to resolve {}: {}", host, e))?;
+            let address = response
+                .records
+                .iter()
+                .filter(|r| r.record_type == record_type)
+                .find_map(|r| r.value.parse().ok());
+            if let Some(address) = address {
+                return Ok(address);
+            }
+        }
+        Err(format!("{} has no A or AAAA records", host))
+    }
+
+    // Name PTR records of an address live at: 4.3.2.1.in-addr.arpa for 1.2.3.4,
+    // nibbles in reverse under ip6.arpa for IPv6
+    pub fn reverse_name(&self, address: IpAddr) -> String {
+        match address {
+            IpAddr::V4(v4) => {
+                let [a, b, c, d] = v4.octets();
+                format!("{}.{}.{}.{}.in-addr.arpa", d, c, b, a)
+            }
+            IpAddr::V6(v6) => {
+                let nibbles: Vec<String> = v6
+                    .octets()
+                    .iter()
+                    .rev()
+                    .flat_map(|byte| [byte & 0x0f, byte >> 4])
+                    .map(|nibble| format!("{:x}", nibble))
+                    .collect();
+                format!("{}.ip6.arpa", nibbles.join("."))
+            }
+        }
+    }
+
+    // Reverse DNS: the names in an address's PTR records, without trailing dots
+    pub async fn reverse_lookup(&self, address: IpAddr) -> Result<Vec<String>, String> {
+        let response = self.query(&self.reverse_name(address), "PTR").await?;
+        Ok(response
+            .records
+            .iter()
+            .filter(|r| r.record_type == "PTR")
+            .map(|r| r.value.trim_end_matches('.').to_lowercase())
+            .collect())
+    }
+
+    // Forward-confirmed reverse DNS of each address, concurrently (bounded by
+    // max_concurrency), in the order given: its PTR names, each resolved again to
+    // see whether it points back. hosts and mail are left for the caller.
+    pub async fn forward_confirm_each(&self, addresses: &[IpAddr]) -> Vec<ReverseDnsCheck> {
+        let mut tasks = JoinSet::new();
+        let permits = Arc::new(Semaphore::new(self.settings().max_concurrency.max(1)));
+
+        for (index, address) in addresses.iter().copied().enumerate() {
+            let adapter = self.clone();
+            let permits = permits.clone();
+            tasks.spawn(async move {
+                let _permit = permits.acquire_owned().await;
+                (index, adapter.forward_confirm(address).await)
+            });
+        }
+
+        let mut checks = Vec::new();
+        while let Some(joined) = tasks.join_next().await {
+            if let Ok(entry) = joined {
+                checks.push(entry);
+            }
+        }
+        checks.sort_by_key(|(index, _)| *index);
+        checks.into_iter().map(|(_, check)| check).collect()
+    }
+
+    async fn forward_confirm(&self, address: IpAddr) -> ReverseDnsCheck {
+        let mut check = ReverseDnsCheck {
+            address: address.to_string(),
+            hosts: Vec::new(),
+            mail: false,
+            ptr_names: Vec::new(),
+            confirmed: false,
+            error: None,
+        };
+        let names = match self.reverse_lookup(address).await {
+            Ok(names) => names,
+            Err(e) => {
+                check.error = Some(e);
+                return check;
+            }
+        };
+
+        for name in names {
+            let ptr = match self.addresses(&name).await {
+                Ok(resolved) => PtrName {
+                    confirmed: resolved.contains(&address),
+                    addresses: resolved.iter().map(IpAddr::to_string).collect(),
+                    name,
+                    error: None,
+                },
+                Err(e) => PtrName {
+                    name,
+                    addresses: Vec::new(),
+                    confirmed: false,
+                    error: Some(e),
+                },
+            };
+            check.ptr_names.push(ptr);
+        }
+        check.confirmed = check.ptr_names.iter().any(|ptr| ptr.confirmed);
+        check
+    }
+
+    // Query every record type in ENUMERATED_RECORD_TYPES concurrently
+    // Failures are reported per type instead of failing the whole enumeration
+    #[instrument(skip_all, fields(domain = %domain))]
+    pub async fn enumerate_records(&self, domain: &str) -> RecordEnumeration {
+        let start = Instant::now();
+        let mut tasks = JoinSet::new();
+        let permits = Arc::new(Semaphore::new(self.settings().max_concurrency.max(1)));
+
+        for (index, record_type) in ENUMERATED_RECORD_TYPES.iter().enumerate() {
+            let adapter = self.clone();
+            let domain = domain.to_string();
+            let permits = permits.clone();
+            tasks.spawn(async move {
+                // Bounded by max_concurrency so large enumerations don't flood the resolver
+                let _permit = permits.acquire_owned().await;
+                let result = adapter.query(&domain, record_type).await;
+                (index, *record_type, result)
+            });
+        }
+
+        let mut completed = Vec::new();
+        while let Some(joined) = tasks.join_next().await {
+            if let Ok(entry) = joined {
+                completed.push(entry);
+            }
+        }
+
+        // Tasks finish in arbitrary order - restore the curated order
+        completed.sort_by_key(|(index, _, _)| *index);
+
+        let results: Vec<RecordTypeResult> = completed
+            .into_iter()
+            .map(|(_, record_type, result)| match result {
+                Ok(response) => RecordTypeResult {
+                    record_type: record_type.to_string(),
+                    records: response.records,
+                    query_time: response.query_time,
+                    error: None,
+                },
+                Err(e) => RecordTypeResult {
+                    record_type: record_type.to_string(),
+                    records: Vec::new(),
+                    query_time: 0.0,
+                    error: Some(e),
+                },
+            })
+            .collect();
+
+        let ns_records: Vec<DnsRecord> = results
+            .iter()
+            .filter(|result| result.record_type == "NS")
+            .flat_map(|result| result.records.clone())
+            .collect();
+        let hosting = self.detect_dns_hosting(&self.extract_ns_names(&ns_records, domain));
+
+        RecordEnumeration {
+            domain: domain.to_string(),
+            results,
+            hosting,
+            total_time: start.elapsed().as_secs_f64(),
+        }
+    }
+
+    // The DNS hosts behind a zone's nameservers, from DNS_PROVIDERS, and whether
+    // they span several (None without nameservers)
+    pub fn detect_dns_hosting(&self, nameservers: &[String]) -> Option<DnsHosting> {
+        if nameservers.is_empty() {
+            return None;
+        }
+
+        let mut providers: Vec<DnsProvider> = Vec::new();
+        let mut unrecognized = Vec::new();
+        for nameserver in nameservers {
+            let nameserver = nameserver.trim_end_matches('.').to_lowercase();
+            match self.dns_provider(&nameserver) {
+                Some(name) => match providers.iter_mut().find(|p| p.name == name) {
+                    Some(provider) => provider.nameservers.push(nameserver),
+                    None => providers.push(DnsProvider {
+                        name: name.to_string(),
+                        nameservers: vec![nameserver],
+                    }),
+                },
+                None => unrecognized.push(nameserver),
+            }
+        }
+
+        let http = HttpAdapter::new();
+        let mut operators: Vec<String> = unrecognized
+            .iter()
+            .map(|nameserver| http.registrable_domain(nameserver))
+            .collect();
+        operators.sort();
+        operators.dedup();
+
+        Some(DnsHosting {
+            multi_provider: providers.len() + operators.len() > 1,
+            providers,
+            unrecognized,
+        })
+    }
+
+    fn dns_provider(&self, nameserver: &str) -> Option<&'static str> {
+        DNS_PROVIDERS
+            .iter()
+            .find(|provider| {
+                provider.patterns.iter().any(|pattern| {
+                    if pattern.contains('.') {
+                        nameserver == *pattern || nameserver.ends_with(&format!(".{}", pattern))
+                    } else {
+                        nameserver
+                            .split('.')
+                            .any(|label| label.starts_with(pattern))
+                    }
+                })
+            })
+            .map(|provider| provider.name)
+    }
+
+    // Time A lookups for every domain against each resolver
+    // Resolvers run concurrently (bounded by max_concurrency) but each resolver's own
+    // queries run one at a time so they don't compete with each other
+    pub async fn benchmark_resolvers(
+        &self,
+        resolvers: &[String],
+        domains: &[String],
+        rounds: u32,
+    ) -> BenchmarkReport {
+        let start = Instant::now();
+        let mut tasks = JoinSet::new();
+        let permits = Arc::new(Semaphore::new(self.settings().max_concurrency.max(1)));
+
+        for resolver in resolvers {
+            let adapter = self.clone();
+            let resolver = resolver.clone();
+            let domains = domains.to_vec();
+            let permits = permits.clone();
+            tasks.spawn(async move {
+                let _permit = permits.acquire_owned().await;
+                adapter
+                    .benchmark_resolver(&resolver, &domains, rounds)
+                    .await
+            });
+        }
+
+        let mut results = Vec::new();
+        while let Some(joined) = tasks.join_next().await {
+            if let Ok(result) = joined {
+                results.push(result);
+            }
+        }
+
+        results.sort_by(|a, b| match (a.avg_ms, b.avg_ms) {
+            (Some(a), Some(b)) => a.total_cmp(&b),
+            (Some(_), None) => std::cmp::Ordering::Less,
+            (None, Some(_)) => std::cmp::Ordering::Greater,
+            (None, None) => a.resolver.cmp(&b.resolver),
+        });
+
+        BenchmarkReport {
+            domains: domains.to_vec(),
+            rounds,
+            results,
+            total_time: start.elapsed().as_secs_f64(),
+        }
+    }
+
+    // Ask every resolver for domain's A and AAAA records, rounds times with a pause
+    // in between, calling on_round after each. Uncached, like the benchmark, so each
+    // round sees what the resolver hands out right then.
+    pub async fn sample_addresses(
+        &self,
+        domain: &str,
+        resolvers: &[String],
+        rounds: u32,
+        on_round: impl Fn(u32),
+    ) -> Vec<AddressSample> {
+        let mut samples = Vec::new();
+        let permits = Arc::new(Semaphore::new(self.settings().max_concurrency.max(1)));
+
+        for round in 1..=rounds {
+            if round > 1 {
+                tokio::time::sleep(LOAD_BALANCING_INTERVAL).await;
+            }
+
+            let mut tasks = JoinSet::new();
+            for (index, resolver) in resolvers.iter().enumerate() {
+                for record_type in ["A", "AAAA"] {
+                    let adapter = self.clone();
+                    let domain = domain.to_string();
+                    let resolver = resolver.clone();
+                    let permits = permits.clone();
+                    tasks.spawn(async move {
+                        let _permit = permits.acquire_owned().await;
+                        let result = adapter.resolve(&domain, record_type, &resolver).await;
+                        let sample = adapter.address_sample(&resolver, round, record_type, result);
+                        ((index, record_type), sample)
+                    });
+                }
+            }
+
+            let mut completed = Vec::new();
+            while let Some(joined) = tasks.join_next().await {
+                if let Ok(entry) = joined {
+                    completed.push(entry);
+                }
+            }
+            completed.sort_by_key(|(key, _)| *key);
+            samples.extend(completed.into_iter().map(|(_, sample)| sample));
+            on_round(round);
+        }
+
+        samples
+    }
+
+    fn address_sample(
+        &self,
+        resolver: &str,
+        round: u32,
+        record_type: &str,
+        result: Result<DnsResponse, String>,
+    ) -> AddressSample {
+        let mut sample = AddressSample {
+            resolver: resolver.to_string(),
+            round,
+            record_type: record_type.to_string(),
+            addresses: Vec::new(),
+            ttl: None,
+            error: None,
+        };
+        match result {
+            Ok(response) => {
+                let records: Vec<&DnsRecord> = response
+                    .records
+                    .iter()
+                    .filter(|r| r.record_type == record_type)
+                    .collect();
+                sample.addresses = records.iter().map(|r| r.value.clone()).collect();
+                sample.ttl = records.iter().map(|r| r.ttl).min();
+                let rcode = response.header.map(|h| h.rcode);
+                if matches!(rcode.as_deref(), Some("SERVFAIL") | Some("REFUSED")) {
+                    sample.error = rcode;
+                }
+            }
+            Err(e) => sample.error = Some(e),
+        }
+        sample
+    }
+
+    // Tell intentional load balancing apart from a stable answer:
+    // - Round-robin: one resolver returns the same set in a different order
+    // - Changing: one resolver returns a different set in a later round
+    // - Resolver-dependent: resolvers disagree on the set (GeoDNS, CDN steering)
+    pub fn analyze_load_balancing(
+        &self,
+        domain: &str,
+        resolvers: &[String],
+        rounds: u32,
+        samples: Vec<AddressSample>,
+    ) -> LoadBalancingReport {
+        let answered: Vec<&AddressSample> = samples.iter().filter(|s| s.error.is_none()).collect();
+        let sorted = |sample: &AddressSample| {
+            let mut addresses = sample.addresses.clone();
+            addresses.sort();
+            addresses
+        };
+
+        let mut round_robin = false;
+        let mut changes_over_time = false;
+        let mut resolver_dependent = false;
+        for record_type in ["A", "AAAA"] {
+            let mut sets_by_resolver: Vec<Vec<String>> = Vec::new();
+            for resolver in resolvers {
+                let own: Vec<&&AddressSample> = answered
+                    .iter()
+                    .filter(|s| &s.resolver == resolver && s.record_type == record_type)
+                    .collect();
+                let Some(first) = own.first() else {
+                    continue;
+                };
+                let first_set = sorted(first);
+                for sample in &own[1..] {
+                    if sorted(sample) != first_set {
+                        changes_over_time = true;
+                    } else if sample.addresses != first.addresses {
+                        round_robin = true;
+                    }
+                }
+
+                let mut seen: Vec<String> = own.iter().flat_map(|s| s.addresses.clone()).collect();
+                seen.sort();
+                seen.dedup();
+                sets_by_resolver.push(seen);
+            }
+            sets_by_resolver.sort();
+            sets_by_resolver.dedup();
+            resolver_dependent |= sets_by_resolver.len() > 1;
+        }
+
+        let mut addresses: Vec<String> =
+            answered.iter().flat_map(|s| s.addresses.clone()).collect();
+        addresses.sort();
+        addresses.dedup();
+        let min_ttl = answered.iter().filter_map(|s| s.ttl).min();
+
+        let mut findings = Vec::new();
+        if answered.is_empty() {
+            findings.push(format!("No resolver returned addresses for {}", domain));
+        } else if !round_robin && !changes_over_time && !resolver_dependent {
+            findings.push(format!(
+                "Every resolver returned the same {} each time: {}",
+                if addresses.len() == 1 {
+                    "address"
+                } else {
+                    "addresses"
+                },
+                addresses.join(", ")
+            ));
+        }
+        if round_robin {
+            findings.push(
+                "The same addresses come back in rotating order: DNS round-robin, spreading clients across them"
+                    .to_string(),
+            );
+        }
+        if changes_over_time {
+            findings.push(format!(
+                "A resolver got different addresses from one round to the next{}: answers are picked per query (weighted, latency-based or health-checked load balancing)",
+                min_ttl
+                    .map(|ttl| format!(" (TTL {})", self.describe_seconds(ttl)))
+                    .unwrap_or_default()
+            ));
+        }
+        if resolver_dependent {
+            findings.push(
+                "Resolvers got different addresses: the answer depends on where the query comes from (GeoDNS or CDN steering)"
+                    .to_string(),
+            );
+        }
+        if round_robin || changes_over_time || resolver_dependent {
+            findings.push(
+                "Addresses that keep changing are expected here; they're intentional load balancing, not a problem".to_string(),
+            );
+        }
+
+        LoadBalancingReport {
+            domain: domain.to_string(),
+            resolvers: resolvers.to_vec(),
+            rounds,
+            samples,
+            addresses,
+            round_robin,
+            changes_over_time,
+            resolver_dependent,
+            min_ttl,
+            findings,
+            total_time: 0.0,
+        }
+    }
+
+    async fn benchmark_resolver(
+        &self,
+        resolver: &str,
+        domains: &[String],
+        rounds: u32,
+    ) -> ResolverBenchmark {
+        let mut latencies = Vec::new();
+        let mut failures = 0;
+        let mut last_error = None;
+
+        for _ in 0..rounds {
+            for domain in domains {
+                match self.resolve(domain, "A", resolver).await {
+                    Ok(response) => {
+                        let rcode = response.header.as_ref().map(|h| h.rcode.as_str());
+                        if matches!(rcode, Some("SERVFAIL") | Some("REFUSED")) {
+                            failures += 1;
+                            last_error = Some(format!("{} for {}", rcode.unwrap(), domain));
+                            continue;
+                        }
+
+                        // Prefer dig's own measurement, which excludes process startup
+                        let latency = response
+                            .raw_output
+                            .as_deref()
+                            .and_then(|out| self.parse_stats_query_time(out))
+                            .unwrap_or(response.query_time * 1000.0);
+                        latencies.push(latency);
+                    }
+                    Err(e) => {
+                        failures += 1;
+                        last_error = Some(e);
+                    }
+                }
+            }
+        }
+
+        self.summarize_benchmark(resolver, &latencies, failures, last_error)
+    }
+
+    // Build min/avg/p95 statistics from successful query latencies (milliseconds)
+    pub fn summarize_benchmark(
+        &self,
+        resolver: &str,
+        latencies: &[f64],
+        failures: usize,
+        last_error: Option<String>,
+    ) -> ResolverBenchmark {
+        let queries = latencies.len() + failures;
+
+        let mut sorted = latencies.to_vec();
+        sorted.sort_by(|a, b| a.total_cmp(b));
+
+        let (min_ms, avg_ms, p95_ms) = if sorted.is_empty() {
+            (None, None, None)
+        } else {
+            // Nearest-rank percentile
+            let rank = ((sorted.len() as f64) * 0.95).ceil() as usize;
+            let p95 = sorted[rank.clamp(1, sorted.len()) - 1];
+            let avg = sorted.iter().sum::<f64>() / sorted.len() as f64;
+            (Some(sorted[0]), Some(avg), Some(p95))
+        };
+
+        ResolverBenchmark {
+            resolver: resolver.to_string(),
+            queries,
+            failures,
+            failure_rate: if queries > 0 {
+                failures as f64 / queries as f64
+            } else {
+                0.0
+            },
+            min_ms,
+            avg_ms,
+            p95_ms,
+            last_error,
+        }
+    }
+
+    // Extract ";; Query time: 12 msec" from dig +stats output
+    pub fn parse_stats_query_time(&self, output: &str) -> Option<f64> {
+        output
+            .lines()
+            .filter_map(|line| line.trim().strip_prefix(";; Query time:"))
+            .filter_map(|rest| rest.trim().strip_suffix("msec"))
+            .filter_map(|ms| ms.trim().parse::<f64>().ok())
+            .next_back()
+    }
+
+    // Accept "system", IP addresses, hostnames and DoH URLs
+    pub fn is_valid_resolver(&self, resolver: &str) -> bool {
+        settings::is_resolver(resolver)
+    }
+
+    // dig's arguments for a resolver: none for the system one, @server otherwise, and
+    // +https with the path for a DoH URL (dig 9.18+ and kdig)
+    pub fn resolver_args(&self, resolver: &str) -> Vec<String> {
+        if resolver == "system" {
+            return Vec::new();
+        }
+        let Some(url) = resolver
+            .starts_with("https://")
+            .then(|| url::Url::parse(resolver).ok())
+            .flatten()
+        else {
+            return vec![format!("@{}", resolver)];
+        };
+        let host = url.host_str().unwrap_or_default();
+        let host = host.trim_start_matches('[').trim_end_matches(']');
+        let https = match url.path() {
+            "/" => "+https".to_string(),
+            path => format!("+https={}", path),
+        };
+        vec![format!("@{}", host), https]
+    }
+
     fn parse_dig_output(&self, output: &str, record_type: &str) -> Result<Vec<DnsRecord>, String> {
         let mut records = Vec::new();
         let mut current_record: Option<DnsRecord> = None;
@@ -127,6 +1223,12 @@ impl DnsAdapter {
                 continue;
             }
 
+            // Skip dig meta lines (";; ANSWER SECTION:", ";; Query time: ...")
+            // so they are never appended to the previous record's value
+            if line.starts_with(";;") {
+                continue;
+            }
+
             // Check if this is a comment line (for +multi format)
             if line.starts_with(';') {
                 // Append comment to accumulated value for multi-line records
@@ -181,8 +1283,57 @@ impl DnsAdapter {
         Ok(records)
     }
 
+    // Parse the response header printed by dig with +comments
+    // ";; ->>HEADER<<- opcode: QUERY, status: NXDOMAIN, id: 4242"
+    // ";; flags: qr aa rd ra; QUERY: 1, ANSWER: 0, AUTHORITY: 1, ADDITIONAL: 1"
+    // If dig printed several headers (e.g., after a retry), the last one wins
+    fn parse_dig_header(&self, output: &str) -> Option<DnsHeader> {
+        let mut header: Option<DnsHeader> = None;
+
+        for line in output.lines() {
+            let line = line.trim();
+
+            if let Some(pos) = line.find("status:") {
+                let rcode = line[pos + "status:".len()..]
+                    .split(',')
+                    .next()
+                    .unwrap_or("")
+                    .trim()
+                    .to_string();
+                header = Some(DnsHeader {
+                    rcode,
+                    ..DnsHeader::default()
+                });
+            } else if let (Some(rest), Some(current)) =
+                (line.strip_prefix(";; flags:"), header.as_mut())
+            {
+                let (flags, counts) = rest.split_once(';').unwrap_or((rest, ""));
+                let flags: Vec<&str> = flags.split_whitespace().collect();
+
+                current.authoritative = flags.contains(&"aa");
+                current.truncated = flags.contains(&"tc");
+                current.recursion_available = flags.contains(&"ra");
+                current.authenticated_data = flags.contains(&"ad");
+
+                for count in counts.split(',') {
+                    if let Some((section, value)) = count.split_once(':') {
+                        let value = value.trim().parse::<u32>().unwrap_or(0);
+                        match section.trim() {
+                            "ANSWER" => current.answer_count = value,
+                            "AUTHORITY" => current.authority_count = value,
+                            "ADDITIONAL" => current.additional_count = value,
+                            _ => {}
+                        }
+                    }
+                }
+            }
+        }
+
+        header
+    }
+
     fn is_dig_available(&self) -> bool {
-        Command::new("dig").arg("-v").output().is_ok()
+        self.backends.dns.is_available()
     }
 
     // Get authoritative nameservers for a domain
@@ -191,10 +1342,856 @@ impl DnsAdapter {
         Ok(response.records.iter().map(|r| r.value.clone()).collect())
     }
 
-    // Query DNSKEY records from zone's own authoritative nameservers
-    // DNSKEY records are served by the zone itself, not the parent
+    // Find the parent zone of a domain and one of its authoritative nameservers
+    // For TLDs (single part like "io", "com"), the parent is the root zone
+    // For domains (like "example.com"), the parent is the next zone up ("com")
+    pub async fn get_parent_nameserver(&self, domain: &str) -> Result<(String, String), String> {
+        let parts: Vec<&str> = domain.trim_end_matches('.').split('.').collect();
+
+        if parts.len() == 1 {
+            // TLD: query from root servers (use any root server, e.g., a.root-servers.net)
+            Ok((".".to_string(), "a.root-servers.net".to_string()))
+        } else if parts.len() >= 2 {
+            // Regular domain: query from parent zone's nameservers
+            let parent = parts[1..].join(".");
+            let parent_ns = self.get_nameservers(&parent).await?;
+
+            if parent_ns.is_empty() {
+                return Err("No parent nameservers found".to_string());
+            }
+
+            Ok((parent, parent_ns[0].clone()))
+        } else {
+            Err("Invalid domain for parent lookup".to_string())
+        }
+    }
+
+    // Query a specific nameserver directly with recursion disabled
+    // Answers come back in the ANSWER section when the server is authoritative,
+    // or as a referral in the AUTHORITY section when it is a parent zone server
+    #[instrument(skip_all, fields(domain = %domain, record_type = %record_type, nameserver = %nameserver))]
+    pub async fn query_authoritative(
+        &self,
+        domain: &str,
+        record_type: &str,
+        nameserver: &str,
+    ) -> Result<DnsResponse, String> {
+        if !self.is_dig_available() {
+            return Err(NO_DNS_TOOL.to_string());
+        }
+
+        if let Some(cached) = self.cached_response(domain, record_type, nameserver) {
+            return Ok(cached);
+        }
+
+        let args = vec![
+            "+norec".to_string(),
+            "+noall".to_string(),
+            "+comments".to_string(),
+            "+answer".to_string(),
+            "+authority".to_string(),
+            format!("@{}", nameserver),
+            record_type.to_string(),
+            domain.to_string(),
+        ];
+
+        let output = self.run_dig(args, domain).await?;
+        let stdout = output.stdout;
+
+        // dig prints timeouts to stdout (";; connection timed out") with a non-zero exit code
+        if !output.success && !stdout.lines().any(|l| l.contains(" IN ")) {
+            let reason = if !output.stderr.is_empty() {
+                output.stderr
+            } else {
+                stdout
+            };
+            return Err(format!("dig command failed: {}", reason.trim()));
+        }
+
+        let records = self
+            .parse_dig_output(&stdout, record_type)
+            .unwrap_or_else(|_| Vec::new());
+
+        let response = DnsResponse {
+            records,
+            query_time: output.query_time,
+            resolver: nameserver.to_string(),
+            header: self.parse_dig_header(&stdout),
+            transport: output.transport,
+            server: output.server,
+            ip_version: output.ip_version,
+            from_cache: false,
+            raw_output: Some(stdout),
+        };
+
+        self.cache_response(domain, record_type, nameserver, &response);
+
+        Ok(response)
+    }
+
+    // Extract the NS hostnames delegated for a domain from a set of records
+    // Names are lowercased with the trailing dot removed, sorted and de-duplicated
+    // so that sets returned by different servers can be compared directly
+    pub fn extract_ns_names(&self, records: &[DnsRecord], domain: &str) -> Vec<String> {
+        let zone = domain.trim_end_matches('.').to_lowercase();
+
+        let mut names: Vec<String> = records
+            .iter()
+            .filter(|r| r.record_type == "NS")
+            .filter(|r| r.name.trim_end_matches('.').to_lowercase() == zone)
+            .map(|r| r.value.trim().trim_end_matches('.').to_lowercase())
+            .collect();
+
+        names.sort();
+        names.dedup();
+        names
+    }
+
+    // Find the closest enclosing zone of a name and its NS set
+    // Walks up one label at a time, so "www.example.com" resolves to "example.com"
+    #[instrument(skip_all, fields(domain = %domain))]
+    pub async fn find_zone(&self, domain: &str) -> Result<(String, Vec<String>), String> {
+        let mut candidate = domain.trim_end_matches('.').to_lowercase();
+
+        loop {
+            let response = self.query(&candidate, "NS").await?;
+            let nameservers = self.extract_ns_names(&response.records, &candidate);
+            if !nameservers.is_empty() {
+                return Ok((candidate, nameservers));
+            }
+
+            match candidate.split_once('.') {
+                Some((_, parent)) if !parent.is_empty() => candidate = parent.to_string(),
+                _ => return Err(format!("No nameservers found for {}", domain)),
+            }
+        }
+    }
+
+    // Send the same query to every nameserver concurrently (bounded by max_concurrency)
+    // Results keep the order of the nameservers argument
+    pub async fn query_each_nameserver(
+        &self,
+        domain: &str,
+        record_type: &str,
+        nameservers: &[String],
+    ) -> Vec<NameserverAnswer> {
+        let mut tasks = JoinSet::new();
+        let permits = Arc::new(Semaphore::new(self.settings().max_concurrency.max(1)));
+
+        for (index, nameserver) in nameservers.iter().enumerate() {
+            let adapter = self.clone();
+            let domain = domain.to_string();
+            let record_type = record_type.to_string();
+            let nameserver = nameserver.clone();
+            let permits = permits.clone();
+            tasks.spawn(async move {
+                let _permit = permits.acquire_owned().await;
+                let result = adapter
+                    .query_authoritative(&domain, &record_type, &nameserver)
+                    .await;
+                (index, nameserver, result)
+            });
+        }
+
+        let mut completed = Vec::new();
+        while let Some(joined) = tasks.join_next().await {
+            if let Ok(entry) = joined {
+                completed.push(entry);
+            }
+        }
+        completed.sort_by_key(|(index, _, _)| *index);
+
+        completed
+            .into_iter()
+            .map(|(_, nameserver, result)| match result {
+                Ok(response) => NameserverAnswer {
+                    nameserver,
+                    // Referrals and glue in the AUTHORITY section aren't part of the answer
+                    records: response
+                        .records
+                        .into_iter()
+                        .filter(|r| r.record_type.eq_ignore_ascii_case(record_type))
+                        .collect(),
+                    query_time: response.query_time,
+                    rcode: response.header.as_ref().map(|h| h.rcode.clone()),
+                    authoritative: response
+                        .header
+                        .as_ref()
+                        .map(|h| h.authoritative)
+                        .unwrap_or(false),
+                    matches_majority: false,
+                    error: None,
+                },
+                Err(e) => NameserverAnswer {
+                    nameserver,
+                    records: Vec::new(),
+                    query_time: 0.0,
+                    rcode: None,
+                    authoritative: false,
+                    matches_majority: false,
+                    error: Some(e),
+                },
+            })
+            .collect()
+    }
+
+    // Ask every nameserver to resolve a name outside zone with recursion desired,
+    // concurrently (bounded by max_concurrency), in the order given. Uncached, so
+    // a re-check after the host fixes its configuration is a fresh query.
+    pub async fn probe_recursion(&self, zone: &str, nameservers: &[String]) -> Vec<RecursionProbe> {
+        let zone = zone.trim_end_matches('.').to_lowercase();
+        let probe_name = OPEN_RESOLVER_PROBES
+            .iter()
+            .find(|name| **name != zone && !name.ends_with(&format!(".{}", zone)))
+            .unwrap_or(&OPEN_RESOLVER_PROBES[0])
+            .to_string();
+        let mut tasks = JoinSet::new();
+        let permits = Arc::new(Semaphore::new(self.settings().max_concurrency.max(1)));
+
+        for (index, nameserver) in nameservers.iter().enumerate() {
+            let adapter = self.clone();
+            let nameserver = nameserver.clone();
+            let probe_name = probe_name.clone();
+            let permits = permits.clone();
+            tasks.spawn(async move {
+                let _permit = permits.acquire_owned().await;
+                let result = adapter.resolve(&probe_name, "A", &nameserver).await;
+                (
+                    index,
+                    adapter.classify_recursion(&nameserver, &probe_name, result),
+                )
+            });
+        }
+
+        let mut probes = Vec::new();
+        while let Some(joined) = tasks.join_next().await {
+            if let Ok(entry) = joined {
+                probes.push(entry);
+            }
+        }
+        probes.sort_by_key(|(index, _)| *index);
+        probes.into_iter().map(|(_, probe)| probe).collect()
+    }
+
+    // Open when the server answered the name without being authoritative for it.
+    // A REFUSED or an empty referral is what an authoritative-only server sends.
+    pub fn classify_recursion(
+        &self,
+        nameserver: &str,
+        probe_name: &str,
+        result: Result<DnsResponse, String>,
+    ) -> RecursionProbe {
+        let response = match result {
+            Ok(response) => response,
+            Err(e) => {
+                return RecursionProbe {
+                    nameserver: nameserver.to_string(),
+                    probe_name: probe_name.to_string(),
+                    open: false,
+                    recursion_available: false,
+                    rcode: None,
+                    answers: Vec::new(),
+                    error: Some(e),
+                }
+            }
+        };
+
+        let answers: Vec<String> = response
+            .records
+            .iter()
+            .filter(|r| r.record_type == "A")
+            .map(|r| r.value.clone())
+            .collect();
+        let header = response.header.as_ref();
+        let rcode = header.map(|h| h.rcode.clone());
+        let authoritative = header.is_some_and(|h| h.authoritative);
+
+        RecursionProbe {
+            nameserver: nameserver.to_string(),
+            probe_name: probe_name.to_string(),
+            open: rcode.as_deref() == Some("NOERROR") && !authoritative && !answers.is_empty(),
+            recursion_available: header.is_some_and(|h| h.recursion_available),
+            rcode,
+            answers,
+            error: None,
+        }
+    }
+
+    // Whether a nameserver's answer to a direct SOA query shows it serving the zone.
+    // Anything short of an authoritative NOERROR with the zone's SOA is lame: a
+    // referral, a cached (non-AA) answer, or an NXDOMAIN from the parent's data.
+    pub fn classify_nameserver(&self, zone: &str, answer: NameserverAnswer) -> NameserverHealth {
+        let zone = zone.trim_end_matches('.').to_lowercase();
+        let has_soa = answer
+            .records
+            .iter()
+            .any(|r| r.record_type == "SOA" && r.name.trim_end_matches('.').to_lowercase() == zone);
+
+        let status = match (&answer.error, answer.rcode.as_deref()) {
+            (Some(e), _) => {
+                let e = e.to_lowercase();
+                if e.contains("timed out") || e.contains("timeout") {
+                    NameserverStatus::Timeout
+                } else {
+                    NameserverStatus::Unreachable
+                }
+            }
+            (None, Some("REFUSED")) => NameserverStatus::Refused,
+            (None, Some("SERVFAIL")) => NameserverStatus::ServFail,
+            (None, Some("NOERROR")) if answer.authoritative && has_soa => {
+                NameserverStatus::Authoritative
+            }
+            (None, _) => NameserverStatus::Lame,
+        };
+
+        NameserverHealth {
+            nameserver: answer.nameserver,
+            status,
+            rcode: answer.rcode,
+            query_time: answer.query_time,
+            error: answer.error,
+        }
+    }
+
+    // Diff the answers returned by each nameserver of a zone
+    // Flags differing record sets, TTLs and response codes, non-authoritative
+    // answers, and nameservers that failed to respond
+    pub fn compare_answers(
+        &self,
+        domain: &str,
+        record_type: &str,
+        zone: &str,
+        mut answers: Vec<NameserverAnswer>,
+    ) -> NameserverComparison {
+        let mut differences = Vec::new();
+
+        let signature = |answer: &NameserverAnswer| -> Vec<String> {
+            let mut values: Vec<String> = answer
+                .records
+                .iter()
+                .map(|r| r.value.trim().trim_end_matches('.').to_string())
+                .collect();
+            values.sort();
+            values
+        };
+
+        // Most common answer set among the nameservers that responded
+        let mut counts: Vec<(Vec<String>, usize)> = Vec::new();
+        for answer in answers.iter().filter(|a| a.error.is_none()) {
+            let sig = signature(answer);
+            match counts.iter_mut().find(|(s, _)| *s == sig) {
+                Some((_, count)) => *count += 1,
+                None => counts.push((sig, 1)),
+            }
+        }
+        let majority = counts
+            .iter()
+            .max_by_key(|(_, count)| *count)
+            .map(|(sig, _)| sig.clone());
+
+        for answer in answers.iter_mut() {
+            if let Some(error) = &answer.error {
+                differences.push(format!("{} failed: {}", answer.nameserver, error));
+                continue;
+            }
+
+            let sig = signature(answer);
+            answer.matches_majority = majority.as_ref() == Some(&sig);
+
+            if !answer.matches_majority {
+                differences.push(format!(
+                    "{} returns [{}] while most nameservers return [{}]",
+                    answer.nameserver,
+                    sig.join(", "),
+                    majority.clone().unwrap_or_default().join(", ")
+                ));
+            }
+
+            if answer.rcode.as_deref() == Some("NOERROR") && !answer.authoritative {
+                differences.push(format!(
+                    "{} answered without the authoritative (AA) flag",
+                    answer.nameserver
+                ));
+            }
+        }
+
+        let responded: Vec<&NameserverAnswer> =
+            answers.iter().filter(|a| a.error.is_none()).collect();
+
+        let mut rcodes: Vec<String> = responded
+            .iter()
+            .map(|a| a.rcode.clone().unwrap_or_else(|| "unknown".to_string()))
+            .collect();
+        rcodes.sort();
+        rcodes.dedup();
+        if rcodes.len() > 1 {
+            differences.push(format!(
+                "Response codes differ: {}",
+                responded
+                    .iter()
+                    .map(|a| format!(
+                        "{}={}",
+                        a.nameserver,
+                        a.rcode.as_deref().unwrap_or("unknown")
+                    ))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ));
+        }
+
+        // Authoritative servers hand out the zone file TTL, so any variation means
+        // the servers are loaded with different copies of the zone
+        let ttl_of = |a: &NameserverAnswer| a.records.iter().map(|r| r.ttl).max();
+        let mut ttls: Vec<u32> = responded.iter().filter_map(|a| ttl_of(a)).collect();
+        ttls.sort();
+        ttls.dedup();
+        if ttls.len() > 1 {
+            differences.push(format!(
+                "TTLs differ: {}",
+                responded
+                    .iter()
+                    .filter_map(|a| ttl_of(a).map(|ttl| format!("{}={}", a.nameserver, ttl)))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ));
+        }
+
+        NameserverComparison {
+            domain: domain.to_string(),
+            record_type: record_type.to_string(),
+            zone: zone.to_string(),
+            is_consistent: differences.is_empty(),
+            answers,
+            differences,
+        }
+    }
+
+    // Query several record types from one nameserver concurrently
+    // Results keep the order of record_types
+    pub async fn query_types_authoritative(
+        &self,
+        domain: &str,
+        record_types: &[&str],
+        nameserver: &str,
+    ) -> Vec<(String, Result<DnsResponse, String>)> {
+        let mut tasks = JoinSet::new();
+        let permits = Arc::new(Semaphore::new(self.settings().max_concurrency.max(1)));
+
+        for (index, record_type) in record_types.iter().enumerate() {
+            let adapter = self.clone();
+            let domain = domain.to_string();
+            let record_type = record_type.to_string();
+            let nameserver = nameserver.to_string();
+            let permits = permits.clone();
+            tasks.spawn(async move {
+                let _permit = permits.acquire_owned().await;
+                let result = adapter
+                    .query_authoritative(&domain, &record_type, &nameserver)
+                    .await;
+                (index, record_type, result)
+            });
+        }
+
+        let mut completed = Vec::new();
+        while let Some(joined) = tasks.join_next().await {
+            if let Ok(entry) = joined {
+                completed.push(entry);
+            }
+        }
+        completed.sort_by_key(|(index, _, _)| *index);
+
+        completed
+            .into_iter()
+            .map(|(_, record_type, result)| (record_type, result))
+            .collect()
+    }
+
+    // Join the quoted character-strings of a TXT record value
+    // "\"v=spf1 include:_spf.example.com \" \"~all\"" -> "v=spf1 include:_spf.example.com ~all"
+    pub fn txt_value(&self, value: &str) -> String {
+        let trimmed = value.trim();
+        if !trimmed.starts_with('"') {
+            return trimmed.to_string();
+        }
+
+        trimmed
+            .split('"')
+            .enumerate()
+            .filter(|(i, _)| i % 2 == 1) // Odd segments are inside quotes
+            .map(|(_, part)| part)
+            .collect::<Vec<_>>()
+            .join("")
+    }
+
+    // SPF policies published as TXT records (RFC 7208 allows exactly one)
+    pub fn spf_records(&self, records: &[DnsRecord]) -> Vec<String> {
+        records
+            .iter()
+            .filter(|r| r.record_type == "TXT")
+            .map(|r| self.txt_value(&r.value))
+            .filter(|v| {
+                let lower = v.to_lowercase();
+                lower == "v=spf1" || lower.starts_with("v=spf1 ")
+            })
+            .collect()
+    }
+
+    // Check the records at a name for combinations the DNS (or SPF) forbids:
+    // - CNAME at the zone apex (conflicts with the mandatory SOA and NS records)
+    // - CNAME alongside any other data (RFC 1034 section 3.6.2)
+    // - More than one SPF policy (RFC 7208 section 3.2, evaluation returns permerror)
+    pub fn analyze_hygiene(
+        &self,
+        domain: &str,
+        zone: &str,
+        records: &[DnsRecord],
+    ) -> (Vec<String>, Vec<HygieneIssue>) {
+        let name = domain.trim_end_matches('.').to_lowercase();
+        let is_apex = name == zone.trim_end_matches('.').to_lowercase();
+
+        // Only records owned by this name (not CNAME targets or referral data)
+        let owned: Vec<DnsRecord> = records
+            .iter()
+            .filter(|r| r.name.trim_end_matches('.').to_lowercase() == name)
+            .cloned()
+            .collect();
+
+        let mut record_types: Vec<String> = owned.iter().map(|r| r.record_type.clone()).collect();
+        record_types.sort();
+        record_types.dedup();
+
+        let mut issues = Vec::new();
+        let has_cname = record_types.iter().any(|t| t == "CNAME");
+
+        if has_cname && is_apex {
+            issues.push(HygieneIssue {
+                severity: "error".to_string(),
+                code: "apex_cname".to_string(),
+                message: format!(
+                    "{} is the zone apex and has a CNAME record; apex names must hold SOA and NS records, so use A/AAAA records or a provider ALIAS/ANAME instead",
+                    name
+                ),
+            });
+        }
+
+        // DNSSEC records are allowed next to a CNAME
+        let others: Vec<&String> = record_types
+            .iter()
+            .filter(|t| !matches!(t.as_str(), "CNAME" | "RRSIG" | "NSEC" | "NSEC3"))
+            .collect();
+
+        if has_cname && !others.is_empty() {
+            issues.push(HygieneIssue {
+                severity: "error".to_string(),
+                code: "cname_with_other_data".to_string(),
+                message: format!(
+                    "{} has a CNAME alongside {} records; resolvers will follow the CNAME and ignore the other data",
+                    name,
+                    others
+                        .iter()
+                        .map(|t| t.as_str())
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                ),
+            });
+        }
+
+        let spf = self.spf_records(&owned);
+        if spf.len() > 1 {
+            issues.push(HygieneIssue {
+                severity: "error".to_string(),
+                code: "multiple_spf".to_string(),
+                message: format!(
+                    "{} publishes {} SPF records; receivers treat this as a permanent error, merge them into one",
+                    name,
+                    spf.len()
+                ),
+            });
+        }
+
+        (record_types, issues)
+    }
+
+    // Lowest TTL of each record type owned by the name, as the authoritative
+    // server hands it out
+    pub fn record_ttls(&self, domain: &str, records: &[DnsRecord]) -> Vec<RecordTtl> {
+        let name = domain.trim_end_matches('.').to_lowercase();
+        let mut ttls: Vec<RecordTtl> = Vec::new();
+        for record in records
+            .iter()
+            .filter(|r| r.name.trim_end_matches('.').to_lowercase() == name)
+        {
+            match ttls
+                .iter_mut()
+                .find(|t| t.record_type == record.record_type)
+            {
+                Some(existing) => existing.ttl = existing.ttl.min(record.ttl),
+                None => ttls.push(RecordTtl {
+                    record_type: record.record_type.clone(),
+                    ttl: record.ttl,
+                }),
+            }
+        }
+        ttls.sort_by(|a, b| a.record_type.cmp(&b.record_type));
+        ttls
+    }
+
+    // Flag TTLs and SOA timers at the extremes, with what each one costs:
+    // - Records cached for under a minute, or address records for over a day
+    // - Anything over a week, which resolvers cap anyway
+    // - SOA expire under a week, expire shorter than refresh, retry above refresh
+    // - Negative caching (RFC 2308) under a minute or over a day
+    pub fn analyze_ttls(
+        &self,
+        domain: &str,
+        soa: Option<&SoaRecord>,
+        ttls: &[RecordTtl],
+    ) -> Vec<HygieneIssue> {
+        let name = domain.trim_end_matches('.').to_lowercase();
+        let mut issues = Vec::new();
+        let mut issue = |severity: &str, code: &str, message: String| {
+            issues.push(HygieneIssue {
+                severity: severity.to_string(),
+                code: code.to_string(),
+                message,
+            })
+        };
+
+        for RecordTtl { record_type, ttl } in ttls {
+            let ttl = *ttl;
+            if record_type == "SOA" {
+                continue;
+            }
+            if ttl < MIN_RECORD_TTL {
+                issue(
+                    "warning",
+                    "ttl_too_low",
+                    format!(
+                        "{} records at {} have a TTL of {}; resolvers come back to your nameservers for almost every lookup, adding latency and load, and some raise TTLs this low anyway",
+                        record_type, name, self.describe_seconds(ttl)
+                    ),
+                );
+            } else if ttl > MAX_CACHED_TTL {
+                issue(
+                    "warning",
+                    "ttl_above_cap",
+                    format!(
+                        "{} records at {} have a TTL of {}; most resolvers cap caching at about a week, so the extra length buys nothing but makes changes take that long",
+                        record_type, name, self.describe_seconds(ttl)
+                    ),
+                );
+            } else if matches!(record_type.as_str(), "A" | "AAAA" | "CNAME")
+                && ttl > MAX_ADDRESS_TTL
+            {
+                issue(
+                    "warning",
+                    "ttl_too_high",
+                    format!(
+                        "{} records at {} have a TTL of {}; a migration or failover to a new address takes up to that long to reach everyone",
+                        record_type, name, self.describe_seconds(ttl)
+                    ),
+                );
+            }
+        }
+
+        let Some(soa) = soa else {
+            return issues;
+        };
+        if soa.expire < soa.refresh {
+            issue(
+                "error",
+                "soa_expire_below_refresh",
+                format!(
+                    "SOA expire ({}) is shorter than refresh ({}); secondaries stop serving the zone before they would even check the primary",
+                    self.describe_seconds(soa.expire),
+                    self.describe_seconds(soa.refresh)
+                ),
+            );
+        } else if soa.expire < MIN_SOA_EXPIRE {
+            issue(
+                "warning",
+                "soa_expire_low",
+                format!(
+                    "SOA expire is {}; if the primary is unreachable for longer, secondaries stop answering and the zone goes dark (2-4 weeks is usual)",
+                    self.describe_seconds(soa.expire)
+                ),
+            );
+        }
+        if soa.retry > soa.refresh {
+            issue(
+                "warning",
+                "soa_retry_above_refresh",
+                format!(
+                    "SOA retry ({}) is longer than refresh ({}); after a failed refresh secondaries wait longer than they would have anyway",
+                    self.describe_seconds(soa.retry),
+                    self.describe_seconds(soa.refresh)
+                ),
+            );
+        }
+
+        let negative_ttl = soa.ttl.min(soa.minimum);
+        if negative_ttl < MIN_NEGATIVE_TTL {
+            issue(
+                "warning",
+                "negative_ttl_low",
+                format!(
+                    "Negative answers are cached for {}; lookups of missing names (typos, probes, unconfigured subdomains) almost all reach your nameservers",
+                    self.describe_seconds(negative_ttl)
+                ),
+            );
+        } else if negative_ttl > MAX_NEGATIVE_TTL {
+            issue(
+                "warning",
+                "negative_ttl_high",
+                format!(
+                    "Negative answers are cached for {}; a name looked up before it was created keeps returning NXDOMAIN that long (1-3 hours is usual)",
+                    self.describe_seconds(negative_ttl)
+                ),
+            );
+        }
+
+        issues
+    }
+
+    // "1 week", "90 minutes", "45 seconds": the largest unit that divides evenly
+    pub fn describe_seconds(&self, seconds: u32) -> String {
+        let (count, unit) = [
+            (604_800, "week"),
+            (86_400, "day"),
+            (3_600, "hour"),
+            (60, "minute"),
+        ]
+        .iter()
+        .find(|(size, _)| seconds > 0 && seconds.is_multiple_of(*size))
+        .map(|(size, unit)| (seconds / size, *unit))
+        .unwrap_or((seconds, "second"));
+        format!("{} {}{}", count, unit, if count == 1 { "" } else { "s" })
+    }
+
+    // The /24 (IPv4) or /48 (IPv6) an address is in, the usual unit of a single
+    // hosting network in resilience reviews
+    pub fn subnet(&self, address: IpAddr) -> String {
+        match address {
+            IpAddr::V4(v4) => {
+                let [a, b, c, _] = v4.octets();
+                format!("{}.{}.{}.0/24", a, b, c)
+            }
+            IpAddr::V6(v6) => {
+                let segments = v6.segments();
+                let network = Ipv6Addr::new(segments[0], segments[1], segments[2], 0, 0, 0, 0, 0);
+                format!("{}/48", network)
+            }
+        }
+    }
+
+    // Summarize where a zone's nameservers are and warn when losing one network
+    // would take them all down:
+    // - Fewer than two nameservers answering
+    // - Every address announced by the same AS
+    // - Every IPv4 address in one /24, or every IPv6 address in one /48
+    pub fn analyze_ns_diversity(
+        &self,
+        domain: &str,
+        zone: &str,
+        nameservers: Vec<NameserverNetwork>,
+    ) -> NameserverDiversity {
+        let mut warnings = Vec::new();
+        let addresses: Vec<&NameserverAddress> =
+            nameservers.iter().flat_map(|ns| &ns.addresses).collect();
+
+        let mut asns: Vec<u32> = addresses.iter().filter_map(|a| a.asn).collect();
+        asns.sort();
+        asns.dedup();
+        let mut subnets: Vec<String> = addresses.iter().map(|a| a.subnet.clone()).collect();
+        subnets.sort();
+        subnets.dedup();
+        let reachable = nameservers.iter().filter(|ns| ns.reachable).count();
+        let mut single_network = false;
+
+        for ns in nameservers.iter().filter(|ns| !ns.reachable) {
+            warnings.push(format!(
+                "{} did not answer for {}{}",
+                ns.nameserver,
+                zone,
+                ns.error
+                    .as_ref()
+                    .map(|e| format!(": {}", e))
+                    .unwrap_or_default()
+            ));
+        }
+        if reachable < 2 {
+            warnings.push(format!(
+                "Only {} of {} nameservers for {} answered; at least two are needed so the zone survives one failing",
+                reachable,
+                nameservers.len(),
+                zone
+            ));
+        }
+
+        let unannounced: Vec<&str> = addresses
+            .iter()
+            .filter(|a| a.asn.is_none())
+            .map(|a| a.address.as_str())
+            .collect();
+        if !unannounced.is_empty() {
+            warnings.push(format!(
+                "No BGP origin found for {}",
+                unannounced.join(", ")
+            ));
+        }
+        if let [asn] = asns[..] {
+            if unannounced.is_empty() {
+                let as_name = addresses
+                    .iter()
+                    .find_map(|a| a.as_name.as_ref())
+                    .map(|name| format!(" ({})", name))
+                    .unwrap_or_default();
+                warnings.push(format!(
+                    "All nameservers are announced by AS{}{}; an outage or route leak there takes every one of them offline",
+                    asn, as_name
+                ));
+                single_network = true;
+            }
+        }
+
+        for (family, is_family) in [
+            ("IPv4", IpAddr::is_ipv4 as fn(&IpAddr) -> bool),
+            ("IPv6", IpAddr::is_ipv6),
+        ] {
+            let mut family_subnets: Vec<&str> = addresses
+                .iter()
+                .filter(|a| a.address.parse().is_ok_and(|ip| is_family(&ip)))
+                .map(|a| a.subnet.as_str())
+                .collect();
+            let count = family_subnets.len();
+            family_subnets.sort();
+            family_subnets.dedup();
+            if count > 1 && family_subnets.len() == 1 {
+                single_network = true;
+                warnings.push(format!(
+                    "All {} nameserver addresses are in {}",
+                    family, family_subnets[0]
+                ));
+            }
+        }
+
+        NameserverDiversity {
+            domain: domain.to_string(),
+            zone: zone.to_string(),
+            is_diverse: reachable >= 2 && !single_network,
+            nameservers,
+            asns,
+            subnets,
+            reachable,
+            warnings,
+        }
+    }
+
+    // Query DNSKEY records from zone's own authoritative nameservers
+    // DNSKEY records are served by the zone itself, not the parent
     // Example: To get DNSKEY for "example.com", we query example.com's nameservers
     //          To get DNSKEY for "io", we query io's nameservers
+    #[instrument(skip_all, fields(domain = %domain))]
     pub async fn query_dnskey(&self, domain: &str) -> Result<DnsResponse, String> {
         let start = Instant::now();
 
@@ -212,61 +2209,34 @@ impl DnsAdapter {
 
         let ns = nameservers[0].clone();
 
-        if !self.is_dig_available() {
-            return Err("dig command not found".to_string());
+        if let Some(cached) = self.cached_response(domain, "DNSKEY", &ns) {
+            return Ok(cached);
         }
 
-        let mut cmd = Command::new("dig");
-        cmd.arg("+noall")
-            .arg("+answer")
-            .arg("+dnssec")
-            .arg("+multi") // Get key tags in comments
-            .arg(format!("@{}", ns))
-            .arg("DNSKEY")
-            .arg(domain);
-
-        let output = cmd
-            .output()
-            .map_err(|e| format!("Failed to execute dig: {}", e))?;
-
-        let query_time = start.elapsed().as_secs_f64();
-        let exit_code = output.status.code().unwrap_or(-1);
-
-        let stdout = String::from_utf8_lossy(&output.stdout).to_string();
-        let stderr = String::from_utf8_lossy(&output.stderr).to_string();
-
-        // Emit command log
-        let log_output = if !stdout.is_empty() {
-            stdout.clone()
-        } else {
-            stderr.clone()
-        };
+        if !self.is_dig_available() {
+            return Err(NO_DNS_TOOL.to_string());
+        }
 
         let args = vec![
             "+noall".to_string(),
+            "+comments".to_string(),
             "+answer".to_string(),
             "+dnssec".to_string(),
-            "+multi".to_string(),
+            "+multi".to_string(), // Get key tags in comments
             format!("@{}", ns),
             "DNSKEY".to_string(),
             domain.to_string(),
         ];
 
-        self.emit_log(CommandLog::new(
-            "dig".to_string(),
-            args,
-            log_output,
-            exit_code,
-            query_time * 1000.0,
-            Some(domain.to_string()),
-        ));
+        let output = self.run_dig(args, domain).await?;
+        let stdout = output.stdout;
 
         // Don't rely solely on exit code for DNSSEC queries
         // dig often returns non-zero exit codes for valid DNSSEC queries
         let has_data = stdout.contains("DNSKEY") || stdout.contains("ANSWER SECTION");
 
-        if !output.status.success() && !has_data && !stderr.is_empty() {
-            return Err(format!("dig command failed: {}", stderr));
+        if !output.success && !has_data && !output.stderr.is_empty() {
+            return Err(format!("dig command failed: {}", output.stderr));
         }
 
         // For DNSSEC queries, empty results are valid (means DNSSEC not enabled)
@@ -274,58 +2244,44 @@ impl DnsAdapter {
             .parse_dig_output(&stdout, "DNSKEY")
             .unwrap_or_else(|_| Vec::new());
 
-        Ok(DnsResponse {
+        let response = DnsResponse {
             records,
-            query_time,
+            // Includes the nameserver lookup above
+            query_time: start.elapsed().as_secs_f64(),
             resolver: ns.clone(),
+            header: self.parse_dig_header(&stdout),
+            transport: output.transport,
+            server: output.server,
+            ip_version: output.ip_version,
+            from_cache: false,
             raw_output: Some(stdout),
-        })
+        };
+
+        self.cache_response(domain, "DNSKEY", &ns, &response);
+
+        Ok(response)
     }
 
     // Query root zone DNSKEY records using dig . DNSKEY +short
     pub async fn query_root_dnskey(&self) -> Result<DnsResponse, String> {
-        let start = Instant::now();
-
         if !self.is_dig_available() {
-            return Err("dig command not found".to_string());
+            return Err(NO_DNS_TOOL.to_string());
         }
 
-        let args = vec![".".to_string(), "DNSKEY".to_string(), "+multi".to_string()];
-
-        let output = Command::new("dig")
-            .arg(".")
-            .arg("DNSKEY")
-            .arg("+multi")
-            .output()
-            .map_err(|e| format!("Failed to execute dig: {}", e))?;
-
-        let query_time = start.elapsed().as_secs_f64();
-        let exit_code = output.status.code().unwrap_or(-1);
-
-        let stdout = String::from_utf8_lossy(&output.stdout).to_string();
-        let stderr = String::from_utf8_lossy(&output.stderr).to_string();
+        if let Some(cached) = self.cached_response(".", "DNSKEY", "root") {
+            return Ok(cached);
+        }
 
-        // Emit command log
-        let log_output = if !stdout.is_empty() {
-            stdout.clone()
-        } else {
-            stderr.clone()
-        };
+        let args = vec![".".to_string(), "DNSKEY".to_string(), "+multi".to_string()];
 
-        self.emit_log(CommandLog::new(
-            "dig".to_string(),
-            args,
-            log_output,
-            exit_code,
-            query_time * 1000.0,
-            Some(".".to_string()),
-        ));
+        let output = self.run_dig(args, ".").await?;
+        let stdout = output.stdout;
 
         // Don't fail on non-zero exit codes if we got valid data
         let has_data = !stdout.is_empty() && stdout.lines().any(|line| !line.trim().is_empty());
 
-        if !output.status.success() && !has_data {
-            return Err(format!("dig command failed: {}", stderr));
+        if !output.success && !has_data {
+            return Err(format!("dig command failed: {}", output.stderr));
         }
 
         // Parse +multi format using standard parser
@@ -337,97 +2293,57 @@ impl DnsAdapter {
             return Err("No root DNSKEY records found".to_string());
         }
 
-        Ok(DnsResponse {
+        let response = DnsResponse {
             records,
-            query_time,
+            query_time: output.query_time,
             resolver: "root".to_string(),
+            header: self.parse_dig_header(&stdout),
+            transport: output.transport,
+            server: output.server,
+            ip_version: output.ip_version,
+            from_cache: false,
             raw_output: Some(stdout.to_string()),
-        })
+        };
+
+        self.cache_response(".", "DNSKEY", "root", &response);
+
+        Ok(response)
     }
 
     // Query DS records from parent zone's authoritative server
+    #[instrument(skip_all, fields(domain = %domain))]
     pub async fn query_ds(&self, domain: &str) -> Result<DnsResponse, String> {
         let start = Instant::now();
 
-        // Get parent domain
-        let parts: Vec<&str> = domain.split('.').collect();
+        let (_parent_domain, ns) = self.get_parent_nameserver(domain).await?;
 
-        // For TLDs (single part like "io", "com"), query from root servers
-        // For domains (like "example.com"), query from parent zone
-        let (_parent_domain, ns) = if parts.len() == 1 {
-            // TLD: query from root servers (use any root server, e.g., a.root-servers.net)
-            (".".to_string(), "a.root-servers.net".to_string())
-        } else if parts.len() >= 2 {
-            // Regular domain: query from parent zone's nameservers
-            let parent = parts[1..].join(".");
-            let parent_ns = self.get_nameservers(&parent).await?;
-
-            if parent_ns.is_empty() {
-                return Err("No parent nameservers found".to_string());
-            }
-
-            (parent, parent_ns[0].clone())
-        } else {
-            return Err("Invalid domain for DS query".to_string());
-        };
+        if let Some(cached) = self.cached_response(domain, "DS", &ns) {
+            return Ok(cached);
+        }
 
         if !self.is_dig_available() {
-            return Err("dig command not found".to_string());
+            return Err(NO_DNS_TOOL.to_string());
         }
 
-        let mut cmd = Command::new("dig");
-        cmd.arg("+noall")
-            .arg("+answer")
-            .arg("+dnssec")
-            .arg("+time=2") // 2 second timeout
-            .arg("+tries=1") // Only try once
-            .arg(format!("@{}", ns))
-            .arg("DS")
-            .arg(domain);
-
-        let output = cmd
-            .output()
-            .map_err(|e| format!("Failed to execute dig: {}", e))?;
-
-        let query_time = start.elapsed().as_secs_f64();
-        let exit_code = output.status.code().unwrap_or(-1);
-
-        let stdout = String::from_utf8_lossy(&output.stdout).to_string();
-        let stderr = String::from_utf8_lossy(&output.stderr).to_string();
-
-        // Emit command log
-        let log_output = if !stdout.is_empty() {
-            stdout.clone()
-        } else {
-            stderr.clone()
-        };
-
         let args = vec![
             "+noall".to_string(),
+            "+comments".to_string(),
             "+answer".to_string(),
             "+dnssec".to_string(),
-            "+time=2".to_string(),
-            "+tries=1".to_string(),
             format!("@{}", ns),
             "DS".to_string(),
             domain.to_string(),
         ];
 
-        self.emit_log(CommandLog::new(
-            "dig".to_string(),
-            args,
-            log_output,
-            exit_code,
-            query_time * 1000.0,
-            Some(domain.to_string()),
-        ));
+        let output = self.run_dig(args, domain).await?;
+        let stdout = output.stdout;
 
         // Don't rely solely on exit code for DS queries
         // dig often returns non-zero exit codes for valid queries
         let has_data = stdout.contains("DS") || stdout.contains("ANSWER SECTION");
 
-        if !output.status.success() && !has_data && !stderr.is_empty() {
-            return Err(format!("dig command failed: {}", stderr));
+        if !output.success && !has_data && !output.stderr.is_empty() {
+            return Err(format!("dig command failed: {}", output.stderr));
         }
 
         // For DNSSEC queries, empty results are valid (means DNSSEC not enabled)
@@ -435,19 +2351,733 @@ impl DnsAdapter {
             .parse_dig_output(&stdout, "DS")
             .unwrap_or_else(|_| Vec::new());
 
-        Ok(DnsResponse {
+        let response = DnsResponse {
             records,
-            query_time,
+            // Includes the parent nameserver lookup above
+            query_time: start.elapsed().as_secs_f64(),
             resolver: ns.clone(),
+            header: self.parse_dig_header(&stdout),
+            transport: output.transport,
+            server: output.server,
+            ip_version: output.ip_version,
+            from_cache: false,
             raw_output: Some(stdout),
-        })
+        };
+
+        self.cache_response(domain, "DS", &ns, &response);
+
+        Ok(response)
+    }
+
+    // DNSSEC algorithm mnemonic (IANA "DNS Security Algorithm Numbers" registry)
+    pub fn algorithm_name(&self, algorithm: u8) -> &'static str {
+        match algorithm {
+            0 => "DELETE",
+            1 => "RSAMD5",
+            3 => "DSA",
+            5 => "RSASHA1",
+            6 => "DSA-NSEC3-SHA1",
+            7 => "RSASHA1-NSEC3-SHA1",
+            8 => "RSASHA256",
+            10 => "RSASHA512",
+            12 => "ECC-GOST",
+            13 => "ECDSAP256SHA256",
+            14 => "ECDSAP384SHA384",
+            15 => "ED25519",
+            16 => "ED448",
+            _ => "UNKNOWN",
+        }
+    }
+
+    // DS digest algorithm name (IANA "Delegation Signer Digest Algorithms" registry)
+    pub fn digest_type_name(&self, digest_type: u8) -> &'static str {
+        match digest_type {
+            0 => "DELETE",
+            1 => "SHA-1",
+            2 => "SHA-256",
+            3 => "GOST R 34.11-94",
+            4 => "SHA-384",
+            _ => "UNKNOWN",
+        }
+    }
+
+    // SSHFP public key algorithm (IANA "SSHFP RR Types for public key algorithms")
+    pub fn sshfp_algorithm_name(&self, algorithm: u8) -> &'static str {
+        match algorithm {
+            1 => "RSA",
+            2 => "DSA",
+            3 => "ECDSA",
+            4 => "Ed25519",
+            6 => "Ed448",
+            _ => "UNKNOWN",
+        }
+    }
+
+    // SSHFP fingerprint type (IANA "SSHFP RR types for fingerprint types")
+    pub fn sshfp_fp_type_name(&self, fp_type: u8) -> &'static str {
+        match fp_type {
+            1 => "SHA-1",
+            2 => "SHA-256",
+            _ => "UNKNOWN",
+        }
+    }
+
+    // Algorithms that must not be used for signing (RFC 8624 section 3.1)
+    pub fn is_deprecated_algorithm(&self, algorithm: u8) -> bool {
+        matches!(algorithm, 1 | 3 | 5 | 6 | 7 | 12)
+    }
+
+    // Key size in bits
+    // RSA keys store exponent length, exponent, then modulus (RFC 3110 section 2)
+    pub fn key_size(&self, key: &DnskeyRecord) -> Option<u32> {
+        match key.algorithm {
+            1 | 5 | 7 | 8 | 10 => {
+                let rdata = self.dnskey_rdata(key).ok()?;
+                let public_key = &rdata[4..];
+                let (exponent_len, offset) = match *public_key.first()? {
+                    0 => (
+                        u16::from_be_bytes([*public_key.get(1)?, *public_key.get(2)?]) as usize,
+                        3,
+                    ),
+                    len => (len as usize, 1),
+                };
+                let modulus = public_key.get(offset + exponent_len..)?;
+                let significant: Vec<u8> =
+                    modulus.iter().copied().skip_while(|b| *b == 0).collect();
+                let first = *significant.first()?;
+                Some((significant.len() as u32 - 1) * 8 + (8 - first.leading_zeros()))
+            }
+            13 | 15 => Some(256),
+            14 => Some(384),
+            16 => Some(456),
+            _ => None,
+        }
+    }
+
+    // Warnings for weak DNSSEC crypto anywhere in the chain:
+    // deprecated signing algorithms, RSA keys shorter than 2048 bits, and SHA-1 DS digests
+    pub fn algorithm_warnings(&self, chain: &[ZoneData]) -> Vec<String> {
+        let mut warnings = Vec::new();
+
+        for zone in chain {
+            let mut algorithms: Vec<u8> = zone.dnskey_records.iter().map(|k| k.algorithm).collect();
+            algorithms.sort();
+            algorithms.dedup();
+
+            for algorithm in algorithms
+                .into_iter()
+                .filter(|a| self.is_deprecated_algorithm(*a))
+            {
+                warnings.push(format!(
+                    "{} is signed with deprecated algorithm {} ({})",
+                    zone.zone_name,
+                    self.algorithm_name(algorithm),
+                    algorithm
+                ));
+            }
+
+            for key in &zone.dnskey_records {
+                if let Some(bits) = key.key_size {
+                    if matches!(key.algorithm, 1 | 5 | 7 | 8 | 10) && bits < 2048 {
+                        warnings.push(format!(
+                            "{} has a {}-bit RSA key (key tag {}); use at least 2048 bits",
+                            zone.zone_name, bits, key.key_tag
+                        ));
+                    }
+                }
+            }
+
+            for ds in zone.ds_records.iter().filter(|ds| ds.digest_type == 1) {
+                warnings.push(format!(
+                    "The DS set in {} includes a SHA-1 digest (key tag {}); SHA-256 is recommended",
+                    zone.zone_name, ds.key_tag
+                ));
+            }
+        }
+
+        warnings
+    }
+
+    // DNSKEY RDATA in wire format: flags (2) | protocol (1) | algorithm (1) | public key
+    pub fn dnskey_rdata(&self, key: &DnskeyRecord) -> Result<Vec<u8>, String> {
+        // +multi output wraps the key in parentheses and splits it across lines
+        let encoded: String = key
+            .public_key
+            .chars()
+            .filter(|c| !c.is_whitespace() && *c != '(' && *c != ')')
+            .collect();
+        let public_key = BASE64
+            .decode(encoded.as_bytes())
+            .map_err(|e| format!("Invalid DNSKEY public key: {}", e))?;
+
+        let mut rdata = Vec::with_capacity(4 + public_key.len());
+        rdata.extend_from_slice(&key.flags.to_be_bytes());
+        rdata.push(key.protocol);
+        rdata.push(key.algorithm);
+        rdata.extend_from_slice(&public_key);
+        Ok(rdata)
+    }
+
+    // Key tag per RFC 4034 Appendix B
+    pub fn compute_key_tag(&self, key: &DnskeyRecord) -> Result<u16, String> {
+        let rdata = self.dnskey_rdata(key)?;
+
+        let mut acc: u32 = 0;
+        for (i, byte) in rdata.iter().enumerate() {
+            acc += if i % 2 == 0 {
+                (*byte as u32) << 8
+            } else {
+                *byte as u32
+            };
+        }
+        acc += (acc >> 16) & 0xFFFF;
+        Ok((acc & 0xFFFF) as u16)
+    }
+
+    // Owner name in canonical (lowercase) wire format, e.g. "example.com" -> 7example3com0
+    fn canonical_wire_name(&self, name: &str) -> Vec<u8> {
+        let mut wire = Vec::new();
+        for label in name
+            .trim_end_matches('.')
+            .split('.')
+            .filter(|l| !l.is_empty())
+        {
+            wire.push(label.len() as u8);
+            wire.extend(label.to_lowercase().bytes());
+        }
+        wire.push(0);
+        wire
+    }
+
+    // DS digest for a DNSKEY (RFC 4034 section 5.1.4), as uppercase hex
+    // digest = hash(canonical owner name | DNSKEY RDATA)
+    pub fn compute_ds_digest(
+        &self,
+        owner: &str,
+        key: &DnskeyRecord,
+        digest_type: u8,
+    ) -> Result<String, String> {
+        let mut data = self.canonical_wire_name(owner);
+        data.extend(self.dnskey_rdata(key)?);
+
+        let digest: Vec<u8> = match digest_type {
+            1 => Sha1::digest(&data).to_vec(),
+            2 => Sha256::digest(&data).to_vec(),
+            4 => Sha384::digest(&data).to_vec(),
+            other => return Err(format!("Unsupported DS digest type {}", other)),
+        };
+
+        Ok(digest.iter().map(|b| format!("{:02X}", b)).collect())
+    }
+
+    // Check each DS record against the child zone's DNSKEYs by recomputing its digest
+    // Key tags alone are only 16 bits and can collide, so a matching tag is not proof
+    pub fn verify_ds_records(
+        &self,
+        owner: &str,
+        ds_records: &mut [DsRecord],
+        dnskeys: &[DnskeyRecord],
+    ) {
+        for ds in ds_records.iter_mut() {
+            let candidates: Vec<&DnskeyRecord> = dnskeys
+                .iter()
+                .filter(|key| key.algorithm == ds.algorithm)
+                .collect();
+
+            let mut verified = None;
+            for key in candidates {
+                match self.compute_ds_digest(owner, key, ds.digest_type) {
+                    Ok(digest) if digest.eq_ignore_ascii_case(&ds.digest) => {
+                        verified = Some(true);
+                        break;
+                    }
+                    Ok(_) => verified = Some(false),
+                    Err(_) => {}
+                }
+            }
+
+            // No key with the DS algorithm at all is a definite mismatch
+            if verified.is_none() && matches!(ds.digest_type, 1 | 2 | 4) {
+                verified = Some(false);
+            }
+
+            ds.digest_verified = verified;
+        }
+    }
+
+    // Match each DS record with the child DNSKEY whose digest it carries
+    // Unsupported digest types fall back to key tag + algorithm, with verified = None
+    pub fn link_ds_records(
+        &self,
+        child_zone: &str,
+        ds_records: &[DsRecord],
+        dnskeys: &[DnskeyRecord],
+    ) -> Vec<DsLink> {
+        ds_records
+            .iter()
+            .map(|ds| {
+                let candidates = dnskeys.iter().filter(|key| key.algorithm == ds.algorithm);
+                let (dnskey_key_tag, verified) = if matches!(ds.digest_type, 1 | 2 | 4) {
+                    let matched = candidates
+                        .filter_map(|key| {
+                            let digest = self.compute_ds_digest(child_zone, key, ds.digest_type);
+                            digest.ok().map(|d| (key, d))
+                        })
+                        .find(|(_, digest)| digest.eq_ignore_ascii_case(&ds.digest))
+                        .map(|(key, _)| key.key_tag);
+                    (matched, Some(matched.is_some()))
+                } else {
+                    let matched = candidates
+                        .map(|key| key.key_tag)
+                        .find(|tag| *tag == ds.key_tag);
+                    (matched, None)
+                };
+
+                DsLink {
+                    child_zone: child_zone.to_string(),
+                    ds_key_tag: ds.key_tag,
+                    digest_type: ds.digest_type,
+                    dnskey_key_tag,
+                    verified,
+                }
+            })
+            .collect()
+    }
+
+    // Match each RRSIG in a zone with the DNSKEY that made it
+    pub fn link_signatures(&self, zone: &ZoneData, now: DateTime<Utc>) -> Vec<SignatureLink> {
+        zone.rrsig_records
+            .iter()
+            .map(|rrsig| {
+                let key = zone
+                    .dnskey_records
+                    .iter()
+                    .find(|k| k.key_tag == rrsig.key_tag && k.algorithm == rrsig.algorithm);
+                let valid_now = matches!(
+                    (rrsig.inception_at, rrsig.expires_at),
+                    (Some(inception), Some(expiry)) if inception <= now && now < expiry
+                );
+
+                SignatureLink {
+                    type_covered: rrsig.type_covered.clone(),
+                    key_tag: rrsig.key_tag,
+                    signed_by: key.map(|k| k.key_tag),
+                    key_flags: key.map(|k| k.flags),
+                    valid_now,
+                }
+            })
+            .collect()
+    }
+
+    // Compare a zone's DNSKEYs with the trust anchors configured for it (RFC 5011 aware)
+    // Returns None when no anchor covers the zone, otherwise whether an anchored key is
+    // published and active, plus warnings for revoked anchors, new unanchored KSKs
+    // (a rollover in progress) and anchors the zone no longer publishes
+    pub fn check_trust_anchors(
+        &self,
+        zone: &str,
+        dnskeys: &[DnskeyRecord],
+        anchors: &[TrustAnchor],
+    ) -> (Option<bool>, Vec<String>) {
+        let mut warnings = Vec::new();
+        let zone = zone.trim_end_matches('.');
+        let anchors: Vec<&TrustAnchor> = anchors
+            .iter()
+            .filter(|a| a.zone.trim_end_matches('.').eq_ignore_ascii_case(zone))
+            .collect();
+        let owner = if zone.is_empty() { "." } else { zone };
+
+        if anchors.is_empty() {
+            return (None, warnings);
+        }
+
+        let digest_matches = |anchor: &TrustAnchor, key: &DnskeyRecord| {
+            key.algorithm == anchor.algorithm
+                && self
+                    .compute_ds_digest(owner, key, anchor.digest_type)
+                    .map(|d| d.eq_ignore_ascii_case(&anchor.digest))
+                    .unwrap_or(false)
+        };
+
+        let mut anchored_keys = Vec::new();
+        for anchor in &anchors {
+            if let Some(key) = dnskeys.iter().find(|k| digest_matches(anchor, k)) {
+                anchored_keys.push(key.key_tag);
+                continue;
+            }
+
+            // Setting the REVOKE bit (0x0080) changes the digest, so compare without it
+            let revoked = dnskeys.iter().any(|k| {
+                let mut unrevoked = k.clone();
+                unrevoked.flags &= !0x0080;
+                k.flags & 0x0080 != 0 && digest_matches(anchor, &unrevoked)
+            });
+
+            if revoked {
+                warnings.push(format!(
+                    "Trust anchor {} for {} has been revoked (RFC 5011); remove it from settings",
+                    anchor.key_tag, owner
+                ));
+            } else {
+                warnings.push(format!(
+                    "Trust anchor {} for {} is not in the published DNSKEY set",
+                    anchor.key_tag, owner
+                ));
+            }
+        }
+
+        for key in dnskeys {
+            let is_active_ksk = key.flags & 0x0001 != 0 && key.flags & 0x0080 == 0;
+            if is_active_ksk && !anchored_keys.contains(&key.key_tag) {
+                warnings.push(format!(
+                    "{} publishes KSK {} which is not a configured trust anchor (RFC 5011 rollover in progress?)",
+                    owner, key.key_tag
+                ));
+            }
+        }
+
+        if anchored_keys.is_empty() {
+            warnings.push(format!(
+                "DNSKEY set for {} doesn't match any configured trust anchor",
+                owner
+            ));
+        }
+
+        (Some(!anchored_keys.is_empty()), warnings)
+    }
+
+    // Fill in DS links, signature links and a status for every zone, top-down
+    // The root is SECURE only when it publishes a configured trust anchor. A zone is
+    // only as secure as its parent: below an INSECURE delegation everything is INSECURE,
+    // and below a BOGUS one everything is BOGUS, unless the zone has its own anchor
+    pub fn link_chain(
+        &self,
+        chain: &mut [ZoneData],
+        anchors: &[TrustAnchor],
+        now: DateTime<Utc>,
+    ) -> Vec<String> {
+        let mut warnings = Vec::new();
+
+        for i in 0..chain.len() {
+            chain[i].signature_links = self.link_signatures(&chain[i], now);
+
+            let (anchored, anchor_warnings) =
+                self.check_trust_anchors(&chain[i].zone_name, &chain[i].dnskey_records, anchors);
+            if !chain[i].dnskey_records.is_empty() {
+                warnings.extend(anchor_warnings);
+            }
+
+            let status = if i == 0 {
+                if chain[0].zone_name != "." || chain[0].dnskey_records.is_empty() {
+                    "INDETERMINATE".to_string()
+                } else {
+                    match anchored {
+                        Some(true) => "SECURE".to_string(),
+                        Some(false) => "BOGUS".to_string(),
+                        None => {
+                            warnings.push("No root trust anchor is configured".to_string());
+                            "INDETERMINATE".to_string()
+                        }
+                    }
+                }
+            } else {
+                let (parents, rest) = chain.split_at_mut(i);
+                let parent = &mut parents[i - 1];
+                let zone = &rest[0];
+
+                // The parent's DS records were queried for its immediate child only
+                let parent_name = match zone.zone_name.split_once('.') {
+                    Some((_, parent_name)) => parent_name,
+                    None => ".",
+                };
+
+                if parent.zone_name != parent_name {
+                    "INDETERMINATE".to_string()
+                } else {
+                    parent.ds_links = self.link_ds_records(
+                        &zone.zone_name,
+                        &parent.ds_records,
+                        &zone.dnskey_records,
+                    );
+
+                    if parent.status != "SECURE" {
+                        parent.status.clone()
+                    } else if parent.ds_records.is_empty() {
+                        "INSECURE".to_string()
+                    } else if parent.ds_links.iter().any(|l| l.verified == Some(true))
+                        || parent
+                            .ds_links
+                            .iter()
+                            .all(|l| l.verified.is_none() && l.dnskey_key_tag.is_some())
+                    {
+                        "SECURE".to_string()
+                    } else {
+                        // DS records exist but point at no key the zone publishes
+                        "BOGUS".to_string()
+                    }
+                }
+            };
+
+            // A zone-level anchor overrides whatever the parent says
+            chain[i].status = match anchored {
+                Some(true) if i > 0 => "SECURE".to_string(),
+                Some(false) if i > 0 && !chain[i].dnskey_records.is_empty() => "BOGUS".to_string(),
+                _ => status,
+            };
+        }
+
+        warnings
+    }
+
+    // Fetch CDS and CDNSKEY records from the zone's own nameserver
+    // They are published by the child, so they never come from the parent
+    pub async fn query_rollover_records(
+        &self,
+        domain: &str,
+    ) -> Result<(Vec<DsRecord>, Vec<DnskeyRecord>), String> {
+        let nameservers = self.get_nameservers(domain).await?;
+        let ns = nameservers
+            .first()
+            .ok_or_else(|| "No nameservers found for domain".to_string())?;
+
+        let cds = self.query_authoritative(domain, "CDS", ns).await?;
+        let cdnskey = self.query_authoritative(domain, "CDNSKEY", ns).await?;
+
+        Ok((
+            self.parse_cds_records(&cds.records),
+            self.parse_cdnskey_records(&cdnskey.records),
+        ))
+    }
+
+    // Compare the child's CDS/CDNSKEY request with the parent's DS set (RFC 7344, RFC 8078)
+    // Returns the rollover status and any warnings worth surfacing
+    pub fn evaluate_rollover(
+        &self,
+        domain: &str,
+        mut cds: Vec<DsRecord>,
+        cdnskey: Vec<DnskeyRecord>,
+        parent_ds: &[DsRecord],
+        dnskeys: &[DnskeyRecord],
+    ) -> (RolloverStatus, Vec<String>) {
+        let mut warnings = Vec::new();
+        let ds_id = |ds: &DsRecord| {
+            format!(
+                "{} {} {} {}",
+                ds.key_tag,
+                ds.algorithm,
+                ds.digest_type,
+                ds.digest.to_uppercase()
+            )
+        };
+
+        if cds.is_empty() && cdnskey.is_empty() {
+            let status = RolloverStatus {
+                state: "NONE".to_string(),
+                cds_records: cds,
+                cdnskey_records: cdnskey,
+                missing_from_parent: Vec::new(),
+                pending_removal: Vec::new(),
+            };
+            return (status, warnings);
+        }
+
+        // RFC 8078 delete request: "CDS 0 0 0 00" / "CDNSKEY 0 3 0 AA=="
+        let delete_requested =
+            cds.iter().any(|ds| ds.algorithm == 0) || cdnskey.iter().any(|key| key.algorithm == 0);
+
+        if delete_requested {
+            warnings.push(format!(
+                "{} publishes a CDS/CDNSKEY delete request; the parent will remove its DS records and the zone will become insecure",
+                domain
+            ));
+            let status = RolloverStatus {
+                state: "DELETE_REQUESTED".to_string(),
+                cds_records: cds,
+                cdnskey_records: cdnskey,
+                missing_from_parent: Vec::new(),
+                pending_removal: parent_ds.iter().map(ds_id).collect(),
+            };
+            return (status, warnings);
+        }
+
+        let mut invalid = false;
+
+        // Every CDS must point at a key the zone actually publishes
+        self.verify_ds_records(domain, &mut cds, dnskeys);
+        for ds in cds.iter().filter(|ds| ds.digest_verified == Some(false)) {
+            invalid = true;
+            warnings.push(format!(
+                "CDS record for key tag {} does not match any DNSKEY in {}",
+                ds.key_tag, domain
+            ));
+        }
+
+        let normalize = |key: &str| -> String {
+            key.chars()
+                .filter(|c| !c.is_whitespace() && *c != '(' && *c != ')')
+                .collect()
+        };
+        for key in &cdnskey {
+            let published = dnskeys.iter().any(|k| {
+                k.flags == key.flags
+                    && k.algorithm == key.algorithm
+                    && normalize(&k.public_key) == normalize(&key.public_key)
+            });
+            if !published {
+                invalid = true;
+                warnings.push(format!(
+                    "CDNSKEY with key tag {} is not in the DNSKEY set of {}",
+                    key.key_tag, domain
+                ));
+            }
+        }
+
+        // When both are published they must describe the same keys
+        if !cds.is_empty() && !cdnskey.is_empty() {
+            let mut cds_from_keys = cds.clone();
+            self.verify_ds_records(domain, &mut cds_from_keys, &cdnskey);
+            if cds_from_keys
+                .iter()
+                .any(|ds| ds.digest_verified == Some(false))
+            {
+                invalid = true;
+                warnings.push(format!(
+                    "CDS and CDNSKEY records of {} describe different keys",
+                    domain
+                ));
+            }
+        }
+
+        let digest_matches = |ds: &DsRecord, key: &DnskeyRecord| {
+            ds.algorithm == key.algorithm
+                && self
+                    .compute_ds_digest(domain, key, ds.digest_type)
+                    .map(|digest| digest.eq_ignore_ascii_case(&ds.digest))
+                    .unwrap_or(false)
+        };
+
+        let (missing_from_parent, pending_removal): (Vec<String>, Vec<String>) = if !cds.is_empty()
+        {
+            let cds_ids: Vec<String> = cds.iter().map(ds_id).collect();
+            let parent_ids: Vec<String> = parent_ds.iter().map(ds_id).collect();
+            (
+                cds_ids
+                    .iter()
+                    .filter(|id| !parent_ids.contains(id))
+                    .cloned()
+                    .collect(),
+                parent_ids
+                    .iter()
+                    .filter(|id| !cds_ids.contains(id))
+                    .cloned()
+                    .collect(),
+            )
+        } else {
+            // CDNSKEY only - match keys against the parent's DS digests
+            (
+                cdnskey
+                    .iter()
+                    .filter(|key| !parent_ds.iter().any(|ds| digest_matches(ds, key)))
+                    .map(|key| format!("{} {} (CDNSKEY)", key.key_tag, key.algorithm))
+                    .collect(),
+                parent_ds
+                    .iter()
+                    .filter(|ds| !cdnskey.iter().any(|key| digest_matches(ds, key)))
+                    .map(ds_id)
+                    .collect(),
+            )
+        };
+
+        let state = if invalid {
+            "INVALID"
+        } else if missing_from_parent.is_empty() && pending_removal.is_empty() {
+            "IN_SYNC"
+        } else {
+            warnings.push(format!(
+                "Key rollover pending for {}: parent DS set differs from the published CDS/CDNSKEY records",
+                domain
+            ));
+            "PENDING"
+        };
+
+        let status = RolloverStatus {
+            state: state.to_string(),
+            cds_records: cds,
+            cdnskey_records: cdnskey,
+            missing_from_parent,
+            pending_removal,
+        };
+        (status, warnings)
+    }
+
+    // Parse SOA records: mname rname serial refresh retry expire minimum
+    pub fn parse_soa_records(&self, records: &[DnsRecord]) -> Vec<SoaRecord> {
+        records
+            .iter()
+            .filter(|r| r.record_type == "SOA")
+            .filter_map(|r| {
+                let value = r.value.replace(['(', ')'], " ");
+                let parts: Vec<&str> = value.split_whitespace().collect();
+                if parts.len() < 7 {
+                    return None;
+                }
+                let number = |i: usize| parts[i].parse::<u32>().ok();
+                Some(SoaRecord {
+                    mname: parts[0].trim_end_matches('.').to_lowercase(),
+                    rname: parts[1].trim_end_matches('.').to_lowercase(),
+                    serial: number(2)?,
+                    refresh: number(3)?,
+                    retry: number(4)?,
+                    expire: number(5)?,
+                    minimum: number(6)?,
+                    ttl: r.ttl,
+                })
+            })
+            .collect()
+    }
+
+    // Parse SSHFP records: algorithm fp_type fingerprint, with the hex split as
+    // dig prints long fingerprints
+    pub fn parse_sshfp_records(&self, records: &[DnsRecord]) -> Vec<SshfpRecord> {
+        records
+            .iter()
+            .filter(|r| r.record_type == "SSHFP")
+            .filter_map(|r| {
+                let mut fields = r.value.split_whitespace();
+                let algorithm = fields.next()?.parse::<u8>().ok()?;
+                let fp_type = fields.next()?.parse::<u8>().ok()?;
+                let fingerprint = fields.collect::<String>().to_lowercase();
+                if fingerprint.is_empty() || !fingerprint.chars().all(|c| c.is_ascii_hexdigit()) {
+                    return None;
+                }
+
+                Some(SshfpRecord {
+                    algorithm,
+                    algorithm_name: self.sshfp_algorithm_name(algorithm).to_string(),
+                    fp_type,
+                    fp_type_name: self.sshfp_fp_type_name(fp_type).to_string(),
+                    fingerprint,
+                    matched: None,
+                })
+            })
+            .collect()
     }
 
     // Parse DNSKEY records from DNS records
     pub fn parse_dnskey_records(&self, records: &[DnsRecord]) -> Vec<DnskeyRecord> {
+        self.parse_key_records(records, "DNSKEY")
+    }
+
+    // Parse CDNSKEY records (child's request for the parent's DS set, RFC 7344)
+    pub fn parse_cdnskey_records(&self, records: &[DnsRecord]) -> Vec<DnskeyRecord> {
+        self.parse_key_records(records, "CDNSKEY")
+    }
+
+    // DNSKEY and CDNSKEY share the same presentation format
+    fn parse_key_records(&self, records: &[DnsRecord], record_type: &str) -> Vec<DnskeyRecord> {
         records
             .iter()
-            .filter(|r| r.record_type == "DNSKEY")
+            .filter(|r| r.record_type == record_type)
             .filter_map(|r| {
                 // DNSKEY format: flags protocol algorithm public_key
                 let parts: Vec<&str> = r.value.split_whitespace().collect();
@@ -457,14 +3087,14 @@ impl DnsAdapter {
                     let algorithm = parts[2].parse::<u8>().ok()?;
                     // Extract key tag from comment if using +multi format
                     // Comment format: "; key id = 55759" or "; KSK; alg = RSASHA256 ; key id = 5116"
-                    let mut key_tag = flags; // Fallback to flags if no comment
+                    let mut key_tag = None;
 
                     // Look for "key id =" in the value
                     if let Some(key_id_pos) = r.value.find("key id =") {
                         let after_key_id = &r.value[key_id_pos + 9..];
                         if let Some(tag_str) = after_key_id.split_whitespace().next() {
                             if let Ok(tag) = tag_str.parse::<u16>() {
-                                key_tag = tag;
+                                key_tag = Some(tag);
                             }
                         }
                     }
@@ -480,13 +3110,26 @@ impl DnsAdapter {
                         parts[3..].join(" ")
                     };
 
-                    Some(DnskeyRecord {
+                    let mut key = DnskeyRecord {
                         flags,
                         protocol,
                         algorithm,
-                        key_tag,
+                        algorithm_name: self.algorithm_name(algorithm).to_string(),
+                        key_tag: key_tag.unwrap_or(flags),
                         public_key,
-                    })
+                        key_size: None,
+                    };
+                    key.key_size = self.key_size(&key);
+
+                    // Without a "key id" comment, compute the tag from the key itself
+                    // (falling back to flags if the key can't be decoded)
+                    if key_tag.is_none() {
+                        if let Ok(tag) = self.compute_key_tag(&key) {
+                            key.key_tag = tag;
+                        }
+                    }
+
+                    Some(key)
                 } else {
                     None
                 }
@@ -496,9 +3139,19 @@ impl DnsAdapter {
 
     // Parse DS records from DNS records
     pub fn parse_ds_records(&self, records: &[DnsRecord]) -> Vec<DsRecord> {
+        self.parse_digest_records(records, "DS")
+    }
+
+    // Parse CDS records (child's request for the parent's DS set, RFC 7344)
+    pub fn parse_cds_records(&self, records: &[DnsRecord]) -> Vec<DsRecord> {
+        self.parse_digest_records(records, "CDS")
+    }
+
+    // DS and CDS share the same presentation format
+    fn parse_digest_records(&self, records: &[DnsRecord], record_type: &str) -> Vec<DsRecord> {
         records
             .iter()
-            .filter(|r| r.record_type == "DS")
+            .filter(|r| r.record_type == record_type)
             .filter_map(|r| {
                 // DS format: key_tag algorithm digest_type digest
                 let parts: Vec<&str> = r.value.split_whitespace().collect();
@@ -506,13 +3159,17 @@ impl DnsAdapter {
                     let key_tag = parts[0].parse::<u16>().ok()?;
                     let algorithm = parts[1].parse::<u8>().ok()?;
                     let digest_type = parts[2].parse::<u8>().ok()?;
-                    let digest = parts[3..].join("");
+                    // Drop the parentheses dig prints around split digests with +multi
+                    let digest = parts[3..].join("").replace(['(', ')'], "");
 
                     Some(DsRecord {
                         key_tag,
                         algorithm,
+                        algorithm_name: self.algorithm_name(algorithm).to_string(),
                         digest_type,
+                        digest_type_name: self.digest_type_name(digest_type).to_string(),
                         digest,
+                        digest_verified: None,
                     })
                 } else {
                     None
@@ -521,6 +3178,234 @@ impl DnsAdapter {
             .collect()
     }
 
+    // RRSIG timestamps are YYYYMMDDHHmmSS in UTC, or seconds since the epoch (RFC 4034 section 3.2)
+    pub fn parse_rrsig_time(&self, value: &str) -> Option<DateTime<Utc>> {
+        if value.len() == 14 {
+            NaiveDateTime::parse_from_str(value, "%Y%m%d%H%M%S")
+                .ok()
+                .map(|dt| dt.and_utc())
+        } else {
+            value
+                .parse::<i64>()
+                .ok()
+                .and_then(|secs| DateTime::from_timestamp(secs, 0))
+        }
+    }
+
+    // Warnings for signatures in the chain that are expired, expire within warn_days,
+    // or aren't valid yet - expired RRSIGs are the most common cause of DNSSEC outages
+    pub fn rrsig_expiry_warnings(
+        &self,
+        chain: &[ZoneData],
+        now: DateTime<Utc>,
+        warn_days: u32,
+    ) -> Vec<String> {
+        let mut warnings = Vec::new();
+        let threshold = now + Duration::days(warn_days as i64);
+
+        for zone in chain {
+            for rrsig in &zone.rrsig_records {
+                let label = format!(
+                    "RRSIG over {} in {} (key tag {})",
+                    rrsig.type_covered, zone.zone_name, rrsig.key_tag
+                );
+
+                if let Some(expires_at) = rrsig.expires_at {
+                    if expires_at <= now {
+                        warnings.push(format!(
+                            "{} expired on {}",
+                            label,
+                            expires_at.format("%Y-%m-%d %H:%M UTC")
+                        ));
+                    } else if expires_at <= threshold {
+                        let hours = (expires_at - now).num_hours();
+                        warnings.push(format!(
+                            "{} expires in {} on {}",
+                            label,
+                            if hours < 48 {
+                                format!("{} hours", hours)
+                            } else {
+                                format!("{} days", hours / 24)
+                            },
+                            expires_at.format("%Y-%m-%d %H:%M UTC")
+                        ));
+                    }
+                }
+
+                if let Some(inception_at) = rrsig.inception_at {
+                    if inception_at > now {
+                        warnings.push(format!(
+                            "{} is not valid until {} (check signer clock)",
+                            label,
+                            inception_at.format("%Y-%m-%d %H:%M UTC")
+                        ));
+                    }
+                }
+            }
+        }
+
+        warnings
+    }
+
+    // Query an RRset together with its RRSIGs from one of the zone's nameservers
+    pub async fn query_signed_rrset(
+        &self,
+        domain: &str,
+        record_type: &str,
+        nameserver: &str,
+    ) -> Result<DnsResponse, String> {
+        if !self.is_dig_available() {
+            return Err(NO_DNS_TOOL.to_string());
+        }
+
+        // Kept apart from plain answers for the same name/type
+        let cache_type = format!("{}+DNSSEC", record_type);
+        if let Some(cached) = self.cached_response(domain, &cache_type, nameserver) {
+            return Ok(cached);
+        }
+
+        let args = vec![
+            "+norec".to_string(),
+            "+noall".to_string(),
+            "+comments".to_string(),
+            "+answer".to_string(),
+            "+dnssec".to_string(),
+            format!("@{}", nameserver),
+            record_type.to_string(),
+            domain.to_string(),
+        ];
+
+        let output = self.run_dig(args, domain).await?;
+        let stdout = output.stdout;
+
+        if !output.success && !stdout.lines().any(|l| l.contains(" IN ")) {
+            let reason = if !output.stderr.is_empty() {
+                output.stderr
+            } else {
+                stdout
+            };
+            return Err(format!("dig command failed: {}", reason.trim()));
+        }
+
+        let records = self
+            .parse_dig_output(&stdout, record_type)
+            .unwrap_or_else(|_| Vec::new());
+
+        let response = DnsResponse {
+            records,
+            query_time: output.query_time,
+            resolver: nameserver.to_string(),
+            header: self.parse_dig_header(&stdout),
+            transport: output.transport,
+            server: output.server,
+            ip_version: output.ip_version,
+            from_cache: false,
+            raw_output: Some(stdout),
+        };
+
+        self.cache_response(domain, &cache_type, nameserver, &response);
+
+        Ok(response)
+    }
+
+    // Check which zone keys have a usable RRSIG over an RRset
+    // A signature counts when it covers the type, is made by the zone, is inside its
+    // validity window, and its key tag and algorithm match one of the zone's DNSKEYs.
+    // Returns the covering RRSIGs, the key tags that sign the RRset, and warnings
+    pub fn evaluate_rrset_signatures(
+        &self,
+        domain: &str,
+        record_type: &str,
+        zone: &str,
+        records: &[DnsRecord],
+        dnskeys: &[DnskeyRecord],
+        now: DateTime<Utc>,
+    ) -> (Vec<RrsigRecord>, Vec<u16>, Vec<String>) {
+        let mut warnings = Vec::new();
+        let zone = zone.trim_end_matches('.').to_lowercase();
+        let name_labels = domain.trim_end_matches('.').split('.').count();
+
+        let rrsigs: Vec<RrsigRecord> = self
+            .parse_rrsig_records(records)
+            .into_iter()
+            .filter(|r| r.type_covered.eq_ignore_ascii_case(record_type))
+            .collect();
+
+        if rrsigs.is_empty() {
+            warnings.push(format!(
+                "No RRSIG covers the {} RRset of {}",
+                record_type, domain
+            ));
+        }
+
+        let mut signing_key_tags = Vec::new();
+        let mut wildcard = false;
+        for rrsig in &rrsigs {
+            let label = format!("RRSIG over {} (key tag {})", record_type, rrsig.key_tag);
+
+            if rrsig.signer_name.trim_end_matches('.').to_lowercase() != zone {
+                warnings.push(format!(
+                    "{} is signed by {}, expected {}",
+                    label, rrsig.signer_name, zone
+                ));
+                continue;
+            }
+
+            match (rrsig.inception_at, rrsig.expires_at) {
+                (Some(inception_at), _) if inception_at > now => {
+                    warnings.push(format!(
+                        "{} is not valid until {}",
+                        label,
+                        inception_at.format("%Y-%m-%d %H:%M UTC")
+                    ));
+                    continue;
+                }
+                (_, Some(expires_at)) if expires_at <= now => {
+                    warnings.push(format!(
+                        "{} expired on {}",
+                        label,
+                        expires_at.format("%Y-%m-%d %H:%M UTC")
+                    ));
+                    continue;
+                }
+                (None, _) | (_, None) => {
+                    warnings.push(format!("{} has an unreadable validity period", label));
+                    continue;
+                }
+                _ => {}
+            }
+
+            let key_found = dnskeys
+                .iter()
+                .any(|k| k.key_tag == rrsig.key_tag && k.algorithm == rrsig.algorithm);
+            if !key_found {
+                warnings.push(format!(
+                    "{} doesn't match any DNSKEY (algorithm {}) in {}",
+                    label, rrsig.algorithm_name, zone
+                ));
+                continue;
+            }
+
+            // Fewer labels than the owner name means the answer came from a wildcard
+            if (rrsig.labels as usize) < name_labels {
+                wildcard = true;
+            }
+
+            if !signing_key_tags.contains(&rrsig.key_tag) {
+                signing_key_tags.push(rrsig.key_tag);
+            }
+        }
+
+        if wildcard {
+            warnings.push(format!(
+                "{} {} was synthesized from a wildcard",
+                domain, record_type
+            ));
+        }
+
+        (rrsigs, signing_key_tags, warnings)
+    }
+
     // Parse RRSIG records from DNS records
     pub fn parse_rrsig_records(&self, records: &[DnsRecord]) -> Vec<RrsigRecord> {
         records
@@ -532,13 +3417,17 @@ impl DnsAdapter {
                 let cleaned_value = r.value.replace('(', "").replace(')', "");
                 let parts: Vec<&str> = cleaned_value.split_whitespace().collect();
                 if parts.len() >= 9 {
+                    let algorithm = parts[1].parse::<u8>().ok()?;
                     Some(RrsigRecord {
                         type_covered: parts[0].to_string(),
-                        algorithm: parts[1].parse::<u8>().ok()?,
+                        algorithm,
+                        algorithm_name: self.algorithm_name(algorithm).to_string(),
                         labels: parts[2].parse::<u8>().ok()?,
                         original_ttl: parts[3].parse::<u32>().ok()?,
                         signature_expiration: parts[4].to_string(),
                         signature_inception: parts[5].to_string(),
+                        expires_at: self.parse_rrsig_time(parts[4]),
+                        inception_at: self.parse_rrsig_time(parts[5]),
                         key_tag: parts[6].parse::<u16>().ok()?,
                         signer_name: parts[7].to_string(),
                         signature: parts[8..].join(" "),
@@ -552,6 +3441,7 @@ impl DnsAdapter {
 }
 
 #[cfg(test)]
+#[path = "dns_test.rs"]
 mod tests;
 
 #[cfg(test)]
@@ -1,305 +1,1825 @@
-#[cfg(test)]
-mod tests {
-    use super::super::dns::DnsAdapter;
-    use crate::models::dns::{DnsRecord, DnsResponse};
-
-    #[test]
-    fn test_parse_dig_output_single_a_record() {
-        let adapter = DnsAdapter::new();
-        let output = "example.com.		3600	IN	A	93.184.216.34";
-
-        let result = adapter.parse_dig_output(output, "A");
-        assert!(result.is_ok());
-
-        let records = result.unwrap();
-        assert_eq!(records.len(), 1);
-        assert_eq!(records[0].name, "example.com.");
-        assert_eq!(records[0].record_type, "A");
-        assert_eq!(records[0].value, "93.184.216.34");
-        assert_eq!(records[0].ttl, 3600);
-    }
+use super::super::backend::{BackendFuture, Backends, DnsBackend, ProcessOutput};
+use super::super::test_support::ZoneDig;
+use super::DnsAdapter;
+use crate::models::dns::{
+    AddressSample, DnsHeader, DnsRecord, DnsResponse, DnskeyRecord, DsRecord, HygieneIssue,
+    NameserverAddress, NameserverAnswer, NameserverNetwork, NameserverStatus, RecordTtl, SoaRecord,
+    ZoneData,
+};
+use crate::models::settings::root_trust_anchors;
+use std::sync::{Arc, Mutex};
+
+#[test]
+fn test_parse_dig_output_single_a_record() {
+    let adapter = DnsAdapter::new();
+    let output = "example.com.		3600	IN	A	93.184.216.34";
+
+    let result = adapter.parse_dig_output(output, "A");
+    assert!(result.is_ok());
 
-    #[test]
-    fn test_parse_dig_output_multiple_a_records() {
-        let adapter = DnsAdapter::new();
-        let output = r#"example.com.		300	IN	A	93.184.216.34
+    let records = result.unwrap();
+    assert_eq!(records.len(), 1);
+    assert_eq!(records[0].name, "example.com.");
+    assert_eq!(records[0].record_type, "A");
+    assert_eq!(records[0].value, "93.184.216.34");
+    assert_eq!(records[0].ttl, 3600);
+}
+
+#[test]
+fn test_parse_dig_output_multiple_a_records() {
+    let adapter = DnsAdapter::new();
+    let output = r#"example.com.		300	IN	A	93.184.216.34
 example.com.		300	IN	A	93.184.216.35"#;
 
-        let result = adapter.parse_dig_output(output, "A");
-        assert!(result.is_ok());
+    let result = adapter.parse_dig_output(output, "A");
+    assert!(result.is_ok());
 
-        let records = result.unwrap();
-        assert_eq!(records.len(), 2);
-        assert_eq!(records[0].value, "93.184.216.34");
-        assert_eq!(records[1].value, "93.184.216.35");
-    }
+    let records = result.unwrap();
+    assert_eq!(records.len(), 2);
+    assert_eq!(records[0].value, "93.184.216.34");
+    assert_eq!(records[1].value, "93.184.216.35");
+}
 
-    #[test]
-    fn test_parse_dig_output_mx_records() {
-        let adapter = DnsAdapter::new();
-        let output = r#"example.com.		3600	IN	MX	10 mail.example.com.
+#[test]
+fn test_parse_dig_output_mx_records() {
+    let adapter = DnsAdapter::new();
+    let output = r#"example.com.		3600	IN	MX	10 mail.example.com.
 example.com.		3600	IN	MX	20 mail2.example.com."#;
 
-        let result = adapter.parse_dig_output(output, "MX");
-        assert!(result.is_ok());
+    let result = adapter.parse_dig_output(output, "MX");
+    assert!(result.is_ok());
 
-        let records = result.unwrap();
-        assert_eq!(records.len(), 2);
-        assert_eq!(records[0].value, "10 mail.example.com.");
-        assert_eq!(records[1].value, "20 mail2.example.com.");
-    }
+    let records = result.unwrap();
+    assert_eq!(records.len(), 2);
+    assert_eq!(records[0].value, "10 mail.example.com.");
+    assert_eq!(records[1].value, "20 mail2.example.com.");
+}
 
-    #[test]
-    fn test_parse_dig_output_txt_records() {
-        let adapter = DnsAdapter::new();
-        let output = r#"example.com.		3600	IN	TXT	"v=spf1 include:_spf.example.com ~all""#;
+#[test]
+fn test_parse_dig_output_txt_records() {
+    let adapter = DnsAdapter::new();
+    let output = r#"example.com.		3600	IN	TXT	"v=spf1 include:_spf.example.com ~all""#;
 
-        let result = adapter.parse_dig_output(output, "TXT");
-        assert!(result.is_ok());
+    let result = adapter.parse_dig_output(output, "TXT");
+    assert!(result.is_ok());
 
-        let records = result.unwrap();
-        assert_eq!(records.len(), 1);
-        assert!(records[0].value.contains("v=spf1"));
-    }
+    let records = result.unwrap();
+    assert_eq!(records.len(), 1);
+    assert!(records[0].value.contains("v=spf1"));
+}
 
-    #[test]
-    fn test_parse_dig_output_ns_records() {
-        let adapter = DnsAdapter::new();
-        let output = r#"example.com.		86400	IN	NS	ns1.example.com.
+#[test]
+fn test_parse_dig_output_ns_records() {
+    let adapter = DnsAdapter::new();
+    let output = r#"example.com.		86400	IN	NS	ns1.example.com.
 example.com.		86400	IN	NS	ns2.example.com."#;
 
-        let result = adapter.parse_dig_output(output, "NS");
-        assert!(result.is_ok());
+    let result = adapter.parse_dig_output(output, "NS");
+    assert!(result.is_ok());
+
+    let records = result.unwrap();
+    assert_eq!(records.len(), 2);
+    assert_eq!(records[0].value, "ns1.example.com.");
+    assert_eq!(records[1].value, "ns2.example.com.");
+}
+
+#[test]
+fn test_parse_dig_output_aaaa_records() {
+    let adapter = DnsAdapter::new();
+    let output = "example.com.		3600	IN	AAAA	2606:2800:220:1:248:1893:25c8:1946";
+
+    let result = adapter.parse_dig_output(output, "AAAA");
+    assert!(result.is_ok());
+
+    let records = result.unwrap();
+    assert_eq!(records.len(), 1);
+    assert_eq!(records[0].record_type, "AAAA");
+    assert_eq!(records[0].value, "2606:2800:220:1:248:1893:25c8:1946");
+}
+
+#[test]
+fn test_parse_dig_output_empty() {
+    let adapter = DnsAdapter::new();
+    let output = "";
+
+    let result = adapter.parse_dig_output(output, "A");
+    assert!(result.is_err());
+}
+
+#[test]
+fn test_parse_dig_output_with_comments() {
+    let adapter = DnsAdapter::new();
+    let output = r#"; <<>> DiG 9.10.6 <<>>
+;; ANSWER SECTION:
+example.com.		3600	IN	A	93.184.216.34"#;
+
+    let result = adapter.parse_dig_output(output, "A");
+    assert!(result.is_ok());
+
+    let records = result.unwrap();
+    assert_eq!(records.len(), 1);
+    assert_eq!(records[0].value, "93.184.216.34");
+}
+
+#[test]
+fn test_parse_dnskey_records() {
+    let adapter = DnsAdapter::new();
+    let records = vec![
+        DnsRecord {
+            name: "example.com.".to_string(),
+            record_type: "DNSKEY".to_string(),
+            value: "257 3 8 AwEAAa...base64key... ; key id = 5116".to_string(),
+            ttl: 3600,
+        },
+        DnsRecord {
+            name: "example.com.".to_string(),
+            record_type: "DNSKEY".to_string(),
+            value: "256 3 8 AwEAAb...base64key... ; key id = 12345".to_string(),
+            ttl: 3600,
+        },
+    ];
+
+    let dnskey_records = adapter.parse_dnskey_records(&records);
+    assert_eq!(dnskey_records.len(), 2);
+
+    // KSK (Key Signing Key) - flags 257
+    assert_eq!(dnskey_records[0].flags, 257);
+    assert_eq!(dnskey_records[0].protocol, 3);
+    assert_eq!(dnskey_records[0].algorithm, 8);
+    assert_eq!(dnskey_records[0].key_tag, 5116);
+    assert!(dnskey_records[0]
+        .public_key
+        .contains("AwEAAa...base64key..."));
+
+    // ZSK (Zone Signing Key) - flags 256
+    assert_eq!(dnskey_records[1].flags, 256);
+    assert_eq!(dnskey_records[1].key_tag, 12345);
+}
+
+#[test]
+fn test_parse_dnskey_records_without_key_tag() {
+    let adapter = DnsAdapter::new();
+    let records = vec![DnsRecord {
+        name: "example.com.".to_string(),
+        record_type: "DNSKEY".to_string(),
+        value: "257 3 8 AwEAAa...base64key...".to_string(),
+        ttl: 3600,
+    }];
+
+    let dnskey_records = adapter.parse_dnskey_records(&records);
+    assert_eq!(dnskey_records.len(), 1);
+    // Should fall back to flags as key tag
+    assert_eq!(dnskey_records[0].key_tag, 257);
+}
+
+#[test]
+fn test_parse_ds_records() {
+    let adapter = DnsAdapter::new();
+    let records = vec![
+        DnsRecord {
+            name: "example.com.".to_string(),
+            record_type: "DS".to_string(),
+            value: "5116 8 2 ABC123DEF456...".to_string(),
+            ttl: 86400,
+        },
+        DnsRecord {
+            name: "example.com.".to_string(),
+            record_type: "DS".to_string(),
+            value: "12345 8 1 789ABC...".to_string(),
+            ttl: 86400,
+        },
+    ];
+
+    let ds_records = adapter.parse_ds_records(&records);
+    assert_eq!(ds_records.len(), 2);
 
-        let records = result.unwrap();
-        assert_eq!(records.len(), 2);
-        assert_eq!(records[0].value, "ns1.example.com.");
-        assert_eq!(records[1].value, "ns2.example.com.");
+    assert_eq!(ds_records[0].key_tag, 5116);
+    assert_eq!(ds_records[0].algorithm, 8);
+    assert_eq!(ds_records[0].digest_type, 2);
+    assert_eq!(ds_records[0].digest, "ABC123DEF456...");
+
+    assert_eq!(ds_records[1].key_tag, 12345);
+    assert_eq!(ds_records[1].algorithm, 8);
+    assert_eq!(ds_records[1].digest_type, 1);
+}
+
+#[test]
+fn test_parse_rrsig_records() {
+    let adapter = DnsAdapter::new();
+    let records = vec![DnsRecord {
+        name: "example.com.".to_string(),
+        record_type: "RRSIG".to_string(),
+        value: "A 8 2 300 20250115000000 20250101000000 12345 example.com. ABC123==".to_string(),
+        ttl: 300,
+    }];
+
+    let rrsig_records = adapter.parse_rrsig_records(&records);
+    assert_eq!(rrsig_records.len(), 1);
+
+    assert_eq!(rrsig_records[0].type_covered, "A");
+    assert_eq!(rrsig_records[0].algorithm, 8);
+    assert_eq!(rrsig_records[0].labels, 2);
+    assert_eq!(rrsig_records[0].original_ttl, 300);
+    assert_eq!(rrsig_records[0].signature_expiration, "20250115000000");
+    assert_eq!(rrsig_records[0].signature_inception, "20250101000000");
+    assert_eq!(rrsig_records[0].key_tag, 12345);
+    assert_eq!(rrsig_records[0].signer_name, "example.com.");
+    assert_eq!(rrsig_records[0].signature, "ABC123==");
+}
+
+#[test]
+fn test_parse_rrsig_records_multiline() {
+    let adapter = DnsAdapter::new();
+    let records = vec![DnsRecord {
+        name: "example.com.".to_string(),
+        record_type: "RRSIG".to_string(),
+        value: "A 8 2 300 ( 20250115000000 20250101000000 12345 example.com. ABC123== )"
+            .to_string(),
+        ttl: 300,
+    }];
+
+    let rrsig_records = adapter.parse_rrsig_records(&records);
+    assert_eq!(rrsig_records.len(), 1);
+    assert_eq!(rrsig_records[0].type_covered, "A");
+}
+
+#[test]
+fn test_parse_dig_output_multiline_dnskey() {
+    let adapter = DnsAdapter::new();
+    let output = r#"example.com.		3600	IN	DNSKEY	257 3 8 ( AwEAAa8GMxKnN0wpBW5qfH6W ; KSK; alg = RSASHA256 ; key id = 5116
+                              Vh+D8gMZCEANdBlQ2jYw ) ; {key id = 5116}"#;
+
+    let result = adapter.parse_dig_output(output, "DNSKEY");
+    assert!(result.is_ok());
+
+    let records = result.unwrap();
+    assert_eq!(records.len(), 1);
+    assert_eq!(records[0].record_type, "DNSKEY");
+    // Should capture the entire value including continuation lines
+    assert!(records[0].value.contains("257 3 8"));
+}
+
+#[test]
+fn test_parse_dig_output_cname() {
+    let adapter = DnsAdapter::new();
+    let output = "www.example.com.		3600	IN	CNAME	example.com.";
+
+    let result = adapter.parse_dig_output(output, "CNAME");
+    assert!(result.is_ok());
+
+    let records = result.unwrap();
+    assert_eq!(records.len(), 1);
+    assert_eq!(records[0].record_type, "CNAME");
+    assert_eq!(records[0].value, "example.com.");
+}
+
+#[test]
+fn test_parse_dig_output_soa() {
+    let adapter = DnsAdapter::new();
+    let output = "example.com.		3600	IN	SOA	ns1.example.com. admin.example.com. 2025010101 3600 900 604800 86400";
+
+    let result = adapter.parse_dig_output(output, "SOA");
+    assert!(result.is_ok());
+
+    let records = result.unwrap();
+    assert_eq!(records.len(), 1);
+    assert_eq!(records[0].record_type, "SOA");
+    assert!(records[0].value.contains("ns1.example.com."));
+}
+
+#[test]
+fn test_extract_ns_names_from_referral() {
+    let adapter = DnsAdapter::new();
+    let output = r#"example.com.		172800	IN	NS	B.IANA-SERVERS.NET.
+example.com.		172800	IN	NS	a.iana-servers.net.
+example.com.		172800	IN	NS	a.iana-servers.net.
+other.com.		172800	IN	NS	ns1.other.com."#;
+
+    let records = adapter.parse_dig_output(output, "NS").unwrap();
+    let names = adapter.extract_ns_names(&records, "Example.com.");
+
+    // Lowercased, trailing dot removed, sorted, de-duplicated, other owners ignored
+    assert_eq!(names, vec!["a.iana-servers.net", "b.iana-servers.net"]);
+}
+
+#[test]
+fn test_parse_soa_records() {
+    let adapter = DnsAdapter::new();
+    let output = "example.com.\t3600\tIN\tSOA\tns.icann.org. noc.dns.icann.org. 2024081466 7200 3600 1209600 3600";
+    let records = adapter.parse_dig_output(output, "SOA").unwrap();
+
+    let soa = adapter.parse_soa_records(&records);
+    assert_eq!(
+        soa,
+        [SoaRecord {
+            mname: "ns.icann.org".to_string(),
+            rname: "noc.dns.icann.org".to_string(),
+            serial: 2024081466,
+            refresh: 7200,
+            retry: 3600,
+            expire: 1209600,
+            minimum: 3600,
+            ttl: 3600,
+        }]
+    );
+
+    let truncated = adapter
+        .parse_dig_output(
+            "example.com. 3600 IN SOA ns.icann.org. noc.dns.icann.org. 1 2",
+            "SOA",
+        )
+        .unwrap();
+    assert!(adapter.parse_soa_records(&truncated).is_empty());
+}
+
+#[test]
+fn test_parse_sshfp_records() {
+    let adapter = DnsAdapter::new();
+    let output = "host.example.com.\t3600\tIN\tSSHFP\t4 2 F83898DF0BEF57A4EE24985BA598AC17 FCCB0C0D333CC4AF1DD92BE14BC23AA5
+host.example.com.\t3600\tIN\tSSHFP\t1 1 3A2B6C1D9E0F11223344556677889900AABBCCDD
+host.example.com.\t3600\tIN\tSSHFP\t9 2 not-hex";
+    let records = adapter.parse_dig_output(output, "SSHFP").unwrap();
+
+    let sshfp = adapter.parse_sshfp_records(&records);
+    assert_eq!(sshfp.len(), 2);
+    assert_eq!(sshfp[0].algorithm, 4);
+    assert_eq!(sshfp[0].algorithm_name, "Ed25519");
+    assert_eq!(sshfp[0].fp_type_name, "SHA-256");
+    assert_eq!(
+        sshfp[0].fingerprint,
+        "f83898df0bef57a4ee24985ba598ac17fccb0c0d333cc4af1dd92be14bc23aa5"
+    );
+    assert_eq!(sshfp[0].matched, None);
+    assert_eq!(sshfp[1].algorithm_name, "RSA");
+    assert_eq!(sshfp[1].fp_type_name, "SHA-1");
+}
+
+#[test]
+fn test_describe_seconds() {
+    let adapter = DnsAdapter::new();
+    assert_eq!(adapter.describe_seconds(1), "1 second");
+    assert_eq!(adapter.describe_seconds(0), "0 seconds");
+    assert_eq!(adapter.describe_seconds(5400), "90 minutes");
+    assert_eq!(adapter.describe_seconds(172800), "2 days");
+    assert_eq!(adapter.describe_seconds(604800), "1 week");
+}
+
+fn soa(refresh: u32, retry: u32, expire: u32, minimum: u32) -> SoaRecord {
+    SoaRecord {
+        mname: "ns1.example.com".to_string(),
+        rname: "hostmaster.example.com".to_string(),
+        serial: 1,
+        refresh,
+        retry,
+        expire,
+        minimum,
+        ttl: 3600,
     }
+}
+
+fn issue_codes(issues: &[HygieneIssue]) -> Vec<&str> {
+    issues.iter().map(|i| i.code.as_str()).collect()
+}
+
+#[test]
+fn test_record_ttls_and_sane_values() {
+    let adapter = DnsAdapter::new();
+    let output = r#"example.com.		300	IN	A	192.0.2.1
+example.com.		120	IN	A	192.0.2.2
+example.com.		3600	IN	MX	10 mail.example.com.
+www.example.com.	1	IN	A	192.0.2.3"#;
+    let records = adapter.parse_dig_output(output, "A").unwrap();
+
+    let ttls = adapter.record_ttls("example.com.", &records);
+    let summary: Vec<(&str, u32)> = ttls
+        .iter()
+        .map(|t| (t.record_type.as_str(), t.ttl))
+        .collect();
+    assert_eq!(summary, [("A", 120), ("MX", 3600)]);
+
+    let issues = adapter.analyze_ttls("example.com", Some(&soa(7200, 3600, 1209600, 3600)), &ttls);
+    assert!(issues.is_empty());
+}
+
+#[test]
+fn test_analyze_ttls_extremes() {
+    let adapter = DnsAdapter::new();
+    let ttls = |entries: &[(&str, u32)]| {
+        entries
+            .iter()
+            .map(|(record_type, ttl)| RecordTtl {
+                record_type: record_type.to_string(),
+                ttl: *ttl,
+            })
+            .collect::<Vec<_>>()
+    };
 
-    #[test]
-    fn test_parse_dig_output_aaaa_records() {
-        let adapter = DnsAdapter::new();
-        let output = "example.com.		3600	IN	AAAA	2606:2800:220:1:248:1893:25c8:1946";
+    let issues = adapter.analyze_ttls(
+        "example.com",
+        None,
+        &ttls(&[("A", 1), ("AAAA", 604800), ("MX", 604800), ("TXT", 2419200)]),
+    );
+    assert_eq!(
+        issue_codes(&issues),
+        ["ttl_too_low", "ttl_too_high", "ttl_above_cap"]
+    );
+    assert!(issues[0].message.contains("TTL of 1 second"));
+    assert!(issues[1].message.contains("AAAA records"));
 
-        let result = adapter.parse_dig_output(output, "AAAA");
-        assert!(result.is_ok());
+    let issues = adapter.analyze_ttls("example.com", Some(&soa(86400, 7200, 3600, 30)), &[]);
+    assert_eq!(
+        issue_codes(&issues),
+        ["soa_expire_below_refresh", "negative_ttl_low"]
+    );
+    assert_eq!(issues[0].severity, "error");
 
-        let records = result.unwrap();
-        assert_eq!(records.len(), 1);
-        assert_eq!(records[0].record_type, "AAAA");
-        assert_eq!(records[0].value, "2606:2800:220:1:248:1893:25c8:1946");
+    let mut long_negative = soa(3600, 7200, 86400, 172800);
+    long_negative.ttl = 172800;
+    let issues = adapter.analyze_ttls("example.com", Some(&long_negative), &[]);
+    assert_eq!(
+        issue_codes(&issues),
+        [
+            "soa_expire_low",
+            "soa_retry_above_refresh",
+            "negative_ttl_high"
+        ]
+    );
+    assert!(issues[2].message.contains("2 days"));
+
+    // Negative caching uses the lower of the SOA's TTL and minimum
+    let issues = adapter.analyze_ttls("example.com", Some(&soa(7200, 3600, 1209600, 172800)), &[]);
+    assert!(issues.is_empty());
+}
+
+fn address_sample(resolver: &str, round: u32, addresses: &[&str]) -> AddressSample {
+    AddressSample {
+        resolver: resolver.to_string(),
+        round,
+        record_type: "A".to_string(),
+        addresses: addresses.iter().map(|a| a.to_string()).collect(),
+        ttl: Some(60),
+        error: None,
     }
+}
+
+#[test]
+fn test_analyze_load_balancing() {
+    let adapter = DnsAdapter::new();
+    let resolvers = ["1.1.1.1".to_string(), "8.8.8.8".to_string()];
+
+    let report = adapter.analyze_load_balancing(
+        "example.com",
+        &resolvers,
+        2,
+        vec![
+            address_sample("1.1.1.1", 1, &["192.0.2.1"]),
+            address_sample("8.8.8.8", 1, &["192.0.2.1"]),
+            address_sample("1.1.1.1", 2, &["192.0.2.1"]),
+            address_sample("8.8.8.8", 2, &["192.0.2.1"]),
+        ],
+    );
+    assert!(!report.round_robin && !report.changes_over_time && !report.resolver_dependent);
+    assert_eq!(
+        report.findings,
+        ["Every resolver returned the same address each time: 192.0.2.1"]
+    );
 
-    #[test]
-    fn test_parse_dig_output_empty() {
-        let adapter = DnsAdapter::new();
-        let output = "";
+    let report = adapter.analyze_load_balancing(
+        "example.com",
+        &resolvers,
+        2,
+        vec![
+            address_sample("1.1.1.1", 1, &["192.0.2.1", "192.0.2.2"]),
+            address_sample("8.8.8.8", 1, &["192.0.2.2", "192.0.2.1"]),
+            address_sample("1.1.1.1", 2, &["192.0.2.2", "192.0.2.1"]),
+            address_sample("8.8.8.8", 2, &["192.0.2.2", "192.0.2.1"]),
+        ],
+    );
+    assert!(report.round_robin);
+    assert!(!report.changes_over_time && !report.resolver_dependent);
+    assert_eq!(report.findings.len(), 2);
 
-        let result = adapter.parse_dig_output(output, "A");
-        assert!(result.is_err());
+    let mut failed = address_sample("8.8.8.8", 2, &[]);
+    failed.error = Some("SERVFAIL".to_string());
+    let report = adapter.analyze_load_balancing(
+        "example.com",
+        &resolvers,
+        2,
+        vec![
+            address_sample("1.1.1.1", 1, &["192.0.2.1"]),
+            address_sample("8.8.8.8", 1, &["198.51.100.1"]),
+            address_sample("1.1.1.1", 2, &["192.0.2.9"]),
+            failed,
+        ],
+    );
+    assert!(!report.round_robin);
+    assert!(report.changes_over_time);
+    assert!(report.resolver_dependent);
+    assert_eq!(report.addresses, ["192.0.2.1", "192.0.2.9", "198.51.100.1"]);
+    assert!(report.findings[0].contains("(TTL 1 minute)"));
+    assert!(report
+        .findings
+        .last()
+        .unwrap()
+        .contains("intentional load balancing"));
+}
+
+#[test]
+fn test_subnet() {
+    let adapter = DnsAdapter::new();
+    assert_eq!(
+        adapter.subnet("192.0.2.53".parse().unwrap()),
+        "192.0.2.0/24"
+    );
+    assert_eq!(
+        adapter.subnet("2001:db8:aa:1::53".parse().unwrap()),
+        "2001:db8:aa::/48"
+    );
+}
+
+fn network(
+    nameserver: &str,
+    addresses: &[(&str, Option<u32>)],
+    reachable: bool,
+) -> NameserverNetwork {
+    let adapter = DnsAdapter::new();
+    NameserverNetwork {
+        nameserver: nameserver.to_string(),
+        addresses: addresses
+            .iter()
+            .map(|(address, asn)| NameserverAddress {
+                address: address.to_string(),
+                subnet: adapter.subnet(address.parse().unwrap()),
+                asn: *asn,
+                as_name: asn.map(|asn| format!("AS-{}", asn)),
+                announced_prefix: None,
+            })
+            .collect(),
+        reachable,
+        error: (!reachable).then(|| "timed out".to_string()),
     }
+}
+
+#[test]
+fn test_analyze_ns_diversity_spread() {
+    let adapter = DnsAdapter::new();
+    let diversity = adapter.analyze_ns_diversity(
+        "www.example.com",
+        "example.com",
+        vec![
+            network(
+                "ns1.example.com",
+                &[("192.0.2.1", Some(64500)), ("2001:db8:1::1", Some(64500))],
+                true,
+            ),
+            network(
+                "ns2.example.net",
+                &[
+                    ("198.51.100.1", Some(64501)),
+                    ("2001:db8:2::1", Some(64501)),
+                ],
+                true,
+            ),
+        ],
+    );
+
+    assert!(diversity.is_diverse);
+    assert!(diversity.warnings.is_empty());
+    assert_eq!(diversity.asns, [64500, 64501]);
+    assert_eq!(diversity.subnets.len(), 4);
+    assert_eq!(diversity.reachable, 2);
+}
+
+#[test]
+fn test_analyze_ns_diversity_single_network() {
+    let adapter = DnsAdapter::new();
+    let diversity = adapter.analyze_ns_diversity(
+        "example.com",
+        "example.com",
+        vec![
+            network("ns1.example.com", &[("192.0.2.1", Some(64500))], true),
+            network("ns2.example.com", &[("192.0.2.2", Some(64500))], true),
+            network("ns3.example.com", &[("192.0.2.3", Some(64500))], false),
+        ],
+    );
+
+    assert!(!diversity.is_diverse);
+    assert_eq!(diversity.subnets, ["192.0.2.0/24"]);
+    assert_eq!(diversity.warnings.len(), 3);
+    assert!(diversity.warnings[0].starts_with("ns3.example.com did not answer"));
+    assert!(diversity.warnings[1].contains("AS64500 (AS-64500)"));
+    assert!(diversity.warnings[2].contains("192.0.2.0/24"));
+}
+
+#[test]
+fn test_analyze_ns_diversity_unreachable() {
+    let adapter = DnsAdapter::new();
+    let diversity = adapter.analyze_ns_diversity(
+        "example.com",
+        "example.com",
+        vec![
+            network("ns1.example.com", &[("192.0.2.1", Some(64500))], true),
+            network("ns2.example.net", &[("198.51.100.1", None)], false),
+        ],
+    );
+
+    // An unannounced address isn't counted as a second AS, nor as the same one
+    assert!(!diversity.is_diverse);
+    assert_eq!(diversity.reachable, 1);
+    assert!(diversity
+        .warnings
+        .iter()
+        .any(|w| w.starts_with("Only 1 of 2")));
+    assert!(diversity
+        .warnings
+        .iter()
+        .any(|w| w == "No BGP origin found for 198.51.100.1"));
+    assert!(!diversity
+        .warnings
+        .iter()
+        .any(|w| w.contains("announced by")));
+}
+
+#[test]
+fn test_detect_dns_hosting() {
+    let adapter = DnsAdapter::new();
+    let names = |names: &[&str]| names.iter().map(|n| n.to_string()).collect::<Vec<_>>();
+
+    assert!(adapter.detect_dns_hosting(&[]).is_none());
 
-    #[test]
-    fn test_parse_dig_output_with_comments() {
-        let adapter = DnsAdapter::new();
-        let output = r#"; <<>> DiG 9.10.6 <<>>
+    // Route 53 spreads one zone's nameservers over several TLDs
+    let hosting = adapter
+        .detect_dns_hosting(&names(&[
+            "ns-1.awsdns-01.org.",
+            "ns-2.awsdns-02.co.uk",
+            "NS-3.AWSDNS-03.com",
+        ]))
+        .unwrap();
+    assert_eq!(hosting.providers.len(), 1);
+    assert_eq!(hosting.providers[0].name, "Amazon Route 53");
+    assert_eq!(hosting.providers[0].nameservers[2], "ns-3.awsdns-03.com");
+    assert!(hosting.unrecognized.is_empty());
+    assert!(!hosting.multi_provider);
+
+    let hosting = adapter
+        .detect_dns_hosting(&names(&["dns1.p01.nsone.net", "kim.ns.cloudflare.com"]))
+        .unwrap();
+    let providers: Vec<&str> = hosting.providers.iter().map(|p| p.name.as_str()).collect();
+    assert_eq!(providers, ["NS1", "Cloudflare"]);
+    assert!(hosting.multi_provider);
+
+    // A lookalike suffix isn't the provider
+    let hosting = adapter
+        .detect_dns_hosting(&names(&["ns1.notgandi.net", "ns2.notgandi.net"]))
+        .unwrap();
+    assert!(hosting.providers.is_empty());
+    assert_eq!(hosting.unrecognized.len(), 2);
+    assert!(!hosting.multi_provider);
+
+    // Self-hosted nameservers alongside a provider as secondary
+    let hosting = adapter
+        .detect_dns_hosting(&names(&["ns1.example.com", "ns1.he.net"]))
+        .unwrap();
+    assert_eq!(hosting.providers[0].name, "Hurricane Electric");
+    assert_eq!(hosting.unrecognized, ["ns1.example.com"]);
+    assert!(hosting.multi_provider);
+}
+
+#[test]
+fn test_parse_dig_header_noerror_with_flags() {
+    let adapter = DnsAdapter::new();
+    let output = r#";; Got answer:
+;; ->>HEADER<<- opcode: QUERY, status: NOERROR, id: 38720
+;; flags: qr rd ra ad; QUERY: 1, ANSWER: 2, AUTHORITY: 0, ADDITIONAL: 1
+
+;; OPT PSEUDOSECTION:
+; EDNS: version: 0, flags:; udp: 1232
 ;; ANSWER SECTION:
-example.com.		3600	IN	A	93.184.216.34"#;
+example.com.		300	IN	A	93.184.216.34
+example.com.		300	IN	A	93.184.216.35"#;
 
-        let result = adapter.parse_dig_output(output, "A");
-        assert!(result.is_ok());
+    let header = adapter.parse_dig_header(output).unwrap();
+    assert_eq!(header.rcode, "NOERROR");
+    assert!(header.recursion_available);
+    assert!(header.authenticated_data);
+    assert!(!header.authoritative);
+    assert!(!header.truncated);
+    assert_eq!(header.answer_count, 2);
+    assert_eq!(header.authority_count, 0);
+    assert_eq!(header.additional_count, 1);
 
-        let records = result.unwrap();
-        assert_eq!(records.len(), 1);
-        assert_eq!(records[0].value, "93.184.216.34");
-    }
+    // Header and section lines must not leak into record values
+    let records = adapter.parse_dig_output(output, "A").unwrap();
+    assert_eq!(records.len(), 2);
+    assert_eq!(records[1].value, "93.184.216.35");
+}
 
-    #[test]
-    fn test_parse_dnskey_records() {
-        let adapter = DnsAdapter::new();
-        let records = vec![
-            DnsRecord {
-                name: "example.com.".to_string(),
-                record_type: "DNSKEY".to_string(),
-                value: "257 3 8 AwEAAa...base64key... ; key id = 5116".to_string(),
-                ttl: 3600,
-            },
-            DnsRecord {
+#[test]
+fn test_parse_dig_header_nxdomain() {
+    let adapter = DnsAdapter::new();
+    let output = r#";; ->>HEADER<<- opcode: QUERY, status: NXDOMAIN, id: 4242
+;; flags: qr aa rd; QUERY: 1, ANSWER: 0, AUTHORITY: 1, ADDITIONAL: 1"#;
+
+    let header = adapter.parse_dig_header(output).unwrap();
+    assert_eq!(header.rcode, "NXDOMAIN");
+    assert!(header.authoritative);
+    assert!(!header.recursion_available);
+    assert_eq!(header.answer_count, 0);
+    assert_eq!(header.authority_count, 1);
+}
+
+#[test]
+fn test_parse_dig_header_truncated() {
+    let adapter = DnsAdapter::new();
+    let output = r#";; ->>HEADER<<- opcode: QUERY, status: NOERROR, id: 1717
+;; flags: qr tc rd ra; QUERY: 1, ANSWER: 0, AUTHORITY: 0, ADDITIONAL: 1"#;
+
+    // The TC bit is what triggers the TCP retry in run_dig
+    let header = adapter.parse_dig_header(output).unwrap();
+    assert!(header.truncated);
+    assert_eq!(header.answer_count, 0);
+}
+
+#[test]
+fn test_parse_dig_header_missing() {
+    let adapter = DnsAdapter::new();
+    let output = ";; connection timed out; no servers could be reached";
+
+    assert!(adapter.parse_dig_header(output).is_none());
+}
+
+#[test]
+fn test_timeout_args_use_default_settings() {
+    let adapter = DnsAdapter::new();
+    let args = adapter.with_timeout_args(vec!["A".to_string(), "example.com".to_string()]);
+
+    assert_eq!(args, vec!["+time=2", "+tries=2", "A", "example.com"]);
+}
+
+#[test]
+fn test_timeout_args_keep_caller_values() {
+    let adapter = DnsAdapter::new();
+    let args = adapter.with_timeout_args(vec!["+time=5".to_string(), "A".to_string()]);
+
+    assert_eq!(args, vec!["+tries=2", "+time=5", "A"]);
+}
+
+#[test]
+fn test_summarize_benchmark_statistics() {
+    let adapter = DnsAdapter::new();
+    let latencies: Vec<f64> = (1..=20).map(|ms| ms as f64).collect();
+
+    let result = adapter.summarize_benchmark("1.1.1.1", &latencies, 5, None);
+
+    assert_eq!(result.queries, 25);
+    assert_eq!(result.failure_rate, 0.2);
+    assert_eq!(result.min_ms, Some(1.0));
+    assert_eq!(result.avg_ms, Some(10.5));
+    assert_eq!(result.p95_ms, Some(19.0));
+}
+
+#[test]
+fn test_summarize_benchmark_all_failed() {
+    let adapter = DnsAdapter::new();
+
+    let result = adapter.summarize_benchmark("192.0.2.1", &[], 4, Some("timed out".to_string()));
+
+    assert_eq!(result.failure_rate, 1.0);
+    assert!(result.avg_ms.is_none());
+    assert!(result.p95_ms.is_none());
+}
+
+#[test]
+fn test_parse_stats_query_time() {
+    let adapter = DnsAdapter::new();
+    let output = "example.com. 300 IN A 93.184.216.34\n\n;; Query time: 23 msec\n;; SERVER: 1.1.1.1#53(1.1.1.1) (UDP)\n";
+
+    assert_eq!(adapter.parse_stats_query_time(output), Some(23.0));
+    assert_eq!(adapter.parse_stats_query_time("no stats"), None);
+}
+
+#[test]
+fn test_parse_dig_server_ipv4_and_ipv6() {
+    let adapter = DnsAdapter::new();
+
+    let v4 = ";; Query time: 12 msec\n;; SERVER: 1.1.1.1#53(1.1.1.1) (UDP)\n";
+    assert_eq!(adapter.parse_dig_server(v4), Some("1.1.1.1".to_string()));
+
+    let v6 = ";; SERVER: 2001:503:ba3e::2:30#53(a.root-servers.net) (TCP)\n";
+    assert_eq!(
+        adapter.parse_dig_server(v6),
+        Some("2001:503:ba3e::2:30".to_string())
+    );
+
+    assert_eq!(adapter.parse_dig_server(";; connection timed out"), None);
+}
+
+#[test]
+fn test_is_valid_resolver() {
+    let adapter = DnsAdapter::new();
+
+    assert!(adapter.is_valid_resolver("system"));
+    assert!(adapter.is_valid_resolver("2606:4700:4700::1111"));
+    assert!(adapter.is_valid_resolver("dns.quad9.net"));
+    assert!(!adapter.is_valid_resolver("-f/etc/passwd"));
+    assert!(!adapter.is_valid_resolver("8.8.8.8 +tcp"));
+    assert!(adapter.is_valid_resolver("https://dns.google/dns-query"));
+    assert!(!adapter.is_valid_resolver("https://dns.google/dns-query?dns=AAAB"));
+}
+
+#[test]
+fn test_resolver_args() {
+    let adapter = DnsAdapter::new();
+
+    assert!(adapter.resolver_args("system").is_empty());
+    assert_eq!(adapter.resolver_args("9.9.9.9"), vec!["@9.9.9.9"]);
+    assert_eq!(
+        adapter.resolver_args("https://cloudflare-dns.com/dns-query"),
+        vec!["@cloudflare-dns.com", "+https=/dns-query"]
+    );
+    assert_eq!(
+        adapter.resolver_args("https://[2620:fe::fe]/"),
+        vec!["@2620:fe::fe", "+https"]
+    );
+}
+
+fn ns_answer(nameserver: &str, values: &[&str], ttl: u32) -> NameserverAnswer {
+    NameserverAnswer {
+        nameserver: nameserver.to_string(),
+        records: values
+            .iter()
+            .map(|v| DnsRecord {
                 name: "example.com.".to_string(),
-                record_type: "DNSKEY".to_string(),
-                value: "256 3 8 AwEAAb...base64key... ; key id = 12345".to_string(),
-                ttl: 3600,
-            },
-        ];
-
-        let dnskey_records = adapter.parse_dnskey_records(&records);
-        assert_eq!(dnskey_records.len(), 2);
-
-        // KSK (Key Signing Key) - flags 257
-        assert_eq!(dnskey_records[0].flags, 257);
-        assert_eq!(dnskey_records[0].protocol, 3);
-        assert_eq!(dnskey_records[0].algorithm, 8);
-        assert_eq!(dnskey_records[0].key_tag, 5116);
-        assert!(dnskey_records[0]
-            .public_key
-            .contains("AwEAAa...base64key..."));
-
-        // ZSK (Zone Signing Key) - flags 256
-        assert_eq!(dnskey_records[1].flags, 256);
-        assert_eq!(dnskey_records[1].key_tag, 12345);
+                record_type: "A".to_string(),
+                value: v.to_string(),
+                ttl,
+            })
+            .collect(),
+        query_time: 0.02,
+        rcode: Some("NOERROR".to_string()),
+        authoritative: true,
+        matches_majority: false,
+        error: None,
     }
+}
 
-    #[test]
-    fn test_parse_dnskey_records_without_key_tag() {
-        let adapter = DnsAdapter::new();
-        let records = vec![DnsRecord {
-            name: "example.com.".to_string(),
-            record_type: "DNSKEY".to_string(),
-            value: "257 3 8 AwEAAa...base64key...".to_string(),
-            ttl: 3600,
-        }];
+#[test]
+fn test_classify_nameserver() {
+    let adapter = DnsAdapter::new();
+    let soa = |nameserver: &str| {
+        let mut answer = ns_answer(
+            nameserver,
+            &["ns1.example.net. hostmaster.example.com. 1 7200 3600 1209600 300"],
+            3600,
+        );
+        answer.records[0].record_type = "SOA".to_string();
+        answer
+    };
+    let status =
+        |answer: NameserverAnswer| adapter.classify_nameserver("example.com.", answer).status;
+
+    assert_eq!(
+        status(soa("ns1.example.net")),
+        NameserverStatus::Authoritative
+    );
+
+    // Answered from cache or a referral: no AA flag
+    let mut cached = soa("ns2.example.net");
+    cached.authoritative = false;
+    assert_eq!(status(cached), NameserverStatus::Lame);
 
-        let dnskey_records = adapter.parse_dnskey_records(&records);
-        assert_eq!(dnskey_records.len(), 1);
-        // Should fall back to flags as key tag
-        assert_eq!(dnskey_records[0].key_tag, 257);
+    // A referral to other servers carries no SOA
+    let mut referral = ns_answer("ns3.example.net", &[], 0);
+    referral.authoritative = false;
+    assert_eq!(status(referral), NameserverStatus::Lame);
+
+    let mut nxdomain = ns_answer("ns4.example.net", &[], 0);
+    nxdomain.rcode = Some("NXDOMAIN".to_string());
+    assert_eq!(status(nxdomain), NameserverStatus::Lame);
+
+    let mut refused = ns_answer("ns5.example.net", &[], 0);
+    refused.rcode = Some("REFUSED".to_string());
+    refused.authoritative = false;
+    assert_eq!(status(refused), NameserverStatus::Refused);
+
+    let mut servfail = ns_answer("ns6.example.net", &[], 0);
+    servfail.rcode = Some("SERVFAIL".to_string());
+    assert_eq!(status(servfail), NameserverStatus::ServFail);
+
+    let mut timeout = ns_answer("ns7.example.net", &[], 0);
+    timeout.rcode = None;
+    timeout.error = Some(
+        "dig command failed: ;; connection timed out; no servers could be reached".to_string(),
+    );
+    assert_eq!(status(timeout), NameserverStatus::Timeout);
+
+    let mut unresolvable = ns_answer("ns8.example.net", &[], 0);
+    unresolvable.rcode = None;
+    unresolvable.error = Some(
+        "dig command failed: couldn't get address for 'ns8.example.net': not found".to_string(),
+    );
+    let health = adapter.classify_nameserver("example.com", unresolvable);
+    assert_eq!(health.status, NameserverStatus::Unreachable);
+    assert!(health.error.unwrap().contains("couldn't get address"));
+}
+
+#[test]
+fn test_compare_answers_consistent() {
+    let adapter = DnsAdapter::new();
+    let answers = vec![
+        ns_answer("ns1.example.net", &["192.0.2.1", "192.0.2.2"], 300),
+        ns_answer("ns2.example.net", &["192.0.2.2", "192.0.2.1"], 300),
+    ];
+
+    let result = adapter.compare_answers("example.com", "A", "example.com", answers);
+
+    assert!(result.is_consistent);
+    assert!(result.differences.is_empty());
+    assert!(result.answers.iter().all(|a| a.matches_majority));
+}
+
+#[test]
+fn test_compare_answers_flags_out_of_sync_nameserver() {
+    let adapter = DnsAdapter::new();
+    let answers = vec![
+        ns_answer("ns1.example.net", &["192.0.2.1"], 300),
+        ns_answer("ns2.example.net", &["192.0.2.1"], 300),
+        ns_answer("ns3.example.org", &["198.51.100.7"], 3600),
+    ];
+
+    let result = adapter.compare_answers("example.com", "A", "example.com", answers);
+
+    assert!(!result.is_consistent);
+    assert!(!result.answers[2].matches_majority);
+    assert!(result.differences[0].starts_with("ns3.example.org returns [198.51.100.7]"));
+    assert!(result
+        .differences
+        .iter()
+        .any(|d| d.starts_with("TTLs differ")));
+}
+
+#[test]
+fn test_compare_answers_reports_failures_and_non_authoritative() {
+    let adapter = DnsAdapter::new();
+    let mut lame = ns_answer("ns2.example.net", &["192.0.2.1"], 300);
+    lame.authoritative = false;
+    let mut failed = ns_answer("ns3.example.net", &[], 0);
+    failed.error = Some("connection timed out".to_string());
+
+    let answers = vec![
+        ns_answer("ns1.example.net", &["192.0.2.1"], 300),
+        lame,
+        failed,
+    ];
+    let result = adapter.compare_answers("example.com", "A", "example.com", answers);
+
+    assert!(!result.is_consistent);
+    assert!(result
+        .differences
+        .iter()
+        .any(|d| d.contains("ns2.example.net answered without the authoritative")));
+    assert!(result
+        .differences
+        .contains(&"ns3.example.net failed: connection timed out".to_string()));
+}
+
+fn record(name: &str, record_type: &str, value: &str) -> DnsRecord {
+    DnsRecord {
+        name: name.to_string(),
+        record_type: record_type.to_string(),
+        value: value.to_string(),
+        ttl: 300,
     }
+}
 
-    #[test]
-    fn test_parse_ds_records() {
-        let adapter = DnsAdapter::new();
-        let records = vec![
-            DnsRecord {
-                name: "example.com.".to_string(),
-                record_type: "DS".to_string(),
-                value: "5116 8 2 ABC123DEF456...".to_string(),
-                ttl: 86400,
-            },
-            DnsRecord {
-                name: "example.com.".to_string(),
-                record_type: "DS".to_string(),
-                value: "12345 8 1 789ABC...".to_string(),
-                ttl: 86400,
-            },
-        ];
-
-        let ds_records = adapter.parse_ds_records(&records);
-        assert_eq!(ds_records.len(), 2);
-
-        assert_eq!(ds_records[0].key_tag, 5116);
-        assert_eq!(ds_records[0].algorithm, 8);
-        assert_eq!(ds_records[0].digest_type, 2);
-        assert_eq!(ds_records[0].digest, "ABC123DEF456...");
-
-        assert_eq!(ds_records[1].key_tag, 12345);
-        assert_eq!(ds_records[1].algorithm, 8);
-        assert_eq!(ds_records[1].digest_type, 1);
+#[test]
+fn test_txt_value_joins_quoted_strings() {
+    let adapter = DnsAdapter::new();
+
+    assert_eq!(
+        adapter.txt_value("\"v=spf1 include:_spf.example.com \" \"~all\""),
+        "v=spf1 include:_spf.example.com ~all"
+    );
+    assert_eq!(adapter.txt_value("unquoted"), "unquoted");
+}
+
+#[test]
+fn test_analyze_hygiene_apex_cname_with_other_data() {
+    let adapter = DnsAdapter::new();
+    let records = vec![
+        record("example.com.", "CNAME", "lb.example.net."),
+        record("example.com.", "MX", "10 mail.example.com."),
+        record("lb.example.net.", "A", "192.0.2.1"),
+    ];
+
+    let (types, issues) = adapter.analyze_hygiene("example.com", "example.com", &records);
+
+    assert_eq!(types, vec!["CNAME", "MX"]);
+    let codes: Vec<&str> = issues.iter().map(|i| i.code.as_str()).collect();
+    assert_eq!(codes, vec!["apex_cname", "cname_with_other_data"]);
+}
+
+#[test]
+fn test_analyze_hygiene_subdomain_cname_is_fine() {
+    let adapter = DnsAdapter::new();
+    let records = vec![
+        record("www.example.com.", "CNAME", "example.com."),
+        record("www.example.com.", "RRSIG", "CNAME 13 3 300 ..."),
+        record("example.com.", "A", "192.0.2.1"),
+    ];
+
+    let (_, issues) = adapter.analyze_hygiene("www.example.com", "example.com", &records);
+
+    assert!(issues.is_empty());
+}
+
+#[test]
+fn test_analyze_hygiene_multiple_spf() {
+    let adapter = DnsAdapter::new();
+    let records = vec![
+        record(
+            "example.com.",
+            "TXT",
+            "\"v=spf1 include:_spf.google.com ~all\"",
+        ),
+        record("example.com.", "TXT", "\"v=spf1 mx -all\""),
+        record("example.com.", "TXT", "\"google-site-verification=abc\""),
+    ];
+
+    let (_, issues) = adapter.analyze_hygiene("example.com", "example.com", &records);
+
+    assert_eq!(issues.len(), 1);
+    assert_eq!(issues[0].code, "multiple_spf");
+}
+
+// Root KSK-2017 and its DS record from the IANA trust anchor
+fn root_ksk() -> DnskeyRecord {
+    DnskeyRecord {
+        flags: 257,
+        protocol: 3,
+        algorithm: 8,
+        algorithm_name: "RSASHA256".to_string(),
+        public_key: "AwEAAaz/tAm8yTn4Mfeh5eyI96WSVexTBAvkMgJzkKTOiW1vkIbzxeF3+/4RgWOq7HrxRixHlFlExOLAJr5emLvN7SWXgnLh4+B5xQlNVz8Og8kvArMtNROxVQuCaSnIDdD5LKyWbRd2n9WGe2R8PzgCmr3EgVLrjyBxWezF0jLHwVN8efS3rCj/EWgvIWgb9tarpVUDK/b58Da+sqqls3eNbuv7pr+eoZG+SrDK6nWeL3c6H5Apxz7LjVc1uTIdsIXxuOLYA4/ilBmSVIzuDWfdRUfhHdY6+cn8HFRm+2hM8AnXGXws9555KrUB5qihylGa8subX2Nn6UwNR1AkUTV74bU=".to_string(),
+        key_tag: 20326,
+        key_size: Some(2048),
     }
+}
+
+fn root_ds(digest: &str) -> DsRecord {
+    DsRecord {
+        key_tag: 20326,
+        algorithm: 8,
+        algorithm_name: "RSASHA256".to_string(),
+        digest_type: 2,
+        digest_type_name: "SHA-256".to_string(),
+        digest: digest.to_string(),
+        digest_verified: None,
+    }
+}
+
+#[test]
+fn test_compute_key_tag_root_ksk() {
+    let adapter = DnsAdapter::new();
+
+    assert_eq!(adapter.compute_key_tag(&root_ksk()), Ok(20326));
+}
+
+#[test]
+fn test_compute_ds_digest_root_ksk() {
+    let adapter = DnsAdapter::new();
+
+    let digest = adapter.compute_ds_digest(".", &root_ksk(), 2).unwrap();
+    assert_eq!(
+        digest,
+        "E06D44B80B8F1D39A95C0B0D7C65D08458E880409BBC683457104237C7F8EC8D"
+    );
+    assert!(adapter.compute_ds_digest(".", &root_ksk(), 3).is_err());
+}
+
+#[test]
+fn test_verify_ds_records_detects_digest_mismatch() {
+    let adapter = DnsAdapter::new();
+    let mut ds_records = vec![
+        root_ds("E06D44B80B8F1D39A95C0B0D7C65D08458E880409BBC683457104237C7F8EC8D"),
+        // Same key tag and algorithm, different digest (collision or stale DS)
+        root_ds("0000000000000000000000000000000000000000000000000000000000000000"),
+    ];
+
+    adapter.verify_ds_records(".", &mut ds_records, &[root_ksk()]);
+
+    assert_eq!(ds_records[0].digest_verified, Some(true));
+    assert_eq!(ds_records[1].digest_verified, Some(false));
+}
+
+const ROOT_DS_DIGEST: &str = "E06D44B80B8F1D39A95C0B0D7C65D08458E880409BBC683457104237C7F8EC8D";
+
+#[test]
+fn test_evaluate_rollover_in_sync() {
+    let adapter = DnsAdapter::new();
+
+    let (status, warnings) = adapter.evaluate_rollover(
+        ".",
+        vec![root_ds(ROOT_DS_DIGEST)],
+        vec![root_ksk()],
+        &[root_ds(ROOT_DS_DIGEST)],
+        &[root_ksk()],
+    );
+
+    assert_eq!(status.state, "IN_SYNC");
+    assert!(warnings.is_empty());
+}
+
+#[test]
+fn test_evaluate_rollover_pending() {
+    let adapter = DnsAdapter::new();
+    let mut old_ds = root_ds("AB".repeat(32).as_str());
+    old_ds.key_tag = 19036;
+
+    let (status, warnings) = adapter.evaluate_rollover(
+        ".",
+        vec![root_ds(ROOT_DS_DIGEST)],
+        vec![],
+        &[old_ds],
+        &[root_ksk()],
+    );
+
+    assert_eq!(status.state, "PENDING");
+    assert_eq!(status.missing_from_parent.len(), 1);
+    assert_eq!(status.pending_removal.len(), 1);
+    assert!(status.pending_removal[0].starts_with("19036 8 2"));
+    assert_eq!(warnings.len(), 1);
+}
+
+#[test]
+fn test_evaluate_rollover_delete_request() {
+    let adapter = DnsAdapter::new();
+    let delete = DsRecord {
+        key_tag: 0,
+        algorithm: 0,
+        algorithm_name: "DELETE".to_string(),
+        digest_type: 0,
+        digest_type_name: "DELETE".to_string(),
+        digest: "00".to_string(),
+        digest_verified: None,
+    };
+
+    let (status, _) = adapter.evaluate_rollover(
+        "example.com",
+        vec![delete],
+        vec![],
+        &[root_ds(ROOT_DS_DIGEST)],
+        &[],
+    );
+
+    assert_eq!(status.state, "DELETE_REQUESTED");
+}
+
+#[test]
+fn test_evaluate_rollover_cds_for_unknown_key() {
+    let adapter = DnsAdapter::new();
+
+    let (status, warnings) = adapter.evaluate_rollover(
+        ".",
+        vec![root_ds(&"00".repeat(32))],
+        vec![],
+        &[root_ds(ROOT_DS_DIGEST)],
+        &[root_ksk()],
+    );
+
+    assert_eq!(status.state, "INVALID");
+    assert!(warnings[0].contains("does not match any DNSKEY"));
+}
+
+#[test]
+fn test_key_size_and_algorithm_names() {
+    let adapter = DnsAdapter::new();
+
+    assert_eq!(adapter.key_size(&root_ksk()), Some(2048));
+    assert_eq!(adapter.algorithm_name(13), "ECDSAP256SHA256");
+    assert_eq!(adapter.digest_type_name(4), "SHA-384");
+}
+
+#[test]
+fn test_algorithm_warnings_flag_weak_crypto() {
+    let adapter = DnsAdapter::new();
+    let mut weak_key = root_ksk();
+    weak_key.algorithm = 5;
+    weak_key.key_size = Some(1024);
+    let mut sha1_ds = root_ds("AB");
+    sha1_ds.digest_type = 1;
+
+    let chain = vec![
+        ZoneData {
+            zone_name: "com".to_string(),
+            dnskey_records: vec![root_ksk()],
+            ds_records: vec![sha1_ds],
+            rrsig_records: vec![],
+            ..Default::default()
+        },
+        ZoneData {
+            zone_name: "example.com".to_string(),
+            dnskey_records: vec![weak_key],
+            ds_records: vec![],
+            rrsig_records: vec![],
+            ..Default::default()
+        },
+    ];
+
+    let warnings = adapter.algorithm_warnings(&chain);
+
+    assert_eq!(warnings.len(), 3);
+    assert!(warnings[0].contains("SHA-1 digest"));
+    assert!(warnings[1].contains("deprecated algorithm RSASHA1 (5)"));
+    assert!(warnings[2].contains("1024-bit RSA key"));
+}
+
+fn com_chain(com_ds: Vec<DsRecord>) -> Vec<ZoneData> {
+    vec![
+        ZoneData {
+            zone_name: ".".to_string(),
+            dnskey_records: vec![root_ksk()],
+            ds_records: com_ds,
+            ..Default::default()
+        },
+        ZoneData {
+            zone_name: "com".to_string(),
+            // Stand-in key for the TLD; only the digest relationship matters here
+            dnskey_records: vec![root_ksk()],
+            ..Default::default()
+        },
+    ]
+}
+
+#[test]
+fn test_link_chain_secure_delegation() {
+    let adapter = DnsAdapter::new();
+    let now = adapter.parse_rrsig_time("20240301000000").unwrap();
+    let digest = adapter.compute_ds_digest("com", &root_ksk(), 2).unwrap();
+    let mut chain = com_chain(vec![root_ds(&digest)]);
+    chain[0].rrsig_records = adapter.parse_rrsig_records(&[DnsRecord {
+        name: ".".to_string(),
+        record_type: "RRSIG".to_string(),
+        value: "DNSKEY 8 0 172800 20240315000000 20240215000000 20326 . sig==".to_string(),
+        ttl: 172800,
+    }]);
+
+    adapter.link_chain(&mut chain, &root_trust_anchors(), now);
+
+    assert_eq!(chain[0].status, "SECURE");
+    assert_eq!(chain[1].status, "SECURE");
+    assert_eq!(chain[0].ds_links.len(), 1);
+    assert_eq!(chain[0].ds_links[0].child_zone, "com");
+    assert_eq!(chain[0].ds_links[0].dnskey_key_tag, Some(20326));
+    assert_eq!(chain[0].ds_links[0].verified, Some(true));
+    assert_eq!(chain[0].signature_links[0].signed_by, Some(20326));
+    assert_eq!(chain[0].signature_links[0].key_flags, Some(257));
+    assert!(chain[0].signature_links[0].valid_now);
+}
+
+#[test]
+fn test_link_chain_bogus_and_insecure_delegations() {
+    let adapter = DnsAdapter::new();
+    let now = adapter.parse_rrsig_time("20240301000000").unwrap();
+
+    // DS digest doesn't match any child key
+    let mut chain = com_chain(vec![root_ds(ROOT_DS_DIGEST)]);
+    adapter.link_chain(&mut chain, &root_trust_anchors(), now);
+    assert_eq!(chain[1].status, "BOGUS");
+    assert_eq!(chain[0].ds_links[0].dnskey_key_tag, None);
+    assert_eq!(chain[0].ds_links[0].verified, Some(false));
+
+    // A root that fails its trust anchor makes everything below it BOGUS
+    let digest = adapter.compute_ds_digest("com", &root_ksk(), 2).unwrap();
+    let mut chain = com_chain(vec![root_ds(&digest)]);
+    let other_root = root_trust_anchors()[1..].to_vec();
+    let warnings = adapter.link_chain(&mut chain, &other_root, now);
+    assert_eq!(chain[0].status, "BOGUS");
+    assert_eq!(chain[1].status, "BOGUS");
+    assert!(warnings
+        .iter()
+        .any(|w| w.contains("doesn't match any configured trust anchor")));
+
+    // No DS at all is an insecure delegation, inherited by everything below it
+    let mut chain = com_chain(vec![]);
+    chain.push(ZoneData {
+        zone_name: "example.com".to_string(),
+        dnskey_records: vec![root_ksk()],
+        ..Default::default()
+    });
+    adapter.link_chain(&mut chain, &root_trust_anchors(), now);
+    assert_eq!(chain[1].status, "INSECURE");
+    assert_eq!(chain[2].status, "INSECURE");
+}
+
+#[test]
+fn test_check_trust_anchors() {
+    let adapter = DnsAdapter::new();
+    let anchors = root_trust_anchors();
+
+    // KSK-2017 matches; KSK-2024 anchor isn't in this key set
+    let (anchored, warnings) = adapter.check_trust_anchors(".", &[root_ksk()], &anchors);
+    assert_eq!(anchored, Some(true));
+    assert_eq!(warnings.len(), 1);
+    assert!(warnings[0].contains("Trust anchor 38696"));
+
+    // Revoked KSK-2017 (REVOKE bit set) no longer counts as an anchored key
+    let mut revoked = root_ksk();
+    revoked.flags |= 0x0080;
+    let (anchored, warnings) = adapter.check_trust_anchors(".", &[revoked], &anchors);
+    assert_eq!(anchored, Some(false));
+    assert!(warnings[0].contains("Trust anchor 20326 for . has been revoked"));
+
+    // A KSK nobody configured is flagged instead of being trusted as served
+    let mut rogue = root_ksk();
+    rogue.public_key = rogue.public_key.replace("AwEAAaz", "AwEAAbz");
+    rogue.key_tag = adapter.compute_key_tag(&rogue).unwrap();
+    let (anchored, warnings) = adapter.check_trust_anchors(".", &[rogue], &anchors);
+    assert_eq!(anchored, Some(false));
+    assert!(warnings
+        .iter()
+        .any(|w| w.contains("not a configured trust anchor")));
+
+    // Zones without an anchor are left to the DS chain
+    let (anchored, _) = adapter.check_trust_anchors("com", &[root_ksk()], &anchors);
+    assert_eq!(anchored, None);
+}
+
+#[test]
+fn test_parse_rrsig_time_formats() {
+    let adapter = DnsAdapter::new();
+
+    let parsed = adapter.parse_rrsig_time("20240315120000").unwrap();
+    assert_eq!(parsed.to_rfc3339(), "2024-03-15T12:00:00+00:00");
+
+    let epoch = adapter.parse_rrsig_time("1710504000").unwrap();
+    assert_eq!(epoch, parsed);
+
+    assert!(adapter.parse_rrsig_time("not-a-time").is_none());
+}
 
-    #[test]
-    fn test_parse_rrsig_records() {
-        let adapter = DnsAdapter::new();
-        let records = vec![DnsRecord {
+#[test]
+fn test_rrsig_expiry_warnings() {
+    let adapter = DnsAdapter::new();
+    let now = adapter.parse_rrsig_time("20240301000000").unwrap();
+    let records = vec![
+        DnsRecord {
             name: "example.com.".to_string(),
             record_type: "RRSIG".to_string(),
-            value: "A 8 2 300 20250115000000 20250101000000 12345 example.com. ABC123=="
+            // Expired yesterday
+            value: "DNSKEY 13 2 3600 20240229000000 20240201000000 370 example.com. sig=="
                 .to_string(),
-            ttl: 300,
-        }];
-
-        let rrsig_records = adapter.parse_rrsig_records(&records);
-        assert_eq!(rrsig_records.len(), 1);
-
-        assert_eq!(rrsig_records[0].type_covered, "A");
-        assert_eq!(rrsig_records[0].algorithm, 8);
-        assert_eq!(rrsig_records[0].labels, 2);
-        assert_eq!(rrsig_records[0].original_ttl, 300);
-        assert_eq!(rrsig_records[0].signature_expiration, "20250115000000");
-        assert_eq!(rrsig_records[0].signature_inception, "20250101000000");
-        assert_eq!(rrsig_records[0].key_tag, 12345);
-        assert_eq!(rrsig_records[0].signer_name, "example.com.");
-        assert_eq!(rrsig_records[0].signature, "ABC123==");
-    }
-
-    #[test]
-    fn test_parse_rrsig_records_multiline() {
-        let adapter = DnsAdapter::new();
-        let records = vec![DnsRecord {
+            ttl: 3600,
+        },
+        DnsRecord {
             name: "example.com.".to_string(),
             record_type: "RRSIG".to_string(),
-            value: "A 8 2 300 ( 20250115000000 20250101000000 12345 example.com. ABC123== )"
+            // Expires in 3 days
+            value: "DNSKEY 13 2 3600 20240304000000 20240201000000 371 example.com. sig=="
                 .to_string(),
-            ttl: 300,
-        }];
+            ttl: 3600,
+        },
+        DnsRecord {
+            name: "example.com.".to_string(),
+            record_type: "RRSIG".to_string(),
+            // Plenty of time left
+            value: "DNSKEY 13 2 3600 20240401000000 20240201000000 372 example.com. sig=="
+                .to_string(),
+            ttl: 3600,
+        },
+    ];
+    let chain = vec![ZoneData {
+        zone_name: "example.com".to_string(),
+        dnskey_records: vec![],
+        ds_records: vec![],
+        rrsig_records: adapter.parse_rrsig_records(&records),
+        ..Default::default()
+    }];
 
-        let rrsig_records = adapter.parse_rrsig_records(&records);
-        assert_eq!(rrsig_records.len(), 1);
-        assert_eq!(rrsig_records[0].type_covered, "A");
+    let warnings = adapter.rrsig_expiry_warnings(&chain, now, 7);
+
+    assert_eq!(warnings.len(), 2);
+    assert!(warnings[0].contains("(key tag 370) expired on 2024-02-29"));
+    assert!(warnings[1].contains("(key tag 371) expires in 3 days"));
+}
+
+fn zone_key(key_tag: u16) -> DnskeyRecord {
+    DnskeyRecord {
+        flags: 256,
+        protocol: 3,
+        algorithm: 13,
+        algorithm_name: "ECDSAP256SHA256".to_string(),
+        public_key: "key==".to_string(),
+        key_tag,
+        key_size: Some(256),
     }
+}
 
-    #[test]
-    fn test_parse_dig_output_multiline_dnskey() {
-        let adapter = DnsAdapter::new();
-        let output = r#"example.com.		3600	IN	DNSKEY	257 3 8 ( AwEAAa8GMxKnN0wpBW5qfH6W ; KSK; alg = RSASHA256 ; key id = 5116
-                                  Vh+D8gMZCEANdBlQ2jYw ) ; {key id = 5116}"#;
+fn signed_a_records(rrsig: &str) -> Vec<DnsRecord> {
+    vec![
+        DnsRecord {
+            name: "www.example.com.".to_string(),
+            record_type: "A".to_string(),
+            value: "93.184.216.34".to_string(),
+            ttl: 300,
+        },
+        DnsRecord {
+            name: "www.example.com.".to_string(),
+            record_type: "RRSIG".to_string(),
+            value: rrsig.to_string(),
+            ttl: 300,
+        },
+    ]
+}
 
-        let result = adapter.parse_dig_output(output, "DNSKEY");
-        assert!(result.is_ok());
+#[test]
+fn test_evaluate_rrset_signatures_secure() {
+    let adapter = DnsAdapter::new();
+    let now = adapter.parse_rrsig_time("20240301000000").unwrap();
+    let records =
+        signed_a_records("A 13 3 300 20240315000000 20240215000000 4242 example.com. sig==");
 
-        let records = result.unwrap();
-        assert_eq!(records.len(), 1);
-        assert_eq!(records[0].record_type, "DNSKEY");
-        // Should capture the entire value including continuation lines
-        assert!(records[0].value.contains("257 3 8"));
-    }
+    let (rrsigs, key_tags, warnings) = adapter.evaluate_rrset_signatures(
+        "www.example.com",
+        "A",
+        "example.com",
+        &records,
+        &[zone_key(4242)],
+        now,
+    );
 
-    #[test]
-    fn test_parse_dig_output_cname() {
-        let adapter = DnsAdapter::new();
-        let output = "www.example.com.		3600	IN	CNAME	example.com.";
+    assert_eq!(rrsigs.len(), 1);
+    assert_eq!(key_tags, vec![4242]);
+    assert!(warnings.is_empty(), "unexpected warnings: {:?}", warnings);
+}
 
-        let result = adapter.parse_dig_output(output, "CNAME");
-        assert!(result.is_ok());
+#[test]
+fn test_evaluate_rrset_signatures_rejects_unusable_signatures() {
+    let adapter = DnsAdapter::new();
+    let now = adapter.parse_rrsig_time("20240301000000").unwrap();
 
-        let records = result.unwrap();
-        assert_eq!(records.len(), 1);
-        assert_eq!(records[0].record_type, "CNAME");
-        assert_eq!(records[0].value, "example.com.");
-    }
+    // Unknown key tag
+    let records =
+        signed_a_records("A 13 3 300 20240315000000 20240215000000 1111 example.com. sig==");
+    let (_, key_tags, warnings) = adapter.evaluate_rrset_signatures(
+        "www.example.com",
+        "A",
+        "example.com",
+        &records,
+        &[zone_key(4242)],
+        now,
+    );
+    assert!(key_tags.is_empty());
+    assert!(warnings[0].contains("doesn't match any DNSKEY"));
 
-    #[test]
-    fn test_parse_dig_output_soa() {
-        let adapter = DnsAdapter::new();
-        let output = "example.com.		3600	IN	SOA	ns1.example.com. admin.example.com. 2025010101 3600 900 604800 86400";
+    // Expired
+    let records =
+        signed_a_records("A 13 3 300 20240220000000 20240201000000 4242 example.com. sig==");
+    let (_, key_tags, warnings) = adapter.evaluate_rrset_signatures(
+        "www.example.com",
+        "A",
+        "example.com",
+        &records,
+        &[zone_key(4242)],
+        now,
+    );
+    assert!(key_tags.is_empty());
+    assert!(warnings[0].contains("expired"));
 
-        let result = adapter.parse_dig_output(output, "SOA");
-        assert!(result.is_ok());
+    // No RRSIG over the requested type
+    let (rrsigs, key_tags, warnings) = adapter.evaluate_rrset_signatures(
+        "www.example.com",
+        "AAAA",
+        "example.com",
+        &records,
+        &[zone_key(4242)],
+        now,
+    );
+    assert!(rrsigs.is_empty());
+    assert!(key_tags.is_empty());
+    assert!(warnings[0].contains("No RRSIG covers the AAAA RRset"));
+}
+
+#[test]
+fn test_evaluate_rrset_signatures_flags_wildcards() {
+    let adapter = DnsAdapter::new();
+    let now = adapter.parse_rrsig_time("20240301000000").unwrap();
+    let records =
+        signed_a_records("A 13 2 300 20240315000000 20240215000000 4242 example.com. sig==");
+
+    let (_, key_tags, warnings) = adapter.evaluate_rrset_signatures(
+        "www.example.com",
+        "A",
+        "example.com",
+        &records,
+        &[zone_key(4242)],
+        now,
+    );
+
+    assert_eq!(key_tags, vec![4242]);
+    assert_eq!(warnings.len(), 1);
+    assert!(warnings[0].contains("wildcard"));
+}
+
+#[test]
+fn test_parse_dnskey_records_computes_missing_key_tag() {
+    let adapter = DnsAdapter::new();
+    let key = root_ksk();
+    let records = vec![DnsRecord {
+        name: ".".to_string(),
+        record_type: "DNSKEY".to_string(),
+        value: format!("257 3 8 ( {} )", key.public_key),
+        ttl: 172800,
+    }];
+
+    let dnskey_records = adapter.parse_dnskey_records(&records);
+    assert_eq!(dnskey_records[0].key_tag, 20326);
+}
+
+#[tokio::test]
+async fn test_dns_response_structure() {
+    // Test that DnsResponse can be properly constructed
+    let response = DnsResponse {
+        records: vec![DnsRecord {
+            name: "example.com.".to_string(),
+            record_type: "A".to_string(),
+            value: "93.184.216.34".to_string(),
+            ttl: 3600,
+        }],
+        query_time: 0.123,
+        resolver: "system".to_string(),
+        header: None,
+        transport: "udp".to_string(),
+        server: None,
+        ip_version: None,
+        from_cache: false,
+        raw_output: Some("example.com. 3600 IN A 93.184.216.34".to_string()),
+    };
 
-        let records = result.unwrap();
-        assert_eq!(records.len(), 1);
-        assert_eq!(records[0].record_type, "SOA");
-        assert!(records[0].value.contains("ns1.example.com."));
+    assert_eq!(response.records.len(), 1);
+    assert_eq!(response.query_time, 0.123);
+    assert_eq!(response.resolver, "system");
+    assert!(response.raw_output.is_some());
+}
+
+// Answers every dig run with the same output and records the arguments
+struct CannedDig {
+    available: bool,
+    stdout: &'static str,
+    runs: Arc<Mutex<Vec<Vec<String>>>>,
+}
+
+impl DnsBackend for CannedDig {
+    fn is_available(&self) -> bool {
+        self.available
     }
 
-    #[tokio::test]
-    async fn test_dns_response_structure() {
-        // Test that DnsResponse can be properly constructed
-        let response = DnsResponse {
-            records: vec![DnsRecord {
-                name: "example.com.".to_string(),
-                record_type: "A".to_string(),
-                value: "93.184.216.34".to_string(),
-                ttl: 3600,
-            }],
-            query_time: 0.123,
-            resolver: "system".to_string(),
-            raw_output: Some("example.com. 3600 IN A 93.184.216.34".to_string()),
-        };
-
-        assert_eq!(response.records.len(), 1);
-        assert_eq!(response.query_time, 0.123);
-        assert_eq!(response.resolver, "system");
-        assert!(response.raw_output.is_some());
+    fn dig<'a>(&'a self, args: &'a [String]) -> BackendFuture<'a, ProcessOutput> {
+        self.runs.lock().unwrap().push(args.to_vec());
+        Box::pin(async move {
+            Ok(ProcessOutput {
+                stdout: self.stdout.to_string(),
+                stderr: String::new(),
+                exit_code: 0,
+            })
+        })
     }
 }
+
+fn canned_adapter(
+    available: bool,
+    stdout: &'static str,
+) -> (DnsAdapter, Arc<Mutex<Vec<Vec<String>>>>) {
+    let runs = Arc::new(Mutex::new(Vec::new()));
+    let dig = CannedDig {
+        available,
+        stdout,
+        runs: runs.clone(),
+    };
+    let adapter = DnsAdapter::new().with_backends(Backends::default().with_dns(dig));
+    (adapter, runs)
+}
+
+#[tokio::test]
+async fn test_probe_recursion_open() {
+    let (adapter, runs) = canned_adapter(
+        true,
+        r#";; ->>HEADER<<- opcode: QUERY, status: NOERROR, id: 1701
+;; flags: qr rd ra; QUERY: 1, ANSWER: 1, AUTHORITY: 0, ADDITIONAL: 1
+
+;; ANSWER SECTION:
+example.net.		300	IN	A	192.0.2.80"#,
+    );
+
+    let probes = adapter
+        .probe_recursion("example.com", &["ns1.example.com".to_string()])
+        .await;
+
+    // example.com is the zone itself, so the next probe name is used
+    assert_eq!(probes[0].probe_name, "example.net");
+    assert!(probes[0].open);
+    assert!(probes[0].recursion_available);
+    assert_eq!(probes[0].answers, ["192.0.2.80"]);
+    let runs = runs.lock().unwrap();
+    assert!(runs[0].contains(&"@ns1.example.com".to_string()));
+    assert!(!runs[0].contains(&"+norec".to_string()));
+}
+
+#[tokio::test]
+async fn test_probe_recursion_refused() {
+    let (adapter, _) = canned_adapter(
+        true,
+        r#";; ->>HEADER<<- opcode: QUERY, status: REFUSED, id: 1702
+;; flags: qr rd; QUERY: 1, ANSWER: 0, AUTHORITY: 0, ADDITIONAL: 1"#,
+    );
+
+    let probes = adapter
+        .probe_recursion("example.org", &["ns1.example.org".to_string()])
+        .await;
+
+    assert_eq!(probes[0].probe_name, "example.com");
+    assert!(!probes[0].open);
+    assert!(!probes[0].recursion_available);
+    assert_eq!(probes[0].rcode.as_deref(), Some("REFUSED"));
+    assert!(probes[0].error.is_none());
+}
+
+#[test]
+fn test_classify_recursion_authoritative_answer() {
+    let adapter = DnsAdapter::new();
+    // The nameserver happens to host the probe name: an answer, but not recursion
+    let response = DnsResponse {
+        records: vec![DnsRecord {
+            name: "example.com.".to_string(),
+            record_type: "A".to_string(),
+            value: "192.0.2.1".to_string(),
+            ttl: 300,
+        }],
+        query_time: 0.01,
+        resolver: "ns1.example.net".to_string(),
+        header: Some(DnsHeader {
+            rcode: "NOERROR".to_string(),
+            authoritative: true,
+            truncated: false,
+            recursion_available: false,
+            authenticated_data: false,
+            answer_count: 1,
+            authority_count: 0,
+            additional_count: 0,
+        }),
+        transport: "udp".to_string(),
+        server: None,
+        ip_version: None,
+        from_cache: false,
+        raw_output: None,
+    };
+
+    let probe = adapter.classify_recursion("ns1.example.net", "example.com", Ok(response));
+    assert!(!probe.open);
+
+    let probe = adapter.classify_recursion(
+        "ns2.example.net",
+        "example.com",
+        Err("timed out".to_string()),
+    );
+    assert!(!probe.open);
+    assert_eq!(probe.error.as_deref(), Some("timed out"));
+}
+
+#[tokio::test]
+async fn test_sample_addresses() {
+    let (adapter, runs) = canned_adapter(
+        true,
+        r#";; ->>HEADER<<- opcode: QUERY, status: NOERROR, id: 1703
+;; flags: qr rd ra; QUERY: 1, ANSWER: 2, AUTHORITY: 0, ADDITIONAL: 1
+
+;; ANSWER SECTION:
+example.com.		60	IN	A	192.0.2.2
+example.com.		30	IN	A	192.0.2.1"#,
+    );
+    let resolvers = ["1.1.1.1".to_string(), "8.8.8.8".to_string()];
+    let rounds_done = Mutex::new(Vec::new());
+
+    let samples = adapter
+        .sample_addresses("example.com", &resolvers, 1, |round| {
+            rounds_done.lock().unwrap().push(round)
+        })
+        .await;
+
+    // Resolver order, then A before AAAA; answer order is kept
+    let order: Vec<(&str, &str)> = samples
+        .iter()
+        .map(|s| (s.resolver.as_str(), s.record_type.as_str()))
+        .collect();
+    assert_eq!(
+        order,
+        [
+            ("1.1.1.1", "A"),
+            ("1.1.1.1", "AAAA"),
+            ("8.8.8.8", "A"),
+            ("8.8.8.8", "AAAA")
+        ]
+    );
+    assert_eq!(samples[0].addresses, ["192.0.2.2", "192.0.2.1"]);
+    assert_eq!(samples[0].ttl, Some(30));
+    assert!(samples[1].addresses.is_empty());
+    assert_eq!(*rounds_done.lock().unwrap(), [1]);
+    assert_eq!(runs.lock().unwrap().len(), 4);
+}
+
+#[tokio::test]
+async fn test_query_with_canned_dig() {
+    let (adapter, runs) = canned_adapter(
+        true,
+        r#";; ->>HEADER<<- opcode: QUERY, status: NOERROR, id: 38720
+;; flags: qr rd ra; QUERY: 1, ANSWER: 2, AUTHORITY: 0, ADDITIONAL: 1
+
+;; ANSWER SECTION:
+example.com.		300	IN	A	192.0.2.1
+example.com.		300	IN	A	192.0.2.2
+
+;; SERVER: 192.0.2.53#53(192.0.2.53) (UDP)"#,
+    );
+
+    let response = adapter.query("example.com", "A").await.unwrap();
+    assert_eq!(response.records.len(), 2);
+    assert_eq!(response.records[1].value, "192.0.2.2");
+    assert_eq!(response.header.unwrap().rcode, "NOERROR");
+    assert_eq!(response.transport, "udp");
+    assert_eq!(response.server.as_deref(), Some("192.0.2.53"));
+
+    let runs = runs.lock().unwrap();
+    assert_eq!(runs.len(), 1);
+    assert!(runs[0].contains(&"+ignore".to_string()));
+    assert!(runs[0].ends_with(&["A".to_string(), "example.com".to_string()]));
+}
+
+#[tokio::test]
+async fn test_query_without_dig() {
+    let (adapter, runs) = canned_adapter(false, "");
+
+    let result = adapter.query("example.com", "A").await;
+    assert!(result.unwrap_err().contains("No DNS query tool found"));
+    assert!(runs.lock().unwrap().is_empty());
+}
+
+#[test]
+fn test_reverse_name() {
+    let adapter = DnsAdapter::new();
+
+    assert_eq!(
+        adapter.reverse_name("192.0.2.1".parse().unwrap()),
+        "1.2.0.192.in-addr.arpa"
+    );
+    assert_eq!(
+        adapter.reverse_name("2001:db8::1".parse().unwrap()),
+        "1.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.8.b.d.0.1.0.0.2.ip6.arpa"
+    );
+}
+
+#[tokio::test]
+async fn test_reverse_lookup() {
+    let (adapter, runs) = canned_adapter(
+        true,
+        r#";; ->>HEADER<<- opcode: QUERY, status: NOERROR, id: 4242
+;; flags: qr rd ra; QUERY: 1, ANSWER: 1, AUTHORITY: 0, ADDITIONAL: 1
+
+;; ANSWER SECTION:
+1.2.0.192.in-addr.arpa.	3600	IN	PTR	Router.Example.NET."#,
+    );
+
+    let names = adapter
+        .reverse_lookup("192.0.2.1".parse().unwrap())
+        .await
+        .unwrap();
+    assert_eq!(names, ["router.example.net"]);
+    let runs = runs.lock().unwrap();
+    assert!(runs[0].ends_with(&["PTR".to_string(), "1.2.0.192.in-addr.arpa".to_string()]));
+}
+
+#[tokio::test]
+async fn test_forward_confirm_each() {
+    let dig = ZoneDig::new(&[
+        ("1.2.0.192.in-addr.arpa", "PTR", "mail.example.com."),
+        ("mail.example.com", "A", "192.0.2.1"),
+        ("2.2.0.192.in-addr.arpa", "PTR", "host-2.isp.example."),
+        ("host-2.isp.example", "A", "198.51.100.2"),
+    ]);
+    let adapter = DnsAdapter::new().with_backends(Backends::default().with_dns(dig));
+    let addresses = ["192.0.2.1", "192.0.2.2", "192.0.2.3"].map(|a| a.parse().unwrap());
+
+    let checks = adapter.forward_confirm_each(&addresses).await;
+
+    assert_eq!(checks.len(), 3);
+    assert!(checks[0].confirmed);
+    assert_eq!(checks[0].ptr_names[0].name, "mail.example.com");
+    assert_eq!(checks[0].ptr_names[0].addresses, ["192.0.2.1"]);
+
+    // Reverses to a name that points elsewhere
+    assert!(!checks[1].confirmed);
+    assert_eq!(checks[1].ptr_names[0].addresses, ["198.51.100.2"]);
+
+    // No PTR record at all
+    assert_eq!(checks[2].address, "192.0.2.3");
+    assert!(!checks[2].confirmed);
+    assert!(checks[2].ptr_names.is_empty());
+}
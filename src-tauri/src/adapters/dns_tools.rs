@@ -0,0 +1,607 @@
+use crate::adapters::backend::{BackendFuture, DnsBackend, ProcessOutput};
+use crate::adapters::environment::EnvironmentAdapter;
+use serde_json::Value;
+use std::sync::OnceLock;
+
+// Programs that can answer DnsAdapter's queries, in order of preference
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DnsTool {
+    Dig,   // BIND
+    Kdig,  // Knot DNS
+    Drill, // ldns
+    Host,  // BIND's host, with -v; it can't set the DO bit, so no RRSIGs
+    // PowerShell's Resolve-DnsName, built into Windows. Only common record types are
+    // converted, and without DNSSEC records or the response flags.
+    ResolveDnsName,
+}
+
+impl DnsTool {
+    #[cfg(windows)]
+    pub const ALL: &[DnsTool] = &[
+        DnsTool::Dig,
+        DnsTool::Kdig,
+        DnsTool::Drill,
+        DnsTool::Host,
+        DnsTool::ResolveDnsName,
+    ];
+    #[cfg(not(windows))]
+    pub const ALL: &[DnsTool] = &[DnsTool::Dig, DnsTool::Kdig, DnsTool::Drill, DnsTool::Host];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            DnsTool::Dig => "dig",
+            DnsTool::Kdig => "kdig",
+            DnsTool::Drill => "drill",
+            DnsTool::Host => "host",
+            DnsTool::ResolveDnsName => "powershell",
+        }
+    }
+
+    // The first tool installed on the PATH, looked up once per process
+    pub fn detect() -> Option<DnsTool> {
+        static DETECTED: OnceLock<Option<DnsTool>> = OnceLock::new();
+        *DETECTED.get_or_init(DnsTool::locate)
+    }
+
+    // Looked up again, to pick up a tool installed since the app started
+    pub fn locate() -> Option<DnsTool> {
+        let environment = EnvironmentAdapter::new();
+        DnsTool::ALL
+            .iter()
+            .copied()
+            .find(|tool| environment.find(tool.name()).is_some())
+    }
+
+    // This tool's command line for the dig arguments DnsAdapter builds
+    //
+    // Only the options DnsAdapter uses are translated. Output options (+noall,
+    // +answer, +multi...) are dropped: the full response is printed and normalize
+    // keeps the sections dig would have shown.
+    pub fn args(&self, dig_args: &[String]) -> Vec<String> {
+        if *self == DnsTool::Dig {
+            return dig_args.to_vec();
+        }
+        let query = DigQuery::parse(dig_args);
+        let mut args: Vec<String> = Vec::new();
+
+        match self {
+            DnsTool::Dig => {}
+            DnsTool::Kdig => {
+                args.extend(query.families.iter().cloned());
+                for option in &query.options {
+                    match option.as_str() {
+                        "+tcp" | "+vc" => args.push("+tcp".to_string()),
+                        "+norec" => args.push("+norecurse".to_string()),
+                        "+dnssec" | "+ignore" => args.push(option.clone()),
+                        _ if option == "+https" || option.starts_with("+https=") => {
+                            args.push(option.clone())
+                        }
+                        _ if option.starts_with("+time=") || option.starts_with("+retry=") => {
+                            args.push(option.clone())
+                        }
+                        _ => {
+                            if let Some(retries) = query.retries(option) {
+                                args.push(format!("+retry={}", retries));
+                            }
+                        }
+                    }
+                }
+                args.extend(query.server.iter().map(|s| format!("@{}", s)));
+                args.extend(query.name.iter().cloned());
+                args.extend(query.record_type.iter().cloned());
+            }
+            DnsTool::Drill => {
+                args.extend(query.families.iter().cloned());
+                if query.tcp() {
+                    args.push("-t".to_string());
+                }
+                if query.has("+dnssec") {
+                    args.push("-D".to_string());
+                }
+                if query.has("+norec") {
+                    // Lowercase clears the flag
+                    args.extend(["-o".to_string(), "rd".to_string()]);
+                }
+                args.extend(query.name.iter().cloned());
+                args.extend(query.server.iter().map(|s| format!("@{}", s)));
+                args.extend(query.record_type.iter().cloned());
+            }
+            DnsTool::Host => {
+                args.push("-v".to_string());
+                args.extend(query.families.iter().cloned());
+                if query.tcp() {
+                    args.push("-T".to_string());
+                }
+                if query.has("+norec") {
+                    args.push("-r".to_string());
+                }
+                for option in &query.options {
+                    if let Some(secs) = option.strip_prefix("+time=") {
+                        args.extend(["-W".to_string(), secs.to_string()]);
+                    } else if let Some(retries) = query.retries(option) {
+                        args.extend(["-R".to_string(), retries.to_string()]);
+                    }
+                }
+                args.extend(
+                    query
+                        .record_type
+                        .iter()
+                        .flat_map(|t| ["-t".to_string(), t.clone()]),
+                );
+                args.extend(query.name.iter().cloned());
+                args.extend(query.server.iter().cloned());
+            }
+            DnsTool::ResolveDnsName => {
+                let mut command = format!(
+                    "Resolve-DnsName -DnsOnly -Name {} -Type {}",
+                    quote(query.name.as_deref().unwrap_or(".")),
+                    query.record_type.as_deref().unwrap_or("A")
+                );
+                if let Some(server) = &query.server {
+                    command.push_str(&format!(" -Server {}", quote(server)));
+                }
+                if query.tcp() {
+                    command.push_str(" -TcpOnly");
+                }
+                if query.has("+norec") {
+                    command.push_str(" -NoRecursion");
+                }
+                // @() keeps a single record a JSON array
+                args.extend([
+                    "-NoProfile".to_string(),
+                    "-NonInteractive".to_string(),
+                    "-Command".to_string(),
+                    format!("ConvertTo-Json -Depth 3 -InputObject @({})", command),
+                ]);
+            }
+        }
+        args
+    }
+
+    // Rewrite this tool's output the way dig would have printed it for dig_args, so
+    // DnsAdapter's parsers work unchanged. Output without a response header (e.g. no
+    // server could be reached) is left as it is.
+    pub fn normalize(&self, dig_args: &[String], output: ProcessOutput) -> ProcessOutput {
+        let response = match self {
+            DnsTool::Dig => return output,
+            DnsTool::ResolveDnsName => Response::from_resolve_dns_name(&output, dig_args),
+            _ => Response::parse(&output.stdout),
+        };
+        let Some(response) = response else {
+            return output;
+        };
+        ProcessOutput {
+            stdout: response.render(&DigQuery::parse(dig_args)),
+            stderr: output.stderr,
+            // dig exits 0 whenever a server answered, NXDOMAIN included; host doesn't
+            exit_code: 0,
+        }
+    }
+}
+
+// The parts of a dig command line the other tools need
+#[derive(Debug, Default)]
+struct DigQuery {
+    name: Option<String>,
+    record_type: Option<String>, // The uppercase positional, e.g. "DNSKEY"
+    server: Option<String>,
+    families: Vec<String>, // -4 or -6
+    options: Vec<String>,  // +tcp, +dnssec, +time=5...
+}
+
+impl DigQuery {
+    fn parse(args: &[String]) -> Self {
+        let mut query = DigQuery::default();
+        for arg in args {
+            if let Some(server) = arg.strip_prefix('@') {
+                query.server = Some(server.to_string());
+            } else if arg.starts_with('+') {
+                query.options.push(arg.clone());
+            } else if arg == "-4" || arg == "-6" {
+                query.families.push(arg.clone());
+            } else if arg.starts_with(|c: char| c.is_ascii_uppercase())
+                && arg
+                    .chars()
+                    .all(|c| c.is_ascii_uppercase() || c.is_ascii_digit())
+            {
+                query.record_type = Some(arg.clone());
+            } else if !arg.starts_with('-') {
+                query.name = Some(arg.clone());
+            }
+        }
+        query
+    }
+
+    fn has(&self, option: &str) -> bool {
+        self.options.iter().any(|o| o == option)
+    }
+
+    fn tcp(&self) -> bool {
+        self.has("+tcp") || self.has("+vc")
+    }
+
+    // Retries after the first try, from +tries=N (tries in all)
+    fn retries(&self, option: &str) -> Option<u32> {
+        let tries: u32 = option.strip_prefix("+tries=")?.parse().ok()?;
+        Some(tries.saturating_sub(1))
+    }
+
+    // Whether dig would print a section: all of them unless +noall, then those asked for
+    fn shows(&self, section: &str) -> bool {
+        !self.has("+noall") || self.has(&format!("+{}", section)) || self.has("+all")
+    }
+}
+
+// A response as printed by kdig, drill or host -v, which all resemble dig's output
+#[derive(Debug, Default)]
+struct Response {
+    rcode: String,
+    id: String,
+    flags: String,
+    counts: [u32; 4], // QUERY, ANSWER, AUTHORITY, ADDITIONAL
+    answer: Vec<String>,
+    authority: Vec<String>,
+    additional: Vec<String>,
+    server: Option<String>,
+    protocol: Option<String>, // "UDP" or "TCP", when the tool says
+}
+
+const COUNTS: [&str; 4] = ["QUERY", "ANSWER", "AUTHORITY", "ADDITIONAL"];
+
+impl Response {
+    // None without a ->>HEADER<<- line
+    fn parse(output: &str) -> Option<Response> {
+        let mut response: Option<Response> = None;
+        let mut section: Option<&str> = None;
+
+        for line in output.lines() {
+            let line = line.trim();
+
+            if line.contains("->>HEADER<<-") {
+                // A later header (e.g. after a retry) replaces the earlier one
+                response = Some(Response {
+                    rcode: field(line, "status:")
+                        .or_else(|| field(line, "rcode:"))
+                        .unwrap_or_default(),
+                    id: field(line, "id:").unwrap_or_default(),
+                    ..Response::default()
+                });
+                section = None;
+                continue;
+            }
+            let Some(current) = response.as_mut() else {
+                continue;
+            };
+
+            if let Some(rest) = strip_prefix_ignore_case(line, ";; flags:") {
+                current.flags = rest.split(';').next().unwrap_or("").trim().to_string();
+                for (count, label) in current.counts.iter_mut().zip(COUNTS) {
+                    *count = field(rest, &format!("{}:", label))
+                        .and_then(|v| v.parse().ok())
+                        .unwrap_or(0);
+                }
+            } else if let Some(name) = line
+                .strip_prefix(";; ")
+                .and_then(|l| l.strip_suffix(" SECTION:"))
+            {
+                section = Some(name).filter(|n| ["ANSWER", "AUTHORITY", "ADDITIONAL"].contains(n));
+            } else if let Some(rest) = line.strip_prefix(";; SERVER:") {
+                // dig and drill: ";; SERVER: 192.0.2.53" or "192.0.2.53#53(...)"
+                current.server = rest
+                    .split(['#', '(', ' '])
+                    .find(|s| !s.is_empty())
+                    .map(str::to_string);
+            } else if let Some(rest) = line.strip_prefix(";; From ") {
+                // kdig: ";; From 192.0.2.53@53(UDP) in 10.2 ms"
+                let address = rest.split_whitespace().next().unwrap_or("");
+                current.server = address
+                    .rsplit_once('@')
+                    .map(|(server, _)| server.to_string());
+                current.protocol = address
+                    .split_once('(')
+                    .and_then(|(_, p)| p.strip_suffix(')'))
+                    .map(str::to_string);
+            } else if let Some(rest) = line.strip_prefix("Received ") {
+                // host: "Received 56 bytes from 192.0.2.53#53 in 10 ms"
+                current.server = rest
+                    .split_once(" from ")
+                    .and_then(|(_, from)| from.split(['#', ' ']).next())
+                    .map(str::to_string);
+            } else if !line.starts_with(';') && is_record(line) {
+                match section {
+                    Some("ANSWER") => current.answer.push(line.to_string()),
+                    Some("AUTHORITY") => current.authority.push(line.to_string()),
+                    Some("ADDITIONAL") => current.additional.push(line.to_string()),
+                    _ => {}
+                }
+            }
+        }
+        response
+    }
+
+    // Resolve-DnsName's records as JSON, or the error it wrote for a failed lookup
+    fn from_resolve_dns_name(output: &ProcessOutput, dig_args: &[String]) -> Option<Response> {
+        let query = DigQuery::parse(dig_args);
+        let flags = if query.has("+norec") { "qr" } else { "qr rd" };
+        let mut response = Response {
+            rcode: "NOERROR".to_string(),
+            id: "0".to_string(),
+            flags: flags.to_string(),
+            server: query.server.clone(),
+            ..Response::default()
+        };
+
+        if output.exit_code != 0 {
+            let error = format!("{}\n{}", output.stdout, output.stderr).to_lowercase();
+            response.rcode = if error.contains("dns name does not exist") {
+                "NXDOMAIN"
+            } else if error.contains("server failure") {
+                "SERVFAIL"
+            } else if error.contains("refused") {
+                "REFUSED"
+            } else {
+                // e.g. a timeout, which dig reports without a header too
+                return None;
+            }
+            .to_string();
+            response.counts[0] = 1;
+            return Some(response);
+        }
+
+        let records: Vec<Value> = match serde_json::from_str(output.stdout.trim()).ok()? {
+            Value::Array(records) => records,
+            Value::Null => Vec::new(),
+            record => vec![record],
+        };
+        for record in &records {
+            let Some(line) = resolve_dns_name_record(record) else {
+                continue;
+            };
+            match record
+                .get("Section")
+                .and_then(section_name)
+                .unwrap_or("ANSWER")
+            {
+                "AUTHORITY" => response.authority.push(line),
+                "ADDITIONAL" => response.additional.push(line),
+                _ => response.answer.push(line),
+            }
+        }
+        response.counts = [
+            1,
+            response.answer.len() as u32,
+            response.authority.len() as u32,
+            response.additional.len() as u32,
+        ];
+        Some(response)
+    }
+
+    fn render(&self, query: &DigQuery) -> String {
+        let mut lines = Vec::new();
+        if !query.has("+noall") || query.has("+comments") {
+            lines.push(format!(
+                ";; ->>HEADER<<- opcode: QUERY, status: {}, id: {}",
+                self.rcode, self.id
+            ));
+            let counts: Vec<String> = COUNTS
+                .iter()
+                .zip(self.counts)
+                .map(|(label, count)| format!("{}: {}", label, count))
+                .collect();
+            lines.push(format!(";; flags: {}; {}", self.flags, counts.join(", ")));
+        }
+        for (name, records) in [
+            ("answer", &self.answer),
+            ("authority", &self.authority),
+            ("additional", &self.additional),
+        ] {
+            if !records.is_empty() && query.shows(name) {
+                lines.push(String::new());
+                lines.push(format!(";; {} SECTION:", name.to_uppercase()));
+                lines.extend(records.iter().cloned());
+            }
+        }
+        if let Some(server) = &self.server {
+            let protocol = match (&self.protocol, query.tcp()) {
+                (Some(protocol), _) => protocol.to_uppercase(),
+                (None, true) => "TCP".to_string(),
+                (None, false) => "UDP".to_string(),
+            };
+            lines.push(String::new());
+            lines.push(format!(
+                ";; SERVER: {}#53({}) ({})",
+                server, server, protocol
+            ));
+        }
+        lines.join("\n") + "\n"
+    }
+}
+
+// A PowerShell single-quoted string
+fn quote(value: &str) -> String {
+    format!("'{}'", value.replace('\'', "''"))
+}
+
+// Section is an enum that ConvertTo-Json writes as a number (or a name with
+// -EnumsAsStrings): Answer = 1, Authority = 2, Additional = 3
+fn section_name(section: &Value) -> Option<&'static str> {
+    match section.as_u64() {
+        Some(1) => Some("ANSWER"),
+        Some(2) => Some("AUTHORITY"),
+        Some(3) => Some("ADDITIONAL"),
+        _ => match section.as_str()? {
+            "Answer" => Some("ANSWER"),
+            "Authority" => Some("AUTHORITY"),
+            "Additional" => Some("ADDITIONAL"),
+            _ => None,
+        },
+    }
+}
+
+// One Resolve-DnsName record as a dig answer line; None for types it doesn't convert
+fn resolve_dns_name_record(record: &Value) -> Option<String> {
+    let text = |key: &str| record.get(key).and_then(Value::as_str);
+    let number = |key: &str| record.get(key).and_then(Value::as_u64);
+    let fqdn = |key: &str| text(key).map(|name| format!("{}.", name.trim_end_matches('.')));
+
+    let record_type = match record.get("Type")? {
+        Value::Number(n) => match n.as_u64()? {
+            1 => "A",
+            2 => "NS",
+            5 => "CNAME",
+            6 => "SOA",
+            12 => "PTR",
+            15 => "MX",
+            16 => "TXT",
+            28 => "AAAA",
+            33 => "SRV",
+            _ => return None,
+        },
+        Value::String(name) => match name.as_str() {
+            "A" | "NS" | "CNAME" | "SOA" | "PTR" | "MX" | "TXT" | "AAAA" | "SRV" => name.as_str(),
+            _ => return None,
+        },
+        _ => return None,
+    };
+    let value = match record_type {
+        "A" | "AAAA" => text("IPAddress")?.to_string(),
+        "NS" | "CNAME" | "PTR" => fqdn("NameHost")?,
+        "MX" => format!("{} {}", number("Preference")?, fqdn("NameExchange")?),
+        "TXT" => record
+            .get("Strings")?
+            .as_array()?
+            .iter()
+            .filter_map(Value::as_str)
+            .map(|s| format!("\"{}\"", s.replace('"', "\\\"")))
+            .collect::<Vec<_>>()
+            .join(" "),
+        "SOA" => format!(
+            "{} {} {} {} {} {} {}",
+            fqdn("PrimaryServer")?,
+            fqdn("NameAdministrator")?,
+            number("SerialNumber")?,
+            number("TimeToZoneRefresh")?,
+            number("TimeToZoneFailureRetry")?,
+            number("TimeToExpiration")?,
+            number("DefaultTTL")?
+        ),
+        "SRV" => format!(
+            "{} {} {} {}",
+            number("Priority")?,
+            number("Weight")?,
+            number("Port")?,
+            fqdn("NameTarget")?
+        ),
+        _ => return None,
+    };
+    Some(format!(
+        "{}\t{}\tIN\t{}\t{}",
+        fqdn("Name")?,
+        number("TTL").unwrap_or(0),
+        record_type,
+        value
+    ))
+}
+
+// The value after label, up to the next comma, semicolon or space
+fn field(line: &str, label: &str) -> Option<String> {
+    let start = line.find(label)? + label.len();
+    line[start..]
+        .split([',', ';', ' '])
+        .find(|s| !s.is_empty())
+        .map(str::to_string)
+}
+
+fn strip_prefix_ignore_case<'a>(line: &'a str, prefix: &str) -> Option<&'a str> {
+    line.get(..prefix.len())
+        .filter(|start| start.eq_ignore_ascii_case(prefix))
+        .map(|_| &line[prefix.len()..])
+}
+
+// "name TTL class type rdata", the class possibly before the TTL
+fn is_record(line: &str) -> bool {
+    let parts: Vec<&str> = line.split_whitespace().collect();
+    parts.len() >= 4 && parts[1..3].iter().any(|p| matches!(*p, "IN" | "CH" | "HS"))
+}
+
+// Runs DNS queries through whichever of dig, kdig, drill and host is installed, or
+// Resolve-DnsName on Windows
+pub struct DnsProcess {
+    tool: Option<DnsTool>, // None when none of them is
+}
+
+impl DnsProcess {
+    pub fn detect() -> Self {
+        DnsProcess {
+            tool: DnsTool::detect(),
+        }
+    }
+
+    pub fn locate() -> Self {
+        DnsProcess {
+            tool: DnsTool::locate(),
+        }
+    }
+
+    pub fn with_tool(tool: DnsTool) -> Self {
+        DnsProcess { tool: Some(tool) }
+    }
+
+    pub fn tool(&self) -> Option<DnsTool> {
+        self.tool
+    }
+}
+
+impl DnsBackend for DnsProcess {
+    fn is_available(&self) -> bool {
+        self.tool.is_some()
+    }
+
+    fn program(&self) -> &str {
+        self.tool.unwrap_or(DnsTool::Dig).name()
+    }
+
+    fn command_line(&self, args: &[String]) -> Vec<String> {
+        self.tool.unwrap_or(DnsTool::Dig).args(args)
+    }
+
+    fn dig<'a>(&'a self, args: &'a [String]) -> BackendFuture<'a, ProcessOutput> {
+        Box::pin(async move {
+            let tool = self.tool.unwrap_or(DnsTool::Dig);
+            if !matches!(tool, DnsTool::Dig | DnsTool::Kdig)
+                && args.iter().any(|a| a.starts_with("+https"))
+            {
+                return Err(format!(
+                    "DNS-over-HTTPS needs dig or kdig, which {} can't stand in for",
+                    tool.name()
+                ));
+            }
+            // host and Resolve-DnsName print no RRSIGs, so a signed zone would look unsigned
+            if matches!(tool, DnsTool::Host | DnsTool::ResolveDnsName)
+                && args.iter().any(|a| a == "+dnssec")
+            {
+                return Err(format!(
+                    "DNSSEC records need dig, kdig or drill, which {} can't stand in for",
+                    tool.name()
+                ));
+            }
+            // Use the async process API so concurrent queries don't block each other
+            let output = tokio::process::Command::new(tool.name())
+                .args(tool.args(args))
+                .output()
+                .await
+                .map_err(|e| format!("Failed to execute {}: {}", tool.name(), e))?;
+
+            let output = ProcessOutput {
+                stdout: String::from_utf8_lossy(&output.stdout).to_string(),
+                stderr: String::from_utf8_lossy(&output.stderr).to_string(),
+                exit_code: output.status.code().unwrap_or(-1),
+            };
+            Ok(tool.normalize(args, output))
+        })
+    }
+}
+
+#[cfg(test)]
+#[path = "dns_tools_test.rs"]
+mod tests;
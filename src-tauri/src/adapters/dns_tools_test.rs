@@ -0,0 +1,333 @@
+use super::super::backend::{BackendFuture, Backends, DnsBackend, ProcessOutput};
+use super::super::dns::DnsAdapter;
+use super::{DnsProcess, DnsTool};
+
+const KDIG_OUTPUT: &str = ";; ->>HEADER<<- opcode: QUERY; status: NOERROR; id: 28163
+;; Flags: qr rd ra; QUERY: 1; ANSWER: 2; AUTHORITY: 0; ADDITIONAL: 1
+
+;; EDNS PSEUDOSECTION:
+;; Version: 0; flags: ; UDP size: 1232 B; ext-rcode: NOERROR
+
+;; QUESTION SECTION:
+;; example.com.        		IN	A
+
+;; ANSWER SECTION:
+example.com.        	300	IN	A	192.0.2.1
+example.com.        	300	IN	A	192.0.2.2
+
+;; Received 72 B
+;; Time 2026-10-16 09:00:00 UTC
+;; From 192.0.2.53@53(TCP) in 10.2 ms
+";
+
+const DRILL_OUTPUT: &str = ";; ->>HEADER<<- opcode: QUERY, rcode: NXDOMAIN, id: 4242
+;; flags: qr aa rd ; QUERY: 1, ANSWER: 0, AUTHORITY: 1, ADDITIONAL: 0 
+;; QUESTION SECTION:
+;; missing.example.com.	IN	A
+
+;; ANSWER SECTION:
+
+;; AUTHORITY SECTION:
+example.com.	3600	IN	SOA	ns1.example.com. hostmaster.example.com. 2026101601 7200 3600 1209600 3600
+
+;; ADDITIONAL SECTION:
+
+;; Query time: 12 msec
+;; SERVER: 192.0.2.53
+;; WHEN: Fri Oct 16 09:00:00 2026
+;; MSG SIZE  rcvd: 92
+";
+
+const HOST_OUTPUT: &str = "Trying \"example.com\"
+;; ->>HEADER<<- opcode: QUERY, status: NOERROR, id: 4953
+;; flags: qr rd ra; QUERY: 1, ANSWER: 1, AUTHORITY: 0, ADDITIONAL: 0
+
+;; QUESTION SECTION:
+;example.com.			IN	MX
+
+;; ANSWER SECTION:
+example.com.		3600	IN	MX	10 mail.example.com.
+
+Received 49 bytes from 192.0.2.53#53 in 12 ms
+";
+
+fn args(args: &[&str]) -> Vec<String> {
+    args.iter().map(|a| a.to_string()).collect()
+}
+
+fn output(stdout: &str, exit_code: i32) -> ProcessOutput {
+    ProcessOutput {
+        stdout: stdout.to_string(),
+        stderr: String::new(),
+        exit_code,
+    }
+}
+
+#[test]
+fn test_args_translation() {
+    let dig = args(&[
+        "+time=5",
+        "+tries=3",
+        "-4",
+        "+norec",
+        "+noall",
+        "+comments",
+        "+answer",
+        "+dnssec",
+        "+tcp",
+        "@a.gtld-servers.net",
+        "DS",
+        "example.com",
+    ]);
+
+    assert_eq!(DnsTool::Dig.args(&dig), dig);
+    assert_eq!(
+        DnsTool::Kdig.args(&dig),
+        args(&[
+            "-4",
+            "+time=5",
+            "+retry=2",
+            "+norecurse",
+            "+dnssec",
+            "+tcp",
+            "@a.gtld-servers.net",
+            "example.com",
+            "DS"
+        ])
+    );
+    assert_eq!(
+        DnsTool::Drill.args(&dig),
+        args(&[
+            "-4",
+            "-t",
+            "-D",
+            "-o",
+            "rd",
+            "example.com",
+            "@a.gtld-servers.net",
+            "DS"
+        ])
+    );
+    assert_eq!(
+        DnsTool::Host.args(&dig),
+        args(&[
+            "-v",
+            "-4",
+            "-T",
+            "-r",
+            "-W",
+            "5",
+            "-R",
+            "2",
+            "-t",
+            "DS",
+            "example.com",
+            "a.gtld-servers.net"
+        ])
+    );
+
+    // The root zone, with the type before the name
+    assert_eq!(
+        DnsTool::Host.args(&args(&[".", "DNSKEY", "+multi"])),
+        args(&["-v", "-t", "DNSKEY", "."])
+    );
+}
+
+#[test]
+fn test_normalize_kdig() {
+    let dig = args(&["+noall", "+comments", "+answer", "A", "example.com"]);
+    let normalized = DnsTool::Kdig.normalize(&dig, output(KDIG_OUTPUT, 0));
+
+    assert_eq!(
+        normalized.stdout,
+        ";; ->>HEADER<<- opcode: QUERY, status: NOERROR, id: 28163
+;; flags: qr rd ra; QUERY: 1, ANSWER: 2, AUTHORITY: 0, ADDITIONAL: 1
+
+;; ANSWER SECTION:
+example.com.        	300	IN	A	192.0.2.1
+example.com.        	300	IN	A	192.0.2.2
+
+;; SERVER: 192.0.2.53#53(192.0.2.53) (TCP)
+"
+    );
+}
+
+#[test]
+fn test_normalize_drill_sections() {
+    // Only the answer was asked for, so the SOA in the authority section is left out
+    let answer_only = args(&["+noall", "+comments", "+answer", "A", "missing.example.com"]);
+    let normalized = DnsTool::Drill.normalize(&answer_only, output(DRILL_OUTPUT, 0));
+    assert!(normalized.stdout.contains("status: NXDOMAIN, id: 4242"));
+    assert!(normalized
+        .stdout
+        .contains(";; flags: qr aa rd; QUERY: 1, ANSWER: 0, AUTHORITY: 1"));
+    assert!(!normalized.stdout.contains("SOA"));
+    assert!(normalized.stdout.contains(";; SERVER: 192.0.2.53#53"));
+
+    let with_authority = args(&[
+        "+noall",
+        "+answer",
+        "+authority",
+        "A",
+        "missing.example.com",
+    ]);
+    let normalized = DnsTool::Drill.normalize(&with_authority, output(DRILL_OUTPUT, 0));
+    assert!(!normalized.stdout.contains("HEADER"));
+    assert!(normalized
+        .stdout
+        .contains(";; AUTHORITY SECTION:\nexample.com.\t3600\tIN\tSOA"));
+
+    // Nothing to normalize without a response
+    let unreachable = output("Error: error sending query: Could not send or receive\n", 1);
+    let normalized = DnsTool::Drill.normalize(&answer_only, unreachable);
+    assert_eq!(normalized.exit_code, 1);
+    assert!(normalized.stdout.starts_with("Error:"));
+}
+
+// Resolve-DnsName's records piped through ConvertTo-Json, as Windows PowerShell prints them
+const RESOLVE_DNS_NAME_OUTPUT: &str = r#"[
+{
+    "Name": "example.com",
+    "Type": 15,
+    "CharacterSet": 1,
+    "Section": 1,
+    "DataLength": 16,
+    "TTL": 3600,
+    "NameExchange": "mail.example.com",
+    "Preference": 10
+},
+{
+    "Name": "example.com",
+    "Type": 16,
+    "Section": 1,
+    "TTL": 300,
+    "Strings": ["v=spf1 -all"]
+},
+{
+    "Name": "mail.example.com",
+    "Type": 1,
+    "Section": 3,
+    "TTL": 300,
+    "IPAddress": "192.0.2.25"
+},
+{
+    "Name": "example.com",
+    "Type": 46,
+    "Section": 1,
+    "TTL": 300
+}
+]"#;
+
+#[test]
+fn test_resolve_dns_name() {
+    let dig = args(&[
+        "+noall",
+        "+comments",
+        "+answer",
+        "+additional",
+        "+tcp",
+        "@192.0.2.53",
+        "MX",
+        "example.com",
+    ]);
+    assert_eq!(
+        DnsTool::ResolveDnsName.args(&dig),
+        args(&[
+            "-NoProfile",
+            "-NonInteractive",
+            "-Command",
+            "ConvertTo-Json -Depth 3 -InputObject @(Resolve-DnsName -DnsOnly \
+             -Name 'example.com' -Type MX -Server '192.0.2.53' -TcpOnly)"
+        ])
+    );
+
+    // Record types it can't convert, like the RRSIG, are left out
+    let normalized = DnsTool::ResolveDnsName.normalize(&dig, output(RESOLVE_DNS_NAME_OUTPUT, 0));
+    assert_eq!(normalized.exit_code, 0);
+    assert_eq!(
+        normalized.stdout,
+        ";; ->>HEADER<<- opcode: QUERY, status: NOERROR, id: 0
+;; flags: qr rd; QUERY: 1, ANSWER: 2, AUTHORITY: 0, ADDITIONAL: 1
+
+;; ANSWER SECTION:
+example.com.\t3600\tIN\tMX\t10 mail.example.com.
+example.com.\t300\tIN\tTXT\t\"v=spf1 -all\"
+
+;; ADDITIONAL SECTION:
+mail.example.com.\t300\tIN\tA\t192.0.2.25
+
+;; SERVER: 192.0.2.53#53(192.0.2.53) (TCP)
+"
+    );
+
+    // Failed lookups are errors in PowerShell, but NXDOMAIN is a DNS answer
+    let nxdomain = ProcessOutput {
+        stdout: String::new(),
+        stderr: "Resolve-DnsName : missing.example.com : DNS name does not exist\n".to_string(),
+        exit_code: 1,
+    };
+    let normalized = DnsTool::ResolveDnsName.normalize(&dig, nxdomain);
+    assert_eq!(normalized.exit_code, 0);
+    assert!(normalized.stdout.contains("status: NXDOMAIN"));
+    assert!(normalized.stdout.contains("ANSWER: 0, AUTHORITY: 0"));
+
+    let timeout = ProcessOutput {
+        stderr: "Resolve-DnsName : example.com : This operation returned because the \
+                 timeout period expired\n"
+            .to_string(),
+        exit_code: 1,
+        ..ProcessOutput::default()
+    };
+    assert_eq!(
+        DnsTool::ResolveDnsName.normalize(&dig, timeout).exit_code,
+        1
+    );
+}
+
+// Answers like host -v does, through the same normalization as DnsProcess
+struct CannedHost;
+
+impl DnsBackend for CannedHost {
+    fn is_available(&self) -> bool {
+        true
+    }
+
+    fn program(&self) -> &str {
+        "host"
+    }
+
+    fn command_line(&self, args: &[String]) -> Vec<String> {
+        DnsTool::Host.args(args)
+    }
+
+    fn dig<'a>(&'a self, args: &'a [String]) -> BackendFuture<'a, ProcessOutput> {
+        Box::pin(async move { Ok(DnsTool::Host.normalize(args, output(HOST_OUTPUT, 1))) })
+    }
+}
+
+#[tokio::test]
+async fn test_query_through_host() {
+    let adapter = DnsAdapter::new().with_backends(Backends::default().with_dns(CannedHost));
+    let response = adapter.query("example.com", "MX").await.unwrap();
+
+    assert_eq!(response.records.len(), 1);
+    assert_eq!(response.records[0].value, "10 mail.example.com.");
+    assert_eq!(response.records[0].ttl, 3600);
+    assert_eq!(response.header.unwrap().rcode, "NOERROR");
+    assert_eq!(response.server.as_deref(), Some("192.0.2.53"));
+}
+
+#[tokio::test]
+async fn test_dnssec_needs_a_capable_tool() {
+    let dig = args(&["+dnssec", "DNSKEY", "example.com"]);
+    for tool in [DnsTool::Host, DnsTool::ResolveDnsName] {
+        let error = DnsProcess::with_tool(tool).dig(&dig).await.unwrap_err();
+        assert!(
+            error.starts_with("DNSSEC records need dig, kdig or drill"),
+            "{}",
+            error
+        );
+    }
+    // drill still gets the DO bit, as -D
+    assert!(DnsTool::Drill.args(&dig).contains(&"-D".to_string()));
+}
@@ -0,0 +1,2801 @@
+use crate::adapters::backend::{BackendFuture, Backends, FileBackend};
+use crate::adapters::certificate::CertificateAdapter;
+use crate::adapters::dns::DnsAdapter;
+use crate::adapters::http::HttpAdapter;
+use crate::models::certificate::{CertificateInfo, StartTls, TlsInfo};
+use crate::models::dns::DnsRecord;
+use crate::models::email::{
+    AutoconfigEndpoint, AutoconfigServer, AutodiscoveryReport, BlacklistReport, BlacklistResult,
+    DaneStatus, DkimLookup, DkimRecord, DkimSignatureCheck, DkimVerification, DmarcAggregateReport,
+    DmarcRecord, DmarcReportUri, DmarcSource, EmailConfig, MailProviderMatch, MailSrvRecord,
+    MxHost, MxTls, SpfEvaluation, SpfInclude, TlsaRecord,
+};
+use crate::state;
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use chrono::{DateTime, Datelike, Months, Utc};
+use flate2::read::GzDecoder;
+use regex::Regex;
+use ring::signature;
+use sha1::Sha1;
+use sha2::{Digest, Sha256};
+use std::io::{Cursor, Read};
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+use std::sync::Arc;
+use tauri::AppHandle;
+use tokio::sync::Semaphore;
+use tokio::task::JoinSet;
+use tracing::instrument;
+use x509_parser::prelude::{FromDer, SubjectPublicKeyInfo};
+
+// A message header field: its name and the raw field, folding included
+type HeaderField = (String, String);
+
+// Port MX hosts receive mail on from other servers
+const SMTP_PORT: u16 = 25;
+
+// SRV services mail clients look up (RFC 6186, RFC 8314) and Outlook's
+const MAIL_SRV_SERVICES: [&str; 7] = [
+    "_submissions._tcp",
+    "_submission._tcp",
+    "_imaps._tcp",
+    "_imap._tcp",
+    "_pop3s._tcp",
+    "_pop3._tcp",
+    "_autodiscover._tcp",
+];
+
+// Selectors tried for every domain, since DNS offers no way to list them
+const COMMON_DKIM_SELECTORS: [&str; 9] = [
+    "default",
+    "google",
+    "selector1",
+    "selector2",
+    "k1",
+    "k2",
+    "s1",
+    "s2",
+    "dkim",
+];
+
+// Selectors some providers sign with but too generic to point at one: a key
+// under s1 alone doesn't make a domain a SendGrid customer
+const GENERIC_DKIM_SELECTORS: [&str; 8] = ["default", "dkim", "k1", "k2", "s1", "s2", "mx", "smtp"];
+
+// What a mail or bulk sending provider's setup looks like: the MX hosts
+// it receives on, the SPF includes it asks customers to publish (first one
+// recommended, others regional alternatives) and the DKIM selectors it signs with
+struct MailProvider {
+    name: &'static str,
+    mx_suffixes: &'static [&'static str],
+    spf_includes: &'static [&'static str],
+    outdated_includes: &'static [&'static str], // Replaced by spf_includes[0]
+    dkim_selectors: &'static [&'static str],
+}
+
+const MAIL_PROVIDERS: [MailProvider; 13] = [
+    MailProvider {
+        name: "Google Workspace",
+        mx_suffixes: &["google.com", "googlemail.com"],
+        spf_includes: &["_spf.google.com"],
+        outdated_includes: &["aspmx.googlemail.com"],
+        dkim_selectors: &["google"],
+    },
+    MailProvider {
+        name: "Microsoft 365",
+        mx_suffixes: &["mail.protection.outlook.com", "mx.microsoft"],
+        spf_includes: &["spf.protection.outlook.com"],
+        outdated_includes: &["spf.messaging.microsoft.com", "outlook.com", "hotmail.com"],
+        dkim_selectors: &["selector1", "selector2"],
+    },
+    MailProvider {
+        name: "Fastmail",
+        mx_suffixes: &["messagingengine.com"],
+        spf_includes: &["spf.messagingengine.com"],
+        outdated_includes: &[],
+        dkim_selectors: &["fm1", "fm2", "fm3", "fd", "fd2"],
+    },
+    MailProvider {
+        name: "Zoho Mail",
+        mx_suffixes: &["zoho.com", "zoho.eu", "zoho.in", "zoho.com.au"],
+        spf_includes: &[
+            "zohomail.com",
+            "zohomail.eu",
+            "zohomail.in",
+            "zohomail.com.au",
+        ],
+        outdated_includes: &["zoho.com", "zoho.eu"],
+        dkim_selectors: &["zoho", "zmail"],
+    },
+    MailProvider {
+        name: "Proton Mail",
+        mx_suffixes: &["protonmail.ch"],
+        spf_includes: &["_spf.protonmail.ch"],
+        outdated_includes: &[],
+        dkim_selectors: &["protonmail", "protonmail2", "protonmail3"],
+    },
+    MailProvider {
+        name: "Cloudflare Email Routing",
+        mx_suffixes: &["mx.cloudflare.net"],
+        spf_includes: &["_spf.mx.cloudflare.net"],
+        outdated_includes: &[],
+        dkim_selectors: &["cf2024-1"],
+    },
+    MailProvider {
+        name: "Mailgun",
+        mx_suffixes: &["mailgun.org"],
+        spf_includes: &["mailgun.org", "eu.mailgun.org"],
+        outdated_includes: &[],
+        dkim_selectors: &["mx", "k1", "smtp"],
+    },
+    MailProvider {
+        name: "iCloud Mail",
+        mx_suffixes: &["icloud.com"],
+        spf_includes: &["icloud.com"],
+        outdated_includes: &[],
+        dkim_selectors: &["sig1"],
+    },
+    MailProvider {
+        name: "Yahoo",
+        mx_suffixes: &["yahoodns.net"],
+        spf_includes: &[],
+        outdated_includes: &[],
+        dkim_selectors: &["s1024", "s2048"],
+    },
+    MailProvider {
+        name: "Amazon SES",
+        mx_suffixes: &["amazonaws.com"],
+        spf_includes: &["amazonses.com"],
+        outdated_includes: &[],
+        dkim_selectors: &[], // Per-domain random selectors, published as CNAMEs
+    },
+    MailProvider {
+        name: "SendGrid",
+        mx_suffixes: &[],
+        spf_includes: &["sendgrid.net"],
+        outdated_includes: &[],
+        dkim_selectors: &["s1", "s2"],
+    },
+    MailProvider {
+        name: "Mailchimp",
+        mx_suffixes: &[],
+        spf_includes: &["servers.mcsv.net"],
+        outdated_includes: &[],
+        dkim_selectors: &["k2", "k3"],
+    },
+    MailProvider {
+        name: "Postmark",
+        mx_suffixes: &[],
+        spf_includes: &["spf.mtasv.net"],
+        outdated_includes: &[],
+        dkim_selectors: &[],
+    },
+];
+
+// Domain blocklists answering 127.0.0.1, rather than a listing, to refuse queries
+const DNSBL_REFUSING_ZONES: [&str; 2] = ["uribl.com", "surbl.org"];
+
+// Decompressed size a DMARC aggregate report may reach; real ones are far smaller
+const DMARC_REPORT_MAX_BYTES: u64 = 64 * 1024 * 1024;
+
+// RFC 7208 section 4.6.4 processing limits
+const SPF_LOOKUP_LIMIT: u32 = 10;
+const SPF_VOID_LOOKUP_LIMIT: u32 = 2;
+const SPF_MX_LIMIT: usize = 10; // Hosts looked up per mx mechanism
+const SPF_DEPTH_LIMIT: u32 = 10; // Nested include: and redirect=, a backstop for the lookup limit
+
+// One term of an SPF record; qualifier is '+', '-', '~' or '?'
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SpfTerm {
+    pub qualifier: char,
+    pub mechanism: SpfMechanism,
+}
+
+// Mechanisms, plus the redirect= and exp= modifiers. a and mx take an optional
+// domain and IPv4/IPv6 prefix lengths ("a:mail.example.com/24//64").
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SpfMechanism {
+    All,
+    Include(String),
+    A {
+        domain: Option<String>,
+        cidr4: Option<u8>,
+        cidr6: Option<u8>,
+    },
+    Mx {
+        domain: Option<String>,
+        cidr4: Option<u8>,
+        cidr6: Option<u8>,
+    },
+    Ptr(Option<String>),
+    Ip4(String),
+    Ip6(String),
+    Exists(String),
+    Redirect(String),
+    Explanation(String),
+}
+
+// A record waiting to be expanded
+struct SpfTarget {
+    domain: String,
+    via: Option<&'static str>, // None for the queried domain itself
+    depth: u32,
+    ancestors: Vec<String>, // Domains that led here, to catch include loops
+    top_level: bool,        // The domain itself or a redirect from it; its all counts
+}
+
+// Reads DMARC reports from the local filesystem
+pub struct LocalFiles;
+
+impl FileBackend for LocalFiles {
+    fn read<'a>(&'a self, path: &'a str) -> BackendFuture<'a, Vec<u8>> {
+        Box::pin(async move {
+            tokio::fs::read(path)
+                .await
+                .map_err(|e| format!("Failed to read {}: {}", path, e))
+        })
+    }
+}
+
+#[derive(Clone)]
+pub struct EmailAdapter {
+    app_handle: Option<AppHandle>,
+    backends: Backends,
+}
+
+impl EmailAdapter {
+    pub fn new() -> Self {
+        EmailAdapter {
+            app_handle: None,
+            backends: Backends::default(),
+        }
+    }
+
+    pub fn with_app_handle(app_handle: AppHandle) -> Self {
+        EmailAdapter {
+            backends: state::backends_for(Some(&app_handle)),
+            app_handle: Some(app_handle),
+        }
+    }
+
+    // Run the DNS, TLS and HTTP checks, and read reports, through these instead of
+    // the app's backends
+    pub fn with_backends(mut self, backends: Backends) -> Self {
+        self.backends = backends;
+        self
+    }
+
+    // DNS and TLS checks log under their own tools
+    fn dns(&self) -> DnsAdapter {
+        DnsAdapter::from_app_handle(self.app_handle.clone()).with_backends(self.backends.clone())
+    }
+
+    fn certificates(&self) -> CertificateAdapter {
+        CertificateAdapter::from_app_handle(self.app_handle.clone())
+            .with_backends(self.backends.clone())
+    }
+
+    fn http(&self) -> HttpAdapter {
+        HttpAdapter::from_app_handle(self.app_handle.clone()).with_backends(self.backends.clone())
+    }
+
+    // MX hosts of the domain, then their addresses and STARTTLS on each of them
+    // concurrently (bounded by max_concurrency). Without MX records the domain
+    // itself is checked as the implicit MX (RFC 5321 5.1), unless it publishes
+    // a null MX. selectors are extra DKIM selectors to try.
+    #[instrument(skip_all, fields(domain = %domain))]
+    pub async fn fetch_email_config(
+        &self,
+        domain: &str,
+        selectors: &[String],
+    ) -> Result<EmailConfig, String> {
+        let response = self.dns().query(domain, "MX").await?;
+        let mut mx_records = self.parse_mx(&response.records);
+        let null_mx = self.is_null_mx(&response.records);
+        let implicit = mx_records.is_empty() && !null_mx;
+        if implicit {
+            mx_records.push(MxHost {
+                priority: 0,
+                hostname: domain.to_string(),
+                ips: Vec::new(),
+                cname: None,
+                tls: None,
+                dane: None,
+            });
+        }
+
+        let settings = state::settings_for(self.app_handle.as_ref());
+        let permits = Arc::new(Semaphore::new(settings.max_concurrency.max(1)));
+        let mut tasks = JoinSet::new();
+        for (index, mx) in mx_records.iter().enumerate() {
+            let adapter = self.clone();
+            let host = mx.hostname.clone();
+            let permits = permits.clone();
+            tasks.spawn(async move {
+                let _permit = permits.acquire_owned().await;
+                let (ips, cname) = adapter.resolve_mx_host(&host).await;
+                // Nothing to connect to; the missing addresses are the finding
+                let (tls, dane) = if ips.is_empty() {
+                    (None, None)
+                } else {
+                    let (tls, dane) = adapter.check_mx_tls(&host).await;
+                    (Some(tls), dane)
+                };
+                (index, ips, cname, tls, dane)
+            });
+        }
+        while let Some(joined) = tasks.join_next().await {
+            if let Ok((index, ips, cname, tls, dane)) = joined {
+                let mx = &mut mx_records[index];
+                mx.ips = ips;
+                mx.cname = cname;
+                mx.tls = tls;
+                mx.dane = dane;
+            }
+        }
+
+        let encrypted = !mx_records.is_empty()
+            && mx_records
+                .iter()
+                .all(|mx| mx.tls.as_ref().is_some_and(|tls| tls.certificate_valid));
+        let implicit_mx = if implicit { mx_records.pop() } else { None };
+        let mut warnings = self.mx_warnings(domain, &mx_records, null_mx, implicit_mx.as_ref());
+        let spf = self.evaluate_spf(domain).await;
+        let (provider, dkim_selectors) =
+            self.dkim_selectors(selectors, &mx_records, spf.as_ref(), Utc::now());
+        let dkim = self.query_dkim(domain, provider, &dkim_selectors).await;
+        let dmarc = self.fetch_dmarc(domain).await;
+        let providers = self.detect_providers(&mx_records, spf.as_ref(), &dkim);
+        for provider in &providers {
+            for missing in &provider.missing {
+                warnings.push(format!("{}: missing {}", provider.name, missing));
+            }
+            for outdated in &provider.outdated {
+                warnings.push(format!("{}: outdated {}", provider.name, outdated));
+            }
+        }
+
+        Ok(EmailConfig {
+            domain: domain.to_string(),
+            mx_records,
+            null_mx,
+            implicit_mx,
+            encrypted,
+            spf,
+            dkim,
+            dmarc,
+            providers,
+            warnings,
+        })
+    }
+
+    // "10 mx1.example.com." -> (10, mx1.example.com), in the order senders try
+    // them. A null MX names no host and is left out.
+    pub fn parse_mx(&self, records: &[DnsRecord]) -> Vec<MxHost> {
+        let mut hosts: Vec<MxHost> = records
+            .iter()
+            .filter(|record| record.record_type == "MX")
+            .filter_map(|record| {
+                let (priority, hostname) = record.value.trim().split_once(char::is_whitespace)?;
+                let hostname = hostname.trim().trim_end_matches('.').to_lowercase();
+                if hostname.is_empty() {
+                    return None;
+                }
+                Some(MxHost {
+                    priority: priority.parse().ok()?,
+                    hostname,
+                    ips: Vec::new(),
+                    cname: None,
+                    tls: None,
+                    dane: None,
+                })
+            })
+            .collect();
+        hosts.sort_by(|a, b| (a.priority, &a.hostname).cmp(&(b.priority, &b.hostname)));
+        hosts.dedup_by(|a, b| a.priority == b.priority && a.hostname == b.hostname);
+        hosts
+    }
+
+    // "0 ." (RFC 7505): the domain declares it accepts no mail
+    pub fn is_null_mx(&self, records: &[DnsRecord]) -> bool {
+        records
+            .iter()
+            .filter(|record| record.record_type == "MX")
+            .any(|record| {
+                let mut parts = record.value.split_whitespace();
+                parts.next().is_some() && parts.next() == Some(".") && parts.next().is_none()
+            })
+    }
+
+    // A and AAAA records of an MX host, and the CNAME it points at if any
+    pub async fn resolve_mx_host(&self, host: &str) -> (Vec<String>, Option<String>) {
+        let dns = self.dns();
+        let (a, aaaa) = tokio::join!(dns.query(host, "A"), dns.query(host, "AAAA"));
+        let records: Vec<DnsRecord> = [a, aaaa]
+            .into_iter()
+            .filter_map(Result::ok)
+            .flat_map(|response| response.records)
+            .collect();
+
+        let ips = records
+            .iter()
+            .filter(|r| r.record_type == "A" || r.record_type == "AAAA")
+            .map(|r| r.value.clone())
+            .collect();
+        let cname = records
+            .iter()
+            .find(|r| r.record_type == "CNAME")
+            .map(|r| r.value.trim_end_matches('.').to_lowercase());
+        (ips, cname)
+    }
+
+    // Everything mail clients try when setting up an account: SRV records,
+    // Thunderbird's autoconfig files and Outlook's autodiscover endpoint.
+    // Hosts that resolve but don't answer usually point at a previous provider.
+    pub async fn check_autodiscovery(&self, domain: &str) -> AutodiscoveryReport {
+        let autoconfig = format!("autoconfig.{}", domain);
+        let autodiscover = format!("autodiscover.{}", domain);
+        let (srv_records, autoconfig_xml, well_known_xml, autodiscover_xml) = tokio::join!(
+            self.query_mail_srv(domain),
+            self.fetch_autoconfig(
+                "autoconfig",
+                Some(&autoconfig),
+                format!(
+                    "https://{}/mail/config-v1.1.xml?emailaddress=info@{}",
+                    autoconfig, domain
+                ),
+                domain,
+            ),
+            self.fetch_autoconfig(
+                "autoconfig (well-known)",
+                None,
+                format!(
+                    "https://{}/.well-known/autoconfig/mail/config-v1.1.xml",
+                    domain
+                ),
+                domain,
+            ),
+            self.fetch_autodiscover(&autodiscover, domain),
+        );
+        let endpoints = vec![autoconfig_xml, well_known_xml, autodiscover_xml];
+
+        let mut mechanisms = Vec::new();
+        if srv_records.iter().any(|r| !r.target.is_empty()) {
+            mechanisms.push("srv".to_string());
+        }
+        for endpoint in endpoints.iter().filter(|e| e.configured) {
+            let mechanism = endpoint.mechanism.split(' ').next().unwrap_or_default();
+            if !mechanisms.iter().any(|m| m == mechanism) {
+                mechanisms.push(mechanism.to_string());
+            }
+        }
+
+        let warnings = self.autodiscovery_warnings(&srv_records, &endpoints, &mechanisms);
+        AutodiscoveryReport {
+            domain: domain.to_string(),
+            srv_records,
+            endpoints,
+            mechanisms,
+            warnings,
+        }
+    }
+
+    async fn query_mail_srv(&self, domain: &str) -> Vec<MailSrvRecord> {
+        let dns = self.dns();
+        let mut records = Vec::new();
+        for service in MAIL_SRV_SERVICES {
+            let Ok(response) = dns.query(&format!("{}.{}", service, domain), "SRV").await else {
+                continue;
+            };
+            for record in response.records.iter().filter(|r| r.record_type == "SRV") {
+                if let Some(mut srv) = self.parse_mail_srv(service, &record.value) {
+                    if !srv.target.is_empty() {
+                        srv.resolves = !self.resolve_mx_host(&srv.target).await.0.is_empty();
+                    }
+                    records.push(srv);
+                }
+            }
+        }
+        records
+    }
+
+    // "0 1 993 imap.example.com." -> priority, weight, port and target
+    pub fn parse_mail_srv(&self, service: &str, value: &str) -> Option<MailSrvRecord> {
+        let fields: Vec<&str> = value.split_whitespace().collect();
+        let [priority, weight, port, target] = fields.as_slice() else {
+            return None;
+        };
+
+        Some(MailSrvRecord {
+            service: service.to_string(),
+            priority: priority.parse().ok()?,
+            weight: weight.parse().ok()?,
+            port: port.parse().ok()?,
+            target: target.trim_end_matches('.').to_lowercase(),
+            resolves: false,
+        })
+    }
+
+    // host is the autoconfig subdomain, checked first so an unused name isn't
+    // reported as a broken endpoint
+    async fn fetch_autoconfig(
+        &self,
+        mechanism: &str,
+        host: Option<&str>,
+        url: String,
+        domain: &str,
+    ) -> AutoconfigEndpoint {
+        let mut endpoint = AutoconfigEndpoint {
+            mechanism: mechanism.to_string(),
+            url,
+            status_code: None,
+            configured: false,
+            servers: Vec::new(),
+            error: None,
+        };
+        if let Some(host) = host {
+            if self.resolve_mx_host(host).await.0.is_empty() {
+                endpoint.error = Some(format!("{} does not resolve", host));
+                return endpoint;
+            }
+        }
+
+        let settings = state::settings_for(self.app_handle.as_ref());
+        match self
+            .http()
+            .get_text(
+                &endpoint.url,
+                "application/xml, text/xml, */*",
+                settings.http_timeout_secs,
+                settings.http_retries,
+                domain,
+            )
+            .await
+        {
+            Ok((status, body)) => {
+                endpoint.status_code = Some(status);
+                if (200..300).contains(&status) {
+                    match self.parse_autoconfig(&body) {
+                        Ok(servers) => {
+                            endpoint.configured = true;
+                            endpoint.servers = servers;
+                        }
+                        Err(e) => endpoint.error = Some(e),
+                    }
+                }
+            }
+            Err(e) => endpoint.error = Some(e),
+        }
+        endpoint
+    }
+
+    // Autodiscover answers POSTs with credentials; a GET that gets an
+    // authentication challenge or an XML error still shows the service is there
+    async fn fetch_autodiscover(&self, host: &str, domain: &str) -> AutoconfigEndpoint {
+        let mut endpoint = AutoconfigEndpoint {
+            mechanism: "autodiscover".to_string(),
+            url: format!("https://{}/autodiscover/autodiscover.xml", host),
+            status_code: None,
+            configured: false,
+            servers: Vec::new(),
+            error: None,
+        };
+        if self.resolve_mx_host(host).await.0.is_empty() {
+            endpoint.error = Some(format!("{} does not resolve", host));
+            return endpoint;
+        }
+
+        let settings = state::settings_for(self.app_handle.as_ref());
+        match self
+            .http()
+            .get_text(
+                &endpoint.url,
+                "text/xml, */*",
+                settings.http_timeout_secs,
+                settings.http_retries,
+                domain,
+            )
+            .await
+        {
+            Ok((status, body)) => {
+                endpoint.status_code = Some(status);
+                endpoint.configured = status == 401
+                    || ((200..300).contains(&status) && body.contains("Autodiscover"));
+            }
+            Err(e) => endpoint.error = Some(e),
+        }
+        endpoint
+    }
+
+    // incomingServer and outgoingServer entries of a Thunderbird autoconfig file
+    pub fn parse_autoconfig(&self, xml: &str) -> Result<Vec<AutoconfigServer>, String> {
+        let document = roxmltree::Document::parse(xml)
+            .map_err(|_| "Not an autoconfig file: the response isn't XML".to_string())?;
+        if document.root_element().tag_name().name() != "clientConfig" {
+            return Err("Not an autoconfig file: <clientConfig> is missing".to_string());
+        }
+
+        let text = |node, name| {
+            self.xml_child(node, name)
+                .and_then(|n| n.text())
+                .map(|t| t.trim().to_string())
+        };
+        Ok(document
+            .descendants()
+            .filter(|n| {
+                n.is_element() && matches!(n.tag_name().name(), "incomingServer" | "outgoingServer")
+            })
+            .filter_map(|server| {
+                Some(AutoconfigServer {
+                    protocol: server.attribute("type").unwrap_or_default().to_lowercase(),
+                    hostname: text(server, "hostname")?.to_lowercase(),
+                    port: text(server, "port").and_then(|port| port.parse().ok()),
+                    socket_type: text(server, "socketType"),
+                })
+            })
+            .collect())
+    }
+
+    pub fn autodiscovery_warnings(
+        &self,
+        srv_records: &[MailSrvRecord],
+        endpoints: &[AutoconfigEndpoint],
+        mechanisms: &[String],
+    ) -> Vec<String> {
+        let mut warnings = Vec::new();
+        if mechanisms.is_empty() {
+            warnings.push(
+                "No autoconfiguration: mail clients have to guess or be set up by hand".to_string(),
+            );
+        }
+
+        for srv in srv_records {
+            if !srv.target.is_empty() && !srv.resolves {
+                warnings.push(format!(
+                    "{} points to {}, which does not resolve",
+                    srv.service, srv.target
+                ));
+            }
+        }
+
+        for endpoint in endpoints {
+            // Unresolvable subdomains simply mean the mechanism isn't used
+            let unused = endpoint
+                .error
+                .as_ref()
+                .is_some_and(|e| e.ends_with("does not resolve"));
+            if !endpoint.configured && !unused && endpoint.mechanism != "autoconfig (well-known)" {
+                let problem = match (&endpoint.error, endpoint.status_code) {
+                    (Some(error), _) => error.clone(),
+                    (None, Some(status)) => format!("HTTP {}", status),
+                    (None, None) => "no answer".to_string(),
+                };
+                warnings.push(format!(
+                    "{} exists but doesn't serve {} ({}); it may still point at a previous provider",
+                    endpoint.url, endpoint.mechanism, problem
+                ));
+            }
+            for server in endpoint.servers.iter().filter(|s| {
+                s.socket_type
+                    .as_deref()
+                    .is_some_and(|t| t.eq_ignore_ascii_case("plain"))
+            }) {
+                warnings.push(format!(
+                    "{} lists {} {} without encryption",
+                    endpoint.mechanism, server.protocol, server.hostname
+                ));
+            }
+        }
+
+        warnings
+    }
+
+    // Each MX address against the IP blocklists and the domain against the
+    // domain blocklists from settings, queried concurrently (bounded by
+    // max_concurrency)
+    #[instrument(skip_all, fields(domain = %domain))]
+    pub async fn check_blacklists(&self, domain: &str) -> BlacklistReport {
+        let settings = state::settings_for(self.app_handle.as_ref());
+        let mut warnings = Vec::new();
+
+        let mut ips: Vec<String> = Vec::new();
+        match self.dns().query(domain, "MX").await {
+            Ok(response) => {
+                for mx in self.parse_mx(&response.records) {
+                    for ip in self.resolve_mx_host(&mx.hostname).await.0 {
+                        if !ips.contains(&ip) {
+                            ips.push(ip);
+                        }
+                    }
+                }
+            }
+            Err(e) => warnings.push(format!("MX lookup failed: {}", e)),
+        }
+        if ips.is_empty() {
+            warnings.push("No MX addresses to check".to_string());
+        }
+
+        let mut checks = Vec::new();
+        for ip in &ips {
+            for zone in &settings.dnsbl_ip_zones {
+                if let Some(query) = self.dnsbl_query_name(ip, zone) {
+                    checks.push((ip.clone(), zone.clone(), query));
+                }
+            }
+        }
+        for zone in &settings.dnsbl_domain_zones {
+            let query = format!("{}.{}", domain, zone.trim_end_matches('.'));
+            checks.push((domain.to_string(), zone.clone(), query));
+        }
+
+        let permits = Arc::new(Semaphore::new(settings.max_concurrency.max(1)));
+        let mut tasks = JoinSet::new();
+        for (index, (target, list, query)) in checks.into_iter().enumerate() {
+            let adapter = self.clone();
+            let permits = permits.clone();
+            tasks.spawn(async move {
+                let _permit = permits.acquire_owned().await;
+                (index, adapter.query_blacklist(target, list, query).await)
+            });
+        }
+        let mut results = Vec::new();
+        while let Some(joined) = tasks.join_next().await {
+            if let Ok(entry) = joined {
+                results.push(entry);
+            }
+        }
+        results.sort_by_key(|(index, _)| *index);
+        let results: Vec<BlacklistResult> = results.into_iter().map(|(_, r)| r).collect();
+
+        for result in &results {
+            if result.listed {
+                warnings.push(format!(
+                    "{} is listed on {} ({})",
+                    result.target,
+                    result.list,
+                    result.codes.join(", ")
+                ));
+            }
+        }
+
+        BlacklistReport {
+            domain: domain.to_string(),
+            ips,
+            listed_count: results.iter().filter(|r| r.listed).count(),
+            results,
+            warnings,
+        }
+    }
+
+    async fn query_blacklist(
+        &self,
+        target: String,
+        list: String,
+        query: String,
+    ) -> BlacklistResult {
+        let dns = self.dns();
+        let mut result = BlacklistResult {
+            list,
+            target,
+            query,
+            listed: false,
+            codes: Vec::new(),
+            reason: None,
+            error: None,
+        };
+
+        match dns.query(&result.query, "A").await {
+            Ok(response) => {
+                result.codes = response
+                    .records
+                    .iter()
+                    .filter(|r| r.record_type == "A")
+                    .map(|r| r.value.clone())
+                    .collect();
+            }
+            Err(e) => {
+                result.error = Some(e);
+                return result;
+            }
+        }
+
+        (result.listed, result.error) = self.interpret_dnsbl_codes(&result.list, &result.codes);
+        if result.listed {
+            result.reason = dns
+                .query(&result.query, "TXT")
+                .await
+                .ok()
+                .and_then(|response| {
+                    response
+                        .records
+                        .iter()
+                        .find(|r| r.record_type == "TXT")
+                        .map(|r| dns.txt_value(&r.value))
+                });
+        }
+        result
+    }
+
+    // Listings answer with 127.0.0.0/8 addresses. Spamhaus answers 127.255.255.x,
+    // and URIBL and SURBL 127.0.0.1, to refuse queries from public resolvers or
+    // over their free quota; anything outside 127/8 suggests a defunct zone that
+    // now resolves everything.
+    pub fn interpret_dnsbl_codes(&self, zone: &str, codes: &[String]) -> (bool, Option<String>) {
+        if codes.is_empty() {
+            return (false, None);
+        }
+        let zone = zone.trim_end_matches('.').to_lowercase();
+        let answers_refusal_as_localhost = DNSBL_REFUSING_ZONES
+            .iter()
+            .any(|refusing| zone == *refusing || zone.ends_with(&format!(".{}", refusing)));
+        if codes.iter().any(|code| {
+            code.starts_with("127.255.255.")
+                || (answers_refusal_as_localhost && code == "127.0.0.1")
+        }) {
+            return (
+                false,
+                Some(format!(
+                    "The list refused the query ({}); it may block public resolvers",
+                    codes.join(", ")
+                )),
+            );
+        }
+        if codes.iter().any(|code| !code.starts_with("127.")) {
+            return (
+                false,
+                Some(format!(
+                    "Unexpected answer ({}); the list may be defunct",
+                    codes.join(", ")
+                )),
+            );
+        }
+        (true, None)
+    }
+
+    // 192.0.2.1 -> 1.2.0.192.<zone>; IPv6 addresses as 32 reversed nibbles
+    pub fn dnsbl_query_name(&self, ip: &str, zone: &str) -> Option<String> {
+        let reversed = match ip.parse::<IpAddr>().ok()? {
+            IpAddr::V4(ip) => ip
+                .octets()
+                .iter()
+                .rev()
+                .map(|octet| octet.to_string())
+                .collect::<Vec<_>>()
+                .join("."),
+            IpAddr::V6(ip) => format!("{:032x}", u128::from(ip))
+                .chars()
+                .rev()
+                .map(|nibble| nibble.to_string())
+                .collect::<Vec<_>>()
+                .join("."),
+        };
+        Some(format!("{}.{}", reversed, zone.trim_end_matches('.')))
+    }
+
+    // Connect to port 25, upgrade with STARTTLS and check the certificate; when
+    // the host publishes TLSA records, verify them against it (RFC 7672)
+    #[instrument(skip_all, fields(host = %host))]
+    pub async fn check_mx_tls(&self, host: &str) -> (MxTls, Option<DaneStatus>) {
+        let certificates = self.certificates();
+        let (info, dane) = tokio::join!(
+            certificates.get_certificate_info(host, SMTP_PORT, Some(StartTls::Smtp)),
+            self.query_tlsa(host)
+        );
+
+        let mut tls = match &info {
+            Ok(info) => self.summarize_tls(host, info),
+            Err(e) => MxTls {
+                error: Some(e.clone()),
+                ..MxTls::default()
+            },
+        };
+        let dane = dane.map(|mut dane| {
+            let chain = info
+                .as_ref()
+                .map(|info| info.certificate_chain.certificates.as_slice())
+                .unwrap_or_default();
+            self.verify_dane(host, &mut dane, chain, &tls);
+            dane
+        });
+        if let Some(dane) = &dane {
+            self.apply_dane(&mut tls, dane);
+        }
+        (tls, dane)
+    }
+
+    // TLSA records at _25._tcp.<host>; None when it publishes none
+    async fn query_tlsa(&self, host: &str) -> Option<DaneStatus> {
+        let response = self
+            .dns()
+            .query(&format!("_25._tcp.{}", host), "TLSA")
+            .await
+            .ok()?;
+        let records: Vec<TlsaRecord> = response
+            .records
+            .iter()
+            .filter(|r| r.record_type == "TLSA")
+            .filter_map(|r| self.parse_tlsa(&r.value))
+            .collect();
+        if records.is_empty() {
+            return None;
+        }
+
+        Some(DaneStatus {
+            records,
+            authenticated: response
+                .header
+                .as_ref()
+                .is_some_and(|header| header.authenticated_data),
+            valid: false,
+            warnings: Vec::new(),
+        })
+    }
+
+    // "3 1 1 0C72AC70...B566 64C5D3D6", with the hex split as dig prints it
+    pub fn parse_tlsa(&self, value: &str) -> Option<TlsaRecord> {
+        let mut fields = value.split_whitespace();
+        let usage = fields.next()?.parse().ok()?;
+        let selector = fields.next()?.parse().ok()?;
+        let matching_type = fields.next()?.parse().ok()?;
+        let data = fields.collect::<String>().to_lowercase();
+        if data.is_empty() || !data.chars().all(|c| c.is_ascii_hexdigit()) {
+            return None;
+        }
+
+        Some(TlsaRecord {
+            usage,
+            selector,
+            matching_type,
+            data,
+            matched: false,
+            error: None,
+        })
+    }
+
+    // DANE-EE (3) records match the leaf and DANE-TA (2) records a CA in the
+    // presented chain. Senders only trust DNSSEC-validated TLSA records, and
+    // defer mail when usable records exist but none match (RFC 7672 2.2).
+    pub fn verify_dane(
+        &self,
+        host: &str,
+        dane: &mut DaneStatus,
+        chain: &[CertificateInfo],
+        tls: &MxTls,
+    ) {
+        if !dane.authenticated {
+            dane.warnings.push(format!(
+                "The TLSA records of {} aren't DNSSEC-validated by the resolver; senders ignore them",
+                host
+            ));
+        }
+
+        let certificates = self.certificates();
+        for record in &mut dane.records {
+            let candidates = match record.usage {
+                3 => chain.get(..1),
+                2 => chain.get(1..),
+                _ => {
+                    record.error = Some(format!(
+                        "Usage {} (PKIX) is unusable for SMTP (RFC 7672 3.1.3)",
+                        record.usage
+                    ));
+                    continue;
+                }
+            };
+            if record.selector > 1 || record.matching_type > 2 {
+                record.error = Some(format!(
+                    "Unknown selector {} or matching type {}",
+                    record.selector, record.matching_type
+                ));
+                continue;
+            }
+
+            record.matched = candidates.unwrap_or_default().iter().any(|cert| {
+                certificates
+                    .tlsa_association(&cert.der, record.selector, record.matching_type)
+                    .is_some_and(|data| data == record.data)
+            });
+            // DANE-TA still requires the certificate to name the host (RFC 7672 3.2.2)
+            if record.matched && record.usage == 2 && !tls.hostname_matches {
+                record.matched = false;
+                record.error = Some(format!(
+                    "The trust anchor matches but the certificate doesn't cover {}",
+                    host
+                ));
+            }
+        }
+
+        let usable = dane.records.iter().any(|r| r.error.is_none());
+        let matched = dane.records.iter().any(|r| r.matched);
+        if let Some(error) = &tls.error {
+            dane.warnings.push(format!(
+                "{} publishes TLSA records but TLS failed ({}); DANE senders defer mail to it",
+                host, error
+            ));
+        } else if !usable {
+            dane.warnings.push(format!(
+                "None of the TLSA records of {} are usable for SMTP; senders require TLS without authenticating it",
+                host
+            ));
+        } else if !matched {
+            dane.warnings.push(format!(
+                "No TLSA record of {} matches the certificate it presents; DANE senders defer mail to it",
+                host
+            ));
+        }
+        dane.valid = dane.authenticated && matched;
+    }
+
+    // A validated DANE-EE match authenticates the leaf by itself: senders skip
+    // PKIX, name and expiry checks for it (RFC 7672 3.1.1), so neither do we
+    pub fn apply_dane(&self, tls: &mut MxTls, dane: &DaneStatus) {
+        if dane.valid && dane.records.iter().any(|r| r.usage == 3 && r.matched) {
+            tls.certificate_valid = true;
+            tls.validation_errors.clear();
+        }
+    }
+
+    pub fn summarize_tls(&self, host: &str, info: &TlsInfo) -> MxTls {
+        let chain = &info.certificate_chain;
+        let Some(leaf) = chain.certificates.first() else {
+            return MxTls {
+                error: Some("No certificates presented".to_string()),
+                ..MxTls::default()
+            };
+        };
+        let hostname_matches = self.certificates().hostname_warnings(host, leaf).is_empty();
+        let expired = leaf.days_until_expiry.is_some_and(|days| days < 0);
+
+        MxTls {
+            starttls: true,
+            certificate_valid: chain.is_valid && hostname_matches && !expired,
+            hostname_matches,
+            common_name: leaf.subject.common_name.clone(),
+            issuer: leaf.issuer.common_name.clone(),
+            not_after: Some(leaf.not_after.clone()),
+            days_until_expiry: leaf.days_until_expiry,
+            validation_errors: chain.validation_errors.clone(),
+            warnings: info.warnings.clone(),
+            error: None,
+        }
+    }
+
+    // Selectors to try, each with why: the caller's own and those in the settings,
+    // then those of the mail provider recognized from the MX hosts and of senders
+    // included in SPF, then common ones. Microsoft 365 tenants with custom keys often rotate to
+    // date-based selectors, so recent months (YYYYMM) are tried for them too.
+    pub fn dkim_selectors(
+        &self,
+        custom: &[String],
+        mx_records: &[MxHost],
+        spf: Option<&SpfEvaluation>,
+        now: DateTime<Utc>,
+    ) -> (Option<String>, Vec<(String, &'static str)>) {
+        let mut selectors: Vec<(String, &'static str)> = Vec::new();
+        let mut add = |selector: String, source: &'static str| {
+            if !selectors.iter().any(|(s, _)| *s == selector) {
+                selectors.push((selector, source));
+            }
+        };
+
+        for selector in custom {
+            add(selector.trim().to_lowercase(), "custom");
+        }
+        for selector in state::settings_for(self.app_handle.as_ref()).dkim_selectors {
+            add(selector.trim().to_lowercase(), "settings");
+        }
+
+        let provider = MAIL_PROVIDERS
+            .iter()
+            .find(|provider| self.provider_mx(provider, mx_records).is_some());
+        if let Some(provider) = provider {
+            for selector in provider.dkim_selectors {
+                add(selector.to_string(), "provider");
+            }
+            if provider.name == "Microsoft 365" {
+                for months_ago in 0..3 {
+                    if let Some(date) = now.checked_sub_months(Months::new(months_ago)) {
+                        add(format!("{}{:02}", date.year(), date.month()), "provider");
+                    }
+                }
+            }
+        }
+
+        for sender in MAIL_PROVIDERS
+            .iter()
+            .filter(|p| self.provider_include(p, spf).is_some())
+        {
+            for selector in sender.dkim_selectors {
+                add(selector.to_string(), "provider");
+            }
+        }
+
+        for selector in COMMON_DKIM_SELECTORS {
+            add(selector.to_string(), "common");
+        }
+
+        (provider.map(|p| p.name.to_string()), selectors)
+    }
+
+    // Providers recognized from the MX hosts, SPF includes and DKIM keys found,
+    // with the records each provider asks for that the domain lacks. DKIM keys
+    // only count as evidence under selectors no other provider uses.
+    pub fn detect_providers(
+        &self,
+        mx_records: &[MxHost],
+        spf: Option<&SpfEvaluation>,
+        dkim: &DkimLookup,
+    ) -> Vec<MailProviderMatch> {
+        let mut matches = Vec::new();
+        for provider in &MAIL_PROVIDERS {
+            let mut evidence = Vec::new();
+            if let Some(mx) = self.provider_mx(provider, mx_records) {
+                evidence.push(format!("MX {}", mx));
+            }
+            let include = self.provider_include(provider, spf);
+            if let Some(include) = &include {
+                evidence.push(format!("SPF include:{}", include));
+            }
+            // Date-based Microsoft 365 selectors aren't in the table
+            let signed_by = |key: &DkimRecord| {
+                let dated =
+                    key.selector.len() == 6 && key.selector.chars().all(|c| c.is_ascii_digit());
+                provider.dkim_selectors.contains(&key.selector.as_str())
+                    || (provider.name == "Microsoft 365" && dated)
+            };
+            let keys: Vec<&DkimRecord> = dkim.records.iter().filter(|k| signed_by(k)).collect();
+            for key in &keys {
+                let shared = MAIL_PROVIDERS.iter().any(|other| {
+                    other.name != provider.name
+                        && other.dkim_selectors.contains(&key.selector.as_str())
+                });
+                if !shared && !GENERIC_DKIM_SELECTORS.contains(&key.selector.as_str()) {
+                    evidence.push(format!("DKIM selector {}", key.selector));
+                }
+            }
+            if evidence.is_empty() {
+                continue;
+            }
+
+            let mut missing = Vec::new();
+            let mut outdated = Vec::new();
+            if let Some(recommended) = provider.spf_includes.first() {
+                match &include {
+                    Some(found) if provider.outdated_includes.contains(&found.as_str()) => {
+                        outdated.push(format!(
+                            "SPF include:{} (use include:{})",
+                            found, recommended
+                        ));
+                    }
+                    Some(_) => {}
+                    None => missing.push(format!("SPF include:{}", recommended)),
+                }
+            }
+            if keys.is_empty() && !provider.dkim_selectors.is_empty() {
+                missing.push(format!(
+                    "DKIM key (selector {})",
+                    provider.dkim_selectors.join(" or ")
+                ));
+            }
+
+            matches.push(MailProviderMatch {
+                name: provider.name.to_string(),
+                evidence,
+                missing,
+                outdated,
+            });
+        }
+        matches
+    }
+
+    fn provider_mx(&self, provider: &MailProvider, mx_records: &[MxHost]) -> Option<String> {
+        mx_records
+            .iter()
+            .find(|mx| {
+                provider.mx_suffixes.iter().any(|suffix| {
+                    mx.hostname == *suffix || mx.hostname.ends_with(&format!(".{}", suffix))
+                })
+            })
+            .map(|mx| mx.hostname.clone())
+    }
+
+    // Current includes first, so a record with both isn't reported as outdated
+    fn provider_include(
+        &self,
+        provider: &MailProvider,
+        spf: Option<&SpfEvaluation>,
+    ) -> Option<String> {
+        let includes = &spf?.includes;
+        provider
+            .spf_includes
+            .iter()
+            .chain(provider.outdated_includes)
+            .find(|name| includes.iter().any(|i| i.domain.eq_ignore_ascii_case(name)))
+            .map(|name| name.to_string())
+    }
+
+    // Query <selector>._domainkey.<domain> for each selector concurrently
+    pub async fn query_dkim(
+        &self,
+        domain: &str,
+        provider: Option<String>,
+        selectors: &[(String, &'static str)],
+    ) -> DkimLookup {
+        let settings = state::settings_for(self.app_handle.as_ref());
+        let permits = Arc::new(Semaphore::new(settings.max_concurrency.max(1)));
+        let mut tasks = JoinSet::new();
+        for (index, (selector, _)) in selectors.iter().enumerate() {
+            let dns = self.dns();
+            let name = format!("{}._domainkey.{}", selector, domain);
+            let permits = permits.clone();
+            tasks.spawn(async move {
+                let _permit = permits.acquire_owned().await;
+                let records = dns
+                    .query(&name, "TXT")
+                    .await
+                    .map(|response| response.records)
+                    .unwrap_or_default();
+                (index, records)
+            });
+        }
+
+        let mut found = Vec::new();
+        while let Some(joined) = tasks.join_next().await {
+            let Ok((index, records)) = joined else {
+                continue;
+            };
+            let (selector, source) = &selectors[index];
+            // Selectors delegated with a CNAME come back with the CNAME first
+            let value = records
+                .iter()
+                .filter(|r| r.record_type == "TXT")
+                .map(|r| self.dns().txt_value(&r.value))
+                .find(|value| value.contains("p="));
+            if let Some(value) = value {
+                found.push((index, self.parse_dkim(selector, &value, source)));
+            }
+        }
+        found.sort_by_key(|(index, _)| *index);
+
+        DkimLookup {
+            provider,
+            tried: selectors.iter().map(|(s, _)| s.clone()).collect(),
+            records: found.into_iter().map(|(_, record)| record).collect(),
+        }
+    }
+
+    // Tag list of a DKIM key record (RFC 6376 section 3.6.1)
+    pub fn parse_dkim(&self, selector: &str, record: &str, source: &str) -> DkimRecord {
+        let tag = |name: &str| {
+            record.split(';').find_map(|pair| {
+                let (key, value) = pair.split_once('=')?;
+                (key.trim() == name).then(|| value.trim().to_string())
+            })
+        };
+
+        DkimRecord {
+            selector: selector.to_string(),
+            record: record.to_string(),
+            key_type: tag("k")
+                .map(|k| k.to_lowercase())
+                .unwrap_or_else(|| "rsa".to_string()),
+            testing: tag("t").is_some_and(|flags| flags.split(':').any(|f| f.trim() == "y")),
+            revoked: tag("p").is_some_and(|key| key.is_empty()),
+            source: source.to_string(),
+        }
+    }
+
+    // Check every DKIM-Signature of a message against the key its selector
+    // publishes (RFC 6376 section 6). Headers alone verify the signature but
+    // not the body hash.
+    pub async fn verify_dkim(&self, message: &str) -> Result<DkimVerification, String> {
+        let (headers, body) = self.split_message(message)?;
+        let mut warnings = Vec::new();
+        let from_count = headers
+            .iter()
+            .filter(|(name, _)| name.eq_ignore_ascii_case("from"))
+            .count();
+        if from_count > 1 {
+            warnings.push(format!(
+                "The message has {} From headers; receivers reject it",
+                from_count
+            ));
+        }
+        let from_domain = self.sender_domain(&headers);
+
+        let now = Utc::now();
+        let mut signatures = Vec::new();
+        for (index, (name, raw)) in headers.iter().enumerate() {
+            if !name.eq_ignore_ascii_case("dkim-signature") {
+                continue;
+            }
+            let tags = self.dkim_tags(raw.split_once(':').map(|(_, v)| v).unwrap_or_default());
+            let tag = |name: &str| tags.iter().find(|(k, _)| k == name).map(|(_, v)| v.clone());
+            let key = match (tag("d"), tag("s")) {
+                (Some(domain), Some(selector)) => self.fetch_dkim_key(&domain, &selector).await,
+                _ => Err("The signature has no d= or s= tag".to_string()),
+            };
+            signatures.push(self.check_dkim_signature(
+                &headers,
+                body.as_deref(),
+                index,
+                key,
+                from_domain.as_deref(),
+                now,
+            ));
+        }
+
+        if signatures.is_empty() {
+            warnings.push("The message has no DKIM-Signature header".to_string());
+        } else {
+            if body.is_none() {
+                warnings
+                    .push("Only headers were given, so body hashes weren't checked".to_string());
+            }
+            if let Some(from) = &from_domain {
+                if !signatures.iter().any(|s| s.valid && s.aligned) {
+                    warnings.push(format!(
+                        "No valid signature is aligned with {}; DMARC's DKIM check fails",
+                        from
+                    ));
+                }
+            }
+        }
+
+        Ok(DkimVerification {
+            from_domain,
+            signatures,
+            warnings,
+        })
+    }
+
+    // Header fields as (name, raw field without the final CRLF) in message
+    // order, and the body when there is one. Line endings become CRLF, as
+    // they were when the message was signed.
+    pub fn split_message(
+        &self,
+        message: &str,
+    ) -> Result<(Vec<HeaderField>, Option<String>), String> {
+        let message = message.replace("\r\n", "\n").replace('\n', "\r\n");
+        let message = message.trim_start_matches("\r\n");
+        let (head, body) = match message.split_once("\r\n\r\n") {
+            Some((head, body)) => (head, Some(body.to_string())),
+            None => (message.trim_end_matches("\r\n"), None),
+        };
+
+        let mut headers: Vec<HeaderField> = Vec::new();
+        for (number, line) in head.split("\r\n").enumerate() {
+            if line.starts_with([' ', '\t']) {
+                let Some((_, raw)) = headers.last_mut() else {
+                    return Err("The message starts with a folded line".to_string());
+                };
+                raw.push_str("\r\n");
+                raw.push_str(line);
+            } else if number == 0 && line.starts_with("From ") {
+                // mbox separator line of a saved message
+                continue;
+            } else if let Some((name, _)) = line.split_once(':') {
+                headers.push((name.trim().to_string(), line.to_string()));
+            } else {
+                return Err(format!(
+                    "Line {} isn't a header field: {}",
+                    number + 1,
+                    line
+                ));
+            }
+        }
+        if headers.is_empty() {
+            return Err("The message has no header fields".to_string());
+        }
+
+        // A pasted header block often ends with a blank line and nothing after it
+        let body = body.filter(|b| !b.trim().is_empty());
+        Ok((headers, body))
+    }
+
+    // Tag list of a DKIM-Signature or key record (RFC 6376 section 3.2)
+    pub fn dkim_tags(&self, value: &str) -> Vec<(String, String)> {
+        value
+            .split(';')
+            .filter_map(|pair| {
+                let (key, value) = pair.split_once('=')?;
+                Some((key.trim().to_lowercase(), value.trim().to_string()))
+            })
+            .collect()
+    }
+
+    // Domain of the address in the From header, which DMARC aligns against
+    fn sender_domain(&self, headers: &[HeaderField]) -> Option<String> {
+        let (_, raw) = headers
+            .iter()
+            .find(|(name, _)| name.eq_ignore_ascii_case("from"))?;
+        let value = raw.split_once(':')?.1;
+        let address = match (value.rfind('<'), value.rfind('>')) {
+            (Some(start), Some(end)) if start < end => &value[start + 1..end],
+            _ => value,
+        };
+        let domain = address.rsplit_once('@')?.1.trim().trim_end_matches('.');
+        (!domain.is_empty()).then(|| domain.to_lowercase())
+    }
+
+    async fn fetch_dkim_key(&self, domain: &str, selector: &str) -> Result<String, String> {
+        let dns = self.dns();
+        let name = format!("{}._domainkey.{}", selector, domain);
+        let response = dns
+            .query(&name, "TXT")
+            .await
+            .map_err(|e| format!("Failed to look up {}: {}", name, e))?;
+        response
+            .records
+            .iter()
+            .filter(|r| r.record_type == "TXT")
+            .map(|r| dns.txt_value(&r.value))
+            .find(|value| value.contains("p="))
+            .ok_or_else(|| format!("{} publishes no DKIM key", name))
+    }
+
+    // Verify the DKIM-Signature at headers[index] with the key record found
+    // for its selector
+    pub fn check_dkim_signature(
+        &self,
+        headers: &[HeaderField],
+        body: Option<&str>,
+        index: usize,
+        key: Result<String, String>,
+        from_domain: Option<&str>,
+        now: DateTime<Utc>,
+    ) -> DkimSignatureCheck {
+        let raw = &headers[index].1;
+        let tags = self.dkim_tags(raw.split_once(':').map(|(_, v)| v).unwrap_or_default());
+        let tag = |name: &str| tags.iter().find(|(k, _)| k == name).map(|(_, v)| v.clone());
+        let base64 = |name: &str| {
+            tag(name).map(|value| {
+                value
+                    .chars()
+                    .filter(|c| !c.is_whitespace())
+                    .collect::<String>()
+            })
+        };
+        let timestamp = |name: &str| {
+            tag(name)
+                .and_then(|t| t.parse::<i64>().ok())
+                .and_then(|t| DateTime::<Utc>::from_timestamp(t, 0))
+        };
+
+        let domain = tag("d").unwrap_or_default().to_lowercase();
+        let algorithm = tag("a").unwrap_or_default().to_lowercase();
+        let mut check = DkimSignatureCheck {
+            domain: domain.clone(),
+            selector: tag("s").unwrap_or_default(),
+            algorithm: algorithm.clone(),
+            canonicalization: tag("c").unwrap_or_else(|| "simple/simple".to_string()),
+            signed_headers: tag("h")
+                .map(|h| {
+                    h.split(':')
+                        .map(|name| name.trim().to_lowercase())
+                        .collect()
+                })
+                .unwrap_or_default(),
+            body_length: tag("l").and_then(|l| l.parse().ok()),
+            signed_at: timestamp("t").map(|t| t.to_rfc3339()),
+            expires_at: timestamp("x").map(|x| x.to_rfc3339()),
+            ..Default::default()
+        };
+        let fail = |mut check: DkimSignatureCheck, error: String| {
+            check.error = Some(error);
+            check
+        };
+
+        if let Some(missing) = ["v", "a", "b", "bh", "d", "h", "s"]
+            .into_iter()
+            .find(|name| tag(name).is_none())
+        {
+            return fail(check, format!("The signature has no {}= tag", missing));
+        }
+        if tag("v").as_deref() != Some("1") {
+            return fail(
+                check,
+                "Unsupported signature version; only v=1 exists".to_string(),
+            );
+        }
+        if !check.signed_headers.iter().any(|h| h == "from") {
+            return fail(
+                check,
+                "h= doesn't include From, which must be signed".to_string(),
+            );
+        }
+        if let Some(from) = from_domain {
+            let http = self.http();
+            check.aligned = http.registrable_domain(from) == http.registrable_domain(&domain);
+        }
+        if let Some(identity) = tag("i") {
+            let identity = identity
+                .rsplit('@')
+                .next()
+                .unwrap_or_default()
+                .to_lowercase();
+            if identity != domain && !identity.ends_with(&format!(".{}", domain)) {
+                return fail(check, format!("i= {} isn't within d= {}", identity, domain));
+            }
+        }
+        if let Some(expiry) = timestamp("x").filter(|x| *x < now) {
+            return fail(
+                check,
+                format!("The signature expired at {}", expiry.to_rfc3339()),
+            );
+        }
+
+        let (key_type, hash) = match algorithm.as_str() {
+            "rsa-sha256" => ("rsa", "sha256"),
+            "rsa-sha1" => ("rsa", "sha1"),
+            "ed25519-sha256" => ("ed25519", "sha256"),
+            other => return fail(check, format!("Unsupported algorithm {}", other)),
+        };
+        let (header_canonicalization, body_canonicalization) = check
+            .canonicalization
+            .to_lowercase()
+            .split_once('/')
+            .map(|(h, b)| (h.to_string(), b.to_string()))
+            .unwrap_or_else(|| (check.canonicalization.to_lowercase(), "simple".to_string()));
+        let relaxed_headers = match header_canonicalization.as_str() {
+            "relaxed" => true,
+            "simple" => false,
+            other => return fail(check, format!("Unknown header canonicalization {}", other)),
+        };
+        let relaxed_body = match body_canonicalization.as_str() {
+            "relaxed" => true,
+            "simple" => false,
+            other => return fail(check, format!("Unknown body canonicalization {}", other)),
+        };
+
+        let digest = |data: &[u8]| -> Vec<u8> {
+            if hash == "sha1" {
+                Sha1::digest(data).to_vec()
+            } else {
+                Sha256::digest(data).to_vec()
+            }
+        };
+        if let Some(body) = body {
+            let canonical = self.canonicalize_body(body, relaxed_body);
+            let mut hashed = canonical.as_bytes();
+            if let Some(length) = check.body_length {
+                if length > hashed.len() as u64 {
+                    return fail(
+                        check,
+                        format!("l={} is longer than the body; it was truncated", length),
+                    );
+                }
+                // l= counts octets and may end inside a multi-byte character
+                hashed = &hashed[..length as usize];
+            }
+            let computed = BASE64.encode(digest(hashed));
+            check.body_hash_valid = Some(Some(computed) == base64("bh"));
+        }
+
+        // Key record (RFC 6376 section 3.6.1)
+        let record = match key {
+            Ok(record) => record,
+            Err(error) => return fail(check, error),
+        };
+        let key_tags = self.dkim_tags(&record);
+        let key_tag = |name: &str| {
+            key_tags
+                .iter()
+                .find(|(k, _)| k == name)
+                .map(|(_, v)| v.clone())
+        };
+        if key_tag("v").is_some_and(|v| v != "DKIM1") {
+            return fail(check, "The key record isn't v=DKIM1".to_string());
+        }
+        let public_key: String = key_tag("p")
+            .unwrap_or_default()
+            .chars()
+            .filter(|c| !c.is_whitespace())
+            .collect();
+        if public_key.is_empty() {
+            return fail(check, "The key has been revoked (empty p=)".to_string());
+        }
+        let record_type = key_tag("k")
+            .unwrap_or_else(|| "rsa".to_string())
+            .to_lowercase();
+        if record_type != key_type {
+            return fail(
+                check,
+                format!(
+                    "The key is {} but the signature uses {}",
+                    record_type, algorithm
+                ),
+            );
+        }
+        if key_tag("h").is_some_and(|h| !h.split(':').any(|a| a.trim().eq_ignore_ascii_case(hash)))
+        {
+            return fail(check, format!("The key doesn't allow {}", hash));
+        }
+        let flags = key_tag("t").unwrap_or_default();
+        let flags: Vec<&str> = flags.split(':').map(|f| f.trim()).collect();
+        if flags.contains(&"y") {
+            check.warnings.push(
+                "The key is in testing mode (t=y); receivers treat the message as unsigned"
+                    .to_string(),
+            );
+        }
+        if flags.contains(&"s") {
+            if let Some(identity) = tag("i") {
+                if !identity.to_lowercase().ends_with(&format!("@{}", domain)) {
+                    return fail(check, "The key forbids subdomains in i= (t=s)".to_string());
+                }
+            }
+        }
+        let Ok(key_bytes) = BASE64.decode(&public_key) else {
+            return fail(check, "The key's p= isn't valid base64".to_string());
+        };
+        let Some(signature) = base64("b").and_then(|b| BASE64.decode(b).ok()) else {
+            return fail(check, "The signature's b= isn't valid base64".to_string());
+        };
+
+        // Signed header fields, each taken from the bottom up, then the
+        // signature itself with an empty b= (RFC 6376 section 3.7)
+        let mut data = String::new();
+        let mut used: Vec<usize> = Vec::new();
+        for name in &check.signed_headers {
+            let field = headers
+                .iter()
+                .enumerate()
+                .rev()
+                .find(|(i, (n, _))| n.eq_ignore_ascii_case(name) && !used.contains(i));
+            if let Some((i, (_, raw))) = field {
+                used.push(i);
+                data.push_str(&self.canonicalize_header(raw, relaxed_headers));
+            }
+        }
+        let unsigned = Regex::new(r"(^|;)(\s*b\s*=)[^;]*").unwrap();
+        let (name, value) = raw.split_once(':').unwrap_or_default();
+        let stripped = format!("{}:{}", name, unsigned.replace(value, "${1}${2}"));
+        data.push_str(
+            self.canonicalize_header(&stripped, relaxed_headers)
+                .trim_end_matches("\r\n"),
+        );
+
+        let verified = if key_type == "ed25519" {
+            // Ed25519 signs the SHA-256 of the header data (RFC 8463)
+            let hashed = Sha256::digest(data.as_bytes());
+            signature::UnparsedPublicKey::new(&signature::ED25519, &key_bytes)
+                .verify(&hashed, &signature)
+                .is_ok()
+        } else {
+            // Keys are published as SubjectPublicKeyInfo; ring expects the
+            // RSAPublicKey inside it
+            let (rsa_key, bits) = match SubjectPublicKeyInfo::from_der(&key_bytes) {
+                Ok((_, spki)) => (
+                    spki.subject_public_key.data.to_vec(),
+                    spki.parsed().map(|k| k.key_size()).unwrap_or_default(),
+                ),
+                Err(_) => (key_bytes.clone(), 0),
+            };
+            if bits > 0 {
+                check.key_bits = Some(bits as u32);
+            }
+            let algorithm = if hash == "sha1" {
+                &signature::RSA_PKCS1_1024_8192_SHA1_FOR_LEGACY_USE_ONLY
+            } else {
+                &signature::RSA_PKCS1_1024_8192_SHA256_FOR_LEGACY_USE_ONLY
+            };
+            signature::UnparsedPublicKey::new(algorithm, &rsa_key)
+                .verify(data.as_bytes(), &signature)
+                .is_ok()
+        };
+
+        if check.key_bits.is_some_and(|bits| bits < 2048) {
+            check.warnings.push(format!(
+                "{}-bit key; 2048 bits is recommended",
+                check.key_bits.unwrap_or_default()
+            ));
+        }
+        if hash == "sha1" {
+            check.warnings.push(
+                "rsa-sha1 is obsolete (RFC 8301); receivers may ignore the signature".to_string(),
+            );
+        }
+        if check.body_length.is_some() {
+            check
+                .warnings
+                .push("l= lets anyone append content to the signed body".to_string());
+        }
+        for header in ["subject", "date", "to", "message-id"] {
+            if !check.signed_headers.iter().any(|h| h == header) {
+                check.warnings.push(format!(
+                    "{} isn't signed and could be changed in transit",
+                    header
+                ));
+            }
+        }
+
+        if !verified {
+            return fail(
+                check,
+                "The signature doesn't match: a signed header was changed or the key is wrong"
+                    .to_string(),
+            );
+        }
+        if check.body_hash_valid == Some(false) {
+            return fail(
+                check,
+                "The body hash doesn't match: the body was changed after signing".to_string(),
+            );
+        }
+        check.valid = true;
+        check
+    }
+
+    // Header canonicalization (RFC 6376 section 3.4.1 and 3.4.2)
+    fn canonicalize_header(&self, raw: &str, relaxed: bool) -> String {
+        if !relaxed {
+            return format!("{}\r\n", raw);
+        }
+        let (name, value) = raw.split_once(':').unwrap_or((raw, ""));
+        let value = value.replace("\r\n", "");
+        let value = value
+            .split([' ', '\t'])
+            .filter(|s| !s.is_empty())
+            .collect::<Vec<_>>()
+            .join(" ");
+        format!("{}:{}\r\n", name.trim().to_lowercase(), value)
+    }
+
+    // Body canonicalization (RFC 6376 section 3.4.3 and 3.4.4)
+    pub fn canonicalize_body(&self, body: &str, relaxed: bool) -> String {
+        let lines: Vec<String> = body
+            .split("\r\n")
+            .map(|line| {
+                if relaxed {
+                    let trimmed = line.trim_end_matches([' ', '\t']);
+                    let mut collapsed = String::new();
+                    let mut space = false;
+                    for c in trimmed.chars() {
+                        if c == ' ' || c == '\t' {
+                            space = true;
+                            continue;
+                        }
+                        if space {
+                            collapsed.push(' ');
+                            space = false;
+                        }
+                        collapsed.push(c);
+                    }
+                    collapsed
+                } else {
+                    line.to_string()
+                }
+            })
+            .collect();
+        let end = lines
+            .iter()
+            .rposition(|line| !line.is_empty())
+            .map_or(0, |i| i + 1);
+        if end == 0 {
+            return if relaxed {
+                String::new()
+            } else {
+                "\r\n".to_string()
+            };
+        }
+        lines[..end]
+            .iter()
+            .map(|line| format!("{}\r\n", line))
+            .collect()
+    }
+
+    // The DMARC record of the domain, falling back to its organizational
+    // domain, with external report destinations checked for the
+    // <domain>._report._dmarc.<destination> record (RFC 7489 7.1)
+    pub async fn fetch_dmarc(&self, domain: &str) -> Option<DmarcRecord> {
+        let dns = self.dns();
+        let organizational = self.http().registrable_domain(domain);
+        let mut candidates = vec![domain.to_string()];
+        if organizational != domain {
+            candidates.push(organizational);
+        }
+
+        let mut found = None;
+        for candidate in candidates {
+            let Ok(response) = dns.query(&format!("_dmarc.{}", candidate), "TXT").await else {
+                continue;
+            };
+            let records: Vec<String> = response
+                .records
+                .iter()
+                .filter(|r| r.record_type == "TXT")
+                .map(|r| dns.txt_value(&r.value))
+                .filter(|v| self.is_dmarc_record(v))
+                .collect();
+            if !records.is_empty() {
+                found = Some((candidate, records));
+                break;
+            }
+        }
+        let (source, records) = found?;
+
+        let mut dmarc = self.parse_dmarc(&source, &records[0]);
+        if records.len() > 1 {
+            dmarc.errors.push(format!(
+                "_dmarc.{} publishes {} DMARC records; receivers ignore them all",
+                source,
+                records.len()
+            ));
+        }
+
+        for uri in dmarc
+            .aggregate_reports
+            .iter_mut()
+            .chain(dmarc.failure_reports.iter_mut())
+        {
+            let Some(address) = uri.address.as_ref().filter(|_| uri.external) else {
+                continue;
+            };
+            let Some((_, destination)) = address.rsplit_once('@') else {
+                continue;
+            };
+            let name = format!("{}._report._dmarc.{}", source, destination);
+            let authorized = match dns.query(&name, "TXT").await {
+                Ok(response) => response
+                    .records
+                    .iter()
+                    .filter(|r| r.record_type == "TXT")
+                    .any(|r| self.is_dmarc_record(&dns.txt_value(&r.value))),
+                Err(_) => false,
+            };
+            uri.authorized = Some(authorized);
+            if !authorized {
+                dmarc.warnings.push(format!(
+                    "{} is outside {} and {} doesn't authorize it with a {} record; receivers won't send it reports",
+                    address, source, destination, name
+                ));
+            }
+        }
+
+        Some(dmarc)
+    }
+
+    fn is_dmarc_record(&self, value: &str) -> bool {
+        let version = value.split(';').next().unwrap_or_default();
+        version.replace(' ', "").eq_ignore_ascii_case("v=DMARC1")
+    }
+
+    // Tag list of a DMARC record (RFC 7489 section 6.3). Tags receivers ignore
+    // fall back to their defaults and are reported as errors.
+    pub fn parse_dmarc(&self, domain: &str, record: &str) -> DmarcRecord {
+        let mut dmarc = DmarcRecord {
+            domain: domain.to_string(),
+            record: record.to_string(),
+            policy: None,
+            subdomain_policy: String::new(),
+            percentage: 100,
+            dkim_alignment: "r".to_string(),
+            spf_alignment: "r".to_string(),
+            failure_options: vec!["0".to_string()],
+            report_formats: vec!["afrf".to_string()],
+            report_interval: 86400,
+            aggregate_reports: Vec::new(),
+            failure_reports: Vec::new(),
+            errors: Vec::new(),
+            warnings: Vec::new(),
+        };
+        let policies = ["none", "quarantine", "reject"];
+        let mut subdomain_policy = None;
+
+        let tags = record
+            .split(';')
+            .map(|pair| pair.trim())
+            .filter(|pair| !pair.is_empty());
+        for (index, pair) in tags.enumerate() {
+            let Some((key, value)) = pair.split_once('=') else {
+                dmarc.errors.push(format!("Malformed tag \"{}\"", pair));
+                continue;
+            };
+            let key = key.trim().to_lowercase();
+            let value = value.trim();
+            if index == 0 && key != "v" {
+                dmarc
+                    .errors
+                    .push("The record must start with v=DMARC1".to_string());
+            }
+            let lower = value.to_lowercase();
+
+            match key.as_str() {
+                "v" => {}
+                "p" if policies.contains(&lower.as_str()) => dmarc.policy = Some(lower),
+                "sp" if policies.contains(&lower.as_str()) => subdomain_policy = Some(lower),
+                "pct" => match value.parse::<u8>() {
+                    Ok(pct) if pct <= 100 => dmarc.percentage = pct,
+                    _ => dmarc
+                        .errors
+                        .push(format!("pct={} is not a percentage; 100 applies", value)),
+                },
+                "adkim" | "aspf" if lower == "r" || lower == "s" => {
+                    if key == "adkim" {
+                        dmarc.dkim_alignment = lower;
+                    } else {
+                        dmarc.spf_alignment = lower;
+                    }
+                }
+                "fo" => {
+                    let options: Vec<String> =
+                        lower.split(':').map(|o| o.trim().to_string()).collect();
+                    match options
+                        .iter()
+                        .find(|o| !["0", "1", "d", "s"].contains(&o.as_str()))
+                    {
+                        Some(option) => dmarc
+                            .errors
+                            .push(format!("Unknown failure reporting option fo={}", option)),
+                        None => dmarc.failure_options = options,
+                    }
+                }
+                "rf" => {
+                    let formats: Vec<String> =
+                        lower.split(':').map(|f| f.trim().to_string()).collect();
+                    if formats.iter().any(|f| f != "afrf") {
+                        dmarc.errors.push(format!(
+                            "Unknown report format rf={}; only afrf is defined",
+                            value
+                        ));
+                    }
+                    dmarc.report_formats = formats;
+                }
+                "ri" => match value.parse::<u32>() {
+                    Ok(interval) => dmarc.report_interval = interval,
+                    Err(_) => dmarc
+                        .errors
+                        .push(format!("ri={} is not a number of seconds", value)),
+                },
+                "rua" | "ruf" => {
+                    let uris: Vec<DmarcReportUri> = value
+                        .split(',')
+                        .map(|uri| self.parse_dmarc_uri(domain, uri.trim()))
+                        .collect();
+                    if key == "rua" {
+                        dmarc.aggregate_reports = uris;
+                    } else {
+                        dmarc.failure_reports = uris;
+                    }
+                }
+                "p" | "sp" | "adkim" | "aspf" => dmarc
+                    .errors
+                    .push(format!("Invalid value {}={}", key, value)),
+                _ => dmarc.warnings.push(format!("Unknown tag {}", key)),
+            }
+        }
+
+        match &dmarc.policy {
+            Some(policy) => {
+                dmarc.subdomain_policy = subdomain_policy.unwrap_or_else(|| policy.clone());
+            }
+            None => {
+                dmarc
+                    .errors
+                    .push("Missing or invalid p= tag: the record is ignored".to_string());
+            }
+        }
+        for uri in dmarc.aggregate_reports.iter().chain(&dmarc.failure_reports) {
+            if let Some(error) = &uri.error {
+                dmarc.errors.push(format!("{}: {}", uri.uri, error));
+            }
+        }
+
+        let strength = |policy: &str| policies.iter().position(|p| *p == policy);
+        if let Some(policy) = dmarc.policy.clone() {
+            if policy == "none" {
+                dmarc
+                    .warnings
+                    .push("p=none only monitors; failing mail is delivered as usual".to_string());
+            }
+            if strength(&dmarc.subdomain_policy) < strength(&policy) {
+                dmarc.warnings.push(format!(
+                    "sp={} is weaker than p={}; spoofed subdomains get the weaker treatment",
+                    dmarc.subdomain_policy, policy
+                ));
+            }
+            if dmarc.percentage < 100 && policy != "none" {
+                dmarc.warnings.push(format!(
+                    "pct={}: the policy applies to only {}% of failing mail",
+                    dmarc.percentage, dmarc.percentage
+                ));
+            }
+        }
+        if dmarc.aggregate_reports.is_empty() {
+            dmarc
+                .warnings
+                .push("No rua= destination: no aggregate reports are sent".to_string());
+        }
+
+        dmarc
+    }
+
+    // Read a DMARC aggregate report as receivers send it: plain XML, gzipped
+    // (.xml.gz) or the first XML file of a zip archive
+    pub async fn analyze_dmarc_report(&self, path: &str) -> Result<DmarcAggregateReport, String> {
+        let bytes = self.backends.files.read(path).await?;
+        let xml = self.decompress_dmarc_report(&bytes)?;
+        self.parse_dmarc_report(&xml)
+    }
+
+    // Recognized by content rather than file extension
+    pub fn decompress_dmarc_report(&self, bytes: &[u8]) -> Result<String, String> {
+        let too_large = || {
+            format!(
+                "The report exceeds {} MiB",
+                DMARC_REPORT_MAX_BYTES / 1024 / 1024
+            )
+        };
+        // One byte past the limit, to tell a report that's too large from one that fits
+        let mut xml = Vec::new();
+        let read = if bytes.starts_with(&[0x1f, 0x8b]) {
+            GzDecoder::new(bytes)
+                .take(DMARC_REPORT_MAX_BYTES + 1)
+                .read_to_end(&mut xml)
+        } else if bytes.starts_with(b"PK\x03\x04") {
+            let mut archive = zip::ZipArchive::new(Cursor::new(bytes))
+                .map_err(|e| format!("Invalid zip archive: {}", e))?;
+            let index = (0..archive.len())
+                .find(|&i| {
+                    archive
+                        .name_for_index(i)
+                        .is_some_and(|name| name.to_lowercase().ends_with(".xml"))
+                })
+                .ok_or("The zip archive contains no XML report")?;
+            let mut file = archive
+                .by_index(index)
+                .map_err(|e| format!("Failed to extract the report: {}", e))?;
+            file.by_ref()
+                .take(DMARC_REPORT_MAX_BYTES + 1)
+                .read_to_end(&mut xml)
+        } else {
+            if bytes.len() as u64 > DMARC_REPORT_MAX_BYTES {
+                return Err(too_large());
+            }
+            return String::from_utf8(bytes.to_vec())
+                .map_err(|_| "The report is neither XML, gzip nor zip".to_string());
+        };
+
+        read.map_err(|e| format!("Failed to decompress the report: {}", e))?;
+        if xml.len() as u64 > DMARC_REPORT_MAX_BYTES {
+            return Err(too_large());
+        }
+        String::from_utf8(xml).map_err(|_| "The decompressed report isn't text".to_string())
+    }
+
+    // <feedback> with report_metadata, policy_published and one <record> per
+    // source IP and identifier combination; records of one IP are merged
+    pub fn parse_dmarc_report(&self, xml: &str) -> Result<DmarcAggregateReport, String> {
+        let document =
+            roxmltree::Document::parse(xml).map_err(|e| format!("Invalid report XML: {}", e))?;
+        let feedback = document.root_element();
+        if feedback.tag_name().name() != "feedback" {
+            return Err("Not a DMARC aggregate report: <feedback> is missing".to_string());
+        }
+
+        let child = |node, name| self.xml_child(node, name);
+        let text = |node: Option<roxmltree::Node>, name: &str| {
+            node.and_then(|n| self.xml_child(n, name))
+                .and_then(|n| n.text())
+                .map(|t| t.trim().to_string())
+                .filter(|t| !t.is_empty())
+        };
+        let timestamp = |value: Option<String>| {
+            value
+                .and_then(|v| v.parse::<i64>().ok())
+                .and_then(|secs| DateTime::from_timestamp(secs, 0))
+                .map(|at| at.to_rfc3339())
+        };
+
+        let metadata = child(feedback, "report_metadata");
+        let date_range = metadata.and_then(|m| child(m, "date_range"));
+        let published = child(feedback, "policy_published");
+        let mut report = DmarcAggregateReport {
+            org_name: text(metadata, "org_name").unwrap_or_default(),
+            report_id: text(metadata, "report_id").unwrap_or_default(),
+            email: text(metadata, "email"),
+            begin: timestamp(text(date_range, "begin")),
+            end: timestamp(text(date_range, "end")),
+            domain: text(published, "domain").unwrap_or_default(),
+            policy: text(published, "p"),
+            subdomain_policy: text(published, "sp"),
+            percentage: text(published, "pct").and_then(|pct| pct.parse().ok()),
+            sources: Vec::new(),
+            total_messages: 0,
+            dmarc_pass: 0,
+            warnings: Vec::new(),
+        };
+
+        let add = |list: &mut Vec<String>, value: Option<String>| {
+            if let Some(value) = value.map(|v| v.to_lowercase()) {
+                if !list.contains(&value) {
+                    list.push(value);
+                }
+            }
+        };
+        for record in feedback
+            .children()
+            .filter(|n| n.is_element() && n.tag_name().name() == "record")
+        {
+            let row = child(record, "row");
+            let Some(source_ip) = text(row, "source_ip") else {
+                report
+                    .warnings
+                    .push("Skipped a record without a source IP".to_string());
+                continue;
+            };
+            let messages = text(row, "count")
+                .and_then(|count| count.parse::<u64>().ok())
+                .unwrap_or(0);
+            let evaluated = row.and_then(|r| child(r, "policy_evaluated"));
+            let passes = |name: &str| text(evaluated, name).is_some_and(|v| v == "pass");
+            let (dkim, spf) = (passes("dkim"), passes("spf"));
+
+            let index = match report.sources.iter().position(|s| s.source_ip == source_ip) {
+                Some(index) => index,
+                None => {
+                    report.sources.push(DmarcSource {
+                        source_ip,
+                        ..DmarcSource::default()
+                    });
+                    report.sources.len() - 1
+                }
+            };
+            let source = &mut report.sources[index];
+            source.messages += messages;
+            if dkim {
+                source.dkim_aligned += messages;
+            }
+            if spf {
+                source.spf_aligned += messages;
+            }
+            if dkim || spf {
+                source.dmarc_pass += messages;
+            }
+            match text(evaluated, "disposition").as_deref() {
+                Some("quarantine") => source.quarantined += messages,
+                Some("reject") => source.rejected += messages,
+                _ => {}
+            }
+
+            add(
+                &mut source.header_from,
+                text(child(record, "identifiers"), "header_from"),
+            );
+            let auth_results = child(record, "auth_results");
+            for result in auth_results.iter().flat_map(|r| r.children()) {
+                match result.tag_name().name() {
+                    "dkim" => add(&mut source.dkim_domains, text(Some(result), "domain")),
+                    "spf" => add(&mut source.spf_domains, text(Some(result), "domain")),
+                    _ => {}
+                }
+            }
+        }
+
+        report.sources.sort_by(|a, b| {
+            b.messages
+                .cmp(&a.messages)
+                .then(a.source_ip.cmp(&b.source_ip))
+        });
+        report.total_messages = report.sources.iter().map(|s| s.messages).sum();
+        report.dmarc_pass = report.sources.iter().map(|s| s.dmarc_pass).sum();
+
+        for source in &report.sources {
+            let failed = source.messages - source.dmarc_pass.min(source.messages);
+            if failed == 0 {
+                continue;
+            }
+            report.warnings.push(if source.dmarc_pass == 0 {
+                format!(
+                    "{} sent {} messages that all failed DMARC; an unauthorized sender or a service missing from SPF and DKIM",
+                    source.source_ip, failed
+                )
+            } else {
+                format!(
+                    "{} sent {} of {} messages that failed DMARC",
+                    source.source_ip, failed, source.messages
+                )
+            });
+        }
+
+        Ok(report)
+    }
+
+    // Elements are matched by local name, so namespaced reports parse too
+    fn xml_child<'a, 'input>(
+        &self,
+        node: roxmltree::Node<'a, 'input>,
+        name: &str,
+    ) -> Option<roxmltree::Node<'a, 'input>> {
+        node.children()
+            .find(|n| n.is_element() && n.tag_name().name() == name)
+    }
+
+    // "mailto:dmarc@example.com!10m"
+    fn parse_dmarc_uri(&self, domain: &str, uri: &str) -> DmarcReportUri {
+        let mut parsed = DmarcReportUri {
+            uri: uri.to_string(),
+            address: None,
+            max_size: None,
+            external: false,
+            authorized: None,
+            error: None,
+        };
+
+        let Some(target) = uri
+            .get(..7)
+            .filter(|scheme| scheme.eq_ignore_ascii_case("mailto:"))
+            .map(|_| &uri[7..])
+        else {
+            parsed.error = Some("Only mailto: destinations receive reports".to_string());
+            return parsed;
+        };
+        let (address, max_size) = match target.split_once('!') {
+            Some((address, size)) => (address, Some(size)),
+            None => (target, None),
+        };
+        if let Some(size) = max_size {
+            let digits = size.trim_end_matches(['k', 'm', 'g', 't', 'K', 'M', 'G', 'T']);
+            if digits.is_empty() || !digits.chars().all(|c| c.is_ascii_digit()) {
+                parsed.error = Some(format!("Invalid size limit !{}", size));
+            }
+            parsed.max_size = Some(size.to_string());
+        }
+
+        let address = address.trim().to_lowercase();
+        match address.rsplit_once('@') {
+            Some((local, host)) if !local.is_empty() && host.contains('.') => {
+                let http = self.http();
+                parsed.external = http.registrable_domain(host) != http.registrable_domain(domain);
+            }
+            _ => parsed.error = Some(format!("{} is not an email address", address)),
+        }
+        parsed.address = Some(address);
+        parsed
+    }
+
+    // Expand the SPF record through include: and redirect=, resolving a and mx
+    // to addresses, and count DNS lookups the way receivers do. Expansion
+    // carries on past most PermErrors so the address list is as full as it can
+    // be, but stops at the term that goes over the lookup limit, as receivers do.
+    pub async fn evaluate_spf(&self, domain: &str) -> Option<SpfEvaluation> {
+        let dns = self.dns();
+        let mut evaluation = SpfEvaluation::default();
+        let mut pending = vec![SpfTarget {
+            domain: domain.to_string(),
+            via: None,
+            depth: 0,
+            ancestors: Vec::new(),
+            top_level: true,
+        }];
+
+        while let Some(target) = pending.pop() {
+            if target.depth > SPF_DEPTH_LIMIT {
+                self.spf_perm_error(
+                    &mut evaluation,
+                    format!(
+                        "{}:{} is nested more than {} deep",
+                        target.via.unwrap_or_default(),
+                        target.domain,
+                        SPF_DEPTH_LIMIT
+                    ),
+                );
+                continue;
+            }
+            if target.ancestors.contains(&target.domain) {
+                self.spf_perm_error(
+                    &mut evaluation,
+                    format!(
+                        "Include loop: {} -> {}",
+                        target.ancestors.join(" -> "),
+                        target.domain
+                    ),
+                );
+                continue;
+            }
+
+            let records = match dns.query(&target.domain, "TXT").await {
+                Ok(response) => dns.spf_records(&response.records),
+                Err(e) => {
+                    evaluation.warnings.push(format!(
+                        "Failed to look up the SPF record of {}: {}",
+                        target.domain, e
+                    ));
+                    continue;
+                }
+            };
+            let record = match (records.as_slice(), target.via) {
+                ([record], _) => record.clone(),
+                ([], None) => return None,
+                ([], Some(via)) => {
+                    evaluation.void_lookup_count += 1;
+                    self.spf_perm_error(
+                        &mut evaluation,
+                        format!("{}:{} has no SPF record", via, target.domain),
+                    );
+                    continue;
+                }
+                (_, _) => {
+                    self.spf_perm_error(
+                        &mut evaluation,
+                        format!("{} publishes {} SPF records", target.domain, records.len()),
+                    );
+                    continue;
+                }
+            };
+
+            match target.via {
+                None => evaluation.record = Some(record.clone()),
+                Some(via) => evaluation.includes.push(SpfInclude {
+                    domain: target.domain.clone(),
+                    via: via.to_string(),
+                    record: Some(record.clone()),
+                    depth: target.depth,
+                }),
+            }
+            let terms = match self.parse_spf(&record) {
+                Ok(terms) => terms,
+                Err(e) => {
+                    self.spf_perm_error(&mut evaluation, format!("{}: {}", target.domain, e));
+                    continue;
+                }
+            };
+
+            let mut ancestors = target.ancestors.clone();
+            ancestors.push(target.domain.clone());
+            let mut includes = Vec::new();
+            let mut redirect = None;
+            let mut has_all = false;
+
+            for term in terms {
+                match term.mechanism {
+                    SpfMechanism::All => {
+                        has_all = true;
+                        if target.top_level {
+                            evaluation.all = Some(format!("{}all", term.qualifier));
+                        }
+                    }
+                    SpfMechanism::Ip4(range) | SpfMechanism::Ip6(range) => {
+                        self.authorize(&mut evaluation, range);
+                    }
+                    SpfMechanism::Include(name) => {
+                        if !self.spf_lookup(&mut evaluation) {
+                            break;
+                        }
+                        if self.is_macro(&name, &mut evaluation) {
+                            continue;
+                        }
+                        includes.push(name.to_lowercase());
+                    }
+                    SpfMechanism::A {
+                        domain: name,
+                        cidr4,
+                        cidr6,
+                    } => {
+                        if !self.spf_lookup(&mut evaluation) {
+                            break;
+                        }
+                        let name = name.unwrap_or_else(|| target.domain.clone());
+                        if self.is_macro(&name, &mut evaluation) {
+                            continue;
+                        }
+                        let addresses = self.resolve_addresses(&name, cidr4, cidr6).await;
+                        if addresses.is_empty() {
+                            evaluation.void_lookup_count += 1;
+                        }
+                        for address in addresses {
+                            self.authorize(&mut evaluation, address);
+                        }
+                    }
+                    SpfMechanism::Mx {
+                        domain: name,
+                        cidr4,
+                        cidr6,
+                    } => {
+                        if !self.spf_lookup(&mut evaluation) {
+                            break;
+                        }
+                        let name = name.unwrap_or_else(|| target.domain.clone());
+                        if self.is_macro(&name, &mut evaluation) {
+                            continue;
+                        }
+                        let hosts = match dns.query(&name, "MX").await {
+                            Ok(response) => self.parse_mx(&response.records),
+                            Err(_) => Vec::new(),
+                        };
+                        if hosts.is_empty() {
+                            evaluation.void_lookup_count += 1;
+                        }
+                        if hosts.len() > SPF_MX_LIMIT {
+                            self.spf_perm_error(
+                                &mut evaluation,
+                                format!(
+                                    "mx:{} has {} MX hosts; at most {} are allowed",
+                                    name,
+                                    hosts.len(),
+                                    SPF_MX_LIMIT
+                                ),
+                            );
+                        }
+                        for host in hosts.iter().take(SPF_MX_LIMIT) {
+                            for address in
+                                self.resolve_addresses(&host.hostname, cidr4, cidr6).await
+                            {
+                                self.authorize(&mut evaluation, address);
+                            }
+                        }
+                    }
+                    SpfMechanism::Ptr(_) => {
+                        if !self.spf_lookup(&mut evaluation) {
+                            break;
+                        }
+                        evaluation.warnings.push(format!(
+                            "{} uses ptr, which is slow, unreliable and deprecated (RFC 7208 5.5)",
+                            target.domain
+                        ));
+                    }
+                    SpfMechanism::Exists(_) => {
+                        // Matches on the sender's IP at evaluation time; nothing to expand
+                        if !self.spf_lookup(&mut evaluation) {
+                            break;
+                        }
+                    }
+                    SpfMechanism::Redirect(name) => redirect = Some(name.to_lowercase()),
+                    SpfMechanism::Explanation(_) => {}
+                }
+            }
+
+            if evaluation.lookup_count > SPF_LOOKUP_LIMIT {
+                break;
+            }
+            // redirect= only applies when the record has no all
+            if let Some(name) = redirect.filter(|_| !has_all) {
+                if !self.spf_lookup(&mut evaluation) {
+                    break;
+                }
+                if !self.is_macro(&name, &mut evaluation) {
+                    pending.push(SpfTarget {
+                        domain: name,
+                        via: Some("redirect"),
+                        depth: target.depth + 1,
+                        ancestors: ancestors.clone(),
+                        top_level: target.top_level,
+                    });
+                }
+            }
+            // Reversed so the stack pops them in record order
+            for name in includes.into_iter().rev() {
+                pending.push(SpfTarget {
+                    domain: name,
+                    via: Some("include"),
+                    depth: target.depth + 1,
+                    ancestors: ancestors.clone(),
+                    top_level: false,
+                });
+            }
+        }
+
+        self.check_spf_limits(domain, &mut evaluation);
+        Some(evaluation)
+    }
+
+    // Lookup limits, and catch-alls that let anyone (or nobody in particular) send
+    pub fn check_spf_limits(&self, domain: &str, evaluation: &mut SpfEvaluation) {
+        if evaluation.lookup_count > SPF_LOOKUP_LIMIT {
+            self.spf_perm_error(
+                evaluation,
+                format!(
+                    "SPF needs more than {0} DNS lookups; receivers stop at {0}",
+                    SPF_LOOKUP_LIMIT
+                ),
+            );
+        } else if evaluation.lookup_count >= SPF_LOOKUP_LIMIT - 1 {
+            evaluation.warnings.push(format!(
+                "SPF needs {} of the {} allowed DNS lookups; one more include will break it",
+                evaluation.lookup_count, SPF_LOOKUP_LIMIT
+            ));
+        }
+        if evaluation.void_lookup_count > SPF_VOID_LOOKUP_LIMIT {
+            self.spf_perm_error(
+                evaluation,
+                format!(
+                    "{} SPF lookups found nothing; receivers stop at {}",
+                    evaluation.void_lookup_count, SPF_VOID_LOOKUP_LIMIT
+                ),
+            );
+        }
+
+        match evaluation.all.as_deref() {
+            Some("+all") => evaluation.warnings.push(format!(
+                "+all lets any server on the internet send mail as {}",
+                domain
+            )),
+            Some("?all") => evaluation
+                .warnings
+                .push("?all gives unlisted senders a neutral result".to_string()),
+            None => evaluation
+                .warnings
+                .push("No all mechanism: unlisted senders get a neutral result".to_string()),
+            _ => {}
+        }
+    }
+
+    // Count a term that costs a DNS lookup; false once it is one over the limit,
+    // where receivers stop evaluating (RFC 7208 section 4.6.4)
+    fn spf_lookup(&self, evaluation: &mut SpfEvaluation) -> bool {
+        evaluation.lookup_count += 1;
+        evaluation.lookup_count <= SPF_LOOKUP_LIMIT
+    }
+
+    // The first PermError is the one receivers report
+    fn spf_perm_error(&self, evaluation: &mut SpfEvaluation, error: String) {
+        if evaluation.perm_error.is_none() {
+            evaluation.perm_error = Some(error);
+        } else {
+            evaluation.warnings.push(error);
+        }
+    }
+
+    fn authorize(&self, evaluation: &mut SpfEvaluation, range: String) {
+        if !evaluation.authorized.contains(&range) {
+            evaluation.authorized.push(range);
+        }
+    }
+
+    // Macros (%{i}, %{d}...) depend on the message being checked
+    fn is_macro(&self, name: &str, evaluation: &mut SpfEvaluation) -> bool {
+        let is_macro = name.contains('%');
+        if is_macro {
+            evaluation.warnings.push(format!(
+                "{} contains SPF macros and can't be expanded without a message",
+                name
+            ));
+        }
+        is_macro
+    }
+
+    // A and AAAA records of a name, with the mechanism's prefix lengths
+    async fn resolve_addresses(
+        &self,
+        name: &str,
+        cidr4: Option<u8>,
+        cidr6: Option<u8>,
+    ) -> Vec<String> {
+        let dns = self.dns();
+        let mut addresses = Vec::new();
+        for (record_type, cidr) in [("A", cidr4), ("AAAA", cidr6)] {
+            let Ok(response) = dns.query(name, record_type).await else {
+                continue;
+            };
+            for record in response
+                .records
+                .iter()
+                .filter(|r| r.record_type == record_type)
+            {
+                addresses.push(match cidr {
+                    Some(cidr) => format!("{}/{}", record.value, cidr),
+                    None => record.value.clone(),
+                });
+            }
+        }
+        addresses
+    }
+
+    // Split a record into terms, rejecting anything RFC 7208 makes a PermError.
+    // Unknown modifiers are ignored, as the RFC requires.
+    pub fn parse_spf(&self, record: &str) -> Result<Vec<SpfTerm>, String> {
+        let mut words = record.split_whitespace();
+        if !words
+            .next()
+            .is_some_and(|version| version.eq_ignore_ascii_case("v=spf1"))
+        {
+            return Err("Record does not start with v=spf1".to_string());
+        }
+
+        let mut terms = Vec::new();
+        let mut seen_redirect = false;
+        for word in words {
+            // Modifiers are name=value; a mechanism's '=' can only follow its ':'
+            if let Some((name, value)) = word.split_once('=') {
+                if !name.contains(':') && !name.contains('/') {
+                    let mechanism = match name.to_lowercase().as_str() {
+                        "redirect" if seen_redirect => {
+                            return Err("redirect= appears more than once".to_string())
+                        }
+                        "redirect" => {
+                            seen_redirect = true;
+                            SpfMechanism::Redirect(value.to_string())
+                        }
+                        "exp" => SpfMechanism::Explanation(value.to_string()),
+                        _ => continue,
+                    };
+                    terms.push(SpfTerm {
+                        qualifier: '+',
+                        mechanism,
+                    });
+                    continue;
+                }
+            }
+
+            let (qualifier, rest) = match word.chars().next() {
+                Some(c @ ('+' | '-' | '~' | '?')) => (c, &word[1..]),
+                _ => ('+', word),
+            };
+            let split = rest.find([':', '/']).unwrap_or(rest.len());
+            let (name, argument) = rest.split_at(split);
+            let domain = argument
+                .strip_prefix(':')
+                .map(|value| value.to_string())
+                .filter(|value| !value.is_empty());
+            let invalid = || format!("Invalid term: {}", word);
+
+            let mechanism = match name.to_lowercase().as_str() {
+                "all" if argument.is_empty() => SpfMechanism::All,
+                "include" => SpfMechanism::Include(domain.ok_or_else(invalid)?),
+                "exists" => SpfMechanism::Exists(domain.ok_or_else(invalid)?),
+                "ptr" if !argument.starts_with('/') => SpfMechanism::Ptr(domain),
+                "ip4" => {
+                    let range = domain.ok_or_else(invalid)?;
+                    let (address, prefix) = range.split_once('/').unwrap_or((&range, "32"));
+                    if address.parse::<Ipv4Addr>().is_err()
+                        || !prefix.parse::<u8>().is_ok_and(|p| p <= 32)
+                    {
+                        return Err(invalid());
+                    }
+                    SpfMechanism::Ip4(range)
+                }
+                "ip6" => {
+                    let range = domain.ok_or_else(invalid)?;
+                    let (address, prefix) = range.split_once('/').unwrap_or((&range, "128"));
+                    if address.parse::<Ipv6Addr>().is_err()
+                        || !prefix.parse::<u8>().is_ok_and(|p| p <= 128)
+                    {
+                        return Err(invalid());
+                    }
+                    SpfMechanism::Ip6(range)
+                }
+                kind @ ("a" | "mx") => {
+                    let (domain, cidr4, cidr6) =
+                        self.parse_dual_cidr(argument).ok_or_else(invalid)?;
+                    if kind == "a" {
+                        SpfMechanism::A {
+                            domain,
+                            cidr4,
+                            cidr6,
+                        }
+                    } else {
+                        SpfMechanism::Mx {
+                            domain,
+                            cidr4,
+                            cidr6,
+                        }
+                    }
+                }
+                _ => return Err(format!("Unknown mechanism: {}", word)),
+            };
+            terms.push(SpfTerm {
+                qualifier,
+                mechanism,
+            });
+        }
+
+        Ok(terms)
+    }
+
+    // ":example.com/24//64", "/24", "//64" or "" -> (domain, IPv4 prefix, IPv6 prefix)
+    fn parse_dual_cidr(&self, argument: &str) -> Option<(Option<String>, Option<u8>, Option<u8>)> {
+        let (domain, cidrs) = match argument.strip_prefix(':') {
+            Some(rest) => {
+                let split = rest.find('/').unwrap_or(rest.len());
+                if split == 0 {
+                    return None;
+                }
+                (Some(rest[..split].to_string()), &rest[split..])
+            }
+            None => (None, argument),
+        };
+        let (cidr4, cidr6) = match cidrs.split_once("//") {
+            Some((cidr4, cidr6)) => (cidr4, Some(cidr6)),
+            None => (cidrs, None),
+        };
+
+        let cidr4 = match cidr4.strip_prefix('/') {
+            Some(prefix) => Some(prefix.parse::<u8>().ok().filter(|p| *p <= 32)?),
+            None if cidr4.is_empty() => None,
+            None => return None,
+        };
+        let cidr6 = match cidr6 {
+            Some(prefix) => Some(prefix.parse::<u8>().ok().filter(|p| *p <= 128)?),
+            None => None,
+        };
+        Some((domain, cidr4, cidr6))
+    }
+
+    // Sending servers fall back to plaintext when STARTTLS fails and accept
+    // invalid certificates unless MTA-STS or DANE says otherwise, so these are
+    // warnings rather than delivery failures
+    pub fn mx_warnings(
+        &self,
+        domain: &str,
+        mx_records: &[MxHost],
+        null_mx: bool,
+        implicit_mx: Option<&MxHost>,
+    ) -> Vec<String> {
+        let mut warnings = Vec::new();
+        if null_mx {
+            warnings.push(if mx_records.is_empty() {
+                format!("{} publishes a null MX (RFC 7505): it accepts no mail", domain)
+            } else {
+                format!(
+                    "{} publishes a null MX alongside other MX records; RFC 7505 requires it to be the only one",
+                    domain
+                )
+            });
+        } else if mx_records.is_empty() {
+            match implicit_mx.filter(|mx| !mx.ips.is_empty()) {
+                Some(mx) => warnings.push(format!(
+                    "No MX records: senders deliver to {} itself ({}) as an implicit MX",
+                    domain,
+                    mx.ips.join(", ")
+                )),
+                None => warnings.push(format!(
+                    "No MX records and no A/AAAA records: mail to {} can't be delivered",
+                    domain
+                )),
+            }
+        }
+
+        // The implicit MX may be a CNAME, and its missing addresses are reported above
+        for (index, mx) in mx_records.iter().chain(implicit_mx).enumerate() {
+            let listed = index < mx_records.len();
+            if let Some(target) = mx.cname.as_ref().filter(|_| listed) {
+                warnings.push(format!(
+                    "{} is a CNAME to {}; MX targets must have their own A/AAAA records (RFC 2181 10.3)",
+                    mx.hostname, target
+                ));
+            }
+            if listed && mx.ips.is_empty() {
+                warnings.push(format!(
+                    "{} does not resolve to any address; mail can't be delivered to it",
+                    mx.hostname
+                ));
+            }
+            if let Some(dane) = &mx.dane {
+                warnings.extend(dane.warnings.iter().cloned());
+            }
+            let Some(tls) = &mx.tls else {
+                continue;
+            };
+            if let Some(error) = &tls.error {
+                warnings.push(format!(
+                    "{} does not accept mail over TLS: {}",
+                    mx.hostname, error
+                ));
+                continue;
+            }
+
+            let mut problems = Vec::new();
+            if !tls.hostname_matches {
+                problems.push(format!("it is not issued for {}", mx.hostname));
+            }
+            if tls.days_until_expiry.is_some_and(|days| days < 0) {
+                problems.push("it has expired".to_string());
+            }
+            problems.extend(tls.validation_errors.iter().cloned());
+            if !tls.certificate_valid && !problems.is_empty() {
+                warnings.push(format!(
+                    "{} presents a certificate that fails verification: {}",
+                    mx.hostname,
+                    problems.join("; ")
+                ));
+            }
+        }
+
+        warnings
+    }
+}
+
+#[cfg(test)]
+#[path = "email_test.rs"]
+mod tests;
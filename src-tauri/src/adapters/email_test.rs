@@ -0,0 +1,1154 @@
+use super::super::backend::{BackendFuture, Backends, FileBackend};
+use super::super::test_support::ZoneDig;
+use super::{EmailAdapter, SpfMechanism, SpfTerm};
+use crate::models::dns::DnsRecord;
+use crate::models::email::{
+    AutoconfigEndpoint, DaneStatus, DkimLookup, DkimSignatureCheck, MxHost, MxTls, SpfEvaluation,
+    SpfInclude,
+};
+use chrono::{DateTime, Utc};
+use std::sync::{Arc, Mutex};
+
+fn mx(value: &str) -> DnsRecord {
+    DnsRecord {
+        name: "example.com.".to_string(),
+        record_type: "MX".to_string(),
+        value: value.to_string(),
+        ttl: 300,
+    }
+}
+
+fn host(hostname: &str, tls: MxTls) -> MxHost {
+    MxHost {
+        priority: 10,
+        hostname: hostname.to_string(),
+        ips: vec!["192.0.2.25".to_string()],
+        cname: None,
+        tls: Some(tls),
+        dane: None,
+    }
+}
+
+#[test]
+fn test_parse_mx() {
+    let adapter = EmailAdapter::new();
+    let hosts = adapter.parse_mx(&[
+        mx("20 MX2.Example.com."),
+        mx("10 mx1.example.com."),
+        mx("10 mx1.example.com."),
+        mx("not an mx"),
+        mx("0 ."),
+    ]);
+
+    let parsed: Vec<(u16, &str)> = hosts
+        .iter()
+        .map(|h| (h.priority, h.hostname.as_str()))
+        .collect();
+    assert_eq!(
+        parsed,
+        vec![(10, "mx1.example.com"), (20, "mx2.example.com")]
+    );
+    assert!(hosts.iter().all(|h| h.tls.is_none()));
+}
+
+#[test]
+fn test_null_mx() {
+    let adapter = EmailAdapter::new();
+    assert!(adapter.is_null_mx(&[mx("0 .")]));
+    assert!(!adapter.is_null_mx(&[mx("10 mx1.example.com.")]));
+    assert!(adapter.parse_mx(&[mx("0 .")]).is_empty());
+
+    assert_eq!(
+        adapter.mx_warnings("example.com", &[], true, None),
+        vec!["example.com publishes a null MX (RFC 7505): it accepts no mail"]
+    );
+
+    let mut implicit = host(
+        "example.com",
+        MxTls {
+            starttls: true,
+            certificate_valid: true,
+            hostname_matches: true,
+            ..MxTls::default()
+        },
+    );
+    assert_eq!(
+        adapter.mx_warnings("example.com", &[], false, Some(&implicit)),
+        vec!["No MX records: senders deliver to example.com itself (192.0.2.25) as an implicit MX"]
+    );
+
+    implicit.ips.clear();
+    implicit.tls = None;
+    assert_eq!(
+        adapter.mx_warnings("example.com", &[], false, Some(&implicit)),
+        vec!["No MX records and no A/AAAA records: mail to example.com can't be delivered"]
+    );
+}
+
+#[test]
+fn test_mx_warnings() {
+    let adapter = EmailAdapter::new();
+
+    let valid = MxTls {
+        starttls: true,
+        certificate_valid: true,
+        hostname_matches: true,
+        days_until_expiry: Some(60),
+        ..MxTls::default()
+    };
+    let wrong_host = MxTls {
+        starttls: true,
+        days_until_expiry: Some(-3),
+        validation_errors: vec!["Certificate chain is not trusted".to_string()],
+        ..MxTls::default()
+    };
+    let no_starttls = MxTls {
+        error: Some("Server does not advertise STARTTLS".to_string()),
+        ..MxTls::default()
+    };
+
+    let warnings = adapter.mx_warnings(
+        "example.com",
+        &[
+            host("mx1.example.com", valid),
+            host("mx2.example.com", wrong_host),
+            host("mx3.example.com", no_starttls),
+        ],
+        false,
+        None,
+    );
+    assert_eq!(
+        warnings,
+        vec![
+            "mx2.example.com presents a certificate that fails verification: it is not issued for mx2.example.com; it has expired; Certificate chain is not trusted",
+            "mx3.example.com does not accept mail over TLS: Server does not advertise STARTTLS",
+        ]
+    );
+}
+
+#[test]
+fn test_mx_address_warnings() {
+    let adapter = EmailAdapter::new();
+    let alias = MxHost {
+        priority: 10,
+        hostname: "mail.example.com".to_string(),
+        ips: vec!["192.0.2.25".to_string()],
+        cname: Some("mx.provider.net".to_string()),
+        tls: None,
+        dane: None,
+    };
+    let unresolved = MxHost {
+        priority: 20,
+        hostname: "backup.example.com".to_string(),
+        ips: Vec::new(),
+        cname: None,
+        tls: None,
+        dane: None,
+    };
+
+    assert_eq!(
+        adapter.mx_warnings("example.com", &[alias, unresolved], false, None),
+        vec![
+            "mail.example.com is a CNAME to mx.provider.net; MX targets must have their own A/AAAA records (RFC 2181 10.3)",
+            "backup.example.com does not resolve to any address; mail can't be delivered to it",
+        ]
+    );
+}
+
+#[test]
+fn test_parse_spf() {
+    let adapter = EmailAdapter::new();
+    let terms = adapter
+        .parse_spf("v=spf1 ip4:192.0.2.0/24 a mx:mail.example.com/24//64 include:_spf.google.com ?ptr ~all redirect=_spf.example.com unknown=1")
+        .unwrap();
+
+    assert_eq!(
+        terms,
+        vec![
+            SpfTerm {
+                qualifier: '+',
+                mechanism: SpfMechanism::Ip4("192.0.2.0/24".to_string()),
+            },
+            SpfTerm {
+                qualifier: '+',
+                mechanism: SpfMechanism::A {
+                    domain: None,
+                    cidr4: None,
+                    cidr6: None,
+                },
+            },
+            SpfTerm {
+                qualifier: '+',
+                mechanism: SpfMechanism::Mx {
+                    domain: Some("mail.example.com".to_string()),
+                    cidr4: Some(24),
+                    cidr6: Some(64),
+                },
+            },
+            SpfTerm {
+                qualifier: '+',
+                mechanism: SpfMechanism::Include("_spf.google.com".to_string()),
+            },
+            SpfTerm {
+                qualifier: '?',
+                mechanism: SpfMechanism::Ptr(None),
+            },
+            SpfTerm {
+                qualifier: '~',
+                mechanism: SpfMechanism::All,
+            },
+            SpfTerm {
+                qualifier: '+',
+                mechanism: SpfMechanism::Redirect("_spf.example.com".to_string()),
+            },
+        ]
+    );
+
+    assert_eq!(
+        adapter.parse_spf("v=spf1 a//64 -all").unwrap()[0].mechanism,
+        SpfMechanism::A {
+            domain: None,
+            cidr4: None,
+            cidr6: Some(64),
+        }
+    );
+}
+
+#[test]
+fn test_parse_spf_perm_errors() {
+    let adapter = EmailAdapter::new();
+    assert!(adapter.parse_spf("v=spf2 -all").is_err());
+    assert_eq!(
+        adapter
+            .parse_spf("v=spf1 ip4:192.0.2.300 -all")
+            .unwrap_err(),
+        "Invalid term: ip4:192.0.2.300"
+    );
+    assert_eq!(
+        adapter.parse_spf("v=spf1 ip6:2001:db8::/129").unwrap_err(),
+        "Invalid term: ip6:2001:db8::/129"
+    );
+    assert_eq!(
+        adapter.parse_spf("v=spf1 include: -all").unwrap_err(),
+        "Invalid term: include:"
+    );
+    assert_eq!(
+        adapter.parse_spf("v=spf1 a/33").unwrap_err(),
+        "Invalid term: a/33"
+    );
+    assert_eq!(
+        adapter
+            .parse_spf("v=spf1 redirect=a.example redirect=b.example")
+            .unwrap_err(),
+        "redirect= appears more than once"
+    );
+    assert_eq!(
+        adapter.parse_spf("v=spf1 ip:192.0.2.1 -all").unwrap_err(),
+        "Unknown mechanism: ip:192.0.2.1"
+    );
+}
+
+#[test]
+fn test_check_spf_limits() {
+    let adapter = EmailAdapter::new();
+
+    let mut evaluation = SpfEvaluation {
+        lookup_count: 12,
+        void_lookup_count: 3,
+        all: Some("-all".to_string()),
+        ..SpfEvaluation::default()
+    };
+    adapter.check_spf_limits("example.com", &mut evaluation);
+    assert_eq!(
+        evaluation.perm_error.as_deref(),
+        Some("SPF needs more than 10 DNS lookups; receivers stop at 10")
+    );
+    assert_eq!(
+        evaluation.warnings,
+        vec!["3 SPF lookups found nothing; receivers stop at 2"]
+    );
+
+    let mut evaluation = SpfEvaluation {
+        lookup_count: 9,
+        all: Some("+all".to_string()),
+        ..SpfEvaluation::default()
+    };
+    adapter.check_spf_limits("example.com", &mut evaluation);
+    assert!(evaluation.perm_error.is_none());
+    assert_eq!(
+        evaluation.warnings,
+        vec![
+            "SPF needs 9 of the 10 allowed DNS lookups; one more include will break it",
+            "+all lets any server on the internet send mail as example.com",
+        ]
+    );
+}
+
+#[tokio::test]
+async fn test_evaluate_spf_stops_at_lookup_limit() {
+    // Every record includes two more, without end
+    let records: Vec<(String, String, String)> = (0..64)
+        .map(|i| {
+            (
+                format!("n{}.example.com", i),
+                "TXT".to_string(),
+                format!(
+                    "\"v=spf1 include:n{}.example.com include:n{}.example.com -all\"",
+                    2 * i + 1,
+                    2 * i + 2
+                ),
+            )
+        })
+        .collect();
+    let dig = ZoneDig::new(&records);
+    let queried = dig.queried.clone();
+    let adapter = EmailAdapter::new().with_backends(Backends::default().with_dns(dig));
+
+    let evaluation = adapter.evaluate_spf("n0.example.com").await.unwrap();
+    assert_eq!(evaluation.lookup_count, 11);
+    assert_eq!(
+        evaluation.perm_error.as_deref(),
+        Some("SPF needs more than 10 DNS lookups; receivers stop at 10")
+    );
+    // The record itself and at most the ten includes allowed
+    assert!(queried.lock().unwrap().len() <= 11);
+
+    // A record that includes itself is caught as a loop
+    let dig = ZoneDig::new(&[("example.com", "TXT", "\"v=spf1 include:EXAMPLE.com -all\"")]);
+    let adapter = EmailAdapter::new().with_backends(Backends::default().with_dns(dig));
+    let evaluation = adapter.evaluate_spf("example.com").await.unwrap();
+    assert_eq!(evaluation.lookup_count, 1);
+    assert_eq!(
+        evaluation.perm_error.as_deref(),
+        Some("Include loop: example.com -> example.com")
+    );
+}
+
+#[test]
+fn test_dkim_selectors() {
+    let adapter = EmailAdapter::new();
+    let now = chrono::DateTime::parse_from_rfc3339("2024-02-15T00:00:00Z")
+        .unwrap()
+        .with_timezone(&chrono::Utc);
+    let mx_records = vec![MxHost {
+        priority: 0,
+        hostname: "example-com.mail.protection.outlook.com".to_string(),
+        ips: Vec::new(),
+        cname: None,
+        tls: None,
+        dane: None,
+    }];
+
+    let (provider, selectors) =
+        adapter.dkim_selectors(&["Custom".to_string()], &mx_records, None, now);
+    assert_eq!(provider.as_deref(), Some("Microsoft 365"));
+    let selectors: Vec<(&str, &str)> = selectors
+        .iter()
+        .map(|(selector, source)| (selector.as_str(), *source))
+        .collect();
+    assert_eq!(
+        &selectors[..7],
+        &[
+            ("custom", "custom"),
+            ("selector1", "provider"),
+            ("selector2", "provider"),
+            ("202402", "provider"),
+            ("202401", "provider"),
+            ("202312", "provider"),
+            ("default", "common"),
+        ]
+    );
+    // selector1 and selector2 aren't repeated from the common list
+    assert_eq!(selectors.len(), 6 + 7);
+
+    let (provider, selectors) = adapter.dkim_selectors(&[], &[], None, now);
+    assert_eq!(provider, None);
+    assert_eq!(selectors.len(), 9);
+
+    // Senders included in SPF contribute their selectors
+    let spf = SpfEvaluation {
+        includes: vec![include("sendgrid.net")],
+        ..SpfEvaluation::default()
+    };
+    let (provider, selectors) = adapter.dkim_selectors(&[], &[], Some(&spf), now);
+    assert_eq!(provider, None);
+    assert_eq!(
+        (selectors[0].0.as_str(), selectors[0].1),
+        ("s1", "provider")
+    );
+}
+
+#[test]
+fn test_parse_dkim() {
+    let adapter = EmailAdapter::new();
+    let record = adapter.parse_dkim(
+        "s1",
+        "v=DKIM1; k=ed25519; t=s:y; p=11qYAYKxCrfVS/7TyWQHOg7hcvPapiMlrwIaaPcHURo=",
+        "common",
+    );
+    assert_eq!(record.key_type, "ed25519");
+    assert!(record.testing);
+    assert!(!record.revoked);
+
+    let record = adapter.parse_dkim("old", "v=DKIM1; p=", "custom");
+    assert_eq!(record.key_type, "rsa");
+    assert!(!record.testing);
+    assert!(record.revoked);
+}
+
+#[test]
+fn test_dnsbl_query_name() {
+    let adapter = EmailAdapter::new();
+    assert_eq!(
+        adapter
+            .dnsbl_query_name("192.0.2.1", "zen.spamhaus.org")
+            .as_deref(),
+        Some("1.2.0.192.zen.spamhaus.org")
+    );
+    assert_eq!(
+        adapter
+            .dnsbl_query_name("2001:db8::1", "zen.spamhaus.org.")
+            .as_deref(),
+        Some("1.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.8.b.d.0.1.0.0.2.zen.spamhaus.org")
+    );
+    assert_eq!(
+        adapter.dnsbl_query_name("mail.example.com", "zen.spamhaus.org"),
+        None
+    );
+}
+
+#[test]
+fn test_interpret_dnsbl_codes() {
+    let adapter = EmailAdapter::new();
+    let codes = |codes: &[&str]| codes.iter().map(|c| c.to_string()).collect::<Vec<_>>();
+    assert_eq!(
+        adapter.interpret_dnsbl_codes("zen.spamhaus.org", &[]),
+        (false, None)
+    );
+    assert_eq!(
+        adapter.interpret_dnsbl_codes("zen.spamhaus.org", &codes(&["127.0.0.2", "127.0.0.11"])),
+        (true, None)
+    );
+
+    let (listed, error) =
+        adapter.interpret_dnsbl_codes("dbl.spamhaus.org", &codes(&["127.255.255.254"]));
+    assert!(!listed);
+    assert!(error.unwrap().starts_with("The list refused the query"));
+
+    // URIBL and SURBL refuse with 127.0.0.1, which other lists may use for a listing
+    for zone in ["multi.uribl.com", "multi.surbl.org."] {
+        let (listed, error) = adapter.interpret_dnsbl_codes(zone, &codes(&["127.0.0.1"]));
+        assert!(!listed, "{}", zone);
+        assert!(error.unwrap().starts_with("The list refused the query"));
+    }
+    assert_eq!(
+        adapter.interpret_dnsbl_codes("multi.uribl.com", &codes(&["127.0.0.2"])),
+        (true, None)
+    );
+    assert_eq!(
+        adapter.interpret_dnsbl_codes("bl.example.net", &codes(&["127.0.0.1"])),
+        (true, None)
+    );
+
+    let (listed, error) =
+        adapter.interpret_dnsbl_codes("zen.spamhaus.org", &codes(&["203.0.113.5"]));
+    assert!(!listed);
+    assert!(error.unwrap().contains("defunct"));
+}
+
+#[test]
+fn test_parse_dmarc() {
+    let adapter = EmailAdapter::new();
+    let dmarc = adapter.parse_dmarc(
+        "example.com",
+        "v=DMARC1; p=reject; sp=none; pct=50; adkim=s; fo=1:d; ri=3600; \
+         rua=mailto:dmarc@example.com,mailto:reports@vendor.net!10m; ruf=mailto:forensic@mail.example.com",
+    );
+    assert!(dmarc.errors.is_empty(), "{:?}", dmarc.errors);
+    assert_eq!(dmarc.policy.as_deref(), Some("reject"));
+    assert_eq!(dmarc.subdomain_policy, "none");
+    assert_eq!(dmarc.percentage, 50);
+    assert_eq!(dmarc.dkim_alignment, "s");
+    assert_eq!(dmarc.spf_alignment, "r");
+    assert_eq!(dmarc.failure_options, vec!["1", "d"]);
+    assert_eq!(dmarc.report_formats, vec!["afrf"]);
+    assert_eq!(dmarc.report_interval, 3600);
+
+    let rua = &dmarc.aggregate_reports;
+    assert_eq!(rua.len(), 2);
+    assert_eq!(rua[0].address.as_deref(), Some("dmarc@example.com"));
+    assert!(!rua[0].external);
+    assert_eq!(rua[1].address.as_deref(), Some("reports@vendor.net"));
+    assert_eq!(rua[1].max_size.as_deref(), Some("10m"));
+    assert!(rua[1].external);
+    assert!(!dmarc.failure_reports[0].external);
+
+    assert!(dmarc
+        .warnings
+        .iter()
+        .any(|w| w.starts_with("sp=none is weaker")));
+    assert!(dmarc.warnings.iter().any(|w| w.starts_with("pct=50")));
+}
+
+#[test]
+fn test_parse_dmarc_defaults_and_errors() {
+    let adapter = EmailAdapter::new();
+    let dmarc = adapter.parse_dmarc("example.com", "v=DMARC1; p=quarantine");
+    assert_eq!(dmarc.subdomain_policy, "quarantine");
+    assert_eq!(dmarc.percentage, 100);
+    assert_eq!(dmarc.failure_options, vec!["0"]);
+    assert_eq!(dmarc.report_interval, 86400);
+    assert!(dmarc.errors.is_empty());
+    assert!(dmarc.warnings.iter().any(|w| w.starts_with("No rua=")));
+
+    let dmarc = adapter.parse_dmarc(
+        "example.com",
+        "v=DMARC1; p=block; pct=150; fo=x; rua=https://example.com/dmarc, mailto:nobody",
+    );
+    assert_eq!(dmarc.policy, None);
+    assert_eq!(dmarc.percentage, 100);
+    assert_eq!(dmarc.failure_options, vec!["0"]);
+    assert_eq!(dmarc.errors.len(), 6, "{:?}", dmarc.errors);
+    assert!(dmarc
+        .errors
+        .iter()
+        .any(|e| e.starts_with("Missing or invalid p=")));
+    assert!(dmarc
+        .errors
+        .iter()
+        .any(|e| e.contains("Only mailto: destinations")));
+}
+
+#[test]
+fn test_parse_tlsa() {
+    let adapter = EmailAdapter::new();
+    let record = adapter
+        .parse_tlsa("3 1 1 0C72AC70B745AC19998811B131D662C9AC69DBDBE7CB23E5B514B566 64C5D3D6")
+        .unwrap();
+    assert_eq!(
+        (record.usage, record.selector, record.matching_type),
+        (3, 1, 1)
+    );
+    assert_eq!(
+        record.data,
+        "0c72ac70b745ac19998811b131d662c9ac69dbdbe7cb23e5b514b56664c5d3d6"
+    );
+    assert!(adapter.parse_tlsa("3 1 1").is_none());
+    assert!(adapter.parse_tlsa("3 1 1 not-hex").is_none());
+}
+
+#[test]
+fn test_verify_dane() {
+    let adapter = EmailAdapter::new();
+    let records = vec![
+        adapter.parse_tlsa("0 0 1 abcd").unwrap(),
+        adapter.parse_tlsa("3 1 1 abcd").unwrap(),
+    ];
+
+    // STARTTLS failed: no certificate to match
+    let mut dane = DaneStatus {
+        records: records.clone(),
+        authenticated: false,
+        valid: false,
+        warnings: Vec::new(),
+    };
+    let failed = MxTls {
+        error: Some("Server does not advertise STARTTLS".to_string()),
+        ..MxTls::default()
+    };
+    adapter.verify_dane("mx.example.com", &mut dane, &[], &failed);
+    assert!(!dane.valid);
+    assert!(dane.records[0]
+        .error
+        .as_deref()
+        .unwrap()
+        .starts_with("Usage 0 (PKIX)"));
+    assert!(dane.records[1].error.is_none());
+    assert!(!dane.records[1].matched);
+    assert_eq!(
+        dane.warnings,
+        vec![
+            "The TLSA records of mx.example.com aren't DNSSEC-validated by the resolver; senders ignore them",
+            "mx.example.com publishes TLSA records but TLS failed (Server does not advertise STARTTLS); DANE senders defer mail to it",
+        ]
+    );
+
+    // Certificate presented but no record matches it
+    let mut dane = DaneStatus {
+        records,
+        authenticated: true,
+        valid: false,
+        warnings: Vec::new(),
+    };
+    adapter.verify_dane("mx.example.com", &mut dane, &[], &MxTls::default());
+    assert_eq!(
+        dane.warnings,
+        vec!["No TLSA record of mx.example.com matches the certificate it presents; DANE senders defer mail to it"]
+    );
+}
+
+#[test]
+fn test_apply_dane() {
+    let adapter = EmailAdapter::new();
+    let self_signed = MxTls {
+        starttls: true,
+        days_until_expiry: Some(60),
+        validation_errors: vec!["Certificate chain is not trusted".to_string()],
+        ..MxTls::default()
+    };
+    let mut record = adapter.parse_tlsa("3 1 1 abcd").unwrap();
+    record.matched = true;
+    let mut dane = DaneStatus {
+        records: vec![record],
+        authenticated: true,
+        valid: true,
+        warnings: Vec::new(),
+    };
+
+    let mut tls = self_signed.clone();
+    adapter.apply_dane(&mut tls, &dane);
+    assert!(tls.certificate_valid);
+    assert!(tls.validation_errors.is_empty());
+    assert!(adapter
+        .mx_warnings("example.com", &[host("mx.example.com", tls)], false, None)
+        .is_empty());
+
+    // DANE-TA, or records senders ignore, leave PKIX in charge
+    dane.records[0].usage = 2;
+    let mut tls = self_signed.clone();
+    adapter.apply_dane(&mut tls, &dane);
+    assert!(!tls.certificate_valid);
+    dane.records[0].usage = 3;
+    dane.valid = false;
+    let mut tls = self_signed;
+    adapter.apply_dane(&mut tls, &dane);
+    assert_eq!(tls.validation_errors.len(), 1);
+}
+
+const DMARC_REPORT: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<feedback>
+  <report_metadata>
+<org_name>google.com</org_name>
+<email>noreply-dmarc-support@google.com</email>
+<report_id>1234567890</report_id>
+<date_range><begin>1760572800</begin><end>1760659199</end></date_range>
+  </report_metadata>
+  <policy_published>
+<domain>example.com</domain><adkim>r</adkim><aspf>r</aspf>
+<p>quarantine</p><sp>quarantine</sp><pct>100</pct>
+  </policy_published>
+  <record>
+<row>
+  <source_ip>192.0.2.10</source_ip><count>40</count>
+  <policy_evaluated><disposition>none</disposition><dkim>pass</dkim><spf>pass</spf></policy_evaluated>
+</row>
+<identifiers><header_from>example.com</header_from></identifiers>
+<auth_results>
+  <dkim><domain>example.com</domain><selector>s1</selector><result>pass</result></dkim>
+  <spf><domain>example.com</domain><result>pass</result></spf>
+</auth_results>
+  </record>
+  <record>
+<row>
+  <source_ip>192.0.2.10</source_ip><count>2</count>
+  <policy_evaluated><disposition>quarantine</disposition><dkim>fail</dkim><spf>fail</spf></policy_evaluated>
+</row>
+<identifiers><header_from>news.example.com</header_from></identifiers>
+<auth_results><spf><domain>bounce.example.net</domain><result>pass</result></spf></auth_results>
+  </record>
+  <record>
+<row>
+  <source_ip>203.0.113.7</source_ip><count>5</count>
+  <policy_evaluated><disposition>quarantine</disposition><dkim>fail</dkim><spf>fail</spf></policy_evaluated>
+</row>
+<identifiers><header_from>example.com</header_from></identifiers>
+<auth_results><spf><domain>spoofer.test</domain><result>fail</result></spf></auth_results>
+  </record>
+</feedback>"#;
+
+#[test]
+fn test_parse_dmarc_report() {
+    let adapter = EmailAdapter::new();
+    let report = adapter.parse_dmarc_report(DMARC_REPORT).unwrap();
+
+    assert_eq!(report.org_name, "google.com");
+    assert_eq!(report.report_id, "1234567890");
+    assert_eq!(report.begin.as_deref(), Some("2025-10-16T00:00:00+00:00"));
+    assert_eq!(report.domain, "example.com");
+    assert_eq!(report.policy.as_deref(), Some("quarantine"));
+    assert_eq!(report.percentage, Some(100));
+    assert_eq!((report.total_messages, report.dmarc_pass), (47, 40));
+
+    let source = &report.sources[0];
+    assert_eq!(source.source_ip, "192.0.2.10");
+    assert_eq!(source.messages, 42);
+    assert_eq!(
+        (source.dkim_aligned, source.spf_aligned, source.dmarc_pass),
+        (40, 40, 40)
+    );
+    assert_eq!(source.quarantined, 2);
+    assert_eq!(source.header_from, vec!["example.com", "news.example.com"]);
+    assert_eq!(
+        source.spf_domains,
+        vec!["example.com", "bounce.example.net"]
+    );
+    assert_eq!(source.dkim_domains, vec!["example.com"]);
+
+    assert_eq!(report.sources[1].source_ip, "203.0.113.7");
+    assert_eq!(
+        report.warnings,
+        vec![
+            "192.0.2.10 sent 2 of 42 messages that failed DMARC",
+            "203.0.113.7 sent 5 messages that all failed DMARC; an unauthorized sender or a service missing from SPF and DKIM",
+        ]
+    );
+
+    assert!(adapter.parse_dmarc_report("<html></html>").is_err());
+    assert!(adapter.parse_dmarc_report("not xml").is_err());
+}
+
+#[test]
+fn test_decompress_dmarc_report() {
+    use flate2::write::GzEncoder;
+    use std::io::{Cursor, Write};
+
+    let adapter = EmailAdapter::new();
+    assert_eq!(
+        adapter
+            .decompress_dmarc_report(DMARC_REPORT.as_bytes())
+            .unwrap(),
+        DMARC_REPORT
+    );
+
+    let mut gzip = GzEncoder::new(Vec::new(), flate2::Compression::default());
+    gzip.write_all(DMARC_REPORT.as_bytes()).unwrap();
+    let gzip = gzip.finish().unwrap();
+    assert_eq!(
+        adapter.decompress_dmarc_report(&gzip).unwrap(),
+        DMARC_REPORT
+    );
+
+    let mut archive = zip::ZipWriter::new(Cursor::new(Vec::new()));
+    let options = zip::write::SimpleFileOptions::default()
+        .compression_method(zip::CompressionMethod::Deflated);
+    archive
+        .start_file("google.com!example.com!1760572800!1760659199.xml", options)
+        .unwrap();
+    archive.write_all(DMARC_REPORT.as_bytes()).unwrap();
+    let archive = archive.finish().unwrap().into_inner();
+    assert_eq!(
+        adapter.decompress_dmarc_report(&archive).unwrap(),
+        DMARC_REPORT
+    );
+
+    assert!(adapter
+        .decompress_dmarc_report(&[0xff, 0xfe, 0x00])
+        .is_err());
+
+    // Past 64 MiB, compressed or not, rather than cut short into invalid XML
+    let oversized = vec![b' '; 64 * 1024 * 1024 + 1];
+    assert_eq!(
+        adapter.decompress_dmarc_report(&oversized),
+        Err("The report exceeds 64 MiB".to_string())
+    );
+    let mut gzip = GzEncoder::new(Vec::new(), flate2::Compression::fast());
+    gzip.write_all(&oversized).unwrap();
+    assert_eq!(
+        adapter.decompress_dmarc_report(&gzip.finish().unwrap()),
+        Err("The report exceeds 64 MiB".to_string())
+    );
+}
+
+// Serves one file's contents, whatever path is asked for, recording the paths
+struct CannedFile {
+    contents: Vec<u8>,
+    read: Arc<Mutex<Vec<String>>>,
+}
+
+impl FileBackend for CannedFile {
+    fn read<'a>(&'a self, path: &'a str) -> BackendFuture<'a, Vec<u8>> {
+        self.read.lock().unwrap().push(path.to_string());
+        Box::pin(async move { Ok(self.contents.clone()) })
+    }
+}
+
+#[tokio::test]
+async fn test_analyze_dmarc_report_reads_through_backend() {
+    use flate2::write::GzEncoder;
+    use std::io::Write;
+
+    let mut gzip = GzEncoder::new(Vec::new(), flate2::Compression::default());
+    gzip.write_all(DMARC_REPORT.as_bytes()).unwrap();
+    let file = CannedFile {
+        contents: gzip.finish().unwrap(),
+        read: Arc::new(Mutex::new(Vec::new())),
+    };
+    let read = file.read.clone();
+    let adapter = EmailAdapter::new().with_backends(Backends::default().with_files(file));
+
+    let report = adapter
+        .analyze_dmarc_report("reports/google.com.xml.gz")
+        .await
+        .unwrap();
+    assert_eq!(report.org_name, "google.com");
+    assert_eq!((report.total_messages, report.dmarc_pass), (47, 40));
+    assert_eq!(*read.lock().unwrap(), vec!["reports/google.com.xml.gz"]);
+}
+
+#[test]
+fn test_parse_mail_srv() {
+    let adapter = EmailAdapter::new();
+    let srv = adapter
+        .parse_mail_srv("_imaps._tcp", "0 1 993 IMAP.Example.com.")
+        .unwrap();
+    assert_eq!(
+        (srv.priority, srv.weight, srv.port, srv.target.as_str()),
+        (0, 1, 993, "imap.example.com")
+    );
+
+    let disabled = adapter.parse_mail_srv("_pop3._tcp", "0 0 0 .").unwrap();
+    assert_eq!(disabled.target, "");
+    assert!(adapter.parse_mail_srv("_imap._tcp", "0 1 143").is_none());
+}
+
+#[test]
+fn test_parse_autoconfig() {
+    let adapter = EmailAdapter::new();
+    let servers = adapter
+        .parse_autoconfig(
+            r#"<?xml version="1.0"?>
+<clientConfig version="1.1">
+  <emailProvider id="example.com">
+<incomingServer type="imap">
+  <hostname>imap.example.com</hostname><port>993</port><socketType>SSL</socketType>
+</incomingServer>
+<incomingServer type="pop3">
+  <hostname>pop.example.com</hostname><port>110</port><socketType>plain</socketType>
+</incomingServer>
+<outgoingServer type="smtp">
+  <hostname>smtp.example.com</hostname><port>587</port><socketType>STARTTLS</socketType>
+</outgoingServer>
+  </emailProvider>
+</clientConfig>"#,
+        )
+        .unwrap();
+
+    let parsed: Vec<(&str, &str, Option<u16>)> = servers
+        .iter()
+        .map(|s| (s.protocol.as_str(), s.hostname.as_str(), s.port))
+        .collect();
+    assert_eq!(
+        parsed,
+        vec![
+            ("imap", "imap.example.com", Some(993)),
+            ("pop3", "pop.example.com", Some(110)),
+            ("smtp", "smtp.example.com", Some(587)),
+        ]
+    );
+    assert!(adapter.parse_autoconfig("<html></html>").is_err());
+    assert!(adapter.parse_autoconfig("Not found").is_err());
+}
+
+#[test]
+fn test_autodiscovery_warnings() {
+    let adapter = EmailAdapter::new();
+    assert_eq!(
+        adapter.autodiscovery_warnings(&[], &[], &[]),
+        vec!["No autoconfiguration: mail clients have to guess or be set up by hand"]
+    );
+
+    let mut srv = adapter
+        .parse_mail_srv("_submission._tcp", "0 1 587 smtp.old-provider.net.")
+        .unwrap();
+    srv.resolves = false;
+    let endpoint =
+        |mechanism: &str, url: &str, status: Option<u16>, error: Option<&str>| AutoconfigEndpoint {
+            mechanism: mechanism.to_string(),
+            url: url.to_string(),
+            status_code: status,
+            configured: false,
+            servers: Vec::new(),
+            error: error.map(|e| e.to_string()),
+        };
+    let endpoints = vec![
+        endpoint(
+            "autoconfig",
+            "https://autoconfig.example.com/mail/config-v1.1.xml",
+            Some(404),
+            None,
+        ),
+        endpoint(
+            "autoconfig (well-known)",
+            "https://example.com/.well-known/autoconfig/mail/config-v1.1.xml",
+            Some(404),
+            None,
+        ),
+        endpoint(
+            "autodiscover",
+            "https://autodiscover.example.com/autodiscover/autodiscover.xml",
+            None,
+            Some("autodiscover.example.com does not resolve"),
+        ),
+    ];
+
+    assert_eq!(
+        adapter.autodiscovery_warnings(&[srv], &endpoints, &["srv".to_string()]),
+        vec![
+            "_submission._tcp points to smtp.old-provider.net, which does not resolve",
+            "https://autoconfig.example.com/mail/config-v1.1.xml exists but doesn't serve autoconfig (HTTP 404); it may still point at a previous provider",
+        ]
+    );
+}
+
+fn include(domain: &str) -> SpfInclude {
+    SpfInclude {
+        domain: domain.to_string(),
+        via: "include".to_string(),
+        record: None,
+        depth: 1,
+    }
+}
+
+#[test]
+fn test_detect_providers() {
+    let adapter = EmailAdapter::new();
+    let mx_records = vec![MxHost {
+        priority: 0,
+        hostname: "example-com.mail.protection.outlook.com".to_string(),
+        ips: Vec::new(),
+        cname: None,
+        tls: None,
+        dane: None,
+    }];
+    let spf = SpfEvaluation {
+        includes: vec![
+            include("spf.messaging.microsoft.com"),
+            include("sendgrid.net"),
+        ],
+        ..SpfEvaluation::default()
+    };
+    let dkim = DkimLookup {
+        provider: Some("Microsoft 365".to_string()),
+        tried: Vec::new(),
+        records: vec![adapter.parse_dkim("s1", "v=DKIM1; p=MIGf", "provider")],
+    };
+
+    let providers = adapter.detect_providers(&mx_records, Some(&spf), &dkim);
+    let names: Vec<&str> = providers.iter().map(|p| p.name.as_str()).collect();
+    assert_eq!(names, vec!["Microsoft 365", "SendGrid"]);
+
+    let microsoft = &providers[0];
+    assert_eq!(
+        microsoft.evidence,
+        vec![
+            "MX example-com.mail.protection.outlook.com",
+            "SPF include:spf.messaging.microsoft.com",
+        ]
+    );
+    assert_eq!(
+        microsoft.outdated,
+        vec!["SPF include:spf.messaging.microsoft.com (use include:spf.protection.outlook.com)"]
+    );
+    assert_eq!(
+        microsoft.missing,
+        vec!["DKIM key (selector selector1 or selector2)"]
+    );
+
+    let sendgrid = &providers[1];
+    assert_eq!(sendgrid.evidence, vec!["SPF include:sendgrid.net"]);
+    assert!(sendgrid.missing.is_empty());
+
+    // A key under a generic selector alone points at no provider
+    assert!(adapter.detect_providers(&[], None, &dkim).is_empty());
+    let proton = DkimLookup {
+        records: vec![adapter.parse_dkim("protonmail", "v=DKIM1; p=MIGf", "provider")],
+        ..DkimLookup::default()
+    };
+    let proton = adapter.detect_providers(&[], None, &proton);
+    assert_eq!(proton[0].evidence, vec!["DKIM selector protonmail"]);
+
+    let google = adapter.detect_providers(
+        &[MxHost {
+            hostname: "aspmx.l.google.com".to_string(),
+            ..mx_records[0].clone()
+        }],
+        None,
+        &DkimLookup::default(),
+    );
+    assert_eq!(
+        google[0].missing,
+        vec!["SPF include:_spf.google.com", "DKIM key (selector google)"]
+    );
+
+    assert!(adapter
+        .detect_providers(&[], None, &DkimLookup::default())
+        .is_empty());
+}
+
+// Signed with the keys below: ed25519-sha256 first, then rsa-sha256
+const DKIM_MESSAGE: &str = concat!(
+    "DKIM-Signature: v=1; a=ed25519-sha256; c=relaxed/relaxed; d=example.com; s=ed;\r\n",
+    " t=1792141200; h=from:to:subject:date:message-id;\r\n",
+    " bh=HD3VkuN20CX8I+9lkWia9E3l4J27mjSbwiQ4VYs4iAI=;\r\n",
+    " b=a6PuGuTnGnjidkX7rc5neguypAfv36+zHvGPcaoGL9fDPL5D1d0pYGOxjj5rSG0v\r\n",
+    " RxfhzPPdfTFORryhiVl6CA==\r\n",
+    "DKIM-Signature: v=1; a=rsa-sha256; c=relaxed/relaxed; d=example.com; s=test;\r\n",
+    " t=1792141200; h=from:to:subject:date:message-id;\r\n",
+    " bh=HD3VkuN20CX8I+9lkWia9E3l4J27mjSbwiQ4VYs4iAI=;\r\n",
+    " b=t+5o9jb+3Q+TmgcBnhX9cPt59jN0mP8bOE8r2qemPbFptHQk1pTjwBUZG5/YZf2A\r\n",
+    " AKhAQUTZoZRm/l3LRiuQ25rKG0/lgLXLHSKTW/DXTojW0QGEJIlMBX5Cnx+5jIL2\r\n",
+    " olkr1YUt2akl/pnr58Fx49QZ591XplH97iQB8BcUU40=\r\n",
+    "From: Alice <alice@example.com>\r\n",
+    "To: bob@example.net\r\n",
+    "Subject: DKIM   test\r\n",
+    " folded\r\n",
+    "Date: Fri, 16 Oct 2026 09:00:00 +0000\r\n",
+    "Message-ID: <test@example.com>\r\n",
+    "\r\n",
+    "Hello Bob,  \r\n",
+    "This is a\t test.\r\n",
+    "\r\n",
+    "\r\n",
+);
+const DKIM_RSA_KEY: &str = "v=DKIM1; k=rsa; p=MIGfMA0GCSqGSIb3DQEBAQUAA4GNADCBiQKBgQC/clOi04Be1v6eRgHrOUR3frVtARsAdIqtzZmu3U2+uDnJIgWnP6rEje21oQVJyX/WuTSggFqHel86Nj/KL1uxPTJaP3nKroIegT9Xm+naQ33WzLnSkokSQe+j7U8mrkM3qeI0C9xcXj4hpeAdL1ONZLW0MZYVJbcZHkfyBKKZkQIDAQAB";
+const DKIM_ED25519_KEY: &str = "v=DKIM1; k=ed25519; p=pILsygVbz4U2//cSMQuufvxLK3ZIO7zsS7s03WzCEcE=";
+
+fn check_dkim(message: &str, index: usize, key: &str) -> DkimSignatureCheck {
+    let adapter = EmailAdapter::new();
+    let (headers, body) = adapter.split_message(message).unwrap();
+    let now = DateTime::parse_from_rfc3339("2026-10-17T00:00:00Z")
+        .unwrap()
+        .with_timezone(&Utc);
+    adapter.check_dkim_signature(
+        &headers,
+        body.as_deref(),
+        index,
+        Ok(key.to_string()),
+        Some("example.com"),
+        now,
+    )
+}
+
+#[test]
+fn test_verify_dkim_signature() {
+    let rsa = check_dkim(DKIM_MESSAGE, 1, DKIM_RSA_KEY);
+    assert!(rsa.valid, "{:?}", rsa.error);
+    assert_eq!(rsa.body_hash_valid, Some(true));
+    assert!(rsa.aligned);
+    assert_eq!(rsa.key_bits, Some(1024));
+    assert_eq!(
+        rsa.signed_headers,
+        vec!["from", "to", "subject", "date", "message-id"]
+    );
+    assert_eq!(rsa.signed_at.as_deref(), Some("2026-10-16T09:00:00+00:00"));
+    assert_eq!(rsa.warnings, vec!["1024-bit key; 2048 bits is recommended"]);
+
+    let ed25519 = check_dkim(DKIM_MESSAGE, 0, DKIM_ED25519_KEY);
+    assert!(ed25519.valid, "{:?}", ed25519.error);
+    assert_eq!(ed25519.algorithm, "ed25519-sha256");
+    assert!(ed25519.warnings.is_empty());
+
+    // Line endings don't matter, relaxed canonicalization ignores refolding
+    let refolded = DKIM_MESSAGE
+        .replace("\r\n", "\n")
+        .replace("DKIM   test\n folded", "DKIM test\n\t folded");
+    assert!(check_dkim(&refolded, 1, DKIM_RSA_KEY).valid);
+}
+
+#[test]
+fn test_verify_dkim_failures() {
+    let body = check_dkim(
+        &DKIM_MESSAGE.replace("Hello Bob", "Hello Eve"),
+        1,
+        DKIM_RSA_KEY,
+    );
+    assert!(!body.valid);
+    assert_eq!(body.body_hash_valid, Some(false));
+    assert!(body
+        .error
+        .unwrap()
+        .starts_with("The body hash doesn't match"));
+
+    let header = check_dkim(
+        &DKIM_MESSAGE.replace("To: bob@", "To: eve@"),
+        0,
+        DKIM_ED25519_KEY,
+    );
+    assert_eq!(header.body_hash_valid, Some(true));
+    assert!(header
+        .error
+        .unwrap()
+        .starts_with("The signature doesn't match"));
+
+    // Keys are tried against the wrong signature
+    let swapped = check_dkim(DKIM_MESSAGE, 0, DKIM_RSA_KEY);
+    assert_eq!(
+        swapped.error.as_deref(),
+        Some("The key is rsa but the signature uses ed25519-sha256")
+    );
+    let revoked = check_dkim(DKIM_MESSAGE, 1, "v=DKIM1; p=");
+    assert_eq!(
+        revoked.error.as_deref(),
+        Some("The key has been revoked (empty p=)")
+    );
+
+    // Headers alone still verify the header signature
+    let headers_only = DKIM_MESSAGE.split("\r\n\r\n").next().unwrap();
+    let check = check_dkim(headers_only, 1, DKIM_RSA_KEY);
+    assert!(check.valid);
+    assert_eq!(check.body_hash_valid, None);
+
+    let unsigned_from = check_dkim(
+        &DKIM_MESSAGE.replace("h=from:to:", "h=to:"),
+        1,
+        DKIM_RSA_KEY,
+    );
+    assert_eq!(
+        unsigned_from.error.as_deref(),
+        Some("h= doesn't include From, which must be signed")
+    );
+}
+
+#[test]
+fn test_verify_dkim_body_length_mid_character() {
+    // l=2 ends inside the é; only "H" and its first byte are hashed
+    let message = concat!(
+        "DKIM-Signature: v=1; a=rsa-sha256; c=relaxed/relaxed; d=example.com; s=test;\r\n",
+        " l=2; h=from; bh=cHA1FUTdnflh9o9bjdFzytH1SxwNxuqj9bCrQ9SldvU=; b=AAAA\r\n",
+        "From: alice@example.com\r\n",
+        "\r\n",
+        "H\u{e9}llo\r\n",
+    );
+    let check = check_dkim(message, 0, DKIM_RSA_KEY);
+    assert_eq!(check.body_length, Some(2));
+    assert_eq!(check.body_hash_valid, Some(true));
+    assert!(!check.valid);
+}
+
+#[test]
+fn test_split_message() {
+    let adapter = EmailAdapter::new();
+    let (headers, body) = adapter
+        .split_message("From sender Fri Oct 16 09:00:00 2026\nFrom: a@example.com\nSubject: one\n  two\n\nBody\n")
+        .unwrap();
+    assert_eq!(headers.len(), 2);
+    assert_eq!(headers[1].0, "Subject");
+    assert_eq!(headers[1].1, "Subject: one\r\n  two");
+    assert_eq!(body.as_deref(), Some("Body\r\n"));
+
+    assert!(adapter.split_message("not a header").is_err());
+    assert_eq!(
+        adapter.split_message("From: a@example.com\n\n").unwrap().1,
+        None
+    );
+}
+
+#[test]
+fn test_canonicalize_body() {
+    let adapter = EmailAdapter::new();
+    let body = " C \r\nD \t E\r\n\r\n\r\n";
+    assert_eq!(adapter.canonicalize_body(body, true), " C\r\nD E\r\n");
+    assert_eq!(adapter.canonicalize_body(body, false), " C \r\nD \t E\r\n");
+    assert_eq!(adapter.canonicalize_body("", true), "");
+    assert_eq!(adapter.canonicalize_body("\r\n\r\n", false), "\r\n");
+}
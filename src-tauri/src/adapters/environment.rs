@@ -0,0 +1,276 @@
+use crate::adapters::dns_tools::DnsTool;
+use crate::models::environment::{DegradedFeature, EnvironmentReport, ToolCheck, ToolRole};
+use chrono::Utc;
+use std::env;
+use std::ffi::OsString;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use tokio::process::Command;
+
+// How long a tool gets to print its version
+const VERSION_TIMEOUT: Duration = Duration::from_secs(5);
+
+struct Tool {
+    name: &'static str,
+    version_args: &'static [&'static str],
+    role: ToolRole,
+    purpose: &'static str,
+    // Used in its place when it's missing
+    fallbacks: &'static [&'static str],
+    // Features that fail without the tool or a fallback, and what happens instead
+    features: &'static [(&'static str, &'static str)],
+}
+
+// Resolve-DnsName only counts as a fallback on Windows, where it's built in
+const DNS_FALLBACKS: &[&str] = if cfg!(windows) {
+    &["kdig", "drill", "host", "powershell"]
+} else {
+    &["kdig", "drill", "host"]
+};
+
+const PLATFORM_TOOLS: &[Tool] = if cfg!(windows) {
+    &[Tool {
+        name: "powershell",
+        version_args: &[
+            "-NoProfile",
+            "-Command",
+            "$PSVersionTable.PSVersion.ToString()",
+        ],
+        role: ToolRole::Fallback,
+        purpose: "DNS queries through Resolve-DnsName when no dig-like tool is installed",
+        fallbacks: &[],
+        features: &[],
+    }]
+} else {
+    &[]
+};
+
+// Windows always has tracert, so only the Unix tools are checked for
+const TRACE_TOOLS: &[Tool] = if cfg!(windows) {
+    &[]
+} else {
+    &[
+        Tool {
+            name: "traceroute",
+            version_args: &["--version"],
+            role: ToolRole::Optional,
+            purpose: "trace_route's hops and their latency",
+            fallbacks: &["tracepath"],
+            features: &[],
+        },
+        Tool {
+            name: "tracepath",
+            version_args: &["-V"],
+            role: ToolRole::Fallback,
+            purpose: "trace_route when traceroute is missing",
+            fallbacks: &[],
+            features: &[],
+        },
+    ]
+};
+
+const TOOLS: [Tool; 7] = [
+    Tool {
+        name: "dig",
+        version_args: &["-v"],
+        role: ToolRole::Required,
+        purpose: "Every DNS query, including DNSSEC validation and email checks",
+        fallbacks: DNS_FALLBACKS,
+        features: &[
+            ("DNS queries", "Fail with \"No DNS query tool found\""),
+            ("DNSSEC validation", "Fails before walking the chain"),
+            (
+                "Nameserver, delegation and DNS hygiene checks",
+                "Fail with \"No DNS query tool found\"",
+            ),
+            (
+                "Email configuration (MX, SPF, DKIM, DMARC, DNSBLs)",
+                "Fails, as every email record is looked up over DNS",
+            ),
+            (
+                "Domain reports",
+                "Run without their DNS, DNSSEC and email sections",
+            ),
+        ],
+    },
+    Tool {
+        name: "kdig",
+        version_args: &["--version"],
+        role: ToolRole::Fallback,
+        purpose: "DNS queries when dig is missing",
+        fallbacks: &[],
+        features: &[],
+    },
+    Tool {
+        name: "drill",
+        version_args: &["-v"],
+        role: ToolRole::Fallback,
+        purpose: "DNS queries when dig and kdig are missing",
+        fallbacks: &[],
+        features: &[],
+    },
+    Tool {
+        name: "host",
+        version_args: &["-V"],
+        role: ToolRole::Fallback,
+        purpose: "DNS queries, without DNSSEC records, when dig, kdig and drill are missing",
+        fallbacks: &[],
+        features: &[],
+    },
+    Tool {
+        name: "whois",
+        version_args: &["--version"],
+        role: ToolRole::Optional,
+        purpose: "Re-running logged WHOIS queries; lookups use the built-in client",
+        fallbacks: &[],
+        features: &[],
+    },
+    Tool {
+        name: "openssl",
+        version_args: &["version"],
+        role: ToolRole::Optional,
+        purpose: "Inspecting certificates by hand; handshakes use the built-in TLS client",
+        fallbacks: &[],
+        features: &[],
+    },
+    Tool {
+        name: "curl",
+        version_args: &["--version"],
+        role: ToolRole::Optional,
+        purpose: "Re-running logged HTTP requests; the app sends its own",
+        fallbacks: &[],
+        features: &[],
+    },
+];
+
+// Checks which external programs the adapters can use, so missing ones are reported
+// up front instead of as one failed lookup after another
+pub struct EnvironmentAdapter {
+    path: Option<OsString>, // Searched instead of $PATH when set
+}
+
+impl EnvironmentAdapter {
+    pub fn new() -> Self {
+        EnvironmentAdapter { path: None }
+    }
+
+    pub fn with_path(mut self, path: impl Into<OsString>) -> Self {
+        self.path = Some(path.into());
+        self
+    }
+
+    pub async fn check(&self) -> EnvironmentReport {
+        let mut tools = Vec::new();
+        for tool in TOOLS.iter().chain(TRACE_TOOLS).chain(PLATFORM_TOOLS) {
+            let path = self.find(tool.name);
+            let version = match &path {
+                Some(path) => version(path, tool.version_args).await,
+                None => None,
+            };
+            tools.push(ToolCheck {
+                name: tool.name.to_string(),
+                role: tool.role,
+                found: path.is_some(),
+                path: path.map(|p| p.display().to_string()),
+                version,
+                purpose: tool.purpose.to_string(),
+            });
+        }
+        let found = |name: &str| tools.iter().any(|t| t.name == name && t.found);
+
+        let mut degraded = Vec::new();
+        let mut ready = true;
+        for tool in &TOOLS {
+            let usable: Vec<&str> = std::iter::once(tool.name)
+                .chain(tool.fallbacks.iter().copied())
+                .collect();
+            if usable.iter().any(|name| found(name)) {
+                continue;
+            }
+            ready &= tool.role != ToolRole::Required;
+            degraded.extend(
+                tool.features
+                    .iter()
+                    .map(|(feature, impact)| DegradedFeature {
+                        feature: feature.to_string(),
+                        missing: usable.iter().map(|name| name.to_string()).collect(),
+                        impact: impact.to_string(),
+                    }),
+            );
+        }
+
+        let dns_tool = DnsTool::ALL.iter().copied().find(|tool| found(tool.name()));
+        if let Some(tool @ (DnsTool::Host | DnsTool::ResolveDnsName)) = dns_tool {
+            degraded.push(DegradedFeature {
+                feature: "DNSSEC validation".to_string(),
+                missing: vec!["dig".to_string(), "kdig".to_string(), "drill".to_string()],
+                impact: format!(
+                    "{} can't return DNSSEC records, so signed zones look unsigned",
+                    tool.name()
+                ),
+            });
+        }
+
+        EnvironmentReport {
+            os: format!("{} {}", env::consts::OS, env::consts::ARCH),
+            dns_tool: dns_tool.map(|tool| tool.name().to_string()),
+            ready,
+            tools,
+            degraded,
+            checked_at: Utc::now(),
+        }
+    }
+
+    // The first executable called name on the PATH
+    pub fn find(&self, name: &str) -> Option<PathBuf> {
+        let path = self.path.clone().or_else(|| env::var_os("PATH"))?;
+        let file = format!("{}{}", name, env::consts::EXE_SUFFIX);
+        env::split_paths(&path)
+            .map(|dir| dir.join(&file))
+            .find(|candidate| is_executable(candidate))
+    }
+}
+
+impl Default for EnvironmentAdapter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(unix)]
+fn is_executable(path: &Path) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+    path.metadata()
+        .map(|m| m.is_file() && m.permissions().mode() & 0o111 != 0)
+        .unwrap_or(false)
+}
+
+#[cfg(not(unix))]
+fn is_executable(path: &Path) -> bool {
+    path.is_file()
+}
+
+// First line of the tool's version output; dig and drill print theirs to stderr
+async fn version(path: &Path, args: &[&str]) -> Option<String> {
+    let output = tokio::time::timeout(VERSION_TIMEOUT, Command::new(path).args(args).output())
+        .await
+        .ok()?
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    first_line(&String::from_utf8_lossy(&output.stdout))
+        .or_else(|| first_line(&String::from_utf8_lossy(&output.stderr)))
+}
+
+pub fn first_line(output: &str) -> Option<String> {
+    output
+        .lines()
+        .map(str::trim)
+        .find(|line| !line.is_empty())
+        .map(str::to_string)
+}
+
+#[cfg(test)]
+#[path = "environment_test.rs"]
+mod tests;
@@ -0,0 +1,86 @@
+use super::{first_line, EnvironmentAdapter};
+use crate::models::environment::ToolRole;
+use std::fs;
+use std::path::PathBuf;
+
+// A directory holding a fake tool that prints its version to stderr, like dig
+fn fake_tools(name: &str, tool: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("d-doctor-{}-{}", name, std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    let path = dir.join(tool);
+    fs::write(&path, "#!/bin/sh\necho 'DiG 9.18.28' >&2\n").unwrap();
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o755)).unwrap();
+    }
+    // Not executable, so not a tool
+    fs::write(dir.join("curl"), "").unwrap();
+    dir
+}
+
+#[test]
+fn test_first_line() {
+    assert_eq!(
+        first_line("\n  curl 8.7.1 (x86_64-apple-darwin23.0)\nRelease-Date: 2024-03-27\n"),
+        Some("curl 8.7.1 (x86_64-apple-darwin23.0)".to_string())
+    );
+    assert_eq!(first_line("  \n"), None);
+}
+
+#[cfg(unix)]
+#[tokio::test]
+async fn test_check_finds_tools_on_path() {
+    let dir = fake_tools("found", "dig");
+    let report = EnvironmentAdapter::new().with_path(&dir).check().await;
+
+    let dig = report.tools.iter().find(|t| t.name == "dig").unwrap();
+    assert!(dig.found);
+    assert_eq!(dig.role, ToolRole::Required);
+    assert_eq!(dig.version.as_deref(), Some("DiG 9.18.28"));
+    assert_eq!(
+        dig.path.as_deref(),
+        Some(dir.join("dig").display().to_string().as_str())
+    );
+
+    let curl = report.tools.iter().find(|t| t.name == "curl").unwrap();
+    assert!(!curl.found);
+    assert!(report.ready);
+    assert_eq!(report.dns_tool.as_deref(), Some("dig"));
+    assert!(report.degraded.is_empty());
+
+    fs::remove_dir_all(dir).unwrap();
+}
+
+#[cfg(unix)]
+#[tokio::test]
+async fn test_check_with_host_instead_of_dig() {
+    let dir = fake_tools("host", "host");
+    let report = EnvironmentAdapter::new().with_path(&dir).check().await;
+
+    assert!(report.ready);
+    assert_eq!(report.dns_tool.as_deref(), Some("host"));
+    let host = report.tools.iter().find(|t| t.name == "host").unwrap();
+    assert_eq!(host.role, ToolRole::Fallback);
+    assert_eq!(report.degraded.len(), 1);
+    assert_eq!(report.degraded[0].feature, "DNSSEC validation");
+
+    fs::remove_dir_all(dir).unwrap();
+}
+
+#[tokio::test]
+async fn test_check_reports_degraded_features() {
+    let report = EnvironmentAdapter::new().with_path("").check().await;
+
+    assert!(!report.ready);
+    assert_eq!(report.dns_tool, None);
+    assert!(report.tools.iter().all(|t| !t.found && t.version.is_none()));
+    assert!(report
+        .degraded
+        .iter()
+        .any(|d| d.feature == "DNSSEC validation"));
+    assert!(report
+        .degraded
+        .iter()
+        .all(|d| d.missing == ["dig", "kdig", "drill", "host"]));
+}
@@ -0,0 +1,630 @@
+use crate::models::certificate::{CertificateWarningKind, TlsInfo};
+use crate::models::dns::{DnssecValidation, RecordEnumeration};
+use crate::models::email::EmailConfig;
+use crate::models::http::header_value;
+use crate::models::report::{
+    CategoryScore, DomainReport, Finding, HealthScore, HttpCheck, Severity,
+};
+use crate::models::whois::{StatusSeverity, WhoisInfo};
+use crate::state;
+use std::cmp::Reverse;
+use tauri::AppHandle;
+
+// Graded areas of a report, in display order, with the section each comes from
+const CATEGORIES: [(&str, &str); 6] = [
+    ("dns", "dns"),
+    ("dnssec", "dnssec"),
+    ("tls", "certificate"),
+    ("http", "http"),
+    ("email", "email"),
+    ("registration", "whois"),
+];
+
+// Points a category loses per finding
+const CRITICAL_PENALTY: u32 = 40;
+const WARNING_PENALTY: u32 = 15;
+const INFO_PENALTY: u32 = 2;
+
+// A critical finding anywhere keeps the overall grade at D or below
+const CRITICAL_SCORE_CAP: u8 = 69;
+
+#[derive(Clone)]
+pub struct HealthAdapter {
+    app_handle: Option<AppHandle>,
+}
+
+impl HealthAdapter {
+    pub fn new() -> Self {
+        HealthAdapter { app_handle: None }
+    }
+
+    pub fn with_app_handle(app_handle: AppHandle) -> Self {
+        HealthAdapter {
+            app_handle: Some(app_handle),
+        }
+    }
+
+    // Grade every section the report has, and list what to fix
+    pub fn score(&self, report: &DomainReport) -> HealthScore {
+        let domain = report.domain.as_str();
+        let mut findings = Vec::new();
+        if let Some(dns) = &report.dns {
+            findings.extend(self.dns_findings(domain, dns));
+        }
+        if let Some(dnssec) = &report.dnssec {
+            findings.extend(self.dnssec_findings(domain, dnssec));
+        }
+        if let Some(tls) = &report.certificate {
+            findings.extend(self.tls_findings(domain, tls));
+        } else if report.errors.iter().any(|e| e.section == "certificate") {
+            findings.push(finding(
+                "tls",
+                Severity::Warning,
+                "tls_unavailable",
+                format!("{} doesn't complete a TLS handshake on port 443", domain),
+                "Browsers can't open the site over HTTPS and show a connection error instead",
+                "Install a certificate for the domain, e.g. a free one from Let's Encrypt",
+            ));
+        }
+        if !report.http.is_empty() {
+            findings.extend(self.http_findings(domain, &report.http));
+        }
+        if let Some(email) = &report.email {
+            findings.extend(self.email_findings(domain, email));
+        }
+        if let Some(whois) = &report.whois {
+            findings.extend(self.registration_findings(domain, whois));
+        }
+        findings.sort_by_key(|f| Reverse(f.severity));
+
+        let mut categories = Vec::new();
+        for (category, section) in CATEGORIES {
+            let graded = match section {
+                "dns" => report.dns.is_some(),
+                "dnssec" => report.dnssec.is_some(),
+                "certificate" => {
+                    report.certificate.is_some()
+                        || report.errors.iter().any(|e| e.section == "certificate")
+                }
+                "http" => !report.http.is_empty(),
+                "email" => report.email.is_some(),
+                _ => report.whois.is_some(),
+            };
+            let score = graded.then(|| {
+                let penalty: u32 = findings
+                    .iter()
+                    .filter(|f| f.category == category)
+                    .map(|f| match f.severity {
+                        Severity::Critical => CRITICAL_PENALTY,
+                        Severity::Warning => WARNING_PENALTY,
+                        Severity::Info => INFO_PENALTY,
+                    })
+                    .sum();
+                100u32.saturating_sub(penalty) as u8
+            });
+            categories.push(CategoryScore {
+                category: category.to_string(),
+                score,
+                grade: score.map(|s| self.grade(s).to_string()),
+            });
+        }
+
+        let graded: Vec<u32> = categories
+            .iter()
+            .filter_map(|c| c.score.map(u32::from))
+            .collect();
+        let mut score = match graded.len() {
+            0 => 0,
+            count => (graded.iter().sum::<u32>() / count as u32) as u8,
+        };
+        if findings.iter().any(|f| f.severity == Severity::Critical) {
+            score = score.min(CRITICAL_SCORE_CAP);
+        }
+
+        HealthScore {
+            score,
+            grade: self.grade(score).to_string(),
+            categories,
+            findings,
+        }
+    }
+
+    pub fn grade(&self, score: u8) -> &'static str {
+        match score {
+            90.. => "A",
+            80..=89 => "B",
+            70..=79 => "C",
+            60..=69 => "D",
+            _ => "F",
+        }
+    }
+
+    fn dns_findings(&self, domain: &str, dns: &RecordEnumeration) -> Vec<Finding> {
+        let records = |record_type: &str| -> Vec<&str> {
+            dns.results
+                .iter()
+                .flat_map(|result| result.records.iter())
+                .filter(|r| r.record_type == record_type)
+                .map(|r| r.value.as_str())
+                .collect()
+        };
+        let mut findings = Vec::new();
+
+        let failed: Vec<&str> = dns
+            .results
+            .iter()
+            .filter(|r| r.error.is_some())
+            .map(|r| r.record_type.as_str())
+            .collect();
+        if !failed.is_empty() {
+            findings.push(finding(
+                "dns",
+                Severity::Warning,
+                "dns_query_failed",
+                format!("{} lookups for {} failed", failed.join(", "), domain),
+                "Timeouts or SERVFAIL answers make the domain intermittently unreachable",
+                "Check that every nameserver answers for the zone, e.g. with compare_nameservers",
+            ));
+        }
+
+        let cname = !records("CNAME").is_empty();
+        if records("A").is_empty() && records("AAAA").is_empty() && !cname {
+            findings.push(finding(
+                "dns",
+                Severity::Warning,
+                "no_address",
+                format!("{} has no A or AAAA record", domain),
+                "Browsers and other clients can't connect to the bare domain",
+                "Add an A record (and AAAA for IPv6), or an ALIAS/ANAME record if your DNS provider supports one",
+            ));
+        } else if records("AAAA").is_empty() && !cname {
+            findings.push(finding(
+                "dns",
+                Severity::Info,
+                "no_ipv6",
+                format!("{} has no AAAA record", domain),
+                "IPv6-only clients, common on mobile networks, go through slower NAT64 gateways",
+                "Add AAAA records if your hosting provider gives you IPv6 addresses",
+            ));
+        }
+
+        let nameservers = records("NS");
+        if nameservers.len() == 1 {
+            findings.push(finding(
+                "dns",
+                Severity::Warning,
+                "single_nameserver",
+                format!("{} has a single nameserver, {}", domain, nameservers[0]),
+                "When it's down the whole domain stops resolving; RFC 1034 asks for at least two",
+                "Add a second nameserver, ideally on a different network",
+            ));
+        }
+
+        if records("CAA").is_empty() {
+            findings.push(finding(
+                "dns",
+                Severity::Info,
+                "no_caa",
+                format!("{} publishes no CAA record", domain),
+                "Any certificate authority may issue certificates for the domain",
+                "Publish CAA records naming the CAs you use, e.g. 0 issue \"letsencrypt.org\"",
+            ));
+        }
+        findings
+    }
+
+    fn dnssec_findings(&self, domain: &str, dnssec: &DnssecValidation) -> Vec<Finding> {
+        let mut findings = Vec::new();
+        // A signed zone whose parent has no DS record for it
+        let missing_ds = dnssec.chain.windows(2).find(|pair| {
+            pair[0].status == "SECURE"
+                && pair[0].ds_records.is_empty()
+                && !pair[1].dnskey_records.is_empty()
+        });
+
+        match dnssec.status.as_str() {
+            "BOGUS" => findings.push(finding(
+                "dnssec",
+                Severity::Critical,
+                "dnssec_bogus",
+                format!("DNSSEC validation fails for {}", domain),
+                "Validating resolvers, used by about a third of users, answer SERVFAIL and the domain doesn't resolve for them",
+                "Replace the DS record at your registrar with one matching the zone's current key signing key, or remove the DS record to turn DNSSEC off",
+            )),
+            "INSECURE" => match missing_ds {
+                Some(pair) => findings.push(finding(
+                    "dnssec",
+                    Severity::Warning,
+                    "dnssec_missing_ds",
+                    format!(
+                        "{} is signed but {} has no DS record for it",
+                        pair[1].zone_name, pair[0].zone_name
+                    ),
+                    "Without the DS record resolvers can't link the zone's keys to the chain of trust, so the signatures protect nothing",
+                    "Add the DS record for the zone's key signing key at your registrar",
+                )),
+                None => findings.push(finding(
+                    "dnssec",
+                    Severity::Info,
+                    "dnssec_unsigned",
+                    format!("{} isn't signed with DNSSEC", domain),
+                    "Resolvers can't detect forged answers for the domain",
+                    "Enable DNSSEC signing at your DNS provider, then add the DS record it gives you at your registrar",
+                )),
+            },
+            "INDETERMINATE" => findings.push(finding(
+                "dnssec",
+                Severity::Info,
+                "dnssec_indeterminate",
+                format!("The DNSSEC chain for {} couldn't be completed", domain),
+                "Some DNSKEY or DS lookups failed, so the domain's status is unknown",
+                "Run the DNSSEC check again; if it keeps failing, check that every nameserver answers DNSKEY queries",
+            )),
+            _ => {}
+        }
+
+        if let Some(rollover) = &dnssec.rollover {
+            if !rollover.missing_from_parent.is_empty() {
+                findings.push(finding(
+                    "dnssec",
+                    Severity::Warning,
+                    "dnssec_rollover_pending",
+                    format!(
+                        "The zone requests DS records its parent doesn't have: {}",
+                        rollover.missing_from_parent.join(", ")
+                    ),
+                    "A key rollover is waiting on the parent; removing the old key before it completes breaks resolution",
+                    "Add the requested DS records at your registrar, or wait for it to pick up the CDS records",
+                ));
+            }
+        }
+        findings
+    }
+
+    fn tls_findings(&self, domain: &str, tls: &TlsInfo) -> Vec<Finding> {
+        let settings = state::settings_for(self.app_handle.as_ref());
+        let warning_days = settings
+            .cert_expiry_warning_days
+            .iter()
+            .max()
+            .copied()
+            .unwrap_or(30) as i64;
+        let chain = &tls.certificate_chain;
+        let mut findings = Vec::new();
+
+        if !chain.is_valid {
+            findings.push(finding(
+                "tls",
+                Severity::Critical,
+                "certificate_invalid",
+                format!(
+                    "The certificate for {} isn't trusted: {}",
+                    domain,
+                    chain.validation_errors.join("; ")
+                ),
+                "Browsers block the site with a full-page security warning",
+                "Serve a certificate from a publicly trusted CA that covers the domain, along with its intermediate certificates",
+            ));
+        }
+
+        let leaf_days = chain.certificates.first().and_then(|c| c.days_until_expiry);
+        match leaf_days {
+            Some(days) if days < 0 => findings.push(finding(
+                "tls",
+                Severity::Critical,
+                "certificate_expired",
+                format!("The certificate for {} expired {} days ago", domain, -days),
+                "Browsers block the site and API clients refuse to connect",
+                "Renew the certificate now and check why automatic renewal didn't run",
+            )),
+            Some(days) if days <= warning_days => findings.push(finding(
+                "tls",
+                Severity::Warning,
+                "certificate_expiring",
+                format!("The certificate for {} expires in {} days", domain, days),
+                "Once it expires browsers block the site",
+                format!(
+                    "Renew the certificate expiring in {} days, and check that automatic renewal is working",
+                    days
+                ),
+            )),
+            _ => {}
+        }
+
+        if tls
+            .certificate_warnings
+            .iter()
+            .any(|w| w.kind == CertificateWarningKind::WeakSignature)
+        {
+            findings.push(finding(
+                "tls",
+                Severity::Warning,
+                "weak_signature",
+                "A certificate in the chain is signed with SHA-1 or MD5".to_string(),
+                "Browsers no longer trust these signatures",
+                "Reissue the certificate; any current CA signs with SHA-256 or better",
+            ));
+        }
+        findings
+    }
+
+    fn http_findings(&self, domain: &str, checks: &[HttpCheck]) -> Vec<Finding> {
+        let mut findings = Vec::new();
+        let responses: Vec<_> = checks.iter().filter_map(|c| c.response.as_ref()).collect();
+        if responses.is_empty() {
+            findings.push(finding(
+                "http",
+                Severity::Warning,
+                "http_unreachable",
+                format!("No web server answered for {}", domain),
+                "Visitors get a connection error; fine only if the domain isn't meant to serve a website",
+                "Point the domain's A/AAAA records at a web server, or redirect it to your main site",
+            ));
+            return findings;
+        }
+
+        let plain = format!("http://{}", domain);
+        let secure = format!("https://{}", domain);
+        let find = |url: &str| checks.iter().find(|c| c.url.eq_ignore_ascii_case(url));
+
+        if let Some(response) = find(&plain).and_then(|c| c.response.as_ref()) {
+            if !response.final_url.starts_with("https://") {
+                findings.push(finding(
+                    "http",
+                    Severity::Warning,
+                    "no_https_redirect",
+                    format!("{} isn't redirected to HTTPS", plain),
+                    "Visitors who type the bare domain stay on an unencrypted connection",
+                    "Redirect every http:// request to https:// with a 301",
+                ));
+            }
+        }
+
+        let secure_check = find(&secure);
+        if let Some(response) = secure_check.and_then(|c| c.response.as_ref()) {
+            if header_value(&response.headers, "strict-transport-security").is_none() {
+                findings.push(finding(
+                    "http",
+                    Severity::Info,
+                    "no_hsts",
+                    format!("{} doesn't send Strict-Transport-Security", secure),
+                    "Browsers keep trying http:// first, where the connection can be downgraded",
+                    "Send Strict-Transport-Security: max-age=31536000; includeSubDomains",
+                ));
+            }
+        } else if let Some(error) = secure_check.and_then(|c| c.error.as_ref()) {
+            findings.push(finding(
+                "http",
+                Severity::Warning,
+                "https_failed",
+                format!("{} failed: {}", secure, error),
+                "The site is only reachable unencrypted",
+                "Serve the site over HTTPS with a valid certificate",
+            ));
+        }
+
+        for response in &responses {
+            if response.status_code >= 500 {
+                findings.push(finding(
+                    "http",
+                    Severity::Warning,
+                    "http_server_error",
+                    format!("{} answers {}", response.url, response.status_code),
+                    "The server fails requests it should handle",
+                    "Check the web server and application logs for the failing requests",
+                ));
+            }
+        }
+        findings
+    }
+
+    fn email_findings(&self, domain: &str, email: &EmailConfig) -> Vec<Finding> {
+        let mut findings = Vec::new();
+        if email.mx_records.is_empty() && !email.null_mx {
+            findings.push(finding(
+                "email",
+                Severity::Info,
+                "no_mx",
+                format!("{} has no MX record", domain),
+                "Senders fall back to delivering to the domain's own address",
+                "Publish MX records for your mail provider, or a null MX (0 .) if the domain receives no mail",
+            ));
+        } else if !email.null_mx && !email.encrypted {
+            findings.push(finding(
+                "email",
+                Severity::Warning,
+                "mx_unencrypted",
+                "Not every MX host offers STARTTLS with a valid certificate".to_string(),
+                "Mail to the domain can travel unencrypted or be intercepted",
+                "Enable STARTTLS with a trusted certificate matching each MX hostname",
+            ));
+        }
+
+        match &email.spf {
+            None => findings.push(finding(
+                "email",
+                Severity::Warning,
+                "no_spf",
+                format!("{} publishes no SPF record", domain),
+                "Receivers can't tell which servers may send as the domain, so spoofed mail is easier to deliver",
+                "Publish a TXT record v=spf1 listing your senders and ending in -all (v=spf1 -all if the domain sends no mail)",
+            )),
+            Some(spf) => {
+                if let Some(error) = &spf.perm_error {
+                    findings.push(finding(
+                        "email",
+                        Severity::Critical,
+                        "spf_perm_error",
+                        format!("The SPF record is broken: {}", error),
+                        "Receivers treat the record as a permanent error and SPF fails for all mail",
+                        "Fix the record; flatten includes if it goes over the 10-lookup limit",
+                    ));
+                }
+                match spf.all.as_deref() {
+                    Some("+all") | Some("all") => findings.push(finding(
+                        "email",
+                        Severity::Critical,
+                        "spf_pass_all",
+                        "The SPF record ends in +all".to_string(),
+                        "Every server on the internet is allowed to send as the domain",
+                        "End the record in -all or ~all",
+                    )),
+                    Some("?all") | None => findings.push(finding(
+                        "email",
+                        Severity::Warning,
+                        "spf_neutral",
+                        "The SPF record doesn't reject unlisted senders".to_string(),
+                        "Mail from servers that aren't listed isn't treated as suspicious",
+                        "End the record in -all (or ~all while you confirm the list is complete)",
+                    )),
+                    _ => {}
+                }
+            }
+        }
+
+        match &email.dmarc {
+            None => findings.push(finding(
+                "email",
+                Severity::Warning,
+                "no_dmarc",
+                format!("{} publishes no DMARC record", domain),
+                "Receivers apply their own policy to mail failing SPF and DKIM, and you get no reports about who sends as the domain",
+                format!(
+                    "Publish _dmarc.{} TXT \"v=DMARC1; p=none; rua=mailto:dmarc@{}\", then tighten to p=quarantine and p=reject",
+                    domain, domain
+                ),
+            )),
+            Some(dmarc) => {
+                if dmarc.policy.as_deref() == Some("none") {
+                    findings.push(finding(
+                        "email",
+                        Severity::Info,
+                        "dmarc_monitoring",
+                        "The DMARC policy is p=none".to_string(),
+                        "Spoofed mail is reported but still delivered",
+                        "Move to p=quarantine, then p=reject once the reports only show your own senders",
+                    ));
+                }
+                for error in &dmarc.errors {
+                    findings.push(finding(
+                        "email",
+                        Severity::Warning,
+                        "dmarc_error",
+                        error.clone(),
+                        "Receivers ignore the invalid part of the record",
+                        "Correct the _dmarc record; see RFC 7489 section 6.3 for the tag syntax",
+                    ));
+                }
+            }
+        }
+
+        for provider in &email.providers {
+            for missing in &provider.missing {
+                findings.push(finding(
+                    "email",
+                    Severity::Warning,
+                    "provider_record_missing",
+                    format!("{} asks for {}, which wasn't found", provider.name, missing),
+                    "Mail sent through the provider may fail SPF or DKIM",
+                    format!(
+                        "Add the {} record from {}'s setup instructions",
+                        missing, provider.name
+                    ),
+                ));
+            }
+            for outdated in &provider.outdated {
+                findings.push(finding(
+                    "email",
+                    Severity::Info,
+                    "provider_record_outdated",
+                    format!("{} uses an outdated record: {}", provider.name, outdated),
+                    "The provider may stop supporting the old record",
+                    format!("Replace {}", outdated),
+                ));
+            }
+        }
+        findings
+    }
+
+    fn registration_findings(&self, domain: &str, whois: &WhoisInfo) -> Vec<Finding> {
+        let settings = state::settings_for(self.app_handle.as_ref());
+        let mut findings = Vec::new();
+        match whois.days_until_expiry {
+            Some(days) if days < 0 => findings.push(finding(
+                "registration",
+                Severity::Critical,
+                "domain_expired",
+                format!("{} expired {} days ago", domain, -days),
+                "The registry will suspend the domain and release it for anyone to register",
+                "Renew the domain at your registrar immediately",
+            )),
+            Some(days) if days <= settings.domain_expiry_warning_days as i64 => {
+                findings.push(finding(
+                    "registration",
+                    Severity::Warning,
+                    "domain_expiring",
+                    format!("{} expires in {} days", domain, days),
+                    "If it lapses the website and email stop working",
+                    format!(
+                        "Renew the domain expiring in {} days, and turn on auto-renew at your registrar",
+                        days
+                    ),
+                ))
+            }
+            _ => {}
+        }
+
+        for status in &whois.status {
+            if status.severity == StatusSeverity::Critical {
+                findings.push(finding(
+                    "registration",
+                    Severity::Critical,
+                    "registry_status",
+                    format!("{} has status {}", domain, status.code),
+                    status.description.clone(),
+                    format!(
+                        "Contact your registrar to resolve the {} status",
+                        status.code
+                    ),
+                ));
+            }
+        }
+
+        if !whois.status.is_empty()
+            && !whois.has_status("clientTransferProhibited")
+            && !whois.has_status("serverTransferProhibited")
+        {
+            findings.push(finding(
+                "registration",
+                Severity::Info,
+                "transfer_unlocked",
+                format!("{} has no transfer lock", domain),
+                "A compromised registrar account or a social-engineered request can move the domain away",
+                "Turn on the transfer lock (clientTransferProhibited) at your registrar",
+            ));
+        }
+        findings
+    }
+}
+
+fn finding(
+    category: &str,
+    severity: Severity,
+    code: &str,
+    message: String,
+    explanation: impl Into<String>,
+    fix: impl Into<String>,
+) -> Finding {
+    Finding {
+        category: category.to_string(),
+        severity,
+        code: code.to_string(),
+        message,
+        explanation: explanation.into(),
+        fix: fix.into(),
+    }
+}
+
+#[cfg(test)]
+#[path = "health_test.rs"]
+mod tests;
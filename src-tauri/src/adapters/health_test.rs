@@ -0,0 +1,243 @@
+use super::super::email::EmailAdapter;
+use super::super::test_support::{http_check, records};
+use super::HealthAdapter;
+use crate::models::dns::{DnskeyRecord, DnssecValidation, RecordEnumeration, ZoneData};
+use crate::models::email::{DkimLookup, EmailConfig, MxHost, MxTls, SpfEvaluation};
+use crate::models::report::{DomainReport, SectionError, Severity};
+use crate::models::whois::{EppStatus, WhoisInfo};
+use serde_json::json;
+
+// Every section present and nothing to fix
+fn healthy_report() -> DomainReport {
+    let certificate = json!({
+        "subject": {}, "issuer": {}, "serial_number": "01", "version": 3,
+        "not_before": "", "not_after": "", "days_until_expiry": 80,
+        "subject_alternative_names": ["example.com"], "public_key_algorithm": "EC",
+        "signature_algorithm": "ecdsa-with-SHA256", "fingerprint_sha256": "",
+    });
+    let mut whois: WhoisInfo = serde_json::from_value(json!({
+        "domain": "example.com", "registrar": null, "creation_date": null,
+        "expiration_date": null, "updated_date": null, "nameservers": [], "status": [],
+        "dnssec": null, "days_until_expiry": 200, "raw_output": "",
+    }))
+    .unwrap();
+    whois.status = vec![EppStatus::from_code("clientTransferProhibited")];
+
+    DomainReport {
+        domain: "example.com".to_string(),
+        started_at: chrono::Utc::now(),
+        total_time: 1.0,
+        dns: Some(RecordEnumeration {
+            domain: "example.com".to_string(),
+            results: vec![
+                records("A", &[("192.0.2.1", 300)]),
+                records("AAAA", &[("2001:db8::1", 300)]),
+                records(
+                    "NS",
+                    &[("ns1.example.net.", 300), ("ns2.example.org.", 300)],
+                ),
+                records("CAA", &[("0 issue \"letsencrypt.org\"", 300)]),
+            ],
+            hosting: None,
+            total_time: 0.1,
+        }),
+        dnssec: Some(DnssecValidation {
+            status: "SECURE".to_string(),
+            chain: vec![],
+            warnings: vec![],
+            rollover: None,
+        }),
+        whois: Some(whois),
+        certificate: Some(
+            serde_json::from_value(json!({
+                "host": "example.com",
+                "port": 443,
+                "certificate_chain": {
+                    "certificates": [certificate],
+                    "is_valid": true,
+                    "validation_errors": [],
+                },
+            }))
+            .unwrap(),
+        ),
+        http: vec![
+            http_check("http://example.com", &[], "https://example.com/", json!([])),
+            http_check(
+                "https://example.com",
+                &[],
+                "https://example.com/",
+                json!([["strict-transport-security", "max-age=31536000"]]),
+            ),
+        ],
+        email: Some(EmailConfig {
+            domain: "example.com".to_string(),
+            mx_records: vec![MxHost {
+                priority: 10,
+                hostname: "mx.example.com".to_string(),
+                ips: vec!["192.0.2.25".to_string()],
+                cname: None,
+                tls: Some(MxTls::default()),
+                dane: None,
+            }],
+            null_mx: false,
+            implicit_mx: None,
+            encrypted: true,
+            spf: Some(SpfEvaluation {
+                all: Some("-all".to_string()),
+                ..Default::default()
+            }),
+            dkim: DkimLookup::default(),
+            dmarc: Some(EmailAdapter::new().parse_dmarc("example.com", "v=DMARC1; p=reject")),
+            providers: vec![],
+            warnings: vec![],
+        }),
+        errors: vec![],
+        health: None,
+        scan_id: None,
+    }
+}
+
+#[test]
+fn test_score_healthy_report() {
+    let health = HealthAdapter::new().score(&healthy_report());
+    assert!(health.findings.is_empty(), "{:?}", health.findings);
+    assert_eq!(health.score, 100);
+    assert_eq!(health.grade, "A");
+    let categories: Vec<&str> = health
+        .categories
+        .iter()
+        .map(|c| c.category.as_str())
+        .collect();
+    assert_eq!(
+        categories,
+        vec!["dns", "dnssec", "tls", "http", "email", "registration"]
+    );
+}
+
+#[test]
+fn test_score_findings() {
+    let mut report = healthy_report();
+    report
+        .certificate
+        .as_mut()
+        .unwrap()
+        .certificate_chain
+        .certificates[0]
+        .days_until_expiry = Some(9);
+    report.email.as_mut().unwrap().dmarc = None;
+    report.http.truncate(1);
+    report.http[0] = http_check("http://example.com", &[], "http://example.com/", json!([]));
+
+    let health = HealthAdapter::new().score(&report);
+    let codes: Vec<&str> = health.findings.iter().map(|f| f.code.as_str()).collect();
+    assert!(codes.contains(&"certificate_expiring"));
+    assert!(codes.contains(&"no_dmarc"));
+    assert!(codes.contains(&"no_https_redirect"));
+
+    let expiring = health
+        .findings
+        .iter()
+        .find(|f| f.code == "certificate_expiring")
+        .unwrap();
+    assert_eq!(expiring.severity, Severity::Warning);
+    assert_eq!(expiring.category, "tls");
+    assert!(expiring
+        .fix
+        .starts_with("Renew the certificate expiring in 9 days"));
+
+    let dmarc = health
+        .findings
+        .iter()
+        .find(|f| f.code == "no_dmarc")
+        .unwrap();
+    assert!(dmarc.fix.contains("_dmarc.example.com"));
+
+    let tls = health
+        .categories
+        .iter()
+        .find(|c| c.category == "tls")
+        .unwrap();
+    assert_eq!(tls.score, Some(85));
+    assert_eq!(tls.grade.as_deref(), Some("B"));
+    assert!(health.score < 100);
+}
+
+#[test]
+fn test_score_missing_ds() {
+    let mut report = healthy_report();
+    let dnskey = DnskeyRecord {
+        flags: 257,
+        protocol: 3,
+        algorithm: 13,
+        algorithm_name: "ECDSAP256SHA256".to_string(),
+        public_key: String::new(),
+        key_tag: 12345,
+        key_size: Some(256),
+    };
+    report.dnssec = Some(DnssecValidation {
+        status: "INSECURE".to_string(),
+        chain: vec![
+            ZoneData {
+                zone_name: "com".to_string(),
+                status: "SECURE".to_string(),
+                ..Default::default()
+            },
+            ZoneData {
+                zone_name: "example.com".to_string(),
+                dnskey_records: vec![dnskey],
+                ..Default::default()
+            },
+        ],
+        warnings: vec![],
+        rollover: None,
+    });
+
+    let health = HealthAdapter::new().score(&report);
+    assert_eq!(health.findings.len(), 1);
+    assert_eq!(health.findings[0].code, "dnssec_missing_ds");
+    assert_eq!(
+        health.findings[0].message,
+        "example.com is signed but com has no DS record for it"
+    );
+    assert!(health.findings[0].fix.contains("DS record"));
+}
+
+#[test]
+fn test_score_critical_caps_grade() {
+    let mut report = healthy_report();
+    report
+        .whois
+        .as_mut()
+        .unwrap()
+        .status
+        .push(EppStatus::from_code("serverHold"));
+    // A section that failed isn't graded
+    report.email = None;
+    report.errors.push(SectionError {
+        section: "email".to_string(),
+        error: "timed out".to_string(),
+    });
+
+    let health = HealthAdapter::new().score(&report);
+    assert_eq!(health.findings[0].severity, Severity::Critical);
+    assert_eq!(health.findings[0].code, "registry_status");
+    assert_eq!(health.score, 69);
+    assert_eq!(health.grade, "D");
+    let email = health
+        .categories
+        .iter()
+        .find(|c| c.category == "email")
+        .unwrap();
+    assert_eq!(email.score, None);
+}
+
+#[test]
+fn test_grade() {
+    let adapter = HealthAdapter::new();
+    assert_eq!(adapter.grade(100), "A");
+    assert_eq!(adapter.grade(90), "A");
+    assert_eq!(adapter.grade(89), "B");
+    assert_eq!(adapter.grade(70), "C");
+    assert_eq!(adapter.grade(60), "D");
+    assert_eq!(adapter.grade(59), "F");
+}
@@ -1,210 +1,1608 @@
+use crate::adapters::backend::{BackendFuture, Backends, HttpBackend};
 use crate::models::command_log::CommandLog;
-use crate::models::http::{HttpRedirect, HttpResponse};
-use std::collections::HashMap;
-use std::process::Command;
-use std::time::Instant;
-use tauri::{AppHandle, Emitter};
+use crate::models::http::{
+    header_value, header_values, CacheAnalysis, CanonicalReport, CanonicalVariant, HstsPolicy,
+    HstsReport, HttpBody, HttpRedirect, HttpRequest, HttpResponse, HttpTiming, RobotsGroup,
+    RobotsTxt, SecurityTxt, Sitemap, WellKnownReport,
+};
+use crate::models::settings::Settings;
+use crate::state;
+use crate::state::command_logs;
+use crate::state::rate_limiter::{self, Target};
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use chrono::{DateTime, Utc};
+use http_body_util::{BodyExt, Full};
+use hyper::body::{Bytes, Incoming};
+use hyper::client::conn::http1;
+use hyper::header::{HeaderName, HeaderValue};
+use hyper::{Method, Request};
+use hyper_util::rt::TokioIo;
+use percent_encoding::percent_decode_str;
+use regex::Regex;
+use rustls::crypto::ring;
+use rustls::pki_types::ServerName;
+use rustls::{ClientConfig, RootCertStore};
+use std::collections::HashSet;
+use std::net::{IpAddr, SocketAddr};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tauri::AppHandle;
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+use tokio::net::{lookup_host, TcpStream};
+use tokio_rustls::TlsConnector;
+use tracing::{instrument, Instrument};
+use url::{Position, Url};
 
+const MAX_REDIRECTS: usize = 20;
+
+const ALLOWED_METHODS: [&str; 7] = ["HEAD", "GET", "POST", "PUT", "PATCH", "DELETE", "OPTIONS"];
+
+// Second-level labels ccTLD registries sell names under (example.co.uk)
+const COUNTRY_SECOND_LEVELS: [&str; 11] = [
+    "co", "com", "net", "org", "gov", "edu", "ac", "ltd", "or", "ne", "go",
+];
+
+// hstspreload.org submission requirements
+const HSTS_PRELOAD_MIN_MAX_AGE: u64 = 31_536_000; // One year
+const HSTS_PRELOAD_STATUS_URL: &str = "https://hstspreload.org/api/v2/status?domain=";
+
+// Sitemaps fetched per check, and <loc> entries kept from each as a sample
+const MAX_SITEMAPS: usize = 5;
+const SITEMAP_SAMPLE_SIZE: usize = 20;
+
+// Upper bound for HttpRequest::max_body_kb
+const MAX_BODY_KB: u32 = 10 * 1024;
+
+const USER_AGENT: &str = concat!("d-dns-debugger/", env!("CARGO_PKG_VERSION"));
+
+// Statuses worth retrying, as with curl --retry
+const TRANSIENT_STATUSES: [u16; 6] = [408, 429, 500, 502, 503, 504];
+
+// One request and its response over a connection opened just for it
+pub struct Exchange {
+    pub status_code: u16,
+    pub headers: Vec<(String, String)>,
+    pub head: String, // Status line and headers as received
+    pub body: Vec<u8>,
+    pub truncated: bool, // The body stopped at Hop::body_limit
+    pub timing: HttpTiming,
+    pub ip: IpAddr,
+}
+
+// What is sent on each hop, and through which proxy; the URL changes as
+// redirects are followed
+pub struct Hop<'a> {
+    pub method: Method,
+    pub headers: Vec<(&'a str, &'a str)>,
+    pub body: Vec<u8>,
+    pub body_limit: Option<usize>, // Bytes of the response body to read; all when None
+    pub user_agent: String,        // Unless headers replace it
+    pub proxy: Option<Url>,
+}
+
+impl<'a> Hop<'a> {
+    // The User-Agent and proxy come from settings
+    fn new(
+        method: Method,
+        headers: Vec<(&'a str, &'a str)>,
+        body: Vec<u8>,
+        body_limit: Option<usize>,
+        settings: &Settings,
+    ) -> Self {
+        Hop {
+            method,
+            headers,
+            body,
+            body_limit,
+            user_agent: settings
+                .http_user_agent
+                .clone()
+                .unwrap_or_else(|| USER_AGENT.to_string()),
+            proxy: settings
+                .http_proxy
+                .as_deref()
+                .and_then(|proxy| Url::parse(proxy.trim()).ok()),
+        }
+    }
+}
+
+#[derive(Clone)]
 pub struct HttpAdapter {
     app_handle: Option<AppHandle>,
+    backends: Backends,
 }
 
 impl HttpAdapter {
     pub fn new() -> Self {
-        HttpAdapter { app_handle: None }
+        Self::from_app_handle(None)
     }
 
     pub fn with_app_handle(app_handle: AppHandle) -> Self {
+        Self::from_app_handle(Some(app_handle))
+    }
+
+    // For other adapters making HTTP requests on behalf of their own handle
+    pub fn from_app_handle(app_handle: Option<AppHandle>) -> Self {
         HttpAdapter {
-            app_handle: Some(app_handle),
+            backends: state::backends_for(app_handle.as_ref()),
+            app_handle,
         }
     }
 
+    // Send requests through these instead of the app's backends
+    pub fn with_backends(mut self, backends: Backends) -> Self {
+        self.backends = backends;
+        self
+    }
+
     fn emit_log(&self, log: CommandLog) {
-        if let Some(handle) = &self.app_handle {
-            let _ = handle.emit("command-log", log);
-        }
+        command_logs::record(self.app_handle.as_ref(), log);
     }
 
-    pub async fn fetch(&self, url: &str) -> Result<HttpResponse, String> {
-        if !self.is_curl_available() {
-            return Err("curl command not found. Please install curl.".to_string());
+    // Send the request and follow redirects one hop at a time. Like browsers,
+    // 301/302/303 turn anything but GET/HEAD into a GET without a body, while
+    // 307/308 repeat the request unchanged. Returning to a URL already requested
+    // with the same method is reported as a loop rather than retried to the limit.
+    #[instrument(skip_all, fields(url = %url, method = %request.method))]
+    pub async fn fetch(&self, url: &str, request: &HttpRequest) -> Result<HttpResponse, String> {
+        let settings = state::settings_for(self.app_handle.as_ref());
+        if let Some(kb) = request.max_body_kb.filter(|kb| *kb > MAX_BODY_KB) {
+            return Err(format!(
+                "Body limit of {} KB is too large (max: {} KB)",
+                kb, MAX_BODY_KB
+            ));
         }
-
+        let mut hop = Hop::new(
+            self.parse_method(&request.method)?,
+            request
+                .headers
+                .iter()
+                .map(|(name, value)| (name.as_str(), value.as_str()))
+                .collect(),
+            request.body.clone().unwrap_or_default().into_bytes(),
+            request.max_body_kb.map(|kb| kb as usize * 1024),
+            &settings,
+        );
         let mut redirects = Vec::new();
-        let mut current_url = url.to_string();
+        let mut current = self.parse_url(url)?;
+        let mut visited = HashSet::new();
         let mut total_time = 0.0;
-        let max_redirects = 20;
-        let mut redirect_count = 0;
 
         loop {
-            if redirect_count >= max_redirects {
-                return Err(format!("Too many redirects (max: {})", max_redirects));
-            }
-
-            let hop_start = Instant::now();
-
-            let args = vec![
-                "-I".to_string(),
-                "-s".to_string(),
-                "-S".to_string(),
-                current_url.clone(),
-            ];
-
-            let output = Command::new("curl")
-                .arg("-I") // Head request only
-                .arg("-s") // Silent
-                .arg("-S") // Show errors
-                .arg(&current_url)
-                .output()
-                .map_err(|e| format!("Failed to execute curl: {}", e))?;
-
-            let hop_time = hop_start.elapsed().as_secs_f64();
-            total_time += hop_time;
-
-            let exit_code = output.status.code().unwrap_or(-1);
-            let stdout = String::from_utf8_lossy(&output.stdout).to_string();
-            let stderr = String::from_utf8_lossy(&output.stderr).to_string();
-
-            // Emit command log for this hop
-            let log_output = if !stdout.is_empty() {
-                stdout.clone()
-            } else {
-                stderr.clone()
-            };
+            let domain = current.host_str().unwrap_or_default().to_string();
+            let exchange = self
+                .send_with_retries(
+                    &hop,
+                    &current,
+                    settings.http_timeout_secs,
+                    settings.http_retries,
+                    &domain,
+                )
+                .await?;
+            total_time += exchange.timing.total;
+            let status_code = exchange.status_code;
+            visited.insert((hop.method.clone(), current.clone()));
 
-            let domain = current_url
-                .trim_start_matches("http://")
-                .trim_start_matches("https://")
-                .split('/')
-                .next()
-                .unwrap_or(&current_url);
-
-            self.emit_log(CommandLog::new(
-                "curl".to_string(),
-                args,
-                log_output,
-                exit_code,
-                hop_time * 1000.0,
-                Some(domain.to_string()),
-            ));
-
-            // Check for HTTP response
-            let has_http_response = stdout.contains("HTTP/");
-            if !output.status.success() && !has_http_response {
-                return Err(format!("curl command failed: {}", stderr));
-            }
-
-            // Parse response
-            let (status_code, headers) = self.parse_response_headers(&stdout)?;
-
-            // Check if this is a redirect
-            if status_code >= 300 && status_code < 400 {
-                if let Some(location) = headers.get("location") {
-                    let next_url =
-                        if location.starts_with("http://") || location.starts_with("https://") {
-                            location.clone()
-                        } else if location.starts_with("/") {
-                            // Relative URL - construct absolute URL
-                            let base = if current_url.starts_with("https://") {
-                                let rest = current_url.trim_start_matches("https://");
-                                let domain = rest.split('/').next().unwrap_or("");
-                                format!("https://{}", domain)
-                            } else {
-                                let rest = current_url.trim_start_matches("http://");
-                                let domain = rest.split('/').next().unwrap_or("");
-                                format!("http://{}", domain)
-                            };
-                            format!("{}{}", base, location)
-                        } else {
-                            // Relative to current path
-                            let mut base = current_url
-                                .rsplitn(2, '/')
-                                .nth(1)
-                                .unwrap_or(&current_url)
-                                .to_string();
-                            if !base.ends_with('/') {
-                                base.push('/');
-                            }
-                            format!("{}{}", base, location)
-                        };
-
-                    redirects.push(HttpRedirect {
-                        from_url: current_url.clone(),
-                        to_url: next_url.clone(),
-                        status_code,
-                        response_time: hop_time,
-                    });
-
-                    current_url = next_url;
-                    redirect_count += 1;
-                } else {
-                    // 3xx without Location header - treat as final response
-                    return Ok(HttpResponse {
-                        url: url.to_string(),
-                        status_code,
-                        final_url: current_url,
-                        redirects,
-                        headers,
-                        response_time: total_time,
-                        raw_output: Some(stdout),
-                    });
+            // A 3xx without a usable Location is treated as the final response
+            let next = match header_value(&exchange.headers, "location") {
+                Some(location) if (300..400).contains(&status_code) => current.join(location).ok(),
+                _ => None,
+            };
+            let Some(next) = next else {
+                let mut warnings = self.redirect_warnings(url, &redirects);
+                let cache = self.analyze_cache(&exchange.headers);
+                let body = request
+                    .max_body_kb
+                    .map(|_| self.describe_body(&exchange, &current));
+                if let Some(target) = body.as_ref().and_then(|b| b.meta_refresh.as_ref()) {
+                    warnings.push(format!(
+                        "The page redirects to {} with a meta refresh, which header-only checks miss",
+                        target
+                    ));
                 }
-            } else {
-                // Non-redirect response - we're done
                 return Ok(HttpResponse {
                     url: url.to_string(),
+                    method: hop.method.to_string(),
                     status_code,
-                    final_url: current_url,
+                    final_url: current.to_string(),
                     redirects,
-                    headers,
+                    headers: exchange.headers,
                     response_time: total_time,
-                    raw_output: Some(stdout),
+                    timing: exchange.timing,
+                    ip: Some(exchange.ip.to_string()),
+                    warnings,
+                    cache,
+                    body,
+                    raw_output: Some(exchange.head),
                 });
+            };
+
+            if redirects.len() >= MAX_REDIRECTS {
+                return Err(format!("Too many redirects (max: {})", MAX_REDIRECTS));
+            }
+            redirects.push(HttpRedirect {
+                from_url: current.to_string(),
+                to_url: next.to_string(),
+                status_code,
+                response_time: exchange.timing.total,
+                headers: exchange.headers,
+                timing: exchange.timing,
+                ip: Some(exchange.ip.to_string()),
+            });
+            current = next;
+
+            if (301..=303).contains(&status_code)
+                && hop.method != Method::GET
+                && hop.method != Method::HEAD
+            {
+                hop.method = Method::GET;
+                hop.body.clear();
+            }
+
+            if visited.contains(&(hop.method.clone(), current.clone())) {
+                let chain: Vec<&str> = redirects
+                    .iter()
+                    .map(|r| r.from_url.as_str())
+                    .chain([current.as_str()])
+                    .collect();
+                return Err(format!("Redirect loop: {}", chain.join(" -> ")));
+            }
+        }
+    }
+
+    // Content type and charset from the headers, falling back to the bytes
+    // themselves, and any meta refresh the page performs
+    fn describe_body(&self, exchange: &Exchange, url: &Url) -> HttpBody {
+        let text = String::from_utf8_lossy(&exchange.body).to_string();
+        let header = header_value(&exchange.headers, "content-type");
+        let mut params = header.map(|h| h.split(';')).into_iter().flatten();
+        let content_type = params
+            .next()
+            .map(|mime| mime.trim().to_lowercase())
+            .filter(|mime| !mime.is_empty());
+        let charset = params
+            .filter_map(|param| param.split_once('='))
+            .find(|(name, _)| name.trim().eq_ignore_ascii_case("charset"))
+            .map(|(_, value)| value.trim().trim_matches('"').to_lowercase())
+            .or_else(|| self.meta_charset(&text));
+        let sniffed = content_type
+            .is_none()
+            .then(|| self.sniff_content_type(&exchange.body));
+        let is_html = content_type
+            .as_deref()
+            .or(sniffed.as_deref())
+            .is_some_and(|mime| mime == "text/html" || mime == "application/xhtml+xml");
+
+        HttpBody {
+            content_type_sniffed: sniffed.is_some(),
+            content_type: content_type.or(sniffed),
+            charset,
+            size: exchange.body.len(),
+            content_length: header_value(&exchange.headers, "content-length")
+                .and_then(|length| length.trim().parse().ok()),
+            truncated: exchange.truncated,
+            meta_refresh: is_html
+                .then(|| self.meta_refresh(&text))
+                .flatten()
+                .and_then(|target| url.join(&target).ok())
+                .map(|target| target.to_string()),
+            text,
+        }
+    }
+
+    // Rough MIME sniffing for responses without a Content-Type
+    pub fn sniff_content_type(&self, body: &[u8]) -> String {
+        let start = String::from_utf8_lossy(&body[..body.len().min(512)])
+            .trim_start()
+            .to_lowercase();
+        let mime = if start.starts_with("<!doctype html") || start.starts_with("<html") {
+            "text/html"
+        } else if start.starts_with("<?xml") {
+            "application/xml"
+        } else if start.starts_with('{') || start.starts_with('[') {
+            "application/json"
+        } else if body.starts_with(b"%PDF-") {
+            "application/pdf"
+        } else if body.starts_with(b"\x89PNG") {
+            "image/png"
+        } else if std::str::from_utf8(body).is_ok() {
+            "text/plain"
+        } else {
+            "application/octet-stream"
+        };
+        mime.to_string()
+    }
+
+    fn meta_charset(&self, html: &str) -> Option<String> {
+        let charset_regex = Regex::new(r#"(?i)<meta[^>]+charset\s*=\s*["']?([\w-]+)"#).unwrap();
+        charset_regex.captures(html).map(|c| c[1].to_lowercase())
+    }
+
+    // The URL of <meta http-equiv="refresh" content="0; url=...">; a refresh
+    // without a URL just reloads the page
+    pub fn meta_refresh(&self, html: &str) -> Option<String> {
+        let tag_regex =
+            Regex::new(r#"(?is)<meta\b[^>]*http-equiv\s*=\s*["']?refresh\b[^>]*>"#).unwrap();
+        let content_regex = Regex::new(r#"(?is)\bcontent\s*=\s*(?:"([^"]*)"|'([^']*)')"#).unwrap();
+        let tag = tag_regex.find(html)?.as_str();
+        let captures = content_regex.captures(tag)?;
+        let content = captures.get(1).or(captures.get(2))?.as_str();
+
+        let (_, target) = content.split_once([';', ','])?;
+        let target = target.trim();
+        let target = match target.split_once('=') {
+            Some((key, value)) if key.trim().eq_ignore_ascii_case("url") => value,
+            _ => target,
+        };
+        let target = target.trim().trim_matches(['"', '\'']);
+        (!target.is_empty()).then(|| target.to_string())
+    }
+
+    // Hops worth a second look: HTTPS -> HTTP downgrades, and hops leaving the
+    // registrable domain of the requested URL for another one
+    pub fn redirect_warnings(&self, url: &str, redirects: &[HttpRedirect]) -> Vec<String> {
+        let domain_of = |url: &Url| url.host_str().map(|host| self.registrable_domain(host));
+        let origin = Url::parse(url).ok().and_then(|url| domain_of(&url));
+        let mut warnings = Vec::new();
+
+        for redirect in redirects {
+            let (Ok(from), Ok(to)) = (Url::parse(&redirect.from_url), Url::parse(&redirect.to_url))
+            else {
+                continue;
+            };
+
+            if from.scheme() == "https" && to.scheme() == "http" {
+                warnings.push(format!(
+                    "Redirect downgrades HTTPS to HTTP: {} -> {}",
+                    from, to
+                ));
+            }
+
+            if let (Some(origin), Some(from_domain), Some(to_domain)) =
+                (&origin, domain_of(&from), domain_of(&to))
+            {
+                if from_domain == *origin && to_domain != *origin {
+                    warnings.push(format!(
+                        "Redirect leaves {} for {}: {} -> {}",
+                        origin, to_domain, from, to
+                    ));
+                }
+            }
+        }
+
+        warnings
+    }
+
+    // The last two labels, or three under a ccTLD's generic second level
+    // (example.co.uk). Approximates the Public Suffix List for common cases.
+    pub fn registrable_domain(&self, host: &str) -> String {
+        let host = host.trim_end_matches('.').to_lowercase();
+        if host.trim_matches(['[', ']']).parse::<IpAddr>().is_ok() {
+            return host;
+        }
+
+        let labels: Vec<&str> = host.split('.').collect();
+        let keep = match labels.as_slice() {
+            [.., second, tld] if tld.len() == 2 && COUNTRY_SECOND_LEVELS.contains(second) => 3,
+            _ => 2,
+        };
+        labels[labels.len().saturating_sub(keep)..].join(".")
+    }
+
+    // Freshness as a shared cache computes it: s-maxage, then max-age, then
+    // Expires - Date, then 10% of the time since Last-Modified (RFC 9111 4.2)
+    pub fn analyze_cache(&self, headers: &[(String, String)]) -> CacheAnalysis {
+        let header = |name: &str| header_value(headers, name).map(|v| v.trim().to_string());
+        let http_date = |name: &str| {
+            header_value(headers, name).and_then(|v| DateTime::parse_from_rfc2822(v.trim()).ok())
+        };
+        let mut warnings = Vec::new();
+
+        // Repeated Cache-Control headers combine into one directive list
+        let cache_control: Vec<String> = header_values(headers, "cache-control")
+            .join(",")
+            .split(',')
+            .map(|d| d.trim().to_lowercase())
+            .filter(|d| !d.is_empty())
+            .collect();
+        let has = |name: &str| {
+            cache_control
+                .iter()
+                .any(|d| d == name || d.starts_with(&format!("{}=", name)))
+        };
+        let seconds = |name: &str| {
+            cache_control.iter().find_map(|d| {
+                d.strip_prefix(&format!("{}=", name))
+                    .and_then(|v| v.trim_matches('"').parse::<u64>().ok())
+            })
+        };
+
+        let max_age = seconds("max-age");
+        let s_maxage = seconds("s-maxage");
+        let age = header("age").and_then(|v| v.parse().ok());
+        let expires = header("expires");
+        let (date, expires_at, last_modified_at) = (
+            http_date("date"),
+            http_date("expires"),
+            http_date("last-modified"),
+        );
+
+        let storable = !has("no-store") && !has("private");
+        let mut heuristic = false;
+        let freshness_lifetime = if !storable {
+            None
+        } else if let Some(lifetime) = s_maxage.or(max_age) {
+            Some(lifetime)
+        } else if expires.is_some() {
+            // An unparseable Expires (often "0" or "-1") means already expired
+            Some(match (expires_at, date) {
+                (Some(at), Some(date)) => (at - date).num_seconds().max(0) as u64,
+                _ => 0,
+            })
+        } else if let (Some(modified), Some(date)) = (last_modified_at, date) {
+            heuristic = true;
+            Some(((date - modified).num_seconds().max(0) / 10) as u64)
+        } else {
+            None
+        };
+
+        if has("no-store") && (max_age.is_some() || s_maxage.is_some() || has("public")) {
+            warnings.push(
+                "no-store overrides max-age/s-maxage/public: the response is never cached"
+                    .to_string(),
+            );
+        }
+        if has("public") && has("private") {
+            warnings
+                .push("Both public and private are set; caches treat it as private".to_string());
+        }
+        if has("no-cache") && max_age.is_some_and(|m| m > 0) {
+            warnings.push(
+                "no-cache with a max-age: caches store it but revalidate on every request"
+                    .to_string(),
+            );
+        }
+        if let (Some(lifetime), Some(expires_at), Some(date)) =
+            (s_maxage.or(max_age), expires_at, date)
+        {
+            if (expires_at - date).num_seconds() != lifetime as i64 {
+                warnings.push(format!(
+                    "Expires disagrees with {}; Expires is ignored",
+                    if s_maxage.is_some() {
+                        "s-maxage"
+                    } else {
+                        "max-age"
+                    }
+                ));
+            }
+        }
+        if expires.is_some() && expires_at.is_none() {
+            warnings.push("Expires is not a valid HTTP date; it means already expired".to_string());
+        }
+        if header("pragma").is_some_and(|p| p.eq_ignore_ascii_case("no-cache"))
+            && freshness_lifetime.is_some_and(|l| l > 0)
+        {
+            warnings
+                .push("Pragma: no-cache is ignored when Cache-Control allows caching".to_string());
+        }
+        if (has("no-cache") || has("must-revalidate"))
+            && header("etag").is_none()
+            && header("last-modified").is_none()
+        {
+            warnings.push(
+                "Revalidation is required but there is no ETag or Last-Modified to revalidate with"
+                    .to_string(),
+            );
+        }
+
+        let remaining = freshness_lifetime.map(|l| l as i64 - age.unwrap_or(0) as i64);
+        if remaining.is_some_and(|r| r < 0) {
+            warnings
+                .push("Age exceeds the freshness lifetime: a stale copy was served".to_string());
+        }
+
+        CacheAnalysis {
+            cache_control,
+            expires,
+            etag: header("etag"),
+            last_modified: header("last-modified"),
+            age,
+            storable,
+            freshness_lifetime,
+            heuristic,
+            remaining,
+            warnings,
+        }
+    }
+
+    // HSTS policy of https://domain/, whether http://domain/ upgrades to it, and
+    // the domain's status on the Chromium preload list (via hstspreload.org)
+    pub async fn check_hsts(&self, domain: &str) -> HstsReport {
+        let settings = state::settings_for(self.app_handle.as_ref());
+        let mut warnings = Vec::new();
+
+        // The policy must be on the first response; a redirect target's doesn't count
+        let policy = match self
+            .fetch(&format!("https://{}/", domain), &HttpRequest::default())
+            .await
+        {
+            Ok(response) => {
+                let headers = response
+                    .redirects
+                    .first()
+                    .map(|hop| &hop.headers)
+                    .unwrap_or(&response.headers);
+                header_value(headers, "strict-transport-security")
+                    .map(|header| self.parse_hsts(header))
+            }
+            Err(e) => {
+                warnings.push(format!("HTTPS request failed: {}", e));
+                None
             }
+        };
+
+        // Sites that don't listen on port 80 have nothing to upgrade
+        let http_upgrades = self
+            .fetch(&format!("http://{}/", domain), &HttpRequest::default())
+            .await
+            .ok()
+            .map(|response| {
+                response.redirects.first().is_some_and(|hop| {
+                    Url::parse(&hop.to_url)
+                        .is_ok_and(|to| to.scheme() == "https" && to.host_str() == Some(domain))
+                })
+            });
+
+        let preload_status = match self
+            .get_text(
+                &format!("{}{}", HSTS_PRELOAD_STATUS_URL, domain),
+                "application/json",
+                settings.http_timeout_secs,
+                settings.http_retries,
+                domain,
+            )
+            .await
+        {
+            Ok((200, body)) => serde_json::from_str::<serde_json::Value>(&body)
+                .ok()
+                .and_then(|v| v["status"].as_str().map(str::to_string)),
+            Ok((status, _)) => {
+                warnings.push(format!("Preload list lookup failed: HTTP {}", status));
+                None
+            }
+            Err(e) => {
+                warnings.push(format!("Preload list lookup failed: {}", e));
+                None
+            }
+        };
+
+        let issues = self.preload_issues(domain, policy.as_ref(), http_upgrades);
+
+        HstsReport {
+            domain: domain.to_string(),
+            preload_eligible: issues.is_empty(),
+            policy,
+            preload_status,
+            issues,
+            warnings,
+        }
+    }
+
+    // Directives are case-insensitive and may be quoted; max-age is required and
+    // no directive may appear twice (RFC 6797 section 6.1)
+    pub fn parse_hsts(&self, header: &str) -> HstsPolicy {
+        let mut policy = HstsPolicy {
+            max_age: None,
+            include_subdomains: false,
+            preload: false,
+            errors: Vec::new(),
+            raw: header.to_string(),
+        };
+        let mut seen = HashSet::new();
+
+        for directive in header.split(';').map(str::trim).filter(|d| !d.is_empty()) {
+            let (name, value) = match directive.split_once('=') {
+                Some((name, value)) => (name.trim(), Some(value.trim().trim_matches('"'))),
+                None => (directive, None),
+            };
+            let name = name.to_lowercase();
+            if !seen.insert(name.clone()) {
+                policy
+                    .errors
+                    .push(format!("Directive {} appears more than once", name));
+                continue;
+            }
+
+            match (name.as_str(), value) {
+                ("max-age", Some(value)) => match value.parse() {
+                    Ok(seconds) => policy.max_age = Some(seconds),
+                    Err(_) => policy.errors.push(format!("Invalid max-age: {}", value)),
+                },
+                ("max-age", None) => policy.errors.push("max-age has no value".to_string()),
+                ("includesubdomains", _) => policy.include_subdomains = true,
+                ("preload", _) => policy.preload = true,
+                // Unknown directives are ignored by browsers
+                _ => {}
+            }
+        }
+
+        if policy.max_age.is_none() && !policy.errors.iter().any(|e| e.contains("max-age")) {
+            policy.errors.push("max-age is missing".to_string());
+        }
+
+        policy
+    }
+
+    // What keeps the domain off the preload list. http_upgrades is None when
+    // nothing answers on port 80, which hstspreload.org accepts.
+    pub fn preload_issues(
+        &self,
+        domain: &str,
+        policy: Option<&HstsPolicy>,
+        http_upgrades: Option<bool>,
+    ) -> Vec<String> {
+        let mut issues = Vec::new();
+
+        let registrable = self.registrable_domain(domain);
+        if registrable != domain.trim_end_matches('.').to_lowercase() {
+            issues.push(format!(
+                "Only registrable domains can be preloaded; submit {} instead",
+                registrable
+            ));
+        }
+        if http_upgrades == Some(false) {
+            issues.push(format!(
+                "http://{}/ does not redirect to HTTPS on the same host",
+                domain
+            ));
+        }
+
+        let Some(policy) = policy else {
+            issues.push("No Strict-Transport-Security header on the HTTPS response".to_string());
+            return issues;
+        };
+        issues.extend(policy.errors.iter().cloned());
+        match policy.max_age {
+            Some(max_age) if max_age < HSTS_PRELOAD_MIN_MAX_AGE => issues.push(format!(
+                "max-age is {} seconds; at least {} (one year) is required",
+                max_age, HSTS_PRELOAD_MIN_MAX_AGE
+            )),
+            _ => {}
+        }
+        if !policy.include_subdomains {
+            issues.push("includeSubDomains directive is missing".to_string());
+        }
+        if !policy.preload {
+            issues.push("preload directive is missing".to_string());
+        }
+
+        issues
+    }
+
+    // Fetch the apex and www over HTTP and HTTPS and check they all end up on
+    // the same URL
+    pub async fn check_canonical(&self, domain: &str) -> CanonicalReport {
+        let apex = domain.strip_prefix("www.").unwrap_or(domain);
+        let urls = [
+            format!("https://{}/", apex),
+            format!("https://www.{}/", apex),
+            format!("http://{}/", apex),
+            format!("http://www.{}/", apex),
+        ];
+        let request = HttpRequest::default();
+        let (a, b, c, d) = tokio::join!(
+            self.fetch(&urls[0], &request),
+            self.fetch(&urls[1], &request),
+            self.fetch(&urls[2], &request),
+            self.fetch(&urls[3], &request),
+        );
+
+        let variants: Vec<CanonicalVariant> = urls
+            .iter()
+            .zip([a, b, c, d])
+            .map(|(url, result)| match result {
+                Ok(response) => CanonicalVariant {
+                    url: url.clone(),
+                    final_url: Some(response.final_url),
+                    status_code: Some(response.status_code),
+                    redirects: response.redirects.len(),
+                    error: None,
+                },
+                Err(e) => CanonicalVariant {
+                    url: url.clone(),
+                    final_url: None,
+                    status_code: None,
+                    redirects: 0,
+                    error: Some(e),
+                },
+            })
+            .collect();
+        let (canonical_url, converges, warnings) = self.canonical_summary(&variants);
+
+        CanonicalReport {
+            domain: apex.to_string(),
+            variants,
+            canonical_url,
+            converges,
+            warnings,
         }
     }
 
-    fn parse_response_headers(
+    // The most common final URL (HTTPS wins ties), whether every variant
+    // reached it, and what stands in the way
+    pub fn canonical_summary(
         &self,
-        output: &str,
-    ) -> Result<(u16, HashMap<String, String>), String> {
-        let mut status_code = 0u16;
-        let mut headers = HashMap::new();
-
-        let lines: Vec<&str> = output.lines().collect();
-        if lines.is_empty() {
-            return Err("Empty response".to_string());
-        }
-
-        // Parse status line (e.g., "HTTP/1.1 200 OK" or "HTTP/2 301")
-        let first_line = lines[0];
-        let parts: Vec<&str> = first_line.split_whitespace().collect();
-        for part in &parts {
-            if let Ok(code) = part.parse::<u16>() {
-                if code >= 100 && code < 600 {
-                    status_code = code;
+        variants: &[CanonicalVariant],
+    ) -> (Option<String>, bool, Vec<String>) {
+        let mut warnings = Vec::new();
+        let mut counts: Vec<(&str, usize)> = Vec::new();
+
+        for variant in variants {
+            if let Some(error) = &variant.error {
+                warnings.push(format!("{} failed: {}", variant.url, error));
+            }
+            if let Some(status) = variant.status_code.filter(|s| !(200..300).contains(s)) {
+                warnings.push(format!("{} ends with HTTP {}", variant.url, status));
+            }
+            if let Some(final_url) = &variant.final_url {
+                match counts.iter_mut().find(|(url, _)| url == final_url) {
+                    Some((_, count)) => *count += 1,
+                    None => counts.push((final_url, 1)),
+                }
+            }
+        }
+
+        let canonical = counts
+            .iter()
+            .max_by_key(|(url, count)| (*count, url.starts_with("https://")))
+            .map(|(url, _)| url.to_string());
+        let converges = counts.len() == 1 && variants.iter().all(|v| v.error.is_none());
+
+        if counts.len() > 1 {
+            let finals: Vec<&str> = counts.iter().map(|(url, _)| *url).collect();
+            warnings.push(format!(
+                "Variants end on {} different URLs: {}",
+                finals.len(),
+                finals.join(", ")
+            ));
+        }
+        if let Some(canonical) = &canonical {
+            if canonical.starts_with("http://") {
+                warnings.push(format!("The canonical URL {} is not HTTPS", canonical));
+            }
+            for variant in variants {
+                if variant.final_url.as_ref() == Some(canonical) && variant.redirects > 1 {
+                    warnings.push(format!(
+                        "{} takes {} redirects to reach {}",
+                        variant.url, variant.redirects, canonical
+                    ));
+                }
+            }
+        }
+
+        (canonical, converges, warnings)
+    }
+
+    // robots.txt, the sitemaps it lists (or /sitemap.xml), and security.txt
+    pub async fn fetch_well_known(&self, domain: &str) -> WellKnownReport {
+        let base = format!("https://{}", domain);
+        let mut warnings = Vec::new();
+
+        let robots_url = format!("{}/robots.txt", base);
+        let robots = match self.fetch_text(&robots_url, domain).await {
+            Ok(Some(body)) => {
+                if self.looks_like_html(&body) {
+                    warnings.push("robots.txt is an HTML page, not a robots file".to_string());
+                }
+                Some(self.parse_robots(&robots_url, &body))
+            }
+            Ok(None) => {
+                warnings.push("No robots.txt; crawlers may fetch everything".to_string());
+                None
+            }
+            Err(e) => {
+                warnings.push(format!("robots.txt: {}", e));
+                None
+            }
+        };
+        if robots.as_ref().is_some_and(|r| r.blocks_all) {
+            warnings.push("robots.txt disallows all crawlers from the whole site".to_string());
+        }
+
+        let sitemap_urls = robots
+            .as_ref()
+            .map(|r| r.sitemaps.clone())
+            .filter(|urls| !urls.is_empty())
+            .unwrap_or_else(|| vec![format!("{}/sitemap.xml", base)]);
+        if sitemap_urls.len() > MAX_SITEMAPS {
+            warnings.push(format!(
+                "Only the first {} of {} sitemaps were checked",
+                MAX_SITEMAPS,
+                sitemap_urls.len()
+            ));
+        }
+        let mut sitemaps = Vec::new();
+        for url in sitemap_urls.iter().take(MAX_SITEMAPS) {
+            sitemaps.push(self.fetch_sitemap(url, domain).await);
+        }
+
+        // RFC 9116 puts it under /.well-known/; the root location is legacy
+        let mut security_txt = None;
+        for path in ["/.well-known/security.txt", "/security.txt"] {
+            let url = format!("{}{}", base, path);
+            if let Ok(Some(body)) = self.fetch_text(&url, domain).await {
+                if !self.looks_like_html(&body) {
+                    security_txt = Some(self.parse_security_txt(&url, &body, Utc::now()));
                     break;
                 }
             }
         }
+        match &security_txt {
+            None => warnings.push("No security.txt".to_string()),
+            Some(file) if !file.url.contains("/.well-known/") => {
+                warnings.push("security.txt should be served from /.well-known/".to_string())
+            }
+            _ => {}
+        }
+
+        WellKnownReport {
+            domain: domain.to_string(),
+            robots,
+            sitemaps,
+            security_txt,
+            warnings,
+        }
+    }
+
+    // Body of a 2xx answer; None when the file isn't there
+    async fn fetch_text(&self, url: &str, domain: &str) -> Result<Option<String>, String> {
+        let settings = state::settings_for(self.app_handle.as_ref());
+        match self
+            .get_text(
+                url,
+                "text/plain, */*",
+                settings.http_timeout_secs,
+                settings.http_retries,
+                domain,
+            )
+            .await?
+        {
+            (200..=299, body) => Ok(Some(body)),
+            (404 | 410, _) => Ok(None),
+            (status, _) => Err(format!("HTTP {}", status)),
+        }
+    }
+
+    async fn fetch_sitemap(&self, url: &str, domain: &str) -> Sitemap {
+        let failed = |error: String| Sitemap {
+            url: url.to_string(),
+            is_index: false,
+            url_count: 0,
+            locations: Vec::new(),
+            error: Some(error),
+        };
+
+        if url.ends_with(".gz") {
+            return failed("Compressed sitemaps are not supported".to_string());
+        }
+        match self.fetch_text(url, domain).await {
+            Ok(Some(body)) => self.parse_sitemap(url, &body),
+            Ok(None) => failed("Not found".to_string()),
+            Err(e) => failed(e),
+        }
+    }
+
+    // Soft 404s: a 200 with the site's HTML error page
+    fn looks_like_html(&self, body: &str) -> bool {
+        let start = body.trim_start().to_lowercase();
+        start.starts_with("<!doctype html") || start.starts_with("<html")
+    }
+
+    // Consecutive User-agent lines open a group and the rules after them belong
+    // to it; Sitemap lines apply to the whole file
+    pub fn parse_robots(&self, url: &str, body: &str) -> RobotsTxt {
+        let mut groups: Vec<RobotsGroup> = Vec::new();
+        let mut sitemaps = Vec::new();
+        let mut in_rules = true;
+
+        for line in body.lines() {
+            let line = line.split('#').next().unwrap_or("").trim();
+            let Some((key, value)) = line.split_once(':') else {
+                continue;
+            };
+            let value = value.trim().to_string();
+
+            match key.trim().to_lowercase().as_str() {
+                "user-agent" => {
+                    if in_rules || groups.is_empty() {
+                        groups.push(RobotsGroup {
+                            user_agents: Vec::new(),
+                            allow: Vec::new(),
+                            disallow: Vec::new(),
+                            crawl_delay: None,
+                        });
+                    }
+                    in_rules = false;
+                    if let Some(group) = groups.last_mut() {
+                        group.user_agents.push(value);
+                    }
+                }
+                "sitemap" => sitemaps.push(value),
+                rule => {
+                    let Some(group) = groups.last_mut() else {
+                        continue;
+                    };
+                    in_rules = true;
+                    match rule {
+                        "allow" => group.allow.push(value),
+                        // An empty Disallow allows everything
+                        "disallow" if !value.is_empty() => group.disallow.push(value),
+                        "crawl-delay" => group.crawl_delay = value.parse().ok(),
+                        _ => {}
+                    }
+                }
+            }
+        }
+
+        let blocks_all = groups.iter().any(|g| {
+            g.user_agents.iter().any(|ua| ua == "*")
+                && g.disallow.iter().any(|path| path == "/")
+                && g.allow.is_empty()
+        });
+
+        RobotsTxt {
+            url: url.to_string(),
+            groups,
+            sitemaps,
+            blocks_all,
+            raw: body.to_string(),
+        }
+    }
+
+    pub fn parse_sitemap(&self, url: &str, body: &str) -> Sitemap {
+        let loc_regex = Regex::new(r"(?is)<loc>\s*(.*?)\s*</loc>").unwrap();
+        let locations: Vec<String> = loc_regex
+            .captures_iter(body)
+            .map(|c| c[1].replace("&amp;", "&"))
+            .collect();
+        let is_index = body.contains("<sitemapindex");
+        let error = (!is_index && !body.contains("<urlset"))
+            .then(|| "Not a sitemap: no <urlset> or <sitemapindex>".to_string());
+
+        Sitemap {
+            url: url.to_string(),
+            is_index,
+            url_count: locations.len(),
+            locations: locations.into_iter().take(SITEMAP_SAMPLE_SIZE).collect(),
+            error,
+        }
+    }
+
+    // Contact and Expires are required; Expires should be under a year away
+    pub fn parse_security_txt(&self, url: &str, body: &str, now: DateTime<Utc>) -> SecurityTxt {
+        let mut file = SecurityTxt {
+            url: url.to_string(),
+            contact: Vec::new(),
+            expires: None,
+            encryption: Vec::new(),
+            policy: Vec::new(),
+            canonical: Vec::new(),
+            preferred_languages: None,
+            signed: body
+                .trim_start()
+                .starts_with("-----BEGIN PGP SIGNED MESSAGE-----"),
+            warnings: Vec::new(),
+            raw: body.to_string(),
+        };
+
+        for line in body.lines() {
+            let line = line.trim();
+            if line.starts_with('#') || line.starts_with("-----") {
+                continue;
+            }
+            let Some((field, value)) = line.split_once(':') else {
+                continue;
+            };
+            let value = value.trim().to_string();
+
+            match field.trim().to_lowercase().as_str() {
+                "contact" => file.contact.push(value),
+                "expires" if file.expires.is_some() => file
+                    .warnings
+                    .push("Expires appears more than once".to_string()),
+                "expires" => file.expires = Some(value),
+                "encryption" => file.encryption.push(value),
+                "policy" => file.policy.push(value),
+                "canonical" => file.canonical.push(value),
+                "preferred-languages" => file.preferred_languages = Some(value),
+                _ => {}
+            }
+        }
+
+        if file.contact.is_empty() {
+            file.warnings
+                .push("Missing required Contact field".to_string());
+        }
+        match file.expires.as_deref().map(DateTime::parse_from_rfc3339) {
+            None => file
+                .warnings
+                .push("Missing required Expires field".to_string()),
+            Some(Err(_)) => file
+                .warnings
+                .push("Expires is not an RFC 3339 date".to_string()),
+            Some(Ok(expires)) if expires <= now => file
+                .warnings
+                .push(format!("Expired on {}", expires.format("%Y-%m-%d"))),
+            Some(Ok(expires)) if (expires.with_timezone(&Utc) - now).num_days() > 366 => file
+                .warnings
+                .push("Expires is more than a year away".to_string()),
+            _ => {}
+        }
+
+        file
+    }
+
+    // GET a URL (following redirects) and return the final status and body.
+    // For JSON APIs such as RDAP and crt.sh, where a 404 is an answer, not a failure.
+    pub async fn get_text(
+        &self,
+        url: &str,
+        accept: &str,
+        timeout_secs: u32,
+        retries: u32,
+        domain: &str,
+    ) -> Result<(u16, String), String> {
+        let settings = state::settings_for(self.app_handle.as_ref());
+        let hop = Hop::new(
+            Method::GET,
+            vec![("accept", accept)],
+            Vec::new(),
+            None,
+            &settings,
+        );
+        let mut current = self.parse_url(url)?;
+
+        for _ in 0..=MAX_REDIRECTS {
+            let exchange = self
+                .send_with_retries(&hop, &current, timeout_secs, retries, domain)
+                .await?;
+
+            match header_value(&exchange.headers, "location") {
+                Some(location) if (300..400).contains(&exchange.status_code) => {
+                    current = current
+                        .join(location)
+                        .map_err(|e| format!("Invalid redirect to {}: {}", location, e))?;
+                }
+                _ => {
+                    return Ok((
+                        exchange.status_code,
+                        String::from_utf8_lossy(&exchange.body).to_string(),
+                    ))
+                }
+            }
+        }
+
+        Err(format!("Too many redirects (max: {})", MAX_REDIRECTS))
+    }
+
+    fn parse_method(&self, method: &str) -> Result<Method, String> {
+        let method = method.trim().to_uppercase();
+        if !ALLOWED_METHODS.contains(&method.as_str()) {
+            return Err(format!(
+                "Unsupported HTTP method: {} (use one of {})",
+                method,
+                ALLOWED_METHODS.join(", ")
+            ));
+        }
+        Method::from_bytes(method.as_bytes())
+            .map_err(|e| format!("Invalid HTTP method {}: {}", method, e))
+    }
+
+    fn parse_url(&self, url: &str) -> Result<Url, String> {
+        let parsed = Url::parse(url).map_err(|e| format!("Invalid URL {}: {}", url, e))?;
+        match parsed.scheme() {
+            "http" | "https" => Ok(parsed),
+            scheme => Err(format!("Unsupported URL scheme: {}", scheme)),
+        }
+    }
+
+    // Each attempt is logged as the equivalent curl command. Connection failures,
+    // timeouts and TRANSIENT_STATUSES are retried with a doubling pause.
+    async fn send_with_retries(
+        &self,
+        hop: &Hop<'_>,
+        url: &Url,
+        timeout_secs: u32,
+        retries: u32,
+        domain: &str,
+    ) -> Result<Exchange, String> {
+        let mut attempt = 0;
+
+        loop {
+            attempt += 1;
+            let host = url.host_str().unwrap_or(domain);
+            let waited = rate_limiter::throttle(self.app_handle.as_ref(), Target::Host, host).await;
+            let start = Instant::now();
+
+            let exchange = self
+                .backends
+                .http
+                .send(hop, url)
+                .instrument(tracing::debug_span!("http_request", url = %url, attempt));
+            let result = tokio::time::timeout(Duration::from_secs(timeout_secs as u64), exchange)
+                .await
+                .unwrap_or_else(|_| {
+                    Err(format!(
+                        "Request to {} timed out after {} seconds",
+                        url, timeout_secs
+                    ))
+                });
+
+            let (output, exit_code) = match &result {
+                Ok(exchange) if exchange.body.is_empty() => (exchange.head.clone(), 0),
+                Ok(exchange) => (
+                    format!(
+                        "{}\r\n{}",
+                        exchange.head,
+                        String::from_utf8_lossy(&exchange.body)
+                    ),
+                    0,
+                ),
+                Err(e) => (e.clone(), 1),
+            };
+            self.emit_log(
+                CommandLog::new(
+                    "curl".to_string(),
+                    self.curl_args(hop, url, timeout_secs),
+                    output,
+                    exit_code,
+                    start.elapsed().as_secs_f64() * 1000.0,
+                    Some(domain.to_string()),
+                )
+                .with_throttled(waited),
+            );
+
+            let transient = match &result {
+                Ok(exchange) => TRANSIENT_STATUSES.contains(&exchange.status_code),
+                Err(_) => true,
+            };
+            if !transient || attempt > retries {
+                return result;
+            }
+            tokio::time::sleep(Duration::from_secs(1 << (attempt - 1).min(4))).await;
+        }
+    }
+
+    // The request as a curl command line, so it can be reproduced from the logs
+    fn curl_args(&self, hop: &Hop<'_>, url: &Url, timeout_secs: u32) -> Vec<String> {
+        let mut args = match hop.method {
+            Method::HEAD => vec!["-I".to_string()],
+            Method::GET => Vec::new(),
+            _ => vec!["-X".to_string(), hop.method.to_string()],
+        };
+        for (name, value) in &hop.headers {
+            args.push("-H".to_string());
+            args.push(format!("{}: {}", name, value));
+        }
+        let custom_agent = hop.user_agent != USER_AGENT
+            && !hop
+                .headers
+                .iter()
+                .any(|(name, _)| name.eq_ignore_ascii_case("user-agent"));
+        if custom_agent {
+            args.push("-A".to_string());
+            args.push(hop.user_agent.clone());
+        }
+        if let Some(proxy) = &hop.proxy {
+            // Credentials stay out of the log
+            let mut proxy = proxy.clone();
+            if proxy.password().is_some() {
+                let _ = proxy.set_password(Some("***"));
+            }
+            args.push("--proxy".to_string());
+            args.push(proxy.to_string());
+        }
+        if !hop.body.is_empty() {
+            args.push("--data-raw".to_string());
+            args.push(String::from_utf8_lossy(&hop.body).to_string());
+        }
+        args.push("--max-time".to_string());
+        args.push(timeout_secs.to_string());
+        args.push(url.to_string());
+        args
+    }
+}
+
+// HTTP/1.1 over TCP with hyper and rustls, directly or through the proxy in the hop
+pub struct HyperHttp;
+
+impl HttpBackend for HyperHttp {
+    fn send<'a>(&'a self, hop: &'a Hop<'a>, url: &'a Url) -> BackendFuture<'a, Exchange> {
+        Box::pin(self.send_request(hop, url))
+    }
+}
+
+impl HyperHttp {
+    // Resolve, connect and (for https) handshake separately so each phase is timed.
+    // Through a proxy, dns and connect time reaching the proxy and opening the
+    // tunnel, and the IP is the proxy's.
+    pub async fn send_request(&self, hop: &Hop<'_>, url: &Url) -> Result<Exchange, String> {
+        let start = Instant::now();
+        let host = url
+            .host_str()
+            .ok_or_else(|| format!("URL has no host: {}", url))?
+            .trim_start_matches('[')
+            .trim_end_matches(']');
+        let port = url
+            .port_or_known_default()
+            .ok_or_else(|| format!("URL has no port: {}", url))?;
+        let mut timing = HttpTiming::default();
+
+        let (connect_host, connect_port) = match &hop.proxy {
+            Some(proxy) => (
+                proxy
+                    .host_str()
+                    .unwrap_or_default()
+                    .trim_matches(['[', ']']),
+                proxy.port_or_known_default().unwrap_or_default(),
+            ),
+            None => (host, port),
+        };
+        let addresses: Vec<SocketAddr> = lookup_host((connect_host, connect_port))
+            .await
+            .map_err(|e| format!("Failed to resolve {}: {}", connect_host, e))?
+            .collect();
+        timing.dns = start.elapsed().as_secs_f64();
+
+        // Try each address in turn, like curl
+        let mut connected = Err(format!("No addresses found for {}", connect_host));
+        for address in addresses {
+            connected = TcpStream::connect(address)
+                .await
+                .map_err(|e| format!("Failed to connect to {}: {}", address, e));
+            if connected.is_ok() {
+                break;
+            }
+        }
+        let stream = connected?;
+        let ip = stream
+            .peer_addr()
+            .map_err(|e| format!("Failed to connect to {}: {}", connect_host, e))?
+            .ip();
+        let stream = match &hop.proxy {
+            Some(proxy) if proxy.scheme().starts_with("socks5") => {
+                self.socks5_connect(stream, proxy, host, port).await?
+            }
+            Some(proxy) if url.scheme() == "https" => {
+                self.proxy_tunnel(stream, proxy, host, port).await?
+            }
+            _ => stream,
+        };
+        timing.connect = start.elapsed().as_secs_f64() - timing.dns;
+
+        if url.scheme() == "http" {
+            return self.exchange(stream, hop, url, ip, start, timing).await;
+        }
+
+        let server_name = ServerName::try_from(host.to_string())
+            .map_err(|e| format!("Invalid host {}: {}", host, e))?;
+        let tls = self
+            .tls_connector()?
+            .connect(server_name, stream)
+            .await
+            .map_err(|e| format!("TLS handshake with {} failed: {}", host, e))?;
+        timing.tls = start.elapsed().as_secs_f64() - timing.dns - timing.connect;
+
+        self.exchange(tls, hop, url, ip, start, timing).await
+    }
+
+    async fn exchange<S>(
+        &self,
+        stream: S,
+        hop: &Hop<'_>,
+        url: &Url,
+        ip: IpAddr,
+        start: Instant,
+        mut timing: HttpTiming,
+    ) -> Result<Exchange, String>
+    where
+        S: AsyncRead + AsyncWrite + Unpin + Send + 'static,
+    {
+        let (mut sender, connection) = http1::handshake(TokioIo::new(stream))
+            .await
+            .map_err(|e| format!("HTTP handshake with {} failed: {}", url, e))?;
+        // Drives the connection; finishes once the response has been read
+        tokio::spawn(connection);
+
+        // Plain HTTP through an HTTP proxy sends the whole URL to the proxy
+        let forward = url.scheme() == "http"
+            && hop
+                .proxy
+                .as_ref()
+                .is_some_and(|proxy| proxy.scheme() == "http");
+        let target = if forward {
+            url.as_str()
+        } else {
+            &url[Position::BeforePath..Position::AfterQuery]
+        };
+        let mut request = Request::builder()
+            .method(hop.method.clone())
+            .uri(target)
+            .body(Full::new(Bytes::copy_from_slice(&hop.body)))
+            .map_err(|e| format!("Invalid request for {}: {}", url, e))?;
+        let proxy_authorization = hop
+            .proxy
+            .as_ref()
+            .filter(|_| forward)
+            .and_then(|proxy| self.proxy_authorization(proxy));
+        let mut defaults = vec![
+            ("host", &url[Position::BeforeHost..Position::AfterPort]),
+            ("user-agent", hop.user_agent.as_str()),
+            ("accept", "*/*"),
+        ];
+        if let Some(credentials) = &proxy_authorization {
+            defaults.push(("proxy-authorization", credentials.as_str()));
+        }
+        for (name, value) in defaults.iter().chain(&hop.headers) {
+            let name = HeaderName::try_from(*name)
+                .map_err(|e| format!("Invalid header name {}: {}", name, e))?;
+            let value = HeaderValue::try_from(*value)
+                .map_err(|e| format!("Invalid value for header {}: {}", name, e))?;
+            request.headers_mut().insert(name, value);
+        }
+
+        let response = sender
+            .send_request(request)
+            .await
+            .map_err(|e| format!("Request to {} failed: {}", url, e))?;
+        timing.first_byte =
+            start.elapsed().as_secs_f64() - timing.dns - timing.connect - timing.tls;
+
+        let status = response.status();
+        let mut head = format!(
+            "{:?} {} {}\r\n",
+            response.version(),
+            status.as_str(),
+            status.canonical_reason().unwrap_or_default()
+        );
+        let mut response_headers = Vec::new();
+        for (name, value) in response.headers() {
+            let value = String::from_utf8_lossy(value.as_bytes()).to_string();
+            head.push_str(&format!("{}: {}\r\n", name, value));
+            response_headers.push((name.as_str().to_string(), value));
+        }
+
+        let (body, truncated) = self
+            .read_body(response.into_body(), hop.body_limit)
+            .await
+            .map_err(|e| format!("Failed to read response from {}: {}", url, e))?;
+        timing.total = start.elapsed().as_secs_f64();
+
+        Ok(Exchange {
+            status_code: status.as_u16(),
+            headers: response_headers,
+            head,
+            body,
+            truncated,
+            timing,
+            ip,
+        })
+    }
+
+    // The proxy URL's username and password, percent-decoded: user%40corp is user@corp
+    fn proxy_credentials(&self, proxy: &Url) -> Option<(String, String)> {
+        if proxy.username().is_empty() {
+            return None;
+        }
+        let decode = |value: &str| percent_decode_str(value).decode_utf8_lossy().into_owned();
+        Some((
+            decode(proxy.username()),
+            decode(proxy.password().unwrap_or("")),
+        ))
+    }
+
+    // Basic credentials from the proxy URL's user info
+    fn proxy_authorization(&self, proxy: &Url) -> Option<String> {
+        let (username, password) = self.proxy_credentials(proxy)?;
+        let credentials = format!("{}:{}", username, password);
+        Some(format!("Basic {}", BASE64.encode(credentials)))
+    }
+
+    // Ask an HTTP proxy to CONNECT to host:port; the TLS handshake then runs
+    // through the tunnel
+    async fn proxy_tunnel(
+        &self,
+        mut stream: TcpStream,
+        proxy: &Url,
+        host: &str,
+        port: u16,
+    ) -> Result<TcpStream, String> {
+        let authority = if host.contains(':') {
+            format!("[{}]:{}", host, port)
+        } else {
+            format!("{}:{}", host, port)
+        };
+        let mut request = format!("CONNECT {0} HTTP/1.1\r\nHost: {0}\r\n", authority);
+        if let Some(credentials) = self.proxy_authorization(proxy) {
+            request.push_str(&format!("Proxy-Authorization: {}\r\n", credentials));
+        }
+        request.push_str("\r\n");
+        let failed = |e: std::io::Error| format!("Proxy tunnel to {} failed: {}", authority, e);
+        stream.write_all(request.as_bytes()).await.map_err(failed)?;
+
+        // A byte at a time so nothing after the proxy's headers is consumed
+        let mut response = Vec::new();
+        let mut byte = [0u8; 1];
+        while !response.ends_with(b"\r\n\r\n") {
+            if stream.read(&mut byte).await.map_err(failed)? == 0 || response.len() > 16384 {
+                return Err(format!(
+                    "Proxy tunnel to {} failed: no valid response from the proxy",
+                    authority
+                ));
+            }
+            response.push(byte[0]);
+        }
+
+        let response = String::from_utf8_lossy(&response);
+        let status_line = response.lines().next().unwrap_or_default();
+        if status_line.split_whitespace().nth(1) != Some("200") {
+            return Err(format!(
+                "Proxy refused to connect to {}: {}",
+                authority, status_line
+            ));
+        }
+        Ok(stream)
+    }
+
+    // SOCKS5 CONNECT (RFC 1928), with username/password authentication (RFC 1929)
+    // when the proxy URL has credentials. socks5h leaves name resolution to the
+    // proxy; socks5 resolves here and sends the address.
+    async fn socks5_connect(
+        &self,
+        mut stream: TcpStream,
+        proxy: &Url,
+        host: &str,
+        port: u16,
+    ) -> Result<TcpStream, String> {
+        let failed = |e: std::io::Error| format!("SOCKS proxy connection failed: {}", e);
+        // RFC 1929 and the domain name form each carry their length in one byte
+        let credentials = self.proxy_credentials(proxy);
+        if let Some((username, password)) = &credentials {
+            if username.len() > 255 || password.len() > 255 {
+                return Err(
+                    "SOCKS proxy usernames and passwords can't be longer than 255 bytes"
+                        .to_string(),
+                );
+            }
+        }
+        if host.len() > 255 {
+            return Err(format!("{} is too long to send to a SOCKS proxy", host));
+        }
+        let authenticate = credentials.is_some();
+        let greeting: &[u8] = if authenticate {
+            &[0x05, 0x02, 0x00, 0x02]
+        } else {
+            &[0x05, 0x01, 0x00]
+        };
+        stream.write_all(greeting).await.map_err(failed)?;
+        let mut reply = [0u8; 2];
+        stream.read_exact(&mut reply).await.map_err(failed)?;
+
+        match reply {
+            [0x05, 0x00] => {}
+            [0x05, 0x02] if authenticate => {
+                let (username, password) = credentials.unwrap_or_default();
+                let mut request = vec![0x01, username.len() as u8];
+                request.extend_from_slice(username.as_bytes());
+                request.push(password.len() as u8);
+                request.extend_from_slice(password.as_bytes());
+                stream.write_all(&request).await.map_err(failed)?;
+                stream.read_exact(&mut reply).await.map_err(failed)?;
+                if reply[1] != 0x00 {
+                    return Err("SOCKS proxy rejected the username or password".to_string());
+                }
+            }
+            _ => {
+                return Err("SOCKS proxy requires an unsupported authentication method".to_string())
+            }
+        }
 
-        // Parse headers
-        for line in &lines[1..] {
-            if line.is_empty() {
-                break; // End of headers
+        let address = match host.parse::<IpAddr>() {
+            Ok(ip) => Some(ip),
+            Err(_) if proxy.scheme() == "socks5" => lookup_host((host, port))
+                .await
+                .map_err(|e| format!("Failed to resolve {}: {}", host, e))?
+                .next()
+                .map(|address| address.ip()),
+            Err(_) => None,
+        };
+        let mut request = vec![0x05, 0x01, 0x00];
+        match address {
+            Some(IpAddr::V4(ip)) => {
+                request.push(0x01);
+                request.extend_from_slice(&ip.octets());
             }
-            if let Some(colon_pos) = line.find(':') {
-                let key = line[..colon_pos].trim().to_lowercase();
-                let value = line[colon_pos + 1..].trim().to_string();
-                headers.insert(key, value);
+            Some(IpAddr::V6(ip)) => {
+                request.push(0x04);
+                request.extend_from_slice(&ip.octets());
+            }
+            None => {
+                request.push(0x03);
+                request.push(host.len() as u8);
+                request.extend_from_slice(host.as_bytes());
             }
         }
+        request.extend_from_slice(&port.to_be_bytes());
+        stream.write_all(&request).await.map_err(failed)?;
+
+        let mut header = [0u8; 4];
+        stream.read_exact(&mut header).await.map_err(failed)?;
+        if header[1] != 0x00 {
+            let reason = match header[1] {
+                0x02 => "not allowed by the proxy's rules",
+                0x03 => "network unreachable",
+                0x04 => "host unreachable",
+                0x05 => "connection refused",
+                0x06 => "TTL expired",
+                _ => "proxy failure",
+            };
+            return Err(format!(
+                "SOCKS proxy could not connect to {}:{}: {}",
+                host, port, reason
+            ));
+        }
+
+        // Skip the address the proxy bound for the connection
+        let bound_len = match header[3] {
+            0x01 => 4,
+            0x04 => 16,
+            _ => {
+                let mut len = [0u8; 1];
+                stream.read_exact(&mut len).await.map_err(failed)?;
+                len[0] as usize
+            }
+        };
+        let mut bound = vec![0u8; bound_len + 2];
+        stream.read_exact(&mut bound).await.map_err(failed)?;
+        Ok(stream)
+    }
 
-        Ok((status_code, headers))
+    // Reads up to limit bytes; the rest is dropped with the connection
+    async fn read_body(
+        &self,
+        mut body: Incoming,
+        limit: Option<usize>,
+    ) -> Result<(Vec<u8>, bool), hyper::Error> {
+        let mut bytes = Vec::new();
+        while let Some(frame) = body.frame().await {
+            if let Ok(data) = frame?.into_data() {
+                bytes.extend_from_slice(&data);
+            }
+            if let Some(limit) = limit.filter(|limit| bytes.len() > *limit) {
+                bytes.truncate(limit);
+                return Ok((bytes, true));
+            }
+        }
+        Ok((bytes, false))
     }
 
-    fn is_curl_available(&self) -> bool {
-        Command::new("curl").arg("--version").output().is_ok()
+    // Verified against the bundled Mozilla roots; hyper is set up for HTTP/1.1 only
+    fn tls_connector(&self) -> Result<TlsConnector, String> {
+        let mut roots = RootCertStore::empty();
+        roots.extend(webpki_roots::TLS_SERVER_ROOTS.iter().cloned());
+
+        let mut config = ClientConfig::builder_with_provider(Arc::new(ring::default_provider()))
+            .with_safe_default_protocol_versions()
+            .map_err(|e| format!("Failed to configure TLS: {}", e))?
+            .with_root_certificates(roots)
+            .with_no_client_auth();
+        config.alpn_protocols = vec![b"http/1.1".to_vec()];
+
+        Ok(TlsConnector::from(Arc::new(config)))
     }
 }
+
+#[cfg(test)]
+#[path = "http_test.rs"]
+mod tests;
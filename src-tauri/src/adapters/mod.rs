@@ -1,4 +1,18 @@
-pub mod dns;
+pub mod backend;
 pub mod certificate;
-pub mod whois;
+pub mod dns;
+pub mod dns_tools;
+pub mod email;
+pub mod environment;
+pub mod health;
 pub mod http;
+pub mod ping;
+pub mod ports;
+pub mod report;
+pub mod reverse_ip;
+pub mod ssh;
+#[cfg(test)]
+pub mod test_support;
+pub mod traceroute;
+pub mod typosquat;
+pub mod whois;
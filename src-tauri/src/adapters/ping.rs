@@ -0,0 +1,386 @@
+use crate::adapters::backend::{BackendFuture, Backends, PingBackend};
+use crate::adapters::dns::DnsAdapter;
+use crate::models::command_log::CommandLog;
+use crate::models::ping::{PingMethod, PingReport, PingStats};
+use crate::state;
+use crate::state::command_logs;
+use crate::state::rate_limiter::{self, Target};
+use socket2::{Domain, Protocol, Socket, Type};
+use std::io::ErrorKind;
+use std::net::IpAddr;
+use std::time::{Duration, Instant};
+use tauri::AppHandle;
+use tokio::net::{TcpStream, UdpSocket};
+use tokio::task::JoinSet;
+use tracing::instrument;
+
+// Probed when ICMP isn't permitted or goes unanswered
+pub const DEFAULT_TCP_PORT: u16 = 443;
+
+// Between probes to one address; ping's one second is slower than it needs to be
+const PROBE_INTERVAL: Duration = Duration::from_millis(250);
+
+// How long to wait for each reply
+const PROBE_TIMEOUT: Duration = Duration::from_secs(2);
+
+// Carried in each echo request and expected back in the reply
+const ECHO_PAYLOAD: &[u8] = b"d-dns-debugger";
+
+// ICMP echo over unprivileged datagram sockets (macOS, and Linux within
+// net.ipv4.ping_group_range), and plain TCP connects
+pub struct SocketPing;
+
+impl PingBackend for SocketPing {
+    fn echo<'a>(
+        &'a self,
+        address: IpAddr,
+        sequence: u16,
+        timeout: Duration,
+    ) -> BackendFuture<'a, Option<Duration>> {
+        Box::pin(icmp_echo(address, sequence, timeout))
+    }
+
+    fn connect<'a>(
+        &'a self,
+        address: IpAddr,
+        port: u16,
+        timeout: Duration,
+    ) -> BackendFuture<'a, Option<Duration>> {
+        Box::pin(tcp_connect(address, port, timeout))
+    }
+}
+
+// Send one echo request and wait for its reply. Only opening the socket fails;
+// anything after that counts as a lost probe.
+async fn icmp_echo(
+    address: IpAddr,
+    sequence: u16,
+    timeout: Duration,
+) -> Result<Option<Duration>, String> {
+    let (domain, protocol) = match address {
+        IpAddr::V4(_) => (Domain::IPV4, Protocol::ICMPV4),
+        IpAddr::V6(_) => (Domain::IPV6, Protocol::ICMPV6),
+    };
+    let socket = Socket::new(domain, Type::DGRAM, Some(protocol))
+        .and_then(|socket| socket.set_nonblocking(true).map(|_| socket))
+        .and_then(|socket| UdpSocket::from_std(socket.into()))
+        .map_err(|e| e.to_string())?;
+
+    let start = Instant::now();
+    let request = echo_request(address.is_ipv4(), sequence);
+    if socket.connect((address, 0)).await.is_err() || socket.send(&request).await.is_err() {
+        return Ok(None);
+    }
+
+    let mut buffer = [0u8; 1500];
+    let deadline = tokio::time::Instant::from_std(start + timeout);
+    loop {
+        match tokio::time::timeout_at(deadline, socket.recv(&mut buffer)).await {
+            Ok(Ok(read)) if is_echo_reply(&buffer[..read], address.is_ipv4(), sequence) => {
+                return Ok(Some(start.elapsed()))
+            }
+            // Another process's reply, on systems that deliver every one to each socket
+            Ok(Ok(_)) => continue,
+            Ok(Err(_)) | Err(_) => return Ok(None),
+        }
+    }
+}
+
+// Echo request (type 8, or 128 for ICMPv6). Linux replaces the identifier with
+// the socket's port and the kernel fills in the ICMPv6 checksum.
+pub fn echo_request(ipv4: bool, sequence: u16) -> Vec<u8> {
+    let mut packet = vec![if ipv4 { 8 } else { 128 }, 0, 0, 0];
+    packet.extend_from_slice(&(std::process::id() as u16).to_be_bytes());
+    packet.extend_from_slice(&sequence.to_be_bytes());
+    packet.extend_from_slice(ECHO_PAYLOAD);
+    if ipv4 {
+        let checksum = icmp_checksum(&packet);
+        packet[2..4].copy_from_slice(&checksum.to_be_bytes());
+    }
+    packet
+}
+
+// Internet checksum (RFC 1071) over the whole message
+fn icmp_checksum(packet: &[u8]) -> u16 {
+    let mut sum: u32 = packet
+        .chunks(2)
+        .map(|pair| u16::from_be_bytes([pair[0], *pair.get(1).unwrap_or(&0)]) as u32)
+        .sum();
+    while sum > 0xffff {
+        sum = (sum & 0xffff) + (sum >> 16);
+    }
+    !(sum as u16)
+}
+
+// Whether a datagram is the reply to our request with this sequence number.
+// macOS hands IPv4 datagrams over with their IP header, which is skipped.
+pub fn is_echo_reply(datagram: &[u8], ipv4: bool, sequence: u16) -> bool {
+    let message = match datagram.first() {
+        Some(byte) if ipv4 && byte >> 4 == 4 => datagram
+            .get(((byte & 0x0f) as usize) * 4..)
+            .unwrap_or_default(),
+        _ => datagram,
+    };
+    message.len() >= 8
+        && message[0] == if ipv4 { 0 } else { 129 }
+        && message[6..8] == sequence.to_be_bytes()
+        && &message[8..] == ECHO_PAYLOAD
+}
+
+// Time to connect. A refusal is a round trip too, so it counts as a reply.
+async fn tcp_connect(
+    address: IpAddr,
+    port: u16,
+    timeout: Duration,
+) -> Result<Option<Duration>, String> {
+    let start = Instant::now();
+    match tokio::time::timeout(timeout, TcpStream::connect((address, port))).await {
+        Ok(Ok(_)) => Ok(Some(start.elapsed())),
+        Ok(Err(e)) if e.kind() == ErrorKind::ConnectionRefused => Ok(Some(start.elapsed())),
+        Ok(Err(_)) | Err(_) => Ok(None),
+    }
+}
+
+#[derive(Clone)]
+pub struct PingAdapter {
+    app_handle: Option<AppHandle>,
+    backends: Backends,
+}
+
+impl PingAdapter {
+    pub fn new() -> Self {
+        PingAdapter {
+            app_handle: None,
+            backends: Backends::default(),
+        }
+    }
+
+    pub fn with_app_handle(app_handle: AppHandle) -> Self {
+        PingAdapter {
+            backends: state::backends_for(Some(&app_handle)),
+            app_handle: Some(app_handle),
+        }
+    }
+
+    // Probe and resolve through these instead of the app's backends
+    pub fn with_backends(mut self, backends: Backends) -> Self {
+        self.backends = backends;
+        self
+    }
+
+    fn emit_log(&self, log: CommandLog) {
+        command_logs::record(self.app_handle.as_ref(), log);
+    }
+
+    // Ping each address of host concurrently, count probes apiece, calling
+    // on_address with each address's stats and the count done as it finishes
+    #[instrument(skip_all, fields(host = %host, count, port))]
+    pub async fn ping(
+        &self,
+        host: &str,
+        count: u32,
+        port: u16,
+        on_address: impl Fn(&PingStats, usize, usize),
+    ) -> Result<PingReport, String> {
+        let start = Instant::now();
+        let addresses = DnsAdapter::from_app_handle(self.app_handle.clone())
+            .with_backends(self.backends.clone())
+            .addresses(host)
+            .await?;
+
+        let mut tasks = JoinSet::new();
+        for (index, address) in addresses.into_iter().enumerate() {
+            let adapter = self.clone();
+            let host = host.to_string();
+            tasks.spawn(async move {
+                (
+                    index,
+                    adapter.ping_address(&host, address, count, port).await,
+                )
+            });
+        }
+
+        let total = tasks.len();
+        let mut results = Vec::new();
+        while let Some(joined) = tasks.join_next().await {
+            if let Ok(entry) = joined {
+                results.push(entry);
+                on_address(&results[results.len() - 1].1, results.len(), total);
+            }
+        }
+        results.sort_by_key(|(index, _)| *index);
+
+        Ok(PingReport {
+            host: host.to_string(),
+            addresses: results.into_iter().map(|(_, stats)| stats).collect(),
+            total_time: start.elapsed().as_secs_f64(),
+        })
+    }
+
+    // ICMP first. Where that isn't permitted, or the host drops every echo (as
+    // many firewalls do), the same number of TCP connects are timed instead.
+    pub async fn ping_address(
+        &self,
+        host: &str,
+        address: IpAddr,
+        count: u32,
+        port: u16,
+    ) -> PingStats {
+        let start = Instant::now();
+        let waited =
+            rate_limiter::throttle(self.app_handle.as_ref(), Target::Host, &address.to_string())
+                .await;
+
+        let stats = match self.probe(PingMethod::Icmp, address, count, port).await {
+            Ok(samples) if samples.iter().any(Option::is_some) => {
+                summarize(address, PingMethod::Icmp, None, &samples)
+            }
+            icmp => {
+                let samples = self
+                    .probe(PingMethod::Tcp, address, count, port)
+                    .await
+                    .unwrap_or_default();
+                let mut stats = summarize(address, PingMethod::Tcp, Some(port), &samples);
+                stats.icmp_error = Some(match icmp {
+                    Err(e) => format!("ICMP not permitted: {}", e),
+                    Ok(_) => "No ICMP echo replies".to_string(),
+                });
+                stats
+            }
+        };
+
+        let (tool, args) = match stats.method {
+            PingMethod::Icmp => ("ping", vec!["-c".to_string(), count.to_string()]),
+            PingMethod::Tcp => ("tcping", vec!["-p".to_string(), port.to_string()]),
+        };
+        self.emit_log(
+            CommandLog::new(
+                tool.to_string(),
+                [args, vec![address.to_string()]].concat(),
+                log_output(&stats),
+                if stats.received > 0 { 0 } else { 1 },
+                start.elapsed().as_secs_f64() * 1000.0,
+                Some(host.to_string()),
+            )
+            .with_throttled(waited),
+        );
+        stats
+    }
+
+    // count probes PROBE_INTERVAL apart; fails only when ICMP isn't permitted
+    async fn probe(
+        &self,
+        method: PingMethod,
+        address: IpAddr,
+        count: u32,
+        port: u16,
+    ) -> Result<Vec<Option<Duration>>, String> {
+        let mut samples = Vec::new();
+        for sequence in 0..count {
+            if sequence > 0 {
+                tokio::time::sleep(PROBE_INTERVAL).await;
+            }
+            let rtt = match method {
+                PingMethod::Icmp => {
+                    self.backends
+                        .ping
+                        .echo(address, sequence as u16, PROBE_TIMEOUT)
+                        .await?
+                }
+                PingMethod::Tcp => self
+                    .backends
+                    .ping
+                    .connect(address, port, PROBE_TIMEOUT)
+                    .await
+                    .unwrap_or(None),
+            };
+            samples.push(rtt);
+        }
+        Ok(samples)
+    }
+}
+
+// Loss and min/avg/max/jitter of a run of probes, in milliseconds
+pub fn summarize(
+    address: IpAddr,
+    method: PingMethod,
+    port: Option<u16>,
+    samples: &[Option<Duration>],
+) -> PingStats {
+    let samples_ms: Vec<Option<f64>> = samples
+        .iter()
+        .map(|rtt| rtt.map(|rtt| rtt.as_secs_f64() * 1000.0))
+        .collect();
+    let received: Vec<f64> = samples_ms.iter().flatten().copied().collect();
+    let sent = samples.len() as u32;
+
+    let (min_ms, avg_ms, max_ms) = if received.is_empty() {
+        (None, None, None)
+    } else {
+        (
+            received.iter().copied().reduce(f64::min),
+            Some(received.iter().sum::<f64>() / received.len() as f64),
+            received.iter().copied().reduce(f64::max),
+        )
+    };
+    let jitter_ms = (received.len() > 1).then(|| {
+        received
+            .windows(2)
+            .map(|pair| (pair[1] - pair[0]).abs())
+            .sum::<f64>()
+            / (received.len() - 1) as f64
+    });
+
+    PingStats {
+        address: address.to_string(),
+        method,
+        port,
+        sent,
+        received: received.len() as u32,
+        loss_percent: if sent == 0 {
+            0.0
+        } else {
+            (sent - received.len() as u32) as f64 * 100.0 / sent as f64
+        },
+        samples_ms,
+        min_ms,
+        avg_ms,
+        max_ms,
+        jitter_ms,
+        icmp_error: None,
+    }
+}
+
+// ping-style transcript for the command log
+fn log_output(stats: &PingStats) -> String {
+    let mut lines: Vec<String> = stats
+        .samples_ms
+        .iter()
+        .enumerate()
+        .map(|(sequence, rtt)| match rtt {
+            Some(rtt) => format!("seq={} time={:.3} ms", sequence, rtt),
+            None => format!("seq={} timeout", sequence),
+        })
+        .collect();
+    if let Some(error) = &stats.icmp_error {
+        lines.insert(0, format!("{}; timing TCP connects instead", error));
+    }
+    lines.push(format!(
+        "{} probes, {} received, {:.1}% loss",
+        stats.sent, stats.received, stats.loss_percent
+    ));
+    if let (Some(min), Some(avg), Some(max)) = (stats.min_ms, stats.avg_ms, stats.max_ms) {
+        lines.push(format!(
+            "min/avg/max/jitter = {:.3}/{:.3}/{:.3}/{:.3} ms",
+            min,
+            avg,
+            max,
+            stats.jitter_ms.unwrap_or(0.0)
+        ));
+    }
+    lines.join("\n")
+}
+
+#[cfg(test)]
+#[path = "ping_test.rs"]
+mod tests;
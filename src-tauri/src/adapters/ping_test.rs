@@ -0,0 +1,116 @@
+use super::super::backend::{BackendFuture, Backends, PingBackend};
+use super::{echo_request, is_echo_reply, summarize, PingAdapter};
+use crate::models::ping::PingMethod;
+use std::net::IpAddr;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+fn millis(samples: &[Option<u64>]) -> Vec<Option<Duration>> {
+    samples
+        .iter()
+        .map(|ms| ms.map(Duration::from_millis))
+        .collect()
+}
+
+#[test]
+fn test_summarize() {
+    let address: IpAddr = "192.0.2.1".parse().unwrap();
+    let stats = summarize(
+        address,
+        PingMethod::Icmp,
+        None,
+        &millis(&[Some(10), None, Some(14), Some(12)]),
+    );
+
+    assert_eq!(stats.sent, 4);
+    assert_eq!(stats.received, 3);
+    assert_eq!(stats.loss_percent, 25.0);
+    assert_eq!(stats.min_ms, Some(10.0));
+    assert_eq!(stats.avg_ms, Some(12.0));
+    assert_eq!(stats.max_ms, Some(14.0));
+    // |14 - 10| and |12 - 14|
+    assert_eq!(stats.jitter_ms, Some(3.0));
+    assert_eq!(stats.samples_ms[1], None);
+}
+
+#[test]
+fn test_summarize_all_lost() {
+    let address: IpAddr = "2001:db8::1".parse().unwrap();
+    let stats = summarize(address, PingMethod::Tcp, Some(443), &millis(&[None, None]));
+
+    assert_eq!(stats.received, 0);
+    assert_eq!(stats.loss_percent, 100.0);
+    assert_eq!(stats.avg_ms, None);
+    assert_eq!(stats.jitter_ms, None);
+}
+
+#[test]
+fn test_echo_reply_matching() {
+    let mut reply = echo_request(true, 3);
+    reply[0] = 0;
+    assert!(is_echo_reply(&reply, true, 3));
+    assert!(!is_echo_reply(&reply, true, 4));
+    assert!(!is_echo_reply(&echo_request(true, 3), true, 3));
+
+    // macOS prefixes the IPv4 header
+    let mut datagram = vec![0x45];
+    datagram.extend_from_slice(&[0; 19]);
+    datagram.extend_from_slice(&reply);
+    assert!(is_echo_reply(&datagram, true, 3));
+
+    let mut reply = echo_request(false, 1);
+    reply[0] = 129;
+    assert!(is_echo_reply(&reply, false, 1));
+}
+
+// ICMP isn't permitted; TCP connects answer in 5ms
+struct NoIcmp {
+    ports: Arc<Mutex<Vec<u16>>>,
+}
+
+impl PingBackend for NoIcmp {
+    fn echo<'a>(&'a self, _: IpAddr, _: u16, _: Duration) -> BackendFuture<'a, Option<Duration>> {
+        Box::pin(async { Err("Operation not permitted (os error 1)".to_string()) })
+    }
+
+    fn connect<'a>(
+        &'a self,
+        _: IpAddr,
+        port: u16,
+        _: Duration,
+    ) -> BackendFuture<'a, Option<Duration>> {
+        self.ports.lock().unwrap().push(port);
+        Box::pin(async { Ok(Some(Duration::from_millis(5))) })
+    }
+}
+
+#[tokio::test]
+async fn test_ping_falls_back_to_tcp() {
+    let ports = Arc::new(Mutex::new(Vec::new()));
+    let adapter = PingAdapter::new().with_backends(Backends::default().with_ping(NoIcmp {
+        ports: ports.clone(),
+    }));
+
+    let progress = Mutex::new(Vec::new());
+    let report = adapter
+        .ping("192.0.2.1", 2, 8443, |stats, done, total| {
+            progress
+                .lock()
+                .unwrap()
+                .push((stats.address.clone(), done, total));
+        })
+        .await
+        .unwrap();
+    assert_eq!(
+        *progress.lock().unwrap(),
+        vec![("192.0.2.1".to_string(), 1, 1)]
+    );
+    assert_eq!(report.addresses.len(), 1);
+    let stats = &report.addresses[0];
+    assert_eq!(stats.method, PingMethod::Tcp);
+    assert_eq!(stats.port, Some(8443));
+    assert_eq!(stats.received, 2);
+    assert_eq!(stats.avg_ms, Some(5.0));
+    assert!(stats.icmp_error.as_ref().unwrap().contains("not permitted"));
+    assert_eq!(*ports.lock().unwrap(), vec![8443, 8443]);
+}
@@ -0,0 +1,274 @@
+use crate::adapters::backend::{BackendFuture, Backends, PortBackend};
+use crate::adapters::dns::DnsAdapter;
+use crate::models::command_log::CommandLog;
+use crate::models::ports::{PortResult, PortScan, PortState};
+use crate::state;
+use crate::state::command_logs;
+use crate::state::rate_limiter::{self, Target};
+use std::io::ErrorKind;
+use std::net::IpAddr;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tauri::AppHandle;
+use tokio::io::AsyncReadExt;
+use tokio::net::TcpStream;
+use tokio::sync::Semaphore;
+use tokio::task::JoinSet;
+use tracing::instrument;
+
+// Usual service on each port named in results
+const SERVICES: [(u16, &str); 24] = [
+    (21, "ftp"),
+    (22, "ssh"),
+    (23, "telnet"),
+    (25, "smtp"),
+    (53, "domain"),
+    (80, "http"),
+    (110, "pop3"),
+    (143, "imap"),
+    (443, "https"),
+    (465, "smtps"),
+    (587, "submission"),
+    (636, "ldaps"),
+    (853, "domain-s"),
+    (993, "imaps"),
+    (995, "pop3s"),
+    (1433, "ms-sql"),
+    (3306, "mysql"),
+    (3389, "rdp"),
+    (5432, "postgresql"),
+    (5900, "vnc"),
+    (6379, "redis"),
+    (8080, "http-alt"),
+    (8443, "https-alt"),
+    (27017, "mongodb"),
+];
+
+// Implicit TLS ports, where the client speaks first so there's no banner to wait for
+const TLS_PORTS: [u16; 7] = [443, 465, 636, 853, 993, 995, 8443];
+
+// How long to wait for a connection, and then for a banner
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(3);
+const BANNER_TIMEOUT: Duration = Duration::from_millis(1500);
+
+// Longest banner kept, in characters
+const MAX_BANNER_CHARS: usize = 200;
+
+// What one probe found
+#[derive(Debug, Clone)]
+pub struct PortProbe {
+    pub state: PortState,
+    pub banner: Option<String>,
+    pub time: Duration,
+    pub error: Option<String>,
+}
+
+// Plain TCP connects
+pub struct TcpPorts;
+
+impl PortBackend for TcpPorts {
+    fn probe<'a>(
+        &'a self,
+        address: IpAddr,
+        port: u16,
+        timeout: Duration,
+        banner_timeout: Option<Duration>,
+    ) -> BackendFuture<'a, PortProbe> {
+        Box::pin(async move {
+            let start = Instant::now();
+            let probe = |state, error| PortProbe {
+                state,
+                banner: None,
+                time: start.elapsed(),
+                error,
+            };
+            let mut stream =
+                match tokio::time::timeout(timeout, TcpStream::connect((address, port))).await {
+                    Ok(Ok(stream)) => stream,
+                    Ok(Err(e)) if e.kind() == ErrorKind::ConnectionRefused => {
+                        return Ok(probe(PortState::Closed, None))
+                    }
+                    Ok(Err(e)) => return Ok(probe(PortState::Filtered, Some(e.to_string()))),
+                    Err(_) => return Ok(probe(PortState::Filtered, None)),
+                };
+
+            let mut result = probe(PortState::Open, None);
+            if let Some(banner_timeout) = banner_timeout {
+                let mut buffer = [0u8; 512];
+                if let Ok(Ok(read)) =
+                    tokio::time::timeout(banner_timeout, stream.read(&mut buffer)).await
+                {
+                    result.banner = banner(&buffer[..read]);
+                }
+            }
+            Ok(result)
+        })
+    }
+}
+
+// First line of what a service sent, printable characters only
+pub fn banner(bytes: &[u8]) -> Option<String> {
+    let line = bytes.split(|&b| b == b'\n' || b == b'\r').next()?;
+    let text: String = String::from_utf8_lossy(line)
+        .chars()
+        .filter(|c| !c.is_control() && *c != char::REPLACEMENT_CHARACTER)
+        .take(MAX_BANNER_CHARS)
+        .collect();
+    let text = text.trim();
+    (!text.is_empty()).then(|| text.to_string())
+}
+
+pub fn service_name(port: u16) -> Option<&'static str> {
+    SERVICES
+        .iter()
+        .find(|(known, _)| *known == port)
+        .map(|(_, name)| *name)
+}
+
+#[derive(Clone)]
+pub struct PortScanAdapter {
+    app_handle: Option<AppHandle>,
+    backends: Backends,
+}
+
+impl PortScanAdapter {
+    pub fn new() -> Self {
+        PortScanAdapter {
+            app_handle: None,
+            backends: Backends::default(),
+        }
+    }
+
+    pub fn with_app_handle(app_handle: AppHandle) -> Self {
+        PortScanAdapter {
+            backends: state::backends_for(Some(&app_handle)),
+            app_handle: Some(app_handle),
+        }
+    }
+
+    // Connect and resolve through these instead of the app's backends
+    pub fn with_backends(mut self, backends: Backends) -> Self {
+        self.backends = backends;
+        self
+    }
+
+    fn emit_log(&self, log: CommandLog) {
+        command_logs::record(self.app_handle.as_ref(), log);
+    }
+
+    // Connect to each port of host's first address, concurrently (bounded by
+    // max_concurrency) and within the per-host rate limit
+    #[instrument(skip_all, fields(host = %host, ports = ports.len()))]
+    pub async fn scan(&self, host: &str, ports: &[u16]) -> Result<PortScan, String> {
+        let start = Instant::now();
+        let address = DnsAdapter::from_app_handle(self.app_handle.clone())
+            .with_backends(self.backends.clone())
+            .first_address(host)
+            .await?;
+        let settings = state::settings_for(self.app_handle.as_ref());
+        let permits = Arc::new(Semaphore::new(settings.max_concurrency.max(1)));
+        let mut tasks = JoinSet::new();
+
+        for (index, port) in ports.iter().copied().enumerate() {
+            let adapter = self.clone();
+            let permits = permits.clone();
+            tasks.spawn(async move {
+                let _permit = permits.acquire_owned().await;
+                let waited = rate_limiter::throttle(
+                    adapter.app_handle.as_ref(),
+                    Target::Host,
+                    &address.to_string(),
+                )
+                .await;
+                (index, adapter.scan_port(address, port).await, waited)
+            });
+        }
+
+        let mut results = Vec::new();
+        let mut throttled = Duration::ZERO;
+        while let Some(joined) = tasks.join_next().await {
+            if let Ok((index, result, waited)) = joined {
+                results.push((index, result));
+                throttled += waited;
+            }
+        }
+        results.sort_by_key(|(index, _)| *index);
+        let results: Vec<PortResult> = results.into_iter().map(|(_, result)| result).collect();
+
+        let ports_arg: Vec<String> = ports.iter().map(u16::to_string).collect();
+        self.emit_log(
+            CommandLog::new(
+                "portscan".to_string(),
+                vec!["-p".to_string(), ports_arg.join(","), address.to_string()],
+                self.log_output(&results),
+                0,
+                start.elapsed().as_secs_f64() * 1000.0,
+                Some(host.to_string()),
+            )
+            .with_throttled(throttled),
+        );
+
+        Ok(PortScan {
+            host: host.to_string(),
+            address: address.to_string(),
+            open: results
+                .iter()
+                .filter(|r| r.state == PortState::Open)
+                .count(),
+            results,
+            total_time: start.elapsed().as_secs_f64(),
+        })
+    }
+
+    async fn scan_port(&self, address: IpAddr, port: u16) -> PortResult {
+        let banner_timeout = (!TLS_PORTS.contains(&port)).then_some(BANNER_TIMEOUT);
+        let probe = self
+            .backends
+            .ports
+            .probe(address, port, CONNECT_TIMEOUT, banner_timeout)
+            .await
+            .unwrap_or_else(|e| PortProbe {
+                state: PortState::Filtered,
+                banner: None,
+                time: Duration::ZERO,
+                error: Some(e),
+            });
+        PortResult {
+            port,
+            service: service_name(port).map(str::to_string),
+            state: probe.state,
+            banner: probe.banner,
+            time_ms: probe.time.as_secs_f64() * 1000.0,
+            error: probe.error,
+        }
+    }
+
+    // nmap-style table for the command log
+    fn log_output(&self, results: &[PortResult]) -> String {
+        results
+            .iter()
+            .map(|result| {
+                let state = match result.state {
+                    PortState::Open => "open",
+                    PortState::Closed => "closed",
+                    PortState::Filtered => "filtered",
+                };
+                let line = format!(
+                    "{:<9} {:<8} {}",
+                    format!("{}/tcp", result.port),
+                    state,
+                    result.service.as_deref().unwrap_or("")
+                );
+                match &result.banner {
+                    Some(banner) => format!("{}  {}", line, banner),
+                    None => line.trim_end().to_string(),
+                }
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
+#[cfg(test)]
+#[path = "ports_test.rs"]
+mod tests;
@@ -0,0 +1,112 @@
+use super::super::backend::{BackendFuture, Backends, PortBackend};
+use super::{banner, service_name, PortProbe, PortScanAdapter};
+use crate::models::ports::PortState;
+use std::net::IpAddr;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::io::AsyncWriteExt;
+use tokio::net::TcpListener;
+
+#[test]
+fn test_banner() {
+    assert_eq!(
+        banner(b"SSH-2.0-OpenSSH_9.6p1 Ubuntu-3ubuntu13\r\n").as_deref(),
+        Some("SSH-2.0-OpenSSH_9.6p1 Ubuntu-3ubuntu13")
+    );
+    assert_eq!(
+        banner(b"220 mail.example.com ESMTP Postfix\r\n250 second line").as_deref(),
+        Some("220 mail.example.com ESMTP Postfix")
+    );
+    assert_eq!(banner(b"\x00\x01\r\n"), None);
+    assert_eq!(banner(b""), None);
+}
+
+#[test]
+fn test_service_name() {
+    assert_eq!(service_name(587), Some("submission"));
+    assert_eq!(service_name(49152), None);
+}
+
+// 22 greets, 80 is open and silent, 25 refuses and everything else times out
+struct CannedPorts {
+    banner_waits: Arc<Mutex<Vec<u16>>>,
+}
+
+impl PortBackend for CannedPorts {
+    fn probe<'a>(
+        &'a self,
+        _: IpAddr,
+        port: u16,
+        _: Duration,
+        banner_timeout: Option<Duration>,
+    ) -> BackendFuture<'a, PortProbe> {
+        if banner_timeout.is_some() {
+            self.banner_waits.lock().unwrap().push(port);
+        }
+        let (state, banner) = match port {
+            22 => (PortState::Open, Some("SSH-2.0-OpenSSH_9.6".to_string())),
+            80 | 443 => (PortState::Open, None),
+            25 => (PortState::Closed, None),
+            _ => (PortState::Filtered, None),
+        };
+        Box::pin(async move {
+            Ok(PortProbe {
+                state,
+                banner,
+                time: Duration::from_millis(12),
+                error: None,
+            })
+        })
+    }
+}
+
+#[tokio::test]
+async fn test_scan_with_canned_ports() {
+    let banner_waits = Arc::new(Mutex::new(Vec::new()));
+    let adapter =
+        PortScanAdapter::new().with_backends(Backends::default().with_ports(CannedPorts {
+            banner_waits: banner_waits.clone(),
+        }));
+
+    let scan = adapter
+        .scan("192.0.2.1", &[22, 25, 80, 443, 3389])
+        .await
+        .unwrap();
+    assert_eq!(scan.address, "192.0.2.1");
+    assert_eq!(scan.open, 3);
+    let states: Vec<PortState> = scan.results.iter().map(|r| r.state).collect();
+    assert_eq!(
+        states,
+        [
+            PortState::Open,
+            PortState::Closed,
+            PortState::Open,
+            PortState::Open,
+            PortState::Filtered
+        ]
+    );
+    assert_eq!(scan.results[0].service.as_deref(), Some("ssh"));
+    assert_eq!(
+        scan.results[0].banner.as_deref(),
+        Some("SSH-2.0-OpenSSH_9.6")
+    );
+    // No banner is waited for on implicit TLS ports
+    assert!(!banner_waits.lock().unwrap().contains(&443));
+}
+
+#[tokio::test]
+async fn test_scan_local_listener() {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let port = listener.local_addr().unwrap().port();
+    tokio::spawn(async move {
+        let (mut stream, _) = listener.accept().await.unwrap();
+        stream.write_all(b"220 ready\r\n").await.unwrap();
+    });
+
+    let scan = PortScanAdapter::new()
+        .scan("127.0.0.1", &[port])
+        .await
+        .unwrap();
+    assert_eq!(scan.results[0].state, PortState::Open);
+    assert_eq!(scan.results[0].banner.as_deref(), Some("220 ready"));
+}
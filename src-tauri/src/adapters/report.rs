@@ -0,0 +1,893 @@
+use crate::models::certificate::CertificateInfo;
+use crate::models::history::{FieldChange, RecordChange, RedirectChange, ScanDiff};
+use crate::models::report::{
+    BulkScanRow, ComparisonRow, DomainComparison, DomainReport, ExportFormat, HttpCheck, Severity,
+};
+use std::collections::{BTreeMap, BTreeSet};
+
+// Column titles that mark a CSV header row and the column holding the domain
+const DOMAIN_COLUMNS: [&str; 7] = [
+    "domain",
+    "domain name",
+    "domains",
+    "host",
+    "hostname",
+    "url",
+    "website",
+];
+
+// Sections diff compares; any missing from either scan is listed as skipped
+const COMPARED_SECTIONS: [&str; 5] = ["dns", "certificate", "whois", "http", "health"];
+
+// Section and field of a compare row, and its values in one report
+type ComparedField = (
+    &'static str,
+    &'static str,
+    fn(&ReportAdapter, &DomainReport) -> Vec<String>,
+);
+
+// Section of an exported report, rendered as Markdown or HTML
+enum Block {
+    Heading(u8, String),
+    Paragraph(String),
+    List(Vec<String>),
+    Table(Vec<&'static str>, Vec<Vec<String>>),
+}
+
+#[derive(Clone)]
+pub struct ReportAdapter;
+
+impl ReportAdapter {
+    pub fn new() -> Self {
+        ReportAdapter
+    }
+
+    // What changed between an older report and a newer one of the same domain
+    pub fn diff(&self, from: &DomainReport, to: &DomainReport) -> ScanDiff {
+        let skipped: Vec<String> = COMPARED_SECTIONS
+            .iter()
+            .filter(|section| !has_section(from, section) || !has_section(to, section))
+            .map(|section| section.to_string())
+            .collect();
+
+        let records = self.record_changes(from, to);
+
+        let (nameservers_added, nameservers_removed) =
+            match (self.nameservers(from), self.nameservers(to)) {
+                (Some(old), Some(new)) => (
+                    new.difference(&old).cloned().collect(),
+                    old.difference(&new).cloned().collect(),
+                ),
+                _ => (vec![], vec![]),
+            };
+
+        let old_leaf = leaf_certificate(from);
+        let new_leaf = leaf_certificate(to);
+        let certificate_renewed = matches!((old_leaf, new_leaf),
+            (Some(old), Some(new)) if old.fingerprint_sha256 != new.fingerprint_sha256);
+        let mut certificate = Vec::new();
+        if let (Some(old), Some(new)) = (old_leaf, new_leaf) {
+            push_change(
+                &mut certificate,
+                "fingerprint",
+                Some(old.fingerprint_sha256.clone()),
+                Some(new.fingerprint_sha256.clone()),
+            );
+            push_change(
+                &mut certificate,
+                "issuer",
+                issuer_name(old),
+                issuer_name(new),
+            );
+            push_change(
+                &mut certificate,
+                "not_after",
+                Some(old.not_after.clone()),
+                Some(new.not_after.clone()),
+            );
+        }
+
+        let mut whois = Vec::new();
+        let mut whois_status_added = Vec::new();
+        let mut whois_status_removed = Vec::new();
+        if let (Some(old), Some(new)) = (&from.whois, &to.whois) {
+            push_change(
+                &mut whois,
+                "registrar",
+                old.registrar.clone(),
+                new.registrar.clone(),
+            );
+            push_change(
+                &mut whois,
+                "expiration_date",
+                old.expiration_date.clone(),
+                new.expiration_date.clone(),
+            );
+            push_change(&mut whois, "dnssec", old.dnssec.clone(), new.dnssec.clone());
+
+            let old_status: BTreeSet<&str> = old.status.iter().map(|s| s.code.as_str()).collect();
+            let new_status: BTreeSet<&str> = new.status.iter().map(|s| s.code.as_str()).collect();
+            whois_status_added = new_status
+                .difference(&old_status)
+                .map(|s| s.to_string())
+                .collect();
+            whois_status_removed = old_status
+                .difference(&new_status)
+                .map(|s| s.to_string())
+                .collect();
+        }
+
+        let mut redirects = Vec::new();
+        for old in &from.http {
+            let Some(new) = to.http.iter().find(|check| check.url == old.url) else {
+                continue;
+            };
+            let old_chain = redirect_chain(old);
+            let new_chain = redirect_chain(new);
+            if old_chain != new_chain {
+                redirects.push(RedirectChange {
+                    url: old.url.clone(),
+                    old_chain,
+                    new_chain,
+                });
+            }
+        }
+
+        let mut health = Vec::new();
+        if let (Some(old), Some(new)) = (&from.health, &to.health) {
+            push_change(
+                &mut health,
+                "score",
+                Some(old.score.to_string()),
+                Some(new.score.to_string()),
+            );
+            push_change(
+                &mut health,
+                "grade",
+                Some(old.grade.clone()),
+                Some(new.grade.clone()),
+            );
+        }
+
+        let changed = !records.is_empty()
+            || !nameservers_added.is_empty()
+            || !nameservers_removed.is_empty()
+            || !certificate.is_empty()
+            || !whois.is_empty()
+            || !whois_status_added.is_empty()
+            || !whois_status_removed.is_empty()
+            || !redirects.is_empty()
+            || !health.is_empty();
+
+        ScanDiff {
+            domain: to.domain.clone(),
+            from_id: from.scan_id,
+            to_id: to.scan_id,
+            from_scanned_at: from.started_at,
+            to_scanned_at: to.started_at,
+            records,
+            nameservers_added,
+            nameservers_removed,
+            certificate_renewed,
+            certificate,
+            whois,
+            whois_status_added,
+            whois_status_removed,
+            redirects,
+            health,
+            skipped,
+            changed,
+        }
+    }
+
+    // The report as a file to share: pretty-printed JSON, Markdown or a standalone HTML page
+    pub fn export(&self, report: &DomainReport, format: ExportFormat) -> Result<String, String> {
+        match format {
+            ExportFormat::Json => serde_json::to_string_pretty(report)
+                .map_err(|e| format!("Failed to serialize the report: {}", e)),
+            ExportFormat::Markdown => Ok(markdown(&self.document(report))),
+            ExportFormat::Html => Ok(html(
+                &format!("Domain report: {}", report.domain),
+                &self.document(report),
+            )),
+        }
+    }
+
+    // Every section the report has, in the order the app shows them
+    fn document(&self, report: &DomainReport) -> Vec<Block> {
+        let mut blocks = vec![Block::Heading(
+            1,
+            format!("Domain report: {}", report.domain),
+        )];
+        let mut scanned = format!(
+            "Scanned {} in {:.1} s.",
+            report.started_at.format("%Y-%m-%d %H:%M UTC"),
+            report.total_time
+        );
+        if let Some(id) = report.scan_id {
+            scanned.push_str(&format!(" Saved as scan {}.", id));
+        }
+        blocks.push(Block::Paragraph(scanned));
+
+        if let Some(health) = &report.health {
+            blocks.push(Block::Heading(2, "Health".to_string()));
+            blocks.push(Block::Paragraph(format!(
+                "Overall grade {} ({}/100).",
+                health.grade, health.score
+            )));
+            let rows = health
+                .categories
+                .iter()
+                .map(|c| {
+                    vec![
+                        c.category.clone(),
+                        c.grade.clone().unwrap_or_else(|| "-".to_string()),
+                        c.score
+                            .map(|s| s.to_string())
+                            .unwrap_or_else(|| "not graded".to_string()),
+                    ]
+                })
+                .collect();
+            blocks.push(Block::Table(vec!["Category", "Grade", "Score"], rows));
+            if !health.findings.is_empty() {
+                blocks.push(Block::Heading(3, "Findings".to_string()));
+                let rows = health
+                    .findings
+                    .iter()
+                    .map(|f| {
+                        let severity = match f.severity {
+                            Severity::Critical => "Critical",
+                            Severity::Warning => "Warning",
+                            Severity::Info => "Info",
+                        };
+                        vec![
+                            severity.to_string(),
+                            f.category.clone(),
+                            f.message.clone(),
+                            f.fix.clone(),
+                        ]
+                    })
+                    .collect();
+                blocks.push(Block::Table(
+                    vec!["Severity", "Category", "Finding", "Fix"],
+                    rows,
+                ));
+            }
+        }
+
+        if let Some(dns) = &report.dns {
+            blocks.push(Block::Heading(2, "DNS records".to_string()));
+            let rows = dns
+                .results
+                .iter()
+                .flat_map(|result| &result.records)
+                .map(|r| {
+                    vec![
+                        r.record_type.clone(),
+                        r.name.clone(),
+                        r.ttl.to_string(),
+                        r.value.clone(),
+                    ]
+                })
+                .collect();
+            blocks.push(Block::Table(vec!["Type", "Name", "TTL", "Value"], rows));
+            let failed: Vec<String> = dns
+                .results
+                .iter()
+                .filter_map(|r| Some(format!("{}: {}", r.record_type, r.error.as_ref()?)))
+                .collect();
+            if !failed.is_empty() {
+                blocks.push(Block::List(failed));
+            }
+        }
+
+        if let Some(dnssec) = &report.dnssec {
+            blocks.push(Block::Heading(2, "DNSSEC".to_string()));
+            blocks.push(Block::Paragraph(format!("Status: {}.", dnssec.status)));
+            if !dnssec.warnings.is_empty() {
+                blocks.push(Block::List(dnssec.warnings.clone()));
+            }
+        }
+
+        if let Some(tls) = &report.certificate {
+            blocks.push(Block::Heading(2, "Certificate".to_string()));
+            let chain = &tls.certificate_chain;
+            if let Some(leaf) = chain.certificates.first() {
+                let mut expires = leaf.not_after.clone();
+                if let Some(days) = leaf.days_until_expiry {
+                    expires.push_str(&format!(" ({} days)", days));
+                }
+                let key = match leaf.public_key_size {
+                    Some(size) => format!("{} {} bits", leaf.public_key_algorithm, size),
+                    None => leaf.public_key_algorithm.clone(),
+                };
+                let rows = vec![
+                    field("Subject", leaf.subject.common_name.clone()),
+                    field("Issuer", issuer_name(leaf)),
+                    field("Valid from", Some(leaf.not_before.clone())),
+                    field("Expires", Some(expires)),
+                    field("Names", Some(leaf.subject_alternative_names.join(", "))),
+                    field("Key", Some(key)),
+                    field(
+                        "Fingerprint (SHA-256)",
+                        Some(leaf.fingerprint_sha256.clone()),
+                    ),
+                    field(
+                        "Chain",
+                        Some(if chain.is_valid { "valid" } else { "invalid" }.to_string()),
+                    ),
+                ];
+                blocks.push(Block::Table(vec!["Field", "Value"], rows));
+            }
+            let problems: Vec<String> = chain
+                .validation_errors
+                .iter()
+                .chain(&tls.warnings)
+                .cloned()
+                .collect();
+            if !problems.is_empty() {
+                blocks.push(Block::List(problems));
+            }
+        }
+
+        if let Some(whois) = &report.whois {
+            blocks.push(Block::Heading(2, "Registration".to_string()));
+            let mut expires = whois.expiration_date.clone();
+            if let (Some(date), Some(days)) = (&mut expires, whois.days_until_expiry) {
+                date.push_str(&format!(" ({} days)", days));
+            }
+            let statuses: Vec<&str> = whois.status.iter().map(|s| s.code.as_str()).collect();
+            let rows = vec![
+                field("Registrar", whois.registrar.clone()),
+                field("Created", whois.creation_date.clone()),
+                field("Updated", whois.updated_date.clone()),
+                field("Expires", expires),
+                field("Nameservers", Some(whois.nameservers.join(", "))),
+                field("Status", Some(statuses.join(", "))),
+                field("DNSSEC", whois.dnssec.clone()),
+            ];
+            blocks.push(Block::Table(vec!["Field", "Value"], rows));
+        }
+
+        if !report.http.is_empty() {
+            blocks.push(Block::Heading(2, "HTTP".to_string()));
+            let rows = report
+                .http
+                .iter()
+                .map(|check| match &check.response {
+                    Some(response) => vec![
+                        check.url.clone(),
+                        response.status_code.to_string(),
+                        response.final_url.clone(),
+                        response.redirects.len().to_string(),
+                        format!("{:.0} ms", response.response_time * 1000.0),
+                    ],
+                    None => vec![
+                        check.url.clone(),
+                        "error".to_string(),
+                        check.error.clone().unwrap_or_default(),
+                        "-".to_string(),
+                        "-".to_string(),
+                    ],
+                })
+                .collect();
+            blocks.push(Block::Table(
+                vec!["URL", "Status", "Final URL", "Redirects", "Time"],
+                rows,
+            ));
+        }
+
+        if let Some(email) = &report.email {
+            blocks.push(Block::Heading(2, "Email".to_string()));
+            let mx: Vec<String> = email
+                .mx_records
+                .iter()
+                .map(|mx| format!("{} {}", mx.priority, mx.hostname))
+                .collect();
+            let dkim: Vec<&str> = email
+                .dkim
+                .records
+                .iter()
+                .map(|r| r.selector.as_str())
+                .collect();
+            let providers: Vec<&str> = email.providers.iter().map(|p| p.name.as_str()).collect();
+            let rows = vec![
+                field(
+                    "MX",
+                    Some(if email.null_mx {
+                        "null MX: accepts no mail".to_string()
+                    } else {
+                        mx.join(", ")
+                    }),
+                ),
+                field(
+                    "STARTTLS",
+                    Some(
+                        if email.encrypted {
+                            "every MX"
+                        } else {
+                            "not every MX"
+                        }
+                        .to_string(),
+                    ),
+                ),
+                field("SPF", email.spf.as_ref().and_then(|s| s.record.clone())),
+                field("DKIM selectors", Some(dkim.join(", "))),
+                field("DMARC", email.dmarc.as_ref().map(|d| d.record.clone())),
+                field("Providers", Some(providers.join(", "))),
+            ];
+            blocks.push(Block::Table(vec!["Field", "Value"], rows));
+            if !email.warnings.is_empty() {
+                blocks.push(Block::List(email.warnings.clone()));
+            }
+        }
+
+        if !report.errors.is_empty() {
+            blocks.push(Block::Heading(2, "Checks that failed".to_string()));
+            blocks.push(Block::List(
+                report
+                    .errors
+                    .iter()
+                    .map(|e| format!("{}: {}", e.section, e.error))
+                    .collect(),
+            ));
+        }
+        blocks
+    }
+
+    // Domains from a CSV or plain-text list, with the rows that had none. A header
+    // row naming a domain column picks that column; otherwise the first cell that
+    // looks like a domain is used. URLs are reduced to their host.
+    pub fn parse_domain_list(&self, text: &str) -> (Vec<String>, Vec<String>) {
+        let mut domains: Vec<String> = Vec::new();
+        let mut skipped = Vec::new();
+        let mut column = None;
+        let mut first_row = true;
+        for (index, line) in text.trim_start_matches('\u{feff}').lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let cells: Vec<&str> = line
+                .split([',', ';', '\t'])
+                .map(|cell| cell.trim().trim_matches('"').trim())
+                .collect();
+            if std::mem::take(&mut first_row) {
+                if let Some(position) = cells
+                    .iter()
+                    .position(|cell| DOMAIN_COLUMNS.contains(&cell.to_lowercase().as_str()))
+                {
+                    column = Some(position);
+                    continue;
+                }
+            }
+
+            let found = match column {
+                Some(position) => cells.get(position).and_then(|cell| domain_from_cell(cell)),
+                None => cells.iter().find_map(|cell| domain_from_cell(cell)),
+            };
+            match found {
+                Some(domain) if !domains.contains(&domain) => domains.push(domain),
+                Some(_) => {}
+                None => skipped.push(format!("line {}: no domain in \"{}\"", index + 1, line)),
+            }
+        }
+        (domains, skipped)
+    }
+
+    // Two domains' reports aligned field by field, e.g. an old domain and the one
+    // replacing it; total_time is left for the caller
+    pub fn compare(&self, a: &DomainReport, b: &DomainReport) -> DomainComparison {
+        let fields: [ComparedField; 9] = [
+            ("dns", "nameservers", |adapter, report| {
+                adapter
+                    .nameservers(report)
+                    .map(|hosts| hosts.into_iter().collect())
+                    .unwrap_or_default()
+            }),
+            ("email", "mx", |_, report| mx_hosts(report)),
+            ("email", "spf", |_, report| {
+                let spf = report.email.as_ref().and_then(|e| e.spf.as_ref());
+                spf.and_then(|spf| spf.record.clone()).into_iter().collect()
+            }),
+            ("email", "dmarc_policy", |_, report| {
+                let dmarc = report.email.as_ref().and_then(|e| e.dmarc.as_ref());
+                dmarc
+                    .and_then(|dmarc| dmarc.policy.clone())
+                    .into_iter()
+                    .collect()
+            }),
+            ("email", "dmarc", |_, report| {
+                let dmarc = report.email.as_ref().and_then(|e| e.dmarc.as_ref());
+                dmarc
+                    .map(|dmarc| dmarc.record.clone())
+                    .into_iter()
+                    .collect()
+            }),
+            ("certificate", "issuer", |_, report| {
+                leaf_certificate(report)
+                    .and_then(issuer_name)
+                    .into_iter()
+                    .collect()
+            }),
+            ("certificate", "not_after", |_, report| {
+                leaf_certificate(report)
+                    .map(|leaf| leaf.not_after.clone())
+                    .into_iter()
+                    .collect()
+            }),
+            ("http", "http_target", |_, report| {
+                redirect_target(report, "http://")
+            }),
+            ("http", "https_target", |_, report| {
+                redirect_target(report, "https://")
+            }),
+        ];
+
+        let rows: Vec<ComparisonRow> = fields
+            .iter()
+            .map(|(section, field, value)| {
+                let (a, b) = (value(self, a), value(self, b));
+                ComparisonRow {
+                    section: section.to_string(),
+                    field: field.to_string(),
+                    same: a == b,
+                    a,
+                    b,
+                }
+            })
+            .collect();
+
+        DomainComparison {
+            domain_a: a.domain.clone(),
+            domain_b: b.domain.clone(),
+            same: rows.iter().all(|row| row.same),
+            rows,
+            errors_a: a.errors.clone(),
+            errors_b: b.errors.clone(),
+            total_time: 0.0,
+        }
+    }
+
+    // Summary table row for a bulk scan; completed and total are left for the caller
+    pub fn summary_row(&self, report: &DomainReport) -> BulkScanRow {
+        let health = report.health.as_ref();
+        let count = |severity: Severity| {
+            health.map_or(0, |h| {
+                h.findings.iter().filter(|f| f.severity == severity).count()
+            })
+        };
+        let https = report
+            .http
+            .iter()
+            .find(|check| check.url.starts_with("https://"));
+        BulkScanRow {
+            domain: report.domain.clone(),
+            score: health
+                .filter(|h| h.categories.iter().any(|c| c.score.is_some()))
+                .map(|h| h.score),
+            grade: health
+                .filter(|h| h.categories.iter().any(|c| c.score.is_some()))
+                .map(|h| h.grade.clone()),
+            critical: count(Severity::Critical),
+            warnings: count(Severity::Warning),
+            dnssec: report.dnssec.as_ref().map(|d| d.status.clone()),
+            certificate_days: leaf_certificate(report).and_then(|leaf| leaf.days_until_expiry),
+            registration_days: report.whois.as_ref().and_then(|w| w.days_until_expiry),
+            https_status: https
+                .and_then(|check| check.response.as_ref())
+                .map(|response| response.status_code),
+            dmarc_policy: report
+                .email
+                .as_ref()
+                .and_then(|email| email.dmarc.as_ref())
+                .and_then(|dmarc| dmarc.policy.clone()),
+            errors: report.errors.clone(),
+            completed: 0,
+            total: 0,
+        }
+    }
+
+    // Records keyed by type, owner name and value. Types that failed to resolve in
+    // either scan are left out rather than reported as removed or added.
+    fn record_changes(&self, from: &DomainReport, to: &DomainReport) -> Vec<RecordChange> {
+        let (Some(old), Some(new)) = (&from.dns, &to.dns) else {
+            return vec![];
+        };
+        let failed: BTreeSet<&str> = old
+            .results
+            .iter()
+            .chain(&new.results)
+            .filter(|result| result.error.is_some())
+            .map(|result| result.record_type.as_str())
+            .collect();
+        let index = |results: &[crate::models::dns::RecordTypeResult]| {
+            results
+                .iter()
+                .filter(|result| !failed.contains(result.record_type.as_str()))
+                .flat_map(|result| &result.records)
+                .map(|record| {
+                    let key = (
+                        record.record_type.clone(),
+                        record.name.to_lowercase(),
+                        record.value.clone(),
+                    );
+                    (key, record.ttl)
+                })
+                .collect::<BTreeMap<_, _>>()
+        };
+        let old_records = index(&old.results);
+        let new_records = index(&new.results);
+
+        let mut changes = Vec::new();
+        let keys: BTreeSet<_> = old_records.keys().chain(new_records.keys()).collect();
+        for key in keys {
+            let old_ttl = old_records.get(key).copied();
+            let new_ttl = new_records.get(key).copied();
+            let change = match (old_ttl, new_ttl) {
+                (None, Some(_)) => "added",
+                (Some(_), None) => "removed",
+                (Some(old), Some(new)) if old != new => "ttl",
+                _ => continue,
+            };
+            let (record_type, name, value) = key.clone();
+            changes.push(RecordChange {
+                change: change.to_string(),
+                record_type,
+                name,
+                value,
+                old_ttl,
+                new_ttl,
+            });
+        }
+        changes
+    }
+
+    // Delegated nameservers, from the NS records, else as the registry lists them
+    fn nameservers(&self, report: &DomainReport) -> Option<BTreeSet<String>> {
+        let normalize = |host: &str| host.trim_end_matches('.').to_lowercase();
+        let from_dns = report.dns.as_ref().and_then(|dns| {
+            let ns = dns
+                .results
+                .iter()
+                .find(|result| result.record_type == "NS" && result.error.is_none())?;
+            Some(
+                ns.records
+                    .iter()
+                    .map(|record| normalize(&record.value))
+                    .collect::<BTreeSet<_>>(),
+            )
+        });
+        match from_dns {
+            Some(hosts) if !hosts.is_empty() => Some(hosts),
+            _ => report.whois.as_ref().map(|whois| {
+                whois
+                    .nameservers
+                    .iter()
+                    .map(|host| normalize(host))
+                    .collect()
+            }),
+        }
+    }
+}
+
+fn has_section(report: &DomainReport, section: &str) -> bool {
+    match section {
+        "dns" => report.dns.is_some(),
+        "certificate" => report.certificate.is_some(),
+        "whois" => report.whois.is_some(),
+        "http" => !report.http.is_empty(),
+        _ => report.health.is_some(),
+    }
+}
+
+fn leaf_certificate(report: &DomainReport) -> Option<&CertificateInfo> {
+    report
+        .certificate
+        .as_ref()
+        .and_then(|tls| tls.certificate_chain.certificates.first())
+}
+
+fn issuer_name(certificate: &CertificateInfo) -> Option<String> {
+    certificate
+        .issuer
+        .common_name
+        .clone()
+        .or_else(|| certificate.issuer.organization.clone())
+}
+
+// "<preference> <host>" of each MX, most preferred first, from the email check or
+// else the DNS records
+fn mx_hosts(report: &DomainReport) -> Vec<String> {
+    let mut hosts: Vec<String> = match &report.email {
+        Some(email) => email
+            .mx_records
+            .iter()
+            .map(|mx| format!("{} {}", mx.priority, mx.hostname.trim_end_matches('.')))
+            .collect(),
+        None => report
+            .dns
+            .iter()
+            .flat_map(|dns| &dns.results)
+            .filter(|result| result.record_type == "MX")
+            .flat_map(|result| &result.records)
+            .map(|record| record.value.trim_end_matches('.').to_string())
+            .collect(),
+    };
+    for host in hosts.iter_mut() {
+        *host = host.to_lowercase();
+    }
+    hosts.sort_by_key(|host| {
+        let preference = host.split(' ').next().and_then(|p| p.parse::<u16>().ok());
+        (preference, host.clone())
+    });
+    hosts
+}
+
+// Where the first check of the scheme ended up, or the error that ended it
+fn redirect_target(report: &DomainReport, scheme: &str) -> Vec<String> {
+    let Some(check) = report
+        .http
+        .iter()
+        .find(|check| check.url.starts_with(scheme))
+    else {
+        return Vec::new();
+    };
+    vec![match &check.response {
+        Some(response) => response.final_url.clone(),
+        None => format!("error: {}", check.error.as_deref().unwrap_or("no response")),
+    }]
+}
+
+// "<status> <url>" for each hop and the final response, or the error that ended the check
+fn redirect_chain(check: &HttpCheck) -> Vec<String> {
+    match &check.response {
+        Some(response) => response
+            .redirects
+            .iter()
+            .map(|hop| format!("{} {}", hop.status_code, hop.from_url))
+            .chain([format!("{} {}", response.status_code, response.final_url)])
+            .collect(),
+        None => vec![format!(
+            "error: {}",
+            check.error.as_deref().unwrap_or("no response")
+        )],
+    }
+}
+
+// The host of a cell holding a domain or URL, lowercase without a trailing dot
+fn domain_from_cell(cell: &str) -> Option<String> {
+    let host = cell.split_once("://").map_or(cell, |(_, rest)| rest);
+    let host = host.split(['/', '?', '#']).next()?;
+    let host = host.rsplit_once('@').map_or(host, |(_, host)| host);
+    let host = match host.rsplit_once(':') {
+        Some((name, port)) if port.chars().all(|c| c.is_ascii_digit()) => name,
+        _ => host,
+    };
+    let host = host.trim_end_matches('.').to_lowercase();
+    let labels: Vec<&str> = host.split('.').collect();
+    let valid = labels.len() >= 2
+        && labels.iter().all(|label| {
+            !label.is_empty()
+                && !label.starts_with('-')
+                && !label.ends_with('-')
+                && label
+                    .chars()
+                    .all(|c| c.is_alphanumeric() || c == '-' || c == '_')
+        })
+        && labels
+            .last()
+            .is_some_and(|tld| !tld.chars().all(|c| c.is_ascii_digit()));
+    valid.then_some(host)
+}
+
+// A "Field | Value" row; missing or empty values show as "-"
+fn field(name: &str, value: Option<String>) -> Vec<String> {
+    let value = value
+        .filter(|v| !v.is_empty())
+        .unwrap_or_else(|| "-".to_string());
+    vec![name.to_string(), value]
+}
+
+fn markdown(blocks: &[Block]) -> String {
+    // Pipes would end a table cell and newlines the row
+    let cell = |text: &str| text.replace('|', "\\|").replace(['\r', '\n'], " ");
+    let mut out = String::new();
+    for block in blocks {
+        match block {
+            Block::Heading(level, text) => {
+                out.push_str(&format!("{} {}\n\n", "#".repeat(*level as usize), text))
+            }
+            Block::Paragraph(text) => out.push_str(&format!("{}\n\n", text)),
+            Block::List(items) => {
+                for item in items {
+                    out.push_str(&format!("- {}\n", item.replace('\n', " ")));
+                }
+                out.push('\n');
+            }
+            Block::Table(headers, rows) => {
+                out.push_str(&format!("| {} |\n", headers.join(" | ")));
+                out.push_str(&format!("|{}\n", " --- |".repeat(headers.len())));
+                for row in rows {
+                    let cells: Vec<String> = row.iter().map(|c| cell(c)).collect();
+                    out.push_str(&format!("| {} |\n", cells.join(" | ")));
+                }
+                out.push('\n');
+            }
+        }
+    }
+    out
+}
+
+const HTML_STYLE: &str = "body{font-family:system-ui,sans-serif;max-width:960px;margin:2em auto;padding:0 1em;color:#1f2328}\
+table{border-collapse:collapse;width:100%;margin-bottom:1em}\
+th,td{border:1px solid #d0d7de;padding:4px 8px;text-align:left;vertical-align:top;word-break:break-word}\
+th{background:#f6f8fa}h2{border-bottom:1px solid #d0d7de;padding-bottom:4px}";
+
+fn html(title: &str, blocks: &[Block]) -> String {
+    let mut body = String::new();
+    for block in blocks {
+        match block {
+            Block::Heading(level, text) => {
+                body.push_str(&format!("<h{0}>{1}</h{0}>\n", level, escape(text)))
+            }
+            Block::Paragraph(text) => body.push_str(&format!("<p>{}</p>\n", escape(text))),
+            Block::List(items) => {
+                body.push_str("<ul>\n");
+                for item in items {
+                    body.push_str(&format!("<li>{}</li>\n", escape(item)));
+                }
+                body.push_str("</ul>\n");
+            }
+            Block::Table(headers, rows) => {
+                body.push_str("<table>\n<tr>");
+                for header in headers {
+                    body.push_str(&format!("<th>{}</th>", escape(header)));
+                }
+                body.push_str("</tr>\n");
+                for row in rows {
+                    body.push_str("<tr>");
+                    for cell in row {
+                        body.push_str(&format!("<td>{}</td>", escape(cell)));
+                    }
+                    body.push_str("</tr>\n");
+                }
+                body.push_str("</table>\n");
+            }
+        }
+    }
+    format!(
+        "<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n\
+         <meta name=\"viewport\" content=\"width=device-width, initial-scale=1\">\n\
+         <title>{}</title>\n<style>{}</style>\n</head>\n<body>\n{}</body>\n</html>\n",
+        escape(title),
+        HTML_STYLE,
+        body
+    )
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+fn push_change(
+    changes: &mut Vec<FieldChange>,
+    field: &str,
+    old: Option<String>,
+    new: Option<String>,
+) {
+    if old != new {
+        changes.push(FieldChange {
+            field: field.to_string(),
+            old,
+            new,
+        });
+    }
+}
+
+#[cfg(test)]
+#[path = "report_test.rs"]
+mod tests;
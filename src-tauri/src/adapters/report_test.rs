@@ -0,0 +1,324 @@
+use super::super::health::HealthAdapter;
+use super::super::test_support::{http_check, records};
+use super::ReportAdapter;
+use crate::models::dns::RecordEnumeration;
+use crate::models::report::{DomainReport, ExportFormat, HttpCheck, SectionError};
+use crate::models::whois::{EppStatus, WhoisInfo};
+use serde_json::json;
+
+fn report(
+    id: i64,
+    a: &[(&str, u32)],
+    ns: &[(&str, u32)],
+    fingerprint: &str,
+    status: &[&str],
+    http: HttpCheck,
+) -> DomainReport {
+    let certificate = json!({
+        "subject": {}, "issuer": {"common_name": "R3"}, "serial_number": "01",
+        "version": 3, "not_before": "", "not_after": format!("{} not after", fingerprint),
+        "subject_alternative_names": ["example.com"], "public_key_algorithm": "EC",
+        "signature_algorithm": "ecdsa-with-SHA256", "fingerprint_sha256": fingerprint,
+    });
+    let mut whois: WhoisInfo = serde_json::from_value(json!({
+        "domain": "example.com", "registrar": "Example Registrar", "creation_date": null,
+        "expiration_date": "2030-01-01T00:00:00Z", "updated_date": null,
+        "nameservers": [], "status": [], "dnssec": null, "raw_output": "",
+    }))
+    .unwrap();
+    whois.status = status
+        .iter()
+        .map(|code| EppStatus::from_code(code))
+        .collect();
+
+    DomainReport {
+        domain: "example.com".to_string(),
+        started_at: chrono::Utc::now(),
+        total_time: 1.0,
+        dns: Some(RecordEnumeration {
+            domain: "example.com".to_string(),
+            results: vec![records("A", a), records("NS", ns)],
+            hosting: None,
+            total_time: 0.1,
+        }),
+        dnssec: None,
+        whois: Some(whois),
+        certificate: Some(
+            serde_json::from_value(json!({
+                "host": "example.com",
+                "port": 443,
+                "certificate_chain": {
+                    "certificates": [certificate],
+                    "is_valid": true,
+                    "validation_errors": [],
+                },
+            }))
+            .unwrap(),
+        ),
+        http: vec![http],
+        email: None,
+        errors: vec![],
+        health: None,
+        scan_id: Some(id),
+    }
+}
+
+fn redirecting() -> HttpCheck {
+    http_check(
+        "http://example.com",
+        &[(301, "http://example.com", "https://example.com/")],
+        "https://example.com/",
+        json!([]),
+    )
+}
+
+#[test]
+fn test_diff_unchanged() {
+    let old = report(
+        1,
+        &[("192.0.2.1", 300)],
+        &[("ns1.example.net.", 3600)],
+        "AA",
+        &["clientTransferProhibited"],
+        redirecting(),
+    );
+    let mut new = old.clone();
+    new.scan_id = Some(2);
+
+    let diff = ReportAdapter::new().diff(&old, &new);
+    assert!(!diff.changed);
+    assert!(diff.records.is_empty());
+    assert!(!diff.certificate_renewed);
+    assert_eq!(diff.from_id, Some(1));
+    assert_eq!(diff.to_id, Some(2));
+    assert_eq!(diff.skipped, vec!["health"]);
+}
+
+#[test]
+fn test_diff_changes() {
+    let old = report(
+        1,
+        &[("192.0.2.1", 300), ("192.0.2.2", 300)],
+        &[("ns1.example.net.", 3600), ("ns2.example.net.", 3600)],
+        "AA",
+        &["clientTransferProhibited"],
+        redirecting(),
+    );
+    let new = report(
+        2,
+        &[("192.0.2.1", 60), ("192.0.2.3", 60)],
+        &[("ns1.example.net.", 3600), ("NS3.example.org.", 3600)],
+        "BB",
+        &["clientTransferProhibited", "clientHold"],
+        http_check("http://example.com", &[], "http://example.com/", json!([])),
+    );
+
+    let diff = ReportAdapter::new().diff(&old, &new);
+    assert!(diff.changed);
+
+    let changes: Vec<(&str, &str, &str)> = diff
+        .records
+        .iter()
+        .map(|r| (r.change.as_str(), r.record_type.as_str(), r.value.as_str()))
+        .collect();
+    assert_eq!(
+        changes,
+        vec![
+            ("ttl", "A", "192.0.2.1"),
+            ("removed", "A", "192.0.2.2"),
+            ("added", "A", "192.0.2.3"),
+            ("added", "NS", "NS3.example.org."),
+            ("removed", "NS", "ns2.example.net."),
+        ]
+    );
+    assert_eq!(diff.records[0].old_ttl, Some(300));
+    assert_eq!(diff.records[0].new_ttl, Some(60));
+
+    assert_eq!(diff.nameservers_added, vec!["ns3.example.org"]);
+    assert_eq!(diff.nameservers_removed, vec!["ns2.example.net"]);
+
+    assert!(diff.certificate_renewed);
+    let fields: Vec<&str> = diff.certificate.iter().map(|c| c.field.as_str()).collect();
+    assert_eq!(fields, vec!["fingerprint", "not_after"]);
+
+    assert!(diff.whois.is_empty());
+    assert_eq!(diff.whois_status_added, vec!["clientHold"]);
+    assert!(diff.whois_status_removed.is_empty());
+
+    assert_eq!(diff.redirects.len(), 1);
+    assert_eq!(
+        diff.redirects[0].old_chain,
+        vec!["301 http://example.com", "200 https://example.com/"]
+    );
+    assert_eq!(diff.redirects[0].new_chain, vec!["200 http://example.com/"]);
+}
+
+#[test]
+fn test_compare_domains() {
+    // The old domain redirects to the new one, which has another nameserver
+    let mut old = report(
+        1,
+        &[("192.0.2.1", 300)],
+        &[("ns1.example.net.", 3600), ("ns2.example.net.", 3600)],
+        "AA",
+        &[],
+        http_check(
+            "http://example.com",
+            &[(301, "http://example.com", "https://example.org/")],
+            "https://example.org/",
+            json!([]),
+        ),
+    );
+    old.dns.as_mut().unwrap().results.push(records(
+        "MX",
+        &[("20 mx2.example.net.", 300), ("10 MX1.example.net.", 300)],
+    ));
+    let mut new = report(
+        2,
+        &[("192.0.2.9", 300)],
+        &[("ns2.example.net.", 3600), ("ns1.example.net.", 3600)],
+        "BB",
+        &[],
+        http_check("http://example.org", &[], "https://example.org/", json!([])),
+    );
+    new.domain = "example.org".to_string();
+    new.dns.as_mut().unwrap().results.push(records(
+        "MX",
+        &[("10 mx1.example.net.", 300), ("20 mx2.example.net.", 300)],
+    ));
+    new.errors.push(SectionError {
+        section: "email".to_string(),
+        error: "timed out".to_string(),
+    });
+
+    let comparison = ReportAdapter::new().compare(&old, &new);
+    assert_eq!(comparison.domain_a, "example.com");
+    assert_eq!(comparison.domain_b, "example.org");
+    assert_eq!(comparison.errors_b.len(), 1);
+
+    let row = |field: &str| {
+        comparison
+            .rows
+            .iter()
+            .find(|row| row.field == field)
+            .unwrap()
+    };
+    assert!(row("nameservers").same);
+    assert_eq!(
+        row("mx").a,
+        vec!["10 mx1.example.net", "20 mx2.example.net"]
+    );
+    assert!(row("mx").same);
+    assert!(row("spf").same && row("spf").a.is_empty());
+    assert!(row("issuer").same);
+    assert!(!row("not_after").same);
+    assert!(row("http_target").same);
+    assert_eq!(row("http_target").a, vec!["https://example.org/"]);
+    assert!(row("https_target").a.is_empty());
+    assert!(!comparison.same);
+}
+
+#[test]
+fn test_diff_skips_failed_record_types() {
+    let old = report(
+        1,
+        &[("192.0.2.1", 300)],
+        &[("ns1.example.net.", 3600)],
+        "AA",
+        &[],
+        redirecting(),
+    );
+    let mut new = old.clone();
+    let a = &mut new.dns.as_mut().unwrap().results[0];
+    a.records.clear();
+    a.error = Some("timed out".to_string());
+    new.whois = None;
+
+    let diff = ReportAdapter::new().diff(&old, &new);
+    assert!(!diff.changed, "{:?}", diff.records);
+    assert_eq!(diff.skipped, vec!["whois", "health"]);
+}
+
+#[test]
+fn test_export_formats() {
+    let mut report = report(
+        7,
+        &[("192.0.2.1", 300)],
+        &[("ns1.example.net.", 3600)],
+        "AA",
+        &["clientTransferProhibited"],
+        redirecting(),
+    );
+    report.dns.as_mut().unwrap().results[0].records[0].value = "a|<b>".to_string();
+    report.errors.push(SectionError {
+        section: "email".to_string(),
+        error: "timed out".to_string(),
+    });
+    let adapter = ReportAdapter::new();
+
+    let json = adapter.export(&report, ExportFormat::Json).unwrap();
+    let parsed: DomainReport = serde_json::from_str(&json).unwrap();
+    assert_eq!(parsed.scan_id, Some(7));
+
+    let markdown = adapter.export(&report, ExportFormat::Markdown).unwrap();
+    assert!(markdown.starts_with("# Domain report: example.com\n"));
+    assert!(markdown.contains("Saved as scan 7."));
+    assert!(markdown.contains("| Type | Name | TTL | Value |\n| --- | --- | --- | --- |\n"));
+    assert!(markdown.contains("| A | example.com. | 300 | a\\|<b> |"));
+    assert!(markdown.contains("| Issuer | R3 |"));
+    assert!(markdown.contains("| Status | clientTransferProhibited |"));
+    assert!(markdown.contains("| http://example.com | 200 | https://example.com/ | 1 | 100 ms |"));
+    assert!(markdown.contains("## Checks that failed\n\n- email: timed out\n"));
+
+    let html = adapter.export(&report, ExportFormat::Html).unwrap();
+    assert!(html.starts_with("<!DOCTYPE html>"));
+    assert!(html.contains("<title>Domain report: example.com</title>"));
+    assert!(html.contains("<td>a|&lt;b&gt;</td>"));
+    assert!(html.trim_end().ends_with("</html>"));
+}
+
+#[test]
+fn test_parse_domain_list() {
+    let csv = "\u{feff}Name,Address,Owner\n\
+               Shop,\"https://Shop.Example.com:8443/cart\",ops\n\
+               # retired\n\
+               Blog,blog.example.org.,marketing\n\
+               Intranet,not a domain,it\n\
+               Shop again,shop.example.com,ops\n";
+    let adapter = ReportAdapter::new();
+    // No header names a domain column, so the first domain-like cell is used
+    let (domains, skipped) = adapter.parse_domain_list(csv);
+    assert_eq!(domains, vec!["shop.example.com", "blog.example.org"]);
+    assert_eq!(skipped.len(), 2);
+    assert!(skipped[1].starts_with("line 5:"));
+
+    let (domains, skipped) =
+        adapter.parse_domain_list("id;Domain\n1;example.com\n2;münchen.de\n3;10.0.0.1\n");
+    assert_eq!(domains, vec!["example.com", "münchen.de"]);
+    assert_eq!(skipped, vec!["line 4: no domain in \"3;10.0.0.1\""]);
+
+    let (domains, _) = adapter.parse_domain_list("example.com\nexample.net\n");
+    assert_eq!(domains, vec!["example.com", "example.net"]);
+}
+
+#[test]
+fn test_summary_row() {
+    let mut report = report(
+        1,
+        &[("192.0.2.1", 300)],
+        &[("ns1.example.net.", 3600)],
+        "AA",
+        &[],
+        redirecting(),
+    );
+    report.whois.as_mut().unwrap().days_until_expiry = Some(120);
+    report.health = Some(HealthAdapter::new().score(&report));
+
+    let row = ReportAdapter::new().summary_row(&report);
+    assert_eq!(row.domain, "example.com");
+    assert!(row.score.is_some());
+    assert_eq!(row.registration_days, Some(120));
+    assert_eq!(row.https_status, None);
+    assert_eq!(row.dnssec, None);
+    assert!(row.errors.is_empty());
+}
@@ -0,0 +1,277 @@
+use crate::adapters::backend::Backends;
+use crate::adapters::dns::DnsAdapter;
+use crate::adapters::http::HttpAdapter;
+use crate::models::reverse_ip::{HostedDomain, Hosting, ReverseIpReport, SourceError};
+use crate::state;
+use chrono::DateTime;
+use serde::Deserialize;
+use std::cmp::Reverse;
+use std::collections::{BTreeMap, HashSet};
+use std::net::IpAddr;
+use std::time::Instant;
+use tauri::AppHandle;
+use tracing::instrument;
+
+// Sites on one address from which it's reported as shared hosting. A company's
+// own server often carries a few of its domains.
+const SHARED_HOSTING_MIN_SITES: usize = 5;
+
+// Passive DNS rows fetched from Mnemonic
+const MNEMONIC_LIMIT: u32 = 1000;
+
+// Mnemonic's passive DNS response, of which only the records are used
+#[derive(Deserialize)]
+struct MnemonicResponse {
+    #[serde(default)]
+    data: Vec<MnemonicRecord>,
+    #[serde(rename = "responseCode")]
+    response_code: Option<u16>,
+    #[serde(default)]
+    messages: Vec<MnemonicMessage>,
+}
+
+#[derive(Deserialize)]
+struct MnemonicRecord {
+    #[serde(default)]
+    query: String, // The name that resolved
+    #[serde(default)]
+    answer: String,
+    #[serde(default)]
+    rrtype: String,
+    #[serde(rename = "firstSeenTimestamp")]
+    first_seen: Option<i64>, // Milliseconds since the epoch
+    #[serde(rename = "lastSeenTimestamp")]
+    last_seen: Option<i64>,
+}
+
+#[derive(Deserialize)]
+struct MnemonicMessage {
+    message: String,
+}
+
+#[derive(Clone)]
+pub struct ReverseIpAdapter {
+    app_handle: Option<AppHandle>,
+    backends: Backends,
+}
+
+impl ReverseIpAdapter {
+    pub fn new() -> Self {
+        ReverseIpAdapter {
+            app_handle: None,
+            backends: Backends::default(),
+        }
+    }
+
+    pub fn with_app_handle(app_handle: AppHandle) -> Self {
+        ReverseIpAdapter {
+            backends: state::backends_for(Some(&app_handle)),
+            app_handle: Some(app_handle),
+        }
+    }
+
+    // Query the APIs and resolve through these instead of the app's backends
+    pub fn with_backends(mut self, backends: Backends) -> Self {
+        self.backends = backends;
+        self
+    }
+
+    fn http(&self) -> HttpAdapter {
+        HttpAdapter::from_app_handle(self.app_handle.clone()).with_backends(self.backends.clone())
+    }
+
+    // Domains pointing at ip according to HackerTarget's reverse-IP lookup (current
+    // A records) and Mnemonic's passive DNS (names seen resolving to it), queried
+    // concurrently, along with the address's PTR names
+    #[instrument(skip_all, fields(ip = %ip))]
+    pub async fn lookup(&self, ip: IpAddr) -> ReverseIpReport {
+        let start = Instant::now();
+        let dns = DnsAdapter::from_app_handle(self.app_handle.clone())
+            .with_backends(self.backends.clone());
+        let (hackertarget, mnemonic, ptr) = tokio::join!(
+            self.query_hackertarget(ip),
+            self.query_mnemonic(ip),
+            dns.reverse_lookup(ip)
+        );
+
+        let mut report = self.merge(
+            ip,
+            vec![("hackertarget", hackertarget), ("mnemonic", mnemonic)],
+        );
+        report.ptr = ptr.unwrap_or_default();
+        report.total_time = start.elapsed().as_secs_f64();
+        report
+    }
+
+    async fn query_hackertarget(&self, ip: IpAddr) -> Result<Vec<HostedDomain>, String> {
+        let settings = state::settings_for(self.app_handle.as_ref());
+        let (status, body) = self
+            .http()
+            .get_text(
+                &format!("https://api.hackertarget.com/reverseiplookup/?q={}", ip),
+                "text/plain",
+                settings.http_timeout_secs,
+                settings.http_retries,
+                &ip.to_string(),
+            )
+            .await?;
+        if !(200..300).contains(&status) {
+            return Err(format!("HTTP {}", status));
+        }
+        self.parse_hackertarget(&body)
+    }
+
+    async fn query_mnemonic(&self, ip: IpAddr) -> Result<Vec<HostedDomain>, String> {
+        let settings = state::settings_for(self.app_handle.as_ref());
+        let (status, body) = self
+            .http()
+            .get_text(
+                &format!(
+                    "https://api.mnemonic.no/pdns/v3/{}?limit={}",
+                    ip, MNEMONIC_LIMIT
+                ),
+                "application/json",
+                settings.http_timeout_secs,
+                settings.http_retries,
+                &ip.to_string(),
+            )
+            .await?;
+        self.parse_mnemonic(status, &body, ip)
+    }
+
+    // One domain per line. Errors and "no records" come back as a single
+    // sentence with a 200, so any line with a space is one of those.
+    pub fn parse_hackertarget(&self, body: &str) -> Result<Vec<HostedDomain>, String> {
+        let lines: Vec<&str> = body
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty())
+            .collect();
+        if let Some(message) = lines.iter().find(|line| line.contains(' ')) {
+            if message.starts_with("No DNS A records found") {
+                return Ok(Vec::new());
+            }
+            return Err(message.to_string());
+        }
+
+        Ok(lines
+            .into_iter()
+            .map(|domain| HostedDomain {
+                domain: domain.trim_end_matches('.').to_lowercase(),
+                sources: vec!["hackertarget".to_string()],
+                first_seen: None,
+                last_seen: None,
+            })
+            .collect())
+    }
+
+    // A and AAAA rows whose answer is ip; Mnemonic also returns rows where the IP
+    // was the query (PTR)
+    pub fn parse_mnemonic(
+        &self,
+        status: u16,
+        body: &str,
+        ip: IpAddr,
+    ) -> Result<Vec<HostedDomain>, String> {
+        let response: MnemonicResponse = serde_json::from_str(body)
+            .map_err(|e| format!("HTTP {}: unexpected response ({})", status, e))?;
+        let code = response.response_code.unwrap_or(status);
+        if !(200..300).contains(&code) {
+            let message = response
+                .messages
+                .first()
+                .map(|m| m.message.clone())
+                .unwrap_or_else(|| format!("HTTP {}", code));
+            return Err(message);
+        }
+
+        Ok(response
+            .data
+            .into_iter()
+            .filter(|row| matches!(row.rrtype.to_lowercase().as_str(), "a" | "aaaa"))
+            .filter(|row| row.answer.parse::<IpAddr>() == Ok(ip))
+            .map(|row| HostedDomain {
+                domain: row.query.trim_end_matches('.').to_lowercase(),
+                sources: vec!["mnemonic".to_string()],
+                first_seen: row.first_seen.and_then(DateTime::from_timestamp_millis),
+                last_seen: row.last_seen.and_then(DateTime::from_timestamp_millis),
+            })
+            .collect())
+    }
+
+    // Combine what each source returned: one entry per domain, the earliest
+    // first_seen and latest last_seen, and the hosting verdict from the number of
+    // distinct registrable domains
+    pub fn merge(
+        &self,
+        ip: IpAddr,
+        results: Vec<(&str, Result<Vec<HostedDomain>, String>)>,
+    ) -> ReverseIpReport {
+        let queried = results.len();
+        let mut domains: BTreeMap<String, HostedDomain> = BTreeMap::new();
+        let mut errors = Vec::new();
+        for (source, result) in results {
+            let found = match result {
+                Ok(found) => found,
+                Err(error) => {
+                    errors.push(SourceError {
+                        source: source.to_string(),
+                        error,
+                    });
+                    continue;
+                }
+            };
+            for hosted in found {
+                match domains.get_mut(&hosted.domain) {
+                    Some(existing) => {
+                        for source in hosted.sources {
+                            if !existing.sources.contains(&source) {
+                                existing.sources.push(source);
+                            }
+                        }
+                        existing.first_seen = match (existing.first_seen, hosted.first_seen) {
+                            (Some(a), Some(b)) => Some(a.min(b)),
+                            (a, b) => a.or(b),
+                        };
+                        existing.last_seen = existing.last_seen.max(hosted.last_seen);
+                    }
+                    None => {
+                        domains.insert(hosted.domain.clone(), hosted);
+                    }
+                }
+            }
+        }
+
+        let mut domains: Vec<HostedDomain> = domains.into_values().collect();
+        // Stable, so domains seen equally recently stay alphabetical
+        domains.sort_by_key(|hosted| Reverse(hosted.last_seen));
+
+        let http = self.http();
+        let sites = domains
+            .iter()
+            .map(|hosted| http.registrable_domain(&hosted.domain))
+            .collect::<HashSet<_>>()
+            .len();
+        let hosting = if errors.len() == queried {
+            Hosting::Unknown
+        } else if sites >= SHARED_HOSTING_MIN_SITES {
+            Hosting::Shared
+        } else {
+            Hosting::Dedicated
+        };
+
+        ReverseIpReport {
+            ip: ip.to_string(),
+            ptr: Vec::new(),
+            domains,
+            sites,
+            hosting,
+            errors,
+            total_time: 0.0,
+        }
+    }
+}
+
+#[cfg(test)]
+#[path = "reverse_ip_test.rs"]
+mod tests;
@@ -0,0 +1,107 @@
+use super::ReverseIpAdapter;
+use crate::models::reverse_ip::{HostedDomain, Hosting};
+use std::net::IpAddr;
+
+fn ip() -> IpAddr {
+    "192.0.2.10".parse().unwrap()
+}
+
+#[test]
+fn test_parse_hackertarget() {
+    let adapter = ReverseIpAdapter::new();
+    let domains = adapter
+        .parse_hackertarget("example.com\nwww.example.com\nExample.org\n")
+        .unwrap();
+    let names: Vec<&str> = domains.iter().map(|d| d.domain.as_str()).collect();
+    assert_eq!(names, ["example.com", "www.example.com", "example.org"]);
+
+    assert!(adapter
+        .parse_hackertarget("No DNS A records found for 192.0.2.10")
+        .unwrap()
+        .is_empty());
+    assert_eq!(
+        adapter
+            .parse_hackertarget("API count exceeded - Increase Quota with Membership")
+            .unwrap_err(),
+        "API count exceeded - Increase Quota with Membership"
+    );
+}
+
+#[test]
+fn test_parse_mnemonic() {
+    let adapter = ReverseIpAdapter::new();
+    let body = r#"{"responseCode":200,"count":3,"data":[
+        {"query":"shop.example.net","answer":"192.0.2.10","rrtype":"a","firstSeenTimestamp":1700000000000,"lastSeenTimestamp":1710000000000},
+        {"query":"10.2.0.192.in-addr.arpa","answer":"host.example.net","rrtype":"ptr"},
+        {"query":"other.example","answer":"192.0.2.11","rrtype":"a"}
+    ],"messages":[]}"#;
+
+    let domains = adapter.parse_mnemonic(200, body, ip()).unwrap();
+    assert_eq!(domains.len(), 1);
+    assert_eq!(domains[0].domain, "shop.example.net");
+    assert_eq!(
+        domains[0].last_seen.unwrap().to_rfc3339(),
+        "2024-03-09T16:00:00+00:00"
+    );
+
+    let limited =
+        r#"{"responseCode":402,"data":[],"messages":[{"message":"Resource limit exceeded"}]}"#;
+    assert_eq!(
+        adapter.parse_mnemonic(402, limited, ip()).unwrap_err(),
+        "Resource limit exceeded"
+    );
+}
+
+fn hosted(domain: &str, source: &str) -> HostedDomain {
+    HostedDomain {
+        domain: domain.to_string(),
+        sources: vec![source.to_string()],
+        first_seen: None,
+        last_seen: None,
+    }
+}
+
+#[test]
+fn test_merge() {
+    let adapter = ReverseIpAdapter::new();
+    let report = adapter.merge(
+        ip(),
+        vec![
+            (
+                "hackertarget",
+                Ok(vec![
+                    hosted("example.com", "hackertarget"),
+                    hosted("www.example.com", "hackertarget"),
+                ]),
+            ),
+            ("mnemonic", Ok(vec![hosted("example.com", "mnemonic")])),
+        ],
+    );
+    assert_eq!(report.domains.len(), 2);
+    assert_eq!(report.domains[0].sources, ["hackertarget", "mnemonic"]);
+    assert_eq!(report.sites, 1);
+    assert_eq!(report.hosting, Hosting::Dedicated);
+
+    let shared: Vec<HostedDomain> = ["a.com", "b.net", "c.org", "d.co.uk", "e.io"]
+        .iter()
+        .map(|domain| hosted(domain, "hackertarget"))
+        .collect();
+    let report = adapter.merge(
+        ip(),
+        vec![
+            ("hackertarget", Ok(shared)),
+            ("mnemonic", Err("HTTP 503".to_string())),
+        ],
+    );
+    assert_eq!(report.hosting, Hosting::Shared);
+    assert_eq!(report.errors[0].source, "mnemonic");
+
+    let report = adapter.merge(
+        ip(),
+        vec![
+            ("hackertarget", Err("HTTP 429".to_string())),
+            ("mnemonic", Err("HTTP 503".to_string())),
+        ],
+    );
+    assert_eq!(report.hosting, Hosting::Unknown);
+}
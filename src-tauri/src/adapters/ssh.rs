@@ -0,0 +1,603 @@
+use crate::adapters::backend::{BackendFuture, Backends, SshBackend};
+use crate::adapters::dns::DnsAdapter;
+use crate::models::command_log::CommandLog;
+use crate::models::ssh::{HostKeyStatus, SshHostKey, SshfpRecord, SshfpReport};
+use crate::state;
+use crate::state::command_logs;
+use crate::state::rate_limiter::{self, Target};
+use base64::engine::general_purpose::{STANDARD as BASE64, STANDARD_NO_PAD};
+use base64::Engine;
+use ring::agreement;
+use ring::rand::{SecureRandom, SystemRandom};
+use sha1::Sha1;
+use sha2::{Digest, Sha256};
+use std::net::IpAddr;
+use std::time::{Duration, Instant};
+use tauri::AppHandle;
+use tokio::io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader};
+use tokio::net::TcpStream;
+use tracing::instrument;
+
+pub const DEFAULT_SSH_PORT: u16 = 22;
+
+// Per key exchange, from connecting until the host key arrives
+const SSH_TIMEOUT: Duration = Duration::from_secs(5);
+
+// Our version line; the software version may not contain '-' (RFC 4253 section 4.2)
+const CLIENT_VERSION: &str = concat!("SSH-2.0-d_dns_debugger_", env!("CARGO_PKG_VERSION"));
+
+// Longest version line or packet accepted from the server
+const MAX_LINE: u64 = 255;
+const MAX_PACKET: usize = 256 * 1024;
+
+// Message numbers (RFC 4253 section 12, RFC 5656 section 7.1)
+const MSG_DISCONNECT: u8 = 1;
+const MSG_IGNORE: u8 = 2;
+const MSG_UNIMPLEMENTED: u8 = 3;
+const MSG_DEBUG: u8 = 4;
+const MSG_KEXINIT: u8 = 20;
+const MSG_KEX_ECDH_INIT: u8 = 30;
+const MSG_KEX_ECDH_REPLY: u8 = 31;
+
+// Key exchanges offered, in preference order; all are one ECDH round trip, so the
+// server's reply carries its host key without any further negotiation
+const KEX_ALGORITHMS: [(&str, &agreement::Algorithm); 4] = [
+    ("curve25519-sha256", &agreement::X25519),
+    ("curve25519-sha256@libssh.org", &agreement::X25519),
+    ("ecdh-sha2-nistp256", &agreement::ECDH_P256),
+    ("ecdh-sha2-nistp384", &agreement::ECDH_P384),
+];
+
+// Offered only so the server accepts our KEXINIT; the exchange stops before
+// NEWKEYS, so none of them is ever used
+const CIPHERS: &str = "chacha20-poly1305@openssh.com,aes128-ctr,aes192-ctr,aes256-ctr,\
+aes128-gcm@openssh.com,aes256-gcm@openssh.com,aes128-cbc,aes256-cbc";
+const MACS: &str = "hmac-sha2-256-etm@openssh.com,hmac-sha2-512-etm@openssh.com,\
+hmac-sha2-256,hmac-sha2-512,hmac-sha1";
+
+// Host key algorithms by SSHFP algorithm number, in the order keys are fetched.
+// The RSA signature algorithms all present the same "ssh-rsa" key.
+const HOST_KEY_ALGORITHMS: [(u8, &[&str]); 5] = [
+    (4, &["ssh-ed25519"]),
+    (
+        3,
+        &[
+            "ecdsa-sha2-nistp256",
+            "ecdsa-sha2-nistp384",
+            "ecdsa-sha2-nistp521",
+        ],
+    ),
+    (1, &["rsa-sha2-512", "rsa-sha2-256", "ssh-rsa"]),
+    (6, &["ssh-ed448"]),
+    (2, &["ssh-dss"]),
+];
+
+// What one key exchange got from the server
+#[derive(Debug, Clone)]
+pub struct HostKeyExchange {
+    pub banner: String,          // Its version line
+    pub algorithms: Vec<String>, // Host key algorithms it offered in its KEXINIT
+    pub key: Vec<u8>,            // The host key blob it presented
+}
+
+// SSH over a plain TCP connection
+pub struct TcpSsh;
+
+impl SshBackend for TcpSsh {
+    fn host_key<'a>(
+        &'a self,
+        address: IpAddr,
+        port: u16,
+        algorithms: &'a [String],
+        timeout: Duration,
+    ) -> BackendFuture<'a, HostKeyExchange> {
+        Box::pin(async move {
+            tokio::time::timeout(timeout, key_exchange(address, port, algorithms))
+                .await
+                .map_err(|_| format!("Timed out after {}s", timeout.as_secs()))?
+        })
+    }
+}
+
+// Version exchange, KEXINIT both ways and our ECDH public key, then the server's
+// KEX_ECDH_REPLY, whose first field is the host key (RFC 5656 section 4)
+async fn key_exchange(
+    address: IpAddr,
+    port: u16,
+    algorithms: &[String],
+) -> Result<HostKeyExchange, String> {
+    let stream = TcpStream::connect((address, port))
+        .await
+        .map_err(|e| e.to_string())?;
+    let mut stream = BufReader::new(stream);
+    stream
+        .get_mut()
+        .write_all(format!("{}\r\n", CLIENT_VERSION).as_bytes())
+        .await
+        .map_err(|e| e.to_string())?;
+
+    // Servers may send other lines before their version (RFC 4253 section 4.2)
+    let banner = loop {
+        let mut line = Vec::new();
+        let read = (&mut stream)
+            .take(MAX_LINE)
+            .read_until(b'\n', &mut line)
+            .await
+            .map_err(|e| e.to_string())?;
+        if read == 0 {
+            return Err("Connection closed before the SSH version line".to_string());
+        }
+        let line = String::from_utf8_lossy(&line).trim_end().to_string();
+        if line.starts_with("SSH-") {
+            break line;
+        }
+    };
+    if !banner.starts_with("SSH-2.0-") && !banner.starts_with("SSH-1.99-") {
+        return Err(format!("Not an SSH 2 server: {}", banner));
+    }
+
+    let kexinit = read_message(&mut stream, MSG_KEXINIT).await?;
+    let mut fields = WireReader::new(kexinit.get(17..).unwrap_or_default());
+    let server_kex = fields.name_list()?;
+    let server_host_keys = fields.name_list()?;
+    let (kex, curve) = KEX_ALGORITHMS
+        .iter()
+        .find(|(name, _)| server_kex.iter().any(|offered| offered == name))
+        .ok_or_else(|| {
+            format!(
+                "No key exchange in common (the server offers {})",
+                server_kex.join(", ")
+            )
+        })?;
+
+    let random = SystemRandom::new();
+    let mut cookie = [0u8; 16];
+    random
+        .fill(&mut cookie)
+        .map_err(|_| "Couldn't generate a random cookie".to_string())?;
+    let private = agreement::EphemeralPrivateKey::generate(curve, &random)
+        .map_err(|_| "Couldn't generate an ECDH key".to_string())?;
+    let public = private
+        .compute_public_key()
+        .map_err(|_| "Couldn't generate an ECDH key".to_string())?;
+
+    let host_keys = algorithms.join(",");
+    let mut ours = vec![MSG_KEXINIT];
+    ours.extend_from_slice(&cookie);
+    for list in [
+        *kex,
+        host_keys.as_str(),
+        CIPHERS,
+        CIPHERS,
+        MACS,
+        MACS,
+        "none",
+        "none",
+        "",
+        "",
+    ] {
+        put_string(&mut ours, list.as_bytes());
+    }
+    ours.push(0); // first_kex_packet_follows
+    ours.extend_from_slice(&0u32.to_be_bytes());
+    write_packet(stream.get_mut(), &ours).await?;
+
+    let mut init = vec![MSG_KEX_ECDH_INIT];
+    put_string(&mut init, public.as_ref());
+    write_packet(stream.get_mut(), &init).await?;
+
+    let reply = read_message(&mut stream, MSG_KEX_ECDH_REPLY).await?;
+    let key = WireReader::new(&reply[1..]).string()?.to_vec();
+    Ok(HostKeyExchange {
+        banner,
+        algorithms: server_host_keys,
+        key,
+    })
+}
+
+// The next message of type wanted, skipping the ones a server may send at any time
+async fn read_message(stream: &mut BufReader<TcpStream>, wanted: u8) -> Result<Vec<u8>, String> {
+    loop {
+        let payload = read_packet(stream).await?;
+        match payload.first().copied() {
+            Some(kind) if kind == wanted => return Ok(payload),
+            Some(MSG_IGNORE | MSG_UNIMPLEMENTED | MSG_DEBUG) => continue,
+            Some(MSG_DISCONNECT) => {
+                let mut fields = WireReader::new(&payload[1..]);
+                let reason = fields.u32().unwrap_or(0);
+                let description = fields
+                    .string()
+                    .map(|text| String::from_utf8_lossy(text).to_string())
+                    .unwrap_or_default();
+                return Err(format!(
+                    "The server disconnected (reason {}): {}",
+                    reason, description
+                ));
+            }
+            Some(kind) => return Err(format!("Unexpected SSH message {}", kind)),
+            None => return Err("Empty SSH packet".to_string()),
+        }
+    }
+}
+
+// An unencrypted binary packet's payload (RFC 4253 section 6)
+async fn read_packet(stream: &mut BufReader<TcpStream>) -> Result<Vec<u8>, String> {
+    let length = stream.read_u32().await.map_err(|e| e.to_string())? as usize;
+    if !(5..=MAX_PACKET).contains(&length) {
+        return Err(format!("Invalid SSH packet length {}", length));
+    }
+    let mut packet = vec![0u8; length];
+    stream
+        .read_exact(&mut packet)
+        .await
+        .map_err(|e| e.to_string())?;
+    let padding = packet[0] as usize;
+    if padding + 1 > length {
+        return Err(format!("Invalid SSH padding length {}", padding));
+    }
+    Ok(packet[1..length - padding].to_vec())
+}
+
+// Padded to a multiple of 8 bytes with at least 4 of padding, and no MAC yet
+async fn write_packet(stream: &mut TcpStream, payload: &[u8]) -> Result<(), String> {
+    let mut padding = 8 - (5 + payload.len()) % 8;
+    if padding < 4 {
+        padding += 8;
+    }
+    let mut packet = Vec::with_capacity(5 + payload.len() + padding);
+    packet.extend_from_slice(&((1 + payload.len() + padding) as u32).to_be_bytes());
+    packet.push(padding as u8);
+    packet.extend_from_slice(payload);
+    packet.resize(packet.len() + padding, 0);
+    stream.write_all(&packet).await.map_err(|e| e.to_string())
+}
+
+fn put_string(buffer: &mut Vec<u8>, value: &[u8]) {
+    buffer.extend_from_slice(&(value.len() as u32).to_be_bytes());
+    buffer.extend_from_slice(value);
+}
+
+// Reads SSH's wire types (RFC 4251 section 5) off the front of a payload
+pub struct WireReader<'a> {
+    data: &'a [u8],
+}
+
+impl<'a> WireReader<'a> {
+    pub fn new(data: &'a [u8]) -> Self {
+        WireReader { data }
+    }
+
+    pub fn u32(&mut self) -> Result<u32, String> {
+        if self.data.len() < 4 {
+            return Err("Truncated SSH message".to_string());
+        }
+        let (value, rest) = self.data.split_at(4);
+        self.data = rest;
+        Ok(u32::from_be_bytes([value[0], value[1], value[2], value[3]]))
+    }
+
+    pub fn string(&mut self) -> Result<&'a [u8], String> {
+        let length = self.u32()? as usize;
+        if self.data.len() < length {
+            return Err("Truncated SSH message".to_string());
+        }
+        let (value, rest) = self.data.split_at(length);
+        self.data = rest;
+        Ok(value)
+    }
+
+    pub fn name_list(&mut self) -> Result<Vec<String>, String> {
+        let names = String::from_utf8_lossy(self.string()?).to_string();
+        Ok(names
+            .split(',')
+            .filter(|name| !name.is_empty())
+            .map(str::to_string)
+            .collect())
+    }
+}
+
+// The SSHFP algorithm number of a host key type or host key algorithm
+pub fn sshfp_algorithm(name: &str) -> Option<u8> {
+    HOST_KEY_ALGORITHMS
+        .iter()
+        .find(|(_, names)| names.contains(&name))
+        .map(|(algorithm, _)| *algorithm)
+}
+
+#[derive(Clone)]
+pub struct SshAdapter {
+    app_handle: Option<AppHandle>,
+    backends: Backends,
+}
+
+impl SshAdapter {
+    pub fn new() -> Self {
+        SshAdapter {
+            app_handle: None,
+            backends: Backends::default(),
+        }
+    }
+
+    pub fn with_app_handle(app_handle: AppHandle) -> Self {
+        SshAdapter {
+            backends: state::backends_for(Some(&app_handle)),
+            app_handle: Some(app_handle),
+        }
+    }
+
+    // Connect and resolve through these instead of the app's backends
+    pub fn with_backends(mut self, backends: Backends) -> Self {
+        self.backends = backends;
+        self
+    }
+
+    fn emit_log(&self, log: CommandLog) {
+        command_logs::record(self.app_handle.as_ref(), log);
+    }
+
+    fn dns(&self) -> DnsAdapter {
+        DnsAdapter::from_app_handle(self.app_handle.clone()).with_backends(self.backends.clone())
+    }
+
+    // host's SSHFP records and, when verify is set, the host keys its SSH server on
+    // port presents, each checked against them
+    #[instrument(skip_all, fields(host = %host, port, verify))]
+    pub async fn check_sshfp(
+        &self,
+        host: &str,
+        port: u16,
+        verify: bool,
+    ) -> Result<SshfpReport, String> {
+        let start = Instant::now();
+        let dns = self.dns();
+        let response = dns.query(host, "SSHFP").await?;
+        let mut report = SshfpReport {
+            host: host.to_string(),
+            records: dns.parse_sshfp_records(&response.records),
+            authenticated: response
+                .header
+                .as_ref()
+                .is_some_and(|header| header.authenticated_data),
+            port,
+            address: None,
+            banner: None,
+            host_keys: Vec::new(),
+            verified: None,
+            error: None,
+            warnings: Vec::new(),
+            total_time: 0.0,
+        };
+
+        if verify {
+            match dns.first_address(host).await {
+                Ok(address) => {
+                    report.address = Some(address.to_string());
+                    match self.fetch_host_keys(host, address, port).await {
+                        Ok((banner, keys)) => {
+                            report.banner = Some(banner);
+                            report.host_keys = keys;
+                        }
+                        Err(error) => report.error = Some(error),
+                    }
+                }
+                Err(error) => report.error = Some(error),
+            }
+        }
+
+        self.verify_sshfp(&mut report);
+        report.total_time = start.elapsed().as_secs_f64();
+        Ok(report)
+    }
+
+    // The server's version line and one host key per SSHFP algorithm it has,
+    // logged as ssh-keyscan would print them
+    pub async fn fetch_host_keys(
+        &self,
+        host: &str,
+        address: IpAddr,
+        port: u16,
+    ) -> Result<(String, Vec<SshHostKey>), String> {
+        let start = Instant::now();
+        let mut throttled = Duration::ZERO;
+        let result = self.exchange_keys(address, port, &mut throttled).await;
+
+        let output = match &result {
+            Ok((_, keys)) => keys
+                .iter()
+                .map(|key| format!("{} {} {}", address, key.key_type, key.public_key))
+                .collect::<Vec<_>>()
+                .join("\n"),
+            Err(error) => error.clone(),
+        };
+        self.emit_log(
+            CommandLog::new(
+                "ssh-keyscan".to_string(),
+                vec!["-p".to_string(), port.to_string(), address.to_string()],
+                output,
+                if result.is_ok() { 0 } else { 1 },
+                start.elapsed().as_secs_f64() * 1000.0,
+                Some(host.to_string()),
+            )
+            .with_throttled(throttled),
+        );
+        result
+    }
+
+    // The first exchange offers every algorithm and learns which ones the server
+    // has; each other key takes one more exchange offering only its algorithms
+    async fn exchange_keys(
+        &self,
+        address: IpAddr,
+        port: u16,
+        throttled: &mut Duration,
+    ) -> Result<(String, Vec<SshHostKey>), String> {
+        let all: Vec<String> = HOST_KEY_ALGORITHMS
+            .iter()
+            .flat_map(|(_, names)| names.iter().map(|name| name.to_string()))
+            .collect();
+
+        *throttled += self.throttle(address).await;
+        let first = self
+            .backends
+            .ssh
+            .host_key(address, port, &all, SSH_TIMEOUT)
+            .await?;
+        let mut keys: Vec<SshHostKey> = self.host_key(&first.key).into_iter().collect();
+
+        for (algorithm, names) in HOST_KEY_ALGORITHMS {
+            if keys.iter().any(|key| key.algorithm == algorithm) {
+                continue;
+            }
+            let offered: Vec<String> = names
+                .iter()
+                .filter(|name| first.algorithms.iter().any(|offered| offered == *name))
+                .map(|name| name.to_string())
+                .collect();
+            if offered.is_empty() {
+                continue;
+            }
+            *throttled += self.throttle(address).await;
+            // A key that can't be fetched is left out rather than failing the others
+            if let Ok(exchange) = self
+                .backends
+                .ssh
+                .host_key(address, port, &offered, SSH_TIMEOUT)
+                .await
+            {
+                keys.extend(
+                    self.host_key(&exchange.key)
+                        .filter(|key| !keys.iter().any(|k| k.algorithm == key.algorithm)),
+                );
+            }
+        }
+
+        if keys.is_empty() {
+            return Err("The server presented no host key of a known type".to_string());
+        }
+        Ok((first.banner, keys))
+    }
+
+    async fn throttle(&self, address: IpAddr) -> Duration {
+        rate_limiter::throttle(self.app_handle.as_ref(), Target::Host, &address.to_string()).await
+    }
+
+    // A host key blob as presented, None for a type SSHFP has no number for
+    pub fn host_key(&self, blob: &[u8]) -> Option<SshHostKey> {
+        let key_type = String::from_utf8_lossy(WireReader::new(blob).string().ok()?).to_string();
+        Some(SshHostKey {
+            algorithm: sshfp_algorithm(&key_type)?,
+            key_type,
+            public_key: BASE64.encode(blob),
+            fingerprint: format!("SHA256:{}", STANDARD_NO_PAD.encode(Sha256::digest(blob))),
+            status: HostKeyStatus::Unpublished,
+        })
+    }
+
+    // The SSHFP fingerprint of a host key, as lowercase hex; None for unknown types
+    pub fn fingerprint(&self, key: &SshHostKey, fp_type: u8) -> Option<String> {
+        let blob = BASE64.decode(&key.public_key).ok()?;
+        let digest = match fp_type {
+            1 => Sha1::digest(&blob).to_vec(),
+            2 => Sha256::digest(&blob).to_vec(),
+            _ => return None,
+        };
+        Some(digest.iter().map(|b| format!("{:02x}", b)).collect())
+    }
+
+    // Match records against the fetched keys as OpenSSH's VerifyHostKeyDNS does: a
+    // key is trusted when a record of its algorithm matches it (RFC 4255 section
+    // 2.1). Clients only trust DNSSEC-validated records.
+    pub fn verify_sshfp(&self, report: &mut SshfpReport) {
+        let host = report.host.clone();
+        if report.records.is_empty() {
+            report
+                .warnings
+                .push(format!("{} publishes no SSHFP records", host));
+        } else if !report.authenticated {
+            report.warnings.push(format!(
+                "The SSHFP records of {} aren't DNSSEC-validated by the resolver; SSH clients won't trust them",
+                host
+            ));
+        }
+
+        for record in &report.records {
+            if record.algorithm_name == "UNKNOWN" || record.fp_type_name == "UNKNOWN" {
+                report.warnings.push(format!(
+                    "SSHFP record with unknown algorithm {} or fingerprint type {}",
+                    record.algorithm, record.fp_type
+                ));
+            } else if record.fp_type == 1
+                && !report
+                    .records
+                    .iter()
+                    .any(|r| r.algorithm == record.algorithm && r.fp_type == 2)
+            {
+                report.warnings.push(format!(
+                    "The {} key is only published as a SHA-1 fingerprint; add a SHA-256 (type 2) record",
+                    record.algorithm_name
+                ));
+            }
+        }
+
+        if report.host_keys.is_empty() {
+            return;
+        }
+        let keys = report.host_keys.clone();
+        for record in report.records.iter_mut() {
+            let key = keys.iter().find(|key| key.algorithm == record.algorithm);
+            record.matched = key.and_then(|key| {
+                self.fingerprint(key, record.fp_type)
+                    .map(|fingerprint| fingerprint == record.fingerprint)
+            });
+            if key.is_none() && record.algorithm_name != "UNKNOWN" {
+                report.warnings.push(format!(
+                    "SSHFP record for an {} key, which the server doesn't have; remove it",
+                    record.algorithm_name
+                ));
+            }
+        }
+
+        for key in report.host_keys.iter_mut() {
+            let records: Vec<&SshfpRecord> = report
+                .records
+                .iter()
+                .filter(|r| r.algorithm == key.algorithm)
+                .collect();
+            key.status = if records.is_empty() {
+                HostKeyStatus::Unpublished
+            } else if records.iter().any(|r| r.matched == Some(true)) {
+                HostKeyStatus::Matched
+            } else {
+                HostKeyStatus::Mismatched
+            };
+            match key.status {
+                HostKeyStatus::Mismatched => report.warnings.push(format!(
+                    "The {} host key ({}) matches none of its SSHFP records; clients will refuse or prompt",
+                    key.key_type, key.fingerprint
+                )),
+                HostKeyStatus::Unpublished if !report.records.is_empty() => {
+                    report.warnings.push(format!(
+                        "The {} host key has no SSHFP record",
+                        key.key_type
+                    ))
+                }
+                _ => {}
+            }
+        }
+
+        report.verified = Some(
+            report
+                .host_keys
+                .iter()
+                .any(|key| key.status == HostKeyStatus::Matched)
+                && !report
+                    .host_keys
+                    .iter()
+                    .any(|key| key.status == HostKeyStatus::Mismatched),
+        );
+    }
+}
+
+#[cfg(test)]
+#[path = "ssh_test.rs"]
+mod tests;
@@ -0,0 +1,389 @@
+use super::super::backend::{BackendFuture, Backends, SshBackend};
+use super::super::test_support::ZoneDig;
+use super::{sshfp_algorithm, HostKeyExchange, SshAdapter, TcpSsh, WireReader};
+use crate::models::ssh::{HostKeyStatus, SshHostKey, SshfpRecord, SshfpReport};
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use std::net::IpAddr;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader};
+use tokio::net::TcpListener;
+
+// github.com's published Ed25519 host key and its SHA-256 fingerprint
+const GITHUB_ED25519: &str = "AAAAC3NzaC1lZDI1NTE5AAAAIOMqqnkVzrm0SdG6UOoqKLsabgH5C9okWi0dh2l9GKJl";
+const GITHUB_ED25519_SHA256: &str = "SHA256:+DiY3wvvV6TuJJhbpZisF/zLDA0zPMSvHdkr4UvCOqU";
+
+fn wire_string(buffer: &mut Vec<u8>, value: &[u8]) {
+    buffer.extend_from_slice(&(value.len() as u32).to_be_bytes());
+    buffer.extend_from_slice(value);
+}
+
+// A host key blob of key_type with made-up key material
+fn blob(key_type: &str, material: &[u8]) -> Vec<u8> {
+    let mut blob = Vec::new();
+    wire_string(&mut blob, key_type.as_bytes());
+    wire_string(&mut blob, material);
+    blob
+}
+
+fn github_key() -> SshHostKey {
+    SshAdapter::new()
+        .host_key(&BASE64.decode(GITHUB_ED25519).unwrap())
+        .unwrap()
+}
+
+fn record(algorithm: u8, fp_type: u8, fingerprint: &str) -> SshfpRecord {
+    SshfpRecord {
+        algorithm,
+        algorithm_name: match algorithm {
+            1 => "RSA",
+            3 => "ECDSA",
+            4 => "Ed25519",
+            _ => "UNKNOWN",
+        }
+        .to_string(),
+        fp_type,
+        fp_type_name: match fp_type {
+            1 => "SHA-1",
+            2 => "SHA-256",
+            _ => "UNKNOWN",
+        }
+        .to_string(),
+        fingerprint: fingerprint.to_string(),
+        matched: None,
+    }
+}
+
+fn report(records: Vec<SshfpRecord>, host_keys: Vec<SshHostKey>) -> SshfpReport {
+    SshfpReport {
+        host: "host.example.com".to_string(),
+        records,
+        authenticated: true,
+        port: 22,
+        address: None,
+        banner: None,
+        host_keys,
+        verified: None,
+        error: None,
+        warnings: Vec::new(),
+        total_time: 0.0,
+    }
+}
+
+#[test]
+fn test_wire_reader() {
+    let mut payload = 7u32.to_be_bytes().to_vec();
+    wire_string(&mut payload, b"ssh-ed25519,ssh-rsa,");
+    let mut reader = WireReader::new(&payload);
+    assert_eq!(reader.u32(), Ok(7));
+    assert_eq!(reader.name_list().unwrap(), ["ssh-ed25519", "ssh-rsa"]);
+    assert!(reader.string().is_err());
+
+    assert!(WireReader::new(&[0, 0, 0, 9, b'x']).string().is_err());
+}
+
+#[test]
+fn test_sshfp_algorithm() {
+    assert_eq!(sshfp_algorithm("ssh-ed25519"), Some(4));
+    assert_eq!(sshfp_algorithm("ecdsa-sha2-nistp384"), Some(3));
+    assert_eq!(sshfp_algorithm("rsa-sha2-512"), Some(1));
+    assert_eq!(sshfp_algorithm("ssh-rsa"), Some(1));
+    assert_eq!(sshfp_algorithm("sk-ssh-ed25519@openssh.com"), None);
+}
+
+#[test]
+fn test_host_key_and_fingerprints() {
+    let adapter = SshAdapter::new();
+    let key = github_key();
+    assert_eq!(key.key_type, "ssh-ed25519");
+    assert_eq!(key.algorithm, 4);
+    assert_eq!(key.public_key, GITHUB_ED25519);
+    assert_eq!(key.fingerprint, GITHUB_ED25519_SHA256);
+
+    // The same digest as the "SHA256:" form, in hex
+    let sha256 = adapter.fingerprint(&key, 2).unwrap();
+    assert_eq!(sha256.len(), 64);
+    assert!(sha256.starts_with("f83898df"));
+    assert_eq!(adapter.fingerprint(&key, 1).unwrap().len(), 40);
+    assert_eq!(adapter.fingerprint(&key, 3), None);
+
+    assert!(adapter
+        .host_key(&blob("sk-ssh-ed25519@openssh.com", b"key"))
+        .is_none());
+    assert!(adapter.host_key(b"\x00\x00").is_none());
+}
+
+#[test]
+fn test_verify_sshfp_matched() {
+    let adapter = SshAdapter::new();
+    let key = github_key();
+    let sha256 = adapter.fingerprint(&key, 2).unwrap();
+    let mut report = report(vec![record(4, 2, &sha256)], vec![key]);
+
+    adapter.verify_sshfp(&mut report);
+    assert_eq!(report.records[0].matched, Some(true));
+    assert_eq!(report.host_keys[0].status, HostKeyStatus::Matched);
+    assert_eq!(report.verified, Some(true));
+    assert!(report.warnings.is_empty(), "{:?}", report.warnings);
+}
+
+#[test]
+fn test_verify_sshfp_mismatched_and_stale() {
+    let adapter = SshAdapter::new();
+    let ed25519 = github_key();
+    let rsa = adapter.host_key(&blob("ssh-rsa", b"modulus")).unwrap();
+    let mut report = report(
+        vec![
+            record(4, 2, &"ab".repeat(32)),
+            record(3, 2, &"cd".repeat(32)),
+        ],
+        vec![ed25519, rsa],
+    );
+    report.authenticated = false;
+
+    adapter.verify_sshfp(&mut report);
+    assert_eq!(report.records[0].matched, Some(false));
+    assert_eq!(report.records[1].matched, None);
+    assert_eq!(report.host_keys[0].status, HostKeyStatus::Mismatched);
+    assert_eq!(report.host_keys[1].status, HostKeyStatus::Unpublished);
+    assert_eq!(report.verified, Some(false));
+    let warnings = report.warnings.join("\n");
+    assert!(warnings.contains("aren't DNSSEC-validated"));
+    assert!(warnings.contains("ECDSA key, which the server doesn't have"));
+    assert!(warnings.contains("ssh-ed25519 host key (SHA256:"));
+    assert!(warnings.contains("ssh-rsa host key has no SSHFP record"));
+}
+
+#[test]
+fn test_verify_sshfp_without_connecting() {
+    let adapter = SshAdapter::new();
+    let mut report = report(vec![record(1, 1, &"ef".repeat(20))], Vec::new());
+    adapter.verify_sshfp(&mut report);
+    assert_eq!(report.records[0].matched, None);
+    assert_eq!(report.verified, None);
+    assert_eq!(
+        report.warnings,
+        ["The RSA key is only published as a SHA-1 fingerprint; add a SHA-256 (type 2) record"]
+    );
+
+    let mut report = self::report(Vec::new(), Vec::new());
+    adapter.verify_sshfp(&mut report);
+    assert_eq!(
+        report.warnings,
+        ["host.example.com publishes no SSHFP records"]
+    );
+}
+
+// An Ed25519 SSHFP record (DNSSEC-validated) and one address
+fn zone(sshfp: &str) -> ZoneDig {
+    let record = format!("4 2 {} {}", &sshfp[..32], &sshfp[32..]);
+    ZoneDig::new(&[
+        ("host.example.com", "SSHFP", record.as_str()),
+        ("host.example.com", "A", "192.0.2.22"),
+    ])
+    .authenticated()
+}
+
+// Has an Ed25519 and an RSA key, presenting the first offered one it has
+struct CannedSsh {
+    offers: Arc<Mutex<Vec<Vec<String>>>>,
+}
+
+impl SshBackend for CannedSsh {
+    fn host_key<'a>(
+        &'a self,
+        _: IpAddr,
+        _: u16,
+        algorithms: &'a [String],
+        _: Duration,
+    ) -> BackendFuture<'a, HostKeyExchange> {
+        self.offers.lock().unwrap().push(algorithms.to_vec());
+        let key = if algorithms.iter().any(|a| a == "ssh-ed25519") {
+            BASE64.decode(GITHUB_ED25519).unwrap()
+        } else {
+            blob("ssh-rsa", b"modulus")
+        };
+        Box::pin(async move {
+            Ok(HostKeyExchange {
+                banner: "SSH-2.0-OpenSSH_9.6".to_string(),
+                algorithms: vec![
+                    "rsa-sha2-512".to_string(),
+                    "ssh-rsa".to_string(),
+                    "ssh-ed25519".to_string(),
+                ],
+                key,
+            })
+        })
+    }
+}
+
+#[tokio::test]
+async fn test_check_sshfp_with_canned_backends() {
+    let sshfp = SshAdapter::new().fingerprint(&github_key(), 2).unwrap();
+    let offers = Arc::new(Mutex::new(Vec::new()));
+    let adapter =
+        SshAdapter::new().with_backends(Backends::default().with_dns(zone(&sshfp)).with_ssh(
+            CannedSsh {
+                offers: offers.clone(),
+            },
+        ));
+
+    let report = adapter
+        .check_sshfp("host.example.com", 22, true)
+        .await
+        .unwrap();
+    assert!(report.authenticated);
+    assert_eq!(report.records.len(), 1);
+    assert_eq!(report.address.as_deref(), Some("192.0.2.22"));
+    assert_eq!(report.banner.as_deref(), Some("SSH-2.0-OpenSSH_9.6"));
+    let types: Vec<&str> = report
+        .host_keys
+        .iter()
+        .map(|key| key.key_type.as_str())
+        .collect();
+    assert_eq!(types, ["ssh-ed25519", "ssh-rsa"]);
+    assert_eq!(report.records[0].matched, Some(true));
+    assert_eq!(report.verified, Some(true));
+
+    // Everything first, then only the RSA algorithms the server offered
+    let offers = offers.lock().unwrap();
+    assert_eq!(offers.len(), 2);
+    assert_eq!(offers[0][0], "ssh-ed25519");
+    assert_eq!(offers[1], ["rsa-sha2-512", "ssh-rsa"]);
+}
+
+#[tokio::test]
+async fn test_check_sshfp_without_verify_skips_connecting() {
+    let offers = Arc::new(Mutex::new(Vec::new()));
+    let adapter = SshAdapter::new().with_backends(
+        Backends::default()
+            .with_dns(zone(&"ab".repeat(32)))
+            .with_ssh(CannedSsh {
+                offers: offers.clone(),
+            }),
+    );
+
+    let report = adapter
+        .check_sshfp("host.example.com", 22, false)
+        .await
+        .unwrap();
+    assert_eq!(report.records[0].algorithm_name, "Ed25519");
+    assert_eq!(report.records[0].fingerprint, "ab".repeat(32));
+    assert!(report.host_keys.is_empty());
+    assert_eq!(report.verified, None);
+    assert!(offers.lock().unwrap().is_empty());
+}
+
+async fn read_packet(stream: &mut BufReader<tokio::net::TcpStream>) -> Vec<u8> {
+    let length = stream.read_u32().await.unwrap() as usize;
+    let mut packet = vec![0u8; length];
+    stream.read_exact(&mut packet).await.unwrap();
+    let padding = packet[0] as usize;
+    packet[1..length - padding].to_vec()
+}
+
+fn packet(payload: &[u8]) -> Vec<u8> {
+    let padding = 8 - (5 + payload.len()) % 8 + 8;
+    let mut packet = ((1 + payload.len() + padding) as u32)
+        .to_be_bytes()
+        .to_vec();
+    packet.push(padding as u8);
+    packet.extend_from_slice(payload);
+    packet.resize(packet.len() + padding, 0);
+    packet
+}
+
+#[tokio::test]
+async fn test_tcp_ssh_against_local_server() {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let port = listener.local_addr().unwrap().port();
+    let key = blob("ssh-ed25519", &[7u8; 32]);
+    let presented = key.clone();
+    let server = tokio::spawn(async move {
+        let (stream, _) = listener.accept().await.unwrap();
+        let mut stream = BufReader::new(stream);
+        stream
+            .get_mut()
+            .write_all(b"Welcome\r\nSSH-2.0-Test_1.0\r\n")
+            .await
+            .unwrap();
+        let mut version = String::new();
+        stream.read_line(&mut version).await.unwrap();
+
+        let mut kexinit = vec![20u8];
+        kexinit.extend_from_slice(&[0u8; 16]);
+        wire_string(&mut kexinit, b"sntrup761x25519-sha512,curve25519-sha256");
+        wire_string(&mut kexinit, b"rsa-sha2-512,ssh-ed25519");
+        for _ in 0..8 {
+            wire_string(&mut kexinit, b"none");
+        }
+        kexinit.extend_from_slice(&[0, 0, 0, 0, 0]);
+        stream
+            .get_mut()
+            .write_all(&packet(&[2, 0, 0, 0, 0]))
+            .await
+            .unwrap();
+        stream.get_mut().write_all(&packet(&kexinit)).await.unwrap();
+
+        let client_kexinit = read_packet(&mut stream).await;
+        let ecdh_init = read_packet(&mut stream).await;
+        let mut reply = vec![31u8];
+        wire_string(&mut reply, &presented);
+        wire_string(&mut reply, &[9u8; 32]);
+        wire_string(&mut reply, b"signature");
+        stream.get_mut().write_all(&packet(&reply)).await.unwrap();
+        (version, client_kexinit, ecdh_init)
+    });
+
+    let algorithms = vec!["ssh-ed25519".to_string()];
+    let exchange = TcpSsh
+        .host_key(
+            "127.0.0.1".parse().unwrap(),
+            port,
+            &algorithms,
+            Duration::from_secs(5),
+        )
+        .await
+        .unwrap();
+    assert_eq!(exchange.banner, "SSH-2.0-Test_1.0");
+    assert_eq!(exchange.algorithms, ["rsa-sha2-512", "ssh-ed25519"]);
+    assert_eq!(exchange.key, key);
+
+    let (version, client_kexinit, ecdh_init) = server.await.unwrap();
+    assert!(version.starts_with("SSH-2.0-d_dns_debugger_"));
+    let mut fields = WireReader::new(&client_kexinit[17..]);
+    assert_eq!(fields.name_list().unwrap(), ["curve25519-sha256"]);
+    assert_eq!(fields.name_list().unwrap(), ["ssh-ed25519"]);
+    // An X25519 public key
+    assert_eq!(ecdh_init[0], 30);
+    assert_eq!(WireReader::new(&ecdh_init[1..]).string().unwrap().len(), 32);
+}
+
+#[tokio::test]
+async fn test_tcp_ssh_reports_disconnect() {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let port = listener.local_addr().unwrap().port();
+    tokio::spawn(async move {
+        let (mut stream, _) = listener.accept().await.unwrap();
+        stream.write_all(b"SSH-2.0-Test_1.0\r\n").await.unwrap();
+        let mut disconnect = vec![1u8, 0, 0, 0, 12];
+        wire_string(&mut disconnect, b"Too many connections");
+        wire_string(&mut disconnect, b"");
+        stream.write_all(&packet(&disconnect)).await.unwrap();
+    });
+
+    let error = TcpSsh
+        .host_key(
+            "127.0.0.1".parse().unwrap(),
+            port,
+            &["ssh-ed25519".to_string()],
+            Duration::from_secs(5),
+        )
+        .await
+        .unwrap_err();
+    assert_eq!(
+        error,
+        "The server disconnected (reason 12): Too many connections"
+    );
+}
@@ -0,0 +1,124 @@
+use super::backend::{BackendFuture, DnsBackend, ProcessOutput};
+use crate::models::dns::{DnsRecord, RecordTypeResult};
+use crate::models::report::HttpCheck;
+use serde_json::json;
+use std::sync::{Arc, Mutex};
+
+// Answers dig from a fixed set of records, by the name and type it is asked for:
+// NXDOMAIN for names without any records, an empty answer for types they lack.
+// Each query is recorded as "name TYPE".
+pub struct ZoneDig {
+    records: Vec<(String, String, String)>, // name, type, value
+    authenticated: bool,
+    pub queried: Arc<Mutex<Vec<String>>>,
+}
+
+impl ZoneDig {
+    pub fn new<S: AsRef<str>>(records: &[(S, S, S)]) -> Self {
+        ZoneDig {
+            records: records
+                .iter()
+                .map(|(n, t, v)| {
+                    let (n, t, v) = (n.as_ref(), t.as_ref(), v.as_ref());
+                    (n.to_string(), t.to_string(), v.to_string())
+                })
+                .collect(),
+            authenticated: false,
+            queried: Arc::new(Mutex::new(Vec::new())),
+        }
+    }
+
+    // Set the AD flag, as a validating resolver would
+    pub fn authenticated(mut self) -> Self {
+        self.authenticated = true;
+        self
+    }
+}
+
+impl DnsBackend for ZoneDig {
+    fn is_available(&self) -> bool {
+        true
+    }
+
+    fn dig<'a>(&'a self, args: &'a [String]) -> BackendFuture<'a, ProcessOutput> {
+        let name = args[args.len() - 1].as_str();
+        let record_type = args[args.len() - 2].as_str();
+        self.queried
+            .lock()
+            .unwrap()
+            .push(format!("{} {}", name, record_type));
+        let exists = self.records.iter().any(|(n, _, _)| n == name);
+        let answers: Vec<String> = self
+            .records
+            .iter()
+            .filter(|(n, t, _)| n == name && t == record_type)
+            .map(|(n, t, v)| format!("{}.\t300\tIN\t{}\t{}", n, t, v))
+            .collect();
+        let stdout = format!(
+            ";; ->>HEADER<<- opcode: QUERY, status: {}, id: 1\n;; flags: qr rd ra{}; QUERY: 1, ANSWER: {}, AUTHORITY: 0, ADDITIONAL: 0\n\n;; ANSWER SECTION:\n{}\n",
+            if exists { "NOERROR" } else { "NXDOMAIN" },
+            if self.authenticated { " ad" } else { "" },
+            answers.len(),
+            answers.join("\n")
+        );
+        Box::pin(async move {
+            Ok(ProcessOutput {
+                stdout,
+                stderr: String::new(),
+                exit_code: 0,
+            })
+        })
+    }
+}
+
+// A lookup of example.com's records of one type, given as (value, TTL)
+pub fn records(record_type: &str, values: &[(&str, u32)]) -> RecordTypeResult {
+    RecordTypeResult {
+        record_type: record_type.to_string(),
+        records: values
+            .iter()
+            .map(|(value, ttl)| DnsRecord {
+                name: "example.com.".to_string(),
+                record_type: record_type.to_string(),
+                value: value.to_string(),
+                ttl: *ttl,
+            })
+            .collect(),
+        query_time: 0.01,
+        error: None,
+    }
+}
+
+// A 200 from final_url after the redirects (status, from, to), with headers as
+// [name, value] pairs
+pub fn http_check(
+    url: &str,
+    redirects: &[(u16, &str, &str)],
+    final_url: &str,
+    headers: serde_json::Value,
+) -> HttpCheck {
+    let redirects: Vec<serde_json::Value> = redirects
+        .iter()
+        .map(|(status, from, to)| {
+            json!({
+                "from_url": from, "to_url": to, "status_code": status,
+                "response_time": 0.1, "headers": [],
+            })
+        })
+        .collect();
+    HttpCheck {
+        url: url.to_string(),
+        response: Some(
+            serde_json::from_value(json!({
+                "url": url,
+                "status_code": 200,
+                "final_url": final_url,
+                "redirects": redirects,
+                "headers": headers,
+                "response_time": 0.1,
+            }))
+            .unwrap(),
+        ),
+        error: None,
+    }
+}
@@ -0,0 +1,371 @@
+use crate::adapters::backend::{BackendFuture, Backends, ProcessOutput, TraceBackend};
+use crate::adapters::dns::DnsAdapter;
+use crate::adapters::environment::EnvironmentAdapter;
+use crate::models::command_log::CommandLog;
+use crate::models::traceroute::{HopResponder, TraceHop, TraceRoute};
+use crate::state;
+use crate::state::command_logs;
+use crate::state::rate_limiter::{self, Target};
+use std::collections::HashMap;
+use std::net::IpAddr;
+use std::process::Stdio;
+use std::sync::OnceLock;
+use std::time::Instant;
+use tauri::AppHandle;
+use tokio::io::{AsyncBufReadExt, BufReader};
+use tokio::sync::mpsc;
+use tracing::instrument;
+
+// Programs that can trace a route, in order of preference
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TraceTool {
+    Traceroute, // UDP probes on Linux and macOS
+    Tracepath,  // iputils, needs no privileges; one line per probe
+    Tracert,    // ICMP, built into Windows
+}
+
+impl TraceTool {
+    #[cfg(windows)]
+    pub const ALL: &[TraceTool] = &[TraceTool::Tracert];
+    #[cfg(not(windows))]
+    pub const ALL: &[TraceTool] = &[TraceTool::Traceroute, TraceTool::Tracepath];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            TraceTool::Traceroute => "traceroute",
+            TraceTool::Tracepath => "tracepath",
+            TraceTool::Tracert => "tracert",
+        }
+    }
+
+    // The first tool installed on the PATH, looked up once per process
+    pub fn detect() -> Option<TraceTool> {
+        static DETECTED: OnceLock<Option<TraceTool>> = OnceLock::new();
+        *DETECTED.get_or_init(TraceTool::locate)
+    }
+
+    // Looked up again, to pick up a tool installed since the app started
+    pub fn locate() -> Option<TraceTool> {
+        let environment = EnvironmentAdapter::new();
+        TraceTool::ALL
+            .iter()
+            .copied()
+            .find(|tool| environment.find(tool.name()).is_some())
+    }
+
+    // Numeric output only; names are looked up afterwards through DnsAdapter
+    pub fn args(&self, address: IpAddr, max_hops: u8) -> Vec<String> {
+        let max_hops = max_hops.to_string();
+        let args: Vec<&str> = match self {
+            TraceTool::Traceroute => vec!["-n", "-q", "3", "-w", "2", "-m", &max_hops],
+            TraceTool::Tracepath => vec!["-n", "-m", &max_hops],
+            TraceTool::Tracert => vec!["-d", "-w", "2000", "-h", &max_hops],
+        };
+        let mut args: Vec<String> = args.into_iter().map(str::to_string).collect();
+        args.push(address.to_string());
+        args
+    }
+}
+
+// Runs the detected tool, streaming its stdout line by line
+pub struct TraceProcess {
+    tool: Option<TraceTool>, // None when none of them is installed
+}
+
+impl TraceProcess {
+    pub fn detect() -> Self {
+        TraceProcess {
+            tool: TraceTool::detect(),
+        }
+    }
+
+    pub fn locate() -> Self {
+        TraceProcess {
+            tool: TraceTool::locate(),
+        }
+    }
+
+    pub fn with_tool(tool: TraceTool) -> Self {
+        TraceProcess { tool: Some(tool) }
+    }
+}
+
+impl TraceBackend for TraceProcess {
+    fn is_available(&self) -> bool {
+        self.tool.is_some()
+    }
+
+    fn program(&self) -> &str {
+        self.tool.unwrap_or(TraceTool::Traceroute).name()
+    }
+
+    fn command_line(&self, address: IpAddr, max_hops: u8) -> Vec<String> {
+        self.tool
+            .unwrap_or(TraceTool::Traceroute)
+            .args(address, max_hops)
+    }
+
+    fn trace<'a>(
+        &'a self,
+        address: IpAddr,
+        max_hops: u8,
+        on_line: &'a (dyn Fn(&str) + Send + Sync),
+    ) -> BackendFuture<'a, ProcessOutput> {
+        Box::pin(async move {
+            let tool = self.tool.unwrap_or(TraceTool::Traceroute);
+            // Killed when a cancelled job drops this future
+            let mut child = tokio::process::Command::new(tool.name())
+                .args(tool.args(address, max_hops))
+                .stdout(Stdio::piped())
+                .stderr(Stdio::piped())
+                .kill_on_drop(true)
+                .spawn()
+                .map_err(|e| format!("Failed to execute {}: {}", tool.name(), e))?;
+
+            let mut stdout = String::new();
+            if let Some(pipe) = child.stdout.take() {
+                let mut lines = BufReader::new(pipe).lines();
+                while let Ok(Some(line)) = lines.next_line().await {
+                    on_line(&line);
+                    stdout.push_str(&line);
+                    stdout.push('\n');
+                }
+            }
+
+            let output = child
+                .wait_with_output()
+                .await
+                .map_err(|e| format!("Failed to execute {}: {}", tool.name(), e))?;
+            Ok(ProcessOutput {
+                stdout,
+                stderr: String::from_utf8_lossy(&output.stderr).to_string(),
+                exit_code: output.status.code().unwrap_or(-1),
+            })
+        })
+    }
+}
+
+#[derive(Clone)]
+pub struct TracerouteAdapter {
+    app_handle: Option<AppHandle>,
+    backends: Backends,
+}
+
+impl TracerouteAdapter {
+    pub fn new() -> Self {
+        TracerouteAdapter {
+            app_handle: None,
+            backends: Backends::default(),
+        }
+    }
+
+    pub fn with_app_handle(app_handle: AppHandle) -> Self {
+        TracerouteAdapter {
+            backends: state::backends_for(Some(&app_handle)),
+            app_handle: Some(app_handle),
+        }
+    }
+
+    // Trace and resolve through these instead of the app's backends
+    pub fn with_backends(mut self, backends: Backends) -> Self {
+        self.backends = backends;
+        self
+    }
+
+    fn emit_log(&self, log: CommandLog) {
+        command_logs::record(self.app_handle.as_ref(), log);
+    }
+
+    fn dns(&self) -> DnsAdapter {
+        DnsAdapter::from_app_handle(self.app_handle.clone()).with_backends(self.backends.clone())
+    }
+
+    // Trace the route to host, calling on_hop with each hop (its responders' names
+    // resolved) as the tool reports it
+    #[instrument(skip_all, fields(host = %host, max_hops))]
+    pub async fn trace(
+        &self,
+        host: &str,
+        max_hops: u8,
+        on_hop: impl Fn(&TraceHop),
+    ) -> Result<TraceRoute, String> {
+        let start = Instant::now();
+        let backend = self.backends.trace.clone();
+        if !backend.is_available() {
+            return Err(format!(
+                "No traceroute tool found (install {})",
+                TraceTool::ALL
+                    .iter()
+                    .map(|tool| tool.name())
+                    .collect::<Vec<_>>()
+                    .join(" or ")
+            ));
+        }
+        let address = self.dns().first_address(host).await?;
+        let waited =
+            rate_limiter::throttle(self.app_handle.as_ref(), Target::Host, &address.to_string())
+                .await;
+
+        // Lines go through a channel so hops are parsed and resolved while the tool
+        // keeps probing; the sender is dropped, ending the loop below, when it exits
+        let (lines_tx, mut lines_rx) = mpsc::unbounded_channel::<String>();
+        let run = async {
+            let on_line = move |line: &str| {
+                let _ = lines_tx.send(line.to_string());
+            };
+            backend.trace(address, max_hops, &on_line).await
+        };
+        let collect = async {
+            let mut hops: Vec<TraceHop> = Vec::new();
+            let mut names: HashMap<String, Option<String>> = HashMap::new();
+            while let Some(line) = lines_rx.recv().await {
+                let Some(mut parsed) = self.parse_hop(&line) else {
+                    continue;
+                };
+                for responder in parsed.responders.iter_mut() {
+                    if !names.contains_key(&responder.address) {
+                        names.insert(responder.address.clone(), self.hostname(responder).await);
+                    }
+                    responder.hostname = names[&responder.address].clone();
+                }
+                let hop = match hops.iter_mut().find(|hop| hop.ttl == parsed.ttl) {
+                    Some(hop) => {
+                        self.merge_hop(hop, parsed);
+                        hop.clone()
+                    }
+                    None => {
+                        hops.push(parsed.clone());
+                        parsed
+                    }
+                };
+                on_hop(&hop);
+            }
+            hops
+        };
+        let (output, mut hops) = tokio::join!(run, collect);
+        let output = output?;
+
+        self.emit_log(
+            CommandLog::new(
+                backend.program().to_string(),
+                backend.command_line(address, max_hops),
+                format!("{}{}", output.stdout, output.stderr),
+                output.exit_code,
+                start.elapsed().as_secs_f64() * 1000.0,
+                Some(host.to_string()),
+            )
+            .with_throttled(waited),
+        );
+        if hops.is_empty() && !output.success() {
+            return Err(format!(
+                "{} failed: {}",
+                backend.program(),
+                output.stderr.trim()
+            ));
+        }
+
+        hops.sort_by_key(|hop| hop.ttl);
+        let reached = hops.last().is_some_and(|hop| {
+            hop.responders
+                .iter()
+                .any(|r| r.address.parse::<IpAddr>() == Ok(address))
+        });
+        Ok(TraceRoute {
+            host: host.to_string(),
+            address: address.to_string(),
+            tool: backend.program().to_string(),
+            hops,
+            reached,
+            total_time: start.elapsed().as_secs_f64(),
+        })
+    }
+
+    async fn hostname(&self, responder: &HopResponder) -> Option<String> {
+        let address = responder.address.parse().ok()?;
+        self.dns()
+            .reverse_lookup(address)
+            .await
+            .ok()?
+            .into_iter()
+            .next()
+    }
+
+    // One hop line of traceroute, tracepath or tracert's numeric output:
+    //   traceroute:  3  10.0.0.1  5.102 ms 10.0.0.2  5.310 ms *
+    //   tracepath:   3:  10.0.0.1                          5.102ms asymm  4
+    //   tracert:     3     5 ms    <1 ms     *     10.0.0.1
+    // Headers, summaries and tracepath's "1?: [LOCALHOST]" lines are skipped.
+    pub fn parse_hop(&self, line: &str) -> Option<TraceHop> {
+        let mut tokens = line.split_whitespace().peekable();
+        let ttl: u8 = tokens.next()?.trim_end_matches(':').parse().ok()?;
+
+        let mut responders = Vec::new();
+        let mut rtts_ms = Vec::new();
+        while let Some(token) = tokens.next() {
+            if token == "*" {
+                rtts_ms.push(None);
+            } else if token == "no" && tokens.peek() == Some(&"reply") {
+                tokens.next();
+                rtts_ms.push(None);
+            } else if let Ok(address) = token.parse::<IpAddr>() {
+                let address = address.to_string();
+                if !responders
+                    .iter()
+                    .any(|r: &HopResponder| r.address == address)
+                {
+                    responders.push(HopResponder {
+                        address,
+                        hostname: None,
+                    });
+                }
+            } else if let Some(rtt) = token.strip_suffix("ms").and_then(|n| n.parse().ok()) {
+                rtts_ms.push(Some(rtt));
+            } else if let Ok(rtt) = token.trim_start_matches('<').parse::<f64>() {
+                // A number is only a round trip when followed by its unit
+                if tokens.peek() == Some(&"ms") {
+                    tokens.next();
+                    rtts_ms.push(Some(rtt));
+                }
+            }
+        }
+
+        if responders.is_empty() && rtts_ms.is_empty() {
+            return None;
+        }
+        let mut hop = TraceHop {
+            ttl,
+            responders,
+            rtts_ms,
+            avg_ms: None,
+            loss_percent: 0.0,
+        };
+        self.summarize_hop(&mut hop);
+        Some(hop)
+    }
+
+    // Another line for a hop already seen (tracepath's next probe)
+    pub fn merge_hop(&self, hop: &mut TraceHop, line: TraceHop) {
+        for responder in line.responders {
+            if !hop.responders.contains(&responder) {
+                hop.responders.push(responder);
+            }
+        }
+        hop.rtts_ms.extend(line.rtts_ms);
+        self.summarize_hop(hop);
+    }
+
+    fn summarize_hop(&self, hop: &mut TraceHop) {
+        let received: Vec<f64> = hop.rtts_ms.iter().flatten().copied().collect();
+        hop.avg_ms =
+            (!received.is_empty()).then(|| received.iter().sum::<f64>() / received.len() as f64);
+        hop.loss_percent = if hop.rtts_ms.is_empty() {
+            0.0
+        } else {
+            (hop.rtts_ms.len() - received.len()) as f64 * 100.0 / hop.rtts_ms.len() as f64
+        };
+    }
+}
+
+#[cfg(test)]
+#[path = "traceroute_test.rs"]
+mod tests;
@@ -0,0 +1,143 @@
+use super::super::backend::{BackendFuture, Backends, ProcessOutput, TraceBackend};
+use super::super::test_support::ZoneDig;
+use super::{TraceTool, TracerouteAdapter};
+use std::net::IpAddr;
+use std::sync::Mutex;
+
+#[test]
+fn test_parse_traceroute_hop() {
+    let adapter = TracerouteAdapter::new();
+    let hop = adapter
+        .parse_hop(" 3  10.0.0.1  5.102 ms 10.0.0.2  6.898 ms *")
+        .unwrap();
+
+    assert_eq!(hop.ttl, 3);
+    let addresses: Vec<&str> = hop.responders.iter().map(|r| r.address.as_str()).collect();
+    assert_eq!(addresses, ["10.0.0.1", "10.0.0.2"]);
+    assert_eq!(hop.rtts_ms, [Some(5.102), Some(6.898), None]);
+    assert_eq!(hop.avg_ms, Some(6.0));
+
+    let hop = adapter.parse_hop(" 4  * * *").unwrap();
+    assert!(hop.responders.is_empty());
+    assert_eq!(hop.loss_percent, 100.0);
+    assert_eq!(hop.avg_ms, None);
+
+    assert!(adapter
+        .parse_hop("traceroute to 192.0.2.1 (192.0.2.1), 30 hops max, 60 byte packets")
+        .is_none());
+}
+
+#[test]
+fn test_parse_tracepath_hops() {
+    let adapter = TracerouteAdapter::new();
+    assert!(adapter
+        .parse_hop(" 1?: [LOCALHOST]                      pmtu 1500")
+        .is_none());
+    assert!(adapter
+        .parse_hop("     Resume: pmtu 1500 hops 2 back 2 ")
+        .is_none());
+
+    let mut hop = adapter
+        .parse_hop(" 1:  192.168.1.1                     0.504ms asymm  2")
+        .unwrap();
+    assert_eq!(hop.rtts_ms, [Some(0.504)]);
+    let next = adapter.parse_hop(" 1:  no reply").unwrap();
+    adapter.merge_hop(&mut hop, next);
+    assert_eq!(hop.responders.len(), 1);
+    assert_eq!(hop.rtts_ms, [Some(0.504), None]);
+    assert_eq!(hop.loss_percent, 50.0);
+}
+
+#[test]
+fn test_parse_tracert_hop() {
+    let adapter = TracerouteAdapter::new();
+    let hop = adapter
+        .parse_hop("  2    <1 ms     3 ms     *     2001:db8::1")
+        .unwrap();
+
+    assert_eq!(hop.ttl, 2);
+    assert_eq!(hop.responders[0].address, "2001:db8::1");
+    assert_eq!(hop.rtts_ms, [Some(1.0), Some(3.0), None]);
+    assert!(adapter
+        .parse_hop("Tracing route to 192.0.2.1 over a maximum of 30 hops")
+        .is_none());
+}
+
+#[test]
+fn test_tool_args() {
+    let address: IpAddr = "192.0.2.1".parse().unwrap();
+    assert_eq!(
+        TraceTool::Tracert.args(address, 20),
+        ["-d", "-w", "2000", "-h", "20", "192.0.2.1"]
+    );
+    assert_eq!(
+        TraceTool::Tracepath.args(address, 20),
+        ["-n", "-m", "20", "192.0.2.1"]
+    );
+}
+
+// Prints a two-hop route, one line at a time
+struct CannedTrace;
+
+impl TraceBackend for CannedTrace {
+    fn is_available(&self) -> bool {
+        true
+    }
+
+    fn trace<'a>(
+        &'a self,
+        address: IpAddr,
+        _: u8,
+        on_line: &'a (dyn Fn(&str) + Send + Sync),
+    ) -> BackendFuture<'a, ProcessOutput> {
+        Box::pin(async move {
+            let stdout = format!(
+                "traceroute to {0} ({0}), 30 hops max, 60 byte packets\n 1  10.0.0.1  0.512 ms  0.488 ms  0.501 ms\n 2  {0}  9.100 ms  *  8.900 ms\n",
+                address
+            );
+            for line in stdout.lines() {
+                on_line(line);
+            }
+            Ok(ProcessOutput {
+                stdout,
+                stderr: String::new(),
+                exit_code: 0,
+            })
+        })
+    }
+}
+
+#[tokio::test]
+async fn test_trace_streams_hops() {
+    let dig = ZoneDig::new(&[
+        ("1.0.0.10.in-addr.arpa", "PTR", "hop.example.net."),
+        ("1.2.0.192.in-addr.arpa", "PTR", "dest.example.net."),
+    ]);
+    let queried = dig.queried.clone();
+    let backends = Backends::default().with_trace(CannedTrace).with_dns(dig);
+    let adapter = TracerouteAdapter::new().with_backends(backends);
+
+    let streamed = Mutex::new(Vec::new());
+    let route = adapter
+        .trace("192.0.2.1", 30, |hop| {
+            streamed.lock().unwrap().push(hop.ttl)
+        })
+        .await
+        .unwrap();
+
+    assert_eq!(*streamed.lock().unwrap(), [1, 2]);
+    assert_eq!(route.tool, "traceroute");
+    assert_eq!(route.hops.len(), 2);
+    assert!(route.reached);
+    assert_eq!(route.hops[1].loss_percent, 100.0 / 3.0);
+    assert_eq!(
+        route.hops[0].responders[0].hostname.as_deref(),
+        Some("hop.example.net")
+    );
+    assert_eq!(
+        route.hops[1].responders[0].hostname.as_deref(),
+        Some("dest.example.net")
+    );
+    // One PTR lookup per responder
+    assert_eq!(queried.lock().unwrap().len(), 2);
+}
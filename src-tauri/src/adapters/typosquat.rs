@@ -0,0 +1,506 @@
+use crate::adapters::backend::Backends;
+use crate::adapters::dns::DnsAdapter;
+use crate::adapters::http::HttpAdapter;
+use crate::adapters::whois::WhoisAdapter;
+use crate::models::typosquat::{RegistrantGroup, TyposquatReport, TyposquatVariant, VariantKind};
+use crate::models::whois::WhoisInfo;
+use crate::state;
+use std::collections::{BTreeMap, HashSet};
+use std::sync::Arc;
+use std::time::Instant;
+use tauri::AppHandle;
+use tokio::sync::Semaphore;
+use tokio::task::JoinSet;
+use tracing::instrument;
+
+// TLDs each name is tried under besides its own: the big generics, ones popular
+// with startups, and cheap ones favoured for throwaway registrations
+const VARIANT_TLDS: [&str; 15] = [
+    "com", "net", "org", "co", "io", "info", "biz", "app", "dev", "xyz", "online", "site", "shop",
+    "us", "eu",
+];
+
+// Keys around each letter on a QWERTY keyboard
+const KEYBOARD_NEIGHBOURS: [(char, &str); 26] = [
+    ('a', "qwsz"),
+    ('b', "vghn"),
+    ('c', "xdfv"),
+    ('d', "erfcxs"),
+    ('e', "wsdr"),
+    ('f', "rtgvcd"),
+    ('g', "tyhbvf"),
+    ('h', "yujnbg"),
+    ('i', "ujko"),
+    ('j', "uikmnh"),
+    ('k', "iolmj"),
+    ('l', "opk"),
+    ('m', "njk"),
+    ('n', "bhjm"),
+    ('o', "iklp"),
+    ('p', "ol"),
+    ('q', "wa"),
+    ('r', "edft"),
+    ('s', "wedxza"),
+    ('t', "rfgy"),
+    ('u', "yhji"),
+    ('v', "cfgb"),
+    ('w', "qase"),
+    ('x', "zsdc"),
+    ('y', "tghu"),
+    ('z', "asx"),
+];
+
+// ASCII sequences that read alike, substituted in both directions
+const HOMOGLYPHS: [(&str, &str); 8] = [
+    ("m", "rn"),
+    ("w", "vv"),
+    ("d", "cl"),
+    ("l", "1"),
+    ("l", "i"),
+    ("i", "1"),
+    ("o", "0"),
+    ("g", "q"),
+];
+
+// Cyrillic letters indistinguishable from Latin ones in most fonts. Browsers show
+// punycode for mixed-script names but may not for a name entirely in Cyrillic.
+const CONFUSABLES: [(char, char); 15] = [
+    ('a', '\u{430}'),
+    ('c', '\u{441}'),
+    ('d', '\u{501}'),
+    ('e', '\u{435}'),
+    ('h', '\u{4bb}'),
+    ('i', '\u{456}'),
+    ('j', '\u{458}'),
+    ('l', '\u{4cf}'),
+    ('o', '\u{43e}'),
+    ('p', '\u{440}'),
+    ('q', '\u{51b}'),
+    ('s', '\u{455}'),
+    ('w', '\u{51d}'),
+    ('x', '\u{445}'),
+    ('y', '\u{443}'),
+];
+
+#[derive(Clone)]
+pub struct TyposquatAdapter {
+    app_handle: Option<AppHandle>,
+    backends: Backends,
+}
+
+impl TyposquatAdapter {
+    pub fn new() -> Self {
+        TyposquatAdapter {
+            app_handle: None,
+            backends: Backends::default(),
+        }
+    }
+
+    pub fn with_app_handle(app_handle: AppHandle) -> Self {
+        TyposquatAdapter {
+            backends: state::backends_for(Some(&app_handle)),
+            app_handle: Some(app_handle),
+        }
+    }
+
+    // Resolve and look up registrations through these instead of the app's backends
+    pub fn with_backends(mut self, backends: Backends) -> Self {
+        self.backends = backends;
+        self
+    }
+
+    fn dns(&self) -> DnsAdapter {
+        DnsAdapter::from_app_handle(self.app_handle.clone()).with_backends(self.backends.clone())
+    }
+
+    fn whois(&self) -> WhoisAdapter {
+        WhoisAdapter::from_app_handle(self.app_handle.clone()).with_backends(self.backends.clone())
+    }
+
+    // Lookalikes of domain's registrable name, which of them exist in DNS, and who
+    // registered those according to WHOIS. on_progress gets (done, total, name) for
+    // the DNS checks and then again for the WHOIS lookups.
+    #[instrument(skip_all, fields(domain = %domain))]
+    pub async fn scan<F>(&self, domain: &str, on_progress: F) -> TyposquatReport
+    where
+        F: Fn(usize, usize, &str) + Send + Sync + 'static,
+    {
+        let start = Instant::now();
+        let on_progress = Arc::new(on_progress);
+        let domain =
+            HttpAdapter::from_app_handle(self.app_handle.clone()).registrable_domain(domain);
+        let candidates = self.generate_variants(&domain);
+
+        // Step 1: Which variants exist, and the domain's own nameservers to compare
+        let dns = self.dns();
+        let nameservers = match dns.query(&domain, "NS").await {
+            Ok(response) => dns.extract_ns_names(&response.records, &domain),
+            Err(_) => Vec::new(),
+        };
+        let progress = on_progress.clone();
+        let mut variants = self
+            .resolve_variants(candidates, move |done, total, name| {
+                progress(done, total, name)
+            })
+            .await;
+
+        // Step 2: WHOIS for the domain itself and every variant that exists, paced
+        // per registry
+        let mut lookups = vec![domain.clone()];
+        lookups.extend(
+            variants
+                .iter()
+                .filter(|variant| variant.registered)
+                .map(|variant| variant.domain.clone()),
+        );
+        let progress = on_progress.clone();
+        let summary = self
+            .whois()
+            .lookup_bulk(&lookups, move |result| {
+                progress(result.completed, result.total, &result.domain)
+            })
+            .await;
+        let mut results = summary.results.into_iter();
+        let original = results.next().and_then(|result| result.info);
+        for result in results {
+            let Some(variant) = variants.iter_mut().find(|v| v.domain == result.domain) else {
+                continue;
+            };
+            match result.info {
+                Some(info) => self.apply_whois(variant, &info),
+                None => variant.error = result.error,
+            }
+        }
+
+        let mut report = self.summarize(&domain, &nameservers, original.as_ref(), variants);
+        report.total_time = start.elapsed().as_secs_f64();
+        report
+    }
+
+    // Lookalikes of a registrable domain in ASCII, each once and in the order of
+    // VariantKind, with the technique that made it. The name is varied and the
+    // suffix kept, then the name is put under the other VARIANT_TLDS.
+    pub fn generate_variants(&self, domain: &str) -> Vec<(String, VariantKind)> {
+        let (unicode, _) = idna::domain_to_unicode(domain);
+        let (name, suffix) = unicode.split_once('.').unwrap_or((unicode.as_str(), "com"));
+        let chars: Vec<char> = name.chars().collect();
+        let mut names: Vec<(String, VariantKind)> = Vec::new();
+        let mut push = |chars: Vec<char>, kind| names.push((chars.into_iter().collect(), kind));
+
+        for i in 0..chars.len() {
+            if chars.len() > 1 {
+                let mut omitted = chars.clone();
+                omitted.remove(i);
+                push(omitted, VariantKind::Omission);
+            }
+        }
+        for i in 0..chars.len() {
+            let mut repeated = chars.clone();
+            repeated.insert(i, chars[i]);
+            push(repeated, VariantKind::Repetition);
+        }
+        for i in 1..chars.len() {
+            if chars[i - 1] != chars[i] {
+                let mut swapped = chars.clone();
+                swapped.swap(i - 1, i);
+                push(swapped, VariantKind::Transposition);
+            }
+        }
+        for i in 0..chars.len() {
+            let neighbours = KEYBOARD_NEIGHBOURS
+                .iter()
+                .find(|(key, _)| *key == chars[i])
+                .map(|(_, neighbours)| *neighbours)
+                .unwrap_or_default();
+            for neighbour in neighbours.chars() {
+                let mut replaced = chars.clone();
+                replaced[i] = neighbour;
+                push(replaced, VariantKind::Replacement);
+            }
+        }
+        for i in 1..chars.len() {
+            if chars[i - 1] != '-' && chars[i] != '-' {
+                let mut hyphenated = chars.clone();
+                hyphenated.insert(i, '-');
+                push(hyphenated, VariantKind::Hyphenation);
+            }
+        }
+        for (a, b) in HOMOGLYPHS {
+            for (from, to) in [(a, b), (b, a)] {
+                for (at, _) in name.match_indices(from) {
+                    let swapped = format!("{}{}{}", &name[..at], to, &name[at + from.len()..]);
+                    push(swapped.chars().collect(), VariantKind::Homoglyph);
+                }
+            }
+        }
+        let confusable = |c: char| {
+            CONFUSABLES
+                .iter()
+                .find(|(latin, _)| *latin == c)
+                .map(|(_, lookalike)| *lookalike)
+        };
+        for i in 0..chars.len() {
+            if let Some(lookalike) = confusable(chars[i]) {
+                let mut replaced = chars.clone();
+                replaced[i] = lookalike;
+                push(replaced, VariantKind::Homograph);
+            }
+        }
+        if chars.len() > 1 {
+            if let Some(whole) = chars
+                .iter()
+                .map(|c| confusable(*c))
+                .collect::<Option<Vec<_>>>()
+            {
+                push(whole, VariantKind::Homograph);
+            }
+        }
+
+        let mut seen: HashSet<String> = HashSet::from([domain.to_lowercase()]);
+        let mut variants = Vec::new();
+        let mut add = |candidate: String, kind| {
+            let Ok(ascii) = idna::domain_to_ascii(&candidate) else {
+                return;
+            };
+            if self.is_valid_name(&ascii) && seen.insert(ascii.clone()) {
+                variants.push((ascii, kind));
+            }
+        };
+        for (name, kind) in names {
+            add(format!("{}.{}", name, suffix), kind);
+        }
+        for tld in VARIANT_TLDS {
+            if tld != suffix {
+                add(format!("{}.{}", name, tld), VariantKind::Tld);
+            }
+        }
+        variants
+    }
+
+    // Registrable as a hostname: LDH labels of at most 63 characters, without a
+    // hyphen at either end or in positions 3-4 unless it's punycode
+    fn is_valid_name(&self, ascii: &str) -> bool {
+        ascii.split('.').all(|label| {
+            !label.is_empty()
+                && label.len() <= 63
+                && label
+                    .chars()
+                    .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-')
+                && !label.starts_with('-')
+                && !label.ends_with('-')
+                && (label.get(2..4) != Some("--") || label.starts_with("xn--"))
+        })
+    }
+
+    // Each candidate checked concurrently (bounded by max_concurrency), in the
+    // order given
+    pub async fn resolve_variants<F>(
+        &self,
+        candidates: Vec<(String, VariantKind)>,
+        on_checked: F,
+    ) -> Vec<TyposquatVariant>
+    where
+        F: Fn(usize, usize, &str) + Send + Sync + 'static,
+    {
+        let settings = state::settings_for(self.app_handle.as_ref());
+        let permits = Arc::new(Semaphore::new(settings.max_concurrency.max(1)));
+        let on_checked = Arc::new(on_checked);
+        let total = candidates.len();
+        let mut tasks = JoinSet::new();
+
+        for (index, (domain, kind)) in candidates.into_iter().enumerate() {
+            let adapter = self.clone();
+            let permits = permits.clone();
+            tasks.spawn(async move {
+                let _permit = permits.acquire_owned().await;
+                (index, adapter.check_variant(&domain, kind).await)
+            });
+        }
+
+        let mut variants = Vec::new();
+        while let Some(joined) = tasks.join_next().await {
+            if let Ok((index, variant)) = joined {
+                on_checked(variants.len() + 1, total, &variant.domain);
+                variants.push((index, variant));
+            }
+        }
+        variants.sort_by_key(|(index, _)| *index);
+        variants.into_iter().map(|(_, variant)| variant).collect()
+    }
+
+    // NXDOMAIN means nobody holds the name (or it's registered without
+    // nameservers, which WHOIS would be needed to tell). Anything else is
+    // registered, and its addresses and mail setup are looked up.
+    pub async fn check_variant(&self, domain: &str, kind: VariantKind) -> TyposquatVariant {
+        let mut variant = TyposquatVariant {
+            domain: domain.to_string(),
+            kind,
+            registered: false,
+            addresses: Vec::new(),
+            nameservers: Vec::new(),
+            has_mx: false,
+            registrar: None,
+            registrant: None,
+            created: None,
+            privacy_protected: false,
+            defensive: false,
+            error: None,
+        };
+        let dns = self.dns();
+        match dns.query(domain, "NS").await {
+            Ok(response) => match response.header.as_ref().map(|h| h.rcode.as_str()) {
+                Some("NXDOMAIN") => return variant,
+                Some(_) => {
+                    variant.registered = true;
+                    variant.nameservers = dns.extract_ns_names(&response.records, domain);
+                }
+                None => {
+                    variant.error = Some("No answer to the NS query".to_string());
+                    return variant;
+                }
+            },
+            Err(e) => {
+                variant.error = Some(e);
+                return variant;
+            }
+        }
+
+        let (addresses, mx) = tokio::join!(dns.addresses(domain), dns.query(domain, "MX"));
+        variant.addresses = addresses
+            .unwrap_or_default()
+            .iter()
+            .map(|address| address.to_string())
+            .collect();
+        // A null MX ("0 .") says the domain takes no mail (RFC 7505)
+        variant.has_mx = mx.is_ok_and(|response| {
+            response.records.iter().any(|r| {
+                r.record_type == "MX"
+                    && r.value
+                        .split_whitespace()
+                        .nth(1)
+                        .is_some_and(|host| host != ".")
+            })
+        });
+        variant
+    }
+
+    pub fn apply_whois(&self, variant: &mut TyposquatVariant, info: &WhoisInfo) {
+        variant.registrar = info.registrar.clone();
+        variant.registrant = self.registrant_name(info);
+        variant.created = info.creation_date.clone();
+        variant.privacy_protected = info.privacy_protected;
+    }
+
+    // The registrant's organization, else their name; None when hidden
+    pub fn registrant_name(&self, info: &WhoisInfo) -> Option<String> {
+        if info.privacy_protected {
+            return None;
+        }
+        let registrant = info.registrant.as_ref()?;
+        [&registrant.organization, &registrant.name]
+            .into_iter()
+            .flatten()
+            .map(|name| name.trim())
+            .find(|name| !name.is_empty())
+            .map(str::to_string)
+    }
+
+    // Flag variants that look like the owner's own defensive registrations, group
+    // the rest's registrars and registrants, and describe what stands out
+    pub fn summarize(
+        &self,
+        domain: &str,
+        nameservers: &[String],
+        original: Option<&WhoisInfo>,
+        mut variants: Vec<TyposquatVariant>,
+    ) -> TyposquatReport {
+        let owner = original.and_then(|info| self.registrant_name(info));
+        for variant in variants.iter_mut().filter(|v| v.registered) {
+            let same_owner = match (&owner, &variant.registrant) {
+                (Some(owner), Some(registrant)) => owner.eq_ignore_ascii_case(registrant),
+                _ => false,
+            };
+            let same_nameservers = !nameservers.is_empty() && variant.nameservers == nameservers;
+            variant.defensive = same_owner || same_nameservers;
+        }
+        // Stable, so each group stays in generation order
+        variants.sort_by_key(|variant| !variant.registered);
+
+        let registered: Vec<&TyposquatVariant> = variants
+            .iter()
+            .filter(|variant| variant.registered)
+            .collect();
+        let group = |name_of: &dyn Fn(&TyposquatVariant) -> Option<String>| {
+            let mut groups: BTreeMap<String, Vec<String>> = BTreeMap::new();
+            for variant in &registered {
+                if let Some(name) = name_of(variant) {
+                    groups.entry(name).or_default().push(variant.domain.clone());
+                }
+            }
+            let mut groups: Vec<RegistrantGroup> = groups
+                .into_iter()
+                .map(|(name, domains)| RegistrantGroup { name, domains })
+                .collect();
+            groups.sort_by_key(|group| std::cmp::Reverse(group.domains.len()));
+            groups
+        };
+        let registrars = group(&|variant| variant.registrar.clone());
+        let registrants = group(&|variant| variant.registrant.clone());
+
+        let display = |name: &str| idna::domain_to_unicode(name).0;
+        let mut findings = Vec::new();
+        let others: Vec<&&TyposquatVariant> = registered
+            .iter()
+            .filter(|variant| !variant.defensive)
+            .collect();
+        if registered.is_empty() {
+            findings.push(format!(
+                "None of the {} lookalikes of {} are registered",
+                variants.len(),
+                display(domain)
+            ));
+        } else {
+            findings.push(format!(
+                "{} of {} lookalikes of {} are registered, {} apparently by someone else",
+                registered.len(),
+                variants.len(),
+                display(domain),
+                others.len()
+            ));
+        }
+        for variant in &others {
+            if variant.has_mx {
+                findings.push(format!(
+                    "{} accepts mail: it can receive mistyped mail or send phishing as {}",
+                    display(&variant.domain),
+                    display(domain)
+                ));
+            }
+            if variant.kind == VariantKind::Homograph {
+                findings.push(format!(
+                    "{} ({}) is a homograph that's hard to tell apart from {}",
+                    display(&variant.domain),
+                    variant.domain,
+                    display(domain)
+                ));
+            }
+        }
+
+        TyposquatReport {
+            domain: domain.to_string(),
+            registrar: original.and_then(|info| info.registrar.clone()),
+            registrant: owner,
+            generated: variants.len(),
+            registered: registered.len(),
+            variants,
+            registrars,
+            registrants,
+            findings,
+            total_time: 0.0,
+        }
+    }
+}
+
+#[cfg(test)]
+#[path = "typosquat_test.rs"]
+mod tests;
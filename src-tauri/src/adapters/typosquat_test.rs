@@ -0,0 +1,248 @@
+use super::super::backend::Backends;
+use super::super::test_support::ZoneDig;
+use super::TyposquatAdapter;
+use crate::models::typosquat::{TyposquatVariant, VariantKind};
+use crate::models::whois::{Contact, WhoisInfo, WhoisSource};
+use std::collections::HashSet;
+use std::sync::{Arc, Mutex};
+
+fn has(variants: &[(String, VariantKind)], domain: &str, kind: VariantKind) -> bool {
+    variants.iter().any(|(d, k)| d == domain && *k == kind)
+}
+
+#[test]
+fn test_generate_variants() {
+    let adapter = TyposquatAdapter::new();
+    let variants = adapter.generate_variants("example.com");
+
+    assert!(has(&variants, "exmple.com", VariantKind::Omission));
+    assert!(has(&variants, "exaample.com", VariantKind::Repetition));
+    assert!(has(&variants, "xeample.com", VariantKind::Transposition));
+    assert!(has(&variants, "wxample.com", VariantKind::Replacement));
+    assert!(has(&variants, "ex-ample.com", VariantKind::Hyphenation));
+    assert!(has(&variants, "examp1e.com", VariantKind::Homoglyph));
+    assert!(has(&variants, "exarnple.com", VariantKind::Homoglyph));
+    // exаmple.com with a Cyrillic а
+    let homograph = idna::domain_to_ascii("ex\u{430}mple.com").unwrap();
+    assert!(homograph.starts_with("xn--"));
+    assert!(has(&variants, &homograph, VariantKind::Homograph));
+    assert!(has(&variants, "example.net", VariantKind::Tld));
+    assert!(!has(&variants, "example.com", VariantKind::Tld));
+
+    // Each once, never the domain itself, and all registrable
+    let unique: HashSet<&String> = variants.iter().map(|(d, _)| d).collect();
+    assert_eq!(unique.len(), variants.len());
+    assert!(!unique.contains(&"example.com".to_string()));
+    assert!(variants
+        .iter()
+        .all(|(d, _)| !d.starts_with('-') && !d.contains("-.")));
+}
+
+#[test]
+fn test_generate_variants_keeps_multi_label_suffix() {
+    let adapter = TyposquatAdapter::new();
+    let variants = adapter.generate_variants("shop.co.uk");
+    assert!(has(&variants, "shp.co.uk", VariantKind::Omission));
+    assert!(has(&variants, "shop.com", VariantKind::Tld));
+    assert!(has(&variants, "shop.co", VariantKind::Tld));
+}
+
+#[test]
+fn test_generate_variants_whole_script_homograph() {
+    let adapter = TyposquatAdapter::new();
+    let variants = adapter.generate_variants("apple.com");
+    // аррӏе.com, every letter Cyrillic
+    let whole = idna::domain_to_ascii("\u{430}\u{440}\u{440}\u{4cf}\u{435}.com").unwrap();
+    assert!(has(&variants, &whole, VariantKind::Homograph));
+
+    // Doubled letters give one transposition fewer, and rn reads as m
+    let variants = adapter.generate_variants("modern.io");
+    assert!(has(&variants, "rnodern.io", VariantKind::Homoglyph));
+    assert!(has(&variants, "modem.io", VariantKind::Homoglyph));
+}
+
+fn variant(domain: &str, kind: VariantKind) -> TyposquatVariant {
+    TyposquatVariant {
+        domain: domain.to_string(),
+        kind,
+        registered: true,
+        addresses: Vec::new(),
+        nameservers: Vec::new(),
+        has_mx: false,
+        registrar: None,
+        registrant: None,
+        created: None,
+        privacy_protected: false,
+        defensive: false,
+        error: None,
+    }
+}
+
+fn whois(registrar: &str, organization: Option<&str>, privacy_protected: bool) -> WhoisInfo {
+    WhoisInfo {
+        domain: "example.com".to_string(),
+        registrar: Some(registrar.to_string()),
+        creation_date: Some("1995-08-14T04:00:00Z".to_string()),
+        expiration_date: None,
+        updated_date: None,
+        creation_date_raw: None,
+        expiration_date_raw: None,
+        updated_date_raw: None,
+        nameservers: Vec::new(),
+        status: Vec::new(),
+        dnssec: None,
+        registrant: Some(Contact {
+            name: None,
+            organization: organization.map(str::to_string),
+            email: None,
+            phone: None,
+        }),
+        admin: None,
+        tech: None,
+        privacy_protected,
+        source: WhoisSource::Rdap,
+        registrar_whois_server: None,
+        expiration_at: None,
+        days_until_expiry: None,
+        warnings: Vec::new(),
+        raw_output: String::new(),
+    }
+}
+
+#[test]
+fn test_registrant_name() {
+    let adapter = TyposquatAdapter::new();
+    assert_eq!(
+        adapter
+            .registrant_name(&whois("MarkMonitor", Some("Example Inc."), false))
+            .as_deref(),
+        Some("Example Inc.")
+    );
+    assert_eq!(
+        adapter.registrant_name(&whois("NameCheap", Some("Privacy service"), true)),
+        None
+    );
+    assert_eq!(
+        adapter.registrant_name(&whois("NameCheap", Some("  "), false)),
+        None
+    );
+}
+
+#[test]
+fn test_summarize() {
+    let adapter = TyposquatAdapter::new();
+    let nameservers = vec!["ns1.example.com".to_string(), "ns2.example.com".to_string()];
+    let original = whois("MarkMonitor", Some("Example Inc."), false);
+
+    let mut owned = variant("exmple.com", VariantKind::Omission);
+    adapter.apply_whois(
+        &mut owned,
+        &whois("MarkMonitor", Some("EXAMPLE INC."), false),
+    );
+    let mut parked = variant("example.net", VariantKind::Tld);
+    parked.nameservers = nameservers.clone();
+    adapter.apply_whois(&mut parked, &whois("MarkMonitor", None, true));
+    let mut phishing = variant("xn--exmple-4nf.com", VariantKind::Homograph);
+    phishing.has_mx = true;
+    adapter.apply_whois(&mut phishing, &whois("NameCheap", None, true));
+    let mut unregistered = variant("exampel.com", VariantKind::Transposition);
+    unregistered.registered = false;
+
+    let report = adapter.summarize(
+        "example.com",
+        &nameservers,
+        Some(&original),
+        vec![unregistered, owned, parked, phishing],
+    );
+
+    assert_eq!(report.generated, 4);
+    assert_eq!(report.registered, 3);
+    assert_eq!(report.registrant.as_deref(), Some("Example Inc."));
+    let order: Vec<&str> = report.variants.iter().map(|v| v.domain.as_str()).collect();
+    assert_eq!(
+        order,
+        [
+            "exmple.com",
+            "example.net",
+            "xn--exmple-4nf.com",
+            "exampel.com"
+        ]
+    );
+    // Same registrant, then same nameservers
+    assert!(report.variants[0].defensive);
+    assert!(report.variants[1].defensive);
+    assert!(!report.variants[2].defensive);
+
+    assert_eq!(report.registrars[0].name, "MarkMonitor");
+    assert_eq!(report.registrars[0].domains, ["exmple.com", "example.net"]);
+    assert_eq!(report.registrars[1].name, "NameCheap");
+    assert_eq!(report.registrants.len(), 1);
+    assert_eq!(report.registrants[0].name, "EXAMPLE INC.");
+
+    assert_eq!(
+        report.findings[0],
+        "3 of 4 lookalikes of example.com are registered, 1 apparently by someone else"
+    );
+    assert!(report.findings[1].contains("accepts mail"));
+    assert!(report.findings[2].contains("(xn--exmple-4nf.com) is a homograph"));
+}
+
+#[test]
+fn test_summarize_nothing_registered() {
+    let adapter = TyposquatAdapter::new();
+    let mut unregistered = variant("exmple.com", VariantKind::Omission);
+    unregistered.registered = false;
+    let report = adapter.summarize("example.com", &[], None, vec![unregistered]);
+    assert_eq!(report.registered, 0);
+    assert!(report.registrars.is_empty());
+    assert_eq!(
+        report.findings,
+        ["None of the 1 lookalikes of example.com are registered"]
+    );
+}
+
+#[tokio::test]
+async fn test_resolve_variants() {
+    let dig = ZoneDig::new(&[
+        ("exmple.com", "NS", "ns1.parking.example."),
+        ("exmple.com", "A", "192.0.2.10"),
+        ("exmple.com", "MX", "10 mx.parking.example."),
+        ("example.net", "NS", "ns1.example.com."),
+        ("example.net", "MX", "0 ."),
+    ]);
+    let queried = dig.queried.clone();
+    let adapter = TyposquatAdapter::new().with_backends(Backends::default().with_dns(dig));
+    let progress = Arc::new(Mutex::new(Vec::new()));
+    let seen = progress.clone();
+
+    let variants = adapter
+        .resolve_variants(
+            vec![
+                ("exmple.com".to_string(), VariantKind::Omission),
+                ("exampel.com".to_string(), VariantKind::Transposition),
+                ("example.net".to_string(), VariantKind::Tld),
+            ],
+            move |done, total, _| seen.lock().unwrap().push((done, total)),
+        )
+        .await;
+
+    let domains: Vec<&str> = variants.iter().map(|v| v.domain.as_str()).collect();
+    assert_eq!(domains, ["exmple.com", "exampel.com", "example.net"]);
+    assert!(variants[0].registered);
+    assert_eq!(variants[0].nameservers, ["ns1.parking.example"]);
+    assert_eq!(variants[0].addresses, ["192.0.2.10"]);
+    assert!(variants[0].has_mx);
+    assert!(!variants[1].registered);
+    assert!(variants[1].error.is_none());
+    assert!(variants[2].registered);
+    // A null MX takes no mail
+    assert!(!variants[2].has_mx);
+
+    // Unregistered names get no further queries
+    let queried = queried.lock().unwrap();
+    assert!(!queried
+        .iter()
+        .any(|q| q.starts_with("exampel.com ") && !q.ends_with(" NS")));
+    let progress = progress.lock().unwrap();
+    assert_eq!(progress.last(), Some(&(3, 3)));
+}
@@ -1,85 +1,1251 @@
+use crate::adapters::backend::{BackendFuture, Backends, WhoisBackend};
+use crate::adapters::dns::DnsAdapter;
+use crate::adapters::http::HttpAdapter;
 use crate::models::command_log::CommandLog;
-use crate::models::whois::WhoisInfo;
+use crate::models::whois::{
+    AvailabilityStatus, BulkWhoisResult, BulkWhoisSummary, Contact, EppStatus, IpWhoisInfo,
+    RegistrationCheck, WhoisInfo, WhoisSource,
+};
+use crate::state::command_logs;
+use crate::state::rate_limiter::{self, Target};
+use crate::state::rdap_bootstrap::RdapBootstrap;
+use crate::state::{self, AppState};
+use chrono::{DateTime, NaiveDate, NaiveDateTime, SecondsFormat, Utc};
 use regex::Regex;
-use std::process::Command;
-use std::time::Instant;
-use tauri::{AppHandle, Emitter};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::fmt;
+use std::net::IpAddr;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Manager};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpStream;
+use tokio::sync::Semaphore;
+use tokio::task::JoinSet;
+use tracing::{instrument, Instrument};
 
+const WHOIS_PORT: u16 = 43;
+
+// Backoff between rate-limited attempts doubles from this, up to the cap
+const RATE_LIMIT_BACKOFF_SECS: u64 = 1;
+const RATE_LIMIT_BACKOFF_MAX_SECS: u64 = 30;
+
+// How long to leave a server alone once it keeps rate limiting us
+const RATE_LIMIT_COOLDOWN_SECS: u64 = 60;
+
+// Pause between bulk lookups sent to the same registry
+const BULK_WHOIS_INTERVAL: Duration = Duration::from_secs(1);
+
+// What registries answer instead of data when queried too often (compared lowercase)
+const RATE_LIMIT_MARKERS: [&str; 9] = [
+    "rate limit",
+    "limit exceeded",
+    "limit reached",
+    "too many queries",
+    "too many requests",
+    "queries exceeded",
+    "query rate",
+    "quota exceeded",
+    "try again later",
+];
+
+// How registries start a "no such domain" answer (compared lowercase)
+const NOT_FOUND_MARKERS: [&str; 10] = [
+    "no match for",
+    "not found",
+    "no data found",
+    "no entries found",
+    "domain not found",
+    "no object found",
+    "status: free",
+    "status: available",
+    "the queried object does not exist",
+    "%% no entries found",
+];
+
+// Phrases privacy/proxy services and GDPR redaction put in contact fields
+const PRIVACY_MARKERS: [&str; 8] = [
+    "redacted",
+    "privacy",
+    "proxy",
+    "whoisguard",
+    "withheld",
+    "not disclosed",
+    "data protected",
+    "identity protect",
+];
+
+// Knows the WHOIS server of every TLD, for those missing from get_whois_server
+const IANA_WHOIS_SERVER: &str = "whois.iana.org";
+
+// IANA registry of RDAP servers per TLD (RFC 9224)
+const RDAP_BOOTSTRAP_URL: &str = "https://data.iana.org/rdap/dns.json";
+
+// ARIN redirects queries for other registries' address space to the right RIR
+const RDAP_IP_URL: &str = "https://rdap.arin.net/registry/ip";
+
+// Failure of a port-43 query; rate limiting is distinct so callers can back off
+#[derive(Debug, Clone, PartialEq)]
+pub enum WhoisError {
+    RateLimited {
+        server: String,
+        retry_after_secs: u64,
+    },
+    Failed(String),
+}
+
+impl fmt::Display for WhoisError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            WhoisError::RateLimited {
+                server,
+                retry_after_secs,
+            } => write!(
+                f,
+                "Rate limited by {}; try again in {} seconds",
+                server, retry_after_secs
+            ),
+            WhoisError::Failed(message) => write!(f, "{}", message),
+        }
+    }
+}
+
+impl From<WhoisError> for String {
+    fn from(error: WhoisError) -> String {
+        error.to_string()
+    }
+}
+
+#[derive(Clone)]
 pub struct WhoisAdapter {
     app_handle: Option<AppHandle>,
+    backends: Backends,
+}
+
+// WHOIS over TCP port 43
+pub struct Port43Whois;
+
+impl WhoisBackend for Port43Whois {
+    // RFC 3912: send the query terminated by CRLF, read until the server closes
+    fn query<'a>(
+        &'a self,
+        server: &'a str,
+        port: u16,
+        query: &'a str,
+    ) -> BackendFuture<'a, String> {
+        Box::pin(async move {
+            let mut stream = TcpStream::connect((server, port))
+                .await
+                .map_err(|e| format!("Failed to connect to {}:{}: {}", server, port, e))?;
+
+            stream
+                .write_all(format!("{}\r\n", query).as_bytes())
+                .await
+                .map_err(|e| format!("Failed to send query to {}: {}", server, e))?;
+
+            let mut response = Vec::new();
+            stream
+                .read_to_end(&mut response)
+                .await
+                .map_err(|e| format!("Failed to read response from {}: {}", server, e))?;
+
+            if response.is_empty() {
+                return Err(format!("{} returned an empty response", server));
+            }
+
+            // Most registries answer in UTF-8; anything else is shown with replacement characters
+            Ok(String::from_utf8_lossy(&response).to_string())
+        })
+    }
 }
 
 impl WhoisAdapter {
     pub fn new() -> Self {
-        WhoisAdapter { app_handle: None }
+        WhoisAdapter {
+            app_handle: None,
+            backends: Backends::default(),
+        }
     }
 
     pub fn with_app_handle(app_handle: AppHandle) -> Self {
+        Self::from_app_handle(Some(app_handle))
+    }
+
+    // For other adapters looking up registrations on behalf of their own handle
+    pub fn from_app_handle(app_handle: Option<AppHandle>) -> Self {
         WhoisAdapter {
-            app_handle: Some(app_handle),
+            backends: state::backends_for(app_handle.as_ref()),
+            app_handle,
         }
     }
 
+    // Query WHOIS servers and RDAP through these instead of the app's backends
+    pub fn with_backends(mut self, backends: Backends) -> Self {
+        self.backends = backends;
+        self
+    }
+
     fn emit_log(&self, log: CommandLog) {
-        if let Some(handle) = &self.app_handle {
-            let _ = handle.emit("command-log", log);
-        }
+        command_logs::record(self.app_handle.as_ref(), log);
     }
 
+    // RDAP first: structured JSON and coverage for every TLD in the IANA registry.
+    // Port-43 WHOIS is the fallback for TLDs without RDAP or when RDAP fails.
+    // A lookup of a domain already being looked up waits for that one's result.
+    #[instrument(skip_all, fields(domain = %domain))]
     pub async fn lookup(&self, domain: &str) -> Result<WhoisInfo, String> {
+        let key = domain.trim_end_matches('.').to_lowercase();
+        // Callers sharing another's lookup log nothing of their own
+        state::session_domains::record(self.app_handle.as_ref(), &key, false);
+        state::in_flight::coalesce(self.app_handle.as_ref(), "whois", &key, || {
+            self.lookup_uncoalesced(domain)
+        })
+        .await
+    }
+
+    async fn lookup_uncoalesced(&self, domain: &str) -> Result<WhoisInfo, String> {
+        let mut info = match self.lookup_rdap(domain).await {
+            Ok(info) => info,
+            Err(rdap_error) => self
+                .lookup_port43(domain)
+                .await
+                .map_err(|e| format!("{} (RDAP: {})", e, rdap_error))?,
+        };
+
+        self.normalize_dates(&mut info);
+
+        let settings = state::settings_for(self.app_handle.as_ref());
+        let warnings =
+            self.expiry_warnings(&mut info, Utc::now(), settings.domain_expiry_warning_days);
+        info.warnings.extend(warnings);
+
+        Ok(info)
+    }
+
+    // Fill in expiration_at/days_until_expiry and warn about domains that expire
+    // within the window, have expired, or are being deleted
+    pub fn expiry_warnings(
+        &self,
+        info: &mut WhoisInfo,
+        now: DateTime<Utc>,
+        window_days: u32,
+    ) -> Vec<String> {
+        let mut warnings = Vec::new();
+
+        info.expiration_at = info
+            .expiration_date
+            .as_deref()
+            .and_then(|date| self.parse_whois_date(date));
+        info.days_until_expiry = info.expiration_at.map(|at| (at - now).num_days());
+
+        if let (Some(at), Some(days)) = (info.expiration_at, info.days_until_expiry) {
+            if at <= now {
+                warnings.push(format!(
+                    "Domain registration expired on {}",
+                    at.format("%Y-%m-%d")
+                ));
+            } else if days < window_days as i64 {
+                warnings.push(format!(
+                    "Domain expires in {} day{} on {}; renew it before it lapses",
+                    days,
+                    if days == 1 { "" } else { "s" },
+                    at.format("%Y-%m-%d")
+                ));
+            }
+        }
+
+        if info.has_status("redemptionPeriod") {
+            warnings.push(
+                "Domain is in redemptionPeriod: it has expired and can only be restored by the registrar for a fee"
+                    .to_string(),
+            );
+        }
+        if info.has_status("pendingDelete") {
+            warnings.push(
+                "Domain is pendingDelete: it will be released for re-registration within days"
+                    .to_string(),
+            );
+        }
+
+        warnings
+    }
+
+    // Look up many domains without hammering any one registry: domains sharing an
+    // RDAP service (or WHOIS server) are queried in turn with BULK_WHOIS_INTERVAL
+    // between them, while different registries run in parallel. on_result is
+    // called as each domain completes.
+    pub async fn lookup_bulk<F>(&self, domains: &[String], on_result: F) -> BulkWhoisSummary
+    where
+        F: Fn(&BulkWhoisResult) + Send + Sync + 'static,
+    {
         let start = Instant::now();
-        if !self.is_whois_available() {
-            return Err("whois command not found. Please install whois.".to_string());
+        let settings = state::settings_for(self.app_handle.as_ref());
+
+        let mut registries: HashMap<String, String> = HashMap::new();
+        let mut groups: Vec<(String, Vec<(usize, String)>)> = Vec::new();
+        for (index, domain) in domains.iter().enumerate() {
+            let tld = domain
+                .trim_end_matches('.')
+                .rsplit('.')
+                .next()
+                .unwrap_or(domain)
+                .to_lowercase();
+            let registry = match registries.get(&tld) {
+                Some(registry) => registry.clone(),
+                None => {
+                    let registry = self.registry_key(&tld, domain).await;
+                    registries.insert(tld, registry.clone());
+                    registry
+                }
+            };
+            match groups.iter_mut().find(|(key, _)| *key == registry) {
+                Some((_, members)) => members.push((index, domain.clone())),
+                None => groups.push((registry, vec![(index, domain.clone())])),
+            }
+        }
+
+        let total = domains.len();
+        let completed = Arc::new(AtomicUsize::new(0));
+        let on_result = Arc::new(on_result);
+        let permits = Arc::new(Semaphore::new(settings.max_concurrency.max(1)));
+        let mut tasks = JoinSet::new();
+
+        for (_, members) in groups {
+            let adapter = self.clone();
+            let completed = completed.clone();
+            let on_result = on_result.clone();
+            let permits = permits.clone();
+            tasks.spawn(async move {
+                let _permit = permits.acquire_owned().await;
+                let mut results = Vec::new();
+                for (position, (index, domain)) in members.into_iter().enumerate() {
+                    if position > 0 {
+                        tokio::time::sleep(BULK_WHOIS_INTERVAL).await;
+                    }
+                    let (info, error) = match adapter.lookup(&domain).await {
+                        Ok(info) => (Some(info), None),
+                        Err(e) => (None, Some(e)),
+                    };
+                    let result = BulkWhoisResult {
+                        domain,
+                        info,
+                        error,
+                        completed: completed.fetch_add(1, Ordering::SeqCst) + 1,
+                        total,
+                    };
+                    on_result(&result);
+                    results.push((index, result));
+                }
+                results
+            });
+        }
+
+        let mut results = Vec::new();
+        while let Some(joined) = tasks.join_next().await {
+            if let Ok(group) = joined {
+                results.extend(group);
+            }
         }
+        results.sort_by_key(|(index, _)| *index);
 
-        // Determine the appropriate WHOIS server based on TLD
-        let whois_server = self.get_whois_server(domain);
+        self.summarize_bulk(
+            results.into_iter().map(|(_, result)| result).collect(),
+            Utc::now(),
+            settings.domain_expiry_warning_days,
+            start.elapsed().as_secs_f64(),
+        )
+    }
+
+    // Host of the RDAP service for the TLD, else its WHOIS server, else the TLD
+    async fn registry_key(&self, tld: &str, domain: &str) -> String {
+        match self.rdap_base_url(tld, domain).await {
+            Ok(Some(base_url)) => base_url
+                .split("://")
+                .last()
+                .unwrap_or(&base_url)
+                .split('/')
+                .next()
+                .unwrap_or(&base_url)
+                .to_lowercase(),
+            _ => self
+                .get_whois_server(domain)
+                .unwrap_or_else(|| tld.to_string()),
+        }
+    }
 
-        let mut args = vec![];
-        let mut cmd = Command::new("whois");
+    pub fn summarize_bulk(
+        &self,
+        results: Vec<BulkWhoisResult>,
+        now: DateTime<Utc>,
+        window_days: u32,
+        total_time: f64,
+    ) -> BulkWhoisSummary {
+        let mut expired = Vec::new();
+        let mut expiring_soon = Vec::new();
 
-        if let Some(server) = whois_server {
-            args.push("-h".to_string());
-            args.push(server.clone());
-            cmd.arg("-h").arg(server);
+        for result in &results {
+            let Some(info) = &result.info else {
+                continue;
+            };
+            match (info.expiration_at, info.days_until_expiry) {
+                (Some(at), _) if at <= now => expired.push(result.domain.clone()),
+                (_, Some(days)) if days < window_days as i64 => {
+                    expiring_soon.push(result.domain.clone())
+                }
+                _ => {}
+            }
         }
 
-        args.push(domain.to_string());
-        cmd.arg(domain);
+        let succeeded = results.iter().filter(|r| r.info.is_some()).count();
+        BulkWhoisSummary {
+            total: results.len(),
+            succeeded,
+            failed: results.len() - succeeded,
+            expired,
+            expiring_soon,
+            results,
+            total_time,
+        }
+    }
 
-        let output = cmd
-            .output()
-            .map_err(|e| format!("Failed to execute whois: {}", e))?;
+    // Keep each date as the registry wrote it in *_raw and rewrite creation,
+    // expiration and updated as ISO 8601; unknown formats are left untouched
+    pub fn normalize_dates(&self, info: &mut WhoisInfo) {
+        for (date, raw) in [
+            (&mut info.creation_date, &mut info.creation_date_raw),
+            (&mut info.expiration_date, &mut info.expiration_date_raw),
+            (&mut info.updated_date, &mut info.updated_date_raw),
+        ] {
+            let Some(value) = date.take() else {
+                continue;
+            };
+            *date = Some(
+                self.parse_whois_date(&value)
+                    .map(|d| d.to_rfc3339_opts(SecondsFormat::Secs, true))
+                    .unwrap_or_else(|| value.clone()),
+            );
+            *raw = Some(value);
+        }
+    }
 
-        let query_time = start.elapsed().as_secs_f64();
-        let exit_code = output.status.code().unwrap_or(-1);
+    // Registries mostly use RFC 3339; ccTLDs use their own layouts (14-Aug-1995,
+    // 1995/08/14, 14.08.1995, 19950814). Dates without a zone, or with a named
+    // one like "(JST)", are taken as UTC.
+    pub fn parse_whois_date(&self, value: &str) -> Option<DateTime<Utc>> {
+        let value = value.split(" (").next().unwrap_or(value).trim();
+        let value = value
+            .strip_suffix(" UTC")
+            .or_else(|| value.strip_suffix(" GMT"))
+            .unwrap_or(value)
+            .trim_end_matches('.');
 
-        let stdout = String::from_utf8_lossy(&output.stdout).to_string();
-        let stderr = String::from_utf8_lossy(&output.stderr).to_string();
+        if let Ok(date) = DateTime::parse_from_rfc3339(value) {
+            return Some(date.with_timezone(&Utc));
+        }
+        if let Some(date) = ["%Y-%m-%dT%H:%M:%S%.f%#z", "%Y-%m-%d %H:%M:%S%.f%#z"]
+            .iter()
+            .find_map(|format| DateTime::parse_from_str(value, format).ok())
+        {
+            return Some(date.with_timezone(&Utc));
+        }
+
+        [
+            "%Y-%m-%dT%H:%M:%S%.f",
+            "%Y-%m-%d %H:%M:%S%.f",
+            "%Y/%m/%d %H:%M:%S",
+            "%Y.%m.%d %H:%M:%S",
+            "%d.%m.%Y %H:%M:%S",
+            "%d-%b-%Y %H:%M:%S",
+        ]
+        .iter()
+        .find_map(|format| NaiveDateTime::parse_from_str(value, format).ok())
+        .or_else(|| {
+            [
+                "%Y-%m-%d",
+                "%Y/%m/%d",
+                "%Y.%m.%d",
+                "%Y. %m. %d",
+                "%d.%m.%Y",
+                "%d-%b-%Y",
+                "%d %b %Y",
+                "%Y%m%d",
+            ]
+            .iter()
+            .find_map(|format| NaiveDate::parse_from_str(value, format).ok())
+            .and_then(|d| d.and_hms_opt(0, 0, 0))
+        })
+        .map(|date| date.and_utc())
+    }
+
+    #[instrument(skip_all, fields(domain = %domain))]
+    pub async fn lookup_rdap(&self, domain: &str) -> Result<WhoisInfo, String> {
+        let tld = domain
+            .trim_end_matches('.')
+            .rsplit('.')
+            .next()
+            .unwrap_or(domain)
+            .to_lowercase();
+        let base_url = self
+            .rdap_base_url(&tld, domain)
+            .await?
+            .ok_or_else(|| format!("No RDAP service for .{}", tld))?;
+
+        let url = format!(
+            "{}/domain/{}",
+            base_url.trim_end_matches('/'),
+            domain.trim_end_matches('.')
+        );
+        let body = self.fetch_rdap_json(&url, domain).await?;
+
+        self.parse_rdap_response(&body, domain)
+    }
+
+    // Ask the registry whether a domain exists: RDAP first (404 means not found),
+    // then port-43 WHOIS "no match" phrases. registered is None when neither
+    // gave a usable answer.
+    pub async fn check_registration(&self, domain: &str) -> RegistrationCheck {
+        let mut check = RegistrationCheck::default();
+        let tld = domain
+            .trim_end_matches('.')
+            .rsplit('.')
+            .next()
+            .unwrap_or(domain)
+            .to_lowercase();
+
+        match self.rdap_base_url(&tld, domain).await {
+            Ok(Some(base_url)) => {
+                let url = format!("{}/domain/{}", base_url.trim_end_matches('/'), domain);
+                match self.fetch_rdap(&url, domain).await {
+                    Ok((404, _)) => {
+                        check.registered = Some(false);
+                        check
+                            .evidence
+                            .push(format!("RDAP: {} returned 404 Not Found", url));
+                        return check;
+                    }
+                    Ok((200..=299, body)) => {
+                        if let Ok(info) = self.parse_rdap_response(&body, domain) {
+                            check.registered = Some(true);
+                            check
+                                .evidence
+                                .push(format!("RDAP: {} returned a domain object", url));
+                            check.status = info.status.into_iter().map(|s| s.code).collect();
+                            check.raw_output = info.raw_output;
+                            return check;
+                        }
+                    }
+                    Ok((status, _)) => check
+                        .evidence
+                        .push(format!("RDAP: {} returned HTTP {}", url, status)),
+                    Err(e) => check.evidence.push(format!("RDAP: {}", e)),
+                }
+            }
+            Ok(None) => check
+                .evidence
+                .push(format!("RDAP: no service for .{}", tld)),
+            Err(e) => check.evidence.push(format!("RDAP: {}", e)),
+        }
+
+        let output = match self.registry_whois_server(domain).await {
+            Ok(server) => {
+                let query = self.format_query(&server, domain);
+                self.query_with_retries(&server, WHOIS_PORT, &query, domain)
+                    .await
+                    .map(|output| (server, output))
+                    .map_err(String::from)
+            }
+            Err(e) => Err(e),
+        };
 
-        // Emit command log
-        let log_output = if !stdout.is_empty() {
-            stdout.clone()
+        match output {
+            Ok((server, output)) => {
+                if let Some(phrase) = self.not_found_phrase(&output) {
+                    check.registered = Some(false);
+                    check
+                        .evidence
+                        .push(format!("WHOIS: {} answered \"{}\"", server, phrase));
+                } else if let Ok(info) = self.parse_whois_output(&output, domain) {
+                    if info.registrar.is_some()
+                        || info.creation_date.is_some()
+                        || !info.status.is_empty()
+                    {
+                        check.registered = Some(true);
+                        check
+                            .evidence
+                            .push(format!("WHOIS: {} returned a registration record", server));
+                    }
+                    check.status = info.status.into_iter().map(|s| s.code).collect();
+                }
+                check.raw_output = output;
+            }
+            Err(e) => check.evidence.push(format!("WHOIS: {}", e)),
+        }
+
+        check
+    }
+
+    // Line of a WHOIS answer saying the domain doesn't exist, if any
+    pub fn not_found_phrase(&self, output: &str) -> Option<String> {
+        output
+            .lines()
+            .map(|line| line.trim())
+            .find(|line| {
+                let line = line.to_lowercase();
+                NOT_FOUND_MARKERS
+                    .iter()
+                    .any(|marker| line.starts_with(marker))
+            })
+            .map(|line| line.to_string())
+    }
+
+    // Registry statuses win (a domain being deleted still exists), then the
+    // registry's answer, then DNS: a delegated domain is registered, but NXDOMAIN
+    // alone doesn't prove availability (registered domains can lack nameservers)
+    pub fn classify_availability(
+        &self,
+        check: &RegistrationCheck,
+        nxdomain: Option<bool>,
+    ) -> AvailabilityStatus {
+        let has_status = |name: &str| check.status.iter().any(|s| s.eq_ignore_ascii_case(name));
+
+        if has_status("pendingDelete") {
+            AvailabilityStatus::PendingDelete
+        } else if has_status("redemptionPeriod") {
+            AvailabilityStatus::Redemption
         } else {
-            stderr.clone()
+            match (check.registered, nxdomain) {
+                (Some(true), _) => AvailabilityStatus::Registered,
+                // Registry says no, but the name resolves: reserved or a sub-registry
+                (Some(false), Some(false)) => AvailabilityStatus::Unknown,
+                (Some(false), _) => AvailabilityStatus::Available,
+                (None, Some(false)) => AvailabilityStatus::Registered,
+                (None, _) => AvailabilityStatus::Unknown,
+            }
+        }
+    }
+
+    // Registration data for an IP network from its RIR
+    pub async fn lookup_ip_rdap(&self, ip: IpAddr) -> Result<IpWhoisInfo, String> {
+        let url = format!("{}/{}", RDAP_IP_URL, ip);
+        let body = self.fetch_rdap_json(&url, &ip.to_string()).await?;
+
+        self.parse_rdap_ip_response(&body, ip)
+    }
+
+    // Map an RDAP IP network object (RFC 9083 section 5.4) onto IpWhoisInfo
+    pub fn parse_rdap_ip_response(&self, json: &str, ip: IpAddr) -> Result<IpWhoisInfo, String> {
+        let value: Value = serde_json::from_str(json)
+            .map_err(|e| format!("Failed to parse RDAP response: {}", e))?;
+
+        if value["objectClassName"].as_str() != Some("ip network") {
+            return Err("RDAP response is not an IP network object".to_string());
+        }
+
+        let text = |field: &str| value[field].as_str().map(|v| v.to_string());
+        let entities = self.rdap_entities(&value);
+        let with_role = |role: &str| {
+            entities.iter().find(|entity| {
+                entity["roles"]
+                    .as_array()
+                    .is_some_and(|roles| roles.iter().any(|r| r == role))
+            })
         };
 
-        self.emit_log(CommandLog::new(
-            "whois".to_string(),
-            args,
-            log_output,
-            exit_code,
-            query_time * 1000.0, // Convert to milliseconds
-            Some(domain.to_string()),
-        ));
+        let organization = with_role("registrant").and_then(|entity| {
+            self.vcard_field(entity, "org")
+                .or_else(|| self.vcard_field(entity, "fn"))
+        });
+        let abuse_email = with_role("abuse").and_then(|entity| self.vcard_field(entity, "email"));
+        let network_range = match (text("startAddress"), text("endAddress")) {
+            (Some(start), Some(end)) => Some(format!("{} - {}", start, end)),
+            _ => None,
+        };
+        let rir = text("port43").and_then(|server| self.rir_for_whois_server(&server));
+
+        Ok(IpWhoisInfo {
+            ip: ip.to_string(),
+            network_name: text("name"),
+            network_handle: text("handle"),
+            network_range,
+            organization,
+            country: text("country"),
+            abuse_email,
+            rir,
+            asn: None,
+            as_name: None,
+            announced_prefix: None,
+            warnings: Vec::new(),
+            raw_output: serde_json::to_string_pretty(&value).unwrap_or_else(|_| json.to_string()),
+        })
+    }
 
-        if !output.status.success() {
-            return Err(format!("whois command failed: {}", stderr));
+    fn rir_for_whois_server(&self, server: &str) -> Option<String> {
+        let rir = match server.to_lowercase().as_str() {
+            "whois.arin.net" => "arin",
+            "whois.ripe.net" => "ripencc",
+            "whois.apnic.net" => "apnic",
+            "whois.lacnic.net" => "lacnic",
+            "whois.afrinic.net" => "afrinic",
+            _ => return None,
+        };
+        Some(rir.to_string())
+    }
+
+    // Team Cymru IP-to-ASN name: reversed octets (IPv4) or nibbles (IPv6)
+    // e.g. 1.1.1.1 -> 1.1.1.1.origin.asn.cymru.com
+    pub fn cymru_origin_name(&self, ip: IpAddr) -> String {
+        match ip {
+            IpAddr::V4(v4) => {
+                let octets = v4.octets();
+                format!(
+                    "{}.{}.{}.{}.origin.asn.cymru.com",
+                    octets[3], octets[2], octets[1], octets[0]
+                )
+            }
+            IpAddr::V6(v6) => {
+                let nibbles: Vec<String> = v6
+                    .octets()
+                    .iter()
+                    .rev()
+                    .flat_map(|b| [b & 0x0f, b >> 4])
+                    .map(|n| format!("{:x}", n))
+                    .collect();
+                format!("{}.origin6.asn.cymru.com", nibbles.join("."))
+            }
         }
+    }
 
-        let whois_info = self.parse_whois_output(&stdout, domain)?;
+    fn dns(&self) -> DnsAdapter {
+        DnsAdapter::from_app_handle(self.app_handle.clone()).with_backends(self.backends.clone())
+    }
 
-        Ok(whois_info)
+    async fn cymru_txt(&self, name: &str) -> Option<Vec<String>> {
+        let dns = self.dns();
+        let response = dns.query(name, "TXT").await.ok()?;
+        Some(
+            response
+                .records
+                .iter()
+                .filter(|r| r.record_type == "TXT")
+                .map(|r| dns.txt_value(&r.value))
+                .collect(),
+        )
+    }
+
+    // Origin AS, announced prefix and registry of an address from Team Cymru's DNS
+    // service; None when the address isn't announced or the lookup failed
+    pub async fn lookup_origin(&self, ip: IpAddr) -> Option<(u32, String, String)> {
+        let records = self.cymru_txt(&self.cymru_origin_name(ip)).await?;
+        self.parse_cymru_origin(&records)
+    }
+
+    // lookup_origin for each address, concurrently (bounded by max_concurrency),
+    // in the order given
+    pub async fn lookup_origins(&self, addresses: &[IpAddr]) -> Vec<Option<(u32, String, String)>> {
+        let settings = state::settings_for(self.app_handle.as_ref());
+        let permits = Arc::new(Semaphore::new(settings.max_concurrency.max(1)));
+        let mut tasks = JoinSet::new();
+
+        for (index, address) in addresses.iter().copied().enumerate() {
+            let adapter = self.clone();
+            let permits = permits.clone();
+            tasks.spawn(async move {
+                let _permit = permits.acquire_owned().await;
+                (index, adapter.lookup_origin(address).await)
+            });
+        }
+
+        let mut origins = vec![None; addresses.len()];
+        while let Some(joined) = tasks.join_next().await {
+            if let Ok((index, origin)) = joined {
+                origins[index] = origin;
+            }
+        }
+        origins
+    }
+
+    pub async fn lookup_as_name(&self, asn: u32) -> Option<String> {
+        let records = self.cymru_txt(&format!("AS{}.asn.cymru.com", asn)).await?;
+        records.first().and_then(|r| self.parse_cymru_as_name(r))
+    }
+
+    // Origin TXT records: "13335 | 1.1.1.0/24 | AU | apnic | 2011-08-11"
+    // With several announcements, the most specific prefix wins
+    pub fn parse_cymru_origin(&self, records: &[String]) -> Option<(u32, String, String)> {
+        records
+            .iter()
+            .filter_map(|record| {
+                let fields: Vec<&str> = record.split('|').map(|f| f.trim()).collect();
+                let asn = fields.first()?.split_whitespace().next()?.parse().ok()?;
+                let prefix = fields.get(1)?.to_string();
+                let length: u8 = prefix.split('/').nth(1)?.parse().ok()?;
+                let registry = fields.get(3).map(|r| r.to_lowercase()).unwrap_or_default();
+                Some((length, asn, prefix, registry))
+            })
+            .max_by_key(|(length, ..)| *length)
+            .map(|(_, asn, prefix, registry)| (asn, prefix, registry))
+    }
+
+    // AS TXT record: "13335 | US | arin | 2010-07-14 | CLOUDFLARENET - Cloudflare, Inc., US"
+    pub fn parse_cymru_as_name(&self, record: &str) -> Option<String> {
+        let name = record.split('|').nth(4)?.trim();
+        (!name.is_empty()).then(|| name.to_string())
+    }
+
+    // Bootstrap lookup, cached in AppState when running inside the app
+    async fn rdap_base_url(&self, tld: &str, domain: &str) -> Result<Option<String>, String> {
+        let state = self
+            .app_handle
+            .as_ref()
+            .and_then(|h| h.try_state::<AppState>());
+
+        if let Some(base_url) = state.as_ref().and_then(|s| s.rdap_bootstrap.base_url(tld)) {
+            return Ok(base_url);
+        }
+
+        let body = self.fetch_rdap_json(RDAP_BOOTSTRAP_URL, domain).await?;
+        let base_urls = RdapBootstrap::parse(&body)?;
+        let base_url = base_urls.get(tld).cloned();
+
+        if let Some(state) = state {
+            state.rdap_bootstrap.store(base_urls);
+        }
+
+        Ok(base_url)
+    }
+
+    async fn fetch_rdap_json(&self, url: &str, domain: &str) -> Result<String, String> {
+        match self.fetch_rdap(url, domain).await? {
+            (200..=299, body) => Ok(body),
+            (status, _) => Err(format!("RDAP request failed: HTTP {}", status)),
+        }
+    }
+
+    // HTTP status and body; a 404 is an answer ("not found"), not a failure.
+    // Redirects are followed for rdap.org-style redirectors and referrals.
+    async fn fetch_rdap(&self, url: &str, domain: &str) -> Result<(u16, String), String> {
+        let settings = state::settings_for(self.app_handle.as_ref());
+        HttpAdapter::from_app_handle(self.app_handle.clone())
+            .with_backends(self.backends.clone())
+            .get_text(
+                url,
+                "application/rdap+json",
+                settings.whois_timeout_secs,
+                settings.whois_retries,
+                domain,
+            )
+            .await
+            .map_err(|e| format!("RDAP request failed: {}", e))
+    }
+
+    // Map an RDAP domain object (RFC 9083) onto WhoisInfo
+    pub fn parse_rdap_response(&self, json: &str, domain: &str) -> Result<WhoisInfo, String> {
+        let value: Value = serde_json::from_str(json)
+            .map_err(|e| format!("Failed to parse RDAP response: {}", e))?;
+
+        if value["objectClassName"].as_str() != Some("domain") {
+            return Err("RDAP response is not a domain object".to_string());
+        }
+
+        let registrar = value["entities"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .find(|entity| {
+                entity["roles"]
+                    .as_array()
+                    .is_some_and(|roles| roles.iter().any(|r| r == "registrar"))
+            })
+            .and_then(|entity| {
+                self.vcard_field(entity, "fn")
+                    .or_else(|| entity["handle"].as_str().map(|h| h.to_string()))
+            });
+
+        let event = |action: &str| {
+            value["events"]
+                .as_array()
+                .into_iter()
+                .flatten()
+                .find(|e| e["eventAction"] == action)
+                .and_then(|e| e["eventDate"].as_str())
+                .map(|d| d.to_string())
+        };
+
+        let nameservers = value["nameservers"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter_map(|ns| ns["ldhName"].as_str())
+            .map(|ns| ns.trim_end_matches('.').to_lowercase())
+            .collect();
+
+        let status = value["status"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter_map(|s| s.as_str())
+            .map(|s| EppStatus::from_code(&self.rdap_status_to_epp(s)))
+            .collect();
+
+        let entities = self.rdap_entities(&value);
+        let contact = |role: &str| {
+            entities
+                .iter()
+                .find(|entity| {
+                    entity["roles"]
+                        .as_array()
+                        .is_some_and(|roles| roles.iter().any(|r| r == role))
+                })
+                .and_then(|entity| {
+                    self.non_empty_contact(Contact {
+                        name: self.vcard_field(entity, "fn"),
+                        organization: self.vcard_field(entity, "org"),
+                        email: self.vcard_field(entity, "email"),
+                        phone: self
+                            .vcard_field(entity, "tel")
+                            .map(|tel| tel.trim_start_matches("tel:").to_string()),
+                    })
+                })
+        };
+        let registrant = contact("registrant");
+        let admin = contact("administrative");
+        let tech = contact("technical");
+
+        let dnssec = value["secureDNS"]["delegationSigned"]
+            .as_bool()
+            .map(|signed| {
+                if signed {
+                    "signedDelegation".to_string()
+                } else {
+                    "unsigned".to_string()
+                }
+            });
+
+        let raw_output = serde_json::to_string_pretty(&value).unwrap_or_else(|_| json.to_string());
+        let privacy_protected =
+            self.is_privacy_protected(&[&registrant, &admin, &tech], &raw_output);
+
+        Ok(WhoisInfo {
+            domain: domain.to_string(),
+            registrar,
+            creation_date: event("registration"),
+            expiration_date: event("expiration"),
+            updated_date: event("last changed"),
+            creation_date_raw: None,
+            expiration_date_raw: None,
+            updated_date_raw: None,
+            nameservers,
+            status,
+            dnssec,
+            registrant,
+            admin,
+            tech,
+            privacy_protected,
+            source: WhoisSource::Rdap,
+            registrar_whois_server: None,
+            expiration_at: None,
+            days_until_expiry: None,
+            warnings: Vec::new(),
+            raw_output,
+        })
+    }
+
+    // Every entity in the response, including those nested under other entities
+    // (registrars often carry their abuse contact, registries nest the registrant)
+    fn rdap_entities<'a>(&self, value: &'a Value) -> Vec<&'a Value> {
+        let mut entities = Vec::new();
+        for entity in value["entities"].as_array().into_iter().flatten() {
+            entities.push(entity);
+            entities.extend(self.rdap_entities(entity));
+        }
+        entities
+    }
+
+    // jCard property value: ["vcard", [["fn", {}, "text", "Example Registrar"], ...]]
+    fn vcard_field(&self, entity: &Value, property: &str) -> Option<String> {
+        entity["vcardArray"][1]
+            .as_array()?
+            .iter()
+            .find(|p| p[0] == property)
+            .and_then(|p| p[3].as_str())
+            .map(|v| v.to_string())
+    }
+
+    // RDAP spells EPP statuses out ("client transfer prohibited"); convert back to
+    // the EPP form port-43 WHOIS uses (RFC 8056), with "active" meaning "ok"
+    fn rdap_status_to_epp(&self, status: &str) -> String {
+        if status == "active" {
+            return "ok".to_string();
+        }
+
+        status
+            .split_whitespace()
+            .enumerate()
+            .map(|(index, word)| {
+                if index == 0 {
+                    word.to_string()
+                } else {
+                    let mut chars = word.chars();
+                    chars
+                        .next()
+                        .map(|first| first.to_uppercase().collect::<String>() + chars.as_str())
+                        .unwrap_or_default()
+                }
+            })
+            .collect()
+    }
+
+    // Determine the appropriate WHOIS server based on TLD, asking IANA for the rest
+    async fn registry_whois_server(&self, domain: &str) -> Result<String, String> {
+        if let Some(server) = self.get_whois_server(domain) {
+            return Ok(server);
+        }
+
+        let tld = domain
+            .trim_end_matches('.')
+            .rsplit('.')
+            .next()
+            .unwrap_or(domain);
+        let response = self
+            .query_with_retries(IANA_WHOIS_SERVER, WHOIS_PORT, tld, domain)
+            .await?;
+        self.parse_iana_referral(&response)
+            .ok_or_else(|| format!("No WHOIS server known for .{}", tld))
+    }
+
+    async fn lookup_port43(&self, domain: &str) -> Result<WhoisInfo, String> {
+        let server = self.registry_whois_server(domain).await?;
+
+        let query = self.format_query(&server, domain);
+        let output = self
+            .query_with_retries(&server, WHOIS_PORT, &query, domain)
+            .await?;
+        let registry_info = self.parse_whois_output(&output, domain)?;
+
+        // Thin registries (.com/.net) only refer to the registrar's server, which
+        // holds the contacts and its own view of dates and statuses
+        let referral = self
+            .parse_registrar_referral(&output)
+            .filter(|referral| !referral.eq_ignore_ascii_case(&server));
+        let Some(referral) = referral else {
+            return Ok(registry_info);
+        };
+
+        // The registry answer is still useful if the registrar's server is down
+        match self
+            .query_with_retries(&referral, WHOIS_PORT, domain, domain)
+            .await
+            .map_err(String::from)
+            .and_then(|response| self.parse_whois_output(&response, domain))
+        {
+            Ok(registrar_info) => {
+                Ok(self.merge_registrar_info(registry_info, registrar_info, &referral))
+            }
+            Err(_) => Ok(WhoisInfo {
+                registrar_whois_server: Some(referral),
+                ..registry_info
+            }),
+        }
+    }
+
+    // "Registrar WHOIS Server:" from a registry answer, without any URL scheme
+    pub fn parse_registrar_referral(&self, output: &str) -> Option<String> {
+        output.lines().find_map(|line| {
+            let (key, value) = line.split_once(':')?;
+            if !key.trim().eq_ignore_ascii_case("Registrar WHOIS Server") {
+                return None;
+            }
+            let server = value
+                .trim()
+                .trim_start_matches("https://")
+                .trim_start_matches("http://")
+                .trim_end_matches('/')
+                .to_lowercase();
+            (!server.is_empty()).then_some(server)
+        })
+    }
+
+    // The registry stays authoritative for the registrar, expiry, nameservers and
+    // DNSSEC; the registrar fills gaps and supplies its last-updated date and contacts
+    pub fn merge_registrar_info(
+        &self,
+        registry: WhoisInfo,
+        registrar: WhoisInfo,
+        registrar_server: &str,
+    ) -> WhoisInfo {
+        let mut status = registry.status;
+        for s in registrar.status {
+            if !status
+                .iter()
+                .any(|existing| existing.code.eq_ignore_ascii_case(&s.code))
+            {
+                status.push(s);
+            }
+        }
+
+        WhoisInfo {
+            domain: registry.domain,
+            registrar: registry.registrar.or(registrar.registrar),
+            creation_date: registry.creation_date.or(registrar.creation_date),
+            expiration_date: registry.expiration_date.or(registrar.expiration_date),
+            updated_date: registrar.updated_date.or(registry.updated_date),
+            creation_date_raw: None,
+            expiration_date_raw: None,
+            updated_date_raw: None,
+            nameservers: if registry.nameservers.is_empty() {
+                registrar.nameservers
+            } else {
+                registry.nameservers
+            },
+            status,
+            dnssec: registry.dnssec.or(registrar.dnssec),
+            registrant: registrar.registrant.or(registry.registrant),
+            admin: registrar.admin.or(registry.admin),
+            tech: registrar.tech.or(registry.tech),
+            privacy_protected: registry.privacy_protected || registrar.privacy_protected,
+            source: registry.source,
+            registrar_whois_server: Some(registrar_server.to_string()),
+            expiration_at: None,
+            days_until_expiry: None,
+            warnings: Vec::new(),
+            raw_output: format!(
+                "{}\n\n# Registrar WHOIS ({})\n\n{}",
+                registry.raw_output, registrar_server, registrar.raw_output
+            ),
+        }
+    }
+
+    // One WHOIS exchange per attempt, retried per settings and logged like the CLI.
+    // Rate-limit answers are retried with exponential backoff; if the server keeps
+    // refusing, it gets a cool-down that later lookups respect.
+    #[instrument(skip_all, fields(server = %server, domain = %domain))]
+    pub async fn query_with_retries(
+        &self,
+        server: &str,
+        port: u16,
+        query: &str,
+        domain: &str,
+    ) -> Result<String, WhoisError> {
+        let settings = state::settings_for(self.app_handle.as_ref());
+        let timeout = Duration::from_secs(settings.whois_timeout_secs as u64);
+        let args = vec!["-h".to_string(), server.to_string(), query.to_string()];
+        let cooldowns = self
+            .app_handle
+            .as_ref()
+            .and_then(|h| h.try_state::<AppState>());
+
+        if let Some(remaining) = cooldowns
+            .as_ref()
+            .and_then(|s| s.whois_cooldowns.remaining(server))
+        {
+            return Err(WhoisError::RateLimited {
+                server: server.to_string(),
+                retry_after_secs: remaining.as_secs().max(1),
+            });
+        }
+
+        let mut attempt = 0;
+
+        loop {
+            attempt += 1;
+            let waited =
+                rate_limiter::throttle(self.app_handle.as_ref(), Target::WhoisServer, server).await;
+            let attempt_start = Instant::now();
+
+            let exchange = self
+                .backends
+                .whois
+                .query(server, port, query)
+                .instrument(tracing::debug_span!("whois_query", server, port, attempt));
+            let result = tokio::time::timeout(timeout, exchange)
+                .await
+                .unwrap_or_else(|_| {
+                    Err(format!(
+                        "whois timed out after {} seconds",
+                        settings.whois_timeout_secs
+                    ))
+                });
+
+            let (log_output, exit_code) = match &result {
+                Ok(response) => (response.clone(), 0),
+                Err(e) => (e.clone(), 1),
+            };
+
+            self.emit_log(
+                CommandLog::new(
+                    "whois".to_string(),
+                    args.clone(),
+                    log_output,
+                    exit_code,
+                    attempt_start.elapsed().as_secs_f64() * 1000.0,
+                    Some(domain.to_string()),
+                )
+                .with_throttled(waited),
+            );
+
+            let exhausted = attempt > settings.whois_retries;
+            match result {
+                Ok(response) if self.is_rate_limited(&response) => {
+                    if exhausted {
+                        if let Some(state) = &cooldowns {
+                            state
+                                .whois_cooldowns
+                                .start(server, Duration::from_secs(RATE_LIMIT_COOLDOWN_SECS));
+                        }
+                        return Err(WhoisError::RateLimited {
+                            server: server.to_string(),
+                            retry_after_secs: RATE_LIMIT_COOLDOWN_SECS,
+                        });
+                    }
+                    tokio::time::sleep(self.rate_limit_backoff(attempt)).await;
+                }
+                Ok(response) => return Ok(response),
+                Err(e) if exhausted => return Err(WhoisError::Failed(e)),
+                Err(_) => {}
+            }
+        }
+    }
+
+    // Delay before the next attempt after `attempt` rate-limited ones: 1s, 2s, 4s...
+    pub fn rate_limit_backoff(&self, attempt: u32) -> Duration {
+        let secs = RATE_LIMIT_BACKOFF_SECS
+            .saturating_mul(1u64 << attempt.saturating_sub(1).min(16))
+            .min(RATE_LIMIT_BACKOFF_MAX_SECS);
+        Duration::from_secs(secs)
+    }
+
+    // Limit notices come back as a normal answer (exit code 0 with the CLI), so
+    // look for the phrases in short responses that carry no domain data. Long
+    // answers often mention query limits in their terms of use.
+    pub fn is_rate_limited(&self, response: &str) -> bool {
+        let response = response.to_lowercase();
+        response.len() < 1000
+            && !response.contains("domain name:")
+            && RATE_LIMIT_MARKERS
+                .iter()
+                .any(|marker| response.contains(marker))
+    }
+
+    // A few registries need extra flags to return full or English output
+    pub fn format_query(&self, server: &str, domain: &str) -> String {
+        match server {
+            "whois.denic.de" => format!("-T dn,ace {}", domain),
+            "whois.jprs.jp" => format!("{}/e", domain),
+            "whois.verisign-grs.com" => format!("domain {}", domain),
+            _ => domain.to_string(),
+        }
+    }
+
+    // "refer:" (or "whois:") line of IANA's answer for a TLD
+    pub fn parse_iana_referral(&self, response: &str) -> Option<String> {
+        response.lines().find_map(|line| {
+            let (key, value) = line.split_once(':')?;
+            let key = key.trim().to_lowercase();
+            let value = value.trim();
+            ((key == "refer" || key == "whois") && !value.is_empty()).then(|| value.to_lowercase())
+        })
     }
 
     fn parse_whois_output(&self, output: &str, domain: &str) -> Result<WhoisInfo, String> {
@@ -93,7 +1259,16 @@ impl WhoisAdapter {
         let dnssec = self.extract_field(output, &["DNSSEC:", "dnssec:"]);
 
         let nameservers = self.extract_nameservers(output);
-        let status = self.extract_status(output);
+        let status = self
+            .extract_status(output)
+            .iter()
+            .map(|code| EppStatus::from_code(code))
+            .collect();
+
+        let registrant = self.extract_contact(output, "registrant");
+        let admin = self.extract_contact(output, "admin");
+        let tech = self.extract_contact(output, "tech");
+        let privacy_protected = self.is_privacy_protected(&[&registrant, &admin, &tech], output);
 
         Ok(WhoisInfo {
             domain: domain.to_string(),
@@ -101,18 +1276,76 @@ impl WhoisAdapter {
             creation_date,
             expiration_date,
             updated_date,
+            creation_date_raw: None,
+            expiration_date_raw: None,
+            updated_date_raw: None,
             nameservers,
             status,
             dnssec,
+            registrant,
+            admin,
+            tech,
+            privacy_protected,
+            source: WhoisSource::Whois,
+            registrar_whois_server: None,
+            expiration_at: None,
+            days_until_expiry: None,
+            warnings: Vec::new(),
             raw_output: output.to_string(),
         })
     }
 
+    // "<Role> Name:", "<Role> Organization:", ... lines of ICANN-format WHOIS
+    fn extract_contact(&self, text: &str, role: &str) -> Option<Contact> {
+        let field = |suffixes: &[&str]| {
+            text.lines().find_map(|line| {
+                let (key, value) = line.split_once(':')?;
+                let key = key.trim().to_lowercase();
+                let field = key.strip_prefix(role)?.trim();
+                let value = value.trim();
+                (suffixes.contains(&field) && !value.is_empty()).then(|| value.to_string())
+            })
+        };
+
+        self.non_empty_contact(Contact {
+            name: field(&["name"]),
+            organization: field(&["organization", "organisation", "org"]),
+            email: field(&["email", "e-mail"]),
+            phone: field(&["phone", "phone number"]),
+        })
+    }
+
+    fn non_empty_contact(&self, contact: Contact) -> Option<Contact> {
+        let empty = contact.name.is_none()
+            && contact.organization.is_none()
+            && contact.email.is_none()
+            && contact.phone.is_none();
+        (!empty).then_some(contact)
+    }
+
+    // Privacy/proxy service or GDPR redaction in any contact, or a redaction
+    // notice elsewhere in the response
+    pub fn is_privacy_protected(&self, contacts: &[&Option<Contact>], raw_output: &str) -> bool {
+        let marked = |value: &Option<String>| {
+            value.as_ref().is_some_and(|v| {
+                let v = v.to_lowercase();
+                PRIVACY_MARKERS.iter().any(|marker| v.contains(marker))
+            })
+        };
+
+        contacts
+            .iter()
+            .filter_map(|c| c.as_ref())
+            .any(|c| marked(&c.name) || marked(&c.organization) || marked(&c.email))
+            || raw_output.to_lowercase().contains("redacted for privacy")
+    }
+
+    // Value after the pattern, so times like "04:00:00Z" aren't cut at their colons
     fn extract_field(&self, text: &str, patterns: &[&str]) -> Option<String> {
         for pattern in patterns {
             if let Some(line) = text.lines().find(|l| l.contains(pattern)) {
-                if let Some(value) = line.split(':').nth(1) {
-                    return Some(value.trim().to_string());
+                if let Some(index) = line.find(pattern) {
+                    return Some(line[index + pattern.len()..].trim().to_string());
                 }
             }
         }
@@ -174,11 +1407,8 @@ impl WhoisAdapter {
 
         Some(server.to_string())
     }
-
-    fn is_whois_available(&self) -> bool {
-        Command::new("whois").arg("--version").output().is_ok()
-    }
 }
 
 #[cfg(test)]
+#[path = "whois_test.rs"]
 mod tests;
@@ -1,146 +1,152 @@
-#[cfg(test)]
-mod tests {
-    use super::super::whois::WhoisAdapter;
-
-    #[test]
-    fn test_get_whois_server_com() {
-        let adapter = WhoisAdapter::new();
-        let server = adapter.get_whois_server("example.com");
-        assert_eq!(server, Some("whois.verisign-grs.com".to_string()));
-    }
+use super::super::backend::{BackendFuture, Backends, HttpBackend, WhoisBackend};
+use super::super::http::{Exchange, Hop};
+use super::{WhoisAdapter, WhoisError};
+use crate::models::http::HttpTiming;
+use crate::models::whois::{
+    AvailabilityStatus, EppStatus, RegistrationCheck, StatusSeverity, WhoisSource,
+};
+use std::sync::{Arc, Mutex};
+use url::Url;
+
+#[test]
+fn test_get_whois_server_com() {
+    let adapter = WhoisAdapter::new();
+    let server = adapter.get_whois_server("example.com");
+    assert_eq!(server, Some("whois.verisign-grs.com".to_string()));
+}
 
-    #[test]
-    fn test_get_whois_server_io() {
-        let adapter = WhoisAdapter::new();
-        let server = adapter.get_whois_server("example.io");
-        assert_eq!(server, Some("whois.nic.io".to_string()));
-    }
+#[test]
+fn test_get_whois_server_io() {
+    let adapter = WhoisAdapter::new();
+    let server = adapter.get_whois_server("example.io");
+    assert_eq!(server, Some("whois.nic.io".to_string()));
+}
 
-    #[test]
-    fn test_get_whois_server_org() {
-        let adapter = WhoisAdapter::new();
-        let server = adapter.get_whois_server("example.org");
-        assert_eq!(server, Some("whois.pir.org".to_string()));
-    }
+#[test]
+fn test_get_whois_server_org() {
+    let adapter = WhoisAdapter::new();
+    let server = adapter.get_whois_server("example.org");
+    assert_eq!(server, Some("whois.pir.org".to_string()));
+}
 
-    #[test]
-    fn test_get_whois_server_uk() {
-        let adapter = WhoisAdapter::new();
-        let server = adapter.get_whois_server("example.uk");
-        assert_eq!(server, Some("whois.nic.uk".to_string()));
-    }
+#[test]
+fn test_get_whois_server_uk() {
+    let adapter = WhoisAdapter::new();
+    let server = adapter.get_whois_server("example.uk");
+    assert_eq!(server, Some("whois.nic.uk".to_string()));
+}
 
-    #[test]
-    fn test_get_whois_server_unknown_tld() {
-        let adapter = WhoisAdapter::new();
-        let server = adapter.get_whois_server("example.unknowntld");
-        assert_eq!(server, None);
-    }
+#[test]
+fn test_get_whois_server_unknown_tld() {
+    let adapter = WhoisAdapter::new();
+    let server = adapter.get_whois_server("example.unknowntld");
+    assert_eq!(server, None);
+}
 
-    #[test]
-    fn test_get_whois_server_net() {
-        let adapter = WhoisAdapter::new();
-        let server = adapter.get_whois_server("example.net");
-        assert_eq!(server, Some("whois.verisign-grs.com".to_string()));
-    }
+#[test]
+fn test_get_whois_server_net() {
+    let adapter = WhoisAdapter::new();
+    let server = adapter.get_whois_server("example.net");
+    assert_eq!(server, Some("whois.verisign-grs.com".to_string()));
+}
 
-    #[test]
-    fn test_extract_field_simple() {
-        let adapter = WhoisAdapter::new();
-        let text = r#"Domain Name: EXAMPLE.COM
+#[test]
+fn test_extract_field_simple() {
+    let adapter = WhoisAdapter::new();
+    let text = r#"Domain Name: EXAMPLE.COM
 Registrar: Example Registrar Inc.
 Creation Date: 1995-08-14T04:00:00Z"#;
 
-        let registrar = adapter.extract_field(text, &["Registrar:"]);
-        assert_eq!(registrar, Some("Example Registrar Inc.".to_string()));
+    let registrar = adapter.extract_field(text, &["Registrar:"]);
+    assert_eq!(registrar, Some("Example Registrar Inc.".to_string()));
 
-        let creation_date = adapter.extract_field(text, &["Creation Date:"]);
-        assert_eq!(creation_date, Some("1995-08-14T04:00:00Z".to_string()));
-    }
+    let creation_date = adapter.extract_field(text, &["Creation Date:"]);
+    assert_eq!(creation_date, Some("1995-08-14T04:00:00Z".to_string()));
+}
 
-    #[test]
-    fn test_extract_field_multiple_patterns() {
-        let adapter = WhoisAdapter::new();
-        let text = "created: 1995-08-14T04:00:00Z";
+#[test]
+fn test_extract_field_multiple_patterns() {
+    let adapter = WhoisAdapter::new();
+    let text = "created: 1995-08-14T04:00:00Z";
 
-        let creation_date = adapter.extract_field(text, &["Creation Date:", "created:"]);
-        assert_eq!(creation_date, Some("1995-08-14T04:00:00Z".to_string()));
-    }
+    let creation_date = adapter.extract_field(text, &["Creation Date:", "created:"]);
+    assert_eq!(creation_date, Some("1995-08-14T04:00:00Z".to_string()));
+}
 
-    #[test]
-    fn test_extract_field_not_found() {
-        let adapter = WhoisAdapter::new();
-        let text = "Domain Name: EXAMPLE.COM";
+#[test]
+fn test_extract_field_not_found() {
+    let adapter = WhoisAdapter::new();
+    let text = "Domain Name: EXAMPLE.COM";
 
-        let result = adapter.extract_field(text, &["Registrar:"]);
-        assert_eq!(result, None);
-    }
+    let result = adapter.extract_field(text, &["Registrar:"]);
+    assert_eq!(result, None);
+}
 
-    #[test]
-    fn test_extract_nameservers() {
-        let adapter = WhoisAdapter::new();
-        let text = r#"Domain Name: EXAMPLE.COM
+#[test]
+fn test_extract_nameservers() {
+    let adapter = WhoisAdapter::new();
+    let text = r#"Domain Name: EXAMPLE.COM
 Name Server: NS1.EXAMPLE.COM
 Name Server: NS2.EXAMPLE.COM
 Name Server: NS3.EXAMPLE.COM"#;
 
-        let nameservers = adapter.extract_nameservers(text);
-        assert_eq!(nameservers.len(), 3);
-        assert_eq!(nameservers[0], "ns1.example.com");
-        assert_eq!(nameservers[1], "ns2.example.com");
-        assert_eq!(nameservers[2], "ns3.example.com");
-    }
+    let nameservers = adapter.extract_nameservers(text);
+    assert_eq!(nameservers.len(), 3);
+    assert_eq!(nameservers[0], "ns1.example.com");
+    assert_eq!(nameservers[1], "ns2.example.com");
+    assert_eq!(nameservers[2], "ns3.example.com");
+}
 
-    #[test]
-    fn test_extract_nameservers_case_insensitive() {
-        let adapter = WhoisAdapter::new();
-        let text = r#"name server: ns1.EXAMPLE.COM
+#[test]
+fn test_extract_nameservers_case_insensitive() {
+    let adapter = WhoisAdapter::new();
+    let text = r#"name server: ns1.EXAMPLE.COM
 NAME SERVER: ns2.example.com"#;
 
-        let nameservers = adapter.extract_nameservers(text);
-        assert_eq!(nameservers.len(), 2);
-        assert_eq!(nameservers[0], "ns1.example.com");
-        assert_eq!(nameservers[1], "ns2.example.com");
-    }
+    let nameservers = adapter.extract_nameservers(text);
+    assert_eq!(nameservers.len(), 2);
+    assert_eq!(nameservers[0], "ns1.example.com");
+    assert_eq!(nameservers[1], "ns2.example.com");
+}
 
-    #[test]
-    fn test_extract_nameservers_empty() {
-        let adapter = WhoisAdapter::new();
-        let text = "Domain Name: EXAMPLE.COM";
+#[test]
+fn test_extract_nameservers_empty() {
+    let adapter = WhoisAdapter::new();
+    let text = "Domain Name: EXAMPLE.COM";
 
-        let nameservers = adapter.extract_nameservers(text);
-        assert_eq!(nameservers.len(), 0);
-    }
+    let nameservers = adapter.extract_nameservers(text);
+    assert_eq!(nameservers.len(), 0);
+}
 
-    #[test]
-    fn test_extract_status() {
-        let adapter = WhoisAdapter::new();
-        let text = r#"Domain Name: EXAMPLE.COM
+#[test]
+fn test_extract_status() {
+    let adapter = WhoisAdapter::new();
+    let text = r#"Domain Name: EXAMPLE.COM
 Domain Status: clientTransferProhibited
 Domain Status: clientUpdateProhibited
 Status: ok"#;
 
-        let status = adapter.extract_status(text);
-        assert_eq!(status.len(), 3);
-        assert!(status.contains(&"clientTransferProhibited".to_string()));
-        assert!(status.contains(&"clientUpdateProhibited".to_string()));
-        assert!(status.contains(&"ok".to_string()));
-    }
+    let status = adapter.extract_status(text);
+    assert_eq!(status.len(), 3);
+    assert!(status.contains(&"clientTransferProhibited".to_string()));
+    assert!(status.contains(&"clientUpdateProhibited".to_string()));
+    assert!(status.contains(&"ok".to_string()));
+}
 
-    #[test]
-    fn test_extract_status_case_insensitive() {
-        let adapter = WhoisAdapter::new();
-        let text = r#"STATUS: clientTransferProhibited
+#[test]
+fn test_extract_status_case_insensitive() {
+    let adapter = WhoisAdapter::new();
+    let text = r#"STATUS: clientTransferProhibited
 status: ok"#;
 
-        let status = adapter.extract_status(text);
-        assert_eq!(status.len(), 2);
-    }
+    let status = adapter.extract_status(text);
+    assert_eq!(status.len(), 2);
+}
 
-    #[test]
-    fn test_parse_whois_output_full() {
-        let adapter = WhoisAdapter::new();
-        let output = r#"Domain Name: EXAMPLE.COM
+#[test]
+fn test_parse_whois_output_full() {
+    let adapter = WhoisAdapter::new();
+    let output = r#"Domain Name: EXAMPLE.COM
 Registrar: Example Registrar Inc.
 Creation Date: 1995-08-14T04:00:00Z
 Expiration Date: 2025-08-13T04:00:00Z
@@ -150,69 +156,750 @@ Name Server: NS2.EXAMPLE.COM
 Domain Status: clientTransferProhibited
 DNSSEC: unsigned"#;
 
-        let result = adapter.parse_whois_output(output, "example.com");
-        assert!(result.is_ok());
-
-        let info = result.unwrap();
-        assert_eq!(info.domain, "example.com");
-        assert_eq!(info.registrar, Some("Example Registrar Inc.".to_string()));
-        assert_eq!(info.creation_date, Some("1995-08-14T04:00:00Z".to_string()));
-        assert_eq!(
-            info.expiration_date,
-            Some("2025-08-13T04:00:00Z".to_string())
-        );
-        assert_eq!(info.updated_date, Some("2024-08-13T09:11:03Z".to_string()));
-        assert_eq!(info.nameservers.len(), 2);
-        assert_eq!(info.status.len(), 1);
-        assert_eq!(info.dnssec, Some("unsigned".to_string()));
-        assert_eq!(info.raw_output, output);
-    }
+    let result = adapter.parse_whois_output(output, "example.com");
+    assert!(result.is_ok());
+
+    let info = result.unwrap();
+    assert_eq!(info.domain, "example.com");
+    assert_eq!(info.registrar, Some("Example Registrar Inc.".to_string()));
+    assert_eq!(info.creation_date, Some("1995-08-14T04:00:00Z".to_string()));
+    assert_eq!(
+        info.expiration_date,
+        Some("2025-08-13T04:00:00Z".to_string())
+    );
+    assert_eq!(info.updated_date, Some("2024-08-13T09:11:03Z".to_string()));
+    assert_eq!(info.nameservers.len(), 2);
+    assert_eq!(info.status.len(), 1);
+    assert_eq!(info.dnssec, Some("unsigned".to_string()));
+    assert_eq!(info.raw_output, output);
+}
 
-    #[test]
-    fn test_parse_whois_output_minimal() {
-        let adapter = WhoisAdapter::new();
-        let output = "Domain Name: EXAMPLE.COM";
+#[test]
+fn test_parse_whois_output_minimal() {
+    let adapter = WhoisAdapter::new();
+    let output = "Domain Name: EXAMPLE.COM";
 
-        let result = adapter.parse_whois_output(output, "example.com");
-        assert!(result.is_ok());
+    let result = adapter.parse_whois_output(output, "example.com");
+    assert!(result.is_ok());
 
-        let info = result.unwrap();
-        assert_eq!(info.domain, "example.com");
-        assert_eq!(info.registrar, None);
-        assert_eq!(info.creation_date, None);
-        assert_eq!(info.nameservers.len(), 0);
-    }
+    let info = result.unwrap();
+    assert_eq!(info.domain, "example.com");
+    assert_eq!(info.registrar, None);
+    assert_eq!(info.creation_date, None);
+    assert_eq!(info.nameservers.len(), 0);
+}
+
+#[test]
+fn test_get_whois_server_app_dev() {
+    let adapter = WhoisAdapter::new();
+
+    let app_server = adapter.get_whois_server("example.app");
+    assert_eq!(app_server, Some("whois.nic.google".to_string()));
+
+    let dev_server = adapter.get_whois_server("example.dev");
+    assert_eq!(dev_server, Some("whois.nic.google".to_string()));
+}
+
+#[test]
+fn test_get_whois_server_international_tlds() {
+    let adapter = WhoisAdapter::new();
+
+    assert_eq!(
+        adapter.get_whois_server("example.jp"),
+        Some("whois.jprs.jp".to_string())
+    );
+    assert_eq!(
+        adapter.get_whois_server("example.de"),
+        Some("whois.denic.de".to_string())
+    );
+    assert_eq!(
+        adapter.get_whois_server("example.fr"),
+        Some("whois.nic.fr".to_string())
+    );
+    assert_eq!(
+        adapter.get_whois_server("example.au"),
+        Some("whois.auda.org.au".to_string())
+    );
+}
+
+const RDAP_RESPONSE: &str = r#"{
+    "objectClassName": "domain",
+    "ldhName": "EXAMPLE.COM",
+    "status": ["client delete prohibited", "client transfer prohibited", "active"],
+    "entities": [
+        {
+            "objectClassName": "entity",
+            "handle": "376",
+            "roles": ["registrar"],
+            "vcardArray": ["vcard", [["version", {}, "text", "4.0"], ["fn", {}, "text", "Example Registrar Inc."]]],
+            "entities": [
+                {
+                    "objectClassName": "entity",
+                    "roles": ["technical"],
+                    "vcardArray": ["vcard", [["fn", {}, "text", "Hostmaster"], ["email", {}, "text", "hostmaster@example.com"], ["tel", {"type": "voice"}, "uri", "tel:+1.5555550100"]]]
+                }
+            ]
+        },
+        {
+            "objectClassName": "entity",
+            "roles": ["registrant"],
+            "vcardArray": ["vcard", [["fn", {}, "text", "REDACTED FOR PRIVACY"], ["org", {}, "text", "Example Org"]]]
+        }
+    ],
+    "events": [
+        {"eventAction": "registration", "eventDate": "1995-08-14T04:00:00Z"},
+        {"eventAction": "expiration", "eventDate": "2025-08-13T04:00:00Z"},
+        {"eventAction": "last changed", "eventDate": "2024-08-14T07:01:34Z"},
+        {"eventAction": "last update of RDAP database", "eventDate": "2024-10-01T00:00:00Z"}
+    ],
+    "nameservers": [
+        {"objectClassName": "nameserver", "ldhName": "A.IANA-SERVERS.NET"},
+        {"objectClassName": "nameserver", "ldhName": "B.IANA-SERVERS.NET"}
+    ],
+    "secureDNS": {"delegationSigned": true}
+}"#;
+
+#[test]
+fn test_parse_rdap_response() {
+    let adapter = WhoisAdapter::new();
+
+    let info = adapter
+        .parse_rdap_response(RDAP_RESPONSE, "example.com")
+        .unwrap();
+    assert_eq!(info.source, WhoisSource::Rdap);
+    assert_eq!(info.registrar, Some("Example Registrar Inc.".to_string()));
+    assert_eq!(info.creation_date, Some("1995-08-14T04:00:00Z".to_string()));
+    assert_eq!(
+        info.expiration_date,
+        Some("2025-08-13T04:00:00Z".to_string())
+    );
+    assert_eq!(info.updated_date, Some("2024-08-14T07:01:34Z".to_string()));
+    assert_eq!(
+        info.nameservers,
+        vec!["a.iana-servers.net", "b.iana-servers.net"]
+    );
+    let codes: Vec<&str> = info.status.iter().map(|s| s.code.as_str()).collect();
+    assert_eq!(
+        codes,
+        vec!["clientDeleteProhibited", "clientTransferProhibited", "ok"]
+    );
+    assert_eq!(info.dnssec, Some("signedDelegation".to_string()));
+
+    let registrant = info.registrant.unwrap();
+    assert_eq!(registrant.organization, Some("Example Org".to_string()));
+    let tech = info.tech.unwrap();
+    assert_eq!(tech.email, Some("hostmaster@example.com".to_string()));
+    assert_eq!(tech.phone, Some("+1.5555550100".to_string()));
+    assert!(info.admin.is_none());
+    assert!(info.privacy_protected);
+}
+
+#[test]
+fn test_parse_rdap_response_minimal() {
+    let adapter = WhoisAdapter::new();
+
+    let info = adapter
+        .parse_rdap_response(r#"{"objectClassName": "domain"}"#, "example.com")
+        .unwrap();
+    assert_eq!(info.registrar, None);
+    assert!(info.nameservers.is_empty());
+    assert_eq!(info.dnssec, None);
+
+    let error =
+        adapter.parse_rdap_response(r#"{"errorCode": 404, "title": "Not Found"}"#, "example.com");
+    assert!(error.is_err());
+}
+
+#[tokio::test]
+async fn test_query_with_retries_reads_until_close() {
+    use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+    use tokio::net::TcpListener;
+
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let port = listener.local_addr().unwrap().port();
+    tokio::spawn(async move {
+        let (socket, _) = listener.accept().await.unwrap();
+        let mut reader = BufReader::new(socket);
+        let mut query = String::new();
+        reader.read_line(&mut query).await.unwrap();
+        let reply = format!("Query: {}Registrar: Example Registrar Inc.\r\n", query);
+        reader.get_mut().write_all(reply.as_bytes()).await.unwrap();
+    });
+
+    let adapter = WhoisAdapter::new();
+    let response = adapter
+        .query_with_retries("127.0.0.1", port, "example.com", "example.com")
+        .await
+        .unwrap();
+    assert!(response.starts_with("Query: example.com\r\n"));
+    assert!(response.contains("Registrar: Example Registrar Inc."));
+}
+
+#[tokio::test]
+async fn test_query_with_retries_connection_refused() {
+    let port = {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        listener.local_addr().unwrap().port()
+    };
+
+    let adapter = WhoisAdapter::new();
+    let error = adapter
+        .query_with_retries("127.0.0.1", port, "example.com", "example.com")
+        .await
+        .unwrap_err();
+    assert!(error
+        .to_string()
+        .starts_with("Failed to connect to 127.0.0.1"));
+}
 
-    #[test]
-    fn test_get_whois_server_app_dev() {
-        let adapter = WhoisAdapter::new();
+#[test]
+fn test_parse_iana_referral() {
+    let adapter = WhoisAdapter::new();
+    let response = "% IANA WHOIS server\n\ndomain:       XYZ\n\norganisation: XYZ.COM LLC\nrefer:        whois.nic.xyz\n";
 
-        let app_server = adapter.get_whois_server("example.app");
-        assert_eq!(app_server, Some("whois.nic.google".to_string()));
+    assert_eq!(
+        adapter.parse_iana_referral(response),
+        Some("whois.nic.xyz".to_string())
+    );
+    assert_eq!(adapter.parse_iana_referral("domain: ARPA\n"), None);
+}
+
+#[test]
+fn test_format_query() {
+    let adapter = WhoisAdapter::new();
+
+    assert_eq!(
+        adapter.format_query("whois.denic.de", "example.de"),
+        "-T dn,ace example.de"
+    );
+    assert_eq!(
+        adapter.format_query("whois.verisign-grs.com", "example.com"),
+        "domain example.com"
+    );
+    assert_eq!(
+        adapter.format_query("whois.pir.org", "example.org"),
+        "example.org"
+    );
+}
+
+#[test]
+fn test_parse_registrar_referral() {
+    let adapter = WhoisAdapter::new();
+    let output = "   Domain Name: EXAMPLE.COM\n   Registrar WHOIS Server: whois.MarkMonitor.com\n   Registrar URL: http://www.markmonitor.com\n";
+
+    assert_eq!(
+        adapter.parse_registrar_referral(output),
+        Some("whois.markmonitor.com".to_string())
+    );
+    assert_eq!(
+        adapter.parse_registrar_referral("Registrar WHOIS Server: https://whois.example.net/"),
+        Some("whois.example.net".to_string())
+    );
+    assert_eq!(
+        adapter.parse_registrar_referral("Registrar WHOIS Server: \n"),
+        None
+    );
+}
+
+#[test]
+fn test_merge_registrar_info() {
+    let adapter = WhoisAdapter::new();
+    let registry = adapter
+        .parse_whois_output(
+            "Registrar: MarkMonitor Inc.\nUpdated Date: 2024\nName Server: A.IANA-SERVERS.NET\nDomain Status: clientTransferProhibited\nDNSSEC: signedDelegation",
+            "example.com",
+        )
+        .unwrap();
+    let registrar = adapter
+        .parse_whois_output(
+            "Registrar: MarkMonitor, Inc.\nUpdated Date: 2025\nName Server: ns1.other.net\nDomain Status: clientTransferProhibited\nDomain Status: clientUpdateProhibited",
+            "example.com",
+        )
+        .unwrap();
+
+    let merged = adapter.merge_registrar_info(registry, registrar, "whois.markmonitor.com");
+    assert_eq!(merged.registrar, Some("MarkMonitor Inc.".to_string()));
+    assert_eq!(merged.updated_date, Some("2025".to_string()));
+    assert_eq!(merged.nameservers, vec!["a.iana-servers.net"]);
+    let codes: Vec<&str> = merged.status.iter().map(|s| s.code.as_str()).collect();
+    assert_eq!(
+        codes,
+        vec!["clientTransferProhibited", "clientUpdateProhibited"]
+    );
+    assert_eq!(merged.dnssec, Some("signedDelegation".to_string()));
+    assert_eq!(
+        merged.registrar_whois_server,
+        Some("whois.markmonitor.com".to_string())
+    );
+    assert!(merged
+        .raw_output
+        .contains("# Registrar WHOIS (whois.markmonitor.com)"));
+}
+
+#[test]
+fn test_parse_whois_contacts() {
+    let adapter = WhoisAdapter::new();
+    let output = "Registrant Name: Jane Doe\nRegistrant Organization: Example Org\nRegistrant Email: jane@example.com\nAdmin Name: John Admin\nAdmin Phone: +1.5555550100\nTech Email: tech@example.com\n";
+
+    let info = adapter.parse_whois_output(output, "example.com").unwrap();
+    let registrant = info.registrant.unwrap();
+    assert_eq!(registrant.name, Some("Jane Doe".to_string()));
+    assert_eq!(registrant.organization, Some("Example Org".to_string()));
+    assert_eq!(registrant.email, Some("jane@example.com".to_string()));
+    assert_eq!(registrant.phone, None);
+    assert_eq!(info.admin.unwrap().phone, Some("+1.5555550100".to_string()));
+    assert_eq!(
+        info.tech.unwrap().email,
+        Some("tech@example.com".to_string())
+    );
+    assert!(!info.privacy_protected);
+}
+
+#[test]
+fn test_privacy_protected_contacts() {
+    let adapter = WhoisAdapter::new();
+    let output = "Registrant Organization: Domains By Proxy, LLC\nRegistrant Email: example.com@domainsbyproxy.com\n";
+
+    let info = adapter.parse_whois_output(output, "example.com").unwrap();
+    assert!(info.privacy_protected);
+    assert!(info.admin.is_none());
+
+    let redacted = adapter
+        .parse_whois_output("Registrant Name: REDACTED FOR PRIVACY\n", "example.com")
+        .unwrap();
+    assert!(redacted.privacy_protected);
+}
+
+const RDAP_IP_RESPONSE: &str = r#"{
+    "objectClassName": "ip network",
+    "handle": "NET-8-8-8-0-2",
+    "startAddress": "8.8.8.0",
+    "endAddress": "8.8.8.255",
+    "name": "GOGL",
+    "country": "US",
+    "port43": "whois.arin.net",
+    "entities": [
+        {
+            "objectClassName": "entity",
+            "roles": ["registrant"],
+            "vcardArray": ["vcard", [["fn", {}, "text", "Google LLC"]]],
+            "entities": [
+                {
+                    "objectClassName": "entity",
+                    "roles": ["abuse"],
+                    "vcardArray": ["vcard", [["fn", {}, "text", "Abuse"], ["email", {}, "text", "network-abuse@google.com"]]]
+                }
+            ]
+        }
+    ]
+}"#;
+
+#[test]
+fn test_parse_rdap_ip_response() {
+    let adapter = WhoisAdapter::new();
+
+    let info = adapter
+        .parse_rdap_ip_response(RDAP_IP_RESPONSE, "8.8.8.8".parse().unwrap())
+        .unwrap();
+    assert_eq!(info.ip, "8.8.8.8");
+    assert_eq!(info.network_name, Some("GOGL".to_string()));
+    assert_eq!(info.network_handle, Some("NET-8-8-8-0-2".to_string()));
+    assert_eq!(info.network_range, Some("8.8.8.0 - 8.8.8.255".to_string()));
+    assert_eq!(info.organization, Some("Google LLC".to_string()));
+    assert_eq!(info.country, Some("US".to_string()));
+    assert_eq!(
+        info.abuse_email,
+        Some("network-abuse@google.com".to_string())
+    );
+    assert_eq!(info.rir, Some("arin".to_string()));
+
+    assert!(adapter
+        .parse_rdap_ip_response(RDAP_RESPONSE, "8.8.8.8".parse().unwrap())
+        .is_err());
+}
+
+#[test]
+fn test_cymru_origin_name() {
+    let adapter = WhoisAdapter::new();
+
+    assert_eq!(
+        adapter.cymru_origin_name("1.2.3.4".parse().unwrap()),
+        "4.3.2.1.origin.asn.cymru.com"
+    );
+    assert_eq!(
+        adapter.cymru_origin_name("2001:db8::1".parse().unwrap()),
+        "1.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.8.b.d.0.1.0.0.2.origin6.asn.cymru.com"
+    );
+}
+
+#[test]
+fn test_parse_cymru_records() {
+    let adapter = WhoisAdapter::new();
+    let records = vec![
+        "15169 | 8.0.0.0/12 | US | arin | 1992-12-01".to_string(),
+        "15169 | 8.8.8.0/24 | US | arin | 2023-12-28".to_string(),
+    ];
+
+    assert_eq!(
+        adapter.parse_cymru_origin(&records),
+        Some((15169, "8.8.8.0/24".to_string(), "arin".to_string()))
+    );
+    assert_eq!(adapter.parse_cymru_origin(&["garbage".to_string()]), None);
+    assert_eq!(
+        adapter.parse_cymru_as_name("15169 | US | arin | 2000-03-30 | GOOGLE - Google LLC, US"),
+        Some("GOOGLE - Google LLC, US".to_string())
+    );
+}
+
+#[test]
+fn test_not_found_phrase() {
+    let adapter = WhoisAdapter::new();
+
+    assert_eq!(
+        adapter.not_found_phrase("\r\nNo match for \"NOPE-EXAMPLE.COM\".\r\n>>> Last update"),
+        Some("No match for \"NOPE-EXAMPLE.COM\".".to_string())
+    );
+    assert_eq!(
+        adapter.not_found_phrase("Domain: nope.de\nStatus: free\n"),
+        Some("Status: free".to_string())
+    );
+    assert_eq!(
+        adapter.not_found_phrase("Domain Name: EXAMPLE.COM\nRegistrar: Example"),
+        None
+    );
+}
+
+#[test]
+fn test_classify_availability() {
+    let adapter = WhoisAdapter::new();
+    let check = |registered: Option<bool>, status: &[&str]| RegistrationCheck {
+        registered,
+        status: status.iter().map(|s| s.to_string()).collect(),
+        ..Default::default()
+    };
+
+    assert_eq!(
+        adapter.classify_availability(&check(Some(true), &["ok"]), Some(false)),
+        AvailabilityStatus::Registered
+    );
+    assert_eq!(
+        adapter.classify_availability(&check(Some(false), &[]), Some(true)),
+        AvailabilityStatus::Available
+    );
+    assert_eq!(
+        adapter.classify_availability(&check(Some(true), &["redemptionPeriod"]), Some(true)),
+        AvailabilityStatus::Redemption
+    );
+    assert_eq!(
+        adapter.classify_availability(
+            &check(Some(true), &["redemptionPeriod", "pendingDelete"]),
+            None
+        ),
+        AvailabilityStatus::PendingDelete
+    );
+    // Conflicting or missing answers
+    assert_eq!(
+        adapter.classify_availability(&check(Some(false), &[]), Some(false)),
+        AvailabilityStatus::Unknown
+    );
+    assert_eq!(
+        adapter.classify_availability(&check(None, &[]), Some(true)),
+        AvailabilityStatus::Unknown
+    );
+    assert_eq!(
+        adapter.classify_availability(&check(None, &[]), Some(false)),
+        AvailabilityStatus::Registered
+    );
+}
+
+#[test]
+fn test_parse_whois_date() {
+    let adapter = WhoisAdapter::new();
+
+    let expected = chrono::DateTime::parse_from_rfc3339("2025-08-13T04:00:00Z")
+        .unwrap()
+        .with_timezone(&chrono::Utc);
+    assert_eq!(
+        adapter.parse_whois_date("2025-08-13T04:00:00Z"),
+        Some(expected)
+    );
+    assert_eq!(
+        adapter.parse_whois_date("2025-08-13T04:00:00.0Z"),
+        Some(expected)
+    );
+    assert_eq!(
+        adapter.parse_whois_date("2025-08-13 04:00:00"),
+        Some(expected)
+    );
+    assert!(adapter.parse_whois_date("2025-08-13").is_some());
+    assert_eq!(adapter.parse_whois_date("soon"), None);
+}
+
+#[test]
+fn test_parse_whois_date_registry_formats() {
+    let adapter = WhoisAdapter::new();
+    let parse = |value: &str| {
+        adapter
+            .parse_whois_date(value)
+            .map(|d| d.to_rfc3339_opts(chrono::SecondsFormat::Secs, true))
+    };
+    let day = Some("1995-08-14T00:00:00Z".to_string());
+
+    assert_eq!(parse("14-Aug-1995"), day);
+    assert_eq!(parse("14-aug-1995"), day);
+    assert_eq!(parse("1995/08/14"), day);
+    assert_eq!(parse("1995/08/14 (JST)"), day);
+    assert_eq!(parse("14.08.1995"), day);
+    assert_eq!(parse("1995. 08. 14."), day);
+    assert_eq!(parse("19950814"), day);
+    assert_eq!(
+        parse("1995-08-14 07:00:00+03"),
+        Some("1995-08-14T04:00:00Z".to_string())
+    );
+    assert_eq!(
+        parse("1995-08-14T04:00:00+0000"),
+        Some("1995-08-14T04:00:00Z".to_string())
+    );
+    assert_eq!(
+        parse("1995-08-14 04:00:00 UTC"),
+        Some("1995-08-14T04:00:00Z".to_string())
+    );
+}
+
+#[test]
+fn test_normalize_dates() {
+    let adapter = WhoisAdapter::new();
+    let mut info = adapter
+        .parse_whois_output(
+            "Domain Name: example.co.uk\nCreation Date: 14-Aug-1995\nRegistry Expiry Date: 2025-08-13T04:00:00Z\nUpdated Date: someday\n",
+            "example.co.uk",
+        )
+        .unwrap();
+    adapter.normalize_dates(&mut info);
+
+    assert_eq!(info.creation_date, Some("1995-08-14T00:00:00Z".to_string()));
+    assert_eq!(info.creation_date_raw, Some("14-Aug-1995".to_string()));
+    assert_eq!(
+        info.expiration_date,
+        Some("2025-08-13T04:00:00Z".to_string())
+    );
+    assert_eq!(
+        info.expiration_date_raw,
+        Some("2025-08-13T04:00:00Z".to_string())
+    );
+    assert_eq!(info.updated_date, Some("someday".to_string()));
+    assert_eq!(info.updated_date_raw, Some("someday".to_string()));
+}
+
+#[test]
+fn test_expiry_warnings() {
+    let adapter = WhoisAdapter::new();
+    let now = chrono::DateTime::parse_from_rfc3339("2025-08-01T00:00:00Z")
+        .unwrap()
+        .with_timezone(&chrono::Utc);
+
+    let mut info = adapter
+        .parse_whois_output("Expiration Date: 2025-08-13T04:00:00Z", "example.com")
+        .unwrap();
+    let warnings = adapter.expiry_warnings(&mut info, now, 30);
+    assert_eq!(info.days_until_expiry, Some(12));
+    assert_eq!(
+        warnings,
+        vec!["Domain expires in 12 days on 2025-08-13; renew it before it lapses"]
+    );
+
+    assert!(adapter.expiry_warnings(&mut info, now, 7).is_empty());
+
+    let mut expired = adapter
+        .parse_whois_output(
+            "Expiry Date: 2025-07-01T00:00:00Z\nDomain Status: redemptionPeriod",
+            "example.com",
+        )
+        .unwrap();
+    let warnings = adapter.expiry_warnings(&mut expired, now, 30);
+    assert_eq!(expired.days_until_expiry, Some(-31));
+    assert_eq!(warnings[0], "Domain registration expired on 2025-07-01");
+    assert!(warnings[1].starts_with("Domain is in redemptionPeriod"));
+}
+
+#[test]
+fn test_epp_status_explanations() {
+    let hold = EppStatus::from_code("serverHold");
+    assert_eq!(hold.severity, StatusSeverity::Critical);
+    assert!(hold.description.contains("does not resolve"));
+
+    let lock = EppStatus::from_code("clientTransferProhibited");
+    assert_eq!(lock.severity, StatusSeverity::Info);
+    assert_eq!(lock.code, "clientTransferProhibited");
+
+    assert_eq!(
+        EppStatus::from_code("pendingTransfer").severity,
+        StatusSeverity::Warning
+    );
+    assert_eq!(
+        EppStatus::from_code("someRegistryThing").description,
+        "Unrecognized status"
+    );
+}
+
+#[test]
+fn test_is_rate_limited() {
+    let adapter = WhoisAdapter::new();
+
+    assert!(adapter.is_rate_limited("WHOIS LIMIT EXCEEDED - SEE WWW.PIR.ORG/WHOIS FOR DETAILS\r\n"));
+    assert!(adapter.is_rate_limited("%% Too many queries from your IP, try again later"));
+    assert!(!adapter.is_rate_limited(
+        "Domain Name: EXAMPLE.COM\nRegistrar: Example\nBy querying you agree not to exceed our query rate limit"
+    ));
+    assert!(!adapter.is_rate_limited("No match for \"NOPE.COM\"."));
+}
 
-        let dev_server = adapter.get_whois_server("example.dev");
-        assert_eq!(dev_server, Some("whois.nic.google".to_string()));
+#[test]
+fn test_rate_limit_backoff() {
+    let adapter = WhoisAdapter::new();
+
+    assert_eq!(adapter.rate_limit_backoff(1).as_secs(), 1);
+    assert_eq!(adapter.rate_limit_backoff(2).as_secs(), 2);
+    assert_eq!(adapter.rate_limit_backoff(4).as_secs(), 8);
+    assert_eq!(adapter.rate_limit_backoff(40).as_secs(), 30);
+}
+
+#[tokio::test]
+async fn test_query_with_retries_rate_limited() {
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let port = listener.local_addr().unwrap().port();
+    tokio::spawn(async move {
+        while let Ok((mut socket, _)) = listener.accept().await {
+            let mut query = [0u8; 256];
+            let _ = socket.read(&mut query).await;
+            let _ = socket.write_all(b"Query rate limit exceeded\r\n").await;
+        }
+    });
+
+    let adapter = WhoisAdapter::new();
+    let error = adapter
+        .query_with_retries("127.0.0.1", port, "example.com", "example.com")
+        .await
+        .unwrap_err();
+    assert_eq!(
+        error,
+        WhoisError::RateLimited {
+            server: "127.0.0.1".to_string(),
+            retry_after_secs: 60,
+        }
+    );
+    assert_eq!(
+        error.to_string(),
+        "Rate limited by 127.0.0.1; try again in 60 seconds"
+    );
+}
+
+#[test]
+fn test_summarize_bulk() {
+    use crate::models::whois::BulkWhoisResult;
+
+    let adapter = WhoisAdapter::new();
+    let now = chrono::DateTime::parse_from_rfc3339("2025-08-01T00:00:00Z")
+        .unwrap()
+        .with_timezone(&chrono::Utc);
+    let result = |domain: &str, expiry: Option<&str>| {
+        let info = expiry.map(|date| {
+            let mut info = adapter
+                .parse_whois_output(
+                    &format!("Domain Name: {}\nRegistry Expiry Date: {}\n", domain, date),
+                    domain,
+                )
+                .unwrap();
+            adapter.expiry_warnings(&mut info, now, 30);
+            info
+        });
+        BulkWhoisResult {
+            domain: domain.to_string(),
+            error: info.is_none().then(|| "timed out".to_string()),
+            info,
+            completed: 0,
+            total: 4,
+        }
+    };
+
+    let summary = adapter.summarize_bulk(
+        vec![
+            result("fine.com", Some("2026-08-01T00:00:00Z")),
+            result("soon.com", Some("2025-08-10T00:00:00Z")),
+            result("lapsed.com", Some("2025-07-31T12:00:00Z")),
+            result("broken.com", None),
+        ],
+        now,
+        30,
+        1.5,
+    );
+
+    assert_eq!(summary.total, 4);
+    assert_eq!(summary.succeeded, 3);
+    assert_eq!(summary.failed, 1);
+    assert_eq!(summary.expired, vec!["lapsed.com"]);
+    assert_eq!(summary.expiring_soon, vec!["soon.com"]);
+    assert_eq!(summary.results[3].domain, "broken.com");
+}
+
+// Every HTTP request (RDAP included) gets a 404
+struct NotFound;
+
+impl HttpBackend for NotFound {
+    fn send<'a>(&'a self, _: &'a Hop<'a>, _: &'a Url) -> BackendFuture<'a, Exchange> {
+        Box::pin(async {
+            Ok(Exchange {
+                status_code: 404,
+                headers: Vec::new(),
+                head: "HTTP/1.1 404 Not Found\r\n".to_string(),
+                body: Vec::new(),
+                truncated: false,
+                timing: HttpTiming::default(),
+                ip: "192.0.2.80".parse().unwrap(),
+            })
+        })
     }
+}
+
+// Canned answers by server, recording the servers asked
+struct CannedWhois {
+    servers: Arc<Mutex<Vec<String>>>,
+}
 
-    #[test]
-    fn test_get_whois_server_international_tlds() {
-        let adapter = WhoisAdapter::new();
-
-        assert_eq!(
-            adapter.get_whois_server("example.jp"),
-            Some("whois.jprs.jp".to_string())
-        );
-        assert_eq!(
-            adapter.get_whois_server("example.de"),
-            Some("whois.denic.de".to_string())
-        );
-        assert_eq!(
-            adapter.get_whois_server("example.fr"),
-            Some("whois.nic.fr".to_string())
-        );
-        assert_eq!(
-            adapter.get_whois_server("example.au"),
-            Some("whois.auda.org.au".to_string())
-        );
+impl WhoisBackend for CannedWhois {
+    fn query<'a>(&'a self, server: &'a str, _: u16, _: &'a str) -> BackendFuture<'a, String> {
+        self.servers.lock().unwrap().push(server.to_string());
+        let response = match server {
+            "whois.verisign-grs.com" => {
+                "   Domain Name: EXAMPLE.COM\r\n   Registrar WHOIS Server: whois.example-registrar.com\r\n   Registry Expiry Date: 2030-08-13T04:00:00Z\r\n"
+            }
+            _ => "Domain Name: example.com\r\nRegistrar: Example Registrar Inc.\r\n",
+        };
+        Box::pin(async move { Ok(response.to_string()) })
     }
 }
+
+#[tokio::test]
+async fn test_lookup_falls_back_to_canned_port43() {
+    let servers = Arc::new(Mutex::new(Vec::new()));
+    let backends = Backends::default()
+        .with_http(NotFound)
+        .with_whois(CannedWhois {
+            servers: servers.clone(),
+        });
+    let adapter = WhoisAdapter::new().with_backends(backends);
+
+    let info = adapter.lookup("example.com").await.unwrap();
+    assert_eq!(info.source, WhoisSource::Whois);
+    assert_eq!(
+        info.registrar_whois_server.as_deref(),
+        Some("whois.example-registrar.com")
+    );
+    assert_eq!(
+        *servers.lock().unwrap(),
+        vec!["whois.verisign-grs.com", "whois.example-registrar.com"]
+    );
+}
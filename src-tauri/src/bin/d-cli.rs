@@ -0,0 +1,284 @@
+// Headless command-line interface to the same checks the app runs
+//
+// Each subcommand calls the library adapters directly with default settings and
+// prints either a short text summary or, with --json, the model the app receives.
+// Exits with 1 when a check fails so scripts and CI can act on it.
+
+use clap::{Parser, Subcommand};
+use d_dns_debugger_lib::adapters::certificate::CertificateAdapter;
+use d_dns_debugger_lib::adapters::dns::DnsAdapter;
+use d_dns_debugger_lib::adapters::email::EmailAdapter;
+use d_dns_debugger_lib::adapters::environment::EnvironmentAdapter;
+use d_dns_debugger_lib::adapters::http::HttpAdapter;
+use d_dns_debugger_lib::adapters::whois::WhoisAdapter;
+use d_dns_debugger_lib::commands::dnssec;
+use d_dns_debugger_lib::commands::idn;
+use d_dns_debugger_lib::models::http::HttpRequest;
+use d_dns_debugger_lib::models::settings::Settings;
+use serde::Serialize;
+use std::process::ExitCode;
+
+#[derive(Parser)]
+#[command(
+    name = "d-cli",
+    version,
+    about = "DNS, DNSSEC, WHOIS, TLS and HTTP checks"
+)]
+struct Cli {
+    /// Print the full result as JSON
+    #[arg(long, global = true)]
+    json: bool,
+
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Query one record type
+    Dns {
+        domain: String,
+        #[arg(default_value = "A")]
+        record_type: String,
+    },
+    /// Query every common record type
+    Records { domain: String },
+    /// Validate the DNSSEC chain of trust
+    Dnssec { domain: String },
+    /// Registration data over RDAP or WHOIS
+    Whois { domain: String },
+    /// Certificate chain served by a host
+    Tls {
+        host: String,
+        #[arg(long, default_value_t = 443)]
+        port: u16,
+    },
+    /// Request a URL and follow its redirects
+    Http {
+        url: String,
+        #[arg(long, default_value = "HEAD")]
+        method: String,
+    },
+    /// MX, SPF, DKIM and DMARC configuration
+    Email { domain: String },
+    /// Check which external tools are installed
+    Doctor,
+}
+
+#[tokio::main]
+async fn main() -> ExitCode {
+    let cli = Cli::parse();
+    d_dns_debugger_lib::init_tracing();
+    match run(cli.command, cli.json).await {
+        Ok(true) => ExitCode::SUCCESS,
+        Ok(false) => ExitCode::FAILURE,
+        Err(e) => {
+            eprintln!("error: {}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+// Ok(false) when the check ran but found a problem: an error response, a BOGUS
+// chain, an invalid certificate, an HTTP error status or an SPF PermError
+async fn run(command: Command, json: bool) -> Result<bool, String> {
+    match command {
+        Command::Dns {
+            domain,
+            record_type,
+        } => {
+            let domain = idn::to_ascii(&domain)?;
+            let response = DnsAdapter::new()
+                .query(&domain, &record_type.to_uppercase())
+                .await?;
+            output(json, &response, || {
+                for record in &response.records {
+                    println!(
+                        "{}\t{}\t{}\t{}",
+                        idn::to_unicode(&record.name),
+                        record.ttl,
+                        record.record_type,
+                        record.value
+                    );
+                }
+                println!(
+                    ";; {:.0} ms from {}",
+                    response.query_time * 1000.0,
+                    response.resolver
+                );
+            })?;
+            let rcode = response.header.as_ref().map(|h| h.rcode.as_str());
+            Ok(matches!(rcode, None | Some("NOERROR")))
+        }
+        Command::Records { domain } => {
+            let enumeration = DnsAdapter::new()
+                .enumerate_records(&idn::to_ascii(&domain)?)
+                .await;
+            output(json, &enumeration, || {
+                for result in &enumeration.results {
+                    if let Some(error) = &result.error {
+                        println!("{}\t(error: {})", result.record_type, error);
+                    }
+                    for record in &result.records {
+                        println!(
+                            "{}\t{}\t{}\t{}",
+                            idn::to_unicode(&record.name),
+                            record.ttl,
+                            record.record_type,
+                            record.value
+                        );
+                    }
+                }
+            })?;
+            Ok(enumeration.results.iter().all(|r| r.error.is_none()))
+        }
+        Command::Dnssec { domain } => {
+            let validation =
+                dnssec::validate_chain(&DnsAdapter::new(), &domain, &Settings::default(), |_| {})
+                    .await?;
+            output(json, &validation, || {
+                println!("{}", validation.status);
+                for zone in &validation.chain {
+                    println!("  {}\t{}", zone.zone_name, zone.status);
+                }
+                warnings(&validation.warnings);
+            })?;
+            Ok(validation.status != "BOGUS")
+        }
+        Command::Whois { domain } => {
+            let mut info = WhoisAdapter::new().lookup(&idn::to_ascii(&domain)?).await?;
+            info.domain = idn::to_unicode(&info.domain);
+            output(json, &info, || {
+                field("Domain", Some(&info.domain));
+                field("Registrar", info.registrar.as_ref());
+                field("Created", info.creation_date.as_ref());
+                field("Expires", info.expiration_date.as_ref());
+                field("Nameservers", Some(&info.nameservers.join(", ")));
+                let statuses: Vec<&str> = info.status.iter().map(|s| s.code.as_str()).collect();
+                field("Status", Some(&statuses.join(", ")));
+                warnings(&info.warnings);
+            })?;
+            Ok(true)
+        }
+        Command::Tls { host, port } => {
+            let mut info = CertificateAdapter::new()
+                .get_certificate_info(&idn::to_ascii(&host)?, port, None)
+                .await?;
+            info.host = idn::to_unicode(&info.host);
+            output(json, &info, || {
+                let chain = &info.certificate_chain;
+                if let Some(leaf) = chain.certificates.first() {
+                    field("Subject", leaf.subject.common_name.as_ref());
+                    field("Issuer", leaf.issuer.common_name.as_ref());
+                    field("Expires", Some(&leaf.not_after));
+                    field("Names", Some(&leaf.subject_alternative_names.join(", ")));
+                }
+                field(
+                    "Chain",
+                    Some(&if chain.is_valid { "valid" } else { "invalid" }),
+                );
+                warnings(&chain.validation_errors);
+                warnings(&info.warnings);
+            })?;
+            Ok(info.certificate_chain.is_valid)
+        }
+        Command::Http { url, method } => {
+            let request = HttpRequest {
+                method: method.to_uppercase(),
+                headers: Vec::new(),
+                body: None,
+                max_body_kb: None,
+            };
+            let mut response = HttpAdapter::new()
+                .fetch(&idn::url_to_ascii(&url)?, &request)
+                .await?;
+            response.url = url;
+            output(json, &response, || {
+                for hop in &response.redirects {
+                    println!("{} {}", hop.status_code, hop.from_url);
+                }
+                println!("{} {}", response.status_code, response.final_url);
+                warnings(&response.warnings);
+            })?;
+            Ok(response.status_code < 400)
+        }
+        Command::Email { domain } => {
+            let domain = idn::to_ascii(&domain)?.trim_end_matches('.').to_lowercase();
+            let mut config = EmailAdapter::new().fetch_email_config(&domain, &[]).await?;
+            config.domain = idn::to_unicode(&config.domain);
+            let spf_error = config.spf.as_ref().and_then(|s| s.perm_error.clone());
+            output(json, &config, || {
+                for mx in &config.mx_records {
+                    println!("MX\t{}\t{}", mx.priority, mx.hostname);
+                }
+                field("SPF", config.spf.as_ref().and_then(|s| s.record.as_ref()));
+                field("DMARC", config.dmarc.as_ref().map(|d| &d.record));
+                let selectors: Vec<&str> = config
+                    .dkim
+                    .records
+                    .iter()
+                    .map(|r| r.selector.as_str())
+                    .collect();
+                field("DKIM", Some(&selectors.join(", ")));
+                warnings(&config.warnings);
+                if let Some(error) = &spf_error {
+                    eprintln!("error: SPF PermError: {}", error);
+                }
+            })?;
+            Ok(spf_error.is_none())
+        }
+        Command::Doctor => {
+            let report = EnvironmentAdapter::new().check().await;
+            output(json, &report, || {
+                for tool in &report.tools {
+                    match &tool.path {
+                        Some(path) => println!(
+                            "{:<8} {}\t{}",
+                            tool.name,
+                            path,
+                            tool.version.as_deref().unwrap_or("-")
+                        ),
+                        None => println!("{:<8} not found\t{}", tool.name, tool.purpose),
+                    }
+                }
+                for feature in &report.degraded {
+                    eprintln!(
+                        "warning: {} (needs {}): {}",
+                        feature.feature,
+                        feature.missing.join(", "),
+                        feature.impact
+                    );
+                }
+            })?;
+            if !report.ready {
+                eprintln!("error: required tools are missing");
+            }
+            Ok(report.ready)
+        }
+    }
+}
+
+// Pretty JSON with --json, else the command's text summary
+fn output<T: Serialize>(json: bool, value: &T, text: impl FnOnce()) -> Result<(), String> {
+    if json {
+        let json = serde_json::to_string_pretty(value)
+            .map_err(|e| format!("Failed to serialize the result: {}", e))?;
+        println!("{}", json);
+    } else {
+        text();
+    }
+    Ok(())
+}
+
+fn field(name: &str, value: Option<&impl std::fmt::Display>) {
+    match value {
+        Some(value) => println!("{:<12} {}", name, value),
+        None => println!("{:<12} -", name),
+    }
+}
+
+fn warnings(warnings: &[String]) {
+    for warning in warnings {
+        eprintln!("warning: {}", warning);
+    }
+}
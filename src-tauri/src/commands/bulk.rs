@@ -0,0 +1,150 @@
+use crate::adapters::health::HealthAdapter;
+use crate::adapters::report::ReportAdapter;
+use crate::commands::idn;
+use crate::commands::report::{self, SECTIONS};
+use crate::models::report::{BulkScanSummary, DomainImport};
+use crate::state;
+use crate::state::jobs;
+use std::sync::Arc;
+use std::time::Instant;
+use tauri::{AppHandle, Emitter};
+use tokio::sync::Semaphore;
+use tokio::task::JoinSet;
+
+const MAX_BULK_SCAN_DOMAINS: usize = 500;
+
+// Each domain already runs its checks in parallel, so only a few domains run at once
+const DEFAULT_BULK_SCAN_CONCURRENCY: usize = 4;
+
+/// Read the domains to scan from a CSV or plain-text file. A header row naming a
+/// domain, host or URL column picks that column; otherwise each row's first cell
+/// that looks like a domain is used. URLs are reduced to their host, duplicates
+/// dropped, and rows without a domain listed in `skipped`.
+#[tauri::command]
+pub async fn import_domains(path: String) -> Result<DomainImport, String> {
+    let bytes = tokio::fs::read(&path)
+        .await
+        .map_err(|e| format!("Failed to read {}: {}", path, e))?;
+    let text = String::from_utf8_lossy(&bytes);
+    let (found, mut skipped) = ReportAdapter::new().parse_domain_list(&text);
+
+    let mut domains: Vec<String> = Vec::new();
+    for domain in found {
+        match idn::to_ascii(&domain) {
+            Ok(ascii) => {
+                let domain = idn::to_unicode(&ascii.to_lowercase());
+                if !domains.contains(&domain) {
+                    domains.push(domain);
+                }
+            }
+            Err(e) => skipped.push(e),
+        }
+    }
+
+    if domains.is_empty() {
+        return Err(format!("No domains found in {}", path));
+    }
+    Ok(DomainImport { domains, skipped })
+}
+
+/// Run the chosen checks ("dns", "dnssec", "whois", "certificate", "http" and
+/// "email"; all of them by default) for every domain, a few domains at a time.
+/// A `bulk-scan-progress` event carries each domain's summary row as it completes;
+/// the result is the whole table, in the order the domains were given. Bulk scans
+/// aren't saved to the scan history.
+#[tauri::command]
+pub async fn scan_domains(
+    app_handle: AppHandle,
+    domains: Vec<String>,
+    checks: Option<Vec<String>>,
+    concurrency: Option<usize>,
+) -> Result<BulkScanSummary, String> {
+    let start = Instant::now();
+    let checks: Vec<&'static str> = match &checks {
+        Some(checks) if !checks.is_empty() => {
+            let mut chosen = Vec::new();
+            for check in checks {
+                let section = SECTIONS
+                    .iter()
+                    .find(|s| s.eq_ignore_ascii_case(check.trim()))
+                    .ok_or_else(|| format!("Unknown check: {}", check))?;
+                if !chosen.contains(section) {
+                    chosen.push(*section);
+                }
+            }
+            chosen
+        }
+        _ => SECTIONS.to_vec(),
+    };
+
+    let mut ascii: Vec<String> = Vec::new();
+    for domain in &domains {
+        let domain = domain.trim().trim_end_matches('.');
+        if domain.is_empty() {
+            continue;
+        }
+        let domain = idn::to_ascii(domain)?.to_lowercase();
+        if !ascii.contains(&domain) {
+            ascii.push(domain);
+        }
+    }
+    if ascii.is_empty() {
+        return Err("No domains to scan".to_string());
+    }
+    if ascii.len() > MAX_BULK_SCAN_DOMAINS {
+        return Err(format!(
+            "Too many domains: {} (at most {})",
+            ascii.len(),
+            MAX_BULK_SCAN_DOMAINS
+        ));
+    }
+
+    let handle = app_handle.clone();
+    let target = format!("{} domains", ascii.len());
+    jobs::run(&handle, "scan_domains", &target, move |job| async move {
+        let settings = state::settings_for(Some(&app_handle));
+        let concurrency = concurrency
+            .unwrap_or(DEFAULT_BULK_SCAN_CONCURRENCY)
+            .clamp(1, settings.max_concurrency.max(1));
+        let permits = Arc::new(Semaphore::new(concurrency));
+        let mut tasks = JoinSet::new();
+        for (index, domain) in ascii.iter().cloned().enumerate() {
+            let (handle, permits, checks) = (app_handle.clone(), permits.clone(), checks.clone());
+            tasks.spawn(async move {
+                let _permit = permits.acquire_owned().await;
+                let mut report =
+                    report::run_sections(&handle, &domain, &checks, |_, _, _, _| {}).await;
+                report.health = Some(HealthAdapter::with_app_handle(handle.clone()).score(&report));
+                (index, report)
+            });
+        }
+
+        let adapter = ReportAdapter::new();
+        let total = ascii.len();
+        let mut rows = Vec::new();
+        while let Some(joined) = tasks.join_next().await {
+            let Ok((index, report)) = joined else {
+                continue;
+            };
+            let mut row = adapter.summary_row(&report);
+            row.completed = rows.len() + 1;
+            row.total = total;
+            job.progress(row.completed, total, Some(row.domain.clone()));
+            let _ = app_handle.emit("bulk-scan-progress", row.clone());
+            rows.push((index, row));
+        }
+        rows.sort_by_key(|(index, _)| *index);
+        let rows: Vec<_> = rows.into_iter().map(|(_, row)| row).collect();
+
+        let failed = rows.iter().filter(|row| !row.errors.is_empty()).count();
+        Ok(BulkScanSummary {
+            checks: checks.iter().map(|c| c.to_string()).collect(),
+            total,
+            succeeded: rows.len() - failed,
+            failed,
+            rows,
+            total_time: start.elapsed().as_secs_f64(),
+        })
+    })
+    .await
+}
@@ -1,5 +1,8 @@
 use crate::adapters::certificate::CertificateAdapter;
-use crate::models::certificate::TlsInfo;
+use crate::commands::idn;
+use crate::models::certificate::{CtHistory, StartTls, TlsInfo, TlsPortScan};
+use crate::state;
+use crate::state::jobs;
 use tauri::AppHandle;
 
 #[tauri::command]
@@ -7,8 +10,53 @@ pub async fn get_certificate(
     app_handle: AppHandle,
     host: String,
     port: Option<u16>,
+    starttls: Option<StartTls>,
 ) -> Result<TlsInfo, String> {
     let adapter = CertificateAdapter::with_app_handle(app_handle);
-    let port = port.unwrap_or(443);
-    adapter.get_certificate_info(&host, port).await
+    let port = port.unwrap_or_else(|| starttls.map(|p| p.default_port()).unwrap_or(443));
+    let mut info = adapter
+        .get_certificate_info(&idn::to_ascii(&host)?, port, starttls)
+        .await?;
+    info.host = idn::to_unicode(&info.host);
+    Ok(info)
+}
+
+/// Certificates logged to Certificate Transparency for a domain, via crt.sh.
+/// With `include_subdomains`, matches every name under the domain as well.
+#[tauri::command]
+pub async fn lookup_ct_history(
+    app_handle: AppHandle,
+    domain: String,
+    include_subdomains: Option<bool>,
+) -> Result<CtHistory, String> {
+    let adapter = CertificateAdapter::with_app_handle(app_handle);
+    let domain = idn::to_ascii(domain.trim().trim_end_matches('.'))?;
+    let mut history = adapter
+        .lookup_ct_history(&domain, include_subdomains.unwrap_or(false))
+        .await?;
+    history.domain = idn::to_unicode(&history.domain);
+    Ok(history)
+}
+
+/// Check TLS on several ports of one host (the configured scan ports by default)
+/// and summarize each: reachable, handshake ok, certificate CN and expiry.
+#[tauri::command]
+pub async fn scan_tls_ports(
+    app_handle: AppHandle,
+    host: String,
+    ports: Option<Vec<u16>>,
+) -> Result<TlsPortScan, String> {
+    let ports = ports.unwrap_or_else(|| state::settings_for(Some(&app_handle)).tls_scan_ports);
+    if ports.is_empty() || ports.contains(&0) {
+        return Err("At least one port between 1 and 65535 is required".to_string());
+    }
+
+    let ascii = idn::to_ascii(&host)?;
+    let adapter = CertificateAdapter::with_app_handle(app_handle.clone());
+    jobs::run(&app_handle, "scan_tls_ports", &host, move |_| async move {
+        let mut scan = adapter.scan_tls_ports(&ascii, &ports).await;
+        scan.host = idn::to_unicode(&scan.host);
+        Ok(scan)
+    })
+    .await
 }
@@ -0,0 +1,146 @@
+use crate::adapters::dns::DnsAdapter;
+use crate::commands::idn;
+use crate::models::dns::{DelegationCheck, NameserverStatus};
+use tauri::AppHandle;
+
+/// Compare the NS set delegated by the parent zone with the NS set served by the zone.
+///
+/// The parent (registry) and child (zone) NS sets are maintained separately and
+/// frequently drift apart after a DNS provider migration. Resolvers may use either
+/// set, so a mismatch shows up as intermittent resolution failures.
+///
+/// 1. Ask a parent zone nameserver for the delegation (referral in AUTHORITY section)
+/// 2. Ask one of the delegated nameservers for the zone's own NS records
+/// 3. Report nameservers missing on either side
+/// 4. Ask every nameserver of either set for the zone's SOA and flag lame ones:
+///    a single nameserver that refuses, times out or answers without authority
+///    fails only the share of lookups that reach it, which is hard to spot
+#[tauri::command]
+pub async fn check_delegation(
+    app_handle: AppHandle,
+    domain: String,
+) -> Result<DelegationCheck, String> {
+    let adapter = DnsAdapter::with_app_handle(app_handle);
+    let domain = idn::to_ascii(&domain)?.trim_end_matches('.').to_lowercase();
+    let mut warnings: Vec<String> = Vec::new();
+
+    // Step 1: NS set published at the parent zone
+    let (parent_zone, parent_server) = adapter.get_parent_nameserver(&domain).await?;
+    let parent_response = adapter
+        .query_authoritative(&domain, "NS", &parent_server)
+        .await?;
+    let parent_nameservers = adapter.extract_ns_names(&parent_response.records, &domain);
+
+    if parent_nameservers.is_empty() {
+        warnings.push(format!(
+            "Parent zone {} returned no delegation for {}",
+            parent_zone, domain
+        ));
+    }
+
+    // Step 2: NS set served by the zone itself
+    // Try each delegated nameserver until one answers
+    let mut child_server: Option<String> = None;
+    let mut child_nameservers: Vec<String> = Vec::new();
+
+    for ns in &parent_nameservers {
+        match adapter.query_authoritative(&domain, "NS", ns).await {
+            Ok(response) => {
+                let names = adapter.extract_ns_names(&response.records, &domain);
+                if names.is_empty() {
+                    warnings.push(format!("{} returned no NS records for {}", ns, domain));
+                    continue;
+                }
+                child_server = Some(ns.clone());
+                child_nameservers = names;
+                break;
+            }
+            // Reported along with the other unresponsive nameservers in step 4
+            Err(_) => continue,
+        }
+    }
+
+    if child_server.is_none() && !parent_nameservers.is_empty() {
+        warnings.push("None of the delegated nameservers returned an NS set".to_string());
+    }
+
+    // Step 3: Compare both sets
+    let missing_from_child: Vec<String> = parent_nameservers
+        .iter()
+        .filter(|ns| child_server.is_some() && !child_nameservers.contains(ns))
+        .cloned()
+        .collect();
+    let missing_from_parent: Vec<String> = child_nameservers
+        .iter()
+        .filter(|ns| !parent_nameservers.contains(ns))
+        .cloned()
+        .collect();
+
+    for ns in &missing_from_child {
+        warnings.push(format!(
+            "{} is delegated at {} but not listed in the zone's NS records",
+            ns, parent_zone
+        ));
+    }
+    for ns in &missing_from_parent {
+        warnings.push(format!(
+            "{} is listed in the zone's NS records but not delegated at {}",
+            ns, parent_zone
+        ));
+    }
+
+    let is_consistent =
+        child_server.is_some() && missing_from_child.is_empty() && missing_from_parent.is_empty();
+
+    // Step 4: Every nameserver should answer authoritatively for the zone
+    let mut all_nameservers = parent_nameservers.clone();
+    all_nameservers.extend(missing_from_parent.iter().cloned());
+    let nameservers: Vec<_> = adapter
+        .query_each_nameserver(&domain, "SOA", &all_nameservers)
+        .await
+        .into_iter()
+        .map(|answer| adapter.classify_nameserver(&domain, answer))
+        .collect();
+
+    for health in &nameservers {
+        let ns = &health.nameserver;
+        match health.status {
+            NameserverStatus::Authoritative => {}
+            NameserverStatus::Lame => warnings.push(format!(
+                "{} is lame: it answered for {} without authority ({})",
+                ns,
+                domain,
+                health.rcode.as_deref().unwrap_or("no status")
+            )),
+            NameserverStatus::Refused => warnings.push(format!(
+                "{} refused queries for {}; it isn't serving the zone",
+                ns, domain
+            )),
+            NameserverStatus::ServFail => {
+                warnings.push(format!("{} returned SERVFAIL for {}", ns, domain))
+            }
+            NameserverStatus::Timeout => {
+                warnings.push(format!("{} did not answer queries for {}", ns, domain))
+            }
+            NameserverStatus::Unreachable => warnings.push(format!(
+                "Failed to query {}: {}",
+                ns,
+                health.error.as_deref().unwrap_or("unknown error")
+            )),
+        }
+    }
+
+    Ok(DelegationCheck {
+        domain: idn::to_unicode(&domain),
+        parent_zone,
+        parent_server,
+        parent_nameservers,
+        child_server,
+        child_nameservers,
+        missing_from_child,
+        missing_from_parent,
+        is_consistent,
+        nameservers,
+        warnings,
+    })
+}
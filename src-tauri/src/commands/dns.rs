@@ -1,15 +1,34 @@
-use crate::adapters::dns::DnsAdapter;
-use crate::models::dns::DnsResponse;
-use tauri::AppHandle;
+use crate::adapters::dns::{DnsAdapter, BENCHMARK_DOMAINS, BENCHMARK_RESOLVERS};
+use crate::adapters::email::EmailAdapter;
+use crate::commands::idn;
+use crate::models::dns::{
+    BenchmarkReport, DnsRecord, DnsResponse, FcrdnsReport, LoadBalancingReport, RecordEnumeration,
+};
+use crate::models::settings::IpVersion;
+use crate::state::{self, jobs, AppState};
+use std::net::IpAddr;
+use std::time::Instant;
+use tauri::{AppHandle, State};
+
+// Show record owner names in Unicode form (e.g., "xn--mnchen-3ya.de." -> "münchen.de.")
+fn display_names(records: &mut [DnsRecord]) {
+    for record in records.iter_mut() {
+        record.name = idn::to_unicode(&record.name);
+    }
+}
 
 #[tauri::command]
 pub async fn query_dns(
     app_handle: AppHandle,
     domain: String,
     record_type: String,
+    ip_version: Option<IpVersion>,
 ) -> Result<DnsResponse, String> {
-    let adapter = DnsAdapter::with_app_handle(app_handle);
-    adapter.query(&domain, &record_type).await
+    let adapter = DnsAdapter::with_app_handle(app_handle).with_ip_version(ip_version);
+    let domain = idn::to_ascii(&domain)?;
+    let mut response = adapter.query(&domain, &record_type).await?;
+    display_names(&mut response.records);
+    Ok(response)
 }
 
 #[tauri::command]
@@ -17,8 +36,230 @@ pub async fn query_dns_multiple(
     app_handle: AppHandle,
     domain: String,
     record_types: Vec<String>,
+    ip_version: Option<IpVersion>,
 ) -> Result<Vec<DnsResponse>, String> {
-    let adapter = DnsAdapter::with_app_handle(app_handle);
+    let adapter = DnsAdapter::with_app_handle(app_handle).with_ip_version(ip_version);
+    let domain = idn::to_ascii(&domain)?;
     let types: Vec<&str> = record_types.iter().map(|s| s.as_str()).collect();
-    adapter.query_multiple(&domain, types).await
+    let mut responses = adapter.query_multiple(&domain, types).await?;
+    for response in responses.iter_mut() {
+        display_names(&mut response.records);
+    }
+    Ok(responses)
+}
+
+#[tauri::command]
+pub async fn enumerate_records(
+    app_handle: AppHandle,
+    domain: String,
+) -> Result<RecordEnumeration, String> {
+    let adapter = DnsAdapter::with_app_handle(app_handle);
+    let mut enumeration = adapter.enumerate_records(&idn::to_ascii(&domain)?).await;
+    enumeration.domain = idn::to_unicode(&enumeration.domain);
+    for result in enumeration.results.iter_mut() {
+        display_names(&mut result.records);
+    }
+    Ok(enumeration)
+}
+
+// The resolvers given, or the system resolver, Cloudflare, Google, and Quad9 plus
+// the DoH endpoints in the settings
+fn resolvers_or_default(app_handle: &AppHandle, resolvers: Option<Vec<String>>) -> Vec<String> {
+    match resolvers {
+        Some(list) if !list.is_empty() => list.iter().map(|r| r.trim().to_string()).collect(),
+        _ => BENCHMARK_RESOLVERS
+            .iter()
+            .map(|r| r.to_string())
+            .chain(state::settings_for(Some(app_handle)).doh_endpoints)
+            .collect(),
+    }
+}
+
+// Compare latency and failure rates across resolvers
+// Defaults to the system resolver, Cloudflare, Google, and Quad9, plus the DoH
+// endpoints in the settings
+#[tauri::command]
+pub async fn benchmark_resolvers(
+    app_handle: AppHandle,
+    resolvers: Option<Vec<String>>,
+    domains: Option<Vec<String>>,
+    rounds: Option<u32>,
+) -> Result<BenchmarkReport, String> {
+    let adapter = DnsAdapter::with_app_handle(app_handle.clone());
+
+    let resolvers = resolvers_or_default(&app_handle, resolvers);
+
+    if let Some(invalid) = resolvers.iter().find(|r| !adapter.is_valid_resolver(r)) {
+        return Err(format!("Invalid resolver: {}", invalid));
+    }
+
+    let domains: Vec<String> = match domains {
+        Some(list) if !list.is_empty() => list
+            .iter()
+            .map(|d| idn::to_ascii(d))
+            .collect::<Result<_, _>>()?,
+        _ => BENCHMARK_DOMAINS.iter().map(|d| d.to_string()).collect(),
+    };
+
+    let rounds = rounds.unwrap_or(2);
+    if !(1..=10).contains(&rounds) {
+        return Err("Rounds must be between 1 and 10".to_string());
+    }
+
+    let target = format!("{} resolvers", resolvers.len());
+    jobs::run(
+        &app_handle,
+        "benchmark_resolvers",
+        &target,
+        move |_| async move {
+            Ok(adapter
+                .benchmark_resolvers(&resolvers, &domains, rounds)
+                .await)
+        },
+    )
+    .await
+}
+
+/// Ask several resolvers for a name's addresses a few times over, to tell
+/// intentional load balancing from a problem when "the IP keeps changing":
+/// round-robin rotation, answers that change per query, and answers that depend on
+/// which resolver (and so which location) asks. Uses the same default resolvers as
+/// benchmark_resolvers.
+#[tauri::command]
+pub async fn detect_load_balancing(
+    app_handle: AppHandle,
+    domain: String,
+    resolvers: Option<Vec<String>>,
+    rounds: Option<u32>,
+) -> Result<LoadBalancingReport, String> {
+    let adapter = DnsAdapter::with_app_handle(app_handle.clone());
+    let ascii = idn::to_ascii(domain.trim().trim_end_matches('.'))?;
+
+    let resolvers = resolvers_or_default(&app_handle, resolvers);
+    if let Some(invalid) = resolvers.iter().find(|r| !adapter.is_valid_resolver(r)) {
+        return Err(format!("Invalid resolver: {}", invalid));
+    }
+
+    let rounds = rounds.unwrap_or(4);
+    if !(2..=10).contains(&rounds) {
+        return Err("Rounds must be between 2 and 10".to_string());
+    }
+
+    jobs::run(
+        &app_handle,
+        "detect_load_balancing",
+        &domain,
+        move |job| async move {
+            let start = Instant::now();
+            let samples = adapter
+                .sample_addresses(&ascii, &resolvers, rounds, |round| {
+                    job.progress(round as usize, rounds as usize, None)
+                })
+                .await;
+            let mut report = adapter.analyze_load_balancing(&ascii, &resolvers, rounds, samples);
+            report.domain = idn::to_unicode(&report.domain);
+            report.total_time = start.elapsed().as_secs_f64();
+            Ok(report)
+        },
+    )
+    .await
+}
+
+/// Forward-confirmed reverse DNS (FCrDNS) for a domain's web and mail addresses:
+/// each address's PTR name must resolve back to the address. Many mail providers
+/// reject or spam-folder mail from servers that fail it.
+///
+/// 1. Resolve the domain's A and AAAA records, and those of its MX hosts
+/// 2. Look up the PTR names of every address
+/// 3. Resolve each PTR name and check it points back at the address
+#[tauri::command]
+pub async fn check_fcrdns(app_handle: AppHandle, domain: String) -> Result<FcrdnsReport, String> {
+    let adapter = DnsAdapter::with_app_handle(app_handle);
+    let domain = idn::to_ascii(&domain)?.trim_end_matches('.').to_lowercase();
+    let mut warnings = Vec::new();
+
+    // Step 1: Web and mail addresses, and the names that lead to them
+    let mx_hosts = match adapter.query(&domain, "MX").await {
+        Ok(response) => EmailAdapter::new().parse_mx(&response.records),
+        Err(e) => {
+            warnings.push(format!("Failed to look up MX records: {}", e));
+            Vec::new()
+        }
+    };
+    let mut hosts: Vec<(String, bool)> = vec![(domain.clone(), false)];
+    hosts.extend(mx_hosts.into_iter().map(|mx| (mx.hostname, true)));
+
+    let mut addresses: Vec<(IpAddr, Vec<String>, bool)> = Vec::new();
+    for (host, mail) in &hosts {
+        let resolved = match adapter.addresses(host).await {
+            Ok(resolved) => resolved,
+            // A domain with no address of its own is fine when it has mail hosts
+            Err(_) if !mail && hosts.len() > 1 => continue,
+            Err(e) => {
+                warnings.push(e);
+                continue;
+            }
+        };
+        for address in resolved {
+            match addresses.iter_mut().find(|(known, _, _)| *known == address) {
+                Some((_, names, is_mail)) => {
+                    if !names.contains(host) {
+                        names.push(host.clone());
+                    }
+                    *is_mail |= mail;
+                }
+                None => addresses.push((address, vec![host.clone()], *mail)),
+            }
+        }
+    }
+
+    // Steps 2 and 3: PTR names, resolved back
+    let ips: Vec<IpAddr> = addresses.iter().map(|(address, _, _)| *address).collect();
+    let mut checks = adapter.forward_confirm_each(&ips).await;
+    for (check, (_, names, mail)) in checks.iter_mut().zip(addresses) {
+        check.hosts = names.iter().map(|name| idn::to_unicode(name)).collect();
+        check.mail = mail;
+
+        let label = format!("{} ({})", check.address, check.hosts.join(", "));
+        let consequence = if check.mail {
+            "; many mail providers reject or spam-folder mail sent from it"
+        } else {
+            ""
+        };
+        if check.confirmed {
+            continue;
+        }
+        if check.ptr_names.is_empty() {
+            let detail = check
+                .error
+                .as_ref()
+                .map(|e| format!(" ({})", e))
+                .unwrap_or_default();
+            warnings.push(format!(
+                "{} has no PTR record{}{}",
+                label, detail, consequence
+            ));
+        } else {
+            let names: Vec<&str> = check.ptr_names.iter().map(|p| p.name.as_str()).collect();
+            warnings.push(format!(
+                "{} reverses to {}, which doesn't resolve back to it{}",
+                label,
+                names.join(", "),
+                consequence
+            ));
+        }
+    }
+
+    Ok(FcrdnsReport {
+        domain: idn::to_unicode(&domain),
+        all_confirmed: !checks.is_empty() && checks.iter().all(|check| check.confirmed),
+        checks,
+        warnings,
+    })
+}
+
+// Forget every cached DNS answer, returning how many entries were dropped
+#[tauri::command]
+pub async fn clear_dns_cache(state: State<'_, AppState>) -> Result<usize, String> {
+    Ok(state.dns_cache.clear())
 }
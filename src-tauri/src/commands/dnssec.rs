@@ -1,7 +1,14 @@
 use crate::adapters::dns::DnsAdapter;
-use crate::models::dns::{DnssecValidation, ZoneData};
+use crate::commands::idn;
+use crate::models::dns::{DnssecProgress, DnssecValidation, RrsetValidation, ZoneData};
+use crate::models::settings::Settings;
+use crate::state;
+use crate::state::jobs::{self, JobHandle};
+use chrono::Utc;
 use std::collections::HashSet;
-use tauri::AppHandle;
+use std::time::Instant;
+use tauri::{AppHandle, Emitter};
+use tracing::instrument;
 
 /// Validate DNSSEC chain of trust for a domain.
 ///
@@ -31,9 +38,10 @@ use tauri::AppHandle;
 ///
 /// **Why queries are sequential:**
 /// The queries MUST be performed sequentially because each level depends on the previous:
-/// - Root DS records contain key tags pointing to TLD DNSKEYs
-/// - TLD DS records contain key tags pointing to domain DNSKEYs
-/// - We verify the chain by matching DS key tags with DNSKEY key tags
+/// - Root DS records contain digests of TLD DNSKEYs
+/// - TLD DS records contain digests of domain DNSKEYs
+/// - We verify the chain by hashing each child DNSKEY (SHA-1/SHA-256/SHA-384) and
+///   comparing it with the parent's DS digest; key tags alone can collide
 /// - A valid chain means: DS(parent) → DNSKEY(child) at each level
 ///
 /// **Parallelization:**
@@ -41,6 +49,10 @@ use tauri::AppHandle;
 /// certificates, HTTP) in the UI, but is typically the slowest operation. This is
 /// expected and unavoidable for proper DNSSEC validation.
 ///
+/// **Progress:**
+/// A `dnssec-progress` event is emitted before each query (zone, step, elapsed) so the
+/// UI can show the chain being built instead of a spinner for 10-15 seconds.
+///
 /// **Key Tag Extraction:**
 /// We use `dig +multi` format to extract real key tags from comments in the output
 /// (e.g., "; key id = 5116"). Key tags are NOT the same as flags (256/257).
@@ -49,13 +61,64 @@ pub async fn validate_dnssec(
     app_handle: AppHandle,
     domain: String,
 ) -> Result<DnssecValidation, String> {
-    let adapter = DnsAdapter::with_app_handle(app_handle);
+    let handle = app_handle.clone();
+    let target = domain.clone();
+    jobs::run(&handle, "validate_dnssec", &target, move |job| {
+        validate_with_progress(app_handle, domain, job)
+    })
+    .await
+}
+
+// validate_dnssec without a job of its own, for commands that validate as one of
+// their steps; progress goes to dnssec-progress events and to job
+pub async fn validate_with_progress(
+    app_handle: AppHandle,
+    domain: String,
+    job: JobHandle,
+) -> Result<DnssecValidation, String> {
+    let adapter = DnsAdapter::with_app_handle(app_handle.clone());
+    let settings = state::settings_for(Some(&app_handle));
+    validate_chain(&adapter, &domain, &settings, |progress| {
+        job.progress(
+            progress.zone_index,
+            progress.zone_count,
+            Some(format!("{} {}", progress.zone, progress.step)),
+        );
+        let _ = app_handle.emit("dnssec-progress", progress);
+    })
+    .await
+}
+
+// Build and check the chain for validate_dnssec, handing each progress step to
+// on_progress; the CLI calls this directly, without an AppHandle
+#[instrument(skip_all, fields(domain = %domain))]
+pub async fn validate_chain(
+    adapter: &DnsAdapter,
+    domain: &str,
+    settings: &Settings,
+    on_progress: impl Fn(DnssecProgress),
+) -> Result<DnssecValidation, String> {
+    let start = Instant::now();
+    let domain = idn::to_ascii(domain)?;
     let mut chain: Vec<ZoneData> = Vec::new();
     let mut warnings: Vec<String> = Vec::new();
 
     // Parse domain parts (e.g., "www.example.com" -> ["www", "example", "com"])
     let parts: Vec<&str> = domain.trim_end_matches('.').split('.').collect();
 
+    // Root plus one zone per label
+    let zone_count = parts.len() + 1;
+    let emit_progress = |zone: &str, step: &str, zone_index: usize| {
+        on_progress(DnssecProgress {
+            domain: domain.clone(),
+            zone: zone.to_string(),
+            step: step.to_string(),
+            zone_index,
+            zone_count,
+            elapsed: start.elapsed().as_secs_f64(),
+        });
+    };
+
     // ========================================================================
     // Build complete DNSSEC chain: root → TLD → domain → subdomain(s)
     // ========================================================================
@@ -71,11 +134,13 @@ pub async fn validate_dnssec(
     // ========================================================================
     // Step 1: Query root zone (.)
     // ========================================================================
-    // The root zone is the trust anchor for all DNSSEC validation.
+    // The root zone is the start of all DNSSEC validation. Its KSK is checked against
+    // the configured trust anchors (bundled IANA root anchors by default) in Step 2c.
     // Root servers are slow (~1-2s per query) but necessary for a complete chain.
     // We query:
     //   1. Root DNSKEY records (the trust anchor)
     //   2. DS records for the TLD (points to TLD's DNSKEY)
+    emit_progress(".", "dnskey", 1);
     match adapter.query_dnskey(".").await {
         Ok(root_response) => {
             let root_dnskeys = adapter.parse_dnskey_records(&root_response.records);
@@ -84,6 +149,7 @@ pub async fn validate_dnssec(
             // Query DS records for TLD from root
             // Example: For "meat.io", query DS records for "io" from root nameservers
             let tld = parts.last().unwrap_or(&"");
+            emit_progress(".", "ds", 1);
             let root_ds = match adapter.query_ds(tld).await {
                 Ok(ds_response) => adapter.parse_ds_records(&ds_response.records),
                 Err(e) => {
@@ -100,6 +166,7 @@ pub async fn validate_dnssec(
                 dnskey_records: root_dnskeys,
                 ds_records: root_ds, // Points to TLD's DNSKEYs
                 rrsig_records: root_rrsigs,
+                ..Default::default()
             });
         }
         Err(e) => {
@@ -116,7 +183,7 @@ pub async fn validate_dnssec(
     // At each level:
     //   1. Query DNSKEY records for the current zone
     //   2. Query DS records for the child zone (if it exists)
-    //   3. Match DS key tags from parent to DNSKEY key tags in current zone
+    //   3. Verify the parent's DS digests against the DNSKEYs in current zone
     for i in (0..parts.len()).rev() {
         let current_zone = parts[i..].join(".");
         let child_zone = if i > 0 {
@@ -125,14 +192,29 @@ pub async fn validate_dnssec(
             None
         };
 
+        let zone_index = parts.len() - i + 1;
+        emit_progress(&current_zone, "dnskey", zone_index);
+
         match adapter.query_dnskey(&current_zone).await {
             Ok(zone_response) => {
                 let zone_dnskeys = adapter.parse_dnskey_records(&zone_response.records);
                 let zone_rrsigs = adapter.parse_rrsig_records(&zone_response.records);
 
+                // Verify the parent's DS digests against this zone's DNSKEYs
+                // Example: For "meat.io", hash meat.io's DNSKEYs and compare with io's DS records
+                let parent_name = if i + 1 < parts.len() {
+                    parts[i + 1..].join(".")
+                } else {
+                    ".".to_string()
+                };
+                if let Some(parent) = chain.iter_mut().find(|z| z.zone_name == parent_name) {
+                    adapter.verify_ds_records(&current_zone, &mut parent.ds_records, &zone_dnskeys);
+                }
+
                 // Query DS records for child zone (if exists)
                 // Example: For "io" zone, query DS records for "meat.io"
                 let zone_ds = if let Some(ref child) = child_zone {
+                    emit_progress(&current_zone, "ds", zone_index);
                     match adapter.query_ds(child).await {
                         Ok(ds_response) => adapter.parse_ds_records(&ds_response.records),
                         Err(e) => {
@@ -162,6 +244,7 @@ pub async fn validate_dnssec(
                     dnskey_records: zone_dnskeys,
                     ds_records: zone_ds, // Points to child zone's DNSKEYs
                     rrsig_records: zone_rrsigs,
+                    ..Default::default()
                 });
             }
             Err(e) => {
@@ -173,13 +256,70 @@ pub async fn validate_dnssec(
         }
     }
 
+    // ========================================================================
+    // Step 2b: Key rollover state (CDS/CDNSKEY)
+    // ========================================================================
+    // Signed zones publish CDS/CDNSKEY to ask the parent for a new DS set.
+    // Comparing them with the parent's DS records shows rollovers in progress.
+    let mut rollover = None;
+    let target_dnskeys = chain
+        .iter()
+        .find(|z| z.zone_name == domain)
+        .map(|z| z.dnskey_records.clone())
+        .unwrap_or_default();
+
+    if !target_dnskeys.is_empty() {
+        emit_progress(&domain, "cds", zone_count);
+        match adapter.query_rollover_records(&domain).await {
+            Ok((cds, cdnskey)) => {
+                let parent_name = if parts.len() > 1 {
+                    parts[1..].join(".")
+                } else {
+                    ".".to_string()
+                };
+                let parent_ds = chain
+                    .iter()
+                    .find(|z| z.zone_name == parent_name)
+                    .map(|z| z.ds_records.clone())
+                    .unwrap_or_default();
+
+                let (status, rollover_warnings) =
+                    adapter.evaluate_rollover(&domain, cds, cdnskey, &parent_ds, &target_dnskeys);
+                warnings.extend(rollover_warnings);
+                rollover = Some(status);
+            }
+            Err(e) => {
+                warnings.push(format!("Failed to query CDS/CDNSKEY for {}: {}", domain, e));
+            }
+        }
+    }
+
+    emit_progress(&domain, "evaluating", zone_count);
+
+    // ========================================================================
+    // Step 2c: Link the chain and check trust anchors
+    // ========================================================================
+    // DS → DNSKEY and RRSIG → DNSKEY links, and a status for each zone
+    // The root KSK is checked against the configured trust anchors, not trusted as served
+    warnings.extend(adapter.link_chain(&mut chain, &settings.trust_anchors, Utc::now()));
+
+    // Weak algorithms, short RSA keys, and SHA-1 digests anywhere in the chain
+    warnings.extend(adapter.algorithm_warnings(&chain));
+
+    // Signatures that are expired or about to expire
+    warnings.extend(adapter.rrsig_expiry_warnings(
+        &chain,
+        Utc::now(),
+        settings.rrsig_expiry_warning_days,
+    ));
+
     // ========================================================================
     // Step 3: Determine validation status
     // ========================================================================
     // Status is based on:
-    //   - SECURE: Domain has DNSKEY, parent has matching DS records
+    //   - SECURE: Domain has DNSKEY, a parent DS digest matches one of them
     //   - INSECURE: Domain has no DNSKEY (not signed)
-    //   - BOGUS: Domain has DNSKEY, but DS key tags don't match DNSKEY key tags
+    //   - BOGUS: Domain has DNSKEY, but no DS digest matches any DNSKEY
     //   - INDETERMINATE: Unable to determine (query failures)
 
     let target_zone = chain.iter().find(|z| z.zone_name == domain);
@@ -204,7 +344,7 @@ pub async fn validate_dnssec(
         // No DNSKEY records = domain is not DNSSEC signed
         "INSECURE".to_string()
     } else if has_dnskey && has_ds {
-        // Both DNSKEY and DS exist - verify key tags match
+        // Both DNSKEY and DS exist - verify a DS digest matches one of the DNSKEYs
         if let (Some(target), Some(parent)) = (target_zone, parent_zone) {
             let ds_keytags: HashSet<u16> = parent.ds_records.iter().map(|ds| ds.key_tag).collect();
             let dnskey_keytags: HashSet<u16> = target
@@ -212,10 +352,39 @@ pub async fn validate_dnssec(
                 .iter()
                 .map(|key| key.key_tag)
                 .collect();
+            let tags_match = ds_keytags.iter().any(|tag| dnskey_keytags.contains(tag));
 
-            // Check if any DS key tag matches any DNSKEY key tag
-            if ds_keytags.iter().any(|tag| dnskey_keytags.contains(tag)) {
+            if parent
+                .ds_records
+                .iter()
+                .any(|ds| ds.digest_verified == Some(true))
+            {
                 "SECURE".to_string()
+            } else if parent
+                .ds_records
+                .iter()
+                .all(|ds| ds.digest_verified.is_none())
+            {
+                // Only unsupported digest types - key tags are the best we can do
+                warnings.push(
+                    "DS digest types are unsupported, falling back to key tag comparison"
+                        .to_string(),
+                );
+                if tags_match {
+                    "SECURE".to_string()
+                } else {
+                    warnings.push(format!(
+                        "DS key tags {:?} don't match DNSKEY tags {:?}",
+                        ds_keytags, dnskey_keytags
+                    ));
+                    "BOGUS".to_string()
+                }
+            } else if tags_match {
+                warnings.push(format!(
+                    "DS key tags {:?} match DNSKEY tags but no DS digest matches a DNSKEY (key tag collision or stale DS)",
+                    ds_keytags
+                ));
+                "BOGUS".to_string()
             } else {
                 warnings.push(format!(
                     "DS key tags {:?} don't match DNSKEY tags {:?}",
@@ -234,9 +403,116 @@ pub async fn validate_dnssec(
         "INDETERMINATE".to_string()
     };
 
+    // Nothing below a root that fails its trust anchor can be trusted
+    let root_bogus = chain
+        .first()
+        .is_some_and(|z| z.zone_name == "." && z.status == "BOGUS");
+    let status = if root_bogus && status == "SECURE" {
+        "BOGUS".to_string()
+    } else {
+        status
+    };
+
+    emit_progress(&domain, "complete", zone_count);
+
     Ok(DnssecValidation {
         status,
         chain,
         warnings,
+        rollover,
+    })
+}
+
+/// Validate DNSSEC coverage of a specific RRset (A, AAAA, MX, TXT, ...).
+///
+/// Finds the zone that holds the name, validates the chain of trust down to that zone
+/// with `validate_dnssec`, then fetches the RRset and its RRSIGs from one of the zone's
+/// nameservers. The RRset is SECURE when the zone is SECURE and at least one RRSIG over
+/// it is made by the zone, inside its validity window, and matches a zone DNSKEY by
+/// key tag and algorithm. An INSECURE/BOGUS zone caps the status of every RRset in it.
+#[tauri::command]
+pub async fn validate_record(
+    app_handle: AppHandle,
+    domain: String,
+    record_type: String,
+) -> Result<RrsetValidation, String> {
+    let adapter = DnsAdapter::with_app_handle(app_handle.clone());
+    let domain = idn::to_ascii(&domain)?;
+    let record_type = record_type.trim().to_uppercase();
+
+    if record_type.is_empty() || record_type == "RRSIG" {
+        return Err(format!("Invalid record type: {}", record_type));
+    }
+
+    let (zone, nameservers) = adapter.find_zone(&domain).await?;
+    let chain =
+        validate_with_progress(app_handle.clone(), zone.clone(), JobHandle::detached()).await?;
+    let mut warnings = Vec::new();
+
+    let nameserver = nameservers
+        .first()
+        .ok_or_else(|| format!("No nameservers found for {}", zone))?;
+    let response = adapter
+        .query_signed_rrset(&domain, &record_type, nameserver)
+        .await?;
+
+    let records: Vec<_> = response
+        .records
+        .iter()
+        .filter(|r| r.record_type.eq_ignore_ascii_case(&record_type))
+        .cloned()
+        .collect();
+
+    let dnskeys = chain
+        .chain
+        .iter()
+        .find(|z| z.zone_name == zone)
+        .map(|z| z.dnskey_records.clone())
+        .unwrap_or_default();
+
+    let (rrsig_records, signing_key_tags, signature_warnings) = adapter.evaluate_rrset_signatures(
+        &domain,
+        &record_type,
+        &zone,
+        &response.records,
+        &dnskeys,
+        Utc::now(),
+    );
+
+    let status = if chain.status != "SECURE" {
+        warnings.push(format!(
+            "Zone {} is {}, so its records can't be validated",
+            zone, chain.status
+        ));
+        chain.status.clone()
+    } else if records.is_empty() {
+        if response.records.iter().any(|r| r.record_type == "CNAME") {
+            warnings.push(format!(
+                "{} is a CNAME; validate the {} record of its target instead",
+                domain, record_type
+            ));
+        } else {
+            warnings.push(format!("No {} records found for {}", record_type, domain));
+        }
+        "INDETERMINATE".to_string()
+    } else {
+        warnings.extend(signature_warnings);
+        if signing_key_tags.is_empty() {
+            "BOGUS".to_string()
+        } else {
+            "SECURE".to_string()
+        }
+    };
+
+    Ok(RrsetValidation {
+        domain,
+        record_type,
+        zone,
+        status,
+        records,
+        rrsig_records,
+        signing_key_tags,
+        chain,
+        warnings,
     })
 }
@@ -0,0 +1,114 @@
+use crate::adapters::email::EmailAdapter;
+use crate::commands::idn;
+use crate::models::email::{
+    AutodiscoveryReport, BlacklistReport, DkimVerification, DmarcAggregateReport, EmailConfig,
+};
+use crate::models::settings::is_dkim_selector;
+use tauri::AppHandle;
+
+/// Check whether inbound mail for a domain can be delivered encrypted, which
+/// servers its SPF policy authorizes to send, and what its DMARC policy enforces.
+///
+/// 1. Look up the MX hosts, in the order senders try them; without any, the domain
+///    itself is the implicit MX unless it publishes a null MX ("0 .")
+/// 2. Connect to port 25 of each one and upgrade with STARTTLS
+/// 3. Verify the presented certificate: chain, hostname and expiry
+///    and, when the host publishes TLSA records, DANE against them
+/// 4. Expand the SPF record through its includes and count DNS lookups, stopping
+///    where receivers do, one past the limit of 10
+/// 5. Look for DKIM keys under the given selectors, those of the mail provider
+///    recognized from the MX hosts, and common ones; every selector tried is returned
+/// 6. Parse the DMARC policy and check that external report destinations accept
+///    the domain's reports
+/// 7. Recognize the mail and sending providers behind the MX, SPF and DKIM records
+///    and report records those providers ask for that are missing or outdated
+#[tauri::command]
+pub async fn fetch_email_config(
+    app_handle: AppHandle,
+    domain: String,
+    selectors: Option<Vec<String>>,
+) -> Result<EmailConfig, String> {
+    let adapter = EmailAdapter::with_app_handle(app_handle);
+    let domain = idn::to_ascii(&domain)?.trim_end_matches('.').to_lowercase();
+
+    let selectors = selectors.unwrap_or_default();
+    if let Some(invalid) = selectors.iter().find(|s| !is_dkim_selector(s)) {
+        return Err(format!("Invalid DKIM selector: {}", invalid));
+    }
+
+    let mut config = adapter.fetch_email_config(&domain, &selectors).await?;
+    config.domain = idn::to_unicode(&config.domain);
+    Ok(config)
+}
+
+/// Check the domain's mail servers and the domain itself against DNS blocklists.
+///
+/// 1. Resolve the MX hosts to their addresses
+/// 2. Look each address up on the IP blocklists from settings (reversed, e.g.
+///    2.0.0.127.zen.spamhaus.org) and the domain on the domain blocklists
+/// 3. Report each list's answer; a 127.0.0.x code means listed
+#[tauri::command]
+pub async fn check_blacklists(
+    app_handle: AppHandle,
+    domain: String,
+) -> Result<BlacklistReport, String> {
+    let adapter = EmailAdapter::with_app_handle(app_handle);
+    let domain = idn::to_ascii(&domain)?.trim_end_matches('.').to_lowercase();
+
+    let mut report = adapter.check_blacklists(&domain).await;
+    report.domain = idn::to_unicode(&report.domain);
+    Ok(report)
+}
+
+/// Summarize a DMARC aggregate report file (.xml, .xml.gz or .zip) by source IP:
+/// how many messages each sent and how many passed SPF or DKIM alignment.
+#[tauri::command]
+pub async fn analyze_dmarc_report(
+    app_handle: AppHandle,
+    path: String,
+) -> Result<DmarcAggregateReport, String> {
+    let adapter = EmailAdapter::with_app_handle(app_handle);
+    adapter.analyze_dmarc_report(&path).await
+}
+
+/// Check how mail clients can configure themselves for the domain: SRV records
+/// for IMAP, POP3 and submission, autoconfig files and the autodiscover endpoint.
+#[tauri::command]
+pub async fn check_autodiscovery(
+    app_handle: AppHandle,
+    domain: String,
+) -> Result<AutodiscoveryReport, String> {
+    let adapter = EmailAdapter::with_app_handle(app_handle);
+    let domain = idn::to_ascii(&domain)?.trim_end_matches('.').to_lowercase();
+
+    let mut report = adapter.check_autodiscovery(&domain).await;
+    report.domain = idn::to_unicode(&report.domain);
+    Ok(report)
+}
+
+/// Verify the DKIM signatures of a message, given as raw headers (with or
+/// without the body) or as the path of an .eml file: which header fields each
+/// signature covers, whether the selector's published key verifies it and, if
+/// not, why.
+#[tauri::command]
+pub async fn verify_dkim(
+    app_handle: AppHandle,
+    message: Option<String>,
+    path: Option<String>,
+) -> Result<DkimVerification, String> {
+    let adapter = EmailAdapter::with_app_handle(app_handle);
+    let message = match (message, path) {
+        (Some(message), _) if !message.trim().is_empty() => message,
+        (_, Some(path)) => {
+            let bytes = tokio::fs::read(&path)
+                .await
+                .map_err(|e| format!("Failed to read {}: {}", path, e))?;
+            String::from_utf8_lossy(&bytes).into_owned()
+        }
+        _ => return Err("Paste the message headers or choose an .eml file".to_string()),
+    };
+
+    let mut verification = adapter.verify_dkim(&message).await?;
+    verification.from_domain = verification.from_domain.map(|d| idn::to_unicode(&d));
+    Ok(verification)
+}
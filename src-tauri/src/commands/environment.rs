@@ -0,0 +1,21 @@
+use crate::adapters::dns_tools::DnsProcess;
+use crate::adapters::environment::EnvironmentAdapter;
+use crate::adapters::traceroute::TraceProcess;
+use crate::models::environment::EnvironmentReport;
+use crate::state::AppState;
+use tauri::State;
+
+/// Which external tools (dig, drill, kdig, whois, openssl, curl) are installed, with
+/// their paths and versions, and which features won't work without the missing ones.
+/// DNS and traceroute tools installed since the app started are used from then on.
+#[tauri::command]
+pub async fn environment_doctor(state: State<'_, AppState>) -> Result<EnvironmentReport, String> {
+    let report = EnvironmentAdapter::new().check().await;
+    state.set_backends(
+        state
+            .backends()
+            .with_dns(DnsProcess::locate())
+            .with_trace(TraceProcess::locate()),
+    );
+    Ok(report)
+}
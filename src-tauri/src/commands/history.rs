@@ -0,0 +1,72 @@
+use crate::adapters::report::ReportAdapter;
+use crate::commands::{idn, report};
+use crate::models::history::{ScanDiff, ScanSummary, StoredScan};
+use crate::state::AppState;
+use tauri::{AppHandle, State};
+
+const DEFAULT_HISTORY_LIMIT: u32 = 100;
+
+/// Saved analyze_domain runs, newest first; all domains unless one is given.
+#[tauri::command]
+pub async fn list_history(
+    state: State<'_, AppState>,
+    domain: Option<String>,
+    limit: Option<u32>,
+) -> Result<Vec<ScanSummary>, String> {
+    // Reports are saved under the Unicode form of the domain
+    let domain = match domain {
+        Some(domain) if !domain.trim().is_empty() => {
+            let ascii = idn::to_ascii(domain.trim().trim_end_matches('.'))?.to_lowercase();
+            Some(idn::to_unicode(&ascii))
+        }
+        _ => None,
+    };
+    state
+        .history
+        .list(domain.as_deref(), limit.unwrap_or(DEFAULT_HISTORY_LIMIT))
+}
+
+/// The full report of a saved scan.
+#[tauri::command]
+pub async fn get_scan(state: State<'_, AppState>, id: i64) -> Result<StoredScan, String> {
+    state
+        .history
+        .get(id)?
+        .ok_or_else(|| format!("No saved scan with ID {}", id))
+}
+
+/// Remove a saved scan from the history.
+#[tauri::command]
+pub async fn delete_scan(state: State<'_, AppState>, id: i64) -> Result<(), String> {
+    if state.history.delete(id)? {
+        Ok(())
+    } else {
+        Err(format!("No saved scan with ID {}", id))
+    }
+}
+
+/// What changed between two saved scans, or between a saved scan and a fresh one when
+/// to_id is omitted. The fresh scan is saved to the history like any other.
+#[tauri::command]
+pub async fn diff_scans(
+    app_handle: AppHandle,
+    state: State<'_, AppState>,
+    from_id: i64,
+    to_id: Option<i64>,
+) -> Result<ScanDiff, String> {
+    let from = get_scan(state.clone(), from_id).await?;
+    let to = match to_id {
+        Some(id) => {
+            let to = get_scan(state, id).await?;
+            if to.domain != from.domain {
+                return Err(format!(
+                    "Scan {} is of {} but scan {} is of {}",
+                    from_id, from.domain, id, to.domain
+                ));
+            }
+            to.report
+        }
+        None => report::analyze_domain(app_handle, from.domain.clone()).await?,
+    };
+    Ok(ReportAdapter::new().diff(&from.report, &to))
+}
@@ -1,9 +1,81 @@
 use crate::adapters::http::HttpAdapter;
-use crate::models::http::HttpResponse;
+use crate::commands::idn;
+use crate::models::http::{
+    CanonicalReport, HstsReport, HttpRequest, HttpResponse, WellKnownReport,
+};
+use std::collections::HashMap;
 use tauri::AppHandle;
 
+/// Request a URL and follow its redirects. A bare HEAD by default; a method,
+/// extra headers (replacing defaults such as User-Agent or Host) and a body can
+/// be given to reproduce API calls or test endpoints that answer HEAD with 405.
+/// With max_body_kb (defaulting to GET), the final body is captured up to that
+/// size, along with its content type, charset and any meta refresh redirect.
 #[tauri::command]
-pub async fn fetch_http(app_handle: AppHandle, url: String) -> Result<HttpResponse, String> {
+pub async fn fetch_http(
+    app_handle: AppHandle,
+    url: String,
+    method: Option<String>,
+    headers: Option<HashMap<String, String>>,
+    body: Option<String>,
+    max_body_kb: Option<u32>,
+) -> Result<HttpResponse, String> {
+    let mut headers: Vec<(String, String)> = headers.unwrap_or_default().into_iter().collect();
+    headers.sort();
+    let request = HttpRequest {
+        // A HEAD response has no body to capture
+        method: method
+            .unwrap_or_else(|| if max_body_kb.is_some() { "GET" } else { "HEAD" }.to_string()),
+        headers,
+        body,
+        max_body_kb,
+    };
+
+    let adapter = HttpAdapter::with_app_handle(app_handle);
+    let mut response = adapter.fetch(&idn::url_to_ascii(&url)?, &request).await?;
+    // Echo the URL exactly as the user entered it
+    response.url = url;
+    Ok(response)
+}
+
+/// Parse the HSTS policy of https://domain/ and check it against the
+/// hstspreload.org requirements (one-year max-age, includeSubDomains, preload,
+/// HTTP upgraded to HTTPS on the same host), along with the domain's current
+/// status on the Chromium preload list.
+#[tauri::command]
+pub async fn check_hsts(app_handle: AppHandle, domain: String) -> Result<HstsReport, String> {
+    let ascii = idn::to_ascii(domain.trim().trim_end_matches('.'))?.to_lowercase();
+    let adapter = HttpAdapter::with_app_handle(app_handle);
+    let mut report = adapter.check_hsts(&ascii).await;
+    report.domain = idn::to_unicode(&report.domain);
+    Ok(report)
+}
+
+/// Fetch the files crawlers and security researchers look for: robots.txt, the
+/// sitemaps it points to (or /sitemap.xml), and security.txt. Useful to confirm a
+/// site is still crawlable after a migration.
+#[tauri::command]
+pub async fn fetch_well_known(
+    app_handle: AppHandle,
+    domain: String,
+) -> Result<WellKnownReport, String> {
+    let ascii = idn::to_ascii(domain.trim().trim_end_matches('.'))?.to_lowercase();
+    let adapter = HttpAdapter::with_app_handle(app_handle);
+    let mut report = adapter.fetch_well_known(&ascii).await;
+    report.domain = idn::to_unicode(&report.domain);
+    Ok(report)
+}
+
+/// Fetch http(s)://domain and http(s)://www.domain, follow their redirects and
+/// report whether all four converge on one canonical URL.
+#[tauri::command]
+pub async fn check_canonical(
+    app_handle: AppHandle,
+    domain: String,
+) -> Result<CanonicalReport, String> {
+    let ascii = idn::to_ascii(domain.trim().trim_end_matches('.'))?.to_lowercase();
     let adapter = HttpAdapter::with_app_handle(app_handle);
-    adapter.fetch(&url).await
+    let mut report = adapter.check_canonical(&ascii).await;
+    report.domain = idn::to_unicode(&report.domain);
+    Ok(report)
 }
@@ -0,0 +1,130 @@
+use crate::adapters::dns::{DnsAdapter, HYGIENE_RECORD_TYPES};
+use crate::commands::idn;
+use crate::models::dns::{DnsHygieneReport, TtlReport};
+use tauri::AppHandle;
+
+/// Flag illegal record combinations that the raw record list doesn't call out.
+///
+/// Records are read straight from an authoritative nameserver: recursive resolvers
+/// follow CNAMEs, which hides any other data published at the same name.
+///
+/// 1. Find the enclosing zone and its nameservers
+/// 2. Query common record types from the first nameserver that answers
+/// 3. Check for apex CNAMEs, CNAMEs with other data, and duplicate SPF records
+#[tauri::command]
+pub async fn check_dns_hygiene(
+    app_handle: AppHandle,
+    domain: String,
+) -> Result<DnsHygieneReport, String> {
+    let adapter = DnsAdapter::with_app_handle(app_handle);
+    let domain = idn::to_ascii(&domain)?.trim_end_matches('.').to_lowercase();
+
+    // Step 1: Zone and its nameservers
+    let (zone, nameservers) = adapter.find_zone(&domain).await?;
+
+    // Step 2: Records at the name, from the first nameserver that answers
+    let mut nameserver = None;
+    let mut records = Vec::new();
+
+    for ns in &nameservers {
+        let results = adapter
+            .query_types_authoritative(&domain, &HYGIENE_RECORD_TYPES, ns)
+            .await;
+
+        // Every query failing means the server is unreachable; try the next one
+        if results.iter().all(|(_, result)| result.is_err()) {
+            continue;
+        }
+
+        for (_, result) in results {
+            if let Ok(response) = result {
+                records.extend(response.records);
+            }
+        }
+        nameserver = Some(ns.clone());
+        break;
+    }
+
+    if nameserver.is_none() {
+        return Err(format!("None of the nameservers for {} responded", zone));
+    }
+
+    // Step 3: Look for illegal combinations
+    let (record_types, issues) = adapter.analyze_hygiene(&domain, &zone, &records);
+
+    Ok(DnsHygieneReport {
+        is_apex: domain == zone,
+        domain: idn::to_unicode(&domain),
+        zone,
+        nameserver,
+        record_types,
+        issues,
+    })
+}
+
+/// Flag TTLs and SOA timers at the extremes, explaining what each one costs.
+///
+/// TTLs are read from an authoritative nameserver, since resolvers count them
+/// down while the records sit in their caches.
+///
+/// 1. Find the enclosing zone and its nameservers
+/// 2. Query the name's records and the zone's SOA from the first nameserver that answers
+/// 3. Check record TTLs, the SOA's expire, refresh and retry, and negative caching
+#[tauri::command]
+pub async fn check_ttls(app_handle: AppHandle, domain: String) -> Result<TtlReport, String> {
+    let adapter = DnsAdapter::with_app_handle(app_handle);
+    let domain = idn::to_ascii(&domain)?.trim_end_matches('.').to_lowercase();
+
+    // Step 1: Zone and its nameservers
+    let (zone, nameservers) = adapter.find_zone(&domain).await?;
+
+    // Step 2: Records at the name and the zone's SOA
+    let mut nameserver = None;
+    let mut records = Vec::new();
+    let mut soa = None;
+
+    for ns in &nameservers {
+        let results = adapter
+            .query_types_authoritative(&domain, &HYGIENE_RECORD_TYPES, ns)
+            .await;
+        if results.iter().all(|(_, result)| result.is_err()) {
+            continue;
+        }
+        for (_, result) in results {
+            if let Ok(response) = result {
+                records.extend(response.records);
+            }
+        }
+
+        let zone_soa = if domain == zone {
+            records.clone()
+        } else {
+            adapter
+                .query_authoritative(&zone, "SOA", ns)
+                .await
+                .map(|response| response.records)
+                .unwrap_or_default()
+        };
+        soa = adapter.parse_soa_records(&zone_soa).into_iter().next();
+        nameserver = Some(ns.clone());
+        break;
+    }
+
+    if nameserver.is_none() {
+        return Err(format!("None of the nameservers for {} responded", zone));
+    }
+
+    // Step 3: Look for extremes
+    let ttls = adapter.record_ttls(&domain, &records);
+    let issues = adapter.analyze_ttls(&domain, soa.as_ref(), &ttls);
+
+    Ok(TtlReport {
+        domain: idn::to_unicode(&domain),
+        zone,
+        nameserver,
+        negative_ttl: soa.as_ref().map(|soa| soa.ttl.min(soa.minimum)),
+        soa,
+        ttls,
+        issues,
+    })
+}
@@ -0,0 +1,122 @@
+// Internationalized domain name (IDN) handling for the command layer
+//
+// External tools (dig, whois, openssl, curl) and DNS itself only understand the ASCII
+// (punycode) form of a domain. Commands convert user input with `to_ascii` before
+// calling adapters and convert echoed names back with `to_unicode` for display.
+//
+// Example: "münchen.de" <-> "xn--mnchen-3ya.de"
+
+// Convert a domain to its ASCII (punycode) form for queries
+// ASCII input is returned unchanged so names like "_dmarc.example.com" pass through
+pub fn to_ascii(domain: &str) -> Result<String, String> {
+    let domain = domain.trim();
+
+    if domain.is_ascii() {
+        return Ok(domain.to_string());
+    }
+
+    idna::domain_to_ascii(domain)
+        .map_err(|e| format!("Invalid internationalized domain {}: {}", domain, e))
+}
+
+// Convert a domain to its Unicode form for display
+// Falls back to the input when it is not valid punycode
+pub fn to_unicode(domain: &str) -> String {
+    if !domain.contains("xn--") {
+        return domain.to_string();
+    }
+
+    match idna::domain_to_unicode(domain) {
+        (unicode, Ok(())) => unicode,
+        _ => domain.to_string(),
+    }
+}
+
+// The form watched and saved domains are stored in: Unicode, lowercase, without a
+// trailing dot, so they match the domains of saved scans
+pub fn stored_name(domain: &str) -> Result<String, String> {
+    let ascii = to_ascii(domain.trim().trim_end_matches('.'))?.to_lowercase();
+    if ascii.is_empty() {
+        return Err("A domain is required".to_string());
+    }
+    Ok(to_unicode(&ascii))
+}
+
+// A host to probe: an IP address (brackets around IPv6 allowed) in its canonical
+// form, or a hostname in ASCII, lowercase and without a trailing dot
+pub fn host_to_ascii(host: &str) -> Result<String, String> {
+    let host = host.trim();
+    let unbracketed = host.trim_start_matches('[').trim_end_matches(']');
+    if let Ok(address) = unbracketed.parse::<std::net::IpAddr>() {
+        return Ok(address.to_string());
+    }
+    let ascii = to_ascii(host.trim_end_matches('.'))?.to_lowercase();
+    if ascii.is_empty() {
+        return Err("A host is required".to_string());
+    }
+    Ok(ascii)
+}
+
+// Convert the host portion of a URL to ASCII, leaving scheme, port, and path intact
+// Example: "https://münchen.de/path" -> "https://xn--mnchen-3ya.de/path"
+pub fn url_to_ascii(url: &str) -> Result<String, String> {
+    if url.is_ascii() {
+        return Ok(url.to_string());
+    }
+
+    let (scheme, rest) = match url.find("://") {
+        Some(pos) => (&url[..pos + 3], &url[pos + 3..]),
+        None => ("", url),
+    };
+
+    let host_end = rest.find(['/', '?', '#']).unwrap_or(rest.len());
+    let (authority, path) = rest.split_at(host_end);
+
+    // Keep an explicit port (e.g., "münchen.de:8443") out of the IDNA conversion
+    let (host, port) = match authority.rfind(':') {
+        Some(pos) if authority[pos + 1..].chars().all(|c| c.is_ascii_digit()) => {
+            authority.split_at(pos)
+        }
+        _ => (authority, ""),
+    };
+
+    Ok(format!("{}{}{}{}", scheme, to_ascii(host)?, port, path))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_to_ascii_converts_unicode() {
+        assert_eq!(to_ascii("münchen.de").unwrap(), "xn--mnchen-3ya.de");
+        assert_eq!(to_ascii("例え.jp").unwrap(), "xn--r8jz45g.jp");
+    }
+
+    #[test]
+    fn test_to_ascii_passes_through_ascii() {
+        assert_eq!(
+            to_ascii("_dmarc.Example.com").unwrap(),
+            "_dmarc.Example.com"
+        );
+        assert_eq!(to_ascii(" example.com ").unwrap(), "example.com");
+    }
+
+    #[test]
+    fn test_to_unicode_round_trip() {
+        assert_eq!(to_unicode("xn--mnchen-3ya.de"), "münchen.de");
+        assert_eq!(to_unicode("example.com"), "example.com");
+    }
+
+    #[test]
+    fn test_url_to_ascii() {
+        assert_eq!(
+            url_to_ascii("https://münchen.de:8443/straße?q=1").unwrap(),
+            "https://xn--mnchen-3ya.de:8443/straße?q=1"
+        );
+        assert_eq!(
+            url_to_ascii("http://example.com/").unwrap(),
+            "http://example.com/"
+        );
+    }
+}
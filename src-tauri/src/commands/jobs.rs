@@ -0,0 +1,20 @@
+use crate::models::job::Job;
+use crate::state::AppState;
+use tauri::{AppHandle, State};
+
+/// Jobs started by long-running commands, newest first, including recently
+/// finished ones. Each change is also sent as a `job-updated` event.
+#[tauri::command]
+pub async fn list_jobs(state: State<'_, AppState>) -> Result<Vec<Job>, String> {
+    Ok(state.jobs.list())
+}
+
+/// Cancel a queued or running job; the command that started it returns an error.
+#[tauri::command]
+pub async fn cancel_job(
+    app_handle: AppHandle,
+    state: State<'_, AppState>,
+    id: u64,
+) -> Result<(), String> {
+    state.jobs.cancel(id, Some(&app_handle))
+}
@@ -0,0 +1,48 @@
+use crate::models::command_log::{CommandLog, CommandLogExport, CommandLogFilter};
+use crate::state::AppState;
+use chrono::Utc;
+use tauri::State;
+
+/// Commands run this session, newest first, optionally only those for one domain or
+/// tool or within a time range. Each is also sent as a `command-log` event as it runs.
+#[tauri::command]
+pub async fn list_command_logs(
+    state: State<'_, AppState>,
+    filter: Option<CommandLogFilter>,
+) -> Result<Vec<CommandLog>, String> {
+    Ok(state.command_logs.query(&filter.unwrap_or_default()))
+}
+
+/// Forget the commands run so far.
+#[tauri::command]
+pub async fn clear_command_logs(state: State<'_, AppState>) -> Result<(), String> {
+    state.command_logs.clear();
+    Ok(())
+}
+
+/// Write this session's command logs, or those matching `filter`, to `path` as JSON
+/// for attaching to a bug report. Returns how many were written.
+#[tauri::command]
+pub async fn export_command_logs(
+    state: State<'_, AppState>,
+    filter: Option<CommandLogFilter>,
+    path: String,
+) -> Result<usize, String> {
+    let filter = filter.unwrap_or_default();
+    let mut logs = state.command_logs.query(&filter);
+    logs.reverse();
+    let count = logs.len();
+
+    let export = CommandLogExport {
+        app_version: env!("CARGO_PKG_VERSION").to_string(),
+        exported_at: Utc::now(),
+        filter,
+        logs,
+    };
+    let json = serde_json::to_string_pretty(&export)
+        .map_err(|e| format!("Failed to serialize the logs: {}", e))?;
+    tokio::fs::write(&path, json)
+        .await
+        .map_err(|e| format!("Failed to write {}: {}", path, e))?;
+    Ok(count)
+}
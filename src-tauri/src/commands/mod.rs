@@ -1,5 +1,27 @@
+pub mod bulk;
 pub mod certificate;
+pub mod delegation;
 pub mod dns;
 pub mod dnssec;
+pub mod email;
+pub mod environment;
+pub mod history;
 pub mod http;
+pub mod hygiene;
+pub mod idn;
+pub mod jobs;
+pub mod logs;
+pub mod nameservers;
+pub mod performance;
+pub mod ping;
+pub mod ports;
+pub mod report;
+pub mod reverse_ip;
+pub mod saved_domains;
+pub mod session;
+pub mod settings;
+pub mod ssh;
+pub mod traceroute;
+pub mod typosquat;
+pub mod watchlist;
 pub mod whois;
@@ -0,0 +1,177 @@
+use crate::adapters::dns::DnsAdapter;
+use crate::adapters::whois::WhoisAdapter;
+use crate::commands::idn;
+use crate::models::dns::{
+    NameserverAddress, NameserverComparison, NameserverDiversity, NameserverNetwork,
+    OpenResolverCheck,
+};
+use std::collections::HashMap;
+use std::net::IpAddr;
+use tauri::AppHandle;
+
+/// Send the same query to every authoritative nameserver of the zone and diff the answers.
+///
+/// Zones served by several providers (or a primary with lagging secondaries) can drift
+/// out of sync, so different resolvers see different answers depending on which
+/// nameserver they happen to ask.
+///
+/// 1. Find the enclosing zone and its NS set
+/// 2. Query each nameserver directly with recursion disabled
+/// 3. Compare record values, TTLs, response codes, and the AA flag
+#[tauri::command]
+pub async fn compare_nameservers(
+    app_handle: AppHandle,
+    domain: String,
+    record_type: String,
+) -> Result<NameserverComparison, String> {
+    let adapter = DnsAdapter::with_app_handle(app_handle);
+    let domain = idn::to_ascii(&domain)?.trim_end_matches('.').to_lowercase();
+    let record_type = record_type.to_uppercase();
+
+    // Step 1: Zone and its nameservers
+    let (zone, nameservers) = adapter.find_zone(&domain).await?;
+
+    // Step 2: Ask every nameserver
+    let answers = adapter
+        .query_each_nameserver(&domain, &record_type, &nameservers)
+        .await;
+
+    // Step 3: Diff the answers
+    let mut comparison = adapter.compare_answers(&domain, &record_type, &zone, answers);
+    comparison.domain = idn::to_unicode(&comparison.domain);
+    Ok(comparison)
+}
+
+/// Check that a zone's nameservers don't all depend on one network.
+///
+/// Nameservers in the same AS or subnet fail together (a provider outage, a route
+/// leak, a DDoS against one prefix), which defeats the point of listing several.
+///
+/// 1. Find the enclosing zone and its NS set
+/// 2. Resolve every nameserver and ask each one for the zone's SOA
+/// 3. Look up the origin AS of every address (Team Cymru)
+/// 4. Warn about fewer than two answering nameservers and a single AS or /24 (/48)
+#[tauri::command]
+pub async fn check_nameserver_diversity(
+    app_handle: AppHandle,
+    domain: String,
+) -> Result<NameserverDiversity, String> {
+    let adapter = DnsAdapter::with_app_handle(app_handle.clone());
+    let whois = WhoisAdapter::with_app_handle(app_handle);
+    let domain = idn::to_ascii(&domain)?.trim_end_matches('.').to_lowercase();
+
+    // Step 1: Zone and its nameservers
+    let (zone, nameservers) = adapter.find_zone(&domain).await?;
+
+    // Step 2: Addresses and reachability
+    let resolve_all = async {
+        let mut resolved = Vec::new();
+        for ns in &nameservers {
+            resolved.push(adapter.addresses(ns).await);
+        }
+        resolved
+    };
+    let (resolved, answers) = tokio::join!(
+        resolve_all,
+        adapter.query_each_nameserver(&zone, "SOA", &nameservers)
+    );
+
+    // Step 3: Origin AS of each distinct address, and the name of each AS
+    let mut addresses: Vec<IpAddr> = resolved.iter().flatten().flatten().copied().collect();
+    addresses.sort();
+    addresses.dedup();
+    let origins: HashMap<IpAddr, (u32, String, String)> = addresses
+        .iter()
+        .copied()
+        .zip(whois.lookup_origins(&addresses).await)
+        .filter_map(|(address, origin)| Some((address, origin?)))
+        .collect();
+    let mut as_names: HashMap<u32, Option<String>> = HashMap::new();
+    for (asn, _, _) in origins.values() {
+        if !as_names.contains_key(asn) {
+            as_names.insert(*asn, whois.lookup_as_name(*asn).await);
+        }
+    }
+
+    let networks = nameservers
+        .into_iter()
+        .zip(resolved)
+        .zip(answers)
+        .map(|((nameserver, resolved), answer)| {
+            let (addresses, resolve_error) = match resolved {
+                Ok(addresses) => (addresses, None),
+                Err(e) => (Vec::new(), Some(e)),
+            };
+            NameserverNetwork {
+                nameserver,
+                addresses: addresses
+                    .into_iter()
+                    .map(|address| {
+                        let origin = origins.get(&address);
+                        NameserverAddress {
+                            address: address.to_string(),
+                            subnet: adapter.subnet(address),
+                            asn: origin.map(|(asn, _, _)| *asn),
+                            as_name: origin.and_then(|(asn, _, _)| as_names[asn].clone()),
+                            announced_prefix: origin.map(|(_, prefix, _)| prefix.clone()),
+                        }
+                    })
+                    .collect(),
+                reachable: answer.error.is_none(),
+                error: resolve_error.or(answer.error),
+            }
+        })
+        .collect();
+
+    // Step 4: Look for a shared point of failure
+    let mut diversity = adapter.analyze_ns_diversity(&domain, &zone, networks);
+    diversity.domain = idn::to_unicode(&diversity.domain);
+    Ok(diversity)
+}
+
+/// Check whether any of a zone's nameservers is an open recursive resolver.
+///
+/// Authoritative servers that also resolve arbitrary names for anyone can be
+/// abused for DNS amplification attacks and cache poisoning, and DNS hosts often
+/// need proof before they'll fix it.
+///
+/// 1. Find the enclosing zone and its NS set
+/// 2. Ask each nameserver, with recursion desired, for a name outside the zone
+/// 3. Report the servers that resolved it
+#[tauri::command]
+pub async fn check_open_resolvers(
+    app_handle: AppHandle,
+    domain: String,
+) -> Result<OpenResolverCheck, String> {
+    let adapter = DnsAdapter::with_app_handle(app_handle);
+    let domain = idn::to_ascii(&domain)?.trim_end_matches('.').to_lowercase();
+
+    // Step 1: Zone and its nameservers
+    let (zone, nameservers) = adapter.find_zone(&domain).await?;
+
+    // Step 2: A recursive query to each nameserver
+    let probes = adapter.probe_recursion(&zone, &nameservers).await;
+
+    // Step 3: Which ones answered it
+    let mut warnings = Vec::new();
+    let mut open_resolvers = Vec::new();
+    for probe in &probes {
+        if probe.open {
+            warnings.push(format!(
+                "{} resolved {} for us: it is an open recursive resolver, which can be abused for amplification attacks",
+                probe.nameserver, probe.probe_name
+            ));
+            open_resolvers.push(probe.nameserver.clone());
+        } else if let Some(e) = &probe.error {
+            warnings.push(format!("Failed to query {}: {}", probe.nameserver, e));
+        }
+    }
+
+    Ok(OpenResolverCheck {
+        domain: idn::to_unicode(&domain),
+        zone,
+        nameservers: probes,
+        open_resolvers,
+        warnings,
+    })
+}
@@ -0,0 +1,10 @@
+use crate::models::performance::PerformanceStats;
+use crate::state::AppState;
+use tauri::State;
+
+/// Latency percentiles and failure rates of the dig, WHOIS, TLS and HTTP requests run
+/// this session, per tool and per operation (e.g. DS queries to one TLD's servers).
+#[tauri::command]
+pub async fn get_performance_stats(state: State<'_, AppState>) -> Result<PerformanceStats, String> {
+    Ok(state.performance.stats())
+}
@@ -0,0 +1,45 @@
+use crate::adapters::ping::{PingAdapter, DEFAULT_TCP_PORT};
+use crate::commands::idn;
+use crate::models::ping::PingReport;
+use crate::state::jobs;
+use tauri::AppHandle;
+
+// Probes sent to each address when no count is given, and the most allowed
+const DEFAULT_COUNT: u32 = 4;
+const MAX_COUNT: u32 = 20;
+
+/// Measure latency and packet loss to a host, or to each of its A and AAAA
+/// addresses concurrently: min/avg/max round trip and jitter per address.
+/// ICMP echo is used where the OS permits unprivileged ICMP sockets; where it
+/// doesn't, or the host drops every echo, connects to port (443 by default) are
+/// timed instead. Runs as a job that can be cancelled, its progress counting the
+/// addresses done.
+#[tauri::command]
+pub async fn ping_host(
+    app_handle: AppHandle,
+    host: String,
+    count: Option<u32>,
+    port: Option<u16>,
+) -> Result<PingReport, String> {
+    let count = count.unwrap_or(DEFAULT_COUNT);
+    if !(1..=MAX_COUNT).contains(&count) {
+        return Err(format!("Count must be between 1 and {}", MAX_COUNT));
+    }
+    if port == Some(0) {
+        return Err("Port must be between 1 and 65535".to_string());
+    }
+
+    let target = idn::host_to_ascii(&host)?;
+    let adapter = PingAdapter::with_app_handle(app_handle.clone());
+    jobs::run(&app_handle, "ping_host", &host, move |job| async move {
+        let port = port.unwrap_or(DEFAULT_TCP_PORT);
+        let mut report = adapter
+            .ping(&target, count, port, |stats, done, total| {
+                job.progress(done, total, Some(stats.address.clone()));
+            })
+            .await?;
+        report.host = idn::to_unicode(&report.host);
+        Ok(report)
+    })
+    .await
+}
@@ -0,0 +1,43 @@
+use crate::adapters::ports::PortScanAdapter;
+use crate::commands::idn;
+use crate::models::ports::PortScan;
+use crate::state;
+use crate::state::jobs;
+use std::collections::HashSet;
+use tauri::AppHandle;
+
+// Most ports one scan may check
+const MAX_PORTS: usize = 1024;
+
+/// Check which TCP ports of a host accept connections: the configured common
+/// service ports (FTP, SSH, SMTP, HTTP, databases...) by default, or the given
+/// list. Each is reported open, closed (refused) or filtered (no answer), with
+/// the greeting services such as SSH, SMTP and FTP send on connect.
+#[tauri::command]
+pub async fn scan_ports(
+    app_handle: AppHandle,
+    host: String,
+    ports: Option<Vec<u16>>,
+) -> Result<PortScan, String> {
+    let mut ports = ports.unwrap_or_else(|| state::settings_for(Some(&app_handle)).scan_ports);
+    if ports.is_empty() || ports.contains(&0) {
+        return Err("At least one port between 1 and 65535 is required".to_string());
+    }
+    let mut seen = HashSet::new();
+    ports.retain(|port| seen.insert(*port));
+    if ports.len() > MAX_PORTS {
+        return Err(format!(
+            "At most {} ports can be scanned at once",
+            MAX_PORTS
+        ));
+    }
+
+    let ascii = idn::host_to_ascii(&host)?;
+    let adapter = PortScanAdapter::with_app_handle(app_handle.clone());
+    jobs::run(&app_handle, "scan_ports", &host, move |_| async move {
+        let mut scan = adapter.scan(&ascii, &ports).await?;
+        scan.host = idn::to_unicode(&scan.host);
+        Ok(scan)
+    })
+    .await
+}
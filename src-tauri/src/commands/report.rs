@@ -0,0 +1,333 @@
+use crate::adapters::health::HealthAdapter;
+use crate::adapters::report::ReportAdapter;
+use crate::commands::{certificate, dns, dnssec, email, http, idn, whois};
+use crate::models::certificate::TlsInfo;
+use crate::models::dns::{DnssecValidation, RecordEnumeration};
+use crate::models::email::EmailConfig;
+use crate::models::report::{
+    DomainComparison, DomainReport, DomainReportProgress, ExportFormat, HttpCheck, SectionError,
+};
+use crate::models::whois::WhoisInfo;
+use crate::state::jobs::{self, JobHandle};
+use crate::state::AppState;
+use chrono::Utc;
+use std::collections::HashMap;
+use std::time::Instant;
+use tauri::{AppHandle, Emitter, Manager, State};
+use tokio::task::JoinSet;
+use tracing::instrument;
+
+// Sections analyze_domain runs, by the names used in progress events and errors
+pub const SECTIONS: [&str; 6] = ["dns", "dnssec", "whois", "certificate", "http", "email"];
+
+// Sections compare_domains runs for each domain
+const COMPARED_SECTIONS: [&str; 4] = ["dns", "certificate", "http", "email"];
+
+// Result of one section, as the command behind it returns it (the largest
+// boxed so finished sections stay small while others run)
+enum Section {
+    Dns(Result<RecordEnumeration, String>),
+    Dnssec(Result<DnssecValidation, String>),
+    Whois(Result<Box<WhoisInfo>, String>),
+    Certificate(Result<TlsInfo, String>),
+    Http(Vec<HttpCheck>),
+    Email(Result<Box<EmailConfig>, String>),
+}
+
+/// Analyze a domain in one call: record enumeration, DNSSEC, WHOIS, the
+/// certificate on port 443, HTTP and HTTPS (of www. as well for an apex domain)
+/// and email, all run concurrently through the same commands the panels use.
+///
+/// A `domain-report-progress` event carries the report so far as each section
+/// completes. A failed section is listed in `errors` and doesn't fail the others.
+/// Once all are in, the report is graded per area and overall, with a list of
+/// findings and how to fix each, and saved to the scan history.
+#[tauri::command]
+pub async fn analyze_domain(app_handle: AppHandle, domain: String) -> Result<DomainReport, String> {
+    let handle = app_handle.clone();
+    let target = domain.trim().to_string();
+    jobs::run(&handle, "analyze_domain", &target, move |job| {
+        analyze(app_handle, domain, job)
+    })
+    .await
+}
+
+#[instrument(skip_all, fields(domain = %domain))]
+async fn analyze(
+    app_handle: AppHandle,
+    domain: String,
+    job: JobHandle,
+) -> Result<DomainReport, String> {
+    let start = Instant::now();
+    let ascii = idn::to_ascii(domain.trim().trim_end_matches('.'))?.to_lowercase();
+    if ascii.is_empty() {
+        return Err("A domain is required".to_string());
+    }
+
+    let mut report = run_sections(
+        &app_handle,
+        &ascii,
+        &SECTIONS,
+        |report, section, completed, total| {
+            job.progress(completed, total, Some(section.to_string()));
+            let _ = app_handle.emit(
+                "domain-report-progress",
+                DomainReportProgress {
+                    section: section.to_string(),
+                    completed,
+                    total,
+                    report: report.clone(),
+                },
+            );
+        },
+    )
+    .await;
+    report.health = Some(HealthAdapter::with_app_handle(app_handle.clone()).score(&report));
+    report.total_time = start.elapsed().as_secs_f64();
+
+    // A report that can't be saved is still returned
+    if let Some(state) = app_handle.try_state::<AppState>() {
+        match state.history.save(&report) {
+            Ok(id) => report.scan_id = Some(id),
+            Err(e) => tracing::error!("Failed to save the scan of {}: {}", report.domain, e),
+        }
+        state.session_domains.record_report(&ascii, report.clone());
+    }
+    Ok(report)
+}
+
+// Run the chosen sections for an ASCII domain concurrently, calling on_section with
+// the report so far, the section's name and the count completed as each finishes
+pub async fn run_sections(
+    app_handle: &AppHandle,
+    ascii: &str,
+    sections: &[&str],
+    mut on_section: impl FnMut(&DomainReport, &str, usize, usize),
+) -> DomainReport {
+    let mut tasks = JoinSet::new();
+    // Section of each task, to report one that panicked
+    let mut names = HashMap::new();
+    if sections.contains(&"dns") {
+        let (handle, name) = (app_handle.clone(), ascii.to_string());
+        let task =
+            tasks.spawn(async move { Section::Dns(dns::enumerate_records(handle, name).await) });
+        names.insert(task.id(), "dns");
+    }
+    if sections.contains(&"dnssec") {
+        let (handle, name) = (app_handle.clone(), ascii.to_string());
+        let task = tasks.spawn(async move {
+            let job = JobHandle::detached();
+            Section::Dnssec(dnssec::validate_with_progress(handle, name, job).await)
+        });
+        names.insert(task.id(), "dnssec");
+    }
+    if sections.contains(&"whois") {
+        let (handle, name) = (app_handle.clone(), ascii.to_string());
+        let task = tasks.spawn(async move {
+            Section::Whois(whois::lookup_whois(handle, name).await.map(Box::new))
+        });
+        names.insert(task.id(), "whois");
+    }
+    if sections.contains(&"certificate") {
+        let (handle, name) = (app_handle.clone(), ascii.to_string());
+        let task = tasks.spawn(async move {
+            Section::Certificate(certificate::get_certificate(handle, name, None, None).await)
+        });
+        names.insert(task.id(), "certificate");
+    }
+    if sections.contains(&"http") {
+        let mut urls = vec![format!("http://{}", ascii), format!("https://{}", ascii)];
+        if !ascii.starts_with("www.") {
+            urls.push(format!("http://www.{}", ascii));
+            urls.push(format!("https://www.{}", ascii));
+        }
+        let handle = app_handle.clone();
+        let task = tasks.spawn(async move { Section::Http(fetch_urls(handle, urls).await) });
+        names.insert(task.id(), "http");
+    }
+    if sections.contains(&"email") {
+        let (handle, name) = (app_handle.clone(), ascii.to_string());
+        let task = tasks.spawn(async move {
+            let config = email::fetch_email_config(handle, name, None).await;
+            Section::Email(config.map(Box::new))
+        });
+        names.insert(task.id(), "email");
+    }
+
+    let mut report = DomainReport {
+        domain: idn::to_unicode(ascii),
+        started_at: Utc::now(),
+        total_time: 0.0,
+        dns: None,
+        dnssec: None,
+        whois: None,
+        certificate: None,
+        http: Vec::new(),
+        email: None,
+        errors: Vec::new(),
+        health: None,
+        scan_id: None,
+    };
+
+    let total = tasks.len();
+    let mut completed = 0;
+    while let Some(joined) = tasks.join_next_with_id().await {
+        completed += 1;
+        let name = match joined {
+            Ok((_, section)) => add_section(&mut report, section),
+            Err(e) => {
+                let name = names.get(&e.id()).copied().unwrap_or("unknown");
+                report.errors.push(SectionError {
+                    section: name.to_string(),
+                    error: format!("Stopped unexpectedly: {}", e),
+                });
+                name
+            }
+        };
+        on_section(&report, name, completed, total);
+    }
+    report
+}
+
+/// Compare two domains side by side, e.g. an old domain and the one replacing it:
+/// nameservers, MX hosts, SPF and DMARC, the certificate's issuer and expiry, and
+/// where http:// and https:// of each end up after redirects. Both are checked
+/// concurrently through the same commands as analyze_domain; nothing is saved.
+#[tauri::command]
+pub async fn compare_domains(
+    app_handle: AppHandle,
+    domain_a: String,
+    domain_b: String,
+) -> Result<DomainComparison, String> {
+    let start = Instant::now();
+    let a = idn::to_ascii(domain_a.trim().trim_end_matches('.'))?.to_lowercase();
+    let b = idn::to_ascii(domain_b.trim().trim_end_matches('.'))?.to_lowercase();
+    if a.is_empty() || b.is_empty() {
+        return Err("Two domains are required".to_string());
+    }
+
+    let handle = app_handle.clone();
+    let target = format!("{} and {}", domain_a.trim(), domain_b.trim());
+    jobs::run(
+        &app_handle,
+        "compare_domains",
+        &target,
+        move |job| async move {
+            let total = COMPARED_SECTIONS.len() * 2;
+            let completed = std::sync::atomic::AtomicUsize::new(0);
+            let on_section = |_: &DomainReport, section: &str, _: usize, _: usize| {
+                let done = completed.fetch_add(1, std::sync::atomic::Ordering::SeqCst) + 1;
+                job.progress(done, total, Some(section.to_string()));
+            };
+            let (report_a, report_b) = tokio::join!(
+                run_sections(&handle, &a, &COMPARED_SECTIONS, on_section),
+                run_sections(&handle, &b, &COMPARED_SECTIONS, on_section)
+            );
+
+            let mut comparison = ReportAdapter::new().compare(&report_a, &report_b);
+            comparison.total_time = start.elapsed().as_secs_f64();
+            Ok(comparison)
+        },
+    )
+    .await
+}
+
+/// Write a report to `path` as JSON, Markdown or a standalone HTML page, for
+/// attaching to a ticket or sending to a client. Exports the saved scan `scan_id`,
+/// or else `report` as analyze_domain returned it.
+#[tauri::command]
+pub async fn export_report(
+    state: State<'_, AppState>,
+    scan_id: Option<i64>,
+    report: Option<DomainReport>,
+    format: ExportFormat,
+    path: String,
+) -> Result<(), String> {
+    let report = match (scan_id, report) {
+        (Some(id), _) => {
+            state
+                .history
+                .get(id)?
+                .ok_or_else(|| format!("No saved scan with ID {}", id))?
+                .report
+        }
+        (None, Some(report)) => report,
+        (None, None) => return Err("A scan ID or a report is required".to_string()),
+    };
+    let contents = ReportAdapter::new().export(&report, format)?;
+    tokio::fs::write(&path, contents)
+        .await
+        .map_err(|e| format!("Failed to write {}: {}", path, e))
+}
+
+// Store a completed section in the report, returning its name
+fn add_section(report: &mut DomainReport, section: Section) -> &'static str {
+    let (name, error) = match section {
+        Section::Dns(result) => ("dns", result.map(|r| report.dns = Some(r)).err()),
+        Section::Dnssec(result) => ("dnssec", result.map(|r| report.dnssec = Some(r)).err()),
+        Section::Whois(result) => ("whois", result.map(|r| report.whois = Some(*r)).err()),
+        Section::Certificate(result) => (
+            "certificate",
+            result.map(|r| report.certificate = Some(r)).err(),
+        ),
+        Section::Http(checks) => {
+            report.http = checks;
+            ("http", None)
+        }
+        Section::Email(result) => ("email", result.map(|r| report.email = Some(*r)).err()),
+    };
+
+    if let Some(error) = error {
+        report.errors.push(SectionError {
+            section: name.to_string(),
+            error,
+        });
+    }
+    name
+}
+
+// HEAD each URL concurrently, following redirects, in the order given
+async fn fetch_urls(app_handle: AppHandle, urls: Vec<String>) -> Vec<HttpCheck> {
+    let mut tasks = JoinSet::new();
+    // Position and URL of each task, to report one that panicked
+    let mut targets = HashMap::new();
+    for (index, url) in urls.into_iter().enumerate() {
+        let handle = app_handle.clone();
+        let target = url.clone();
+        let task = tasks.spawn(async move {
+            let method = Some("HEAD".to_string());
+            let result = http::fetch_http(handle, url.clone(), method, None, None, None).await;
+            let (response, error) = match result {
+                Ok(response) => (Some(response), None),
+                Err(error) => (None, Some(error)),
+            };
+            HttpCheck {
+                url,
+                response,
+                error,
+            }
+        });
+        targets.insert(task.id(), (index, target));
+    }
+
+    let mut checks = Vec::new();
+    while let Some(joined) = tasks.join_next_with_id().await {
+        let id = match &joined {
+            Ok((id, _)) => *id,
+            Err(e) => e.id(),
+        };
+        let Some((index, url)) = targets.remove(&id) else {
+            continue;
+        };
+        let check = joined
+            .map(|(_, check)| check)
+            .unwrap_or_else(|e| HttpCheck {
+                url,
+                response: None,
+                error: Some(format!("Stopped unexpectedly: {}", e)),
+            });
+        checks.push((index, check));
+    }
+    checks.sort_by_key(|(index, _)| *index);
+    checks.into_iter().map(|(_, check)| check).collect()
+}
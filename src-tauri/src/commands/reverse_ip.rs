@@ -0,0 +1,29 @@
+use crate::adapters::reverse_ip::ReverseIpAdapter;
+use crate::commands::idn;
+use crate::models::reverse_ip::ReverseIpReport;
+use std::net::IpAddr;
+use tauri::AppHandle;
+
+/// List other domains known to point at an IP address, from HackerTarget's
+/// reverse-IP lookup and Mnemonic's passive DNS, to tell whether a site is on
+/// shared hosting or a dedicated address. A source that fails (both have daily
+/// limits without an account) is listed in `errors`; the other is still used.
+#[tauri::command]
+pub async fn lookup_reverse_ip(
+    app_handle: AppHandle,
+    ip: String,
+) -> Result<ReverseIpReport, String> {
+    let ip: IpAddr = ip
+        .trim()
+        .trim_start_matches('[')
+        .trim_end_matches(']')
+        .parse()
+        .map_err(|_| format!("Invalid IP address: {}", ip.trim()))?;
+
+    let adapter = ReverseIpAdapter::with_app_handle(app_handle);
+    let mut report = adapter.lookup(ip).await;
+    for hosted in report.domains.iter_mut() {
+        hosted.domain = idn::to_unicode(&hosted.domain);
+    }
+    Ok(report)
+}
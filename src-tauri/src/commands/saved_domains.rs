@@ -0,0 +1,94 @@
+use crate::commands::idn;
+use crate::models::saved_domain::SavedDomain;
+use crate::state::AppState;
+use chrono::Utc;
+use tauri::State;
+
+const MAX_TAGS: usize = 16;
+const MAX_TAG_LENGTH: usize = 32;
+
+/// Save a domain for quick access, optionally with tags. Saving one that's already
+/// saved keeps its tags unless new ones are given.
+#[tauri::command]
+pub async fn save_domain(
+    state: State<'_, AppState>,
+    domain: String,
+    tags: Option<Vec<String>>,
+) -> Result<SavedDomain, String> {
+    let domain = idn::stored_name(&domain)?;
+    let tags = tags.map(|tags| clean_tags(&tags)).transpose()?;
+    let saved = state
+        .saved_domains
+        .save(&domain, tags.as_deref(), Utc::now())?;
+    with_last_scan(&state, saved)
+}
+
+/// Replace the tags of a saved domain.
+#[tauri::command]
+pub async fn tag_saved_domain(
+    state: State<'_, AppState>,
+    domain: String,
+    tags: Vec<String>,
+) -> Result<SavedDomain, String> {
+    let domain = idn::stored_name(&domain)?;
+    if !state.saved_domains.set_tags(&domain, &clean_tags(&tags)?)? {
+        return Err(format!("{} isn't saved", domain));
+    }
+    let saved = state
+        .saved_domains
+        .get(&domain)?
+        .ok_or_else(|| format!("{} isn't saved", domain))?;
+    with_last_scan(&state, saved)
+}
+
+/// Remove a domain from the saved domains. Its scan history is kept.
+#[tauri::command]
+pub async fn remove_saved_domain(state: State<'_, AppState>, domain: String) -> Result<(), String> {
+    let domain = idn::stored_name(&domain)?;
+    if state.saved_domains.remove(&domain)? {
+        Ok(())
+    } else {
+        Err(format!("{} isn't saved", domain))
+    }
+}
+
+/// Saved domains sorted by name, each with its latest saved scan's score and grade;
+/// only those with the tag when one is given.
+#[tauri::command]
+pub async fn list_saved_domains(
+    state: State<'_, AppState>,
+    tag: Option<String>,
+) -> Result<Vec<SavedDomain>, String> {
+    let tag = tag.map(|t| t.trim().to_string()).filter(|t| !t.is_empty());
+    state
+        .saved_domains
+        .list(tag.as_deref())?
+        .into_iter()
+        .map(|saved| with_last_scan(&state, saved))
+        .collect()
+}
+
+fn with_last_scan(state: &AppState, mut saved: SavedDomain) -> Result<SavedDomain, String> {
+    saved.last_scan = state.history.list(Some(&saved.domain), 1)?.pop();
+    Ok(saved)
+}
+
+// Trimmed and without duplicates (ignoring case), keeping the first spelling
+fn clean_tags(tags: &[String]) -> Result<Vec<String>, String> {
+    let mut cleaned: Vec<String> = Vec::new();
+    for tag in tags.iter().map(|t| t.trim()).filter(|t| !t.is_empty()) {
+        if tag.chars().count() > MAX_TAG_LENGTH || tag.chars().any(char::is_control) {
+            return Err(format!(
+                "Tags must be a single line of at most {} characters",
+                MAX_TAG_LENGTH
+            ));
+        }
+        if !cleaned.iter().any(|t| t.eq_ignore_ascii_case(tag)) {
+            cleaned.push(tag.to_string());
+        }
+    }
+    if cleaned.len() > MAX_TAGS {
+        return Err(format!("A domain can have at most {} tags", MAX_TAGS));
+    }
+    Ok(cleaned)
+}
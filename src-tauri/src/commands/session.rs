@@ -0,0 +1,70 @@
+use crate::commands::idn;
+use crate::models::command_log::CommandLogFilter;
+use crate::models::session::{Session, SESSION_FORMAT};
+use crate::state::AppState;
+use chrono::Utc;
+use tauri::State;
+
+/// Write this session to `path` as JSON: every domain queried, each with its latest
+/// analyze_domain report from this session, the command logs with their output, and
+/// `panels`, the frontend's state, as given. Returns how many domains were written.
+#[tauri::command]
+pub async fn save_session(
+    state: State<'_, AppState>,
+    path: String,
+    panels: Option<serde_json::Value>,
+) -> Result<usize, String> {
+    let mut logs = state.command_logs.query(&CommandLogFilter::default());
+    logs.reverse();
+
+    let mut domains = state.session_domains.list();
+    for domain in domains.iter_mut() {
+        domain.domain = idn::to_unicode(&domain.domain);
+    }
+    let count = domains.len();
+
+    let session = Session {
+        format: SESSION_FORMAT,
+        app_version: env!("CARGO_PKG_VERSION").to_string(),
+        started_at: state.started_at,
+        saved_at: Utc::now(),
+        domains,
+        logs,
+        panels,
+    };
+    let json = serde_json::to_string_pretty(&session)
+        .map_err(|e| format!("Failed to serialize the session: {}", e))?;
+    tokio::fs::write(&path, json)
+        .await
+        .map_err(|e| format!("Failed to write {}: {}", path, e))?;
+    Ok(count)
+}
+
+/// Read a session written by save_session, possibly on another machine. Its command
+/// logs and domains replace this session's, so they can be listed and filtered as
+/// usual and are saved again with the next save_session.
+#[tauri::command]
+pub async fn load_session(state: State<'_, AppState>, path: String) -> Result<Session, String> {
+    let json = tokio::fs::read_to_string(&path)
+        .await
+        .map_err(|e| format!("Failed to read {}: {}", path, e))?;
+    let session: Session = serde_json::from_str(&json)
+        .map_err(|e| format!("{} isn't a saved session: {}", path, e))?;
+    if session.format > SESSION_FORMAT {
+        return Err(format!(
+            "{} was saved by a newer version ({}) of the app",
+            path, session.app_version
+        ));
+    }
+
+    state.command_logs.clear();
+    for log in &session.logs {
+        state.command_logs.push(log.clone());
+    }
+    let mut domains = session.domains.clone();
+    for domain in domains.iter_mut() {
+        domain.domain = idn::to_ascii(&domain.domain).unwrap_or_else(|_| domain.domain.clone());
+    }
+    state.session_domains.replace(domains);
+    Ok(session)
+}
@@ -0,0 +1,22 @@
+use crate::models::settings::Settings;
+use crate::state::{settings, AppState};
+use tauri::{AppHandle, State};
+
+#[tauri::command]
+pub async fn get_settings(state: State<'_, AppState>) -> Result<Settings, String> {
+    Ok(state.settings())
+}
+
+// Validate, persist, and apply new settings
+// Adapters read settings per query, so changes take effect on the next lookup
+#[tauri::command]
+pub async fn update_settings(
+    app_handle: AppHandle,
+    state: State<'_, AppState>,
+    settings: Settings,
+) -> Result<Settings, String> {
+    settings.validate()?;
+    settings::save(&app_handle, &settings)?;
+    state.set_settings(settings.clone());
+    Ok(settings)
+}
@@ -0,0 +1,32 @@
+use crate::adapters::ssh::{SshAdapter, DEFAULT_SSH_PORT};
+use crate::commands::idn;
+use crate::models::ssh::SshfpReport;
+use tauri::AppHandle;
+
+/// Look up a host's SSHFP records, the fingerprints of its SSH host keys
+/// published in DNS. With `verify`, also connect to its SSH server (port 22 by
+/// default) to fetch each host key and check it against the records, as OpenSSH's
+/// VerifyHostKeyDNS does. Clients only trust DNSSEC-validated records.
+#[tauri::command]
+pub async fn check_sshfp(
+    app_handle: AppHandle,
+    host: String,
+    verify: Option<bool>,
+    port: Option<u16>,
+) -> Result<SshfpReport, String> {
+    if port == Some(0) {
+        return Err("Port must be between 1 and 65535".to_string());
+    }
+
+    let ascii = idn::host_to_ascii(&host)?;
+    let adapter = SshAdapter::with_app_handle(app_handle);
+    let mut report = adapter
+        .check_sshfp(
+            &ascii,
+            port.unwrap_or(DEFAULT_SSH_PORT),
+            verify.unwrap_or(false),
+        )
+        .await?;
+    report.host = idn::to_unicode(&report.host);
+    Ok(report)
+}
@@ -0,0 +1,57 @@
+use crate::adapters::traceroute::TracerouteAdapter;
+use crate::commands::idn;
+use crate::models::traceroute::{TraceHopEvent, TraceRoute};
+use crate::state::jobs;
+use tauri::{AppHandle, Emitter};
+
+// Hops traced when no max_hops is given, and the most allowed
+const DEFAULT_MAX_HOPS: u8 = 30;
+const MAX_HOPS: u8 = 64;
+
+/// Trace the route to a host (its first A address, or AAAA without one) with
+/// traceroute, tracepath or, on Windows, tracert: each hop's addresses, their
+/// reverse DNS names and the round trip of each probe. Runs as a job that can be
+/// cancelled; every hop is sent as a `trace-route-hop` event as it's discovered,
+/// and again if more of its probes come in.
+#[tauri::command]
+pub async fn trace_route(
+    app_handle: AppHandle,
+    host: String,
+    max_hops: Option<u8>,
+) -> Result<TraceRoute, String> {
+    let max_hops = max_hops.unwrap_or(DEFAULT_MAX_HOPS);
+    if !(1..=MAX_HOPS).contains(&max_hops) {
+        return Err(format!("Max hops must be between 1 and {}", MAX_HOPS));
+    }
+    let target = idn::host_to_ascii(&host)?;
+
+    let handle = app_handle.clone();
+    jobs::run(
+        &handle,
+        "trace_route",
+        &target.clone(),
+        move |job| async move {
+            let display = idn::to_unicode(&target);
+            let adapter = TracerouteAdapter::with_app_handle(app_handle.clone());
+            let mut route = adapter
+                .trace(&target, max_hops, |hop| {
+                    job.progress(
+                        hop.ttl as usize,
+                        max_hops as usize,
+                        hop.responders.first().map(|r| r.address.clone()),
+                    );
+                    let _ = app_handle.emit(
+                        "trace-route-hop",
+                        TraceHopEvent {
+                            host: display.clone(),
+                            hop: hop.clone(),
+                        },
+                    );
+                })
+                .await?;
+            route.host = display;
+            Ok(route)
+        },
+    )
+    .await
+}
@@ -0,0 +1,54 @@
+use crate::adapters::typosquat::TyposquatAdapter;
+use crate::commands::idn;
+use crate::models::typosquat::TyposquatReport;
+use crate::state::jobs;
+use tauri::AppHandle;
+
+/// Brand-protection check: generate typo and homograph lookalikes of a domain's
+/// registrable name (dropped, doubled, swapped and neighbouring keys, hyphens,
+/// lookalike ASCII and confusable Cyrillic letters, other TLDs) and report which
+/// are registered and by whom.
+///
+/// 1. Query NS for every variant; NXDOMAIN means it's unregistered
+/// 2. Look up WHOIS for the domain and each variant that exists, paced per registry
+/// 3. Group registered variants by registrar and registrant, and flag those sharing
+///    the domain's registrant or nameservers as likely defensive registrations
+#[tauri::command]
+pub async fn scan_typosquats(
+    app_handle: AppHandle,
+    domain: String,
+) -> Result<TyposquatReport, String> {
+    let ascii = idn::to_ascii(domain.trim().trim_end_matches('.'))?.to_lowercase();
+    if !ascii.contains('.') {
+        return Err(format!("{} is not a registrable domain", domain.trim()));
+    }
+
+    let adapter = TyposquatAdapter::with_app_handle(app_handle.clone());
+    jobs::run(
+        &app_handle,
+        "scan_typosquats",
+        &domain,
+        move |job| async move {
+            let mut report = adapter
+                .scan(&ascii, move |done, total, name| {
+                    job.progress(done, total, Some(idn::to_unicode(name)))
+                })
+                .await;
+            report.domain = idn::to_unicode(&report.domain);
+            for variant in report.variants.iter_mut() {
+                variant.domain = idn::to_unicode(&variant.domain);
+            }
+            for group in report
+                .registrars
+                .iter_mut()
+                .chain(report.registrants.iter_mut())
+            {
+                for domain in group.domains.iter_mut() {
+                    *domain = idn::to_unicode(domain);
+                }
+            }
+            Ok(report)
+        },
+    )
+    .await
+}
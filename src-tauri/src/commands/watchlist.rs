@@ -0,0 +1,213 @@
+use crate::commands::{certificate, idn, whois};
+use crate::models::watchlist::{ExpiryKind, UpcomingExpiration, WatchedDomain};
+use crate::state::jobs;
+use crate::state::{self, AppState};
+use chrono::{Duration, Utc};
+use tauri::{AppHandle, Manager, State};
+use tauri_plugin_notification::NotificationExt;
+
+// How often the background task looks for domains due a re-check
+const WAKE_INTERVAL: std::time::Duration = std::time::Duration::from_secs(15 * 60);
+
+/// Watch a domain's certificate and registration expiry. It's checked right
+/// away, then in the background every `watchlist_check_hours`.
+#[tauri::command]
+pub async fn add_to_watchlist(
+    app_handle: AppHandle,
+    state: State<'_, AppState>,
+    domain: String,
+) -> Result<WatchedDomain, String> {
+    let domain = idn::stored_name(&domain)?;
+    let watched = state.watchlist.add(&domain, Utc::now())?;
+    if watched.checked_at.is_some() {
+        return Ok(watched);
+    }
+    let watched = check(&app_handle, watched).await;
+    state.watchlist.update(&watched)?;
+    notify(&app_handle, &state)?;
+    Ok(watched)
+}
+
+/// Stop watching a domain.
+#[tauri::command]
+pub async fn remove_from_watchlist(
+    state: State<'_, AppState>,
+    domain: String,
+) -> Result<(), String> {
+    let domain = idn::stored_name(&domain)?;
+    if state.watchlist.remove(&domain)? {
+        Ok(())
+    } else {
+        Err(format!("{} isn't being watched", domain))
+    }
+}
+
+/// Watched domains with the expiry dates found by their last check.
+#[tauri::command]
+pub async fn list_watchlist(state: State<'_, AppState>) -> Result<Vec<WatchedDomain>, String> {
+    state.watchlist.list()
+}
+
+/// Re-check every watched domain now rather than waiting for the next background check.
+#[tauri::command]
+pub async fn check_watchlist(
+    app_handle: AppHandle,
+    state: State<'_, AppState>,
+) -> Result<Vec<WatchedDomain>, String> {
+    let watchlist = state.watchlist.list()?;
+    let handle = app_handle.clone();
+    let target = format!("{} domains", watchlist.len());
+    jobs::run(
+        &app_handle,
+        "check_watchlist",
+        &target,
+        move |job| async move {
+            let total = watchlist.len();
+            for (index, watched) in watchlist.into_iter().enumerate() {
+                let domain = watched.domain.clone();
+                let watched = check(&handle, watched).await;
+                if let Some(state) = handle.try_state::<AppState>() {
+                    state.watchlist.update(&watched)?;
+                }
+                job.progress(index + 1, total, Some(domain));
+            }
+            Ok(())
+        },
+    )
+    .await?;
+    notify(&app_handle, &state)?;
+    state.watchlist.list()
+}
+
+/// Certificate and registration expiries of watched domains within `days`
+/// (by default the largest notification threshold), soonest first. Expiries
+/// already past are included.
+#[tauri::command]
+pub async fn list_upcoming_expirations(
+    app_handle: AppHandle,
+    state: State<'_, AppState>,
+    days: Option<u32>,
+) -> Result<Vec<UpcomingExpiration>, String> {
+    let settings = state::settings_for(Some(&app_handle));
+    let thresholds = &settings.expiry_notification_days;
+    let days = days.unwrap_or_else(|| thresholds.iter().copied().max().unwrap_or(30));
+    state.watchlist.upcoming(Utc::now(), days, thresholds)
+}
+
+// Background task started at launch: re-checks domains whose last check is older
+// than watchlist_check_hours and raises notifications for thresholds crossed since
+pub async fn watch_expiries(app_handle: AppHandle) {
+    loop {
+        if let Some(state) = app_handle.try_state::<AppState>() {
+            if let Err(e) = check_due(&app_handle, &state).await {
+                tracing::warn!("Watchlist check failed: {}", e);
+            }
+        }
+        tokio::time::sleep(WAKE_INTERVAL).await;
+    }
+}
+
+async fn check_due(app_handle: &AppHandle, state: &AppState) -> Result<(), String> {
+    let settings = state::settings_for(Some(app_handle));
+    let cutoff = Utc::now() - Duration::hours(i64::from(settings.watchlist_check_hours));
+    for watched in state.watchlist.list()? {
+        if watched.checked_at.is_some_and(|checked| checked > cutoff) {
+            continue;
+        }
+        let watched = check(app_handle, watched).await;
+        state.watchlist.update(&watched)?;
+    }
+    notify(app_handle, state)
+}
+
+// Look up the certificate and registration expiry; a failed lookup keeps the date
+// from the previous check and is listed in errors
+async fn check(app_handle: &AppHandle, mut watched: WatchedDomain) -> WatchedDomain {
+    let ascii = match idn::to_ascii(&watched.domain) {
+        Ok(ascii) => ascii,
+        Err(e) => {
+            watched.errors = vec![e];
+            watched.checked_at = Some(Utc::now());
+            return watched;
+        }
+    };
+    let (tls, registration) = tokio::join!(
+        certificate::get_certificate(app_handle.clone(), ascii.clone(), None, None),
+        whois::lookup_whois(app_handle.clone(), ascii),
+    );
+
+    watched.errors.clear();
+    match tls {
+        Ok(tls) => {
+            watched.certificate_expires_at = tls
+                .certificate_chain
+                .certificates
+                .first()
+                .and_then(|leaf| leaf.not_after_at)
+        }
+        Err(e) => watched.errors.push(format!("certificate: {}", e)),
+    }
+    match registration {
+        Ok(info) if info.expiration_at.is_some() => {
+            watched.registration_expires_at = info.expiration_at
+        }
+        Ok(_) => watched
+            .errors
+            .push("registration: no expiration date published".to_string()),
+        Err(e) => watched.errors.push(format!("registration: {}", e)),
+    }
+    watched.checked_at = Some(Utc::now());
+    watched
+}
+
+// Show a desktop notification for each threshold an expiry has newly crossed
+fn notify(app_handle: &AppHandle, state: &AppState) -> Result<(), String> {
+    let settings = state::settings_for(Some(app_handle));
+    let thresholds = &settings.expiry_notification_days;
+    let Some(largest) = thresholds.iter().copied().max() else {
+        return Ok(());
+    };
+    for expiration in state.watchlist.upcoming(Utc::now(), largest, thresholds)? {
+        if !state.watchlist.mark_notified(&expiration)? {
+            continue;
+        }
+        let (title, body) = notification_text(&expiration);
+        if let Err(e) = app_handle
+            .notification()
+            .builder()
+            .title(title)
+            .body(body)
+            .show()
+        {
+            tracing::warn!("Failed to show an expiry notification: {}", e);
+        }
+    }
+    Ok(())
+}
+
+fn notification_text(expiration: &UpcomingExpiration) -> (String, String) {
+    let what = match expiration.kind {
+        ExpiryKind::Certificate => "certificate",
+        ExpiryKind::Registration => "domain registration",
+    };
+    let when = match expiration.days_remaining {
+        days if days < -1 => format!("expired {} days ago", -days),
+        -1 => "expired yesterday".to_string(),
+        0 => "expires today".to_string(),
+        1 => "expires tomorrow".to_string(),
+        days => format!("expires in {} days", days),
+    };
+    let title = if expiration.days_remaining < 0 {
+        format!("{} {} expired", expiration.domain, what)
+    } else {
+        format!("{} {} expiring", expiration.domain, what)
+    };
+    let body = format!(
+        "The {} for {} {} ({})",
+        what,
+        expiration.domain,
+        when,
+        expiration.expires_at.format("%Y-%m-%d")
+    );
+    (title, body)
+}
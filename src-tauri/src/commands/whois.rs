@@ -1,9 +1,182 @@
+use crate::adapters::dns::DnsAdapter;
 use crate::adapters::whois::WhoisAdapter;
-use crate::models::whois::WhoisInfo;
-use tauri::AppHandle;
+use crate::commands::idn;
+use crate::models::whois::{
+    BulkWhoisResult, BulkWhoisSummary, DomainAvailability, IpWhoisInfo, WhoisInfo,
+};
+use crate::state::jobs;
+use std::net::IpAddr;
+use tauri::{AppHandle, Emitter};
+
+// Enough for a large portfolio; at one query per second per registry, more
+// would take too long for a single command
+const MAX_BULK_WHOIS_DOMAINS: usize = 500;
 
 #[tauri::command]
 pub async fn lookup_whois(app_handle: AppHandle, domain: String) -> Result<WhoisInfo, String> {
     let adapter = WhoisAdapter::with_app_handle(app_handle);
-    adapter.lookup(&domain).await
+    let mut info = adapter.lookup(&idn::to_ascii(&domain)?).await?;
+    info.domain = idn::to_unicode(&info.domain);
+    Ok(info)
+}
+
+/// WHOIS for a list of domains, e.g. a portfolio checked for upcoming expiry.
+/// Domains at the same registry are queried one after another with a pause in
+/// between so long lists don't trip registry rate limits; different registries
+/// run in parallel. A `whois-bulk-result` event is emitted as each domain
+/// completes, and the summary lists expired and soon-to-expire domains.
+#[tauri::command]
+pub async fn lookup_whois_bulk(
+    app_handle: AppHandle,
+    domains: Vec<String>,
+) -> Result<BulkWhoisSummary, String> {
+    let mut ascii: Vec<String> = Vec::new();
+    for domain in &domains {
+        let domain = domain.trim().trim_end_matches('.');
+        if domain.is_empty() {
+            continue;
+        }
+        let domain = idn::to_ascii(domain)?.to_lowercase();
+        if !ascii.contains(&domain) {
+            ascii.push(domain);
+        }
+    }
+
+    if ascii.is_empty() {
+        return Err("No domains to look up".to_string());
+    }
+    if ascii.len() > MAX_BULK_WHOIS_DOMAINS {
+        return Err(format!(
+            "Too many domains: {} (at most {})",
+            ascii.len(),
+            MAX_BULK_WHOIS_DOMAINS
+        ));
+    }
+
+    let handle = app_handle.clone();
+    let target = format!("{} domains", ascii.len());
+    jobs::run(
+        &handle,
+        "lookup_whois_bulk",
+        &target,
+        move |job| async move {
+            let adapter = WhoisAdapter::with_app_handle(app_handle.clone());
+            let mut summary = adapter
+                .lookup_bulk(&ascii, move |result| {
+                    job.progress(result.completed, result.total, Some(result.domain.clone()));
+                    let _ = app_handle.emit("whois-bulk-result", unicode_result(result.clone()));
+                })
+                .await;
+
+            summary.results = summary.results.into_iter().map(unicode_result).collect();
+            for domain in summary
+                .expired
+                .iter_mut()
+                .chain(summary.expiring_soon.iter_mut())
+            {
+                *domain = idn::to_unicode(domain);
+            }
+
+            Ok(summary)
+        },
+    )
+    .await
+}
+
+fn unicode_result(mut result: BulkWhoisResult) -> BulkWhoisResult {
+    result.domain = idn::to_unicode(&result.domain);
+    if let Some(info) = result.info.as_mut() {
+        info.domain = idn::to_unicode(&info.domain);
+    }
+    result
+}
+
+/// Owner of an IP address: network registration and abuse contact from the RIR
+/// over RDAP, plus origin ASN and announced prefix from Team Cymru's DNS service
+/// (RDAP has no routing data). Either half may fail on its own; the failure is
+/// reported as a warning unless both do.
+#[tauri::command]
+pub async fn lookup_ip(app_handle: AppHandle, ip: String) -> Result<IpWhoisInfo, String> {
+    let ip: IpAddr = ip
+        .trim()
+        .parse()
+        .map_err(|_| format!("Invalid IP address: {}", ip))?;
+    let whois = WhoisAdapter::with_app_handle(app_handle);
+    let origin = whois.lookup_origin(ip).await;
+
+    let mut info = match whois.lookup_ip_rdap(ip).await {
+        Ok(info) => info,
+        Err(e) if origin.is_some() => IpWhoisInfo {
+            ip: ip.to_string(),
+            network_name: None,
+            network_handle: None,
+            network_range: None,
+            organization: None,
+            country: None,
+            abuse_email: None,
+            rir: None,
+            asn: None,
+            as_name: None,
+            announced_prefix: None,
+            warnings: vec![format!("RDAP lookup failed: {}", e)],
+            raw_output: String::new(),
+        },
+        Err(e) => return Err(e),
+    };
+
+    match origin {
+        Some((asn, prefix, registry)) => {
+            info.asn = Some(asn);
+            info.announced_prefix = Some(prefix);
+            if info.rir.is_none() && !registry.is_empty() {
+                info.rir = Some(registry);
+            }
+            info.as_name = whois.lookup_as_name(asn).await;
+        }
+        None => info
+            .warnings
+            .push("No BGP origin found; the address may not be announced".to_string()),
+    }
+
+    Ok(info)
+}
+
+/// Whether a domain looks registered, available, or on its way out
+/// (redemptionPeriod / pendingDelete), from the registry's RDAP or WHOIS answer
+/// and an NXDOMAIN check. The evidence lists what each source said.
+#[tauri::command]
+pub async fn check_availability(
+    app_handle: AppHandle,
+    domain: String,
+) -> Result<DomainAvailability, String> {
+    let ascii = idn::to_ascii(domain.trim().trim_end_matches('.'))?;
+    let whois = WhoisAdapter::with_app_handle(app_handle.clone());
+    let dns = DnsAdapter::with_app_handle(app_handle);
+
+    let check = whois.check_registration(&ascii).await;
+    let mut evidence = check.evidence.clone();
+
+    let nxdomain = match dns.query(&ascii, "NS").await {
+        Ok(response) => {
+            let rcode = response.header.map(|h| h.rcode);
+            evidence.push(format!(
+                "DNS: NS query answered {}",
+                rcode.as_deref().unwrap_or("without a status")
+            ));
+            rcode.map(|r| r == "NXDOMAIN")
+        }
+        Err(e) => {
+            evidence.push(format!("DNS: {}", e));
+            None
+        }
+    };
+
+    Ok(DomainAvailability {
+        domain: idn::to_unicode(&ascii),
+        status: whois.classify_availability(&check, nxdomain),
+        registration_status: check.status,
+        nxdomain,
+        evidence,
+        raw_output: check.raw_output,
+    })
 }
@@ -2,25 +2,150 @@
 pub mod adapters;
 pub mod commands;
 pub mod models;
+pub mod state;
 
 // Re-export commands
-use commands::certificate::get_certificate;
-use commands::dns::{query_dns, query_dns_multiple};
-use commands::dnssec::validate_dnssec;
-use commands::http::fetch_http;
-use commands::whois::lookup_whois;
+use commands::bulk::{import_domains, scan_domains};
+use commands::certificate::{get_certificate, lookup_ct_history, scan_tls_ports};
+use commands::delegation::check_delegation;
+use commands::dns::{
+    benchmark_resolvers, check_fcrdns, clear_dns_cache, detect_load_balancing, enumerate_records,
+    query_dns, query_dns_multiple,
+};
+use commands::dnssec::{validate_dnssec, validate_record};
+use commands::email::{
+    analyze_dmarc_report, check_autodiscovery, check_blacklists, fetch_email_config, verify_dkim,
+};
+use commands::environment::environment_doctor;
+use commands::history::{delete_scan, diff_scans, get_scan, list_history};
+use commands::http::{check_canonical, check_hsts, fetch_http, fetch_well_known};
+use commands::hygiene::{check_dns_hygiene, check_ttls};
+use commands::jobs::{cancel_job, list_jobs};
+use commands::logs::{clear_command_logs, export_command_logs, list_command_logs};
+use commands::nameservers::{
+    check_nameserver_diversity, check_open_resolvers, compare_nameservers,
+};
+use commands::performance::get_performance_stats;
+use commands::ping::ping_host;
+use commands::ports::scan_ports;
+use commands::report::{analyze_domain, compare_domains, export_report};
+use commands::reverse_ip::lookup_reverse_ip;
+use commands::saved_domains::{
+    list_saved_domains, remove_saved_domain, save_domain, tag_saved_domain,
+};
+use commands::session::{load_session, save_session};
+use commands::settings::{get_settings, update_settings};
+use commands::ssh::check_sshfp;
+use commands::traceroute::trace_route;
+use commands::typosquat::scan_typosquats;
+use commands::watchlist::{
+    add_to_watchlist, check_watchlist, list_upcoming_expirations, list_watchlist,
+    remove_from_watchlist,
+};
+use commands::whois::{check_availability, lookup_ip, lookup_whois, lookup_whois_bulk};
+use state::AppState;
+use tauri::Manager;
+use tracing_subscriber::EnvFilter;
+
+// Print tracing spans and events to stderr, filtered by RUST_LOG (warnings by default),
+// e.g. RUST_LOG=d_dns_debugger_lib=debug to see every lookup and how long it took
+pub fn init_tracing() {
+    let filter = EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("warn"));
+    let _ = tracing_subscriber::fmt()
+        .with_env_filter(filter)
+        .with_writer(std::io::stderr)
+        .try_init();
+}
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
+    init_tracing();
     tauri::Builder::default()
         .plugin(tauri_plugin_opener::init())
+        .plugin(tauri_plugin_notification::init())
+        .setup(|app| {
+            // Load persisted settings, scan history, saved domains and watchlist before
+            // any command runs
+            let settings = state::settings::load(app.handle());
+            let history = state::history::load(app.handle());
+            let saved_domains = state::saved_domains::load(app.handle());
+            let watchlist = state::watchlist::load(app.handle());
+            app.manage(
+                AppState::with_settings(settings)
+                    .with_history(history)
+                    .with_saved_domains(saved_domains)
+                    .with_watchlist(watchlist),
+            );
+            tauri::async_runtime::spawn(commands::watchlist::watch_expiries(app.handle().clone()));
+            Ok(())
+        })
         .invoke_handler(tauri::generate_handler![
             query_dns,
             query_dns_multiple,
+            enumerate_records,
+            clear_dns_cache,
+            benchmark_resolvers,
+            detect_load_balancing,
+            check_fcrdns,
             validate_dnssec,
+            validate_record,
+            check_delegation,
+            compare_nameservers,
+            check_nameserver_diversity,
+            check_open_resolvers,
+            check_dns_hygiene,
+            check_ttls,
             get_certificate,
+            lookup_ct_history,
+            scan_tls_ports,
             lookup_whois,
+            lookup_whois_bulk,
+            lookup_ip,
+            lookup_reverse_ip,
+            check_availability,
+            scan_typosquats,
             fetch_http,
+            check_hsts,
+            fetch_well_known,
+            check_canonical,
+            ping_host,
+            trace_route,
+            scan_ports,
+            check_sshfp,
+            fetch_email_config,
+            check_blacklists,
+            analyze_dmarc_report,
+            check_autodiscovery,
+            verify_dkim,
+            analyze_domain,
+            compare_domains,
+            export_report,
+            import_domains,
+            scan_domains,
+            list_history,
+            get_scan,
+            delete_scan,
+            diff_scans,
+            add_to_watchlist,
+            remove_from_watchlist,
+            list_watchlist,
+            check_watchlist,
+            list_upcoming_expirations,
+            list_jobs,
+            cancel_job,
+            list_command_logs,
+            clear_command_logs,
+            export_command_logs,
+            get_performance_stats,
+            environment_doctor,
+            save_domain,
+            tag_saved_domain,
+            remove_saved_domain,
+            list_saved_domains,
+            save_session,
+            load_session,
+            get_settings,
+            update_settings,
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
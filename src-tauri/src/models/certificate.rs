@@ -1,3 +1,4 @@
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -8,11 +9,60 @@ pub struct CertificateInfo {
     pub version: i32,
     pub not_before: String,
     pub not_after: String,
+    #[serde(default)]
+    pub not_before_at: Option<DateTime<Utc>>, // Parsed not_before
+    #[serde(default)]
+    pub not_after_at: Option<DateTime<Utc>>, // Parsed not_after
+    #[serde(default)]
+    pub days_until_expiry: Option<i64>, // Negative once expired
     pub subject_alternative_names: Vec<String>,
     pub public_key_algorithm: String,
     pub public_key_size: Option<u32>,
     pub signature_algorithm: String,
     pub fingerprint_sha256: String,
+    #[serde(default)]
+    pub key_usage: Vec<String>, // e.g. "digitalSignature", "keyCertSign"
+    #[serde(default)]
+    pub extended_key_usage: Vec<String>, // e.g. "serverAuth", or the OID for unnamed purposes
+    #[serde(default)]
+    pub is_ca: bool, // basicConstraints CA flag
+    #[serde(default)]
+    pub path_length: Option<u32>, // basicConstraints pathLenConstraint
+    #[serde(default)]
+    pub embedded_scts: usize, // Certificate Transparency SCTs in the certificate itself
+    #[serde(default)]
+    pub valid_for_server_auth: bool, // KU/EKU/CA flag allow use as a TLS server certificate
+    #[serde(default)]
+    pub role: CertificateRole, // Position in the presented chain
+    #[serde(default)]
+    pub self_signed: bool, // Issuer and subject are the same name
+    #[serde(skip)]
+    pub der: Vec<u8>, // Raw encoding for in-process checks such as DANE; not sent to the frontend
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CertificateRole {
+    #[default]
+    Leaf,
+    Intermediate,
+    Root, // Self-signed CA at the end of the chain
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CertificateWarningKind {
+    SelfSignedLeaf,
+    WeakSignature, // SHA-1 or MD5
+    LongValidity,  // Leaf valid for more than 398 days
+}
+
+// Problem with one element of the chain, for the UI to badge the right certificate
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CertificateWarning {
+    pub index: usize, // Position in certificate_chain.certificates
+    pub kind: CertificateWarningKind,
+    pub message: String,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -30,12 +80,119 @@ pub struct CertificateChain {
     pub certificates: Vec<CertificateInfo>,
     pub is_valid: bool,
     pub validation_errors: Vec<String>,
+    #[serde(default)]
+    pub trust_anchor: Option<String>, // Root the chain was validated against
+}
+
+// Leaf certificate presented by one address of a load-balanced host
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IpCertificate {
+    pub ip: String,
+    pub serial_number: Option<String>,
+    pub fingerprint_sha256: Option<String>,
+    pub not_after: Option<String>,
+    pub matches_primary: bool, // Same certificate as the connection made by hostname
+    pub error: Option<String>,
+}
+
+// One certificate logged to Certificate Transparency (precertificate and final
+// certificate are merged into a single entry)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CtLogEntry {
+    pub id: u64, // crt.sh certificate ID
+    pub issuer: String,
+    pub common_name: String,
+    pub names: Vec<String>, // SANs
+    pub serial_number: String,
+    pub not_before: String,
+    pub not_after: String,
+    pub logged_at: String, // Earliest log entry timestamp
+    pub expired: bool,
+}
+
+// Number of logged certificates per issuing CA
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CtIssuer {
+    pub name: String,
+    pub certificates: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CtHistory {
+    pub domain: String,
+    pub include_subdomains: bool,
+    pub entries: Vec<CtLogEntry>, // Newest first
+    pub issuers: Vec<CtIssuer>,   // Most certificates first
+    pub warnings: Vec<String>,
+    pub raw_output: Option<String>,
+}
+
+// TLS status of one port in a multi-port scan
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PortTlsResult {
+    pub port: u16,
+    pub starttls: Option<StartTls>, // Used for well-known plaintext mail ports
+    pub reachable: bool,            // TCP connection accepted
+    pub tls_ok: bool,               // Handshake completed and a certificate was presented
+    pub common_name: Option<String>,
+    pub issuer: Option<String>,
+    pub not_after: Option<String>,
+    pub days_until_expiry: Option<i64>,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TlsPortScan {
+    pub host: String,
+    pub results: Vec<PortTlsResult>, // In the order the ports were requested
+    pub total_time: f64,
+}
+
+// Plaintext protocols that upgrade to TLS with a STARTTLS-style command
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum StartTls {
+    Smtp, // EHLO + STARTTLS (ports 25, 587)
+    Imap, // STARTTLS (port 143)
+    Pop3, // STLS (port 110)
+}
+
+impl StartTls {
+    // Protocol that needs an upgrade on a well-known plaintext port
+    pub fn for_port(port: u16) -> Option<StartTls> {
+        match port {
+            25 | 587 => Some(StartTls::Smtp),
+            143 => Some(StartTls::Imap),
+            110 => Some(StartTls::Pop3),
+            _ => None,
+        }
+    }
+
+    pub fn default_port(&self) -> u16 {
+        match self {
+            StartTls::Smtp => 25,
+            StartTls::Imap => 143,
+            StartTls::Pop3 => 110,
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TlsInfo {
     pub host: String,
     pub port: u16,
+    #[serde(default)]
+    pub starttls: Option<StartTls>, // None for implicit TLS
+    #[serde(default)]
+    pub alpn_protocol: Option<String>, // "h2" or "http/1.1"; None if the server ignored ALPN
+    #[serde(default)]
+    pub supports_http2: bool, // Server selected h2
     pub certificate_chain: CertificateChain,
+    #[serde(default)]
+    pub per_ip: Vec<IpCertificate>, // Leaf served by each address, when the host has several
+    #[serde(default)]
+    pub warnings: Vec<String>, // e.g. the queried host isn't covered by the leaf's SANs
+    #[serde(default)]
+    pub certificate_warnings: Vec<CertificateWarning>, // Per-certificate findings, also listed in warnings
     pub raw_output: Option<String>,
 }
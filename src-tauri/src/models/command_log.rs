@@ -1,4 +1,6 @@
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
+use std::time::Duration;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CommandLog {
@@ -9,6 +11,10 @@ pub struct CommandLog {
     pub exit_code: i32,
     pub duration: f64, // in milliseconds
     pub domain: Option<String>,
+    #[serde(default)]
+    pub throttled_ms: Option<f64>, // Time spent waiting on the rate limiter first, if any
+    #[serde(default = "Utc::now")]
+    pub logged_at: DateTime<Utc>,
 }
 
 impl CommandLog {
@@ -29,6 +35,80 @@ impl CommandLog {
             exit_code,
             duration,
             domain,
+            throttled_ms: None,
+            logged_at: Utc::now(),
         }
     }
+
+    // What the command did, for grouping its timing: the record type and nameserver
+    // for dig and the tools used in its place, e.g. "DS @a.gtld-servers.net", and the
+    // server for whois
+    pub fn operation(&self) -> Option<String> {
+        match self.tool.as_str() {
+            "dig" | "kdig" | "drill" | "host" => {
+                let record_type = self.args.iter().find(|a| {
+                    a.starts_with(|c: char| c.is_ascii_uppercase())
+                        && a.chars()
+                            .all(|c| c.is_ascii_uppercase() || c.is_ascii_digit())
+                })?;
+                Some(match self.args.iter().find(|a| a.starts_with('@')) {
+                    Some(server) => format!("{} {}", record_type, server),
+                    None => record_type.clone(),
+                })
+            }
+            "whois" => self
+                .args
+                .iter()
+                .position(|a| a == "-h")
+                .and_then(|i| self.args.get(i + 1))
+                .cloned(),
+            _ => None,
+        }
+    }
+
+    // Record a wait on the rate limiter before the command ran
+    pub fn with_throttled(mut self, waited: Duration) -> Self {
+        if !waited.is_zero() {
+            self.throttled_ms = Some(waited.as_secs_f64() * 1000.0);
+        }
+        self
+    }
+}
+
+// Which stored logs list_command_logs and export_command_logs return; unset fields match all
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CommandLogFilter {
+    pub domain: Option<String>, // Case-insensitive, without a trailing dot
+    pub tool: Option<String>,   // "dig", "whois", "tls" or "curl"
+    pub since: Option<DateTime<Utc>>,
+    pub until: Option<DateTime<Utc>>,
+    pub limit: Option<usize>, // Newest first
+}
+
+impl CommandLogFilter {
+    pub fn matches(&self, log: &CommandLog) -> bool {
+        let same_name = |a: &str, b: &str| {
+            a.trim_end_matches('.')
+                .eq_ignore_ascii_case(b.trim().trim_end_matches('.'))
+        };
+        self.domain
+            .as_deref()
+            .is_none_or(|domain| log.domain.as_deref().is_some_and(|d| same_name(d, domain)))
+            && self
+                .tool
+                .as_deref()
+                .is_none_or(|tool| log.tool.eq_ignore_ascii_case(tool.trim()))
+            && self.since.is_none_or(|since| log.logged_at >= since)
+            && self.until.is_none_or(|until| log.logged_at <= until)
+    }
+}
+
+// The file export_command_logs writes, to attach to a bug report
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CommandLogExport {
+    pub app_version: String,
+    pub exported_at: DateTime<Utc>,
+    pub filter: CommandLogFilter,
+    pub logs: Vec<CommandLog>, // Oldest first, as they ran
 }
@@ -1,3 +1,4 @@
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -8,50 +9,158 @@ pub struct DnsRecord {
     pub ttl: u32,
 }
 
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct DnsHeader {
+    pub rcode: String,             // NOERROR, NXDOMAIN, SERVFAIL, REFUSED
+    pub authoritative: bool,       // AA
+    pub truncated: bool,           // TC
+    pub recursion_available: bool, // RA
+    pub authenticated_data: bool,  // AD
+    pub answer_count: u32,
+    pub authority_count: u32,
+    pub additional_count: u32,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DnsResponse {
     pub records: Vec<DnsRecord>,
     pub query_time: f64,
     pub resolver: String,
+    #[serde(default)]
+    pub header: Option<DnsHeader>, // None when dig printed no header (e.g., timeout)
+    #[serde(default)]
+    pub transport: String, // "udp", "tcp" (after a retry on a truncated answer) or "https"
+    #[serde(default)]
+    pub server: Option<String>, // Address that answered the final query
+    #[serde(default)]
+    pub ip_version: Option<String>, // "ipv4" or "ipv6", the family used to reach server
+    #[serde(default)]
+    pub from_cache: bool, // Served from the in-memory DNS cache without a new query
     pub raw_output: Option<String>,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecordTypeResult {
+    pub record_type: String,
+    pub records: Vec<DnsRecord>,
+    pub query_time: f64,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecordEnumeration {
+    pub domain: String,
+    pub results: Vec<RecordTypeResult>, // One entry per queried type, in display order
+    #[serde(default)]
+    pub hosting: Option<DnsHosting>, // None without NS records at the name
+    pub total_time: f64,
+}
+
+// A DNS host recognized from its nameserver hostnames
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DnsProvider {
+    pub name: String,
+    pub nameservers: Vec<String>,
+}
+
+// Who serves a zone, fingerprinted from its NS records
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DnsHosting {
+    pub providers: Vec<DnsProvider>, // Recognized providers, in nameserver order
+    pub unrecognized: Vec<String>,   // Nameservers of no provider in the table
+    // Nameservers span more than one provider; unrecognized ones count as one
+    // provider per registrable domain
+    pub multi_provider: bool,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DnskeyRecord {
     pub flags: u16,
     pub protocol: u8,
     pub algorithm: u8,
+    #[serde(default)]
+    pub algorithm_name: String, // e.g. "RSASHA256", "ECDSAP256SHA256"
     pub public_key: String,
     pub key_tag: u16,
+    #[serde(default)]
+    pub key_size: Option<u32>, // Modulus bits for RSA keys, curve size otherwise
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DsRecord {
     pub key_tag: u16,
     pub algorithm: u8,
+    #[serde(default)]
+    pub algorithm_name: String,
     pub digest_type: u8,
+    #[serde(default)]
+    pub digest_type_name: String, // "SHA-1", "SHA-256", "SHA-384"
     pub digest: String,
+    #[serde(default)]
+    pub digest_verified: Option<bool>, // Digest matches a child DNSKEY (None = not checked/unsupported)
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct RrsigRecord {
     pub type_covered: String,
     pub algorithm: u8,
+    #[serde(default)]
+    pub algorithm_name: String,
     pub labels: u8,
     pub original_ttl: u32,
     pub signature_expiration: String,
     pub signature_inception: String,
+    #[serde(default)]
+    pub expires_at: Option<DateTime<Utc>>, // Parsed signature_expiration
+    #[serde(default)]
+    pub inception_at: Option<DateTime<Utc>>, // Parsed signature_inception
     pub key_tag: u16,
     pub signer_name: String,
     pub signature: String,
 }
 
+// A DS record in the parent zone and the child DNSKEY it points to
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DsLink {
+    pub child_zone: String,
+    pub ds_key_tag: u16,
+    pub digest_type: u8,
+    pub dnskey_key_tag: Option<u16>, // Child key whose digest matches, None if no key matches
+    pub verified: Option<bool>, // None when the digest type is unsupported (key tag match only)
+}
+
+// An RRSIG and the zone DNSKEY that made it
 #[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SignatureLink {
+    pub type_covered: String,
+    pub key_tag: u16,
+    pub signed_by: Option<u16>, // Key tag of the matching DNSKEY, None if the zone has no such key
+    pub key_flags: Option<u16>, // 257 = KSK, 256 = ZSK
+    pub valid_now: bool,        // Inside the signature's inception/expiration window
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct ZoneData {
     pub zone_name: String,
     pub dnskey_records: Vec<DnskeyRecord>,
     pub ds_records: Vec<DsRecord>,
     pub rrsig_records: Vec<RrsigRecord>,
+    #[serde(default)]
+    pub status: String, // SECURE, INSECURE, BOGUS, INDETERMINATE
+    #[serde(default)]
+    pub ds_links: Vec<DsLink>, // This zone's DS records matched against the child zone's DNSKEYs
+    #[serde(default)]
+    pub signature_links: Vec<SignatureLink>,
+}
+
+// CDS/CDNSKEY records published by the child zone and how they compare to the parent DS set
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RolloverStatus {
+    pub state: String, // NONE, IN_SYNC, PENDING, DELETE_REQUESTED, INVALID
+    pub cds_records: Vec<DsRecord>,
+    pub cdnskey_records: Vec<DnskeyRecord>,
+    pub missing_from_parent: Vec<String>, // Requested by CDS/CDNSKEY, not yet in the parent DS set
+    pub pending_removal: Vec<String>,     // In the parent DS set, no longer requested
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -59,4 +168,260 @@ pub struct DnssecValidation {
     pub status: String, // SECURE, INSECURE, BOGUS, INDETERMINATE
     pub chain: Vec<ZoneData>,
     pub warnings: Vec<String>,
+    #[serde(default)]
+    pub rollover: Option<RolloverStatus>, // None when the target zone isn't signed or CDS lookups failed
+}
+
+// DNSSEC coverage of a single RRset (A, AAAA, MX, TXT, ...) within a validated zone
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RrsetValidation {
+    pub domain: String,
+    pub record_type: String,
+    pub zone: String,   // Enclosing zone whose keys should sign the RRset
+    pub status: String, // SECURE, INSECURE, BOGUS, INDETERMINATE
+    pub records: Vec<DnsRecord>,
+    pub rrsig_records: Vec<RrsigRecord>, // RRSIGs covering record_type
+    pub signing_key_tags: Vec<u16>,      // Zone keys with a usable signature over the RRset
+    pub chain: DnssecValidation,         // Chain of trust down to the zone
+    pub warnings: Vec<String>,
+}
+
+// Payload of the "dnssec-progress" event emitted while validate_dnssec builds the chain
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DnssecProgress {
+    pub domain: String,
+    pub zone: String,      // Zone currently being queried ("." for root)
+    pub step: String,      // "dnskey", "ds", "evaluating", or "complete"
+    pub zone_index: usize, // 1-based position of zone in the chain
+    pub zone_count: usize, // Root plus one zone per label
+    pub elapsed: f64,      // Seconds since validation started
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DelegationCheck {
+    pub domain: String,
+    pub parent_zone: String,
+    pub parent_server: String,
+    pub parent_nameservers: Vec<String>, // NS set published by the registry
+    pub child_server: Option<String>,
+    pub child_nameservers: Vec<String>, // NS set served by the zone itself
+    pub missing_from_child: Vec<String>, // Delegated at parent, not listed by zone
+    pub missing_from_parent: Vec<String>, // Listed by zone, not delegated at parent
+    pub is_consistent: bool,
+    pub nameservers: Vec<NameserverHealth>, // Every nameserver of either set
+    pub warnings: Vec<String>,
+}
+
+// How a nameserver answered a direct SOA query for the zone
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NameserverStatus {
+    Authoritative,
+    Lame,        // Answered, but not authoritatively for the zone
+    Refused,     // REFUSED: the server isn't configured for the zone
+    ServFail,    // SERVFAIL, e.g. a zone that failed to load
+    Timeout,     // No answer
+    Unreachable, // Couldn't be resolved or queried
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NameserverHealth {
+    pub nameserver: String,
+    pub status: NameserverStatus,
+    pub rcode: Option<String>,
+    pub query_time: f64,
+    pub error: Option<String>,
+}
+
+// A nameserver's answer to a recursive query for a name outside its zones
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecursionProbe {
+    pub nameserver: String,
+    pub probe_name: String,
+    pub open: bool, // Resolved the name for us: an open recursive resolver
+    pub recursion_available: bool, // RA flag set, whatever the answer
+    pub rcode: Option<String>,
+    pub answers: Vec<String>,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OpenResolverCheck {
+    pub domain: String,
+    pub zone: String,
+    pub nameservers: Vec<RecursionProbe>,
+    pub open_resolvers: Vec<String>,
+    pub warnings: Vec<String>,
+}
+
+// Where one nameserver address sits on the network
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NameserverAddress {
+    pub address: String,
+    pub subnet: String,   // Covering /24 (IPv4) or /48 (IPv6)
+    pub asn: Option<u32>, // Origin AS; None when not announced or the lookup failed
+    pub as_name: Option<String>,
+    pub announced_prefix: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NameserverNetwork {
+    pub nameserver: String,
+    pub addresses: Vec<NameserverAddress>,
+    pub reachable: bool, // Answered an SOA query for the zone
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NameserverDiversity {
+    pub domain: String,
+    pub zone: String, // Zone whose NS set was audited
+    pub nameservers: Vec<NameserverNetwork>,
+    pub asns: Vec<u32>,       // Distinct origin ASes across all addresses
+    pub subnets: Vec<String>, // Distinct /24 and /48 networks
+    pub reachable: usize,
+    pub is_diverse: bool, // Two or more answering, not all in one AS or subnet
+    pub warnings: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ResolverBenchmark {
+    pub resolver: String,
+    pub queries: usize,
+    pub failures: usize,     // Errors, timeouts, SERVFAIL and REFUSED answers
+    pub failure_rate: f64,   // 0.0 - 1.0
+    pub min_ms: Option<f64>, // None when every query failed
+    pub avg_ms: Option<f64>,
+    pub p95_ms: Option<f64>,
+    pub last_error: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BenchmarkReport {
+    pub domains: Vec<String>,
+    pub rounds: u32,
+    pub results: Vec<ResolverBenchmark>, // Fastest average first, all-failed resolvers last
+    pub total_time: f64,
+}
+
+// A PTR name of an address and what it resolves to in turn
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PtrName {
+    pub name: String,
+    pub addresses: Vec<String>,
+    pub confirmed: bool, // Resolves back to the address
+    pub error: Option<String>,
+}
+
+// Forward-confirmed reverse DNS of one address
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReverseDnsCheck {
+    pub address: String,
+    pub hosts: Vec<String>, // Names of the domain's that resolve to the address
+    pub mail: bool,         // An address of one of the domain's MX hosts
+    pub ptr_names: Vec<PtrName>,
+    pub confirmed: bool, // At least one PTR name resolves back to the address
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FcrdnsReport {
+    pub domain: String,
+    pub checks: Vec<ReverseDnsCheck>,
+    pub all_confirmed: bool,
+    pub warnings: Vec<String>,
+}
+
+// One resolver's A or AAAA answer in one round of detect_load_balancing
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AddressSample {
+    pub resolver: String,
+    pub round: u32, // From 1
+    pub record_type: String,
+    pub addresses: Vec<String>, // In answer order
+    pub ttl: Option<u32>,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LoadBalancingReport {
+    pub domain: String,
+    pub resolvers: Vec<String>,
+    pub rounds: u32,
+    pub samples: Vec<AddressSample>,
+    pub addresses: Vec<String>,   // Every address seen
+    pub round_robin: bool,        // Same addresses in rotating order
+    pub changes_over_time: bool,  // One resolver got different addresses between rounds
+    pub resolver_dependent: bool, // Resolvers got different addresses (GeoDNS, CDN steering)
+    pub min_ttl: Option<u32>,
+    pub findings: Vec<String>,
+    pub total_time: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NameserverAnswer {
+    pub nameserver: String,
+    pub records: Vec<DnsRecord>,
+    pub query_time: f64,
+    pub rcode: Option<String>,
+    pub authoritative: bool,    // AA flag set
+    pub matches_majority: bool, // Same record values as most other nameservers
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NameserverComparison {
+    pub domain: String,
+    pub record_type: String,
+    pub zone: String, // Zone whose NS set was queried
+    pub answers: Vec<NameserverAnswer>,
+    pub is_consistent: bool,
+    pub differences: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HygieneIssue {
+    pub severity: String, // "error" or "warning"
+    pub code: String,     // Stable identifier, e.g. "apex_cname"
+    pub message: String,
+}
+
+// A zone's SOA record (RFC 1035 section 3.3.13), times in seconds
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SoaRecord {
+    pub mname: String, // Primary nameserver
+    pub rname: String, // Responsible mailbox, first dot meaning @
+    pub serial: u32,
+    pub refresh: u32, // How often secondaries check the serial
+    pub retry: u32,   // How soon they check again after a failed refresh
+    pub expire: u32,  // How long they keep answering without reaching the primary
+    pub minimum: u32, // Negative caching TTL (RFC 2308)
+    pub ttl: u32,     // Of the SOA record itself
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecordTtl {
+    pub record_type: String,
+    pub ttl: u32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TtlReport {
+    pub domain: String,
+    pub zone: String,
+    pub nameserver: Option<String>, // Authoritative server the records were read from
+    pub soa: Option<SoaRecord>,
+    pub negative_ttl: Option<u32>, // Lower of the SOA's TTL and minimum
+    pub ttls: Vec<RecordTtl>,      // Lowest TTL of each type at the name
+    pub issues: Vec<HygieneIssue>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DnsHygieneReport {
+    pub domain: String,
+    pub zone: String,
+    pub is_apex: bool,
+    pub nameserver: Option<String>, // Authoritative server the records were read from
+    pub record_types: Vec<String>,  // Types present at the name
+    pub issues: Vec<HygieneIssue>,
 }
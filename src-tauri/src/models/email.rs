@@ -0,0 +1,258 @@
+use serde::{Deserialize, Serialize};
+
+// STARTTLS on port 25 of one MX host and the certificate it presented
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct MxTls {
+    pub starttls: bool,          // Upgrade completed and a certificate was presented
+    pub certificate_valid: bool, // Chains to a trusted root, covers the host and hasn't expired
+    pub hostname_matches: bool,
+    pub common_name: Option<String>,
+    pub issuer: Option<String>,
+    pub not_after: Option<String>,
+    pub days_until_expiry: Option<i64>, // Negative once expired
+    pub validation_errors: Vec<String>,
+    pub warnings: Vec<String>,
+    pub error: Option<String>, // Connection or STARTTLS failure
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MxHost {
+    pub priority: u16,
+    pub hostname: String,
+    pub ips: Vec<String>,         // A and AAAA
+    pub cname: Option<String>,    // Target when the hostname is an alias, which MX forbids
+    pub tls: Option<MxTls>,       // None when the host has no address to connect to
+    pub dane: Option<DaneStatus>, // None when the host publishes no TLSA records
+}
+
+// One TLSA record at _25._tcp.<mx-host> (RFC 6698 section 2.1)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TlsaRecord {
+    pub usage: u8,             // 2 DANE-TA or 3 DANE-EE; 0 and 1 are unusable for SMTP
+    pub selector: u8,          // 0 full certificate, 1 public key
+    pub matching_type: u8,     // 0 exact, 1 SHA-256, 2 SHA-512
+    pub data: String,          // Lowercase hex
+    pub matched: bool,         // Matches the certificate presented over STARTTLS
+    pub error: Option<String>, // Why the record can't be used
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DaneStatus {
+    pub records: Vec<TlsaRecord>,
+    pub authenticated: bool, // Resolver validated the answer with DNSSEC (AD flag)
+    pub valid: bool,         // Authenticated and a usable record matched
+    pub warnings: Vec<String>,
+}
+
+// An include: or redirect= target pulled into the SPF evaluation
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SpfInclude {
+    pub domain: String,
+    pub via: String, // "include" or "redirect"
+    pub record: Option<String>,
+    pub depth: u32, // 1 for targets named by the domain's own record
+}
+
+// SPF policy expanded through its includes (RFC 7208)
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SpfEvaluation {
+    pub record: Option<String>,
+    pub lookup_count: u32, // Terms that cost a DNS lookup, across includes (limit 10; counting stops at 11)
+    pub void_lookup_count: u32, // Lookups that found nothing (limit 2)
+    pub authorized: Vec<String>, // IP addresses and ranges allowed to send
+    pub includes: Vec<SpfInclude>, // Depth-first, in evaluation order
+    pub all: Option<String>, // Catch-all of the policy, e.g. "-all" or "~all"
+    pub perm_error: Option<String>, // Receivers treat the whole policy as broken
+    pub warnings: Vec<String>,
+}
+
+// DKIM public key published at <selector>._domainkey.<domain>
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DkimRecord {
+    pub selector: String,
+    pub record: String,
+    pub key_type: String, // k=, rsa unless stated
+    pub testing: bool,    // t=y: receivers shouldn't act on failures
+    pub revoked: bool,    // Empty p=: the key has been withdrawn
+    pub source: String,   // "custom", "settings", "provider" or "common": why it was tried
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct DkimLookup {
+    pub provider: Option<String>, // Mail provider recognized from the MX hosts
+    pub tried: Vec<String>,       // Every selector queried, in order
+    pub records: Vec<DkimRecord>,
+}
+
+// One DKIM-Signature header of a message and whether it verifies (RFC 6376)
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct DkimSignatureCheck {
+    pub domain: String,
+    pub selector: String,
+    pub algorithm: String,             // a=, e.g. "rsa-sha256"
+    pub canonicalization: String,      // c=, header/body
+    pub signed_headers: Vec<String>,   // h=, in signing order
+    pub body_length: Option<u64>,      // l=: only this much of the body is covered
+    pub signed_at: Option<String>,     // t=, RFC 3339
+    pub expires_at: Option<String>,    // x=
+    pub aligned: bool,                 // d= shares the organizational domain of From
+    pub key_bits: Option<u32>,         // RSA modulus size
+    pub body_hash_valid: Option<bool>, // None when only headers were given
+    pub valid: bool,
+    pub error: Option<String>, // Why verification failed
+    pub warnings: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DkimVerification {
+    pub from_domain: Option<String>,
+    pub signatures: Vec<DkimSignatureCheck>, // In header order
+    pub warnings: Vec<String>,
+}
+
+// One rua= or ruf= destination of a DMARC record
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DmarcReportUri {
+    pub uri: String,
+    pub address: Option<String>, // mailto: address, without the size limit
+    pub max_size: Option<String>, // "!10m" suffix: reports larger than this aren't sent
+    pub external: bool,          // Outside the policy's organizational domain
+    pub authorized: Option<bool>, // External only: the destination publishes the verification record
+    pub error: Option<String>,
+}
+
+// The policy at _dmarc.<domain>, or at the organizational domain (RFC 7489 6.6.3)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DmarcRecord {
+    pub domain: String, // Where the record was found
+    pub record: String,
+    pub policy: Option<String>,       // p=: none, quarantine or reject
+    pub subdomain_policy: String,     // sp=, p= unless stated
+    pub percentage: u8,               // pct=: share of failing mail the policy applies to
+    pub dkim_alignment: String,       // adkim=: "r" (relaxed) or "s" (strict)
+    pub spf_alignment: String,        // aspf=
+    pub failure_options: Vec<String>, // fo=: when failure reports are sent
+    pub report_formats: Vec<String>,  // rf=
+    pub report_interval: u32,         // ri=: seconds between aggregate reports
+    pub aggregate_reports: Vec<DmarcReportUri>, // rua=
+    pub failure_reports: Vec<DmarcReportUri>, // ruf=
+    pub errors: Vec<String>,          // Syntax receivers reject or ignore
+    pub warnings: Vec<String>,
+}
+
+// Messages one source IP sent in a DMARC aggregate report
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct DmarcSource {
+    pub source_ip: String,
+    pub messages: u64,
+    pub dkim_aligned: u64, // policy_evaluated dkim=pass: DKIM passed for the From domain
+    pub spf_aligned: u64,  // policy_evaluated spf=pass
+    pub dmarc_pass: u64,   // Either of the two
+    pub quarantined: u64,
+    pub rejected: u64,
+    pub header_from: Vec<String>,  // From domains seen for this source
+    pub dkim_domains: Vec<String>, // d= domains of the signatures checked
+    pub spf_domains: Vec<String>,  // MAIL FROM (or HELO) domains checked
+}
+
+// An aggregate (rua) report a receiver sent about one domain (RFC 7489 appendix C)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DmarcAggregateReport {
+    pub org_name: String, // Receiver that sent the report
+    pub report_id: String,
+    pub email: Option<String>,
+    pub begin: Option<String>, // RFC 3339
+    pub end: Option<String>,
+    pub domain: String,         // policy_published
+    pub policy: Option<String>, // p= the receiver applied
+    pub subdomain_policy: Option<String>,
+    pub percentage: Option<u8>,
+    pub sources: Vec<DmarcSource>, // Most messages first
+    pub total_messages: u64,
+    pub dmarc_pass: u64,
+    pub warnings: Vec<String>,
+}
+
+// One SRV record mail clients use to find their servers (RFC 6186, RFC 8314)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MailSrvRecord {
+    pub service: String, // e.g. "_imaps._tcp"
+    pub priority: u16,
+    pub weight: u16,
+    pub port: u16,
+    pub target: String, // Empty for "." (the service is not offered)
+    pub resolves: bool, // Target has A/AAAA records
+}
+
+// A server listed in an autoconfig file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AutoconfigServer {
+    pub protocol: String, // "imap", "pop3" or "smtp"
+    pub hostname: String,
+    pub port: Option<u16>,
+    pub socket_type: Option<String>, // "SSL", "STARTTLS" or "plain"
+}
+
+// An HTTP endpoint mail clients fetch their settings from
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AutoconfigEndpoint {
+    pub mechanism: String, // "autoconfig", "autoconfig (well-known)" or "autodiscover"
+    pub url: String,
+    pub status_code: Option<u16>,
+    pub configured: bool,
+    pub servers: Vec<AutoconfigServer>, // Parsed from autoconfig files
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AutodiscoveryReport {
+    pub domain: String,
+    pub srv_records: Vec<MailSrvRecord>,
+    pub endpoints: Vec<AutoconfigEndpoint>,
+    pub mechanisms: Vec<String>, // Those configured: "srv", "autoconfig", "autodiscover"
+    pub warnings: Vec<String>,
+}
+
+// One address or domain looked up on one blocklist
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BlacklistResult {
+    pub list: String,   // Zone, e.g. zen.spamhaus.org
+    pub target: String, // MX address or the domain
+    pub query: String,  // Name looked up, e.g. 2.0.0.127.zen.spamhaus.org
+    pub listed: bool,
+    pub codes: Vec<String>,     // A answers; the list's return codes say why
+    pub reason: Option<String>, // TXT answer, for listed targets
+    pub error: Option<String>,  // Lookup failed or the list refused to answer
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BlacklistReport {
+    pub domain: String,
+    pub ips: Vec<String>,              // MX addresses checked
+    pub results: Vec<BlacklistResult>, // Addresses first, then the domain
+    pub listed_count: usize,
+    pub warnings: Vec<String>,
+}
+
+// A mail or sending provider the domain's records point to
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MailProviderMatch {
+    pub name: String,
+    pub evidence: Vec<String>, // e.g. "MX aspmx.l.google.com", "SPF include:_spf.google.com"
+    pub missing: Vec<String>,  // Records the provider asks for that weren't found
+    pub outdated: Vec<String>, // Records the provider has since replaced
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EmailConfig {
+    pub domain: String,
+    pub mx_records: Vec<MxHost>, // Lowest preference value (tried first) first
+    pub null_mx: bool,           // "0 ." (RFC 7505): the domain accepts no mail
+    pub implicit_mx: Option<MxHost>, // Without MX records, senders deliver to the domain itself
+    pub encrypted: bool,         // Every MX offers STARTTLS with a valid certificate
+    pub spf: Option<SpfEvaluation>, // None when the domain publishes no SPF record
+    pub dkim: DkimLookup,        // DKIM selectors can't be listed, only guessed
+    pub dmarc: Option<DmarcRecord>, // None when neither the domain nor its organizational domain has one
+    pub providers: Vec<MailProviderMatch>,
+    pub warnings: Vec<String>,
+}
@@ -0,0 +1,40 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ToolRole {
+    Required, // Some features fail without it (or a fallback)
+    Fallback, // Used in place of a missing required tool
+    Optional, // Only for re-running logged commands by hand
+}
+
+// One external program the doctor looked for on the PATH
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ToolCheck {
+    pub name: String,
+    pub role: ToolRole,
+    pub found: bool,
+    pub path: Option<String>,
+    pub version: Option<String>, // First line the tool prints for its version
+    pub purpose: String,         // What the app uses it for
+}
+
+// A feature that won't work because a required tool is missing
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DegradedFeature {
+    pub feature: String,
+    pub missing: Vec<String>, // Tools it needs
+    pub impact: String,       // What happens instead, e.g. a section left out of reports
+}
+
+// Returned by environment_doctor
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EnvironmentReport {
+    pub os: String,               // e.g. "macos aarch64"
+    pub dns_tool: Option<String>, // The program DNS queries run through; None without one
+    pub tools: Vec<ToolCheck>,
+    pub degraded: Vec<DegradedFeature>,
+    pub ready: bool, // Every required tool was found
+    pub checked_at: DateTime<Utc>,
+}
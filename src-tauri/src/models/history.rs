@@ -0,0 +1,69 @@
+use crate::models::report::DomainReport;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+// One saved analyze_domain run, as listed by list_history
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScanSummary {
+    pub id: i64,
+    pub domain: String,
+    pub scanned_at: DateTime<Utc>,
+    pub score: Option<u8>, // Health score, when the scan was graded
+    pub grade: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StoredScan {
+    pub id: i64,
+    pub domain: String,
+    pub scanned_at: DateTime<Utc>,
+    pub report: DomainReport,
+}
+
+// A DNS record found in only one of two scans, or whose TTL changed
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecordChange {
+    pub change: String, // "added", "removed" or "ttl"
+    pub record_type: String,
+    pub name: String,
+    pub value: String,
+    pub old_ttl: Option<u32>,
+    pub new_ttl: Option<u32>,
+}
+
+// A single value that differs between two scans; None where it was absent
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FieldChange {
+    pub field: String, // e.g. "registrar", "issuer", "grade"
+    pub old: Option<String>,
+    pub new: Option<String>,
+}
+
+// Redirects of one requested URL, one "<status> <url>" entry per hop, the final response last
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RedirectChange {
+    pub url: String,
+    pub old_chain: Vec<String>,
+    pub new_chain: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScanDiff {
+    pub domain: String,
+    pub from_id: Option<i64>,
+    pub to_id: Option<i64>,
+    pub from_scanned_at: DateTime<Utc>,
+    pub to_scanned_at: DateTime<Utc>,
+    pub records: Vec<RecordChange>, // Sorted by type, name and value
+    pub nameservers_added: Vec<String>,
+    pub nameservers_removed: Vec<String>,
+    pub certificate_renewed: bool, // A different leaf certificate is served
+    pub certificate: Vec<FieldChange>,
+    pub whois: Vec<FieldChange>,
+    pub whois_status_added: Vec<String>,
+    pub whois_status_removed: Vec<String>,
+    pub redirects: Vec<RedirectChange>,
+    pub health: Vec<FieldChange>, // Score and grade
+    pub skipped: Vec<String>,     // Sections missing from either scan, so not compared
+    pub changed: bool,
+}
@@ -1,21 +1,203 @@
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+
+// What fetch_http sends: a bare HEAD unless the caller builds a request
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HttpRequest {
+    pub method: String,
+    pub headers: Vec<(String, String)>, // Replace default headers of the same name
+    pub body: Option<String>,
+    pub max_body_kb: Option<u32>, // Capture the final response body up to this size
+}
+
+impl Default for HttpRequest {
+    fn default() -> Self {
+        HttpRequest {
+            method: "HEAD".to_string(),
+            headers: Vec::new(),
+            body: None,
+            max_body_kb: None,
+        }
+    }
+}
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct HttpResponse {
     pub url: String,
+    #[serde(default)]
+    pub method: String, // Of the final hop; 301/302/303 turn a POST into a GET
     pub status_code: u16,
     pub final_url: String,
     pub redirects: Vec<HttpRedirect>,
-    pub headers: HashMap<String, String>,
+    pub headers: Vec<(String, String)>, // In order received, repeats kept; names lowercase
     pub response_time: f64,
+    #[serde(default)]
+    pub timing: HttpTiming, // Phases of the final hop
+    #[serde(default)]
+    pub ip: Option<String>, // Address the final hop connected to
+    #[serde(default)]
+    pub warnings: Vec<String>, // HTTPS downgrades and hops to other domains
+    #[serde(default)]
+    pub cache: CacheAnalysis, // Of the final response
+    #[serde(default)]
+    pub body: Option<HttpBody>, // Only when the request asked for it
     pub raw_output: Option<String>,
 }
 
+// First value of a response header; names are matched lowercase
+pub fn header_value<'a>(headers: &'a [(String, String)], name: &str) -> Option<&'a str> {
+    headers
+        .iter()
+        .find(|(header, _)| header == name)
+        .map(|(_, value)| value.as_str())
+}
+
+// Every value of a response header, for repeatable ones such as Set-Cookie
+pub fn header_values<'a>(headers: &'a [(String, String)], name: &str) -> Vec<&'a str> {
+    headers
+        .iter()
+        .filter(|(header, _)| header == name)
+        .map(|(_, value)| value.as_str())
+        .collect()
+}
+
+// Captured body of the final response
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HttpBody {
+    pub content_type: Option<String>, // Without parameters, e.g. text/html
+    pub content_type_sniffed: bool,   // No Content-Type header; guessed from the bytes
+    pub charset: Option<String>,      // From the header, else <meta charset>
+    pub size: usize,                  // Bytes received
+    pub content_length: Option<u64>,  // As announced by the server
+    pub truncated: bool,              // Stopped at the size limit
+    pub text: String,                 // Decoded as UTF-8, invalid bytes replaced
+    pub meta_refresh: Option<String>, // Target of <meta http-equiv="refresh">
+}
+
+// Caching headers of a response and how long a shared cache (CDN) may reuse it
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CacheAnalysis {
+    pub cache_control: Vec<String>, // Directives as sent, lowercased
+    pub expires: Option<String>,
+    pub etag: Option<String>,
+    pub last_modified: Option<String>,
+    pub age: Option<u64>,
+    pub storable: bool,                  // No no-store or private
+    pub freshness_lifetime: Option<u64>, // Seconds, per RFC 9111 section 4.2.1
+    pub heuristic: bool,                 // Lifetime guessed from Last-Modified
+    pub remaining: Option<i64>,          // Lifetime minus Age; negative when stale
+    pub warnings: Vec<String>,           // Conflicting or ineffective directives
+}
+
+// Seconds spent in each phase of one request; tls is 0 for plain HTTP
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct HttpTiming {
+    pub dns: f64,
+    pub connect: f64,
+    pub tls: f64,
+    pub first_byte: f64, // From sending the request to the response headers
+    pub total: f64,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct HttpRedirect {
     pub from_url: String,
     pub to_url: String,
     pub status_code: u16,
     pub response_time: f64,
+    #[serde(default)]
+    pub headers: Vec<(String, String)>,
+    #[serde(default)]
+    pub timing: HttpTiming,
+    #[serde(default)]
+    pub ip: Option<String>, // Address this hop connected to
+}
+
+// Parsed Strict-Transport-Security header (RFC 6797)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HstsPolicy {
+    pub max_age: Option<u64>, // Seconds; the policy is invalid without it
+    pub include_subdomains: bool,
+    pub preload: bool,
+    pub errors: Vec<String>, // Malformed or repeated directives
+    pub raw: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HstsReport {
+    pub domain: String,
+    pub policy: Option<HstsPolicy>, // From the first HTTPS response, before any redirect
+    pub preload_status: Option<String>, // hstspreload.org: preloaded, pending, unknown...
+    pub preload_eligible: bool,
+    pub issues: Vec<String>,   // Preload requirements the site doesn't meet
+    pub warnings: Vec<String>, // Checks that could not be completed
+}
+
+// Group of robots.txt rules for one or more user agents (RFC 9309)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RobotsGroup {
+    pub user_agents: Vec<String>,
+    pub allow: Vec<String>,
+    pub disallow: Vec<String>,
+    pub crawl_delay: Option<f64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RobotsTxt {
+    pub url: String,
+    pub groups: Vec<RobotsGroup>,
+    pub sitemaps: Vec<String>,
+    pub blocks_all: bool, // "User-agent: *" is disallowed from "/"
+    pub raw: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Sitemap {
+    pub url: String,
+    pub is_index: bool,         // Lists other sitemaps rather than pages
+    pub url_count: usize,       // <loc> entries
+    pub locations: Vec<String>, // The first entries, as a sample
+    pub error: Option<String>,
+}
+
+// Vulnerability disclosure contacts (RFC 9116)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SecurityTxt {
+    pub url: String,
+    pub contact: Vec<String>,
+    pub expires: Option<String>,
+    pub encryption: Vec<String>,
+    pub policy: Vec<String>,
+    pub canonical: Vec<String>,
+    pub preferred_languages: Option<String>,
+    pub signed: bool, // OpenPGP cleartext signature
+    pub warnings: Vec<String>,
+    pub raw: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WellKnownReport {
+    pub domain: String,
+    pub robots: Option<RobotsTxt>,
+    pub sitemaps: Vec<Sitemap>, // Listed in robots.txt, else /sitemap.xml
+    pub security_txt: Option<SecurityTxt>,
+    pub warnings: Vec<String>,
+}
+
+// One of http(s)://domain and http(s)://www.domain, after following redirects
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CanonicalVariant {
+    pub url: String,
+    pub final_url: Option<String>,
+    pub status_code: Option<u16>,
+    pub redirects: usize,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CanonicalReport {
+    pub domain: String, // The apex
+    pub variants: Vec<CanonicalVariant>,
+    pub canonical_url: Option<String>, // Where most variants end up
+    pub converges: bool,               // All four end on the same URL
+    pub warnings: Vec<String>,
 }
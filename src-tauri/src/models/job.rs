@@ -0,0 +1,37 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum JobStatus {
+    Queued, // Waiting for a free slot
+    Running,
+    Completed,
+    Failed,
+    Cancelled,
+}
+
+impl JobStatus {
+    pub fn is_finished(&self) -> bool {
+        matches!(
+            self,
+            JobStatus::Completed | JobStatus::Failed | JobStatus::Cancelled
+        )
+    }
+}
+
+// A long-running command tracked by the job manager, sent with each `job-updated` event
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Job {
+    pub id: u64,
+    pub kind: String,   // Command that started it, e.g. "analyze_domain"
+    pub target: String, // Domain or host it runs for, or e.g. "25 domains"
+    pub status: JobStatus,
+    pub completed: usize, // Steps done; total is 0 until known
+    pub total: usize,
+    pub message: Option<String>, // Latest step, e.g. "dnssec" or "example.com"
+    pub created_at: DateTime<Utc>,
+    pub started_at: Option<DateTime<Utc>>,
+    pub finished_at: Option<DateTime<Utc>>,
+    pub error: Option<String>,
+}
@@ -1,5 +1,21 @@
 pub mod certificate;
 pub mod command_log;
 pub mod dns;
+pub mod email;
+pub mod environment;
+pub mod history;
 pub mod http;
+pub mod job;
+pub mod performance;
+pub mod ping;
+pub mod ports;
+pub mod report;
+pub mod reverse_ip;
+pub mod saved_domain;
+pub mod session;
+pub mod settings;
+pub mod ssh;
+pub mod traceroute;
+pub mod typosquat;
+pub mod watchlist;
 pub mod whois;
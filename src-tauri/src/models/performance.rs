@@ -0,0 +1,26 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+// Latency and failures of the commands run this session, from get_performance_stats
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PerformanceStats {
+    pub since: DateTime<Utc>, // When the app started collecting
+    pub operations: Vec<OperationStats>,
+}
+
+// One tool overall (operation None), or one kind of query it ran
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OperationStats {
+    pub tool: String,              // "dig", "whois", "tls" or "curl"
+    pub operation: Option<String>, // e.g. "DS @a.gtld-servers.net" for dig, the server for whois
+    pub count: u64,
+    pub failures: u64,     // Runs that exited non-zero or errored
+    pub failure_rate: f64, // 0.0 to 1.0
+    // Percentiles in milliseconds, over the most recent runs
+    pub mean_ms: f64,
+    pub p50_ms: f64,
+    pub p90_ms: f64,
+    pub p99_ms: f64,
+    pub max_ms: f64,
+    pub throttled_ms: f64, // Total time spent waiting on the rate limiter
+}
@@ -0,0 +1,34 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PingMethod {
+    Icmp, // Echo request and reply
+    Tcp,  // Time to a completed or refused connect
+}
+
+// Latency and loss to one address
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PingStats {
+    pub address: String,
+    pub method: PingMethod,
+    pub port: Option<u16>, // For TCP
+    pub sent: u32,
+    pub received: u32,
+    pub loss_percent: f64,
+    pub samples_ms: Vec<Option<f64>>, // Each probe in order, None when lost
+    // None when nothing was received
+    pub min_ms: Option<f64>,
+    pub avg_ms: Option<f64>,
+    pub max_ms: Option<f64>,
+    pub jitter_ms: Option<f64>, // Mean difference between consecutive round trips
+    pub icmp_error: Option<String>, // Why TCP was used instead, if it was
+}
+
+// Returned by ping_host: one entry per address the host resolved to
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PingReport {
+    pub host: String,
+    pub addresses: Vec<PingStats>,
+    pub total_time: f64,
+}
@@ -0,0 +1,30 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PortState {
+    Open,     // Accepted the connection
+    Closed,   // Refused it: the host is up, nothing listens
+    Filtered, // No answer, or unreachable: usually a firewall dropping the probe
+}
+
+// One port of a scan
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PortResult {
+    pub port: u16,
+    pub service: Option<String>, // Usual service on the port, e.g. "smtp"
+    pub state: PortState,
+    pub banner: Option<String>, // First line the service sent unprompted, e.g. SSH's version
+    pub time_ms: f64,           // Until connected, refused or timed out
+    pub error: Option<String>,  // Why a port is filtered, when the OS said
+}
+
+// Returned by scan_ports
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PortScan {
+    pub host: String,
+    pub address: String, // The address scanned
+    pub results: Vec<PortResult>,
+    pub open: usize,
+    pub total_time: f64,
+}
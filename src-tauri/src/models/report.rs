@@ -0,0 +1,151 @@
+use crate::models::certificate::TlsInfo;
+use crate::models::dns::{DnssecValidation, RecordEnumeration};
+use crate::models::email::EmailConfig;
+use crate::models::http::HttpResponse;
+use crate::models::whois::WhoisInfo;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+// One URL requested for the HTTP section
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HttpCheck {
+    pub url: String,
+    pub response: Option<HttpResponse>,
+    pub error: Option<String>,
+}
+
+// A section that failed; the other sections are still reported
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SectionError {
+    pub section: String,
+    pub error: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Info,     // Worth doing, nothing is broken
+    Warning,  // Degrades security or deliverability, or will break soon
+    Critical, // Broken now for some or all users
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ExportFormat {
+    Json,
+    Markdown,
+    Html, // Standalone page with its styles inlined
+}
+
+// One problem found in a report and how to fix it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Finding {
+    pub category: String, // "dns", "dnssec", "tls", "http", "email" or "registration"
+    pub severity: Severity,
+    pub code: String,        // Stable identifier, e.g. "certificate_expiring"
+    pub message: String,     // What was found, e.g. "The certificate expires in 9 days"
+    pub explanation: String, // Why it matters
+    pub fix: String,         // What to change, and where
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CategoryScore {
+    pub category: String,
+    pub score: Option<u8>, // 0-100; None when the section failed and wasn't graded
+    pub grade: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HealthScore {
+    pub score: u8, // Average of the graded categories, at most 69 with a critical finding
+    pub grade: String, // A (90+), B, C, D or F (below 60)
+    pub categories: Vec<CategoryScore>,
+    pub findings: Vec<Finding>, // Most severe first
+}
+
+// Everything analyze_domain found. A section is None until it completes, and
+// stays None when it failed.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DomainReport {
+    pub domain: String,
+    pub started_at: DateTime<Utc>,
+    pub total_time: f64, // Seconds; 0 until every section completed
+    pub dns: Option<RecordEnumeration>,
+    pub dnssec: Option<DnssecValidation>,
+    pub whois: Option<WhoisInfo>,
+    pub certificate: Option<TlsInfo>, // Port 443 of the domain
+    pub http: Vec<HttpCheck>,         // http:// and https://, then www. for an apex
+    pub email: Option<EmailConfig>,
+    pub errors: Vec<SectionError>,
+    pub health: Option<HealthScore>, // Graded once every section completed
+    #[serde(default)]
+    pub scan_id: Option<i64>, // ID in the scan history, once saved
+}
+
+// Payload of the "domain-report-progress" event emitted as each section of
+// analyze_domain completes
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DomainReportProgress {
+    pub section: String, // "dns", "dnssec", "whois", "certificate", "http" or "email"
+    pub completed: usize,
+    pub total: usize,
+    pub report: DomainReport, // Sections completed so far
+}
+
+// Domains read from a CSV or text file by import_domains
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DomainImport {
+    pub domains: Vec<String>, // In file order, duplicates removed
+    pub skipped: Vec<String>, // Rows without a usable domain, e.g. "line 4: not a domain"
+}
+
+// One aspect of two domains side by side; a side is empty when nothing was found
+// or its section failed
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ComparisonRow {
+    pub section: String, // "dns", "email", "certificate" or "http"
+    pub field: String,   // e.g. "nameservers", "mx", "issuer", "http_target"
+    pub a: Vec<String>,
+    pub b: Vec<String>,
+    pub same: bool,
+}
+
+// Returned by compare_domains
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DomainComparison {
+    pub domain_a: String,
+    pub domain_b: String,
+    pub rows: Vec<ComparisonRow>,
+    pub errors_a: Vec<SectionError>, // Sections that failed for domain_a
+    pub errors_b: Vec<SectionError>,
+    pub same: bool, // Every row matches
+    pub total_time: f64,
+}
+
+// One domain of a bulk scan, as a row of the summary table
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BulkScanRow {
+    pub domain: String,
+    pub score: Option<u8>, // Over the checks that ran; None if none completed
+    pub grade: Option<String>,
+    pub critical: usize, // Findings by severity
+    pub warnings: usize,
+    pub dnssec: Option<String>,        // Validation status, e.g. "SECURE"
+    pub certificate_days: Option<i64>, // Until the leaf certificate expires
+    pub registration_days: Option<i64>,
+    pub https_status: Option<u16>, // Final status of https://<domain>
+    pub dmarc_policy: Option<String>,
+    pub errors: Vec<SectionError>,
+    pub completed: usize, // Domains finished so far, including this one
+    pub total: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BulkScanSummary {
+    pub checks: Vec<String>, // Sections that were run for every domain
+    pub total: usize,
+    pub succeeded: usize, // Every check completed
+    pub failed: usize,
+    pub rows: Vec<BulkScanRow>, // In the order the domains were given
+    pub total_time: f64,
+}
@@ -0,0 +1,38 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Hosting {
+    Dedicated, // Only a few sites seen on the address
+    Shared,    // Many unrelated sites: typical of shared hosting
+    Unknown,   // No source answered
+}
+
+// A domain a source has seen resolving to the address
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HostedDomain {
+    pub domain: String,
+    pub sources: Vec<String>, // "hackertarget" (current A records), "mnemonic" (passive DNS)
+    pub first_seen: Option<DateTime<Utc>>, // Passive DNS only
+    pub last_seen: Option<DateTime<Utc>>,
+}
+
+// A source that couldn't be queried; the others are still reported
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SourceError {
+    pub source: String,
+    pub error: String,
+}
+
+// Returned by lookup_reverse_ip
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReverseIpReport {
+    pub ip: String,
+    pub ptr: Vec<String>,           // The address's own reverse DNS names
+    pub domains: Vec<HostedDomain>, // Most recently seen first
+    pub sites: usize,               // Distinct registrable domains among them
+    pub hosting: Hosting,
+    pub errors: Vec<SourceError>,
+    pub total_time: f64,
+}
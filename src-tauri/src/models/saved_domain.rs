@@ -0,0 +1,12 @@
+use crate::models::history::ScanSummary;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+// A frequently-checked domain kept for quick access, with its latest saved scan
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SavedDomain {
+    pub domain: String,
+    pub tags: Vec<String>, // e.g. "client", "production"; in the order given
+    pub added_at: DateTime<Utc>,
+    pub last_scan: Option<ScanSummary>, // None until analyze_domain has run for it
+}
@@ -0,0 +1,68 @@
+use crate::models::command_log::CommandLog;
+use crate::models::report::DomainReport;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+// Version of the session file; files from a newer version are refused
+pub const SESSION_FORMAT: u32 = 1;
+
+// A domain queried during the session
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionDomain {
+    pub domain: String,
+    pub first_queried_at: DateTime<Utc>,
+    pub last_queried_at: DateTime<Utc>,
+    pub commands: usize,              // Commands logged for it
+    pub report: Option<DomainReport>, // The latest analyze_domain run this session
+}
+
+// What save_session writes and load_session reads back
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Session {
+    pub format: u32,
+    pub app_version: String,
+    pub started_at: DateTime<Utc>,
+    pub saved_at: DateTime<Utc>,
+    pub domains: Vec<SessionDomain>, // In the order first queried
+    pub logs: Vec<CommandLog>,       // Oldest first, with each command's output
+    // State of the frontend's panels, stored as given and returned on load
+    #[serde(default)]
+    pub panels: Option<serde_json::Value>,
+}
+
+impl SessionDomain {
+    // The domains the logs were for, lowercase without a trailing dot; logs are oldest first
+    pub fn from_logs(logs: &[CommandLog]) -> Vec<SessionDomain> {
+        let mut domains: Vec<SessionDomain> = Vec::new();
+        for log in logs {
+            if let Some(domain) = log.domain.as_deref() {
+                SessionDomain::note(&mut domains, domain, log.logged_at, true);
+            }
+        }
+        domains
+    }
+
+    // Count a query of domain at `at` in domains, adding it when it's new; logged is
+    // false for answers that ran no command (cached or shared with another lookup)
+    pub fn note(domains: &mut Vec<SessionDomain>, domain: &str, at: DateTime<Utc>, logged: bool) {
+        let domain = domain.trim().trim_end_matches('.').to_lowercase();
+        if domain.is_empty() {
+            return;
+        }
+        let commands = usize::from(logged);
+        match domains.iter_mut().find(|d| d.domain == domain) {
+            Some(existing) => {
+                existing.first_queried_at = existing.first_queried_at.min(at);
+                existing.last_queried_at = existing.last_queried_at.max(at);
+                existing.commands += commands;
+            }
+            None => domains.push(SessionDomain {
+                domain,
+                first_queried_at: at,
+                last_queried_at: at,
+                commands,
+                report: None,
+            }),
+        }
+    }
+}
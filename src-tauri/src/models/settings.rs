@@ -0,0 +1,345 @@
+use serde::{Deserialize, Serialize};
+
+// Address family used to reach nameservers
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum IpVersion {
+    #[default]
+    Auto, // Let dig pick (usually whatever the OS prefers)
+    Ipv4, // dig -4
+    Ipv6, // dig -6
+}
+
+// DS-style trust anchor, in the same form IANA publishes in root-anchors.xml
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TrustAnchor {
+    pub zone: String, // "." for the root
+    pub key_tag: u16,
+    pub algorithm: u8,
+    pub digest_type: u8,
+    pub digest: String, // Hex
+}
+
+// IANA root trust anchors bundled with the app: KSK-2017 and KSK-2024
+pub fn root_trust_anchors() -> Vec<TrustAnchor> {
+    vec![
+        TrustAnchor {
+            zone: ".".to_string(),
+            key_tag: 20326,
+            algorithm: 8,
+            digest_type: 2,
+            digest: "E06D44B80B8F1D39A95C0B0D7C65D08458E880409BBC683457104237C7F8EC8D".to_string(),
+        },
+        TrustAnchor {
+            zone: ".".to_string(),
+            key_tag: 38696,
+            algorithm: 8,
+            digest_type: 2,
+            digest: "683D2D0ACB8C9B712A1948B27F741219298D0A450D612C483AF444A4C0FB2B16".to_string(),
+        },
+    ]
+}
+
+// User-configurable limits applied by the adapters
+// Missing fields fall back to their defaults so older settings files keep loading
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Settings {
+    pub dns_timeout_secs: u32, // Per-try timeout (dig +time)
+    pub dns_retries: u32,      // Extra attempts after the first (dig +tries = retries + 1)
+    pub dns_ip_version: IpVersion,
+    pub dns_resolver: String, // Used by lookups: "system", an address or hostname, or a DoH URL
+    pub doh_endpoints: Vec<String>, // DNS-over-HTTPS URLs offered as resolvers, and benchmarked
+    pub whois_timeout_secs: u32,
+    pub whois_retries: u32,
+    pub http_timeout_secs: u32,          // Per-hop limit
+    pub http_retries: u32,               // Extra attempts after transient failures only
+    pub http_proxy: Option<String>,      // http://, socks5:// or socks5h:// URL
+    pub http_user_agent: Option<String>, // Replaces d-dns-debugger/<version>
+    pub tls_timeout_secs: u32,
+    pub tls_retries: u32,
+    pub max_concurrency: usize, // Parallel queries in enumerations and bulk operations
+    pub nameserver_queries_per_second: u32, // Per nameserver queried directly (dig @server)
+    pub whois_queries_per_minute: u32, // Per port-43 WHOIS server
+    pub host_requests_per_second: u32, // Per host, for TLS handshakes and HTTP requests
+    pub rrsig_expiry_warning_days: u32, // Warn when a DNSSEC signature expires within this many days
+    pub cert_expiry_warning_days: Vec<u32>, // Warn when a certificate expires within any of these
+    pub domain_expiry_warning_days: u32, // Warn when a domain registration expires within this many days
+    pub expiry_notification_days: Vec<u32>, // Notify when a watched expiry comes within each of these
+    pub watchlist_check_hours: u32,         // How often watched domains are re-checked
+    pub tls_scan_ports: Vec<u16>,           // Ports checked by a multi-port TLS scan
+    pub scan_ports: Vec<u16>,               // Ports scan_ports checks when given none
+    pub dnsbl_ip_zones: Vec<String>,        // Blocklists MX addresses are checked against
+    pub dnsbl_domain_zones: Vec<String>,    // Blocklists the domain itself is checked against
+    pub dkim_selectors: Vec<String>, // Tried for every domain, after those passed to the check
+    pub trust_anchors: Vec<TrustAnchor>, // Keys DNSSEC validation starts from (bundled root anchors by default)
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Settings {
+            dns_timeout_secs: 2,
+            dns_retries: 1,
+            dns_ip_version: IpVersion::Auto,
+            dns_resolver: "system".to_string(),
+            doh_endpoints: vec![
+                "https://cloudflare-dns.com/dns-query".to_string(),
+                "https://dns.google/dns-query".to_string(),
+                "https://dns.quad9.net/dns-query".to_string(),
+            ],
+            whois_timeout_secs: 10,
+            whois_retries: 1,
+            http_timeout_secs: 10,
+            http_retries: 0,
+            http_proxy: None,
+            http_user_agent: None,
+            tls_timeout_secs: 10,
+            tls_retries: 0,
+            max_concurrency: 8,
+            nameserver_queries_per_second: 20,
+            whois_queries_per_minute: 30,
+            host_requests_per_second: 10,
+            rrsig_expiry_warning_days: 7,
+            cert_expiry_warning_days: vec![30, 14, 7],
+            domain_expiry_warning_days: 30,
+            expiry_notification_days: vec![30, 14, 7, 1],
+            watchlist_check_hours: 12,
+            // MySQL (3306) is left out: it upgrades after its own SSLRequest packet
+            tls_scan_ports: vec![443, 465, 587, 993, 995, 8443],
+            scan_ports: vec![
+                21, 22, 23, 25, 53, 80, 110, 143, 443, 465, 587, 993, 995, 1433, 3306, 3389, 5432,
+                6379, 8080, 8443,
+            ],
+            // SORBS shut down in 2024 and is left out
+            dnsbl_ip_zones: vec![
+                "zen.spamhaus.org".to_string(),
+                "b.barracudacentral.org".to_string(),
+                "bl.spamcop.net".to_string(),
+                "psbl.surriel.com".to_string(),
+                "dnsbl-1.uceprotect.net".to_string(),
+            ],
+            dnsbl_domain_zones: vec![
+                "dbl.spamhaus.org".to_string(),
+                "multi.uribl.com".to_string(),
+                "multi.surbl.org".to_string(),
+            ],
+            dkim_selectors: Vec::new(),
+            trust_anchors: root_trust_anchors(),
+        }
+    }
+}
+
+impl Settings {
+    pub fn validate(&self) -> Result<(), String> {
+        let timeouts = [
+            ("DNS", self.dns_timeout_secs),
+            ("WHOIS", self.whois_timeout_secs),
+            ("HTTP", self.http_timeout_secs),
+            ("TLS", self.tls_timeout_secs),
+        ];
+
+        for (name, timeout) in timeouts {
+            if !(1..=120).contains(&timeout) {
+                return Err(format!(
+                    "{} timeout must be between 1 and 120 seconds",
+                    name
+                ));
+            }
+        }
+
+        let retries = [
+            self.dns_retries,
+            self.whois_retries,
+            self.http_retries,
+            self.tls_retries,
+        ];
+
+        if retries.iter().any(|r| *r > 10) {
+            return Err("Retry counts must be 10 or less".to_string());
+        }
+
+        if !(1..=64).contains(&self.max_concurrency) {
+            return Err("Max concurrency must be between 1 and 64".to_string());
+        }
+
+        let rates = [
+            (
+                "Nameserver queries per second",
+                self.nameserver_queries_per_second,
+            ),
+            ("WHOIS queries per minute", self.whois_queries_per_minute),
+            ("Host requests per second", self.host_requests_per_second),
+        ];
+
+        for (name, rate) in rates {
+            if !(1..=1000).contains(&rate) {
+                return Err(format!("{} must be between 1 and 1000", name));
+            }
+        }
+
+        if !(1..=365).contains(&self.rrsig_expiry_warning_days) {
+            return Err("RRSIG expiry warning must be between 1 and 365 days".to_string());
+        }
+
+        if !(1..=365).contains(&self.domain_expiry_warning_days) {
+            return Err("Domain expiry warning must be between 1 and 365 days".to_string());
+        }
+
+        if self
+            .cert_expiry_warning_days
+            .iter()
+            .any(|days| !(1..=365).contains(days))
+        {
+            return Err("Certificate expiry warnings must be between 1 and 365 days".to_string());
+        }
+
+        if self
+            .expiry_notification_days
+            .iter()
+            .any(|days| !(1..=365).contains(days))
+        {
+            return Err("Expiry notifications must be between 1 and 365 days".to_string());
+        }
+
+        if !(1..=168).contains(&self.watchlist_check_hours) {
+            return Err("Watchlist checks must run every 1 to 168 hours".to_string());
+        }
+
+        if self.tls_scan_ports.is_empty()
+            || self.tls_scan_ports.len() > 64
+            || self.tls_scan_ports.contains(&0)
+        {
+            return Err("TLS scan ports must list between 1 and 64 ports (1-65535)".to_string());
+        }
+
+        if self.scan_ports.is_empty() || self.scan_ports.len() > 256 || self.scan_ports.contains(&0)
+        {
+            return Err("Scan ports must list between 1 and 256 ports (1-65535)".to_string());
+        }
+
+        let zones = self.dnsbl_ip_zones.iter().chain(&self.dnsbl_domain_zones);
+        if self.dnsbl_ip_zones.len() > 32
+            || self.dnsbl_domain_zones.len() > 32
+            || zones.clone().any(|zone| !is_zone_name(zone))
+        {
+            return Err("Blocklists must be at most 32 DNS zone names of each kind".to_string());
+        }
+
+        if !is_resolver(&self.dns_resolver) {
+            return Err(format!("Invalid resolver: {}", self.dns_resolver));
+        }
+
+        if self.doh_endpoints.len() > 16
+            || self
+                .doh_endpoints
+                .iter()
+                .any(|url| !url.starts_with("https://") || !is_resolver(url))
+        {
+            return Err(
+                "DoH endpoints must be at most 16 https:// URLs on the default port".to_string(),
+            );
+        }
+
+        if self.dkim_selectors.len() > 32
+            || self.dkim_selectors.iter().any(|s| !is_dkim_selector(s))
+        {
+            return Err("DKIM selectors must be at most 32 DNS labels like s1".to_string());
+        }
+
+        if let Some(proxy) = self.http_proxy.as_deref().filter(|p| !p.trim().is_empty()) {
+            let valid = url::Url::parse(proxy.trim()).is_ok_and(|url| {
+                ["http", "socks5", "socks5h"].contains(&url.scheme())
+                    && url.host_str().is_some()
+                    && url.port_or_known_default().is_some()
+            });
+            if !valid {
+                return Err(
+                    "Proxy must be an http://, socks5:// or socks5h:// URL with a host and port"
+                        .to_string(),
+                );
+            }
+        }
+
+        if let Some(user_agent) = &self.http_user_agent {
+            if user_agent.trim().is_empty() || user_agent.chars().any(char::is_control) {
+                return Err("User-Agent must be a single line of text".to_string());
+            }
+        }
+
+        if !self.trust_anchors.iter().any(|a| a.zone == ".") {
+            return Err("At least one root trust anchor is required".to_string());
+        }
+
+        for anchor in &self.trust_anchors {
+            let digest_len = match anchor.digest_type {
+                1 => 40,
+                2 => 64,
+                4 => 96,
+                other => {
+                    return Err(format!(
+                        "Trust anchor {} uses unsupported digest type {}",
+                        anchor.key_tag, other
+                    ))
+                }
+            };
+
+            if anchor.zone.trim().is_empty()
+                || anchor.digest.len() != digest_len
+                || !anchor.digest.chars().all(|c| c.is_ascii_hexdigit())
+            {
+                return Err(format!("Trust anchor {} is malformed", anchor.key_tag));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+// "system", an IP address, a hostname, or a DoH URL such as
+// https://dns.google/dns-query (rejects anything dig could read as an option)
+pub fn is_resolver(resolver: &str) -> bool {
+    if resolver.starts_with("https://") {
+        // dig and kdig take the host and path, so a port, query or credentials would be lost
+        return url::Url::parse(resolver).is_ok_and(|url| {
+            url.host_str().is_some()
+                && url.port().is_none()
+                && url.username().is_empty()
+                && url.query().is_none()
+                && url.fragment().is_none()
+        });
+    }
+    if resolver == "system" || resolver.parse::<std::net::IpAddr>().is_ok() {
+        return true;
+    }
+
+    !resolver.is_empty()
+        && !resolver.starts_with('-')
+        && !resolver.starts_with('.')
+        && resolver
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '.')
+}
+
+// One or more DNS labels, e.g. "s1" or "2024.mail"
+pub fn is_dkim_selector(selector: &str) -> bool {
+    let selector = selector.trim();
+    !selector.is_empty()
+        && selector.split('.').all(|label| {
+            !label.is_empty()
+                && label.len() <= 63
+                && label
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+        })
+}
+
+// "zen.spamhaus.org": two or more DNS labels
+fn is_zone_name(zone: &str) -> bool {
+    let labels: Vec<&str> = zone.trim_end_matches('.').split('.').collect();
+    labels.len() >= 2
+        && labels.iter().all(|label| {
+            !label.is_empty()
+                && label.len() <= 63
+                && label.chars().all(|c| c.is_ascii_alphanumeric() || c == '-')
+        })
+}
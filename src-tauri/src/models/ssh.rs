@@ -0,0 +1,46 @@
+use serde::{Deserialize, Serialize};
+
+// One SSHFP record (RFC 4255, with the algorithms of RFC 6594 and 7479)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SshfpRecord {
+    pub algorithm: u8,          // 1 RSA, 2 DSA, 3 ECDSA, 4 Ed25519, 6 Ed448
+    pub algorithm_name: String, // e.g. "Ed25519"
+    pub fp_type: u8,            // 1 SHA-1, 2 SHA-256
+    pub fp_type_name: String,
+    pub fingerprint: String,   // Lowercase hex
+    pub matched: Option<bool>, // Against the server's key of this algorithm; None when none was fetched
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum HostKeyStatus {
+    Matched,     // An SSHFP record of its algorithm matches it
+    Mismatched,  // Records exist for its algorithm but none match
+    Unpublished, // No SSHFP record for its algorithm
+}
+
+// A host key the SSH server presented
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SshHostKey {
+    pub key_type: String,    // "ssh-ed25519", "ecdsa-sha2-nistp256", "ssh-rsa"...
+    pub algorithm: u8,       // Its SSHFP algorithm number
+    pub public_key: String,  // Base64, as in known_hosts
+    pub fingerprint: String, // "SHA256:..." as ssh-keygen -l prints it
+    pub status: HostKeyStatus,
+}
+
+// Returned by check_sshfp
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SshfpReport {
+    pub host: String,
+    pub records: Vec<SshfpRecord>,
+    pub authenticated: bool, // Resolver validated the SSHFP answer with DNSSEC (AD flag)
+    pub port: u16,
+    pub address: Option<String>, // The address connected to, when verifying
+    pub banner: Option<String>,  // The server's version line, e.g. "SSH-2.0-OpenSSH_9.6"
+    pub host_keys: Vec<SshHostKey>,
+    pub verified: Option<bool>, // A key matched and none contradicted the records; None when not connected
+    pub error: Option<String>,  // Why fetching the host keys failed
+    pub warnings: Vec<String>,
+    pub total_time: f64,
+}
@@ -0,0 +1,38 @@
+use serde::{Deserialize, Serialize};
+
+// A router or host that answered probes at a hop
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HopResponder {
+    pub address: String,
+    pub hostname: Option<String>, // From its PTR record
+}
+
+// One TTL of a traceroute
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TraceHop {
+    pub ttl: u8,
+    // Several when load balancing spreads the probes, none when all timed out
+    pub responders: Vec<HopResponder>,
+    pub rtts_ms: Vec<Option<f64>>, // Each probe in order, None when it timed out
+    pub avg_ms: Option<f64>,
+    pub loss_percent: f64,
+}
+
+// Emitted as trace-route-hop while tracing. A hop is sent again as more of its
+// probes come in (tracepath prints one line per probe).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TraceHopEvent {
+    pub host: String,
+    pub hop: TraceHop,
+}
+
+// Returned by trace_route
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TraceRoute {
+    pub host: String,
+    pub address: String, // The address traced to
+    pub tool: String,    // "traceroute", "tracepath" or "tracert"
+    pub hops: Vec<TraceHop>,
+    pub reached: bool, // Whether the last hop answered from address
+    pub total_time: f64,
+}
@@ -0,0 +1,54 @@
+use serde::{Deserialize, Serialize};
+
+// How a lookalike was derived from the domain's name
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum VariantKind {
+    Omission,      // A character left out: exmple.com
+    Repetition,    // A character typed twice: exxample.com
+    Transposition, // Two neighbours swapped: exmaple.com
+    Replacement,   // A key next to the intended one: exanple.com
+    Hyphenation,   // A hyphen inserted: ex-ample.com
+    Homoglyph,     // ASCII that looks alike: examp1e.com, rnicrosoft.com
+    Homograph,     // Confusable Unicode: exаmple.com with a Cyrillic а
+    Tld,           // The same name under another TLD: example.net
+}
+
+// One generated lookalike and what DNS and WHOIS say about it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TyposquatVariant {
+    pub domain: String, // Unicode for homographs
+    pub kind: VariantKind,
+    pub registered: bool, // Exists in DNS (anything but NXDOMAIN)
+    pub addresses: Vec<String>,
+    pub nameservers: Vec<String>,
+    pub has_mx: bool, // Accepts mail, so it can receive misdirected mail or send phishing
+    pub registrar: Option<String>,
+    pub registrant: Option<String>, // Organization, else name; None when hidden
+    pub created: Option<String>,
+    pub privacy_protected: bool,
+    pub defensive: bool, // Same registrant or nameservers as the domain: likely its owner's
+    pub error: Option<String>, // Why DNS or WHOIS couldn't tell
+}
+
+// Registered lookalikes sharing a registrar or registrant
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RegistrantGroup {
+    pub name: String,
+    pub domains: Vec<String>,
+}
+
+// Returned by scan_typosquats
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TyposquatReport {
+    pub domain: String, // The registrable domain the variants were made from
+    pub registrar: Option<String>, // The domain's own, for comparison
+    pub registrant: Option<String>,
+    pub generated: usize,
+    pub registered: usize,
+    pub variants: Vec<TyposquatVariant>, // Registered ones first, each in generation order
+    pub registrars: Vec<RegistrantGroup>, // Most domains first
+    pub registrants: Vec<RegistrantGroup>,
+    pub findings: Vec<String>,
+    pub total_time: f64,
+}
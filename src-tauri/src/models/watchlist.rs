@@ -0,0 +1,38 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ExpiryKind {
+    Certificate,  // TLS certificate served on port 443
+    Registration, // Domain registration, from RDAP or WHOIS
+}
+
+impl ExpiryKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ExpiryKind::Certificate => "certificate",
+            ExpiryKind::Registration => "registration",
+        }
+    }
+}
+
+// A domain whose expiries are checked in the background
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WatchedDomain {
+    pub domain: String,
+    pub added_at: DateTime<Utc>,
+    pub checked_at: Option<DateTime<Utc>>, // None until the first check finishes
+    pub certificate_expires_at: Option<DateTime<Utc>>,
+    pub registration_expires_at: Option<DateTime<Utc>>,
+    pub errors: Vec<String>, // Checks that failed on the last run; earlier dates are kept
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UpcomingExpiration {
+    pub domain: String,
+    pub kind: ExpiryKind,
+    pub expires_at: DateTime<Utc>,
+    pub days_remaining: i64,    // Negative once expired
+    pub threshold: Option<u32>, // Smallest notification threshold it falls within
+}
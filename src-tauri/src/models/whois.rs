@@ -1,3 +1,4 @@
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -7,10 +8,104 @@ pub struct WhoisInfo {
     pub creation_date: Option<String>,
     pub expiration_date: Option<String>,
     pub updated_date: Option<String>,
+    #[serde(default)]
+    pub creation_date_raw: Option<String>, // As the registry wrote it; *_date hold ISO 8601
+    #[serde(default)]
+    pub expiration_date_raw: Option<String>,
+    #[serde(default)]
+    pub updated_date_raw: Option<String>,
     pub nameservers: Vec<String>,
-    pub status: Vec<String>,
+    pub status: Vec<EppStatus>,
     pub dnssec: Option<String>,
-    pub raw_output: String,
+    #[serde(default)]
+    pub registrant: Option<Contact>,
+    #[serde(default)]
+    pub admin: Option<Contact>,
+    #[serde(default)]
+    pub tech: Option<Contact>,
+    #[serde(default)]
+    pub privacy_protected: bool, // Contacts hidden by a privacy/proxy service or redacted
+    #[serde(default)]
+    pub source: WhoisSource,
+    #[serde(default)]
+    pub registrar_whois_server: Option<String>, // Followed referral from a thin registry
+    #[serde(default)]
+    pub expiration_at: Option<DateTime<Utc>>, // Parsed expiration_date
+    #[serde(default)]
+    pub days_until_expiry: Option<i64>, // Negative once expired
+    #[serde(default)]
+    pub warnings: Vec<String>, // Upcoming expiry, redemptionPeriod, pendingDelete
+    pub raw_output: String, // RDAP JSON or WHOIS text, depending on source
+}
+
+impl WhoisInfo {
+    pub fn has_status(&self, code: &str) -> bool {
+        self.status
+            .iter()
+            .any(|s| s.code.eq_ignore_ascii_case(code))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum StatusSeverity {
+    Info,     // Normal or protective (transfer locks, grace periods)
+    Warning,  // Pending change or expiring; needs attention
+    Critical, // Domain doesn't resolve or is about to be lost
+}
+
+// EPP domain status (RFC 5731, RFC 3915) with what it means for the owner
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EppStatus {
+    pub code: String, // e.g. "clientTransferProhibited"
+    pub severity: StatusSeverity,
+    pub description: String,
+}
+
+impl EppStatus {
+    pub fn from_code(code: &str) -> EppStatus {
+        let (severity, description) = match code.to_lowercase().as_str() {
+            "ok" | "active" => (StatusSeverity::Info, "No pending operations or restrictions"),
+            "clienttransferprohibited" => (StatusSeverity::Info, "Registrar lock: transfers to another registrar are blocked"),
+            "clientupdateprohibited" => (StatusSeverity::Info, "Registrar lock: changes to contacts and nameservers are blocked"),
+            "clientdeleteprohibited" => (StatusSeverity::Info, "Registrar lock: the domain cannot be deleted"),
+            "clientrenewprohibited" => (StatusSeverity::Warning, "Registrar has blocked renewal, often during a dispute or non-payment"),
+            "servertransferprohibited" => (StatusSeverity::Info, "Registry lock: transfers are blocked"),
+            "serverupdateprohibited" => (StatusSeverity::Info, "Registry lock: changes are blocked"),
+            "serverdeleteprohibited" => (StatusSeverity::Info, "Registry lock: the domain cannot be deleted"),
+            "serverrenewprohibited" => (StatusSeverity::Warning, "Registry has blocked renewal, often during a dispute"),
+            "clienthold" => (StatusSeverity::Critical, "Registrar has suspended the domain; it is not published in DNS and does not resolve"),
+            "serverhold" => (StatusSeverity::Critical, "Registry has suspended the domain; it is not published in DNS and does not resolve"),
+            "inactive" => (StatusSeverity::Warning, "No nameservers are delegated, so the domain does not resolve"),
+            "addperiod" => (StatusSeverity::Info, "Recently registered; can be deleted for a refund"),
+            "autorenewperiod" => (StatusSeverity::Info, "Automatically renewed by the registry; can still be deleted for a refund"),
+            "renewperiod" => (StatusSeverity::Info, "Recently renewed"),
+            "transferperiod" => (StatusSeverity::Info, "Recently transferred to a new registrar"),
+            "pendingcreate" => (StatusSeverity::Warning, "Registration has been requested but not completed"),
+            "pendingrenew" => (StatusSeverity::Warning, "Renewal has been requested but not completed"),
+            "pendingtransfer" => (StatusSeverity::Warning, "A transfer to another registrar is in progress"),
+            "pendingupdate" => (StatusSeverity::Warning, "An update has been requested but not completed"),
+            "pendingrestore" => (StatusSeverity::Warning, "Restore from redemption requested; waiting for the registrar's report"),
+            "redemptionperiod" => (StatusSeverity::Critical, "Expired and deleted; only the registrar can restore it, usually for a fee"),
+            "pendingdelete" => (StatusSeverity::Critical, "Will be purged and released for anyone to register within days"),
+            _ => (StatusSeverity::Info, "Unrecognized status"),
+        };
+
+        EppStatus {
+            code: code.to_string(),
+            severity,
+            description: description.to_string(),
+        }
+    }
+}
+
+// Protocol the data came from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum WhoisSource {
+    Rdap,
+    #[default]
+    Whois, // Port 43
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -20,3 +115,71 @@ pub struct Contact {
     pub email: Option<String>,
     pub phone: Option<String>,
 }
+
+// One domain of a lookup_whois_bulk run; also the "whois-bulk-result" event payload
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BulkWhoisResult {
+    pub domain: String,
+    pub info: Option<WhoisInfo>,
+    pub error: Option<String>,
+    pub completed: usize, // Domains finished so far, including this one
+    pub total: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BulkWhoisSummary {
+    pub total: usize,
+    pub succeeded: usize,
+    pub failed: usize,
+    pub expired: Vec<String>,
+    pub expiring_soon: Vec<String>, // Within domain_expiry_warning_days
+    pub results: Vec<BulkWhoisResult>, // In the order the domains were given
+    pub total_time: f64,
+}
+
+// Who runs an IP address: RIR registration data plus its BGP origin
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IpWhoisInfo {
+    pub ip: String,
+    pub network_name: Option<String>,
+    pub network_handle: Option<String>,
+    pub network_range: Option<String>, // "start - end" as registered
+    pub organization: Option<String>,
+    pub country: Option<String>,
+    pub abuse_email: Option<String>,
+    pub rir: Option<String>, // arin, ripencc, apnic, lacnic or afrinic
+    pub asn: Option<u32>,    // Origin AS announcing the address
+    pub as_name: Option<String>,
+    pub announced_prefix: Option<String>, // Most specific announced prefix covering the IP
+    pub warnings: Vec<String>,
+    pub raw_output: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AvailabilityStatus {
+    Registered,
+    Available,
+    Redemption,    // Expired, recoverable by the previous registrant
+    PendingDelete, // Will be released within days
+    Unknown,       // Registry and DNS gave no usable or conflicting answers
+}
+
+// Registry's answer to "does this domain exist?"
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RegistrationCheck {
+    pub registered: Option<bool>,
+    pub status: Vec<String>,   // EPP statuses when registered
+    pub evidence: Vec<String>, // What each source answered
+    pub raw_output: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DomainAvailability {
+    pub domain: String,
+    pub status: AvailabilityStatus,
+    pub registration_status: Vec<String>,
+    pub nxdomain: Option<bool>, // None if the DNS lookup failed
+    pub evidence: Vec<String>,
+    pub raw_output: String,
+}
@@ -0,0 +1,172 @@
+use crate::models::command_log::{CommandLog, CommandLogFilter};
+use crate::state::AppState;
+use std::collections::VecDeque;
+use std::sync::Mutex;
+use tauri::{AppHandle, Emitter, Manager};
+
+// Logs kept for the session; the oldest are dropped first
+const MAX_COMMAND_LOGS: usize = 2000;
+
+// Every command the adapters ran this session, for list_command_logs and export
+//
+// The logs slideout only sees the `command-log` events sent while it was listening;
+// these are kept so they can be searched and attached to bug reports later.
+pub struct CommandLogs {
+    logs: Mutex<VecDeque<CommandLog>>,
+    capacity: usize,
+}
+
+impl CommandLogs {
+    pub fn new() -> Self {
+        Self::with_capacity(MAX_COMMAND_LOGS)
+    }
+
+    pub fn with_capacity(capacity: usize) -> Self {
+        CommandLogs {
+            logs: Mutex::new(VecDeque::new()),
+            capacity: capacity.max(1),
+        }
+    }
+
+    pub fn push(&self, log: CommandLog) {
+        if let Ok(mut logs) = self.logs.lock() {
+            while logs.len() >= self.capacity {
+                logs.pop_front();
+            }
+            logs.push_back(log);
+        }
+    }
+
+    // Newest first
+    pub fn query(&self, filter: &CommandLogFilter) -> Vec<CommandLog> {
+        let Ok(logs) = self.logs.lock() else {
+            return Vec::new();
+        };
+        logs.iter()
+            .rev()
+            .filter(|log| filter.matches(log))
+            .take(filter.limit.unwrap_or(usize::MAX))
+            .cloned()
+            .collect()
+    }
+
+    pub fn clear(&self) {
+        if let Ok(mut logs) = self.logs.lock() {
+            logs.clear();
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.logs.lock().map(|logs| logs.len()).unwrap_or(0)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl Default for CommandLogs {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// Keep a log in the app's CommandLogs, count it in the performance stats and the
+// session's domains, and send it as a `command-log` event; without an AppHandle (tests, CLI) it goes nowhere
+pub fn record(app_handle: Option<&AppHandle>, log: CommandLog) {
+    tracing::debug!(
+        tool = %log.tool,
+        exit_code = log.exit_code,
+        duration_ms = log.duration,
+        "{}",
+        log.command
+    );
+    let Some(handle) = app_handle else {
+        return;
+    };
+    if let Some(state) = handle.try_state::<AppState>() {
+        state.performance.record(&log);
+        if let Some(domain) = &log.domain {
+            state.session_domains.record(domain, true);
+        }
+        state.command_logs.push(log.clone());
+    }
+    let _ = handle.emit("command-log", log);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{Duration, Utc};
+
+    fn log(tool: &str, domain: Option<&str>) -> CommandLog {
+        CommandLog::new(
+            tool.to_string(),
+            Vec::new(),
+            String::new(),
+            0,
+            1.0,
+            domain.map(str::to_string),
+        )
+    }
+
+    #[test]
+    fn test_ring_buffer_drops_oldest() {
+        let logs = CommandLogs::with_capacity(3);
+        for domain in ["a.com", "b.com", "c.com", "d.com"] {
+            logs.push(log("dig", Some(domain)));
+        }
+        assert_eq!(logs.len(), 3);
+
+        let domains: Vec<String> = logs
+            .query(&CommandLogFilter::default())
+            .into_iter()
+            .filter_map(|l| l.domain)
+            .collect();
+        assert_eq!(domains, vec!["d.com", "c.com", "b.com"]);
+
+        logs.clear();
+        assert!(logs.is_empty());
+    }
+
+    #[test]
+    fn test_query_filters() {
+        let logs = CommandLogs::new();
+        let mut old = log("whois", Some("example.com"));
+        old.logged_at = Utc::now() - Duration::hours(2);
+        logs.push(old);
+        logs.push(log("dig", Some("example.com")));
+        logs.push(log("dig", Some("example.org")));
+        logs.push(log("curl", None));
+
+        let by_domain = CommandLogFilter {
+            domain: Some("Example.COM.".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(logs.query(&by_domain).len(), 2);
+
+        let by_tool = CommandLogFilter {
+            tool: Some("DIG".to_string()),
+            limit: Some(1),
+            ..Default::default()
+        };
+        let newest_dig = logs.query(&by_tool);
+        assert_eq!(newest_dig.len(), 1);
+        assert_eq!(newest_dig[0].domain.as_deref(), Some("example.org"));
+
+        let last_hour = CommandLogFilter {
+            domain: Some("example.com".to_string()),
+            since: Some(Utc::now() - Duration::hours(1)),
+            ..Default::default()
+        };
+        let recent = logs.query(&last_hour);
+        assert_eq!(recent.len(), 1);
+        assert_eq!(recent[0].tool, "dig");
+
+        let earlier = CommandLogFilter {
+            until: Some(Utc::now() - Duration::hours(1)),
+            ..Default::default()
+        };
+        assert_eq!(logs.query(&earlier)[0].tool, "whois");
+    }
+}
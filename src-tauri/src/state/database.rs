@@ -0,0 +1,128 @@
+use chrono::{DateTime, SecondsFormat, Utc};
+use rusqlite::types::Type;
+use rusqlite::{Connection, Row};
+use std::fs;
+use std::path::Path;
+use std::sync::{Mutex, MutexGuard};
+use tauri::{AppHandle, Manager};
+
+// A SQLite file in the app data directory, locked so commands can share it
+//
+// Each store (scan history, watchlist...) has its own file and schema; opening,
+// locking and the fallback to memory when the file can't be used are the same.
+pub struct Database {
+    connection: Mutex<Connection>,
+    name: &'static str, // What the store holds, for messages: "scan history"
+}
+
+impl Database {
+    // Open or create the file and apply schema, which must only create what's missing
+    pub fn open(path: &Path, name: &'static str, schema: &str) -> Result<Self, String> {
+        let connection = Connection::open(path)
+            .map_err(|e| format!("Failed to open {}: {}", path.display(), e))?;
+        Self::with_connection(connection, name, schema)
+    }
+
+    // Kept for the session only: tests, or when the data directory isn't writable
+    pub fn in_memory(name: &'static str, schema: &str) -> Self {
+        let connection = Connection::open_in_memory().expect("SQLite in-memory database");
+        Self::with_connection(connection, name, schema).expect("database schema")
+    }
+
+    fn with_connection(
+        connection: Connection,
+        name: &'static str,
+        schema: &str,
+    ) -> Result<Self, String> {
+        connection
+            .execute_batch(schema)
+            .map_err(|e| format!("Failed to create the {} tables: {}", name, e))?;
+        Ok(Database {
+            connection: Mutex::new(connection),
+            name,
+        })
+    }
+
+    // Open file in the app data directory, falling back to an in-memory database so
+    // a broken file doesn't block startup
+    pub fn load(app_handle: &AppHandle, file: &str, name: &'static str, schema: &str) -> Self {
+        let path = app_handle
+            .path()
+            .app_data_dir()
+            .map_err(|e| format!("Failed to resolve data directory: {}", e))
+            .and_then(|dir| {
+                fs::create_dir_all(&dir)
+                    .map_err(|e| format!("Failed to create data directory: {}", e))?;
+                Ok(dir.join(file))
+            });
+
+        match path.and_then(|path| Self::open(&path, name, schema)) {
+            Ok(database) => database,
+            Err(e) => {
+                tracing::warn!("The {} won't be kept: {}", name, e);
+                Self::in_memory(name, schema)
+            }
+        }
+    }
+
+    pub fn connection(&self) -> Result<MutexGuard<'_, Connection>, String> {
+        self.connection
+            .lock()
+            .map_err(|_| format!("The {} database is unavailable", self.name))
+    }
+}
+
+// Fixed width so a text column sorts chronologically
+pub fn format_time(time: DateTime<Utc>) -> String {
+    time.to_rfc3339_opts(SecondsFormat::Millis, true)
+}
+
+pub fn parse_time(value: &str) -> Result<DateTime<Utc>, String> {
+    DateTime::parse_from_rfc3339(value)
+        .map(|t| t.with_timezone(&Utc))
+        .map_err(|e| format!("Invalid time {}: {}", value, e))
+}
+
+// A nullable time column, failing the row when it holds something else
+pub fn time_column(row: &Row, index: usize) -> rusqlite::Result<Option<DateTime<Utc>>> {
+    let value: Option<String> = row.get(index)?;
+    value
+        .map(|value| {
+            DateTime::parse_from_rfc3339(&value)
+                .map(|t| t.with_timezone(&Utc))
+                .map_err(|e| {
+                    rusqlite::Error::FromSqlConversionFailure(index, Type::Text, Box::new(e))
+                })
+        })
+        .transpose()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_times_round_trip() {
+        let time = parse_time("2026-10-16T08:00:00.250+02:00").unwrap();
+        assert_eq!(format_time(time), "2026-10-16T06:00:00.250Z");
+        assert!(parse_time("yesterday").is_err());
+
+        let database = Database::in_memory("test", "CREATE TABLE t (at TEXT);");
+        let connection = database.connection().unwrap();
+        connection
+            .execute(
+                "INSERT INTO t VALUES (?1), (NULL), ('soon')",
+                [format_time(time)],
+            )
+            .unwrap();
+        let mut statement = connection.prepare("SELECT at FROM t").unwrap();
+        let times: Vec<rusqlite::Result<Option<DateTime<Utc>>>> = statement
+            .query_map([], |row| Ok(time_column(row, 0)))
+            .unwrap()
+            .map(|row| row.unwrap())
+            .collect();
+        assert_eq!(times[0].as_ref().unwrap(), &Some(time));
+        assert_eq!(times[1].as_ref().unwrap(), &None);
+        assert!(times[2].is_err());
+    }
+}
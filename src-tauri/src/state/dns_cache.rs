@@ -0,0 +1,201 @@
+use crate::models::dns::DnsResponse;
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+// How long to remember answers without records (NXDOMAIN / NODATA)
+const NEGATIVE_TTL_SECS: u64 = 30;
+
+// Upper bound so long-TTL records are still re-checked during a debugging session
+const MAX_TTL_SECS: u64 = 3600;
+
+// (name, record type, resolver)
+type CacheKey = (String, String, String);
+
+struct CacheEntry {
+    response: DnsResponse,
+    expires_at: Instant,
+}
+
+// In-memory DNS response cache with TTL-aware expiry
+//
+// DNSSEC walks and delegation checks re-query the same NS/DNSKEY records many times.
+// Entries live for the smallest TTL in the answer (capped at MAX_TTL_SECS) and are
+// keyed by resolver, so answers from different nameservers are never mixed.
+pub struct DnsCache {
+    entries: Mutex<HashMap<CacheKey, CacheEntry>>,
+}
+
+impl DnsCache {
+    pub fn new() -> Self {
+        DnsCache {
+            entries: Mutex::new(HashMap::new()),
+        }
+    }
+
+    fn key(name: &str, record_type: &str, resolver: &str) -> CacheKey {
+        (
+            name.trim_end_matches('.').to_lowercase(),
+            record_type.to_uppercase(),
+            resolver.trim_end_matches('.').to_lowercase(),
+        )
+    }
+
+    // Return a cached response if present and not yet expired
+    pub fn get(&self, name: &str, record_type: &str, resolver: &str) -> Option<DnsResponse> {
+        let key = Self::key(name, record_type, resolver);
+        let mut entries = self.entries.lock().ok()?;
+
+        match entries.get(&key) {
+            Some(entry) if entry.expires_at > Instant::now() => {
+                let mut response = entry.response.clone();
+                response.from_cache = true;
+                Some(response)
+            }
+            Some(_) => {
+                entries.remove(&key);
+                None
+            }
+            None => None,
+        }
+    }
+
+    // Store a response for the lifetime of its smallest record TTL
+    pub fn insert(&self, name: &str, record_type: &str, resolver: &str, response: &DnsResponse) {
+        // Server failures are usually transient, retry them on the next lookup
+        if let Some(header) = &response.header {
+            if header.rcode == "SERVFAIL" || header.rcode == "REFUSED" {
+                return;
+            }
+        }
+
+        let ttl = match response.records.iter().map(|r| r.ttl as u64).min() {
+            Some(ttl) => ttl.min(MAX_TTL_SECS),
+            None => NEGATIVE_TTL_SECS,
+        };
+
+        // TTL 0 means "do not cache"
+        if ttl == 0 {
+            return;
+        }
+
+        if let Ok(mut entries) = self.entries.lock() {
+            entries.insert(
+                Self::key(name, record_type, resolver),
+                CacheEntry {
+                    response: response.clone(),
+                    expires_at: Instant::now() + Duration::from_secs(ttl),
+                },
+            );
+        }
+    }
+
+    // Drop every entry, returning how many were removed
+    pub fn clear(&self) -> usize {
+        match self.entries.lock() {
+            Ok(mut entries) => {
+                let count = entries.len();
+                entries.clear();
+                count
+            }
+            Err(_) => 0,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.lock().map(|e| e.len()).unwrap_or(0)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl Default for DnsCache {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::dns::{DnsHeader, DnsRecord};
+
+    fn response(ttls: &[u32]) -> DnsResponse {
+        DnsResponse {
+            records: ttls
+                .iter()
+                .map(|ttl| DnsRecord {
+                    name: "example.com.".to_string(),
+                    record_type: "A".to_string(),
+                    value: "93.184.216.34".to_string(),
+                    ttl: *ttl,
+                })
+                .collect(),
+            query_time: 0.05,
+            resolver: "system".to_string(),
+            header: None,
+            transport: "udp".to_string(),
+            server: None,
+            ip_version: None,
+            from_cache: false,
+            raw_output: None,
+        }
+    }
+
+    #[test]
+    fn test_cache_hit_is_case_and_dot_insensitive() {
+        let cache = DnsCache::new();
+        cache.insert("Example.com.", "a", "system", &response(&[300]));
+
+        let cached = cache.get("example.com", "A", "system").unwrap();
+        assert_eq!(cached.records.len(), 1);
+        assert!(cached.from_cache);
+    }
+
+    #[test]
+    fn test_cache_is_keyed_by_resolver() {
+        let cache = DnsCache::new();
+        cache.insert("example.com", "NS", "a.iana-servers.net", &response(&[300]));
+
+        assert!(cache
+            .get("example.com", "NS", "b.iana-servers.net")
+            .is_none());
+        assert!(cache
+            .get("example.com", "NS", "a.iana-servers.net")
+            .is_some());
+    }
+
+    #[test]
+    fn test_zero_ttl_is_not_cached() {
+        let cache = DnsCache::new();
+        cache.insert("example.com", "A", "system", &response(&[300, 0]));
+
+        assert!(cache.is_empty());
+    }
+
+    #[test]
+    fn test_servfail_is_not_cached() {
+        let cache = DnsCache::new();
+        let mut servfail = response(&[]);
+        servfail.header = Some(DnsHeader {
+            rcode: "SERVFAIL".to_string(),
+            ..Default::default()
+        });
+        cache.insert("example.com", "A", "system", &servfail);
+
+        assert!(cache.is_empty());
+    }
+
+    #[test]
+    fn test_clear_returns_removed_count() {
+        let cache = DnsCache::new();
+        cache.insert("example.com", "A", "system", &response(&[300]));
+        cache.insert("example.com", "AAAA", "system", &response(&[]));
+
+        assert_eq!(cache.len(), 2);
+        assert_eq!(cache.clear(), 2);
+        assert!(cache.get("example.com", "A", "system").is_none());
+    }
+}
@@ -0,0 +1,226 @@
+use super::database::{format_time, parse_time, Database};
+use crate::models::history::{ScanSummary, StoredScan};
+use crate::models::report::DomainReport;
+use rusqlite::{params, OptionalExtension};
+use std::path::Path;
+use tauri::AppHandle;
+
+const HISTORY_FILE: &str = "history.sqlite3";
+const HISTORY_NAME: &str = "scan history";
+const HISTORY_SCHEMA: &str = "CREATE TABLE IF NOT EXISTS scans (
+        id INTEGER PRIMARY KEY AUTOINCREMENT,
+        domain TEXT NOT NULL,
+        scanned_at TEXT NOT NULL,
+        score INTEGER,
+        grade TEXT,
+        report TEXT NOT NULL
+    );
+    CREATE INDEX IF NOT EXISTS scans_by_domain ON scans (domain, scanned_at);";
+
+// Completed domain scans kept in SQLite so earlier results can be reviewed
+//
+// The full report is stored as JSON; domain, time and grade are kept in their
+// own columns so listing doesn't have to parse every report.
+pub struct ScanHistory {
+    database: Database,
+}
+
+impl ScanHistory {
+    pub fn open(path: &Path) -> Result<Self, String> {
+        Database::open(path, HISTORY_NAME, HISTORY_SCHEMA).map(|database| ScanHistory { database })
+    }
+
+    // Kept for the session only: tests, or when the data directory isn't writable
+    pub fn in_memory() -> Self {
+        ScanHistory {
+            database: Database::in_memory(HISTORY_NAME, HISTORY_SCHEMA),
+        }
+    }
+
+    // Save a report, returning its scan ID
+    pub fn save(&self, report: &DomainReport) -> Result<i64, String> {
+        let json = serde_json::to_string(report)
+            .map_err(|e| format!("Failed to serialize the report: {}", e))?;
+        let health = report.health.as_ref();
+        let connection = self.database.connection()?;
+        connection
+            .execute(
+                "INSERT INTO scans (domain, scanned_at, score, grade, report) VALUES (?1, ?2, ?3, ?4, ?5)",
+                params![
+                    report.domain,
+                    format_time(report.started_at),
+                    health.map(|h| h.score),
+                    health.map(|h| h.grade.clone()),
+                    json,
+                ],
+            )
+            .map_err(|e| format!("Failed to save the scan: {}", e))?;
+        Ok(connection.last_insert_rowid())
+    }
+
+    // Newest first, optionally for one domain only
+    pub fn list(&self, domain: Option<&str>, limit: u32) -> Result<Vec<ScanSummary>, String> {
+        let connection = self.database.connection()?;
+        let mut statement = connection
+            .prepare(
+                "SELECT id, domain, scanned_at, score, grade FROM scans
+                 WHERE ?1 IS NULL OR domain = ?1
+                 ORDER BY scanned_at DESC, id DESC LIMIT ?2",
+            )
+            .map_err(|e| format!("Failed to read the history: {}", e))?;
+        let rows = statement
+            .query_map(params![domain, limit], |row| {
+                Ok((
+                    row.get::<_, i64>(0)?,
+                    row.get::<_, String>(1)?,
+                    row.get::<_, String>(2)?,
+                    row.get::<_, Option<u8>>(3)?,
+                    row.get::<_, Option<String>>(4)?,
+                ))
+            })
+            .map_err(|e| format!("Failed to read the history: {}", e))?;
+
+        let mut scans = Vec::new();
+        for row in rows {
+            let (id, domain, scanned_at, score, grade) =
+                row.map_err(|e| format!("Failed to read the history: {}", e))?;
+            scans.push(ScanSummary {
+                id,
+                domain,
+                scanned_at: parse_time(&scanned_at)?,
+                score,
+                grade,
+            });
+        }
+        Ok(scans)
+    }
+
+    pub fn get(&self, id: i64) -> Result<Option<StoredScan>, String> {
+        let connection = self.database.connection()?;
+        let row = connection
+            .query_row(
+                "SELECT domain, scanned_at, report FROM scans WHERE id = ?1",
+                params![id],
+                |row| {
+                    Ok((
+                        row.get::<_, String>(0)?,
+                        row.get::<_, String>(1)?,
+                        row.get::<_, String>(2)?,
+                    ))
+                },
+            )
+            .optional()
+            .map_err(|e| format!("Failed to read scan {}: {}", id, e))?;
+        let Some((domain, scanned_at, report)) = row else {
+            return Ok(None);
+        };
+
+        let mut report: DomainReport = serde_json::from_str(&report)
+            .map_err(|e| format!("Scan {} can't be read: {}", id, e))?;
+        report.scan_id = Some(id);
+        Ok(Some(StoredScan {
+            id,
+            domain,
+            scanned_at: parse_time(&scanned_at)?,
+            report,
+        }))
+    }
+
+    // Whether a scan with that ID existed
+    pub fn delete(&self, id: i64) -> Result<bool, String> {
+        let deleted = self
+            .database
+            .connection()?
+            .execute("DELETE FROM scans WHERE id = ?1", params![id])
+            .map_err(|e| format!("Failed to delete scan {}: {}", id, e))?;
+        Ok(deleted > 0)
+    }
+}
+
+impl Default for ScanHistory {
+    fn default() -> Self {
+        Self::in_memory()
+    }
+}
+
+// Open the history database in the app data directory
+pub fn load(app_handle: &AppHandle) -> ScanHistory {
+    ScanHistory {
+        database: Database::load(app_handle, HISTORY_FILE, HISTORY_NAME, HISTORY_SCHEMA),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn report(domain: &str, started_at: &str) -> DomainReport {
+        DomainReport {
+            domain: domain.to_string(),
+            started_at: parse_time(started_at).unwrap(),
+            total_time: 1.5,
+            dns: None,
+            dnssec: None,
+            whois: None,
+            certificate: None,
+            http: Vec::new(),
+            email: None,
+            errors: Vec::new(),
+            health: None,
+            scan_id: None,
+        }
+    }
+
+    #[test]
+    fn test_save_and_get() {
+        let history = ScanHistory::in_memory();
+        let id = history
+            .save(&report("example.com", "2026-10-09T08:00:00Z"))
+            .unwrap();
+
+        let scan = history.get(id).unwrap().unwrap();
+        assert_eq!(scan.domain, "example.com");
+        assert_eq!(scan.scanned_at, parse_time("2026-10-09T08:00:00Z").unwrap());
+        assert_eq!(scan.report.total_time, 1.5);
+        assert_eq!(scan.report.scan_id, Some(id));
+        assert!(history.get(id + 1).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_list_newest_first() {
+        let history = ScanHistory::in_memory();
+        history
+            .save(&report("example.com", "2026-10-09T08:00:00Z"))
+            .unwrap();
+        history
+            .save(&report("example.org", "2026-10-12T08:00:00Z"))
+            .unwrap();
+        history
+            .save(&report("example.com", "2026-10-16T08:00:00Z"))
+            .unwrap();
+
+        let all = history.list(None, 10).unwrap();
+        let domains: Vec<&str> = all.iter().map(|s| s.domain.as_str()).collect();
+        assert_eq!(domains, vec!["example.com", "example.org", "example.com"]);
+        assert_eq!(
+            all[0].scanned_at,
+            parse_time("2026-10-16T08:00:00Z").unwrap()
+        );
+
+        let one = history.list(Some("example.com"), 10).unwrap();
+        assert_eq!(one.len(), 2);
+        assert_eq!(history.list(None, 1).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_delete() {
+        let history = ScanHistory::in_memory();
+        let id = history
+            .save(&report("example.com", "2026-10-09T08:00:00Z"))
+            .unwrap();
+
+        assert!(history.delete(id).unwrap());
+        assert!(!history.delete(id).unwrap());
+        assert!(history.list(None, 10).unwrap().is_empty());
+    }
+}
@@ -0,0 +1,164 @@
+use crate::state::AppState;
+use std::any::Any;
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+use tauri::{AppHandle, Manager};
+use tokio::sync::OnceCell;
+
+// (operation, key), e.g. ("whois", "example.com")
+type CallKey = (String, String);
+
+type Call<T> = OnceCell<Result<T, String>>;
+
+// Lookups currently running, shared with identical ones started meanwhile
+//
+// The dashboard panels start at the same time and often ask for the same dig query,
+// WHOIS record or certificate. The first caller runs the work; later callers with
+// the same (operation, key) wait for its result instead of running their own. A
+// call is forgotten once it finishes, so nothing is cached here. If the caller
+// running the work is dropped, one of the waiting callers runs it instead.
+pub struct InFlight {
+    calls: Mutex<HashMap<CallKey, Arc<dyn Any + Send + Sync>>>,
+}
+
+impl InFlight {
+    pub fn new() -> Self {
+        InFlight {
+            calls: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub async fn run<T, F, Fut>(&self, operation: &str, key: &str, work: F) -> Result<T, String>
+    where
+        T: Clone + Send + Sync + 'static,
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = Result<T, String>>,
+    {
+        let id = (operation.to_string(), key.to_string());
+        let Some(call) = self.join::<T>(&id) else {
+            return work().await;
+        };
+        let result = call.get_or_init(work).await.clone();
+        self.finish(&id, &call);
+        result
+    }
+
+    // The call in flight for id, or a new one; None when the map is unavailable
+    fn join<T: Send + Sync + 'static>(&self, id: &CallKey) -> Option<Arc<Call<T>>> {
+        let mut calls = self.calls.lock().ok()?;
+        if let Some(call) = calls
+            .get(id)
+            .and_then(|c| c.clone().downcast::<Call<T>>().ok())
+        {
+            return Some(call);
+        }
+        let call = Arc::new(Call::<T>::new());
+        calls.insert(id.clone(), call.clone());
+        Some(call)
+    }
+
+    // Forget a finished call, unless a newer one already replaced it
+    fn finish<T>(&self, id: &CallKey, call: &Arc<Call<T>>) {
+        if let Ok(mut calls) = self.calls.lock() {
+            let current = calls.get(id).map(|c| Arc::as_ptr(c) as *const ());
+            if current == Some(Arc::as_ptr(call) as *const ()) {
+                calls.remove(id);
+            }
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.calls.lock().map(|calls| calls.len()).unwrap_or(0)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl Default for InFlight {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// Run work through the app's InFlight, or directly without managed state (tests, CLI)
+pub async fn coalesce<T, F, Fut>(
+    app_handle: Option<&AppHandle>,
+    operation: &str,
+    key: &str,
+    work: F,
+) -> Result<T, String>
+where
+    T: Clone + Send + Sync + 'static,
+    F: FnOnce() -> Fut,
+    Fut: Future<Output = Result<T, String>>,
+{
+    match app_handle.and_then(|h| h.try_state::<AppState>()) {
+        Some(state) => state.in_flight.run(operation, key, work).await,
+        None => work().await,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::time::Duration;
+
+    #[tokio::test]
+    async fn test_identical_calls_share_one_run() {
+        let in_flight = InFlight::new();
+        let runs = &AtomicUsize::new(0);
+        let lookup = |key: &'static str| {
+            in_flight.run("dig", key, move || async move {
+                runs.fetch_add(1, Ordering::SeqCst);
+                tokio::time::sleep(Duration::from_millis(50)).await;
+                Ok(format!("answer for {}", key))
+            })
+        };
+
+        let (a, b, c) = tokio::join!(
+            lookup("example.com A"),
+            lookup("example.com A"),
+            lookup("example.com MX")
+        );
+        assert_eq!(a.unwrap(), "answer for example.com A");
+        assert_eq!(b.unwrap(), "answer for example.com A");
+        assert_eq!(c.unwrap(), "answer for example.com MX");
+        assert_eq!(runs.load(Ordering::SeqCst), 2);
+        assert!(in_flight.is_empty());
+
+        // Finished calls aren't reused
+        lookup("example.com A").await.unwrap();
+        assert_eq!(runs.load(Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test]
+    async fn test_errors_are_shared_and_dropped_calls_rerun() {
+        let in_flight = InFlight::new();
+        let failing = || async {
+            tokio::time::sleep(Duration::from_millis(20)).await;
+            Err::<u32, String>("timed out".to_string())
+        };
+        let (a, b) = tokio::join!(
+            in_flight.run("whois", "example.com", failing),
+            in_flight.run("whois", "example.com", || async { Ok(1_u32) })
+        );
+        assert_eq!(a.unwrap_err(), "timed out");
+        assert_eq!(b.unwrap_err(), "timed out");
+
+        // A caller dropped mid-run leaves the call to the next one
+        let first = in_flight.run("tls", "example.com:443", || async {
+            tokio::time::sleep(Duration::from_secs(30)).await;
+            Ok(1)
+        });
+        assert!(tokio::time::timeout(Duration::from_millis(20), first)
+            .await
+            .is_err());
+        let second = in_flight.run("tls", "example.com:443", || async { Ok(2) });
+        assert_eq!(second.await.unwrap(), 2);
+        assert!(in_flight.is_empty());
+    }
+}
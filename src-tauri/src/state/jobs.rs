@@ -0,0 +1,347 @@
+use crate::models::job::{Job, JobStatus};
+use crate::state::AppState;
+use chrono::Utc;
+use std::collections::BTreeMap;
+use std::future::Future;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use tauri::{AppHandle, Emitter, Manager};
+use tokio::sync::Semaphore;
+use tokio::task::AbortHandle;
+
+// Jobs running at once; later ones wait as Queued until one finishes
+const MAX_RUNNING_JOBS: usize = 4;
+
+// Finished jobs kept for list_jobs, oldest dropped first
+const MAX_FINISHED_JOBS: usize = 50;
+
+struct Entry {
+    job: Job,
+    abort: Option<AbortHandle>, // Set once the job's task is spawned
+}
+
+struct Jobs {
+    next_id: AtomicU64,
+    entries: Mutex<BTreeMap<u64, Entry>>,
+    slots: Arc<Semaphore>,
+}
+
+impl Jobs {
+    // Change a job and announce it with a `job-updated` event
+    fn update(&self, id: u64, app_handle: Option<&AppHandle>, change: impl FnOnce(&mut Job)) {
+        let job = {
+            let Ok(mut entries) = self.entries.lock() else {
+                return;
+            };
+            let Some(entry) = entries.get_mut(&id) else {
+                return;
+            };
+            change(&mut entry.job);
+            entry.job.clone()
+        };
+        if let Some(app_handle) = app_handle {
+            let _ = app_handle.emit("job-updated", job);
+        }
+    }
+}
+
+// Long-running commands, each run as a job that can be listed and cancelled
+//
+// A job's work runs in its own task, so cancelling aborts it at its next await
+// point and the command that started it returns an error. Only a few jobs run at
+// once; the rest queue in the order they were started.
+pub struct JobManager {
+    jobs: Arc<Jobs>,
+}
+
+impl JobManager {
+    pub fn new() -> Self {
+        Self::with_limit(MAX_RUNNING_JOBS)
+    }
+
+    pub fn with_limit(running: usize) -> Self {
+        JobManager {
+            jobs: Arc::new(Jobs {
+                next_id: AtomicU64::new(1),
+                entries: Mutex::new(BTreeMap::new()),
+                slots: Arc::new(Semaphore::new(running.max(1))),
+            }),
+        }
+    }
+
+    // Newest first
+    pub fn list(&self) -> Vec<Job> {
+        self.jobs
+            .entries
+            .lock()
+            .map(|entries| entries.values().rev().map(|e| e.job.clone()).collect())
+            .unwrap_or_default()
+    }
+
+    pub fn get(&self, id: u64) -> Option<Job> {
+        let entries = self.jobs.entries.lock().ok()?;
+        entries.get(&id).map(|e| e.job.clone())
+    }
+
+    pub fn cancel(&self, id: u64, app_handle: Option<&AppHandle>) -> Result<(), String> {
+        {
+            let mut entries = self
+                .jobs
+                .entries
+                .lock()
+                .map_err(|_| "The job list is unavailable".to_string())?;
+            let entry = entries
+                .get_mut(&id)
+                .ok_or_else(|| format!("No job with ID {}", id))?;
+            if entry.job.status.is_finished() {
+                return Err(format!("Job {} has already finished", id));
+            }
+            if let Some(abort) = &entry.abort {
+                abort.abort();
+            }
+        }
+        self.jobs.update(id, app_handle, |job| {
+            job.status = JobStatus::Cancelled;
+            job.finished_at = Some(Utc::now());
+        });
+        Ok(())
+    }
+
+    // Run work as a job and return its result. work gets a JobHandle to report progress.
+    pub async fn run<T, F, Fut>(
+        &self,
+        app_handle: Option<AppHandle>,
+        kind: &str,
+        target: &str,
+        work: F,
+    ) -> Result<T, String>
+    where
+        F: FnOnce(JobHandle) -> Fut,
+        Fut: Future<Output = Result<T, String>> + Send + 'static,
+        T: Send + 'static,
+    {
+        let id = self.jobs.next_id.fetch_add(1, Ordering::SeqCst);
+        let job = Job {
+            id,
+            kind: kind.to_string(),
+            target: target.to_string(),
+            status: JobStatus::Queued,
+            completed: 0,
+            total: 0,
+            message: None,
+            created_at: Utc::now(),
+            started_at: None,
+            finished_at: None,
+            error: None,
+        };
+        if let Ok(mut entries) = self.jobs.entries.lock() {
+            entries.insert(id, Entry { job, abort: None });
+        }
+        self.jobs.update(id, app_handle.as_ref(), |_| {});
+
+        let handle = JobHandle {
+            id: Some(id),
+            jobs: Some(self.jobs.clone()),
+            app_handle: app_handle.clone(),
+        };
+        let future = work(handle.clone());
+        let slots = self.jobs.slots.clone();
+        let task = tokio::spawn(async move {
+            let _slot = slots.acquire_owned().await;
+            handle.start();
+            future.await
+        });
+
+        // Cancelled before the task existed: stop it now
+        if let Ok(mut entries) = self.jobs.entries.lock() {
+            if let Some(entry) = entries.get_mut(&id) {
+                entry.abort = Some(task.abort_handle());
+                if entry.job.status == JobStatus::Cancelled {
+                    task.abort();
+                }
+            }
+        }
+
+        let result = match task.await {
+            Ok(result) => result,
+            Err(e) if e.is_cancelled() => Err(format!("Job {} was cancelled", id)),
+            Err(e) => Err(format!("Job {} failed: {}", id, e)),
+        };
+        self.jobs.update(id, app_handle.as_ref(), |job| {
+            if job.status == JobStatus::Cancelled {
+                return;
+            }
+            job.status = if result.is_ok() {
+                JobStatus::Completed
+            } else {
+                JobStatus::Failed
+            };
+            job.error = result.as_ref().err().cloned();
+            job.finished_at = Some(Utc::now());
+        });
+        self.prune();
+        result
+    }
+
+    fn prune(&self) {
+        let Ok(mut entries) = self.jobs.entries.lock() else {
+            return;
+        };
+        let finished: Vec<u64> = entries
+            .iter()
+            .filter(|(_, e)| e.job.status.is_finished())
+            .map(|(id, _)| *id)
+            .collect();
+        for id in finished
+            .iter()
+            .take(finished.len().saturating_sub(MAX_FINISHED_JOBS))
+        {
+            entries.remove(id);
+        }
+    }
+}
+
+impl Default for JobManager {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// Given to a job's work to report its progress
+#[derive(Clone)]
+pub struct JobHandle {
+    id: Option<u64>,
+    jobs: Option<Arc<Jobs>>,
+    app_handle: Option<AppHandle>,
+}
+
+impl JobHandle {
+    // For work run outside the job manager, e.g. without managed state in tests
+    pub fn detached() -> Self {
+        JobHandle {
+            id: None,
+            jobs: None,
+            app_handle: None,
+        }
+    }
+
+    pub fn id(&self) -> Option<u64> {
+        self.id
+    }
+
+    pub fn progress(&self, completed: usize, total: usize, message: Option<String>) {
+        self.update(|job| {
+            if job.status == JobStatus::Running {
+                job.completed = completed;
+                job.total = total;
+                job.message = message;
+            }
+        });
+    }
+
+    fn start(&self) {
+        self.update(|job| {
+            if job.status == JobStatus::Queued {
+                job.status = JobStatus::Running;
+                job.started_at = Some(Utc::now());
+            }
+        });
+    }
+
+    fn update(&self, change: impl FnOnce(&mut Job)) {
+        if let (Some(id), Some(jobs)) = (self.id, &self.jobs) {
+            jobs.update(id, self.app_handle.as_ref(), change);
+        }
+    }
+}
+
+// Run work as a job of the app's JobManager, or directly when there is no managed state
+pub async fn run<T, F, Fut>(
+    app_handle: &AppHandle,
+    kind: &str,
+    target: &str,
+    work: F,
+) -> Result<T, String>
+where
+    F: FnOnce(JobHandle) -> Fut,
+    Fut: Future<Output = Result<T, String>> + Send + 'static,
+    T: Send + 'static,
+{
+    match app_handle.try_state::<AppState>() {
+        Some(state) => {
+            state
+                .jobs
+                .run(Some(app_handle.clone()), kind, target, work)
+                .await
+        }
+        None => work(JobHandle::detached()).await,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[tokio::test]
+    async fn test_run_completes() {
+        let manager = JobManager::new();
+        let result = manager
+            .run(None, "analyze_domain", "example.com", |job| async move {
+                job.progress(1, 2, Some("dns".to_string()));
+                Ok(job.id())
+            })
+            .await;
+        let id = result.unwrap().unwrap();
+
+        let job = manager.get(id).unwrap();
+        assert_eq!(job.status, JobStatus::Completed);
+        assert_eq!(job.kind, "analyze_domain");
+        assert_eq!((job.completed, job.total), (1, 2));
+        assert_eq!(job.message.as_deref(), Some("dns"));
+        assert!(job.started_at.is_some() && job.finished_at.is_some());
+
+        let failed: Result<(), String> = manager
+            .run(None, "validate_dnssec", "example.com", |_| async {
+                Err("timed out".to_string())
+            })
+            .await;
+        assert!(failed.is_err());
+        let jobs = manager.list();
+        assert_eq!(jobs[0].status, JobStatus::Failed);
+        assert_eq!(jobs[0].error.as_deref(), Some("timed out"));
+    }
+
+    #[tokio::test]
+    async fn test_cancel_running_and_queued() {
+        let manager = Arc::new(JobManager::with_limit(1));
+        let slow = |_| async {
+            tokio::time::sleep(Duration::from_secs(30)).await;
+            Ok::<(), String>(())
+        };
+        let running = tokio::spawn({
+            let manager = manager.clone();
+            async move { manager.run(None, "scan_domains", "2 domains", slow).await }
+        });
+        let queued = tokio::spawn({
+            let manager = manager.clone();
+            async move { manager.run(None, "scan_domains", "3 domains", slow).await }
+        });
+        tokio::time::sleep(Duration::from_millis(50)).await;
+
+        let statuses: Vec<JobStatus> = manager.list().iter().map(|j| j.status).collect();
+        assert_eq!(statuses, vec![JobStatus::Queued, JobStatus::Running]);
+
+        let ids: Vec<u64> = manager.list().iter().map(|j| j.id).collect();
+        manager.cancel(ids[0], None).unwrap();
+        manager.cancel(ids[1], None).unwrap();
+        assert!(running.await.unwrap().unwrap_err().contains("cancelled"));
+        assert!(queued.await.unwrap().unwrap_err().contains("cancelled"));
+        assert!(manager
+            .list()
+            .iter()
+            .all(|j| j.status == JobStatus::Cancelled));
+        assert!(manager.cancel(ids[0], None).is_err());
+        assert!(manager.cancel(999, None).is_err());
+    }
+}
@@ -0,0 +1,136 @@
+pub mod command_logs;
+pub mod database;
+pub mod dns_cache;
+pub mod history;
+pub mod in_flight;
+pub mod jobs;
+pub mod performance;
+pub mod rate_limiter;
+pub mod rdap_bootstrap;
+pub mod saved_domains;
+pub mod session_domains;
+pub mod settings;
+pub mod watchlist;
+pub mod whois_cooldown;
+
+use crate::adapters::backend::Backends;
+use crate::models::settings::Settings;
+use chrono::{DateTime, Utc};
+use command_logs::CommandLogs;
+use dns_cache::DnsCache;
+use history::ScanHistory;
+use in_flight::InFlight;
+use jobs::JobManager;
+use performance::Performance;
+use rate_limiter::RateLimiter;
+use rdap_bootstrap::RdapBootstrap;
+use saved_domains::SavedDomains;
+use session_domains::SessionDomains;
+use std::sync::RwLock;
+use tauri::{AppHandle, Manager};
+use watchlist::Watchlist;
+use whois_cooldown::WhoisCooldowns;
+
+// Application-wide state managed by Tauri (see `lib.rs`)
+// Adapters reach it through their AppHandle, so commands don't need extra parameters
+pub struct AppState {
+    pub command_logs: CommandLogs,
+    pub dns_cache: DnsCache,
+    pub rate_limiter: RateLimiter,
+    pub rdap_bootstrap: RdapBootstrap,
+    pub whois_cooldowns: WhoisCooldowns,
+    pub history: ScanHistory,
+    pub in_flight: InFlight,
+    pub jobs: JobManager,
+    pub performance: Performance,
+    pub saved_domains: SavedDomains,
+    pub session_domains: SessionDomains,
+    pub watchlist: Watchlist,
+    pub started_at: DateTime<Utc>, // When the app started
+    settings: RwLock<Settings>,
+    backends: RwLock<Backends>,
+}
+
+impl AppState {
+    pub fn new() -> Self {
+        Self::with_settings(Settings::default())
+    }
+
+    pub fn with_settings(settings: Settings) -> Self {
+        AppState {
+            command_logs: CommandLogs::new(),
+            dns_cache: DnsCache::new(),
+            rate_limiter: RateLimiter::new(),
+            rdap_bootstrap: RdapBootstrap::new(),
+            whois_cooldowns: WhoisCooldowns::new(),
+            history: ScanHistory::in_memory(),
+            in_flight: InFlight::new(),
+            jobs: JobManager::new(),
+            performance: Performance::new(),
+            saved_domains: SavedDomains::in_memory(),
+            session_domains: SessionDomains::new(),
+            watchlist: Watchlist::in_memory(),
+            started_at: Utc::now(),
+            settings: RwLock::new(settings),
+            backends: RwLock::new(Backends::default()),
+        }
+    }
+
+    pub fn with_history(mut self, history: ScanHistory) -> Self {
+        self.history = history;
+        self
+    }
+
+    pub fn with_saved_domains(mut self, saved_domains: SavedDomains) -> Self {
+        self.saved_domains = saved_domains;
+        self
+    }
+
+    pub fn with_watchlist(mut self, watchlist: Watchlist) -> Self {
+        self.watchlist = watchlist;
+        self
+    }
+
+    pub fn settings(&self) -> Settings {
+        self.settings.read().map(|s| s.clone()).unwrap_or_default()
+    }
+
+    pub fn set_settings(&self, settings: Settings) {
+        if let Ok(mut current) = self.settings.write() {
+            *current = settings;
+        }
+    }
+
+    pub fn backends(&self) -> Backends {
+        self.backends.read().map(|b| b.clone()).unwrap_or_default()
+    }
+
+    // Adapters created from now on use these
+    pub fn set_backends(&self, backends: Backends) {
+        if let Ok(mut current) = self.backends.write() {
+            *current = backends;
+        }
+    }
+}
+
+impl Default for AppState {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// Current settings for an adapter, or defaults when running without an AppHandle (tests, CLI)
+pub fn settings_for(app_handle: Option<&AppHandle>) -> Settings {
+    app_handle
+        .and_then(|h| h.try_state::<AppState>())
+        .map(|state| state.settings())
+        .unwrap_or_default()
+}
+
+// Backends for an adapter, or the defaults when running without an AppHandle (tests, CLI)
+pub fn backends_for(app_handle: Option<&AppHandle>) -> Backends {
+    app_handle
+        .and_then(|h| h.try_state::<AppState>())
+        .map(|state| state.backends())
+        .unwrap_or_default()
+}
@@ -0,0 +1,188 @@
+use crate::models::command_log::CommandLog;
+use crate::models::performance::{OperationStats, PerformanceStats};
+use chrono::{DateTime, Utc};
+use std::collections::{BTreeMap, VecDeque};
+use std::sync::Mutex;
+
+// Durations kept per tool and operation for the percentiles; counts cover the session
+const MAX_SAMPLES: usize = 1000;
+
+#[derive(Default)]
+struct Samples {
+    durations: VecDeque<f64>, // Most recent last
+    count: u64,
+    failures: u64,
+    throttled_ms: f64,
+}
+
+impl Samples {
+    fn add(&mut self, log: &CommandLog) {
+        if self.durations.len() >= MAX_SAMPLES {
+            self.durations.pop_front();
+        }
+        self.durations.push_back(log.duration);
+        self.count += 1;
+        if log.exit_code != 0 {
+            self.failures += 1;
+        }
+        self.throttled_ms += log.throttled_ms.unwrap_or(0.0);
+    }
+
+    fn stats(&self, tool: &str, operation: Option<&str>) -> OperationStats {
+        let mut sorted: Vec<f64> = self.durations.iter().copied().collect();
+        sorted.sort_by(f64::total_cmp);
+        let mean_ms = if sorted.is_empty() {
+            0.0
+        } else {
+            sorted.iter().sum::<f64>() / sorted.len() as f64
+        };
+        OperationStats {
+            tool: tool.to_string(),
+            operation: operation.map(str::to_string),
+            count: self.count,
+            failures: self.failures,
+            failure_rate: self.failures as f64 / self.count.max(1) as f64,
+            mean_ms,
+            p50_ms: percentile(&sorted, 50.0),
+            p90_ms: percentile(&sorted, 90.0),
+            p99_ms: percentile(&sorted, 99.0),
+            max_ms: sorted.last().copied().unwrap_or(0.0),
+            throttled_ms: self.throttled_ms,
+        }
+    }
+}
+
+// Nearest-rank percentile of sorted durations
+fn percentile(sorted: &[f64], p: f64) -> f64 {
+    if sorted.is_empty() {
+        return 0.0;
+    }
+    let rank = (p / 100.0 * sorted.len() as f64).ceil() as usize;
+    sorted[rank.clamp(1, sorted.len()) - 1]
+}
+
+// Latency and failures of every command the adapters run, for get_performance_stats
+//
+// Each command log is counted under its tool and under its operation (see
+// CommandLog::operation), so slow spots like DS queries to one TLD's nameservers
+// stand out from the tool's overall numbers.
+pub struct Performance {
+    since: DateTime<Utc>,
+    samples: Mutex<BTreeMap<(String, Option<String>), Samples>>,
+}
+
+impl Performance {
+    pub fn new() -> Self {
+        Performance {
+            since: Utc::now(),
+            samples: Mutex::new(BTreeMap::new()),
+        }
+    }
+
+    pub fn record(&self, log: &CommandLog) {
+        let Ok(mut samples) = self.samples.lock() else {
+            return;
+        };
+        samples
+            .entry((log.tool.clone(), None))
+            .or_default()
+            .add(log);
+        if let Some(operation) = log.operation() {
+            samples
+                .entry((log.tool.clone(), Some(operation)))
+                .or_default()
+                .add(log);
+        }
+    }
+
+    // Each tool's overall numbers first, then its operations slowest (p90) first
+    pub fn stats(&self) -> PerformanceStats {
+        let mut operations: Vec<OperationStats> = self
+            .samples
+            .lock()
+            .map(|samples| {
+                samples
+                    .iter()
+                    .map(|((tool, operation), s)| s.stats(tool, operation.as_deref()))
+                    .collect()
+            })
+            .unwrap_or_default();
+        operations.sort_by(|a, b| {
+            a.tool
+                .cmp(&b.tool)
+                .then(a.operation.is_some().cmp(&b.operation.is_some()))
+                .then(b.p90_ms.total_cmp(&a.p90_ms))
+        });
+        PerformanceStats {
+            since: self.since,
+            operations,
+        }
+    }
+}
+
+impl Default for Performance {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn log(tool: &str, args: &[&str], exit_code: i32, duration: f64) -> CommandLog {
+        CommandLog::new(
+            tool.to_string(),
+            args.iter().map(|a| a.to_string()).collect(),
+            String::new(),
+            exit_code,
+            duration,
+            Some("example.com".to_string()),
+        )
+    }
+
+    #[test]
+    fn test_percentiles() {
+        let sorted: Vec<f64> = (1..=100).map(f64::from).collect();
+        assert_eq!(percentile(&sorted, 50.0), 50.0);
+        assert_eq!(percentile(&sorted, 90.0), 90.0);
+        assert_eq!(percentile(&sorted, 99.0), 99.0);
+        assert_eq!(percentile(&[7.0], 99.0), 7.0);
+        assert_eq!(percentile(&[], 50.0), 0.0);
+    }
+
+    #[test]
+    fn test_stats_by_tool_and_operation() {
+        let performance = Performance::new();
+        for duration in [10.0, 20.0, 30.0] {
+            performance.record(&log("dig", &["+time=5", "A", "example.com"], 0, duration));
+        }
+        let ds = ["+norec", "@a.gtld-servers.net", "DS", "example.com"];
+        performance.record(&log("dig", &ds, 0, 400.0));
+        performance.record(&log("dig", &ds, 9, 5000.0));
+        performance.record(&log("curl", &["-I", "https://example.com/"], 0, 80.0));
+
+        let stats = performance.stats().operations;
+        let rows: Vec<(&str, Option<&str>, u64)> = stats
+            .iter()
+            .map(|s| (s.tool.as_str(), s.operation.as_deref(), s.count))
+            .collect();
+        assert_eq!(
+            rows,
+            vec![
+                ("curl", None, 1),
+                ("dig", None, 5),
+                ("dig", Some("DS @a.gtld-servers.net"), 2),
+                ("dig", Some("A"), 3),
+            ]
+        );
+
+        let dig = &stats[1];
+        assert_eq!(dig.failures, 1);
+        assert_eq!(dig.failure_rate, 0.2);
+        assert_eq!(dig.p50_ms, 30.0);
+        assert_eq!(dig.max_ms, 5000.0);
+        assert_eq!(stats[2].failure_rate, 0.5);
+        assert_eq!(stats[3].mean_ms, 20.0);
+    }
+}
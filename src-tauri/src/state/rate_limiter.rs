@@ -0,0 +1,188 @@
+use crate::state::AppState;
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Manager};
+
+// Once more servers than this have buckets, idle (full) ones are dropped
+const MAX_IDLE_BUCKETS: usize = 256;
+
+// What a rate limit applies to, each with its own limit in the settings
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Target {
+    Nameserver,  // dig @server
+    WhoisServer, // Port-43 WHOIS
+    Host,        // TLS handshakes and HTTP requests
+}
+
+impl Target {
+    fn as_str(&self) -> &'static str {
+        match self {
+            Target::Nameserver => "nameserver",
+            Target::WhoisServer => "whois",
+            Target::Host => "host",
+        }
+    }
+}
+
+struct Bucket {
+    tokens: f64, // Negative while callers are queued for the next tokens
+    capacity: f64,
+    per_second: f64,
+    updated: Instant,
+}
+
+impl Bucket {
+    fn refill(&mut self, now: Instant) {
+        let elapsed = now.saturating_duration_since(self.updated).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * self.per_second).min(self.capacity);
+        self.updated = self.updated.max(now);
+    }
+}
+
+// Token buckets per external server, shared by every command
+//
+// Bulk operations and DNSSEC walks send many queries to the same TLD nameservers
+// and WHOIS servers, which block clients that exceed their limits. Each server
+// gets a bucket refilled at the configured rate that holds up to one second's
+// worth (at least one) of tokens; a caller without a token waits its turn.
+pub struct RateLimiter {
+    buckets: Mutex<HashMap<String, Bucket>>,
+}
+
+impl RateLimiter {
+    pub fn new() -> Self {
+        RateLimiter {
+            buckets: Mutex::new(HashMap::new()),
+        }
+    }
+
+    // Take a token for key, returning how long to wait before using it
+    pub fn reserve(&self, key: &str, per_second: f64) -> Duration {
+        self.reserve_at(key, per_second, Instant::now())
+    }
+
+    pub fn reserve_at(&self, key: &str, per_second: f64, now: Instant) -> Duration {
+        if per_second <= 0.0 {
+            return Duration::ZERO;
+        }
+        let Ok(mut buckets) = self.buckets.lock() else {
+            return Duration::ZERO;
+        };
+        if buckets.len() > MAX_IDLE_BUCKETS {
+            buckets.retain(|_, bucket| {
+                bucket.refill(now);
+                bucket.tokens < bucket.capacity
+            });
+        }
+
+        let capacity = per_second.max(1.0);
+        let bucket = buckets.entry(key.to_lowercase()).or_insert(Bucket {
+            tokens: capacity,
+            capacity,
+            per_second,
+            updated: now,
+        });
+        // The limit may have changed in the settings since the last call
+        bucket.capacity = capacity;
+        bucket.per_second = per_second;
+        bucket.refill(now);
+
+        bucket.tokens -= 1.0;
+        if bucket.tokens >= 0.0 {
+            Duration::ZERO
+        } else {
+            Duration::from_secs_f64(-bucket.tokens / per_second)
+        }
+    }
+}
+
+impl Default for RateLimiter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// Wait until target may be contacted under the app's limits, returning how long that
+// took; no limits apply without managed state (tests, CLI)
+pub async fn throttle(app_handle: Option<&AppHandle>, kind: Target, target: &str) -> Duration {
+    let Some(state) = app_handle.and_then(|h| h.try_state::<AppState>()) else {
+        return Duration::ZERO;
+    };
+    let settings = state.settings();
+    let per_second = match kind {
+        Target::Nameserver => f64::from(settings.nameserver_queries_per_second),
+        Target::WhoisServer => f64::from(settings.whois_queries_per_minute) / 60.0,
+        Target::Host => f64::from(settings.host_requests_per_second),
+    };
+    let key = format!("{}:{}", kind.as_str(), target.trim_end_matches('.'));
+    let wait = state.rate_limiter.reserve(&key, per_second);
+    if !wait.is_zero() {
+        tokio::time::sleep(wait).await;
+    }
+    wait
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_burst_then_wait() {
+        let limiter = RateLimiter::new();
+        let now = Instant::now();
+
+        for _ in 0..5 {
+            assert_eq!(
+                limiter.reserve_at("a.gtld-servers.net", 5.0, now),
+                Duration::ZERO
+            );
+        }
+        // Queued callers wait one interval more each
+        assert_eq!(
+            limiter.reserve_at("A.GTLD-servers.net", 5.0, now),
+            Duration::from_millis(200)
+        );
+        assert_eq!(
+            limiter.reserve_at("a.gtld-servers.net", 5.0, now),
+            Duration::from_millis(400)
+        );
+        // Other servers have their own bucket
+        assert_eq!(
+            limiter.reserve_at("b.gtld-servers.net", 5.0, now),
+            Duration::ZERO
+        );
+
+        // Refilled, but only up to the burst size
+        let later = now + Duration::from_secs(60);
+        for _ in 0..5 {
+            assert_eq!(
+                limiter.reserve_at("a.gtld-servers.net", 5.0, later),
+                Duration::ZERO
+            );
+        }
+        assert!(!limiter
+            .reserve_at("a.gtld-servers.net", 5.0, later)
+            .is_zero());
+    }
+
+    #[test]
+    fn test_slow_rate_allows_one_at_a_time() {
+        let limiter = RateLimiter::new();
+        let now = Instant::now();
+
+        // 30 per minute
+        assert_eq!(
+            limiter.reserve_at("whois.verisign-grs.com", 0.5, now),
+            Duration::ZERO
+        );
+        assert_eq!(
+            limiter.reserve_at("whois.verisign-grs.com", 0.5, now),
+            Duration::from_secs(2)
+        );
+        assert_eq!(
+            limiter.reserve_at("whois.verisign-grs.com", 0.5, now + Duration::from_secs(4)),
+            Duration::ZERO
+        );
+    }
+}
@@ -0,0 +1,132 @@
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+// IANA's bootstrap file only changes when a TLD moves RDAP servers
+const BOOTSTRAP_TTL_SECS: u64 = 86400;
+
+struct Services {
+    base_urls: HashMap<String, String>,
+    fetched_at: Instant,
+}
+
+// RDAP base URL per TLD from the IANA bootstrap registry (RFC 9224)
+//
+// Fetched once per day instead of once per lookup, so bulk WHOIS doesn't
+// re-download the ~100 KB file for every domain.
+pub struct RdapBootstrap {
+    services: Mutex<Option<Services>>,
+}
+
+impl RdapBootstrap {
+    pub fn new() -> Self {
+        RdapBootstrap {
+            services: Mutex::new(None),
+        }
+    }
+
+    // Base URL for a TLD, if the bootstrap file is loaded and still fresh
+    // Some(None) means the registry is loaded but the TLD has no RDAP service
+    pub fn base_url(&self, tld: &str) -> Option<Option<String>> {
+        let services = self.services.lock().ok()?;
+        let services = services.as_ref()?;
+        if services.fetched_at.elapsed() > Duration::from_secs(BOOTSTRAP_TTL_SECS) {
+            return None;
+        }
+        Some(services.base_urls.get(&tld.to_lowercase()).cloned())
+    }
+
+    pub fn store(&self, base_urls: HashMap<String, String>) {
+        if let Ok(mut services) = self.services.lock() {
+            *services = Some(Services {
+                base_urls,
+                fetched_at: Instant::now(),
+            });
+        }
+    }
+
+    // Parse IANA's dns.json: {"services": [[["com", "net"], ["https://..."]], ...]}
+    // Prefers an HTTPS URL when a service lists several
+    pub fn parse(json: &str) -> Result<HashMap<String, String>, String> {
+        let value: serde_json::Value = serde_json::from_str(json)
+            .map_err(|e| format!("Failed to parse RDAP bootstrap: {}", e))?;
+        let services = value["services"]
+            .as_array()
+            .ok_or("RDAP bootstrap has no services")?;
+
+        let mut base_urls = HashMap::new();
+        for service in services {
+            let urls: Vec<&str> = service[1]
+                .as_array()
+                .map(|urls| urls.iter().filter_map(|u| u.as_str()).collect())
+                .unwrap_or_default();
+            let url = urls
+                .iter()
+                .find(|u| u.starts_with("https://"))
+                .or(urls.first());
+            let Some(url) = url else {
+                continue;
+            };
+
+            for tld in service[0].as_array().into_iter().flatten() {
+                if let Some(tld) = tld.as_str() {
+                    base_urls.insert(tld.to_lowercase(), url.to_string());
+                }
+            }
+        }
+
+        Ok(base_urls)
+    }
+}
+
+impl Default for RdapBootstrap {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BOOTSTRAP: &str = r#"{
+        "version": "1.0",
+        "services": [
+            [["com", "net"], ["http://rdap.verisign.com/com/v1/", "https://rdap.verisign.com/com/v1/"]],
+            [["ORG"], ["https://rdap.publicinterestregistry.org/rdap/"]],
+            [["empty"], []]
+        ]
+    }"#;
+
+    #[test]
+    fn test_parse_prefers_https() {
+        let base_urls = RdapBootstrap::parse(BOOTSTRAP).unwrap();
+
+        assert_eq!(base_urls["com"], "https://rdap.verisign.com/com/v1/");
+        assert_eq!(base_urls["net"], "https://rdap.verisign.com/com/v1/");
+        assert_eq!(
+            base_urls["org"],
+            "https://rdap.publicinterestregistry.org/rdap/"
+        );
+        assert!(!base_urls.contains_key("empty"));
+    }
+
+    #[test]
+    fn test_base_url_needs_loaded_registry() {
+        let bootstrap = RdapBootstrap::new();
+        assert_eq!(bootstrap.base_url("com"), None);
+
+        bootstrap.store(RdapBootstrap::parse(BOOTSTRAP).unwrap());
+        assert_eq!(
+            bootstrap.base_url("COM"),
+            Some(Some("https://rdap.verisign.com/com/v1/".to_string()))
+        );
+        assert_eq!(bootstrap.base_url("unknowntld"), Some(None));
+    }
+
+    #[test]
+    fn test_parse_rejects_garbage() {
+        assert!(RdapBootstrap::parse("<html>").is_err());
+        assert!(RdapBootstrap::parse("{}").is_err());
+    }
+}
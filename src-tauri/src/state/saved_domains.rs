@@ -0,0 +1,215 @@
+use super::database::{format_time, time_column, Database};
+use crate::models::saved_domain::SavedDomain;
+use chrono::{DateTime, Utc};
+use rusqlite::types::Type;
+use rusqlite::{params, OptionalExtension, Row};
+use std::path::Path;
+use tauri::AppHandle;
+
+const SAVED_DOMAINS_FILE: &str = "saved_domains.sqlite3";
+const SAVED_DOMAINS_NAME: &str = "saved domains";
+const SAVED_DOMAINS_SCHEMA: &str = "CREATE TABLE IF NOT EXISTS saved_domains (
+        domain TEXT PRIMARY KEY,
+        added_at TEXT NOT NULL,
+        tags TEXT NOT NULL DEFAULT '[]'
+    );";
+
+// Domains the user checks often, with their tags, kept in SQLite
+//
+// Only the domain, tags and when it was saved are stored here; the latest scan
+// comes from the scan history when the domains are listed.
+pub struct SavedDomains {
+    database: Database,
+}
+
+impl SavedDomains {
+    pub fn open(path: &Path) -> Result<Self, String> {
+        Database::open(path, SAVED_DOMAINS_NAME, SAVED_DOMAINS_SCHEMA)
+            .map(|database| SavedDomains { database })
+    }
+
+    pub fn in_memory() -> Self {
+        SavedDomains {
+            database: Database::in_memory(SAVED_DOMAINS_NAME, SAVED_DOMAINS_SCHEMA),
+        }
+    }
+
+    // Save a domain; saving one that's already saved keeps its tags unless new ones are given
+    pub fn save(
+        &self,
+        domain: &str,
+        tags: Option<&[String]>,
+        now: DateTime<Utc>,
+    ) -> Result<SavedDomain, String> {
+        self.database
+            .connection()?
+            .execute(
+                "INSERT OR IGNORE INTO saved_domains (domain, added_at) VALUES (?1, ?2)",
+                params![domain, format_time(now)],
+            )
+            .map_err(|e| format!("Failed to save {}: {}", domain, e))?;
+        if let Some(tags) = tags {
+            self.set_tags(domain, tags)?;
+        }
+        self.get(domain)?
+            .ok_or_else(|| format!("Failed to save {}", domain))
+    }
+
+    // Replace a saved domain's tags; false if the domain isn't saved
+    pub fn set_tags(&self, domain: &str, tags: &[String]) -> Result<bool, String> {
+        let tags =
+            serde_json::to_string(tags).map_err(|e| format!("Failed to serialize tags: {}", e))?;
+        let updated = self
+            .database
+            .connection()?
+            .execute(
+                "UPDATE saved_domains SET tags = ?2 WHERE domain = ?1",
+                params![domain, tags],
+            )
+            .map_err(|e| format!("Failed to tag {}: {}", domain, e))?;
+        Ok(updated > 0)
+    }
+
+    // Whether the domain was saved
+    pub fn remove(&self, domain: &str) -> Result<bool, String> {
+        let deleted = self
+            .database
+            .connection()?
+            .execute(
+                "DELETE FROM saved_domains WHERE domain = ?1",
+                params![domain],
+            )
+            .map_err(|e| format!("Failed to remove {}: {}", domain, e))?;
+        Ok(deleted > 0)
+    }
+
+    pub fn get(&self, domain: &str) -> Result<Option<SavedDomain>, String> {
+        self.database
+            .connection()?
+            .query_row(
+                "SELECT domain, added_at, tags FROM saved_domains WHERE domain = ?1",
+                params![domain],
+                read_row,
+            )
+            .optional()
+            .map_err(|e| format!("Failed to read the saved domains: {}", e))
+    }
+
+    // Sorted by domain; only those with the tag when one is given (case-insensitive)
+    pub fn list(&self, tag: Option<&str>) -> Result<Vec<SavedDomain>, String> {
+        let connection = self.database.connection()?;
+        let mut statement = connection
+            .prepare("SELECT domain, added_at, tags FROM saved_domains ORDER BY domain")
+            .map_err(|e| format!("Failed to read the saved domains: {}", e))?;
+        let rows = statement
+            .query_map([], read_row)
+            .map_err(|e| format!("Failed to read the saved domains: {}", e))?;
+
+        let mut domains = Vec::new();
+        for row in rows {
+            let saved = row.map_err(|e| format!("Failed to read the saved domains: {}", e))?;
+            if tag.is_none_or(|tag| saved.tags.iter().any(|t| t.eq_ignore_ascii_case(tag))) {
+                domains.push(saved);
+            }
+        }
+        Ok(domains)
+    }
+}
+
+impl Default for SavedDomains {
+    fn default() -> Self {
+        Self::in_memory()
+    }
+}
+
+fn read_row(row: &Row) -> rusqlite::Result<SavedDomain> {
+    let tags: String = row.get(2)?;
+    Ok(SavedDomain {
+        domain: row.get(0)?,
+        added_at: time_column(row, 1)?.unwrap_or_default(),
+        tags: serde_json::from_str(&tags)
+            .map_err(|e| rusqlite::Error::FromSqlConversionFailure(2, Type::Text, Box::new(e)))?,
+        last_scan: None,
+    })
+}
+
+// Open the saved domains database in the app data directory
+pub fn load(app_handle: &AppHandle) -> SavedDomains {
+    SavedDomains {
+        database: Database::load(
+            app_handle,
+            SAVED_DOMAINS_FILE,
+            SAVED_DOMAINS_NAME,
+            SAVED_DOMAINS_SCHEMA,
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+
+    fn tags(tags: &[&str]) -> Vec<String> {
+        tags.iter().map(|t| t.to_string()).collect()
+    }
+
+    #[test]
+    fn test_save_tag_and_remove() {
+        let saved = SavedDomains::in_memory();
+        let now = DateTime::parse_from_rfc3339("2026-10-16T08:00:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+
+        let domain = saved
+            .save("example.com", Some(&tags(&["client"])), now)
+            .unwrap();
+        assert_eq!(domain.added_at, now);
+        assert_eq!(domain.tags, tags(&["client"]));
+
+        // Saving again keeps the date, and the tags unless new ones are given
+        let again = saved
+            .save("example.com", None, now + Duration::hours(1))
+            .unwrap();
+        assert_eq!(again.added_at, now);
+        assert_eq!(again.tags, tags(&["client"]));
+        assert!(saved
+            .set_tags("example.com", &tags(&["client", "Production"]))
+            .unwrap());
+        assert!(!saved.set_tags("example.net", &tags(&["client"])).unwrap());
+
+        assert!(saved.remove("example.com").unwrap());
+        assert!(!saved.remove("example.com").unwrap());
+        assert!(saved.get("example.com").unwrap().is_none());
+    }
+
+    #[test]
+    fn test_list_by_tag() {
+        let saved = SavedDomains::in_memory();
+        let now = Utc::now();
+        saved
+            .save("example.org", Some(&tags(&["production"])), now)
+            .unwrap();
+        saved
+            .save("example.com", Some(&tags(&["Production", "client"])), now)
+            .unwrap();
+        saved.save("example.net", None, now).unwrap();
+
+        let all: Vec<String> = saved
+            .list(None)
+            .unwrap()
+            .into_iter()
+            .map(|s| s.domain)
+            .collect();
+        assert_eq!(all, vec!["example.com", "example.net", "example.org"]);
+
+        let production: Vec<String> = saved
+            .list(Some("production"))
+            .unwrap()
+            .into_iter()
+            .map(|s| s.domain)
+            .collect();
+        assert_eq!(production, vec!["example.com", "example.org"]);
+        assert!(saved.list(Some("staging")).unwrap().is_empty());
+    }
+}
@@ -0,0 +1,113 @@
+use crate::models::report::DomainReport;
+use crate::models::session::SessionDomain;
+use crate::state::AppState;
+use chrono::Utc;
+use std::sync::Mutex;
+use tauri::{AppHandle, Manager};
+
+// Every domain queried this session, with its latest analyze_domain report, for
+// save_session
+//
+// The command logs can't stand in for this: they drop the oldest entries, and
+// answers served from the DNS cache or shared with an identical lookup already
+// running log no command at all.
+pub struct SessionDomains {
+    domains: Mutex<Vec<SessionDomain>>,
+}
+
+impl SessionDomains {
+    pub fn new() -> Self {
+        SessionDomains {
+            domains: Mutex::new(Vec::new()),
+        }
+    }
+
+    // A query of domain that ran a logged command, or not (logged false)
+    pub fn record(&self, domain: &str, logged: bool) {
+        if let Ok(mut domains) = self.domains.lock() {
+            SessionDomain::note(&mut domains, domain, Utc::now(), logged);
+        }
+    }
+
+    // Keep report as the latest for its ASCII domain
+    pub fn record_report(&self, domain: &str, report: DomainReport) {
+        self.record(domain, false);
+        let domain = domain.trim_end_matches('.').to_lowercase();
+        if let Ok(mut domains) = self.domains.lock() {
+            if let Some(existing) = domains.iter_mut().find(|d| d.domain == domain) {
+                existing.report = Some(report);
+            }
+        }
+    }
+
+    // In the order first queried
+    pub fn list(&self) -> Vec<SessionDomain> {
+        self.domains.lock().map(|d| d.clone()).unwrap_or_default()
+    }
+
+    // Start over from a loaded session's domains
+    pub fn replace(&self, domains: Vec<SessionDomain>) {
+        if let Ok(mut current) = self.domains.lock() {
+            *current = domains;
+        }
+    }
+}
+
+impl Default for SessionDomains {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// Note a query of domain in the app's SessionDomains; without an AppHandle (tests,
+// CLI) there is no session to note it in
+pub fn record(app_handle: Option<&AppHandle>, domain: &str, logged: bool) {
+    if let Some(state) = app_handle.and_then(|h| h.try_state::<AppState>()) {
+        state.session_domains.record(domain, logged);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_record_and_report() {
+        let domains = SessionDomains::new();
+        domains.record("Example.com.", true);
+        domains.record("example.org", false);
+        domains.record("example.com", false);
+        domains.record("", true);
+
+        let list = domains.list();
+        let found: Vec<(&str, usize)> = list
+            .iter()
+            .map(|d| (d.domain.as_str(), d.commands))
+            .collect();
+        assert_eq!(found, [("example.com", 1), ("example.org", 0)]);
+        assert!(list[0].last_queried_at >= list[0].first_queried_at);
+
+        let report = DomainReport {
+            domain: "example.org".to_string(),
+            started_at: Utc::now(),
+            total_time: 1.5,
+            dns: None,
+            dnssec: None,
+            whois: None,
+            certificate: None,
+            http: Vec::new(),
+            email: None,
+            errors: Vec::new(),
+            health: None,
+            scan_id: None,
+        };
+        domains.record_report("example.org", report);
+        assert_eq!(
+            domains.list()[1].report.as_ref().map(|r| r.domain.as_str()),
+            Some("example.org")
+        );
+
+        domains.replace(Vec::new());
+        assert!(domains.list().is_empty());
+    }
+}
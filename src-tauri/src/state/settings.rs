@@ -0,0 +1,47 @@
+use crate::models::settings::Settings;
+use std::fs;
+use std::path::PathBuf;
+use tauri::{AppHandle, Manager};
+
+const SETTINGS_FILE: &str = "settings.json";
+
+fn settings_path(app_handle: &AppHandle) -> Result<PathBuf, String> {
+    app_handle
+        .path()
+        .app_config_dir()
+        .map(|dir| dir.join(SETTINGS_FILE))
+        .map_err(|e| format!("Failed to resolve config directory: {}", e))
+}
+
+// Load settings from the app config directory
+// A missing or unreadable file falls back to defaults rather than blocking startup
+pub fn load(app_handle: &AppHandle) -> Settings {
+    let Ok(path) = settings_path(app_handle) else {
+        return Settings::default();
+    };
+
+    match fs::read_to_string(&path) {
+        Ok(contents) => match serde_json::from_str::<Settings>(&contents) {
+            Ok(settings) if settings.validate().is_ok() => settings,
+            Ok(_) | Err(_) => {
+                tracing::warn!("Ignoring invalid settings file: {}", path.display());
+                Settings::default()
+            }
+        },
+        Err(_) => Settings::default(),
+    }
+}
+
+// Write settings to the app config directory, creating it if needed
+pub fn save(app_handle: &AppHandle, settings: &Settings) -> Result<(), String> {
+    let path = settings_path(app_handle)?;
+
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).map_err(|e| format!("Failed to create config directory: {}", e))?;
+    }
+
+    let contents = serde_json::to_string_pretty(settings)
+        .map_err(|e| format!("Failed to serialize settings: {}", e))?;
+
+    fs::write(&path, contents).map_err(|e| format!("Failed to write settings: {}", e))
+}
@@ -0,0 +1,327 @@
+use super::database::{format_time, time_column, Database};
+use crate::models::watchlist::{ExpiryKind, UpcomingExpiration, WatchedDomain};
+use chrono::{DateTime, Utc};
+use rusqlite::types::Type;
+use rusqlite::{params, OptionalExtension, Row};
+use std::path::Path;
+use tauri::AppHandle;
+
+const WATCHLIST_FILE: &str = "watchlist.sqlite3";
+const WATCHLIST_NAME: &str = "watchlist";
+const WATCHLIST_SCHEMA: &str = "CREATE TABLE IF NOT EXISTS watchlist (
+        domain TEXT PRIMARY KEY,
+        added_at TEXT NOT NULL,
+        checked_at TEXT,
+        certificate_expires_at TEXT,
+        registration_expires_at TEXT,
+        errors TEXT NOT NULL DEFAULT '[]'
+    );
+    CREATE TABLE IF NOT EXISTS expiry_notifications (
+        domain TEXT NOT NULL,
+        kind TEXT NOT NULL,
+        expires_at TEXT NOT NULL,
+        threshold INTEGER NOT NULL,
+        PRIMARY KEY (domain, kind, expires_at, threshold)
+    );";
+
+// Domains whose certificate and registration expiries are tracked, kept in SQLite
+//
+// Notifications already raised are recorded per expiry date and threshold, so each
+// threshold fires once, and again after a renewal moves the date.
+pub struct Watchlist {
+    database: Database,
+}
+
+impl Watchlist {
+    pub fn open(path: &Path) -> Result<Self, String> {
+        Database::open(path, WATCHLIST_NAME, WATCHLIST_SCHEMA)
+            .map(|database| Watchlist { database })
+    }
+
+    pub fn in_memory() -> Self {
+        Watchlist {
+            database: Database::in_memory(WATCHLIST_NAME, WATCHLIST_SCHEMA),
+        }
+    }
+
+    // Start watching a domain; adding one that's already watched leaves it as it was
+    pub fn add(&self, domain: &str, now: DateTime<Utc>) -> Result<WatchedDomain, String> {
+        self.database
+            .connection()?
+            .execute(
+                "INSERT OR IGNORE INTO watchlist (domain, added_at) VALUES (?1, ?2)",
+                params![domain, format_time(now)],
+            )
+            .map_err(|e| format!("Failed to watch {}: {}", domain, e))?;
+        self.get(domain)?
+            .ok_or_else(|| format!("Failed to watch {}", domain))
+    }
+
+    // Whether the domain was being watched
+    pub fn remove(&self, domain: &str) -> Result<bool, String> {
+        let connection = self.database.connection()?;
+        connection
+            .execute(
+                "DELETE FROM expiry_notifications WHERE domain = ?1",
+                params![domain],
+            )
+            .map_err(|e| format!("Failed to stop watching {}: {}", domain, e))?;
+        let deleted = connection
+            .execute("DELETE FROM watchlist WHERE domain = ?1", params![domain])
+            .map_err(|e| format!("Failed to stop watching {}: {}", domain, e))?;
+        Ok(deleted > 0)
+    }
+
+    pub fn get(&self, domain: &str) -> Result<Option<WatchedDomain>, String> {
+        self.database
+            .connection()?
+            .query_row(
+                "SELECT domain, added_at, checked_at, certificate_expires_at,
+                        registration_expires_at, errors
+                 FROM watchlist WHERE domain = ?1",
+                params![domain],
+                read_row,
+            )
+            .optional()
+            .map_err(|e| format!("Failed to read the watchlist: {}", e))
+    }
+
+    // Sorted by domain
+    pub fn list(&self) -> Result<Vec<WatchedDomain>, String> {
+        let connection = self.database.connection()?;
+        let mut statement = connection
+            .prepare(
+                "SELECT domain, added_at, checked_at, certificate_expires_at,
+                        registration_expires_at, errors
+                 FROM watchlist ORDER BY domain",
+            )
+            .map_err(|e| format!("Failed to read the watchlist: {}", e))?;
+        let rows = statement
+            .query_map([], read_row)
+            .map_err(|e| format!("Failed to read the watchlist: {}", e))?;
+
+        let mut domains = Vec::new();
+        for row in rows {
+            domains.push(row.map_err(|e| format!("Failed to read the watchlist: {}", e))?);
+        }
+        Ok(domains)
+    }
+
+    // Store the result of a check; does nothing if the domain was removed meanwhile
+    pub fn update(&self, watched: &WatchedDomain) -> Result<(), String> {
+        let errors = serde_json::to_string(&watched.errors)
+            .map_err(|e| format!("Failed to serialize check errors: {}", e))?;
+        self.database
+            .connection()?
+            .execute(
+                "UPDATE watchlist SET checked_at = ?2, certificate_expires_at = ?3,
+                        registration_expires_at = ?4, errors = ?5
+                 WHERE domain = ?1",
+                params![
+                    watched.domain,
+                    watched.checked_at.map(format_time),
+                    watched.certificate_expires_at.map(format_time),
+                    watched.registration_expires_at.map(format_time),
+                    errors,
+                ],
+            )
+            .map_err(|e| format!("Failed to update {}: {}", watched.domain, e))?;
+        Ok(())
+    }
+
+    // Expiries within `within_days` of now (and any already past), soonest first
+    pub fn upcoming(
+        &self,
+        now: DateTime<Utc>,
+        within_days: u32,
+        thresholds: &[u32],
+    ) -> Result<Vec<UpcomingExpiration>, String> {
+        let mut upcoming = Vec::new();
+        for watched in self.list()? {
+            let expiries = [
+                (ExpiryKind::Certificate, watched.certificate_expires_at),
+                (ExpiryKind::Registration, watched.registration_expires_at),
+            ];
+            for (kind, expires_at) in expiries {
+                let Some(expires_at) = expires_at else {
+                    continue;
+                };
+                let days_remaining = (expires_at - now).num_days();
+                if days_remaining > i64::from(within_days) {
+                    continue;
+                }
+                upcoming.push(UpcomingExpiration {
+                    domain: watched.domain.clone(),
+                    kind,
+                    expires_at,
+                    days_remaining,
+                    threshold: threshold(days_remaining, thresholds),
+                });
+            }
+        }
+        upcoming.sort_by(|a, b| {
+            (a.expires_at, &a.domain, a.kind).cmp(&(b.expires_at, &b.domain, b.kind))
+        });
+        Ok(upcoming)
+    }
+
+    // Record that the expiry's threshold has been notified; false if it already was
+    pub fn mark_notified(&self, expiration: &UpcomingExpiration) -> Result<bool, String> {
+        let Some(threshold) = expiration.threshold else {
+            return Ok(false);
+        };
+        let inserted = self
+            .database
+            .connection()?
+            .execute(
+                "INSERT OR IGNORE INTO expiry_notifications (domain, kind, expires_at, threshold)
+                 VALUES (?1, ?2, ?3, ?4)",
+                params![
+                    expiration.domain,
+                    expiration.kind.as_str(),
+                    format_time(expiration.expires_at),
+                    threshold,
+                ],
+            )
+            .map_err(|e| format!("Failed to record a notification: {}", e))?;
+        Ok(inserted > 0)
+    }
+}
+
+impl Default for Watchlist {
+    fn default() -> Self {
+        Self::in_memory()
+    }
+}
+
+// The smallest threshold the remaining days fall within, e.g. 7 for 5 days left of [30, 14, 7, 1]
+pub fn threshold(days_remaining: i64, thresholds: &[u32]) -> Option<u32> {
+    thresholds
+        .iter()
+        .copied()
+        .filter(|t| days_remaining <= i64::from(*t))
+        .min()
+}
+
+fn read_row(row: &Row) -> rusqlite::Result<WatchedDomain> {
+    let errors: String = row.get(5)?;
+    Ok(WatchedDomain {
+        domain: row.get(0)?,
+        added_at: time_column(row, 1)?.unwrap_or_default(),
+        checked_at: time_column(row, 2)?,
+        certificate_expires_at: time_column(row, 3)?,
+        registration_expires_at: time_column(row, 4)?,
+        errors: serde_json::from_str(&errors)
+            .map_err(|e| rusqlite::Error::FromSqlConversionFailure(5, Type::Text, Box::new(e)))?,
+    })
+}
+
+// Open the watchlist database in the app data directory
+pub fn load(app_handle: &AppHandle) -> Watchlist {
+    Watchlist {
+        database: Database::load(app_handle, WATCHLIST_FILE, WATCHLIST_NAME, WATCHLIST_SCHEMA),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+
+    fn time(value: &str) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(value)
+            .unwrap()
+            .with_timezone(&Utc)
+    }
+
+    #[test]
+    fn test_add_update_and_remove() {
+        let watchlist = Watchlist::in_memory();
+        let now = time("2026-10-16T08:00:00Z");
+        let watched = watchlist.add("example.com", now).unwrap();
+        assert_eq!(watched.added_at, now);
+        assert!(watched.checked_at.is_none());
+
+        let checked = WatchedDomain {
+            checked_at: Some(now),
+            certificate_expires_at: Some(now + Duration::days(20)),
+            errors: vec!["registration: RDAP timed out".to_string()],
+            ..watched
+        };
+        watchlist.update(&checked).unwrap();
+        // Adding again keeps what was checked
+        let again = watchlist
+            .add("example.com", now + Duration::hours(1))
+            .unwrap();
+        assert_eq!(again.added_at, now);
+        assert_eq!(again.certificate_expires_at, checked.certificate_expires_at);
+        assert_eq!(again.errors, checked.errors);
+
+        watchlist.add("example.org", now).unwrap();
+        let domains: Vec<String> = watchlist
+            .list()
+            .unwrap()
+            .into_iter()
+            .map(|w| w.domain)
+            .collect();
+        assert_eq!(domains, vec!["example.com", "example.org"]);
+
+        assert!(watchlist.remove("example.com").unwrap());
+        assert!(!watchlist.remove("example.com").unwrap());
+        assert!(watchlist.get("example.com").unwrap().is_none());
+    }
+
+    #[test]
+    fn test_upcoming_and_notifications() {
+        let watchlist = Watchlist::in_memory();
+        let now = time("2026-10-16T08:00:00Z");
+        let thresholds = [30, 14, 7, 1];
+        for (domain, certificate, registration) in
+            [("example.com", 5, 200), ("example.org", 60, -2)]
+        {
+            let watched = watchlist.add(domain, now).unwrap();
+            watchlist
+                .update(&WatchedDomain {
+                    checked_at: Some(now),
+                    certificate_expires_at: Some(now + Duration::days(certificate)),
+                    registration_expires_at: Some(now + Duration::days(registration)),
+                    ..watched
+                })
+                .unwrap();
+        }
+
+        let upcoming = watchlist.upcoming(now, 30, &thresholds).unwrap();
+        let found: Vec<(&str, ExpiryKind, i64, Option<u32>)> = upcoming
+            .iter()
+            .map(|u| (u.domain.as_str(), u.kind, u.days_remaining, u.threshold))
+            .collect();
+        assert_eq!(
+            found,
+            vec![
+                ("example.org", ExpiryKind::Registration, -2, Some(1)),
+                ("example.com", ExpiryKind::Certificate, 5, Some(7)),
+            ]
+        );
+        assert_eq!(watchlist.upcoming(now, 365, &thresholds).unwrap().len(), 4);
+
+        assert!(watchlist.mark_notified(&upcoming[1]).unwrap());
+        assert!(!watchlist.mark_notified(&upcoming[1]).unwrap());
+        // A renewal moves the date, so the same threshold fires again
+        let renewed = UpcomingExpiration {
+            expires_at: upcoming[1].expires_at + Duration::days(90),
+            ..upcoming[1].clone()
+        };
+        assert!(watchlist.mark_notified(&renewed).unwrap());
+    }
+
+    #[test]
+    fn test_threshold() {
+        let thresholds = [30, 14, 7, 1];
+        assert_eq!(threshold(45, &thresholds), None);
+        assert_eq!(threshold(30, &thresholds), Some(30));
+        assert_eq!(threshold(10, &thresholds), Some(14));
+        assert_eq!(threshold(0, &thresholds), Some(1));
+        assert_eq!(threshold(-3, &thresholds), Some(1));
+        assert_eq!(threshold(3, &[]), None);
+    }
+}
@@ -0,0 +1,68 @@
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+// WHOIS servers that rate limited us, and until when to leave them alone
+//
+// Registries often block clients that keep querying after a limit notice, so
+// lookups (including bulk ones) skip a server until its cool-down has passed.
+pub struct WhoisCooldowns {
+    until: Mutex<HashMap<String, Instant>>,
+}
+
+impl WhoisCooldowns {
+    pub fn new() -> Self {
+        WhoisCooldowns {
+            until: Mutex::new(HashMap::new()),
+        }
+    }
+
+    // Time left before the server may be queried again, if it is cooling down
+    pub fn remaining(&self, server: &str) -> Option<Duration> {
+        let mut until = self.until.lock().ok()?;
+        let key = server.to_lowercase();
+        let deadline = *until.get(&key)?;
+
+        match deadline.checked_duration_since(Instant::now()) {
+            Some(remaining) if !remaining.is_zero() => Some(remaining),
+            _ => {
+                until.remove(&key);
+                None
+            }
+        }
+    }
+
+    pub fn start(&self, server: &str, duration: Duration) {
+        if let Ok(mut until) = self.until.lock() {
+            until.insert(server.to_lowercase(), Instant::now() + duration);
+        }
+    }
+}
+
+impl Default for WhoisCooldowns {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cooldown_is_per_server() {
+        let cooldowns = WhoisCooldowns::new();
+        cooldowns.start("WHOIS.Example.NET", Duration::from_secs(60));
+
+        assert!(cooldowns.remaining("whois.example.net").is_some());
+        assert!(cooldowns.remaining("whois.other.net").is_none());
+    }
+
+    #[test]
+    fn test_cooldown_expires() {
+        let cooldowns = WhoisCooldowns::new();
+        cooldowns.start("whois.example.net", Duration::ZERO);
+
+        assert!(cooldowns.remaining("whois.example.net").is_none());
+    }
+}
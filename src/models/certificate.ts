@@ -14,22 +14,105 @@ export interface CertificateInfo {
   version: number;
   not_before: string;
   not_after: string;
+  not_before_at?: string | null; // RFC 3339
+  not_after_at?: string | null;
+  days_until_expiry?: number | null; // Negative once expired
   subject_alternative_names: string[];
   public_key_algorithm: string;
   public_key_size?: number;
   signature_algorithm: string;
   fingerprint_sha256: string;
+  key_usage?: string[];
+  extended_key_usage?: string[]; // Names, or dotted OIDs for unnamed purposes
+  is_ca?: boolean;
+  path_length?: number | null;
+  embedded_scts?: number;
+  valid_for_server_auth?: boolean;
+  role?: CertificateRole;
+  self_signed?: boolean;
+}
+
+export type CertificateRole = 'leaf' | 'intermediate' | 'root';
+
+export type CertificateWarningKind = 'self_signed_leaf' | 'weak_signature' | 'long_validity';
+
+export interface CertificateWarning {
+  index: number; // Position in certificate_chain.certificates
+  kind: CertificateWarningKind;
+  message: string;
 }
 
 export interface CertificateChain {
   certificates: CertificateInfo[];
   is_valid: boolean;
   validation_errors: string[];
+  trust_anchor?: string | null; // Root the chain was validated against
+}
+
+export interface IpCertificate {
+  ip: string;
+  serial_number: string | null;
+  fingerprint_sha256: string | null;
+  not_after: string | null;
+  matches_primary: boolean; // Same certificate as the connection made by hostname
+  error: string | null;
 }
 
+export type StartTls = 'smtp' | 'imap' | 'pop3';
+
 export interface TlsInfo {
   host: string;
   port: number;
+  starttls?: StartTls | null; // null for implicit TLS
+  alpn_protocol?: string | null; // Protocol selected via ALPN, if any
+  supports_http2?: boolean;
+  per_ip?: IpCertificate[]; // Only filled when the host resolves to several addresses
   certificate_chain: CertificateChain;
+  warnings?: string[]; // e.g. the queried host isn't covered by the SANs
+  certificate_warnings?: CertificateWarning[];
   raw_output?: string;
 }
+
+export interface CtLogEntry {
+  id: number; // crt.sh certificate ID
+  issuer: string;
+  common_name: string;
+  names: string[];
+  serial_number: string;
+  not_before: string;
+  not_after: string;
+  logged_at: string;
+  expired: boolean;
+}
+
+export interface CtIssuer {
+  name: string;
+  certificates: number;
+}
+
+export interface CtHistory {
+  domain: string;
+  include_subdomains: boolean;
+  entries: CtLogEntry[]; // Newest first
+  issuers: CtIssuer[];
+  warnings: string[];
+  raw_output: string | null;
+}
+
+export interface PortTlsResult {
+  port: number;
+  starttls: StartTls | null; // Used for well-known plaintext mail ports
+  reachable: boolean;
+  tls_ok: boolean;
+  common_name: string | null;
+  issuer: string | null;
+  not_after: string | null;
+  days_until_expiry: number | null;
+  error: string | null;
+}
+
+export interface TlsPortScan {
+  host: string;
+  results: PortTlsResult[];
+  total_time: number;
+}
@@ -0,0 +1,30 @@
+// A command an adapter ran, as sent with each `command-log` event and returned by
+// list_command_logs
+export interface CommandLog {
+  command: string;
+  tool: string; // "dig", "whois", "tls" or "curl"
+  args: string[];
+  output: string;
+  exit_code: number;
+  duration: number; // in milliseconds
+  domain: string | null;
+  throttled_ms: number | null; // Time spent waiting on the rate limiter first, if any
+  logged_at: string; // RFC 3339
+}
+
+// Unset fields match all
+export interface CommandLogFilter {
+  domain?: string | null; // Case-insensitive, without a trailing dot
+  tool?: string | null;
+  since?: string | null; // RFC 3339
+  until?: string | null;
+  limit?: number | null; // Newest first
+}
+
+// The file export_command_logs writes
+export interface CommandLogExport {
+  app_version: string;
+  exported_at: string; // RFC 3339
+  filter: CommandLogFilter;
+  logs: CommandLog[]; // Oldest first, as they ran
+}
@@ -12,38 +12,276 @@ export interface DnsResponse {
   raw_output?: string;
 }
 
+export interface RecordTypeResult {
+  record_type: string;
+  records: DnsRecord[];
+  query_time: number;
+  error: string | null;
+}
+
+export interface RecordEnumeration {
+  domain: string;
+  results: RecordTypeResult[]; // One entry per queried type, in display order
+  hosting: DnsHosting | null; // null without NS records at the name
+  total_time: number;
+}
+
+export interface DnsProvider {
+  name: string;
+  nameservers: string[];
+}
+
+// DNS hosts recognized from the zone's NS hostnames
+export interface DnsHosting {
+  providers: DnsProvider[];
+  unrecognized: string[]; // Nameservers matching no known provider
+  multi_provider: boolean; // Nameservers span more than one operator
+}
+
+// A PTR name of an address and what it resolves to in turn
+export interface PtrName {
+  name: string;
+  addresses: string[];
+  confirmed: boolean; // Resolves back to the address
+  error: string | null;
+}
+
+// Forward-confirmed reverse DNS of one address
+export interface ReverseDnsCheck {
+  address: string;
+  hosts: string[]; // Names of the domain's that resolve to the address
+  mail: boolean; // An address of one of the domain's MX hosts
+  ptr_names: PtrName[];
+  confirmed: boolean; // At least one PTR name resolves back to the address
+  error: string | null;
+}
+
+// Returned by check_fcrdns
+export interface FcrdnsReport {
+  domain: string;
+  checks: ReverseDnsCheck[];
+  all_confirmed: boolean;
+  warnings: string[];
+}
+
+// One resolver's A or AAAA answer in one round of detect_load_balancing
+export interface AddressSample {
+  resolver: string;
+  round: number; // From 1
+  record_type: string;
+  addresses: string[]; // In answer order
+  ttl: number | null;
+  error: string | null;
+}
+
+// Returned by detect_load_balancing
+export interface LoadBalancingReport {
+  domain: string;
+  resolvers: string[];
+  rounds: number;
+  samples: AddressSample[];
+  addresses: string[]; // Every address seen
+  round_robin: boolean; // Same addresses in rotating order
+  changes_over_time: boolean; // One resolver got different addresses between rounds
+  resolver_dependent: boolean; // Resolvers got different addresses (GeoDNS, CDN steering)
+  min_ttl: number | null;
+  findings: string[];
+  total_time: number;
+}
+
+// A zone's SOA record, times in seconds
+export interface SoaRecord {
+  mname: string; // Primary nameserver
+  rname: string; // Responsible mailbox, first dot meaning @
+  serial: number;
+  refresh: number;
+  retry: number;
+  expire: number;
+  minimum: number; // Negative caching TTL (RFC 2308)
+  ttl: number;
+}
+
+export interface RecordTtl {
+  record_type: string;
+  ttl: number;
+}
+
+// A finding of check_ttls or check_dns_hygiene
+export interface HygieneIssue {
+  severity: 'error' | 'warning';
+  code: string; // Stable identifier, e.g. "ttl_too_low" or "apex_cname"
+  message: string;
+}
+
+// Returned by check_ttls
+export interface TtlReport {
+  domain: string;
+  zone: string;
+  nameserver: string | null; // Authoritative server the records were read from
+  soa: SoaRecord | null;
+  negative_ttl: number | null; // Lower of the SOA's TTL and minimum
+  ttls: RecordTtl[]; // Lowest TTL of each type at the name
+  issues: HygieneIssue[];
+}
+
+// Returned by check_dns_hygiene
+export interface DnsHygieneReport {
+  domain: string;
+  zone: string;
+  is_apex: boolean;
+  nameserver: string | null; // Authoritative server the records were read from
+  record_types: string[]; // Types present at the name
+  issues: HygieneIssue[];
+}
+
+// How a nameserver answered a direct SOA query for the zone
+export type NameserverStatus =
+  | 'authoritative'
+  | 'lame' // Answered, but not authoritatively for the zone
+  | 'refused'
+  | 'serv_fail'
+  | 'timeout'
+  | 'unreachable'; // Couldn't be resolved or queried
+
+export interface NameserverHealth {
+  nameserver: string;
+  status: NameserverStatus;
+  rcode: string | null;
+  query_time: number;
+  error: string | null;
+}
+
+// Returned by check_delegation
+export interface DelegationCheck {
+  domain: string;
+  parent_zone: string;
+  parent_server: string;
+  parent_nameservers: string[]; // NS set published by the registry
+  child_server: string | null;
+  child_nameservers: string[]; // NS set served by the zone itself
+  missing_from_child: string[];
+  missing_from_parent: string[];
+  is_consistent: boolean;
+  nameservers: NameserverHealth[]; // Every nameserver of either set
+  warnings: string[];
+}
+
+// A nameserver's answer to a recursive query for a name outside its zones
+export interface RecursionProbe {
+  nameserver: string;
+  probe_name: string;
+  open: boolean; // Resolved the name: an open recursive resolver
+  recursion_available: boolean; // RA flag set, whatever the answer
+  rcode: string | null;
+  answers: string[];
+  error: string | null;
+}
+
+// Returned by check_open_resolvers
+export interface OpenResolverCheck {
+  domain: string;
+  zone: string;
+  nameservers: RecursionProbe[];
+  open_resolvers: string[];
+  warnings: string[];
+}
+
+// Where one nameserver address sits on the network
+export interface NameserverAddress {
+  address: string;
+  subnet: string; // Covering /24 (IPv4) or /48 (IPv6)
+  asn: number | null; // Origin AS; null when not announced or the lookup failed
+  as_name: string | null;
+  announced_prefix: string | null;
+}
+
+export interface NameserverNetwork {
+  nameserver: string;
+  addresses: NameserverAddress[];
+  reachable: boolean; // Answered an SOA query for the zone
+  error: string | null;
+}
+
+// Returned by check_nameserver_diversity
+export interface NameserverDiversity {
+  domain: string;
+  zone: string; // Zone whose NS set was audited
+  nameservers: NameserverNetwork[];
+  asns: number[]; // Distinct origin ASes across all addresses
+  subnets: string[]; // Distinct /24 and /48 networks
+  reachable: number;
+  is_diverse: boolean; // Two or more answering, not all in one AS or subnet
+  warnings: string[];
+}
+
 export interface DnskeyRecord {
   flags: number;
   protocol: number;
   algorithm: number;
+  algorithm_name: string; // e.g. RSASHA256, ECDSAP256SHA256
   public_key: string;
   key_tag: number;
+  key_size?: number; // bits
 }
 
 export interface DsRecord {
   key_tag: number;
   algorithm: number;
+  algorithm_name: string;
   digest_type: number;
+  digest_type_name: string; // SHA-1, SHA-256, SHA-384
   digest: string;
+  digest_verified?: boolean | null;
 }
 
 export interface RrsigRecord {
   type_covered: string;
   algorithm: number;
+  algorithm_name: string;
   labels: number;
   original_ttl: number;
   signature_expiration: string;
   signature_inception: string;
+  expires_at?: string | null; // RFC 3339, parsed from signature_expiration
+  inception_at?: string | null;
   key_tag: number;
   signer_name: string;
   signature: string;
 }
 
+export interface DsLink {
+  child_zone: string;
+  ds_key_tag: number;
+  digest_type: number;
+  dnskey_key_tag: number | null; // Child key whose digest matches
+  verified: boolean | null; // null when the digest type is unsupported
+}
+
+export interface SignatureLink {
+  type_covered: string;
+  key_tag: number;
+  signed_by: number | null; // Key tag of the matching DNSKEY
+  key_flags: number | null; // 257 = KSK, 256 = ZSK
+  valid_now: boolean;
+}
+
 export interface ZoneData {
   zone_name: string;
   dnskey_records: DnskeyRecord[];
   ds_records: DsRecord[];
   rrsig_records: RrsigRecord[];
+  status: string; // SECURE, INSECURE, BOGUS, INDETERMINATE
+  ds_links: DsLink[];
+  signature_links: SignatureLink[];
+}
+
+export interface DnssecProgress {
+  domain: string;
+  zone: string; // Zone currently being queried ("." for root)
+  step: string; // dnskey, ds, evaluating, complete
+  zone_index: number;
+  zone_count: number;
+  elapsed: number; // seconds
 }
 
 export interface DnssecValidation {
@@ -51,3 +289,15 @@ export interface DnssecValidation {
   chain: ZoneData[];
   warnings: string[];
 }
+
+export interface RrsetValidation {
+  domain: string;
+  record_type: string;
+  zone: string; // Enclosing zone whose keys should sign the RRset
+  status: string; // SECURE, INSECURE, BOGUS, INDETERMINATE
+  records: DnsRecord[];
+  rrsig_records: RrsigRecord[];
+  signing_key_tags: number[];
+  chain: DnssecValidation;
+  warnings: string[];
+}
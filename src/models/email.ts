@@ -0,0 +1,234 @@
+// STARTTLS on port 25 of one MX host and the certificate it presented
+export interface MxTls {
+  starttls: boolean; // Upgrade completed and a certificate was presented
+  certificate_valid: boolean; // Chains to a trusted root, covers the host and hasn't expired
+  hostname_matches: boolean;
+  common_name: string | null;
+  issuer: string | null;
+  not_after: string | null;
+  days_until_expiry: number | null; // Negative once expired
+  validation_errors: string[];
+  warnings: string[];
+  error: string | null; // Connection or STARTTLS failure
+}
+
+export interface MxHost {
+  priority: number;
+  hostname: string;
+  ips: string[]; // A and AAAA
+  cname: string | null; // Target when the hostname is an alias, which MX forbids
+  tls: MxTls | null; // null when the host has no address to connect to
+  dane: DaneStatus | null; // null when the host publishes no TLSA records
+}
+
+// One TLSA record at _25._tcp.<mx-host> (RFC 6698 section 2.1)
+export interface TlsaRecord {
+  usage: number; // 2 DANE-TA or 3 DANE-EE; 0 and 1 are unusable for SMTP
+  selector: number; // 0 full certificate, 1 public key
+  matching_type: number; // 0 exact, 1 SHA-256, 2 SHA-512
+  data: string; // Lowercase hex
+  matched: boolean; // Matches the certificate presented over STARTTLS
+  error: string | null; // Why the record can't be used
+}
+
+export interface DaneStatus {
+  records: TlsaRecord[];
+  authenticated: boolean; // Resolver validated the answer with DNSSEC (AD flag)
+  valid: boolean; // Authenticated and a usable record matched
+  warnings: string[];
+}
+
+// An include: or redirect= target pulled into the SPF evaluation
+export interface SpfInclude {
+  domain: string;
+  via: 'include' | 'redirect';
+  record: string | null;
+  depth: number; // 1 for targets named by the domain's own record
+}
+
+// SPF policy expanded through its includes (RFC 7208)
+export interface SpfEvaluation {
+  record: string | null;
+  lookup_count: number; // Terms that cost a DNS lookup, across includes (limit 10; counting stops at 11)
+  void_lookup_count: number; // Lookups that found nothing (limit 2)
+  authorized: string[]; // IP addresses and ranges allowed to send
+  includes: SpfInclude[]; // Depth-first, in evaluation order
+  all: string | null; // Catch-all of the policy, e.g. "-all" or "~all"
+  perm_error: string | null; // Receivers treat the whole policy as broken
+  warnings: string[];
+}
+
+// DKIM public key published at <selector>._domainkey.<domain>
+export interface DkimRecord {
+  selector: string;
+  record: string;
+  key_type: string; // k=, rsa unless stated
+  testing: boolean; // t=y: receivers shouldn't act on failures
+  revoked: boolean; // Empty p=: the key has been withdrawn
+  source: 'custom' | 'settings' | 'provider' | 'common'; // Why the selector was tried
+}
+
+export interface DkimLookup {
+  provider: string | null; // Mail provider recognized from the MX hosts
+  tried: string[]; // Every selector queried, in order
+  records: DkimRecord[];
+}
+
+// One DKIM-Signature header of a message and whether it verifies (RFC 6376)
+export interface DkimSignatureCheck {
+  domain: string;
+  selector: string;
+  algorithm: string; // a=, e.g. "rsa-sha256"
+  canonicalization: string; // c=, header/body
+  signed_headers: string[]; // h=, in signing order
+  body_length: number | null; // l=: only this much of the body is covered
+  signed_at: string | null; // t=, RFC 3339
+  expires_at: string | null; // x=
+  aligned: boolean; // d= shares the organizational domain of From
+  key_bits: number | null; // RSA modulus size
+  body_hash_valid: boolean | null; // null when only headers were given
+  valid: boolean;
+  error: string | null; // Why verification failed
+  warnings: string[];
+}
+
+export interface DkimVerification {
+  from_domain: string | null;
+  signatures: DkimSignatureCheck[]; // In header order
+  warnings: string[];
+}
+
+// One rua= or ruf= destination of a DMARC record
+export interface DmarcReportUri {
+  uri: string;
+  address: string | null; // mailto: address, without the size limit
+  max_size: string | null; // "!10m" suffix: reports larger than this aren't sent
+  external: boolean; // Outside the policy's organizational domain
+  authorized: boolean | null; // External only: the destination publishes the verification record
+  error: string | null;
+}
+
+// The policy at _dmarc.<domain>, or at the organizational domain (RFC 7489 6.6.3)
+export interface DmarcRecord {
+  domain: string; // Where the record was found
+  record: string;
+  policy: string | null; // p=: none, quarantine or reject
+  subdomain_policy: string; // sp=, p= unless stated
+  percentage: number; // pct=: share of failing mail the policy applies to
+  dkim_alignment: string; // adkim=: "r" (relaxed) or "s" (strict)
+  spf_alignment: string; // aspf=
+  failure_options: string[]; // fo=: when failure reports are sent
+  report_formats: string[]; // rf=
+  report_interval: number; // ri=: seconds between aggregate reports
+  aggregate_reports: DmarcReportUri[]; // rua=
+  failure_reports: DmarcReportUri[]; // ruf=
+  errors: string[]; // Syntax receivers reject or ignore
+  warnings: string[];
+}
+
+// Messages one source IP sent in a DMARC aggregate report
+export interface DmarcSource {
+  source_ip: string;
+  messages: number;
+  dkim_aligned: number; // policy_evaluated dkim=pass: DKIM passed for the From domain
+  spf_aligned: number; // policy_evaluated spf=pass
+  dmarc_pass: number; // Either of the two
+  quarantined: number;
+  rejected: number;
+  header_from: string[]; // From domains seen for this source
+  dkim_domains: string[]; // d= domains of the signatures checked
+  spf_domains: string[]; // MAIL FROM (or HELO) domains checked
+}
+
+// An aggregate (rua) report a receiver sent about one domain (RFC 7489 appendix C)
+export interface DmarcAggregateReport {
+  org_name: string; // Receiver that sent the report
+  report_id: string;
+  email: string | null;
+  begin: string | null; // RFC 3339
+  end: string | null;
+  domain: string; // policy_published
+  policy: string | null; // p= the receiver applied
+  subdomain_policy: string | null;
+  percentage: number | null;
+  sources: DmarcSource[]; // Most messages first
+  total_messages: number;
+  dmarc_pass: number;
+  warnings: string[];
+}
+
+// One SRV record mail clients use to find their servers (RFC 6186, RFC 8314)
+export interface MailSrvRecord {
+  service: string; // e.g. "_imaps._tcp"
+  priority: number;
+  weight: number;
+  port: number;
+  target: string; // Empty for "." (the service is not offered)
+  resolves: boolean; // Target has A/AAAA records
+}
+
+// A server listed in an autoconfig file
+export interface AutoconfigServer {
+  protocol: string; // "imap", "pop3" or "smtp"
+  hostname: string;
+  port: number | null;
+  socket_type: string | null; // "SSL", "STARTTLS" or "plain"
+}
+
+// An HTTP endpoint mail clients fetch their settings from
+export interface AutoconfigEndpoint {
+  mechanism: string; // "autoconfig", "autoconfig (well-known)" or "autodiscover"
+  url: string;
+  status_code: number | null;
+  configured: boolean;
+  servers: AutoconfigServer[]; // Parsed from autoconfig files
+  error: string | null;
+}
+
+export interface AutodiscoveryReport {
+  domain: string;
+  srv_records: MailSrvRecord[];
+  endpoints: AutoconfigEndpoint[];
+  mechanisms: string[]; // Those configured: "srv", "autoconfig", "autodiscover"
+  warnings: string[];
+}
+
+// One address or domain looked up on one blocklist
+export interface BlacklistResult {
+  list: string; // Zone, e.g. zen.spamhaus.org
+  target: string; // MX address or the domain
+  query: string; // Name looked up, e.g. 2.0.0.127.zen.spamhaus.org
+  listed: boolean;
+  codes: string[]; // A answers; the list's return codes say why
+  reason: string | null; // TXT answer, for listed targets
+  error: string | null; // Lookup failed or the list refused to answer
+}
+
+export interface BlacklistReport {
+  domain: string;
+  ips: string[]; // MX addresses checked
+  results: BlacklistResult[]; // Addresses first, then the domain
+  listed_count: number;
+  warnings: string[];
+}
+
+// A mail or sending provider the domain's records point to
+export interface MailProviderMatch {
+  name: string;
+  evidence: string[]; // e.g. "MX aspmx.l.google.com", "SPF include:_spf.google.com"
+  missing: string[]; // Records the provider asks for that weren't found
+  outdated: string[]; // Records the provider has since replaced
+}
+
+export interface EmailConfig {
+  domain: string;
+  mx_records: MxHost[]; // Lowest preference value (tried first) first
+  null_mx: boolean; // "0 ." (RFC 7505): the domain accepts no mail
+  implicit_mx: MxHost | null; // Without MX records, senders deliver to the domain itself
+  encrypted: boolean; // Every MX offers STARTTLS with a valid certificate
+  spf: SpfEvaluation | null; // null when the domain publishes no SPF record
+  dkim: DkimLookup; // DKIM selectors can't be listed, only guessed
+  dmarc: DmarcRecord | null; // null when neither the domain nor its organizational domain has one
+  providers: MailProviderMatch[];
+  warnings: string[];
+}
@@ -0,0 +1,29 @@
+export type ToolRole = 'required' | 'fallback' | 'optional';
+
+// One external program the doctor looked for on the PATH
+export interface ToolCheck {
+  name: string;
+  role: ToolRole; // fallback tools stand in for a missing required one; optional ones
+  // are only for re-running logged commands by hand
+  found: boolean;
+  path: string | null;
+  version: string | null; // First line the tool prints for its version
+  purpose: string; // What the app uses it for
+}
+
+// A feature that won't work because a required tool is missing
+export interface DegradedFeature {
+  feature: string;
+  missing: string[]; // Tools it needs
+  impact: string; // What happens instead
+}
+
+// Returned by environment_doctor
+export interface EnvironmentReport {
+  os: string; // e.g. "macos aarch64"
+  dns_tool: string | null; // The program DNS queries run through; null without one
+  tools: ToolCheck[];
+  degraded: DegradedFeature[];
+  ready: boolean; // Every required tool was found
+  checked_at: string; // RFC 3339
+}
@@ -0,0 +1,60 @@
+import type { DomainReport } from './report';
+
+// One saved analyze_domain run, as listed by list_history
+export interface ScanSummary {
+  id: number;
+  domain: string;
+  scanned_at: string; // RFC 3339
+  score: number | null; // Health score, when the scan was graded
+  grade: string | null;
+}
+
+export interface StoredScan {
+  id: number;
+  domain: string;
+  scanned_at: string;
+  report: DomainReport;
+}
+
+// A DNS record present in only one scan, or whose TTL changed
+export interface RecordChange {
+  change: 'added' | 'removed' | 'ttl';
+  record_type: string;
+  name: string;
+  value: string;
+  old_ttl: number | null;
+  new_ttl: number | null;
+}
+
+export interface FieldChange {
+  field: string;
+  old: string | null;
+  new: string | null;
+}
+
+// One "<status> <url>" entry per hop, the final response last
+export interface RedirectChange {
+  url: string;
+  old_chain: string[];
+  new_chain: string[];
+}
+
+export interface ScanDiff {
+  domain: string;
+  from_id: number | null;
+  to_id: number | null;
+  from_scanned_at: string;
+  to_scanned_at: string;
+  records: RecordChange[];
+  nameservers_added: string[];
+  nameservers_removed: string[];
+  certificate_renewed: boolean;
+  certificate: FieldChange[]; // fingerprint, issuer, not_after
+  whois: FieldChange[]; // registrar, expiration_date, dnssec
+  whois_status_added: string[];
+  whois_status_removed: string[];
+  redirects: RedirectChange[];
+  health: FieldChange[]; // score, grade
+  skipped: string[]; // Sections missing from either scan
+  changed: boolean;
+}
@@ -3,14 +3,147 @@ export interface HttpRedirect {
   to_url: string;
   status_code: number;
   response_time: number;
+  headers?: [string, string][];
+  timing?: HttpTiming;
+  ip?: string | null; // Address this hop connected to
+}
+
+// What fetch_http sends: a bare HEAD unless the caller builds a request
+export interface HttpRequest {
+  method?: 'HEAD' | 'GET' | 'POST' | 'PUT' | 'PATCH' | 'DELETE' | 'OPTIONS';
+  headers?: Record<string, string>; // Replace default headers of the same name
+  body?: string;
+  max_body_kb?: number; // Capture the final response body up to this size
 }
 
 export interface HttpResponse {
   url: string;
+  method?: string; // Of the final hop; 301/302/303 turn a POST into a GET
   status_code: number;
   final_url: string;
   redirects: HttpRedirect[];
-  headers: Record<string, string>;
+  headers: [string, string][]; // In order received, repeats kept; names lowercase
   response_time: number;
+  timing?: HttpTiming; // Phases of the final hop
+  ip?: string | null; // Address the final hop connected to
+  warnings?: string[]; // HTTPS downgrades and hops to other domains
+  cache?: CacheAnalysis; // Of the final response
+  body?: HttpBody | null; // Only when the request asked for it
   raw_output?: string;
 }
+
+// Captured body of the final response
+export interface HttpBody {
+  content_type: string | null; // Without parameters, e.g. text/html
+  content_type_sniffed: boolean; // No Content-Type header; guessed from the bytes
+  charset: string | null; // From the header, else <meta charset>
+  size: number; // Bytes received
+  content_length: number | null; // As announced by the server
+  truncated: boolean; // Stopped at the size limit
+  text: string; // Decoded as UTF-8, invalid bytes replaced
+  meta_refresh: string | null; // Target of <meta http-equiv="refresh">
+}
+
+// Caching headers of a response and how long a shared cache (CDN) may reuse it
+export interface CacheAnalysis {
+  cache_control: string[]; // Directives as sent, lowercased
+  expires: string | null;
+  etag: string | null;
+  last_modified: string | null;
+  age: number | null;
+  storable: boolean; // No no-store or private
+  freshness_lifetime: number | null; // Seconds, per RFC 9111 section 4.2.1
+  heuristic: boolean; // Lifetime guessed from Last-Modified
+  remaining: number | null; // Lifetime minus Age; negative when stale
+  warnings: string[]; // Conflicting or ineffective directives
+}
+
+// Seconds spent in each phase of one request; tls is 0 for plain HTTP
+export interface HttpTiming {
+  dns: number;
+  connect: number;
+  tls: number;
+  first_byte: number; // From sending the request to the response headers
+  total: number;
+}
+
+// Parsed Strict-Transport-Security header (RFC 6797)
+export interface HstsPolicy {
+  max_age: number | null; // Seconds; the policy is invalid without it
+  include_subdomains: boolean;
+  preload: boolean;
+  errors: string[]; // Malformed or repeated directives
+  raw: string;
+}
+
+export interface HstsReport {
+  domain: string;
+  policy: HstsPolicy | null; // From the first HTTPS response, before any redirect
+  preload_status: string | null; // hstspreload.org: preloaded, pending, unknown...
+  preload_eligible: boolean;
+  issues: string[]; // Preload requirements the site doesn't meet
+  warnings: string[]; // Checks that could not be completed
+}
+
+// Group of robots.txt rules for one or more user agents (RFC 9309)
+export interface RobotsGroup {
+  user_agents: string[];
+  allow: string[];
+  disallow: string[];
+  crawl_delay: number | null;
+}
+
+export interface RobotsTxt {
+  url: string;
+  groups: RobotsGroup[];
+  sitemaps: string[];
+  blocks_all: boolean; // "User-agent: *" is disallowed from "/"
+  raw: string;
+}
+
+export interface Sitemap {
+  url: string;
+  is_index: boolean; // Lists other sitemaps rather than pages
+  url_count: number; // <loc> entries
+  locations: string[]; // The first entries, as a sample
+  error: string | null;
+}
+
+// Vulnerability disclosure contacts (RFC 9116)
+export interface SecurityTxt {
+  url: string;
+  contact: string[];
+  expires: string | null;
+  encryption: string[];
+  policy: string[];
+  canonical: string[];
+  preferred_languages: string | null;
+  signed: boolean; // OpenPGP cleartext signature
+  warnings: string[];
+  raw: string;
+}
+
+export interface WellKnownReport {
+  domain: string;
+  robots: RobotsTxt | null;
+  sitemaps: Sitemap[]; // Listed in robots.txt, else /sitemap.xml
+  security_txt: SecurityTxt | null;
+  warnings: string[];
+}
+
+// One of http(s)://domain and http(s)://www.domain, after following redirects
+export interface CanonicalVariant {
+  url: string;
+  final_url: string | null;
+  status_code: number | null;
+  redirects: number;
+  error: string | null;
+}
+
+export interface CanonicalReport {
+  domain: string; // The apex
+  variants: CanonicalVariant[];
+  canonical_url: string | null; // Where most variants end up
+  converges: boolean; // All four end on the same URL
+  warnings: string[];
+}
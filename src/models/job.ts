@@ -0,0 +1,16 @@
+export type JobStatus = 'queued' | 'running' | 'completed' | 'failed' | 'cancelled';
+
+// A long-running command, also sent with each `job-updated` event
+export interface Job {
+  id: number;
+  kind: string; // Command that started it, e.g. "analyze_domain"
+  target: string; // Domain or host it runs for, or e.g. "25 domains"
+  status: JobStatus;
+  completed: number; // Steps done; total is 0 until known
+  total: number;
+  message: string | null; // Latest step, e.g. "dnssec" or "example.com"
+  created_at: string; // RFC 3339
+  started_at: string | null;
+  finished_at: string | null;
+  error: string | null;
+}
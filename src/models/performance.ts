@@ -0,0 +1,21 @@
+// One tool overall (operation null), or one kind of query it ran
+export interface OperationStats {
+  tool: string; // "dig", "whois", "tls" or "curl"
+  operation: string | null; // e.g. "DS @a.gtld-servers.net" for dig, the server for whois
+  count: number;
+  failures: number; // Runs that exited non-zero or errored
+  failure_rate: number; // 0 to 1
+  // Milliseconds, over the most recent runs
+  mean_ms: number;
+  p50_ms: number;
+  p90_ms: number;
+  p99_ms: number;
+  max_ms: number;
+  throttled_ms: number; // Total time spent waiting on the rate limiter
+}
+
+// Returned by get_performance_stats
+export interface PerformanceStats {
+  since: string; // RFC 3339, when the app started collecting
+  operations: OperationStats[];
+}
@@ -0,0 +1,25 @@
+export type PingMethod = 'icmp' | 'tcp';
+
+// Latency and loss to one address
+export interface PingStats {
+  address: string;
+  method: PingMethod;
+  port: number | null; // For TCP
+  sent: number;
+  received: number;
+  loss_percent: number;
+  samples_ms: (number | null)[]; // Each probe in order, null when lost
+  // null when nothing was received
+  min_ms: number | null;
+  avg_ms: number | null;
+  max_ms: number | null;
+  jitter_ms: number | null; // Mean difference between consecutive round trips
+  icmp_error: string | null; // Why TCP was used instead, if it was
+}
+
+// Returned by ping_host: one entry per address the host resolved to
+export interface PingReport {
+  host: string;
+  addresses: PingStats[];
+  total_time: number;
+}
@@ -0,0 +1,20 @@
+export type PortState = 'open' | 'closed' | 'filtered';
+
+// One port of a scan
+export interface PortResult {
+  port: number;
+  service: string | null; // Usual service on the port, e.g. "smtp"
+  state: PortState; // closed: refused; filtered: no answer, usually a firewall
+  banner: string | null; // First line the service sent unprompted, e.g. SSH's version
+  time_ms: number; // Until connected, refused or timed out
+  error: string | null; // Why a port is filtered, when the OS said
+}
+
+// Returned by scan_ports
+export interface PortScan {
+  host: string;
+  address: string; // The address scanned
+  results: PortResult[];
+  open: number;
+  total_time: number;
+}
@@ -0,0 +1,125 @@
+import type { TlsInfo } from './certificate';
+import type { DnssecValidation, RecordEnumeration } from './dns';
+import type { EmailConfig } from './email';
+import type { HttpResponse } from './http';
+import type { WhoisInfo } from './whois';
+
+// One URL requested for the HTTP section
+export interface HttpCheck {
+  url: string;
+  response: HttpResponse | null;
+  error: string | null;
+}
+
+// A section that failed; the other sections are still reported
+export interface SectionError {
+  section: string;
+  error: string;
+}
+
+export type Severity = 'info' | 'warning' | 'critical';
+
+// Formats export_report writes; html is a standalone page
+export type ExportFormat = 'json' | 'markdown' | 'html';
+
+// One problem found in a report and how to fix it
+export interface Finding {
+  category: 'dns' | 'dnssec' | 'tls' | 'http' | 'email' | 'registration';
+  severity: Severity;
+  code: string; // Stable identifier, e.g. "certificate_expiring"
+  message: string; // What was found, e.g. "The certificate expires in 9 days"
+  explanation: string; // Why it matters
+  fix: string; // What to change, and where
+}
+
+export interface CategoryScore {
+  category: string;
+  score: number | null; // 0-100; null when the section failed and wasn't graded
+  grade: string | null;
+}
+
+export interface HealthScore {
+  score: number; // Average of the graded categories, at most 69 with a critical finding
+  grade: string; // A (90+), B, C, D or F (below 60)
+  categories: CategoryScore[];
+  findings: Finding[]; // Most severe first
+}
+
+// Everything analyze_domain found. A section is null until it completes, and
+// stays null when it failed.
+export interface DomainReport {
+  domain: string;
+  started_at: string; // RFC 3339
+  total_time: number; // Seconds; 0 until every section completed
+  dns: RecordEnumeration | null;
+  dnssec: DnssecValidation | null;
+  whois: WhoisInfo | null;
+  certificate: TlsInfo | null; // Port 443 of the domain
+  http: HttpCheck[]; // http:// and https://, then www. for an apex
+  email: EmailConfig | null;
+  errors: SectionError[];
+  health: HealthScore | null; // Graded once every section completed
+  scan_id: number | null; // ID in the scan history, once saved
+}
+
+// Payload of the "domain-report-progress" event emitted as each section of
+// analyze_domain completes
+export interface DomainReportProgress {
+  section: 'dns' | 'dnssec' | 'whois' | 'certificate' | 'http' | 'email';
+  completed: number;
+  total: number;
+  report: DomainReport; // Sections completed so far
+}
+
+// Domains read from a CSV or text file by import_domains
+export interface DomainImport {
+  domains: string[]; // In file order, duplicates removed
+  skipped: string[]; // Rows without a usable domain
+}
+
+// One aspect of two domains side by side; a side is empty when nothing was found
+// or its section failed
+export interface ComparisonRow {
+  section: 'dns' | 'email' | 'certificate' | 'http';
+  field: string; // e.g. "nameservers", "mx", "issuer", "http_target"
+  a: string[];
+  b: string[];
+  same: boolean;
+}
+
+// Returned by compare_domains
+export interface DomainComparison {
+  domain_a: string;
+  domain_b: string;
+  rows: ComparisonRow[];
+  errors_a: SectionError[]; // Sections that failed for domain_a
+  errors_b: SectionError[];
+  same: boolean; // Every row matches
+  total_time: number;
+}
+
+// One domain of a bulk scan, as a row of the summary table
+export interface BulkScanRow {
+  domain: string;
+  score: number | null;
+  grade: string | null;
+  critical: number; // Findings by severity
+  warnings: number;
+  dnssec: string | null;
+  certificate_days: number | null;
+  registration_days: number | null;
+  https_status: number | null; // Final status of https://<domain>
+  dmarc_policy: string | null;
+  errors: SectionError[];
+  completed: number; // Domains finished so far, including this one
+  total: number;
+}
+
+export interface BulkScanSummary {
+  checks: string[];
+  total: number;
+  succeeded: number;
+  failed: number;
+  rows: BulkScanRow[]; // In the order the domains were given
+  total_time: number;
+}
@@ -0,0 +1,26 @@
+export type Hosting = 'dedicated' | 'shared' | 'unknown'; // unknown: no source answered
+
+// A domain a source has seen resolving to the address
+export interface HostedDomain {
+  domain: string;
+  sources: string[]; // "hackertarget" (current A records), "mnemonic" (passive DNS)
+  first_seen: string | null; // RFC 3339, passive DNS only
+  last_seen: string | null;
+}
+
+// A source that couldn't be queried; the others are still reported
+export interface SourceError {
+  source: string;
+  error: string;
+}
+
+// Returned by lookup_reverse_ip
+export interface ReverseIpReport {
+  ip: string;
+  ptr: string[]; // The address's own reverse DNS names
+  domains: HostedDomain[]; // Most recently seen first
+  sites: number; // Distinct registrable domains among them
+  hosting: Hosting;
+  errors: SourceError[];
+  total_time: number;
+}
@@ -0,0 +1,9 @@
+import type { ScanSummary } from './history';
+
+// A frequently-checked domain, as returned by save_domain and list_saved_domains
+export interface SavedDomain {
+  domain: string;
+  tags: string[]; // e.g. "client", "production"
+  added_at: string; // RFC 3339
+  last_scan: ScanSummary | null; // Null until analyze_domain has run for it
+}
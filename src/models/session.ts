@@ -0,0 +1,22 @@
+import type { CommandLog } from './command_log';
+import type { DomainReport } from './report';
+
+// A domain queried during the session
+export interface SessionDomain {
+  domain: string;
+  first_queried_at: string; // RFC 3339
+  last_queried_at: string;
+  commands: number; // Commands logged for it
+  report: DomainReport | null; // The latest analyze_domain run this session
+}
+
+// Returned by load_session, as written by save_session
+export interface Session {
+  format: number;
+  app_version: string;
+  started_at: string;
+  saved_at: string;
+  domains: SessionDomain[]; // In the order first queried
+  logs: CommandLog[]; // Oldest first
+  panels: unknown | null; // Frontend state passed to save_session, as given
+}
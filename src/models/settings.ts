@@ -0,0 +1,43 @@
+export type IpVersion = 'auto' | 'ipv4' | 'ipv6';
+
+// DS-style trust anchor, as IANA publishes them in root-anchors.xml
+export interface TrustAnchor {
+  zone: string; // "." for the root
+  key_tag: number;
+  algorithm: number;
+  digest_type: number;
+  digest: string; // Hex
+}
+
+// Returned by get_settings and update_settings, which saves them to settings.json
+// in the app config directory. Fields left out of a saved file keep their defaults.
+export interface Settings {
+  dns_timeout_secs: number; // Per try
+  dns_retries: number; // Extra attempts after the first
+  dns_ip_version: IpVersion;
+  dns_resolver: string; // "system", an address or hostname, or a DoH URL
+  doh_endpoints: string[]; // https:// URLs offered as resolvers and benchmarked
+  whois_timeout_secs: number;
+  whois_retries: number;
+  http_timeout_secs: number; // Per redirect hop
+  http_retries: number; // Transient failures only
+  http_proxy: string | null; // http://, socks5:// or socks5h:// URL
+  http_user_agent: string | null; // Replaces d-dns-debugger/<version>
+  tls_timeout_secs: number;
+  tls_retries: number;
+  max_concurrency: number; // Parallel queries in enumerations and bulk operations
+  nameserver_queries_per_second: number;
+  whois_queries_per_minute: number;
+  host_requests_per_second: number;
+  rrsig_expiry_warning_days: number;
+  cert_expiry_warning_days: number[];
+  domain_expiry_warning_days: number;
+  expiry_notification_days: number[];
+  watchlist_check_hours: number; // How often watched domains are re-checked
+  tls_scan_ports: number[];
+  scan_ports: number[]; // Checked by scan_ports when given none
+  dnsbl_ip_zones: string[]; // Blocklists MX addresses are checked against
+  dnsbl_domain_zones: string[]; // Blocklists the domain itself is checked against
+  dkim_selectors: string[]; // Tried for every domain by fetch_email_config
+  trust_anchors: TrustAnchor[];
+}
@@ -0,0 +1,35 @@
+// One SSHFP record (RFC 4255, with the algorithms of RFC 6594 and 7479)
+export interface SshfpRecord {
+  algorithm: number; // 1 RSA, 2 DSA, 3 ECDSA, 4 Ed25519, 6 Ed448
+  algorithm_name: string; // e.g. Ed25519
+  fp_type: number; // 1 SHA-1, 2 SHA-256
+  fp_type_name: string;
+  fingerprint: string; // Lowercase hex
+  matched: boolean | null; // Against the server's key of this algorithm; null when none was fetched
+}
+
+export type HostKeyStatus = 'matched' | 'mismatched' | 'unpublished';
+
+// A host key the SSH server presented
+export interface SshHostKey {
+  key_type: string; // ssh-ed25519, ecdsa-sha2-nistp256, ssh-rsa...
+  algorithm: number; // Its SSHFP algorithm number
+  public_key: string; // Base64, as in known_hosts
+  fingerprint: string; // "SHA256:..." as ssh-keygen -l prints it
+  status: HostKeyStatus;
+}
+
+// Returned by check_sshfp
+export interface SshfpReport {
+  host: string;
+  records: SshfpRecord[];
+  authenticated: boolean; // Resolver validated the SSHFP answer with DNSSEC (AD flag)
+  port: number;
+  address: string | null; // The address connected to, when verifying
+  banner: string | null; // The server's version line, e.g. SSH-2.0-OpenSSH_9.6
+  host_keys: SshHostKey[];
+  verified: boolean | null; // A key matched and none contradicted the records; null when not connected
+  error: string | null; // Why fetching the host keys failed
+  warnings: string[];
+  total_time: number;
+}
@@ -0,0 +1,31 @@
+// A router or host that answered probes at a hop
+export interface HopResponder {
+  address: string;
+  hostname: string | null; // From its PTR record
+}
+
+// One TTL of a traceroute
+export interface TraceHop {
+  ttl: number;
+  responders: HopResponder[]; // Several when load balancing spreads the probes, none when all timed out
+  rtts_ms: (number | null)[]; // Each probe in order, null when it timed out
+  avg_ms: number | null;
+  loss_percent: number;
+}
+
+// Payload of trace-route-hop events. A hop is sent again as more of its probes
+// come in (tracepath prints one line per probe).
+export interface TraceHopEvent {
+  host: string;
+  hop: TraceHop;
+}
+
+// Returned by trace_route
+export interface TraceRoute {
+  host: string;
+  address: string; // The address traced to
+  tool: string; // "traceroute", "tracepath" or "tracert"
+  hops: TraceHop[];
+  reached: boolean; // Whether the last hop answered from address
+  total_time: number;
+}
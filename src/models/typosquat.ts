@@ -0,0 +1,46 @@
+// How a lookalike was derived from the domain's name
+export type VariantKind =
+  | 'omission' // A character left out: exmple.com
+  | 'repetition' // A character typed twice: exxample.com
+  | 'transposition' // Two neighbours swapped: exmaple.com
+  | 'replacement' // A key next to the intended one: exanple.com
+  | 'hyphenation' // A hyphen inserted: ex-ample.com
+  | 'homoglyph' // ASCII that looks alike: examp1e.com, rnicrosoft.com
+  | 'homograph' // Confusable Unicode: exаmple.com with a Cyrillic а
+  | 'tld'; // The same name under another TLD: example.net
+
+// One generated lookalike and what DNS and WHOIS say about it
+export interface TyposquatVariant {
+  domain: string; // Unicode for homographs
+  kind: VariantKind;
+  registered: boolean; // Exists in DNS (anything but NXDOMAIN)
+  addresses: string[];
+  nameservers: string[];
+  has_mx: boolean; // Accepts mail, so it can receive misdirected mail or send phishing
+  registrar: string | null;
+  registrant: string | null; // Organization, else name; null when hidden
+  created: string | null;
+  privacy_protected: boolean;
+  defensive: boolean; // Same registrant or nameservers as the domain: likely its owner's
+  error: string | null; // Why DNS or WHOIS couldn't tell
+}
+
+// Registered lookalikes sharing a registrar or registrant
+export interface RegistrantGroup {
+  name: string;
+  domains: string[];
+}
+
+// Returned by scan_typosquats
+export interface TyposquatReport {
+  domain: string; // The registrable domain the variants were made from
+  registrar: string | null; // The domain's own, for comparison
+  registrant: string | null;
+  generated: number;
+  registered: number;
+  variants: TyposquatVariant[]; // Registered ones first, each in generation order
+  registrars: RegistrantGroup[]; // Most domains first
+  registrants: RegistrantGroup[];
+  findings: string[];
+  total_time: number;
+}
@@ -0,0 +1,19 @@
+export type ExpiryKind = 'certificate' | 'registration';
+
+// A domain whose expiries are checked in the background
+export interface WatchedDomain {
+  domain: string;
+  added_at: string; // RFC 3339
+  checked_at: string | null; // Null until the first check finishes
+  certificate_expires_at: string | null;
+  registration_expires_at: string | null;
+  errors: string[]; // Checks that failed on the last run
+}
+
+export interface UpcomingExpiration {
+  domain: string;
+  kind: ExpiryKind;
+  expires_at: string;
+  days_remaining: number; // Negative once expired
+  threshold: number | null; // Smallest notification threshold it falls within
+}
@@ -4,15 +4,89 @@ export interface WhoisInfo {
   creation_date?: string;
   expiration_date?: string;
   updated_date?: string;
+  creation_date_raw?: string | null; // As the registry wrote it; *_date hold ISO 8601
+  expiration_date_raw?: string | null;
+  updated_date_raw?: string | null;
   nameservers: string[];
-  status: string[];
+  status: EppStatus[];
   dnssec?: string;
-  raw_output: string;
+  registrant?: Contact | null;
+  admin?: Contact | null;
+  tech?: Contact | null;
+  privacy_protected?: boolean; // Hidden by a privacy/proxy service or redacted
+  source?: WhoisSource;
+  registrar_whois_server?: string | null; // Followed referral from a thin registry
+  expiration_at?: string | null; // Parsed expiration_date (ISO 8601)
+  days_until_expiry?: number | null; // Negative once expired
+  warnings?: string[]; // Upcoming expiry, redemptionPeriod, pendingDelete
+  raw_output: string; // RDAP JSON or WHOIS text, depending on source
 }
 
+export type StatusSeverity = 'info' | 'warning' | 'critical';
+
+// EPP domain status with what it means for the owner
+export interface EppStatus {
+  code: string; // e.g. "clientTransferProhibited"
+  severity: StatusSeverity;
+  description: string;
+}
+
+export type WhoisSource = 'rdap' | 'whois';
+
 export interface Contact {
   name?: string;
   organization?: string;
   email?: string;
   phone?: string;
 }
+
+export interface IpWhoisInfo {
+  ip: string;
+  network_name: string | null;
+  network_handle: string | null;
+  network_range: string | null; // "start - end" as registered
+  organization: string | null;
+  country: string | null;
+  abuse_email: string | null;
+  rir: string | null;
+  asn: number | null; // Origin AS announcing the address
+  as_name: string | null;
+  announced_prefix: string | null;
+  warnings: string[];
+  raw_output: string;
+}
+
+// One domain of a lookup_whois_bulk run; also the "whois-bulk-result" event payload
+export interface BulkWhoisResult {
+  domain: string;
+  info: WhoisInfo | null;
+  error: string | null;
+  completed: number; // Domains finished so far, including this one
+  total: number;
+}
+
+export interface BulkWhoisSummary {
+  total: number;
+  succeeded: number;
+  failed: number;
+  expired: string[];
+  expiring_soon: string[]; // Within domain_expiry_warning_days
+  results: BulkWhoisResult[]; // In the order the domains were given
+  total_time: number;
+}
+
+export type AvailabilityStatus =
+  | 'registered'
+  | 'available'
+  | 'redemption'
+  | 'pending_delete'
+  | 'unknown';
+
+export interface DomainAvailability {
+  domain: string;
+  status: AvailabilityStatus;
+  registration_status: string[];
+  nxdomain: boolean | null; // null if the DNS lookup failed
+  evidence: string[]; // What each source answered
+  raw_output: string;
+}
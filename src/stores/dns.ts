@@ -79,6 +79,8 @@ export const useDNSStore = defineStore('dns', () => {
 
   const clearCache = () => {
     cache.value.clear();
+    // Also drop the backend's TTL cache so the next lookup hits the network
+    invoke('clear_dns_cache').catch(() => {});
   };
 
   const setDNSData = (type: string, data: DnsResponse) => {
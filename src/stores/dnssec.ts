@@ -1,16 +1,24 @@
 import { defineStore } from 'pinia';
 import { ref } from 'vue';
 import { invoke } from '@tauri-apps/api/core';
-import type { DnssecValidation } from '../models/dns';
+import { listen } from '@tauri-apps/api/event';
+import type { DnssecProgress, DnssecValidation } from '../models/dns';
 
 export const useDnssecStore = defineStore('dnssec', () => {
   const validation = ref<DnssecValidation | null>(null);
   const loading = ref<boolean>(false);
   const error = ref<string | null>(null);
+  const progress = ref<DnssecProgress | null>(null);
 
   const fetchDnssec = async (domain: string) => {
     loading.value = true;
     error.value = null;
+    progress.value = null;
+
+    // Live chain-building progress emitted by validate_dnssec
+    const unlisten = await listen<DnssecProgress>('dnssec-progress', (event) => {
+      progress.value = event.payload;
+    });
 
     try {
       const result = await invoke<DnssecValidation>('validate_dnssec', { domain });
@@ -19,6 +27,8 @@ export const useDnssecStore = defineStore('dnssec', () => {
       error.value = e as string;
       validation.value = null;
     } finally {
+      unlisten();
+      progress.value = null;
       loading.value = false;
     }
   };
@@ -27,12 +37,14 @@ export const useDnssecStore = defineStore('dnssec', () => {
     validation.value = null;
     loading.value = false;
     error.value = null;
+    progress.value = null;
   };
 
   return {
     validation,
     loading,
     error,
+    progress,
     fetchDnssec,
     reset,
   };
@@ -11,6 +11,8 @@ export interface CommandLog {
   exitCode: number;
   duration: number; // in milliseconds
   domain?: string;
+  throttled_ms?: number | null; // Time spent waiting on the rate limiter first, if any
+  logged_at?: string; // RFC 3339, when the backend ran it
 }
 
 export const useLogsStore = defineStore('logs', () => {
@@ -33,7 +33,13 @@ describe('WHOIS Store', () => {
       expiration_date: '2025-08-13T04:00:00Z',
       updated_date: '2024-08-13T09:11:03Z',
       nameservers: ['ns1.example.com', 'ns2.example.com'],
-      status: ['clientTransferProhibited'],
+      status: [
+        {
+          code: 'clientTransferProhibited',
+          severity: 'info',
+          description: 'Registrar lock: transfers to another registrar are blocked',
+        },
+      ],
       dnssec: 'unsigned',
       raw_output: 'raw whois data',
     };
@@ -70,7 +76,7 @@ describe('WHOIS Store', () => {
       expiration_date: '2025-08-13T04:00:00Z',
       updated_date: '2024-08-13T09:11:03Z',
       nameservers: ['ns1.example.com'],
-      status: ['ok'],
+      status: [{ code: 'ok', severity: 'info', description: 'No pending operations or restrictions' }],
       dnssec: 'unsigned',
       raw_output: 'raw',
     };